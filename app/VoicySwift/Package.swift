@@ -28,7 +28,11 @@ let package = Package(
                 .linkedFramework("CoreML"),
                 .linkedFramework("Accelerate"),
                 .linkedFramework("CoreAudio"),
-                .linkedFramework("AVFoundation")
+                .linkedFramework("AVFoundation"),
+                .linkedFramework("EventKit"),
+                .linkedFramework("ScreenCaptureKit"),
+                .linkedFramework("Intents"),
+                .linkedFramework("IOKit")
             ]
         ),
     ]
@@ -1,6 +1,6 @@
 import Foundation
 
-private var pushToTalkCallback: ((Bool) -> Void)?
+private var pushToTalkCallback: ((Bool, Int32) -> Void)?
 private var pttPressedObserver: NSObjectProtocol?
 private var pttReleasedObserver: NSObjectProtocol?
 
@@ -14,10 +14,67 @@ public func swift_shutdown_keyboard_monitor() {
     TypeswiftKeyboardMonitor.shutdownKeyboardMonitor()
 }
 
+@_cdecl("swift_set_push_to_talk_chord")
+public func swift_set_push_to_talk_chord(flags: UInt64, deviceFlags: UInt64) {
+    TypeswiftKeyboardMonitor.setPushToTalkChord(flags: flags, deviceFlags: deviceFlags)
+}
+
+/// Extra chords that start push-to-talk for a profile; `profiles` are the indices reported back
+@_cdecl("swift_set_profile_chords")
+public func swift_set_profile_chords(
+    flags: UnsafePointer<UInt64>,
+    deviceFlags: UnsafePointer<UInt64>,
+    profiles: UnsafePointer<Int32>,
+    count: Int
+) {
+    let chords = (0..<count).map { i in
+        (flags: flags[i], deviceFlags: deviceFlags[i], profile: profiles[i])
+    }
+    TypeswiftKeyboardMonitor.shared.setProfileChords(chords)
+}
+
+@_cdecl("swift_set_suppress_system_fn")
+public func swift_set_suppress_system_fn(suppress: Bool) {
+    TypeswiftKeyboardMonitor.setSuppressSystemFn(suppress)
+}
+
+/// macOS virtual key code of the push-to-talk key to swallow while recording; -1 = none
+@_cdecl("swift_set_swallowed_key")
+public func swift_set_swallowed_key(keyCode: Int32) {
+    TypeswiftKeyboardMonitor.setSwallowedKey(keyCode)
+}
+
+@_cdecl("swift_set_recording_active")
+public func swift_set_recording_active(active: Bool) {
+    TypeswiftKeyboardMonitor.setRecordingActive(active)
+}
+
+/// 0 = not monitoring, 1 = event tap enabled, 2 = event tap disabled, 3 = NSEvent fallback
+@_cdecl("swift_keyboard_tap_status")
+public func swift_keyboard_tap_status() -> Int32 {
+    return TypeswiftKeyboardMonitor.shared.tapStatus()
+}
+
+@_cdecl("swift_reinstall_keyboard_tap")
+public func swift_reinstall_keyboard_tap() -> Bool {
+    if Thread.isMainThread {
+        return TypeswiftKeyboardMonitor.shared.reinstallTap()
+    }
+    return DispatchQueue.main.sync { TypeswiftKeyboardMonitor.shared.reinstallTap() }
+}
+
+/// `callback` gets 0 when macOS disabled the tap for a slow callback, 1 for user input
+@_cdecl("swift_register_tap_health_callback")
+public func swift_register_tap_health_callback(callback: @escaping @convention(c) (Int32) -> Void) {
+    TypeswiftKeyboardMonitor.shared.tapDisabledHandler = { reason in
+        callback(reason)
+    }
+}
+
 @_cdecl("swift_register_push_to_talk_callback")
-public func swift_register_push_to_talk_callback(callback: @escaping @convention(c) (Bool) -> Void) {
-    pushToTalkCallback = { isPressed in
-        callback(isPressed)
+public func swift_register_push_to_talk_callback(callback: @escaping @convention(c) (Bool, Int32) -> Void) {
+    pushToTalkCallback = { isPressed, profile in
+        callback(isPressed, profile)
     }
     
     let center = NotificationCenter.default
@@ -29,8 +86,9 @@ public func swift_register_push_to_talk_callback(callback: @escaping @convention
         forName: NSNotification.Name("TypeswiftPushToTalkPressed"),
         object: nil,
         queue: .main
-    ) { _ in
-        pushToTalkCallback?(true)
+    ) { note in
+        // -1: the main push-to-talk chord
+        pushToTalkCallback?(true, note.userInfo?["profile"] as? Int32 ?? -1)
     }
     
     pttReleasedObserver = center.addObserver(
@@ -38,6 +96,6 @@ public func swift_register_push_to_talk_callback(callback: @escaping @convention
         object: nil,
         queue: .main
     ) { _ in
-        pushToTalkCallback?(false)
+        pushToTalkCallback?(false, -1)
     }
 }
@@ -22,6 +22,23 @@ public func swift_register_preferences_callback(_ callback: @escaping @conventio
     }
 }
 
+// Generic menu action callback: menu items post "TypeswiftMenuAction" with an "action" name
+private var menuActionObserver: NSObjectProtocol?
+
+@_cdecl("swift_register_menu_action_callback")
+public func swift_register_menu_action_callback(_ callback: @escaping @convention(c) (UnsafePointer<CChar>) -> Void) {
+    let center = NotificationCenter.default
+    if let o = menuActionObserver { center.removeObserver(o) }
+    menuActionObserver = center.addObserver(
+        forName: NSNotification.Name("TypeswiftMenuAction"),
+        object: nil,
+        queue: .main
+    ) { note in
+        guard let action = note.userInfo?["action"] as? String else { return }
+        action.withCString { callback($0) }
+    }
+}
+
 // FFI exports for menu bar functionality
 
 @_cdecl("typeswift_setup_menubar")
@@ -65,6 +82,92 @@ public func typeswift_show_notification(_ title: UnsafePointer<CChar>, _ message
     TypeswiftMenuBar.shared.showNotification(title: titleStr, text: messageStr)
 }
 
+@_cdecl("typeswift_show_alert")
+public func typeswift_show_alert(_ title: UnsafePointer<CChar>, _ message: UnsafePointer<CChar>) {
+    let titleStr = String(cString: title)
+    let messageStr = String(cString: message)
+    DispatchQueue.main.async {
+        NSApp.activate(ignoringOtherApps: true)
+        let alert = NSAlert()
+        alert.messageText = titleStr
+        alert.informativeText = messageStr
+        alert.alertStyle = .warning
+        alert.addButton(withTitle: "OK")
+        alert.runModal()
+    }
+}
+
+/// Warning alert with a "Don't warn again" checkbox. Blocks until dismissed and
+/// returns whether the checkbox was ticked; call off the main thread.
+@_cdecl("typeswift_show_suppressible_alert")
+public func typeswift_show_suppressible_alert(_ title: UnsafePointer<CChar>, _ message: UnsafePointer<CChar>) -> Bool {
+    let titleStr = String(cString: title)
+    let messageStr = String(cString: message)
+    let present: () -> Bool = {
+        NSApp.activate(ignoringOtherApps: true)
+        let alert = NSAlert()
+        alert.messageText = titleStr
+        alert.informativeText = messageStr
+        alert.alertStyle = .warning
+        alert.addButton(withTitle: "OK")
+        alert.showsSuppressionButton = true
+        alert.suppressionButton?.title = "Don't warn again for this device"
+        alert.runModal()
+        return alert.suppressionButton?.state == .on
+    }
+    if Thread.isMainThread {
+        return present()
+    }
+    return DispatchQueue.main.sync { present() }
+}
+
+/// Two-button dialog. Blocks until dismissed and returns whether the confirm
+/// button was chosen; call off the main thread.
+@_cdecl("typeswift_confirm")
+public func typeswift_confirm(_ title: UnsafePointer<CChar>, _ message: UnsafePointer<CChar>, _ confirmLabel: UnsafePointer<CChar>) -> Bool {
+    let titleStr = String(cString: title)
+    let messageStr = String(cString: message)
+    let confirmStr = String(cString: confirmLabel)
+    let present: () -> Bool = {
+        NSApp.activate(ignoringOtherApps: true)
+        let alert = NSAlert()
+        alert.messageText = titleStr
+        alert.informativeText = messageStr
+        alert.alertStyle = .informational
+        alert.addButton(withTitle: confirmStr)
+        alert.addButton(withTitle: "Later")
+        return alert.runModal() == .alertFirstButtonReturn
+    }
+    if Thread.isMainThread {
+        return present()
+    }
+    return DispatchQueue.main.sync { present() }
+}
+
+/// Dialog with one button per newline-separated label. Blocks until dismissed and returns
+/// the index of the chosen button; call off the main thread.
+@_cdecl("typeswift_choose")
+public func typeswift_choose(_ title: UnsafePointer<CChar>, _ message: UnsafePointer<CChar>, _ labels: UnsafePointer<CChar>) -> Int32 {
+    let titleStr = String(cString: title)
+    let messageStr = String(cString: message)
+    let labelList = String(cString: labels).split(separator: "\n").map(String.init)
+    let present: () -> Int32 = {
+        NSApp.activate(ignoringOtherApps: true)
+        let alert = NSAlert()
+        alert.messageText = titleStr
+        alert.informativeText = messageStr
+        alert.alertStyle = .informational
+        for label in labelList {
+            alert.addButton(withTitle: label)
+        }
+        return Int32(alert.runModal().rawValue - NSApplication.ModalResponse.alertFirstButtonReturn.rawValue)
+    }
+    if Thread.isMainThread {
+        return present()
+    }
+    return DispatchQueue.main.sync { present() }
+}
+
 @_cdecl("typeswift_set_recording_state")
 public func typeswift_set_recording_state(_ isRecording: Bool) {
     DispatchQueue.main.async {
@@ -72,6 +175,35 @@ public func typeswift_set_recording_state(_ isRecording: Bool) {
     }
 }
 
+@_cdecl("typeswift_set_recording_locked")
+public func typeswift_set_recording_locked(_ locked: Bool) {
+    DispatchQueue.main.async {
+        TypeswiftMenuBar.shared.setRecordingLocked(locked)
+    }
+}
+
+@_cdecl("typeswift_set_typing_health")
+public func typeswift_set_typing_health(_ ok: Bool) {
+    TypeswiftMenuBar.shared.setTypingHealth(ok)
+}
+
+/// Menu item suggesting transcription of a detected call; NULL hides it
+@_cdecl("typeswift_set_call_suggestion")
+public func typeswift_set_call_suggestion(_ title: UnsafePointer<CChar>?) {
+    TypeswiftMenuBar.shared.setCallSuggestion(title.map { String(cString: $0) })
+}
+
+/// Menu item showing the most recent error; clicking it repeats the details in an alert
+@_cdecl("typeswift_set_last_error")
+public func typeswift_set_last_error(_ title: UnsafePointer<CChar>, _ message: UnsafePointer<CChar>) {
+    TypeswiftMenuBar.shared.setLastError(String(cString: title), message: String(cString: message))
+}
+
+@_cdecl("typeswift_set_quick_settings")
+public func typeswift_set_quick_settings(_ json: UnsafePointer<CChar>) {
+    TypeswiftMenuBar.shared.setQuickSettings(String(cString: json))
+}
+
 @_cdecl("typeswift_run_app")
 public func typeswift_run_app() {
     // Ensure we're on the main thread
@@ -0,0 +1,42 @@
+import Foundation
+import CoreML
+
+// Core ML model preparation for backends that load a compiled .mlmodelc, such as the
+// whisper.cpp Core ML encoder. Errors come back as strings (free with typeswift_free_string).
+
+/// Compile `source` (.mlpackage or .mlmodel) into `destination` (.mlmodelc), replacing it.
+/// Returns nil on success, otherwise the error.
+@_cdecl("typeswift_compile_coreml_model")
+public func typeswift_compile_coreml_model(
+    _ source: UnsafePointer<CChar>,
+    _ destination: UnsafePointer<CChar>
+) -> UnsafeMutablePointer<CChar>? {
+    let sourceURL = URL(fileURLWithPath: String(cString: source))
+    let destinationURL = URL(fileURLWithPath: String(cString: destination))
+    do {
+        // Compiles into a temporary directory
+        let compiled = try MLModel.compileModel(at: sourceURL)
+        try? FileManager.default.removeItem(at: destinationURL)
+        try FileManager.default.moveItem(at: compiled, to: destinationURL)
+        return nil
+    } catch {
+        NSLog("Core ML compile failed for \(sourceURL.path): \(error.localizedDescription)")
+        return strdup(error.localizedDescription)
+    }
+}
+
+/// Load a compiled model once, so Core ML does its first-run device compilation here rather
+/// than inside a backend that can't recover from it. Returns nil if it loads, otherwise the error.
+@_cdecl("typeswift_check_coreml_model")
+public func typeswift_check_coreml_model(_ path: UnsafePointer<CChar>) -> UnsafeMutablePointer<CChar>? {
+    let url = URL(fileURLWithPath: String(cString: path))
+    let configuration = MLModelConfiguration()
+    configuration.computeUnits = .all
+    do {
+        _ = try MLModel(contentsOf: url, configuration: configuration)
+        return nil
+    } catch {
+        NSLog("Core ML model failed to load from \(url.path): \(error.localizedDescription)")
+        return strdup(error.localizedDescription)
+    }
+}
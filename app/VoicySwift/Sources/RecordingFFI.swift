@@ -0,0 +1,127 @@
+import Foundation
+import AVFoundation
+
+// Compressed dictation audio for the recordings archive and uploads, encoded and decoded
+// with the system AudioToolbox codecs. The container follows the path's extension (.m4a, .caf, .flac).
+
+/// Encoder settings for a codec code: 0 = AAC, 1 = Opus, 2 = FLAC
+private func encoderSettings(codec: Int32, sampleRate: Double) -> [String: Any]? {
+    // Speech at 16 kHz stays intelligible well below music bitrates
+    switch codec {
+    case 0:
+        return [
+            AVFormatIDKey: kAudioFormatMPEG4AAC,
+            AVSampleRateKey: sampleRate,
+            AVNumberOfChannelsKey: 1,
+            AVEncoderBitRateKey: 32_000,
+        ]
+    case 1:
+        return [
+            AVFormatIDKey: kAudioFormatOpus,
+            AVSampleRateKey: sampleRate,
+            AVNumberOfChannelsKey: 1,
+            AVEncoderBitRateKey: 24_000,
+        ]
+    case 2:
+        return [
+            AVFormatIDKey: kAudioFormatFLAC,
+            AVSampleRateKey: sampleRate,
+            AVNumberOfChannelsKey: 1,
+            AVEncoderBitDepthHintKey: 16,
+        ]
+    default:
+        return nil
+    }
+}
+
+/// Write mono Float32 `samples` at `sampleRate` to `path` with `codec` (see `encoderSettings`)
+@_cdecl("typeswift_write_audio_file")
+public func typeswift_write_audio_file(
+    _ samples: UnsafePointer<Float>,
+    _ count: Int,
+    _ sampleRate: Int32,
+    _ codec: Int32,
+    _ path: UnsafePointer<CChar>
+) -> Bool {
+    let url = URL(fileURLWithPath: String(cString: path))
+    guard count > 0,
+          let settings = encoderSettings(codec: codec, sampleRate: Double(sampleRate)),
+          let format = AVAudioFormat(commonFormat: .pcmFormatFloat32, sampleRate: Double(sampleRate), channels: 1, interleaved: false),
+          let buffer = AVAudioPCMBuffer(pcmFormat: format, frameCapacity: AVAudioFrameCount(count)),
+          let channel = buffer.floatChannelData?[0] else {
+        return false
+    }
+    channel.update(from: samples, count: count)
+    buffer.frameLength = AVAudioFrameCount(count)
+
+    do {
+        // The file is finalized when it goes out of scope
+        let file = try AVAudioFile(forWriting: url, settings: settings, commonFormat: .pcmFormatFloat32, interleaved: false)
+        try file.write(from: buffer)
+        return true
+    } catch {
+        NSLog("Audio archive failed for \(url.path): \(error.localizedDescription)")
+        try? FileManager.default.removeItem(at: url)
+        return false
+    }
+}
+
+/// Read a mono file at `sampleRate` written by `typeswift_write_audio_file` into `buffer`, at
+/// most `max` samples. Returns how many were read, or -1 if the file can't be decoded or has
+/// another channel count or rate.
+@_cdecl("typeswift_read_audio_file")
+public func typeswift_read_audio_file(
+    _ path: UnsafePointer<CChar>,
+    _ sampleRate: Int32,
+    _ buffer: UnsafeMutablePointer<Float>,
+    _ max: Int
+) -> Int {
+    let url = URL(fileURLWithPath: String(cString: path))
+    do {
+        let file = try AVAudioFile(forReading: url, commonFormat: .pcmFormatFloat32, interleaved: false)
+        let format = file.processingFormat
+        guard format.channelCount == 1, format.sampleRate == Double(sampleRate) else {
+            return -1
+        }
+        let frames = AVAudioFrameCount(min(Int(file.length), max))
+        guard frames > 0 else {
+            return 0
+        }
+        guard let pcm = AVAudioPCMBuffer(pcmFormat: format, frameCapacity: frames) else {
+            return -1
+        }
+        try file.read(into: pcm, frameCount: frames)
+        guard let channel = pcm.floatChannelData?[0] else {
+            return -1
+        }
+        buffer.update(from: channel, count: Int(pcm.frameLength))
+        return Int(pcm.frameLength)
+    } catch {
+        NSLog("Audio decoding failed for \(url.path): \(error.localizedDescription)")
+        return -1
+    }
+}
+
+private var audioPlayer: AVAudioPlayer?
+
+/// Play an audio file, replacing anything already playing
+@_cdecl("typeswift_play_audio_file")
+public func typeswift_play_audio_file(_ path: UnsafePointer<CChar>) -> Bool {
+    let url = URL(fileURLWithPath: String(cString: path))
+    audioPlayer?.stop()
+    do {
+        let player = try AVAudioPlayer(contentsOf: url)
+        audioPlayer = player
+        return player.play()
+    } catch {
+        NSLog("Audio playback failed for \(url.path): \(error.localizedDescription)")
+        audioPlayer = nil
+        return false
+    }
+}
+
+@_cdecl("typeswift_stop_audio")
+public func typeswift_stop_audio() {
+    audioPlayer?.stop()
+    audioPlayer = nil
+}
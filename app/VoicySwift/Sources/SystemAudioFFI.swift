@@ -0,0 +1,145 @@
+import Foundation
+import CoreMedia
+import ScreenCaptureKit
+
+// System audio capture (what the Mac plays, e.g. the other side of a call) via ScreenCaptureKit.
+// Needs Screen Recording permission. Samples are mono Float32 at the requested rate and are
+// buffered here until Rust reads them.
+
+private final class SystemAudioCapture: NSObject, SCStreamOutput, SCStreamDelegate {
+    private var stream: SCStream?
+    private let lock = NSLock()
+    private var samples: [Float] = []
+    private var maxBuffered = 0
+    private let queue = DispatchQueue(label: "com.typeswift.system-audio")
+
+    /// Blocks until capture is running or has failed; call off the main thread
+    func start(sampleRate: Int) -> Bool {
+        let done = DispatchSemaphore(value: 0)
+        var shareable: SCShareableContent?
+        SCShareableContent.getExcludingDesktopWindows(false, onScreenWindowsOnly: true) { content, error in
+            if let error = error {
+                NSLog("System audio unavailable: \(error.localizedDescription)")
+            }
+            shareable = content
+            done.signal()
+        }
+        done.wait()
+        guard let display = shareable?.displays.first else { return false }
+
+        let config = SCStreamConfiguration()
+        config.capturesAudio = true
+        config.excludesCurrentProcessAudio = true
+        config.sampleRate = sampleRate
+        config.channelCount = 1
+        // Video can't be switched off; keep it as cheap as possible
+        config.width = 2
+        config.height = 2
+        config.minimumFrameInterval = CMTime(value: 1, timescale: 1)
+
+        lock.lock()
+        samples.removeAll()
+        // Drop the oldest audio rather than grow without bound if nobody reads
+        maxBuffered = sampleRate * 60
+        lock.unlock()
+
+        let stream = SCStream(filter: SCContentFilter(display: display, excludingWindows: []), configuration: config, delegate: self)
+        do {
+            try stream.addStreamOutput(self, type: .audio, sampleHandlerQueue: queue)
+        } catch {
+            NSLog("System audio output failed: \(error.localizedDescription)")
+            return false
+        }
+        var started = false
+        stream.startCapture { error in
+            if let error = error {
+                NSLog("System audio capture failed: \(error.localizedDescription)")
+            }
+            started = error == nil
+            done.signal()
+        }
+        done.wait()
+        if started {
+            self.stream = stream
+        }
+        return started
+    }
+
+    func stop() {
+        guard let stream = stream else { return }
+        self.stream = nil
+        let done = DispatchSemaphore(value: 0)
+        stream.stopCapture { _ in done.signal() }
+        _ = done.wait(timeout: .now() + 2)
+    }
+
+    func read(into buffer: UnsafeMutablePointer<Float>, max: Int) -> Int {
+        lock.lock()
+        defer { lock.unlock() }
+        let count = min(max, samples.count)
+        guard count > 0 else { return 0 }
+        samples.withUnsafeBufferPointer { source in
+            buffer.update(from: source.baseAddress!, count: count)
+        }
+        samples.removeFirst(count)
+        return count
+    }
+
+    func stream(_ stream: SCStream, didOutputSampleBuffer sampleBuffer: CMSampleBuffer, of type: SCStreamOutputType) {
+        guard type == .audio, sampleBuffer.isValid else { return }
+        var blockBuffer: CMBlockBuffer?
+        var bufferList = AudioBufferList()
+        guard CMSampleBufferGetAudioBufferListWithRetainedBlockBuffer(
+            sampleBuffer,
+            bufferListSizeNeededOut: nil,
+            bufferListOut: &bufferList,
+            bufferListSize: MemoryLayout<AudioBufferList>.size,
+            blockBufferAllocator: nil,
+            blockBufferMemoryAllocator: nil,
+            flags: 0,
+            blockBufferOut: &blockBuffer
+        ) == noErr, let data = bufferList.mBuffers.mData else {
+            return
+        }
+        let frames = Int(bufferList.mBuffers.mDataByteSize) / MemoryLayout<Float>.size
+        let chunk = UnsafeBufferPointer(start: data.assumingMemoryBound(to: Float.self), count: frames)
+        lock.lock()
+        samples.append(contentsOf: chunk)
+        if samples.count > maxBuffered {
+            samples.removeFirst(samples.count - maxBuffered)
+        }
+        lock.unlock()
+    }
+
+    func stream(_ stream: SCStream, didStopWithError error: Error) {
+        NSLog("System audio capture stopped: \(error.localizedDescription)")
+        self.stream = nil
+    }
+}
+
+private var systemAudio: SystemAudioCapture?
+
+/// Start capturing system audio at `sampleRate`; false without Screen Recording permission
+@_cdecl("typeswift_system_audio_start")
+public func typeswift_system_audio_start(_ sampleRate: Int32) -> Bool {
+    systemAudio?.stop()
+    let capture = SystemAudioCapture()
+    guard capture.start(sampleRate: Int(sampleRate)) else {
+        systemAudio = nil
+        return false
+    }
+    systemAudio = capture
+    return true
+}
+
+/// Copy up to `max` buffered samples into `buffer`; returns how many were copied
+@_cdecl("typeswift_system_audio_read")
+public func typeswift_system_audio_read(_ buffer: UnsafeMutablePointer<Float>, _ max: Int) -> Int {
+    return systemAudio?.read(into: buffer, max: max) ?? 0
+}
+
+@_cdecl("typeswift_system_audio_stop")
+public func typeswift_system_audio_stop() {
+    systemAudio?.stop()
+    systemAudio = nil
+}
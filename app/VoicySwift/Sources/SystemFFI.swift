@@ -0,0 +1,665 @@
+import Foundation
+import AppKit
+import ApplicationServices
+import AVFoundation
+import Carbon.HIToolbox
+import CoreAudio
+import EventKit
+import Intents
+import IOKit.hid
+import Security
+
+// FFI exports for system integration (pasteboard, focused element inspection, keychain)
+
+@_cdecl("typeswift_set_clipboard_text")
+public func typeswift_set_clipboard_text(_ text: UnsafePointer<CChar>) {
+    let str = String(cString: text)
+    let write = {
+        let pasteboard = NSPasteboard.general
+        pasteboard.clearContents()
+        pasteboard.setString(str, forType: .string)
+    }
+    if Thread.isMainThread {
+        write()
+    } else {
+        DispatchQueue.main.sync { write() }
+    }
+}
+
+/// The active input source when it is an input method (Pinyin, Kotoeri, 2-Set Korean...)
+/// rather than a plain keyboard layout; nil for layouts. Caller frees with typeswift_free_string.
+@_cdecl("typeswift_active_input_method")
+public func typeswift_active_input_method() -> UnsafeMutablePointer<CChar>? {
+    guard let source = TISCopyCurrentKeyboardInputSource()?.takeRetainedValue(),
+          let type = inputSourceProperty(source, kTISPropertyInputSourceType) as String?,
+          type != (kTISTypeKeyboardLayout as String) else {
+        return nil
+    }
+    let id = inputSourceProperty(source, kTISPropertyInputSourceID) ?? type
+    return strdup(id)
+}
+
+/// Switch to the ASCII-capable layout macOS would use for a password field (usually ABC or
+/// U.S.). Returns the ID of the source that was active, to hand back to
+/// typeswift_select_input_source, or nil if nothing changed. Caller frees the string.
+@_cdecl("typeswift_select_ascii_input_source")
+public func typeswift_select_ascii_input_source() -> UnsafeMutablePointer<CChar>? {
+    guard let current = TISCopyCurrentKeyboardInputSource()?.takeRetainedValue(),
+          let currentID = inputSourceProperty(current, kTISPropertyInputSourceID),
+          let ascii = TISCopyCurrentASCIICapableKeyboardLayoutInputSource()?.takeRetainedValue(),
+          TISSelectInputSource(ascii) == noErr else {
+        return nil
+    }
+    return strdup(currentID)
+}
+
+/// Select the input source with this ID again. Returns false if it is gone or can't be selected.
+@_cdecl("typeswift_select_input_source")
+public func typeswift_select_input_source(_ id: UnsafePointer<CChar>) -> Bool {
+    let filter = [kTISPropertyInputSourceID as String: String(cString: id)] as CFDictionary
+    guard let sources = TISCreateInputSourceList(filter, false)?.takeRetainedValue() as? [TISInputSource],
+          let source = sources.first else {
+        return false
+    }
+    return TISSelectInputSource(source) == noErr
+}
+
+private func inputSourceProperty(_ source: TISInputSource, _ key: CFString) -> String? {
+    guard let pointer = TISGetInputSourceProperty(source, key) else { return nil }
+    return Unmanaged<CFString>.fromOpaque(pointer).takeUnretainedValue() as String
+}
+
+/// Insert `text` with Cmd+V: input methods don't intercept a paste the way they do synthesized
+/// keystrokes. What was on the pasteboard is put back afterwards unless something replaced it.
+@_cdecl("typeswift_paste_text")
+public func typeswift_paste_text(_ text: UnsafePointer<CChar>) -> Bool {
+    let str = String(cString: text)
+    var previous: String?
+    var changeCount = 0
+    let write = {
+        let pasteboard = NSPasteboard.general
+        previous = pasteboard.string(forType: .string)
+        pasteboard.clearContents()
+        pasteboard.setString(str, forType: .string)
+        changeCount = pasteboard.changeCount
+    }
+    if Thread.isMainThread {
+        write()
+    } else {
+        DispatchQueue.main.sync { write() }
+    }
+
+    let source = CGEventSource(stateID: .combinedSessionState)
+    guard let down = CGEvent(keyboardEventSource: source, virtualKey: CGKeyCode(kVK_ANSI_V), keyDown: true),
+          let up = CGEvent(keyboardEventSource: source, virtualKey: CGKeyCode(kVK_ANSI_V), keyDown: false) else {
+        return false
+    }
+    down.flags = .maskCommand
+    up.flags = .maskCommand
+    down.post(tap: .cghidEventTap)
+    up.post(tap: .cghidEventTap)
+
+    // The target app reads the pasteboard asynchronously; give it time before restoring
+    DispatchQueue.main.asyncAfter(deadline: .now() + 0.5) {
+        let pasteboard = NSPasteboard.general
+        guard pasteboard.changeCount == changeCount, let previous = previous else { return }
+        pasteboard.clearContents()
+        pasteboard.setString(previous, forType: .string)
+    }
+    return true
+}
+
+/// Roles that accept typed text
+private let textInputRoles: Set<String> = [
+    kAXTextFieldRole as String,
+    kAXTextAreaRole as String,
+    kAXComboBoxRole as String,
+    "AXSearchField",
+]
+
+/// Whether the system-wide focused element can receive typed text.
+/// Returns 1 = text input focused, 0 = no text target (e.g. desktop), -1 = unknown.
+@_cdecl("typeswift_focused_text_target")
+public func typeswift_focused_text_target() -> Int32 {
+    let systemWide = AXUIElementCreateSystemWide()
+    var focused: CFTypeRef?
+    let err = AXUIElementCopyAttributeValue(systemWide, kAXFocusedUIElementAttribute as CFString, &focused)
+    if err == .noValue {
+        return 0
+    }
+    guard err == .success, let element = focused else {
+        // Not trusted or app doesn't implement AX: don't guess
+        return -1
+    }
+    let axElement = element as! AXUIElement
+
+    var roleRef: CFTypeRef?
+    if AXUIElementCopyAttributeValue(axElement, kAXRoleAttribute as CFString, &roleRef) == .success,
+       let role = roleRef as? String, textInputRoles.contains(role) {
+        return 1
+    }
+    var settable: DarwinBoolean = false
+    if AXUIElementIsAttributeSettable(axElement, kAXValueAttribute as CFString, &settable) == .success, settable.boolValue {
+        return 1
+    }
+    // Only call it "no target" where that is reliable: Finder/desktop focus
+    if NSWorkspace.shared.frontmostApplication?.bundleIdentifier == "com.apple.finder" {
+        return 0
+    }
+    return -1
+}
+
+/// Up to `maxChars` UTF-16 units before the insertion point in the focused element: "" at the
+/// start of a document, nil when AX can't tell (caller frees with typeswift_free_string)
+@_cdecl("typeswift_text_before_cursor")
+public func typeswift_text_before_cursor(_ maxChars: Int32) -> UnsafeMutablePointer<CChar>? {
+    let systemWide = AXUIElementCreateSystemWide()
+    var focused: CFTypeRef?
+    guard AXUIElementCopyAttributeValue(systemWide, kAXFocusedUIElementAttribute as CFString, &focused) == .success,
+          let focusedRef = focused else {
+        return nil
+    }
+    let element = focusedRef as! AXUIElement
+    var rangeRef: CFTypeRef?
+    guard AXUIElementCopyAttributeValue(element, kAXSelectedTextRangeAttribute as CFString, &rangeRef) == .success,
+          let rangeValue = rangeRef else {
+        return nil
+    }
+    var selection = CFRange()
+    guard AXValueGetValue(rangeValue as! AXValue, .cfRange, &selection) else { return nil }
+    let length = min(selection.location, CFIndex(maxChars))
+    if length <= 0 {
+        return strdup("")
+    }
+    var before = CFRange(location: selection.location - length, length: length)
+    if let beforeValue = AXValueCreate(.cfRange, &before) {
+        var textRef: CFTypeRef?
+        if AXUIElementCopyParameterizedAttributeValue(element, kAXStringForRangeParameterizedAttribute as CFString, beforeValue, &textRef) == .success,
+           let text = textRef as? String {
+            return strdup(text)
+        }
+    }
+    // Some apps only expose the whole value
+    var valueRef: CFTypeRef?
+    guard AXUIElementCopyAttributeValue(element, kAXValueAttribute as CFString, &valueRef) == .success,
+          let value = valueRef as? String else {
+        return nil
+    }
+    let units = Array(value.utf16)
+    guard selection.location <= units.count else { return nil }
+    return strdup(String(decoding: units[(selection.location - length)..<selection.location], as: UTF16.self))
+}
+
+/// Bundle identifier of the frontmost app (caller frees with typeswift_free_string)
+@_cdecl("typeswift_frontmost_bundle_id")
+public func typeswift_frontmost_bundle_id() -> UnsafeMutablePointer<CChar>? {
+    guard let id = NSWorkspace.shared.frontmostApplication?.bundleIdentifier else {
+        return nil
+    }
+    return strdup(id)
+}
+
+/// What the Fn/Globe key does on its own (System Settings → Keyboard → "Press 🌐 key to"):
+/// 0 = nothing, 1 = change input source, 2 = emoji & symbols, 3 = start dictation, -1 = not set
+@_cdecl("typeswift_fn_key_usage")
+public func typeswift_fn_key_usage() -> Int32 {
+    guard let value = UserDefaults(suiteName: "com.apple.HIToolbox")?.object(forKey: "AppleFnUsageType") as? Int else {
+        return -1
+    }
+    return Int32(value)
+}
+
+@_cdecl("typeswift_open_keyboard_settings")
+public func typeswift_open_keyboard_settings() {
+    if let url = URL(string: "x-apple.systempreferences:com.apple.Keyboard-Settings.extension") {
+        NSWorkspace.shared.open(url)
+    }
+}
+
+/// Whether Typeswift itself (Preferences, History, …) is the frontmost app
+@_cdecl("typeswift_own_app_frontmost")
+public func typeswift_own_app_frontmost() -> Bool {
+    return NSWorkspace.shared.frontmostApplication?.processIdentifier == ProcessInfo.processInfo.processIdentifier
+}
+
+/// Transport of the default input device: 1 = Bluetooth (Classic or LE), 0 = other, -1 = unknown
+@_cdecl("typeswift_default_input_is_bluetooth")
+public func typeswift_default_input_is_bluetooth() -> Int32 {
+    var deviceID = AudioDeviceID(0)
+    var size = UInt32(MemoryLayout<AudioDeviceID>.size)
+    var address = AudioObjectPropertyAddress(
+        mSelector: kAudioHardwarePropertyDefaultInputDevice,
+        mScope: kAudioObjectPropertyScopeGlobal,
+        mElement: kAudioObjectPropertyElementMain
+    )
+    guard AudioObjectGetPropertyData(AudioObjectID(kAudioObjectSystemObject), &address, 0, nil, &size, &deviceID) == noErr else {
+        return -1
+    }
+    var transport = UInt32(0)
+    size = UInt32(MemoryLayout<UInt32>.size)
+    address.mSelector = kAudioDevicePropertyTransportType
+    guard AudioObjectGetPropertyData(deviceID, &address, 0, nil, &size, &transport) == noErr else {
+        return -1
+    }
+    return (transport == kAudioDeviceTransportTypeBluetooth || transport == kAudioDeviceTransportTypeBluetoothLE) ? 1 : 0
+}
+
+private func defaultInputDevice() -> AudioDeviceID? {
+    var deviceID = AudioDeviceID(0)
+    var size = UInt32(MemoryLayout<AudioDeviceID>.size)
+    var address = AudioObjectPropertyAddress(
+        mSelector: kAudioHardwarePropertyDefaultInputDevice,
+        mScope: kAudioObjectPropertyScopeGlobal,
+        mElement: kAudioObjectPropertyElementMain
+    )
+    guard AudioObjectGetPropertyData(AudioObjectID(kAudioObjectSystemObject), &address, 0, nil, &size, &deviceID) == noErr else {
+        return nil
+    }
+    return deviceID
+}
+
+/// Whether any process (a call app, or Typeswift itself) is using the default input device
+@_cdecl("typeswift_input_in_use")
+public func typeswift_input_in_use() -> Bool {
+    guard let deviceID = defaultInputDevice() else { return false }
+    var running = UInt32(0)
+    var size = UInt32(MemoryLayout<UInt32>.size)
+    var address = AudioObjectPropertyAddress(
+        mSelector: kAudioDevicePropertyDeviceIsRunningSomewhere,
+        mScope: kAudioObjectPropertyScopeGlobal,
+        mElement: kAudioObjectPropertyElementMain
+    )
+    guard AudioObjectGetPropertyData(deviceID, &address, 0, nil, &size, &running) == noErr else { return false }
+    return running != 0
+}
+
+/// Title of the frontmost app's focused window, via Accessibility (caller frees with typeswift_free_string)
+@_cdecl("typeswift_frontmost_window_title")
+public func typeswift_frontmost_window_title() -> UnsafeMutablePointer<CChar>? {
+    guard let pid = NSWorkspace.shared.frontmostApplication?.processIdentifier else { return nil }
+    let app = AXUIElementCreateApplication(pid)
+    var windowRef: CFTypeRef?
+    guard AXUIElementCopyAttributeValue(app, kAXFocusedWindowAttribute as CFString, &windowRef) == .success,
+          let window = windowRef else {
+        return nil
+    }
+    var titleRef: CFTypeRef?
+    guard AXUIElementCopyAttributeValue(window as! AXUIElement, kAXTitleAttribute as CFString, &titleRef) == .success,
+          let title = titleRef as? String else {
+        return nil
+    }
+    return strdup(title)
+}
+
+// ===== Spoken system commands =====
+
+/// A running app (not Typeswift) whose name is `name`, ignoring case; failing that, one
+/// whose name starts with it
+private func runningApp(named name: String) -> NSRunningApplication? {
+    let wanted = name.lowercased()
+    let apps = NSWorkspace.shared.runningApplications.filter {
+        $0.activationPolicy == .regular && $0.processIdentifier != ProcessInfo.processInfo.processIdentifier
+    }
+    return apps.first { $0.localizedName?.lowercased() == wanted }
+        ?? apps.first { $0.localizedName?.lowercased().hasPrefix(wanted) == true }
+}
+
+/// An installed app called `name` (ignoring case) in the usual Applications folders
+private func installedApp(named name: String) -> URL? {
+    let wanted = name.lowercased() + ".app"
+    let folders = ["/Applications", "/System/Applications", "/System/Applications/Utilities",
+                   NSHomeDirectory() + "/Applications"]
+    for folder in folders {
+        guard let entries = try? FileManager.default.contentsOfDirectory(atPath: folder) else { continue }
+        if let match = entries.first(where: { $0.lowercased() == wanted }) {
+            return URL(fileURLWithPath: folder).appendingPathComponent(match)
+        }
+    }
+    return nil
+}
+
+/// Bring the app called `name` to the front, launching it if it isn't running. False if no
+/// app has that name.
+@_cdecl("typeswift_activate_app")
+public func typeswift_activate_app(_ name: UnsafePointer<CChar>) -> Bool {
+    let name = String(cString: name)
+    if let app = runningApp(named: name) {
+        return app.activate(options: [.activateAllWindows])
+    }
+    guard let url = installedApp(named: name) else { return false }
+    NSWorkspace.shared.openApplication(at: url, configuration: NSWorkspace.OpenConfiguration())
+    return true
+}
+
+/// Display name of the running app called `name`, or of the frontmost app when `name` is nil;
+/// nil if there's none (caller frees with typeswift_free_string)
+@_cdecl("typeswift_running_app_name")
+public func typeswift_running_app_name(_ name: UnsafePointer<CChar>?) -> UnsafeMutablePointer<CChar>? {
+    let app = name.map { runningApp(named: String(cString: $0)) } ?? NSWorkspace.shared.frontmostApplication
+    guard let app, app.processIdentifier != ProcessInfo.processInfo.processIdentifier,
+          let appName = app.localizedName else {
+        return nil
+    }
+    return strdup(appName)
+}
+
+/// Ask the running app called `name` (the frontmost one when nil) to quit, as Cmd+Q would
+@_cdecl("typeswift_quit_app")
+public func typeswift_quit_app(_ name: UnsafePointer<CChar>?) -> Bool {
+    let app = name.map { runningApp(named: String(cString: $0)) } ?? NSWorkspace.shared.frontmostApplication
+    guard let app, app.processIdentifier != ProcessInfo.processInfo.processIdentifier else { return false }
+    return app.terminate()
+}
+
+/// Act on the frontmost app's focused window: 0 = close, 1 = minimize, 2 = fill the screen
+/// (without entering full screen), 3 = toggle full screen. False if AX refused or there's no
+/// window.
+@_cdecl("typeswift_window_action")
+public func typeswift_window_action(_ action: Int32) -> Bool {
+    guard let pid = NSWorkspace.shared.frontmostApplication?.processIdentifier else { return false }
+    let app = AXUIElementCreateApplication(pid)
+    var windowRef: CFTypeRef?
+    guard AXUIElementCopyAttributeValue(app, kAXFocusedWindowAttribute as CFString, &windowRef) == .success,
+          let windowRef else {
+        return false
+    }
+    let window = windowRef as! AXUIElement
+    switch action {
+    case 0:
+        var button: CFTypeRef?
+        guard AXUIElementCopyAttributeValue(window, kAXCloseButtonAttribute as CFString, &button) == .success,
+              let button else {
+            return false
+        }
+        return AXUIElementPerformAction(button as! AXUIElement, kAXPressAction as CFString) == .success
+    case 1:
+        return AXUIElementSetAttributeValue(window, kAXMinimizedAttribute as CFString, kCFBooleanTrue) == .success
+    case 2:
+        // AX puts the origin at the top left of the main screen; AppKit at its bottom left
+        guard let screen = NSScreen.main, let main = NSScreen.screens.first else { return false }
+        let frame = screen.visibleFrame
+        var origin = CGPoint(x: frame.minX, y: main.frame.maxY - frame.maxY)
+        var size = CGSize(width: frame.width, height: frame.height)
+        guard let position = AXValueCreate(.cgPoint, &origin), let extent = AXValueCreate(.cgSize, &size) else {
+            return false
+        }
+        let moved = AXUIElementSetAttributeValue(window, kAXPositionAttribute as CFString, position) == .success
+        let sized = AXUIElementSetAttributeValue(window, kAXSizeAttribute as CFString, extent) == .success
+        return moved && sized
+    case 3:
+        let attribute = "AXFullScreen" as CFString
+        var current: CFTypeRef?
+        let isFull = AXUIElementCopyAttributeValue(window, attribute, &current) == .success
+            && (current as? Bool) == true
+        let value = isFull ? kCFBooleanFalse : kCFBooleanTrue
+        return AXUIElementSetAttributeValue(window, attribute, value!) == .success
+    default:
+        return false
+    }
+}
+
+/// Input volume address of the device: the main element if it has one, else the first channel
+private func inputVolumeAddress(_ deviceID: AudioDeviceID) -> AudioObjectPropertyAddress? {
+    for element in [kAudioObjectPropertyElementMain, 1] {
+        var address = AudioObjectPropertyAddress(
+            mSelector: kAudioDevicePropertyVolumeScalar,
+            mScope: kAudioDevicePropertyScopeInput,
+            mElement: element
+        )
+        if AudioObjectHasProperty(deviceID, &address) {
+            return address
+        }
+    }
+    return nil
+}
+
+/// Hardware input volume (0...1) of the default input device, or -1 when it has none
+@_cdecl("typeswift_input_volume")
+public func typeswift_input_volume() -> Float {
+    guard let deviceID = defaultInputDevice(), var address = inputVolumeAddress(deviceID) else { return -1 }
+    var volume = Float32(0)
+    var size = UInt32(MemoryLayout<Float32>.size)
+    guard AudioObjectGetPropertyData(deviceID, &address, 0, nil, &size, &volume) == noErr else { return -1 }
+    return volume
+}
+
+/// Set the default input device's hardware volume; false when it can't be changed
+@_cdecl("typeswift_set_input_volume")
+public func typeswift_set_input_volume(_ volume: Float) -> Bool {
+    guard let deviceID = defaultInputDevice(), var address = inputVolumeAddress(deviceID) else { return false }
+    var settable = DarwinBoolean(false)
+    guard AudioObjectIsPropertySettable(deviceID, &address, &settable) == noErr, settable.boolValue else { return false }
+    var value = Float32(min(max(volume, 0), 1))
+    return AudioObjectSetPropertyData(deviceID, &address, 0, nil, UInt32(MemoryLayout<Float32>.size), &value) == noErr
+}
+
+/// Microphone access: 0 not asked yet, 1 restricted, 2 denied, 3 granted
+@_cdecl("typeswift_microphone_authorization")
+public func typeswift_microphone_authorization() -> Int32 {
+    return Int32(AVCaptureDevice.authorizationStatus(for: .audio).rawValue)
+}
+
+/// Whether the process currently holds Accessibility permission (never prompts)
+@_cdecl("typeswift_accessibility_trusted")
+public func typeswift_accessibility_trusted() -> Bool {
+    return AXIsProcessTrusted()
+}
+
+/// Select a file in a Finder window
+@_cdecl("typeswift_reveal_in_finder")
+public func typeswift_reveal_in_finder(_ path: UnsafePointer<CChar>) {
+    let url = URL(fileURLWithPath: String(cString: path))
+    DispatchQueue.main.async {
+        NSWorkspace.shared.activateFileViewerSelecting([url])
+    }
+}
+
+/// Generic password from the login keychain (caller frees with typeswift_free_string)
+@_cdecl("typeswift_keychain_get")
+public func typeswift_keychain_get(_ service: UnsafePointer<CChar>, _ account: UnsafePointer<CChar>) -> UnsafeMutablePointer<CChar>? {
+    let query: [String: Any] = [
+        kSecClass as String: kSecClassGenericPassword,
+        kSecAttrService as String: String(cString: service),
+        kSecAttrAccount as String: String(cString: account),
+        kSecReturnData as String: true,
+        kSecMatchLimit as String: kSecMatchLimitOne,
+    ]
+    var result: CFTypeRef?
+    guard SecItemCopyMatching(query as CFDictionary, &result) == errSecSuccess,
+          let data = result as? Data,
+          let value = String(data: data, encoding: .utf8) else {
+        return nil
+    }
+    return strdup(value)
+}
+
+/// Store (or replace) a generic password in the login keychain
+@_cdecl("typeswift_keychain_set")
+public func typeswift_keychain_set(_ service: UnsafePointer<CChar>, _ account: UnsafePointer<CChar>, _ value: UnsafePointer<CChar>) -> Bool {
+    let base: [String: Any] = [
+        kSecClass as String: kSecClassGenericPassword,
+        kSecAttrService as String: String(cString: service),
+        kSecAttrAccount as String: String(cString: account),
+    ]
+    let data = Data(String(cString: value).utf8)
+    SecItemDelete(base as CFDictionary)
+    var item = base
+    item[kSecValueData as String] = data
+    item[kSecAttrAccessible as String] = kSecAttrAccessibleAfterFirstUnlockThisDeviceOnly
+    return SecItemAdd(item as CFDictionary, nil) == errSecSuccess
+}
+
+// MARK: - Focus
+
+private let focusDatabase = FileManager.default.homeDirectoryForCurrentUser.appendingPathComponent("Library/DoNotDisturb/DB")
+
+private func readFocusDatabase(_ name: String) -> [[String: Any]]? {
+    guard let data = try? Data(contentsOf: focusDatabase.appendingPathComponent(name)),
+          let json = try? JSONSerialization.jsonObject(with: data) as? [String: Any] else {
+        return nil
+    }
+    return json["data"] as? [[String: Any]]
+}
+
+/// Mode identifiers of manually enabled Focus modes; nil when the Do Not Disturb database
+/// can't be read (it needs Full Disk Access). Scheduled Focus isn't recorded there.
+private func focusAssertionModes() -> [String]? {
+    guard let entries = readFocusDatabase("Assertions.json") else { return nil }
+    return entries
+        .flatMap { ($0["storeAssertionRecords"] as? [[String: Any]]) ?? [] }
+        .compactMap { ($0["assertionDetails"] as? [String: Any])?["assertionDetailsModeIdentifier"] as? String }
+}
+
+/// Whether a Focus mode is on: 1 = yes, 0 = no, -1 = unknown (not authorized and database unreadable)
+@_cdecl("typeswift_focus_active")
+public func typeswift_focus_active() -> Int32 {
+    let center = INFocusStatusCenter.default
+    if center.authorizationStatus == .notDetermined {
+        center.requestAuthorization { _ in }
+    }
+    if center.authorizationStatus == .authorized, let focused = center.focusStatus.isFocused {
+        return focused ? 1 : 0
+    }
+    guard let modes = focusAssertionModes() else { return -1 }
+    return modes.isEmpty ? 0 : 1
+}
+
+/// Name of the active Focus mode (e.g. "Work"), when the database is readable
+/// (caller frees with typeswift_free_string)
+@_cdecl("typeswift_focus_mode_name")
+public func typeswift_focus_mode_name() -> UnsafeMutablePointer<CChar>? {
+    guard let identifier = focusAssertionModes()?.last,
+          let configurations = readFocusDatabase("ModeConfigurations.json")?.first?["modeConfigurations"] as? [String: Any],
+          let mode = (configurations[identifier] as? [String: Any])?["mode"] as? [String: Any],
+          let name = mode["name"] as? String else {
+        return nil
+    }
+    return strdup(name)
+}
+
+// MARK: - Caps Lock LED
+
+/// Set the Caps Lock LED on every keyboard without changing Caps Lock itself.
+/// Turning it off restores the LED to the real Caps Lock state.
+@_cdecl("typeswift_set_caps_lock_led")
+public func typeswift_set_caps_lock_led(_ on: Bool) {
+    let lit = on || NSEvent.modifierFlags.contains(.capsLock)
+    let manager = IOHIDManagerCreate(kCFAllocatorDefault, IOOptionBits(kIOHIDOptionsTypeNone))
+    let keyboards: [String: Any] = [
+        kIOHIDDeviceUsagePageKey as String: kHIDPage_GenericDesktop,
+        kIOHIDDeviceUsageKey as String: kHIDUsage_GD_Keyboard,
+    ]
+    IOHIDManagerSetDeviceMatching(manager, keyboards as CFDictionary)
+    guard IOHIDManagerOpen(manager, IOOptionBits(kIOHIDOptionsTypeNone)) == kIOReturnSuccess else { return }
+    defer { IOHIDManagerClose(manager, IOOptionBits(kIOHIDOptionsTypeNone)) }
+    guard let devices = IOHIDManagerCopyDevices(manager) as? Set<IOHIDDevice> else { return }
+    let capsLockLED: [String: Any] = [
+        kIOHIDElementUsagePageKey as String: kHIDPage_LEDs,
+        kIOHIDElementUsageKey as String: kHIDUsage_LED_CapsLock,
+    ]
+    for device in devices {
+        guard let elements = IOHIDDeviceCopyMatchingElements(device, capsLockLED as CFDictionary, IOOptionBits(kIOHIDOptionsTypeNone)) as? [IOHIDElement] else {
+            continue
+        }
+        for element in elements {
+            let value = IOHIDValueCreateWithIntegerValue(kCFAllocatorDefault, element, 0, lit ? 1 : 0)
+            IOHIDDeviceSetValue(device, element, value)
+        }
+    }
+}
+
+// MARK: - Read-back
+
+// Must outlive the utterance
+private let speechSynthesizer = AVSpeechSynthesizer()
+
+/// Speak `text` with the default system voice, interrupting anything still being read
+@_cdecl("typeswift_speak")
+public func typeswift_speak(_ text: UnsafePointer<CChar>) {
+    let utterance = AVSpeechUtterance(string: String(cString: text))
+    DispatchQueue.main.async {
+        speechSynthesizer.stopSpeaking(at: .immediate)
+        speechSynthesizer.speak(utterance)
+    }
+}
+
+/// Have VoiceOver speak `text`; no-op when VoiceOver is off
+@_cdecl("typeswift_announce")
+public func typeswift_announce(_ text: UnsafePointer<CChar>) {
+    let message = String(cString: text)
+    DispatchQueue.main.async {
+        guard NSWorkspace.shared.isVoiceOverEnabled else { return }
+        NSAccessibility.post(element: NSApp as Any, notification: .announcementRequested, userInfo: [
+            .announcement: message,
+            .priority: NSAccessibilityPriorityLevel.high.rawValue,
+        ])
+    }
+}
+
+@_cdecl("typeswift_reduce_motion")
+public func typeswift_reduce_motion() -> Bool {
+    return NSWorkspace.shared.accessibilityDisplayShouldReduceMotion
+}
+
+@_cdecl("typeswift_increase_contrast")
+public func typeswift_increase_contrast() -> Bool {
+    return NSWorkspace.shared.accessibilityDisplayShouldIncreaseContrast
+}
+
+// MARK: - Calendar
+
+private let eventStore = EKEventStore()
+
+/// Whether Typeswift may read calendar events, asking the user the first time.
+/// Blocks until the permission prompt is answered; call off the main thread.
+@_cdecl("typeswift_calendar_request_access")
+public func typeswift_calendar_request_access() -> Bool {
+    let status = EKEventStore.authorizationStatus(for: .event)
+    if #available(macOS 14.0, *) {
+        if status == .fullAccess { return true }
+    } else if status == .authorized {
+        return true
+    }
+    guard status == .notDetermined else { return false }
+    let answered = DispatchSemaphore(value: 0)
+    var granted = false
+    let completion: EKEventStoreRequestAccessCompletionHandler = { ok, _ in
+        granted = ok
+        answered.signal()
+    }
+    if #available(macOS 14.0, *) {
+        eventStore.requestFullAccessToEvents(completion: completion)
+    } else {
+        eventStore.requestAccess(to: .event, completion: completion)
+    }
+    answered.wait()
+    return granted
+}
+
+/// Timed (not all-day) events overlapping now ± `windowSeconds`, as a JSON array
+/// of {"id", "title", "start", "end"} with times in Unix seconds
+@_cdecl("typeswift_calendar_events")
+public func typeswift_calendar_events(_ windowSeconds: Double) -> UnsafeMutablePointer<CChar>? {
+    let now = Date()
+    let predicate = eventStore.predicateForEvents(
+        withStart: now.addingTimeInterval(-windowSeconds),
+        end: now.addingTimeInterval(windowSeconds),
+        calendars: nil
+    )
+    let events: [[String: Any]] = eventStore.events(matching: predicate)
+        .filter { !$0.isAllDay }
+        .map { event in
+            [
+                // Recurring events share an identifier; the start time tells occurrences apart
+                "id": "\(event.eventIdentifier ?? "")@\(Int(event.startDate.timeIntervalSince1970))",
+                "title": event.title ?? "",
+                "start": event.startDate.timeIntervalSince1970,
+                "end": event.endDate.timeIntervalSince1970,
+            ]
+        }
+    guard let data = try? JSONSerialization.data(withJSONObject: events),
+          let json = String(data: data, encoding: .utf8) else {
+        return nil
+    }
+    return strdup(json)
+}
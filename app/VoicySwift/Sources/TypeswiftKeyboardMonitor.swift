@@ -10,7 +10,29 @@ import Carbon
     private var runLoopSource: CFRunLoopSource?
     private var isMonitoring: Bool = false
     private var isRecording = false
-    private var lastModifierFlags: NSEvent.ModifierFlags = []
+    
+    // Push-to-talk chord: device-independent modifier bits that must be held
+    // exactly, plus optional device-dependent (left/right) bits. Defaults to Fn.
+    private var chordFlags: UInt64 = CGEventFlags.maskSecondaryFn.rawValue
+    private var chordDeviceFlags: UInt64 = 0
+    // Chords that start push-to-talk for a profile, and the profile of the chord being held
+    // (-1 for the main chord)
+    private var profileChords: [(flags: UInt64, deviceFlags: UInt64, profile: Int32)] = []
+    private var heldProfile: Int32 = -1
+    // Swallow Fn/Globe presses so macOS doesn't also start dictation or show the emoji picker
+    private var suppressSystemFn = false
+    private static let functionKeyCode: Int64 = 63 // kVK_Function
+    // Key held for push-to-talk whose repeats are swallowed while recording (-1 = none)
+    private var swallowedKeyCode: Int64 = -1
+    private var recordingActive = false
+    // Told when macOS disables the event tap: 0 = timeout, 1 = user input
+    var tapDisabledHandler: ((Int32) -> Void)?
+    private static let trackedFlags: UInt64 =
+        CGEventFlags.maskCommand.rawValue |
+        CGEventFlags.maskControl.rawValue |
+        CGEventFlags.maskAlternate.rawValue |
+        CGEventFlags.maskShift.rawValue |
+        CGEventFlags.maskSecondaryFn.rawValue
     
     @objc public static let shared = TypeswiftKeyboardMonitor()
     
@@ -21,7 +43,7 @@ import Carbon
     @objc public func startMonitoring() {
         if isMonitoring { return }
         isMonitoring = true
-        print("Starting keyboard monitoring for fn key")
+        print("Starting keyboard monitoring for push-to-talk chord")
         
         // Monitor modifier flags changes (for fn key)
         flagsMonitor = NSEvent.addGlobalMonitorForEvents(matching: .flagsChanged) { [weak self] event in
@@ -58,51 +80,94 @@ import Carbon
         print("Keyboard monitoring stopped")
     }
     
-    private func handleFlagsChanged(_ event: NSEvent) {
-        let currentFlags = event.modifierFlags
-        
-        // Check if fn key state changed
-        let fnWasPressed = lastModifierFlags.contains(.function)
-        let fnIsPressed = currentFlags.contains(.function)
+    /// Set the modifier chord that acts as push-to-talk
+    @objc public func setChord(flags: UInt64, deviceFlags: UInt64) {
+        chordFlags = flags & TypeswiftKeyboardMonitor.trackedFlags
+        chordDeviceFlags = deviceFlags
+        isRecording = false
+        print("Push-to-talk chord set: flags=0x\(String(chordFlags, radix: 16)) device=0x\(String(chordDeviceFlags, radix: 16))")
+    }
+    
+    /// Set the profiles' own push-to-talk chords
+    public func setProfileChords(_ chords: [(flags: UInt64, deviceFlags: UInt64, profile: Int32)]) {
+        profileChords = chords.map { chord in
+            (flags: chord.flags & TypeswiftKeyboardMonitor.trackedFlags, deviceFlags: chord.deviceFlags, profile: chord.profile)
+        }
+        print("Profile push-to-talk chords set: \(profileChords.count)")
+    }
+    
+    /// Stop Fn/Globe key events reaching the system while Fn alone is the chord (event tap only)
+    @objc public func setSuppressSystemFn(_ suppress: Bool) {
+        suppressSystemFn = suppress
+        print("Suppress system Fn action: \(suppress)")
+    }
+    
+    /// Swallow key-down events (auto-repeat) of `keyCode` while recording; -1 turns this off
+    @objc public func setSwallowedKey(_ keyCode: Int32) {
+        swallowedKeyCode = Int64(keyCode)
+        print("Swallowed push-to-talk key: \(keyCode)")
+    }
+    
+    @objc public func setRecordingActive(_ active: Bool) {
+        recordingActive = active
+    }
+    
+    /// Chord is held when exactly the chord's modifiers are down (plus the required sides)
+    private func chordIsHeld(_ rawFlags: UInt64, flags: UInt64, deviceFlags: UInt64) -> Bool {
+        if flags == 0 { return false }
+        let held = rawFlags & TypeswiftKeyboardMonitor.trackedFlags
+        return held == flags && (rawFlags & deviceFlags) == deviceFlags
+    }
+    
+    /// Profile of the chord held right now: -1 for the main chord, nil when none is
+    private func heldChord(_ rawFlags: UInt64) -> Int32? {
+        if chordIsHeld(rawFlags, flags: chordFlags, deviceFlags: chordDeviceFlags) {
+            return -1
+        }
+        return profileChords.first { chordIsHeld(rawFlags, flags: $0.flags, deviceFlags: $0.deviceFlags) }?.profile
+    }
+    
+    private func handleModifierChange(_ rawFlags: UInt64, source: String) {
+        let chord = heldChord(rawFlags)
+        let held = isRecording ? chord == heldProfile : chord != nil
         
-        if fnIsPressed && !fnWasPressed {
-            // fn key was just pressed
-            if !isRecording {
-                isRecording = true
-                print("Fn key PRESSED - Starting recording")
-                
-                // Post notification to Rust side
-                DispatchQueue.main.async {
-                    NotificationCenter.default.post(
-                        name: NSNotification.Name("TypeswiftPushToTalkPressed"),
-                        object: nil
-                    )
-                }
+        if held && !isRecording, let profile = chord {
+            isRecording = true
+            heldProfile = profile
+            print("Push-to-talk chord PRESSED (\(source), profile \(profile)) - Starting recording")
+            
+            // Post notification to Rust side
+            DispatchQueue.main.async {
+                NotificationCenter.default.post(
+                    name: NSNotification.Name("TypeswiftPushToTalkPressed"),
+                    object: nil,
+                    userInfo: ["profile": profile]
+                )
             }
-        } else if !fnIsPressed && fnWasPressed {
-            // fn key was just released
-            if isRecording {
-                isRecording = false
-                print("Fn key RELEASED - Stopping recording")
-                
-                // Post notification to Rust side
-                DispatchQueue.main.async {
-                    NotificationCenter.default.post(
-                        name: NSNotification.Name("TypeswiftPushToTalkReleased"),
-                        object: nil
-                    )
-                }
+        } else if !held && isRecording {
+            isRecording = false
+            print("Push-to-talk chord RELEASED (\(source)) - Stopping recording")
+            
+            // Post notification to Rust side
+            DispatchQueue.main.async {
+                NotificationCenter.default.post(
+                    name: NSNotification.Name("TypeswiftPushToTalkReleased"),
+                    object: nil
+                )
             }
         }
-        
-        lastModifierFlags = currentFlags
+    }
+    
+    private func handleFlagsChanged(_ event: NSEvent) {
+        // NSEvent modifier raw values share the CGEventFlags bit layout
+        handleModifierChange(UInt64(event.modifierFlags.rawValue), source: "NSEvent")
     }
     
     // Alternative method using CGEvent for system-wide monitoring
     @objc public func startCGEventMonitoring() -> Bool {
         if isMonitoring { return true }
         isMonitoring = true
-        print("Starting CGEvent monitoring for fn key")
+        print("Starting CGEvent monitoring for push-to-talk chord")
         
         // Request accessibility permissions
         let options = [kAXTrustedCheckOptionPrompt.takeUnretainedValue() as String: true] as CFDictionary
@@ -119,11 +184,22 @@ import Carbon
             tap: .cgSessionEventTap,
             place: .headInsertEventTap,
             options: .defaultTap,
-            eventsOfInterest: CGEventMask(1 << CGEventType.flagsChanged.rawValue),
+            eventsOfInterest: CGEventMask(1 << CGEventType.flagsChanged.rawValue)
+                | CGEventMask(1 << CGEventType.keyDown.rawValue),
             callback: { (proxy, type, event, refcon) -> Unmanaged<CGEvent>? in
                 guard let refcon = refcon else { return Unmanaged.passRetained(event) }
                 let monitor = Unmanaged<TypeswiftKeyboardMonitor>.fromOpaque(refcon).takeUnretainedValue()
+                if type == .tapDisabledByTimeout || type == .tapDisabledByUserInput {
+                    monitor.handleTapDisabled(byTimeout: type == .tapDisabledByTimeout)
+                    return Unmanaged.passUnretained(event)
+                }
+                if type == .keyDown {
+                    return monitor.swallowsKeyDown(event) ? nil : Unmanaged.passRetained(event)
+                }
                 monitor.handleCGEvent(event)
+                if monitor.swallowsFunctionKey(event) {
+                    return nil
+                }
                 return Unmanaged.passRetained(event)
             },
             userInfo: Unmanaged.passUnretained(self).toOpaque()
@@ -144,32 +220,51 @@ import Carbon
     }
     
     private func handleCGEvent(_ event: CGEvent) {
-        let flags = event.flags
-        
-        // Check if fn key is pressed (function flag)
-        let fnIsPressed = flags.contains(.maskSecondaryFn)
-        
-        if fnIsPressed && !isRecording {
-            isRecording = true
-            print("Fn key PRESSED (CGEvent) - Starting recording")
-            
-            DispatchQueue.main.async {
-                NotificationCenter.default.post(
-                    name: NSNotification.Name("TypeswiftPushToTalkPressed"),
-                    object: nil
-                )
-            }
-        } else if !fnIsPressed && isRecording {
-            isRecording = false
-            print("Fn key RELEASED (CGEvent) - Stopping recording")
-            
-            DispatchQueue.main.async {
-                NotificationCenter.default.post(
-                    name: NSNotification.Name("TypeswiftPushToTalkReleased"),
-                    object: nil
-                )
-            }
+        handleModifierChange(event.flags.rawValue, source: "CGEvent")
+    }
+    
+    /// macOS turns the tap off when a callback is slow or around secure input; turn it back on
+    /// and catch up on a release that may have been missed meanwhile
+    private func handleTapDisabled(byTimeout: Bool) {
+        print("Event tap disabled by \(byTimeout ? "timeout" : "user input"); re-enabling")
+        if let tap = eventTap {
+            CGEvent.tapEnable(tap: tap, enable: true)
+        }
+        handleModifierChange(CGEventSource.flagsState(.combinedSessionState).rawValue, source: "resync")
+        tapDisabledHandler?(byTimeout ? 0 : 1)
+    }
+    
+    /// 0 = not monitoring, 1 = event tap enabled, 2 = event tap disabled, 3 = NSEvent fallback
+    @objc public func tapStatus() -> Int32 {
+        guard isMonitoring else { return 0 }
+        guard let tap = eventTap else { return 3 }
+        return CGEvent.tapIsEnabled(tap: tap) ? 1 : 2
+    }
+    
+    /// Tear down and recreate the event tap, falling back to NSEvent monitoring if it can't be
+    /// created; must run on the main thread
+    @objc public func reinstallTap() -> Bool {
+        stopMonitoring()
+        if startCGEventMonitoring() {
+            return true
         }
+        stopMonitoring()
+        startMonitoring()
+        return false
+    }
+    
+    /// Key-down of the push-to-talk key while recording, so holding it never types. Key-ups pass
+    /// through: the hotkey's release has to reach its handler to stop recording.
+    private func swallowsKeyDown(_ event: CGEvent) -> Bool {
+        return recordingActive
+            && swallowedKeyCode >= 0
+            && event.getIntegerValueField(.keyboardEventKeycode) == swallowedKeyCode
+    }
+    
+    private func swallowsFunctionKey(_ event: CGEvent) -> Bool {
+        return suppressSystemFn
+            && chordFlags == CGEventFlags.maskSecondaryFn.rawValue
+            && event.getIntegerValueField(.keyboardEventKeycode) == TypeswiftKeyboardMonitor.functionKeyCode
     }
 }
 
@@ -177,7 +272,7 @@ import Carbon
 @objc public extension TypeswiftKeyboardMonitor {
     
     @objc static func initializeKeyboardMonitor() -> Bool {
-        // Try CGEvent monitoring first (more reliable for fn key)
+        // Try CGEvent monitoring first (more reliable for fn/modifier keys)
         if shared.startCGEventMonitoring() {
             return true
         }
@@ -190,4 +285,20 @@ import Carbon
     @objc static func shutdownKeyboardMonitor() {
         shared.stopMonitoring()
     }
+    
+    @objc static func setPushToTalkChord(flags: UInt64, deviceFlags: UInt64) {
+        shared.setChord(flags: flags, deviceFlags: deviceFlags)
+    }
+    
+    @objc static func setSuppressSystemFn(_ suppress: Bool) {
+        shared.setSuppressSystemFn(suppress)
+    }
+    
+    @objc static func setSwallowedKey(_ keyCode: Int32) {
+        shared.setSwallowedKey(keyCode)
+    }
+    
+    @objc static func setRecordingActive(_ active: Bool) {
+        shared.setRecordingActive(active)
+    }
 }
@@ -45,6 +45,35 @@ public func typeswift_transcribe(
     return result
 }
 
+/// Start handing over a recording of about `capacity` samples in slices
+@_cdecl("typeswift_session_begin")
+public func typeswift_session_begin(_ capacity: Int) {
+    TypeswiftTranscriber.shared.beginSession(capacity: capacity)
+}
+
+/// Append `count` samples to the recording started by typeswift_session_begin
+@_cdecl("typeswift_session_add_samples")
+public func typeswift_session_add_samples(_ samples: UnsafePointer<Float>?, _ count: Int) {
+    guard let samples = samples, count > 0 else { return }
+    TypeswiftTranscriber.shared.addSamples(samples, count: count)
+}
+
+/// Transcribe the handed-over recording with token timings and release it
+/// Returns a JSON C string that caller must free, or NULL on error
+@_cdecl("typeswift_session_transcribe_with_timings")
+public func typeswift_session_transcribe_with_timings() -> UnsafeMutablePointer<CChar>? {
+    let semaphore = DispatchSemaphore(value: 0)
+    var result: UnsafeMutablePointer<CChar>? = nil
+    
+    Task {
+        result = await TypeswiftTranscriber.shared.transcribeSessionWithTimings()
+        semaphore.signal()
+    }
+    
+    semaphore.wait()
+    return result
+}
+
 /// Free a C string returned by transcribe
 @_cdecl("typeswift_free_string")
 public func typeswift_free_string(_ str: UnsafeMutablePointer<CChar>?) {
@@ -10,6 +10,16 @@ import ServiceManagement
     private var menu: NSMenu?
     private var baseIcon: NSImage?
     private var recordingIcon: NSImage?
+    private var typingHealthItem: NSMenuItem?
+    private var callSuggestionItem: NSMenuItem?
+    private var lastErrorItem: NSMenuItem?
+    private var lastError: (title: String, message: String)?
+    private var quickSettingsItem: NSMenuItem?
+    private var languageItem: NSMenuItem?
+    private var modelItem: NSMenuItem?
+    private var outputItem: NSMenuItem?
+    // Last state from Rust, re-applied if it arrives before the menu exists
+    private var quickSettings: QuickSettings?
     
     
     @objc public static let shared = TypeswiftMenuBar()
@@ -94,6 +104,27 @@ import ServiceManagement
         let titleItem = NSMenuItem(title: "Typeswift - Speech Recognition", action: nil, keyEquivalent: "")
         titleItem.isEnabled = false
         menu?.addItem(titleItem)
+
+        // Shown only while typing is failing
+        let healthItem = NSMenuItem(title: "⚠︎ Typing is failing – Open Accessibility Settings…", action: #selector(openAccessibilitySettings), keyEquivalent: "")
+        healthItem.target = self
+        healthItem.isHidden = true
+        menu?.addItem(healthItem)
+        typingHealthItem = healthItem
+
+        // Shown while a video call is detected; titled by setCallSuggestion
+        let callItem = NSMenuItem(title: "", action: #selector(transcribeCall), keyEquivalent: "")
+        callItem.target = self
+        callItem.isHidden = true
+        menu?.addItem(callItem)
+        callSuggestionItem = callItem
+
+        // Shown once something has gone wrong; titled by setLastError
+        let errorItem = NSMenuItem(title: "", action: #selector(showLastError), keyEquivalent: "")
+        errorItem.target = self
+        errorItem.isHidden = true
+        menu?.addItem(errorItem)
+        lastErrorItem = errorItem
         
         menu?.addItem(NSMenuItem.separator())
         
@@ -101,14 +132,57 @@ import ServiceManagement
         let settingsItem = NSMenuItem(title: "Preferences", action: #selector(openPreferences), keyEquivalent: "")
         settingsItem.target = self
         menu?.addItem(settingsItem)
+
+        let historyItem = NSMenuItem(title: "History…", action: #selector(openHistory), keyEquivalent: "")
+        historyItem.target = self
+        menu?.addItem(historyItem)
+
+        let paletteItem = NSMenuItem(title: "Command Palette…", action: #selector(openCommandPalette), keyEquivalent: "")
+        paletteItem.target = self
+        menu?.addItem(paletteItem)
+
+        let speakItem = NSMenuItem(title: "Speak Last Result", action: #selector(speakLastResult), keyEquivalent: "")
+        speakItem.target = self
+        menu?.addItem(speakItem)
+
+        let stopItem = NSMenuItem(title: "Stop Typing", action: #selector(stopTyping), keyEquivalent: "")
+        stopItem.target = self
+        menu?.addItem(stopItem)
+        
+        // Quick toggles; filled in by setQuickSettings
+        let quickItem = NSMenuItem(title: "Quick Settings", action: nil, keyEquivalent: "")
+        quickItem.submenu = NSMenu(title: "Quick Settings")
+        menu?.addItem(quickItem)
+        quickSettingsItem = quickItem
         
-        // Language info
+        // Language info (becomes a picker once settings arrive)
         let languageItem = NSMenuItem(title: "Language: Auto-detect (25 languages)", action: nil, keyEquivalent: "")
         languageItem.isEnabled = false
         menu?.addItem(languageItem)
+        self.languageItem = languageItem
+        
+        // Model picker; hidden until settings list the installed models
+        let modelItem = NSMenuItem(title: "Model", action: nil, keyEquivalent: "")
+        modelItem.isHidden = true
+        menu?.addItem(modelItem)
+        self.modelItem = modelItem
+        
+        // Output destination picker; filled in by setQuickSettings
+        let outputItem = NSMenuItem(title: "Output", action: nil, keyEquivalent: "")
+        outputItem.isHidden = true
+        menu?.addItem(outputItem)
+        self.outputItem = outputItem
+        
+        let resetPopupItem = NSMenuItem(title: "Reset Popup Position", action: #selector(resetPopupPosition), keyEquivalent: "")
+        resetPopupItem.target = self
+        menu?.addItem(resetPopupItem)
         
         menu?.addItem(NSMenuItem.separator())
         
+        let supportItem = NSMenuItem(title: "Generate Support Bundle…", action: #selector(generateSupportBundle), keyEquivalent: "")
+        supportItem.target = self
+        menu?.addItem(supportItem)
+
         // About
         let aboutItem = NSMenuItem(title: "About Typeswift", action: #selector(showAbout), keyEquivalent: "")
         aboutItem.target = self
@@ -116,6 +190,11 @@ import ServiceManagement
         
         menu?.addItem(NSMenuItem.separator())
         
+        // Relaunch (e.g. after granting Accessibility)
+        let relaunchItem = NSMenuItem(title: "Relaunch Typeswift", action: #selector(relaunchApp), keyEquivalent: "")
+        relaunchItem.target = self
+        menu?.addItem(relaunchItem)
+        
         // Quit
         let quitItem = NSMenuItem(title: "Quit Typeswift", action: #selector(quitApp), keyEquivalent: "q")
         quitItem.target = self
@@ -123,6 +202,9 @@ import ServiceManagement
         
         // Assign menu to status item
         statusItem?.menu = menu
+        if let settings = quickSettings {
+            applyQuickSettings(settings)
+        }
     }
     
     
@@ -136,6 +218,206 @@ import ServiceManagement
         NotificationCenter.default.post(name: NSNotification.Name("TypeswiftOpenPreferences"), object: nil)
     }
     
+    @objc private func openHistory() {
+        DispatchQueue.main.async {
+            NSApp.activate(ignoringOtherApps: true)
+        }
+        postMenuAction("history")
+    }
+
+    @objc private func openCommandPalette() {
+        DispatchQueue.main.async {
+            NSApp.activate(ignoringOtherApps: true)
+        }
+        postMenuAction("command_palette")
+    }
+
+    /// Forward a menu action to Rust (see swift_register_menu_action_callback)
+    private func postMenuAction(_ action: String) {
+        NotificationCenter.default.post(name: NSNotification.Name("TypeswiftMenuAction"), object: nil, userInfo: ["action": action])
+    }
+    
+    @objc private func resetPopupPosition() {
+        postMenuAction("reset_popup_position")
+    }
+    
+    @objc private func speakLastResult() {
+        postMenuAction("speak_last")
+    }
+    
+    @objc private func stopTyping() {
+        postMenuAction("stop_typing")
+    }
+    
+    @objc private func relaunchApp() {
+        postMenuAction("relaunch")
+    }
+    
+    @objc private func openAccessibilitySettings() {
+        if let url = URL(string: "x-apple.systempreferences:com.apple.preference.security?Privacy_Accessibility") {
+            NSWorkspace.shared.open(url)
+        }
+    }
+    
+    /// Show or hide the typing-failure warning in the menu
+    @objc public func setTypingHealth(_ ok: Bool) {
+        DispatchQueue.main.async { [weak self] in
+            self?.typingHealthItem?.isHidden = ok
+        }
+    }
+    
+    @objc private func transcribeCall() {
+        postMenuAction("transcribe_call")
+    }
+
+    /// Show the call suggestion with `title`, or hide it when nil
+    @objc public func setCallSuggestion(_ title: String?) {
+        DispatchQueue.main.async { [weak self] in
+            guard let item = self?.callSuggestionItem else { return }
+            item.title = title ?? ""
+            item.isHidden = title == nil
+        }
+    }
+    
+    /// Remember the most recent error and show it in the menu
+    @objc public func setLastError(_ title: String, message: String) {
+        DispatchQueue.main.async { [weak self] in
+            self?.lastError = (title, message)
+            self?.lastErrorItem?.title = "⚠︎ Last error: \(title)…"
+            self?.lastErrorItem?.isHidden = false
+        }
+    }
+
+    @objc private func showLastError() {
+        guard let error = lastError else { return }
+        let alert = NSAlert()
+        alert.messageText = error.title
+        alert.informativeText = error.message
+        alert.alertStyle = .warning
+        alert.addButton(withTitle: "OK")
+        alert.addButton(withTitle: "Dismiss Error")
+        NSApp.activate(ignoringOtherApps: true)
+        if alert.runModal() == .alertSecondButtonReturn {
+            lastError = nil
+            lastErrorItem?.isHidden = true
+        }
+    }
+    
+    /// Quick Settings state sent from Rust as JSON (see QuickSettings in ffi.rs)
+    private struct QuickSettings: Decodable {
+        let typing: Bool
+        let streaming: Bool
+        let hands_free: Bool
+        let presentation: Bool
+        let rehearsal: Bool
+        let profiles: [String]
+        let active_profile: Int?
+        let languages: [[String]]
+        let language: Int?
+        let models: [String]
+        let model: Int?
+        let presets: [String]
+        let preset: Int?
+        let outputs: [String]
+        let output: Int
+    }
+    
+    /// Rebuild the Quick Settings, Language and Model submenus
+    @objc public func setQuickSettings(_ json: String) {
+        guard let data = json.data(using: .utf8),
+              let settings = try? JSONDecoder().decode(QuickSettings.self, from: data) else { return }
+        DispatchQueue.main.async { [weak self] in
+            self?.quickSettings = settings
+            self?.applyQuickSettings(settings)
+        }
+    }
+    
+    private func applyQuickSettings(_ settings: QuickSettings) {
+        let quick = NSMenu(title: "Quick Settings")
+        quick.addItem(quickItem("Enable Typing", action: "quick:typing", on: settings.typing))
+        quick.addItem(quickItem("Streaming", action: "quick:streaming", on: settings.streaming))
+        quick.addItem(quickItem("Hands-free (tap to start/stop)", action: "quick:hands_free", on: settings.hands_free))
+        quick.addItem(quickItem("Presentation Mode", action: "quick:presentation", on: settings.presentation))
+        quick.addItem(quickItem("Rehearsal (show, don't type)", action: "quick:rehearsal", on: settings.rehearsal))
+        if !settings.profiles.isEmpty {
+            quick.addItem(NSMenuItem.separator())
+            let profiles = NSMenu(title: "Profile")
+            profiles.addItem(quickItem("Automatic (by app)", action: "quick:profile", on: settings.active_profile == nil))
+            for (i, name) in settings.profiles.enumerated() {
+                profiles.addItem(quickItem(name, action: "quick:profile:\(i)", on: settings.active_profile == i))
+            }
+            let profileItem = NSMenuItem(title: "Profile", action: nil, keyEquivalent: "")
+            profileItem.submenu = profiles
+            quick.addItem(profileItem)
+        }
+        quickSettingsItem?.submenu = quick
+        
+        let languages = NSMenu(title: "Language")
+        languages.addItem(quickItem("Auto-detect", action: "quick:language", on: settings.language == nil))
+        languages.addItem(NSMenuItem.separator())
+        for (i, pair) in settings.languages.enumerated() where pair.count == 2 {
+            languages.addItem(quickItem(pair[1], action: "quick:language:\(i)", on: settings.language == i))
+        }
+        var current = "Auto-detect"
+        if let i = settings.language, settings.languages.indices.contains(i), let name = settings.languages[i].last {
+            current = name
+        }
+        languageItem?.title = "Language: \(current)"
+        languageItem?.submenu = languages
+        languageItem?.isEnabled = true
+        
+        let models = NSMenu(title: "Model")
+        for (i, label) in settings.presets.enumerated() {
+            models.addItem(quickItem(label, action: "quick:preset:\(i)", on: settings.preset == i))
+        }
+        if !settings.presets.isEmpty && !settings.models.isEmpty {
+            models.addItem(NSMenuItem.separator())
+        }
+        for (i, label) in settings.models.enumerated() {
+            models.addItem(quickItem(label, action: "quick:model:\(i)", on: settings.model == i))
+        }
+        if let i = settings.preset, settings.presets.indices.contains(i) {
+            modelItem?.title = "Model: \(settings.presets[i])"
+        } else if let i = settings.model, settings.models.indices.contains(i) {
+            modelItem?.title = "Model: \(settings.models[i])"
+        } else {
+            modelItem?.title = "Model"
+        }
+        modelItem?.submenu = models
+        modelItem?.isHidden = settings.models.isEmpty && settings.presets.isEmpty
+        
+        let outputs = NSMenu(title: "Output")
+        for (i, label) in settings.outputs.enumerated() {
+            outputs.addItem(quickItem(label, action: "quick:output:\(i)", on: settings.output == i))
+        }
+        outputs.addItem(NSMenuItem.separator())
+        outputs.addItem(quickItem("Next Output", action: "quick:cycle_output", on: false))
+        if settings.outputs.indices.contains(settings.output) {
+            outputItem?.title = "Output: \(settings.outputs[settings.output])"
+        } else {
+            outputItem?.title = "Output"
+        }
+        outputItem?.submenu = outputs
+        outputItem?.isHidden = settings.outputs.isEmpty
+    }
+    
+    private func quickItem(_ title: String, action: String, on: Bool) -> NSMenuItem {
+        let item = NSMenuItem(title: title, action: #selector(quickSettingChosen(_:)), keyEquivalent: "")
+        item.target = self
+        item.representedObject = action
+        item.state = on ? .on : .off
+        return item
+    }
+    
+    @objc private func quickSettingChosen(_ sender: NSMenuItem) {
+        guard let action = sender.representedObject as? String else { return }
+        postMenuAction(action)
+    }
+    
+    @objc private func generateSupportBundle() {
+        postMenuAction("support_bundle")
+    }
+    
     @objc private func showAbout() {
         let alert = NSAlert()
         alert.messageText = "Typeswift"
@@ -466,6 +748,9 @@ import ServiceManagement
 extension TypeswiftMenuBar {
     @objc public func setRecordingState(_ isRecording: Bool) {
         guard let button = statusItem?.button else { return }
+        if !isRecording {
+            setRecordingLocked(false)
+        }
         if isRecording {
             if let rec = recordingIcon {
                 button.image = rec
@@ -480,6 +765,16 @@ extension TypeswiftMenuBar {
     }
 }
 
+// Lock badge next to the icon while a recording runs without push-to-talk held
+extension TypeswiftMenuBar {
+    @objc public func setRecordingLocked(_ locked: Bool) {
+        guard let button = statusItem?.button else { return }
+        button.imagePosition = .imageLeft
+        button.title = locked ? "🔒" : ""
+        button.toolTip = locked ? "Recording locked — press push-to-talk to stop" : nil
+    }
+}
+
 private extension NSImage {
     func sized(to size: NSSize) -> NSImage {
         self.size = size
@@ -7,6 +7,9 @@ import FluidAudio
     private var isInitialized = false
     private let initializationQueue = DispatchQueue(label: "com.typeswift.initialization")
     private let transcriptionQueue = DispatchQueue(label: "com.typeswift.transcription", attributes: .concurrent)
+    /// Audio handed over in slices by `addSamples`, until `transcribeSessionWithTimings` takes it
+    private var sessionSamples: [Float] = []
+    private let sessionLock = NSLock()
     
     /// Singleton instance for FFI usage
     @objc public static let shared = TypeswiftTranscriber()
@@ -146,6 +149,63 @@ import FluidAudio
         }
     }
     
+    /// Start collecting a recording of about `capacity` samples for `transcribeSessionWithTimings`
+    @objc public func beginSession(capacity: Int) {
+        sessionLock.lock()
+        defer { sessionLock.unlock() }
+        sessionSamples = []
+        sessionSamples.reserveCapacity(capacity)
+    }
+    
+    /// Append a slice of the recording; the caller's buffer is only read during the call
+    @objc public func addSamples(_ samples: UnsafePointer<Float>, count: Int) {
+        sessionLock.lock()
+        defer { sessionLock.unlock() }
+        sessionSamples.append(contentsOf: UnsafeBufferPointer(start: samples, count: count))
+    }
+    
+    /// Transcribe the audio added since `beginSession` and release it, reporting per-token timings as JSON:
+    /// {"text": String, "tokens": [{"token": String, "start": Double, "end": Double, "confidence": Float}]}
+    /// Times are seconds from the start of the recording. Tokens are model subwords ("▁" starts a word).
+    @objc public func transcribeSessionWithTimings() async -> UnsafeMutablePointer<CChar>? {
+        return await timingsJSON(for: takeSession())
+    }
+    
+    private func takeSession() -> [Float] {
+        sessionLock.lock()
+        defer { sessionLock.unlock() }
+        // Hands the storage over rather than copying it
+        let audio = sessionSamples
+        sessionSamples = []
+        return audio
+    }
+    
+    private func timingsJSON(for audioArray: [Float]) async -> UnsafeMutablePointer<CChar>? {
+        guard isInitialized, let asrManager = asrManager else {
+            print("Transcriber not initialized")
+            return nil
+        }
+        
+        do {
+            let result = try await asrManager.transcribe(audioArray, source: .system)
+            let tokens: [[String: Any]] = (result.tokenTimings ?? []).map { timing in
+                [
+                    "token": timing.token,
+                    "start": timing.startTime,
+                    "end": timing.endTime,
+                    "confidence": timing.confidence,
+                ]
+            }
+            let payload: [String: Any] = ["text": result.text, "tokens": tokens]
+            let data = try JSONSerialization.data(withJSONObject: payload)
+            guard let json = String(data: data, encoding: .utf8) else { return nil }
+            return strdup(json)
+        } catch {
+            print("Transcription failed: \(error)")
+            return nil
+        }
+    }
+    
     /// Cleanup resources
     @objc public func cleanup() async {
         if let asrManager = asrManager {
@@ -3,6 +3,7 @@
 
 #include <stdint.h>
 #include <stdbool.h>
+#include <stddef.h>
 
 #ifdef __cplusplus
 extern "C" {
@@ -19,6 +20,20 @@ int32_t typeswift_init(const char* model_path);
 /// @return Transcribed text as C string (caller must free with typeswift_free_string)
 char* typeswift_transcribe(const float* samples, int32_t sample_count);
 
+/// Start handing over a recording in slices, without one full-length copy across the FFI
+/// @param capacity Expected total sample count (16kHz mono)
+void typeswift_session_begin(size_t capacity);
+
+/// Append a slice of the recording; the buffer is only read during the call
+/// @param samples Pointer to float32 audio samples
+/// @param count Number of samples
+void typeswift_session_add_samples(const float* samples, size_t count);
+
+/// Transcribe the handed-over recording with token timings and release it
+/// @return JSON {"text", "tokens": [{"token", "start", "end", "confidence"}]} with times in seconds,
+///         or NULL on failure (caller must free with typeswift_free_string)
+char* typeswift_session_transcribe_with_timings(void);
+
 /// Free a string returned by typeswift_transcribe
 /// @param str String to free
 void typeswift_free_string(char* str);
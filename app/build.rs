@@ -34,6 +34,8 @@ fn main() {
     println!("cargo:rustc-link-lib=framework=CoreML");
     println!("cargo:rustc-link-lib=framework=Accelerate");
     println!("cargo:rustc-link-lib=framework=ApplicationServices");
+    println!("cargo:rustc-link-lib=framework=EventKit");
+    println!("cargo:rustc-link-lib=framework=ScreenCaptureKit");
     
     // Set rpath for finding the dylib at runtime
     if cfg!(target_os = "macos") {
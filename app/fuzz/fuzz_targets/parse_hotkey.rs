@@ -0,0 +1,16 @@
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+use typeswift::input::{canonicalize_hotkey, format_hotkey, parse_hotkey};
+
+fuzz_target!(|data: &[u8]| {
+    let Ok(input) = std::str::from_utf8(data) else { return };
+    // Whatever parses must format, and the formatted form must parse back to the same hotkey
+    if let Ok(hotkey) = parse_hotkey(input) {
+        let formatted = format_hotkey(&hotkey).expect("parsed key has a name");
+        assert_eq!(parse_hotkey(&formatted).unwrap(), hotkey);
+    }
+    if let Ok(canonical) = canonicalize_hotkey(input) {
+        assert_eq!(canonicalize_hotkey(&canonical).unwrap(), canonical);
+    }
+});
@@ -1 +0,0 @@
-// Consolidated in services::audio. This module is kept for backward compatibility if needed.
@@ -0,0 +1,829 @@
+//! Command-line modes that run instead of the menu bar app, e.g. `typeswift stream`.
+//! They log to stderr so stdout stays machine-readable.
+
+use crate::config::Config;
+use crate::controller::{post_process, post_process_partial};
+use crate::services::audio::{chunk_ranges, Transcriber, Transcript, MIN_CHUNK_MS};
+use crate::services::backend::create_backend;
+use crate::services::supervisor;
+use crate::services::wav::{parse_stream_header, resample_linear, PcmFormat};
+use parking_lot::Mutex;
+use serde_json::{json, Value};
+use std::io::{Read, Write};
+use std::sync::Arc;
+use std::time::Duration;
+
+const USAGE: &str = "\
+Usage: typeswift [COMMAND]
+
+Without a command, starts the menu bar app.
+
+Commands:
+  stream            Transcribe audio from stdin, printing events as JSON lines
+  raycast-install   Write Raycast script commands (default ~/.config/raycast/scripts, or --dir DIR)
+  doctor            Check permissions, audio devices, the model, hotkeys and typing; print a report
+  config            Print the commented default config (--print-default) or the one in use (--print-effective)
+  backend-worker    Serve the configured model on --socket PATH (started by the app with [model] isolate),
+                    or to other Macs' remote backend on --listen HOST:PORT
+  help              Show this help
+
+typeswift stream [OPTIONS]
+  --format jsonl               Output format (only jsonl for now)
+  --input auto|wav|pcm         Input container; auto detects a RIFF header (default auto)
+  --encoding s16le|f32le       Raw PCM sample encoding (default s16le)
+  --rate HZ                    Raw PCM sample rate (default 16000)
+  --channels N                 Raw PCM channel count (default 1)
+  --interval-ms MS             Audio between partial results (default [streaming] process_interval_ms)
+  --raw                        Skip post-processing (commands, pipeline)
+
+Events: {\"type\":\"partial\",\"text\",\"audio_ms\"}, {\"type\":\"final\",\"text\",\"raw_text\",\"audio_ms\",\"words\"}
+and {\"type\":\"error\",\"message\"}.
+
+typeswift doctor [--type]
+  --type                       Also type a test line into the focused app, after a countdown
+Exits with 1 if any check fails. Quit the menu bar app first: it holds the hotkeys being tested.
+
+typeswift config --print-default|--print-effective [--set SECTION.KEY=VALUE]...
+  --print-default              Every setting with its default value and a comment on what it does
+  --print-effective            The same, with the values in use: --set values over TYPESWIFT_* environment
+                               overrides over ~/.typeswift/config.toml over the defaults
+  --set SECTION.KEY=VALUE      With --print-effective, show the result of changing a setting, e.g.
+                               --set output.max_chars_per_second=200 (VALUE is read as in TYPESWIFT_*)
+";
+
+/// Run the command in `args` (without the program name). Returns the exit code,
+/// or `None` when there is no command and the app should start.
+pub fn run(args: &[String]) -> Option<i32> {
+    let (command, rest) = args.split_first()?;
+    let code = match command.as_str() {
+        "stream" => {
+            init_stderr_logging();
+            match StreamOptions::parse(rest) {
+                Ok(options) => stream(options),
+                Err(e) => {
+                    eprintln!("typeswift stream: {}\n\n{}", e, USAGE);
+                    2
+                }
+            }
+        }
+        "raycast-install" => raycast_install(rest),
+        "doctor" => {
+            init_stderr_logging();
+            match rest {
+                [] => doctor(false),
+                [flag] if flag == "--type" => doctor(true),
+                _ => {
+                    eprintln!("Usage: typeswift doctor [--type]");
+                    2
+                }
+            }
+        }
+        "config" => match rest {
+            [flag] if flag == "--print-default" => print_config(Config::default()),
+            [flag, settings @ ..] if flag == "--print-effective" => match parse_settings(settings) {
+                Ok(settings) => match Config::load_with(&settings) {
+                    Ok(config) => print_config(config),
+                    Err(e) => {
+                        eprintln!("typeswift config: {}", e);
+                        1
+                    }
+                },
+                Err(e) => {
+                    eprintln!("typeswift config: {}\n\n{}", e, USAGE);
+                    2
+                }
+            },
+            _ => {
+                eprintln!("Usage: typeswift config --print-default|--print-effective [--set SECTION.KEY=VALUE]...");
+                2
+            }
+        },
+        "backend-worker" => {
+            init_stderr_logging();
+            backend_worker(rest)
+        }
+        "help" | "--help" | "-h" => {
+            print!("{}", USAGE);
+            0
+        }
+        // Anything else (e.g. flags macOS passes to apps) starts the app as before
+        _ => return None,
+    };
+    Some(code)
+}
+
+fn init_stderr_logging() {
+    use tracing_subscriber::{fmt, EnvFilter};
+    let filter = EnvFilter::try_from_default_env().unwrap_or_else(|_| EnvFilter::new("warn"));
+    let _ = fmt().with_env_filter(filter).with_writer(std::io::stderr).try_init();
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum InputKind {
+    Auto,
+    Wav,
+    Pcm,
+}
+
+#[derive(Debug, Clone)]
+struct StreamOptions {
+    input: InputKind,
+    pcm: PcmFormat,
+    interval_ms: Option<u64>,
+    post_process: bool,
+}
+
+impl StreamOptions {
+    fn parse(args: &[String]) -> Result<Self, String> {
+        let mut options =
+            Self { input: InputKind::Auto, pcm: PcmFormat::s16le(16000), interval_ms: None, post_process: true };
+        let mut args = args.iter();
+        while let Some(arg) = args.next() {
+            let mut value = |name: &str| args.next().cloned().ok_or_else(|| format!("{} needs a value", name));
+            match arg.as_str() {
+                "--format" => {
+                    let format = value("--format")?;
+                    if format != "jsonl" {
+                        return Err(format!("unsupported format {:?}", format));
+                    }
+                }
+                "--input" => {
+                    options.input = match value("--input")?.as_str() {
+                        "auto" => InputKind::Auto,
+                        "wav" => InputKind::Wav,
+                        "pcm" => InputKind::Pcm,
+                        other => return Err(format!("unknown input {:?}", other)),
+                    }
+                }
+                "--encoding" => {
+                    let rate = options.pcm.sample_rate;
+                    let channels = options.pcm.channels;
+                    options.pcm = match value("--encoding")?.as_str() {
+                        "s16le" => PcmFormat::s16le(rate),
+                        "f32le" => PcmFormat::f32le(rate),
+                        other => return Err(format!("unknown encoding {:?}", other)),
+                    };
+                    options.pcm.channels = channels;
+                }
+                "--rate" => {
+                    options.pcm.sample_rate =
+                        value("--rate")?.parse().ok().filter(|&r| r > 0).ok_or("--rate must be a positive number")?
+                }
+                "--channels" => {
+                    options.pcm.channels =
+                        value("--channels")?.parse().ok().filter(|&c| c > 0).ok_or("--channels must be a positive number")?
+                }
+                "--interval-ms" => {
+                    options.interval_ms = Some(value("--interval-ms")?.parse().map_err(|_| "--interval-ms must be a number")?)
+                }
+                "--raw" => options.post_process = false,
+                other => return Err(format!("unknown option {:?}", other)),
+            }
+        }
+        Ok(options)
+    }
+}
+
+fn emit(event: Value) {
+    let mut stdout = std::io::stdout().lock();
+    let _ = writeln!(stdout, "{}", event);
+    let _ = stdout.flush();
+}
+
+/// Audio read from stdin so far, at the input's own rate
+#[derive(Default)]
+struct StdinAudio {
+    samples: Vec<f32>,
+    sample_rate: u32,
+    finished: bool,
+    error: Option<String>,
+}
+
+/// Decode stdin on a background thread, appending to `audio` as bytes arrive
+fn spawn_stdin_reader(options: &StreamOptions, audio: Arc<Mutex<StdinAudio>>) {
+    let input = options.input;
+    let pcm = options.pcm;
+    std::thread::spawn(move || {
+        let mut stdin = std::io::stdin().lock();
+        let mut pending = Vec::new();
+        let mut format = (input == InputKind::Pcm).then_some(pcm);
+        let mut buf = [0u8; 8192];
+        loop {
+            let n = match stdin.read(&mut buf) {
+                Ok(0) => break,
+                Ok(n) => n,
+                Err(e) if e.kind() == std::io::ErrorKind::Interrupted => continue,
+                Err(e) => {
+                    audio.lock().error = Some(e.to_string());
+                    break;
+                }
+            };
+            pending.extend_from_slice(&buf[..n]);
+            if format.is_none() {
+                if input == InputKind::Auto && pending.len() >= 4 && &pending[..4] != b"RIFF" {
+                    format = Some(pcm);
+                } else {
+                    match parse_stream_header(&pending) {
+                        Ok(Some((wav, data_start))) => {
+                            pending.drain(..data_start);
+                            format = Some(wav);
+                        }
+                        Ok(None) => continue,
+                        Err(e) => {
+                            audio.lock().error = Some(e.to_string());
+                            break;
+                        }
+                    }
+                }
+            }
+            let Some(format) = format else { continue };
+            let whole = pending.len() - pending.len() % format.frame_bytes();
+            match format.decode(&pending[..whole]) {
+                Ok(samples) => {
+                    let mut audio = audio.lock();
+                    audio.sample_rate = format.sample_rate;
+                    audio.samples.extend_from_slice(&samples);
+                }
+                Err(e) => {
+                    audio.lock().error = Some(e.to_string());
+                    break;
+                }
+            }
+            pending.drain(..whole);
+        }
+        audio.lock().finished = true;
+    });
+}
+
+fn words(transcript: &Transcript) -> Value {
+    transcript
+        .tokens
+        .iter()
+        .map(|t| json!({ "word": t.text, "start_ms": t.start_ms, "end_ms": t.end_ms }))
+        .collect()
+}
+
+/// `typeswift backend-worker --socket PATH`: load the configured backend in this process and
+/// serve it to the app that started us (see `services::supervisor`)
+fn backend_worker(args: &[String]) -> i32 {
+    let (flag, target) = match args {
+        [flag, target] if flag == "--socket" || flag == "--listen" => (flag.as_str(), target),
+        _ => {
+            eprintln!("typeswift backend-worker: expected --socket PATH or --listen HOST:PORT");
+            return 2;
+        }
+    };
+    let mut config = Config::load().unwrap_or_default();
+    // We are the isolated process
+    config.model.isolate = false;
+    let backend = match create_backend(&config.model) {
+        Ok(backend) => backend,
+        Err(e) => {
+            eprintln!("{}", e);
+            return 1;
+        }
+    };
+    let served = match flag {
+        "--listen" => supervisor::serve_tcp(target, backend.as_ref(), config.model.remote.token.as_deref()),
+        _ => supervisor::serve(std::path::Path::new(target), backend.as_ref()),
+    };
+    match served {
+        Ok(()) => 0,
+        Err(e) => {
+            eprintln!("{}: {}", target, e);
+            1
+        }
+    }
+}
+
+/// `typeswift stream`: partial results while stdin is open, then one final result
+fn stream(options: StreamOptions) -> i32 {
+    let config = Config::load().unwrap_or_default();
+    let mut transcriber = match Transcriber::new(config.model.clone()) {
+        Ok(transcriber) => transcriber,
+        Err(e) => {
+            emit(json!({ "type": "error", "message": e.to_string() }));
+            return 1;
+        }
+    };
+    transcriber.set_chunk_duration_ms(config.streaming.chunk_duration_ms);
+    let model_rate = transcriber.get_sample_rate();
+    let interval_ms = options.interval_ms.unwrap_or(config.streaming.process_interval_ms).max(100);
+    let min_audio_ms = config.streaming.min_initial_audio_ms;
+
+    let threshold = config.audio.vad.threshold;
+    let segment_ms = config.streaming.chunk_duration_ms;
+
+    let audio = Arc::new(Mutex::new(StdinAudio::default()));
+    spawn_stdin_reader(&options, Arc::clone(&audio));
+
+    // Created once the input's sample rate is known
+    let mut segmenter: Option<Segmenter> = None;
+    let mut committed = Transcript::default();
+    let mut last_partial_ms = 0;
+    let mut last_partial = String::new();
+    loop {
+        std::thread::sleep(Duration::from_millis(50));
+        let (samples, rate, finished, error) = {
+            let mut audio = audio.lock();
+            (std::mem::take(&mut audio.samples), audio.sample_rate, audio.finished, audio.error.clone())
+        };
+        if let Some(message) = error {
+            emit(json!({ "type": "error", "message": message }));
+            return 1;
+        }
+        if rate != 0 {
+            segmenter.get_or_insert_with(|| Segmenter::new(rate, threshold, segment_ms)).push(&samples);
+        }
+
+        // Finished segments are transcribed once and dropped
+        if let Some(segmenter) = segmenter.as_mut() {
+            loop {
+                let ended = if finished { segmenter.finish() } else { segmenter.next_segment() };
+                let Some((start_ms, segment)) = ended else { break };
+                let resampled = resample_linear(&segment, rate, model_rate);
+                match transcriber.transcribe_audio(&resampled) {
+                    Ok(part) => append_transcript(&mut committed, part, start_ms),
+                    Err(e) => {
+                        emit(json!({ "type": "error", "message": e.to_string() }));
+                        return 1;
+                    }
+                }
+            }
+        }
+        let audio_ms = segmenter.as_ref().map_or(0, Segmenter::total_ms);
+
+        if finished {
+            let text = if options.post_process {
+                post_process(&committed.text, &config, None, None)
+            } else {
+                committed.text.clone()
+            };
+            emit(json!({
+                "type": "final",
+                "text": text,
+                "raw_text": committed.text,
+                "audio_ms": audio_ms,
+                "words": words(&committed),
+            }));
+            return 0;
+        }
+
+        let Some(segmenter) = segmenter.as_ref() else { continue };
+        if audio_ms < min_audio_ms || audio_ms < last_partial_ms + interval_ms {
+            continue;
+        }
+        last_partial_ms = audio_ms;
+        // Only the segment still being heard is re-transcribed
+        let partial = match segmenter.pending() {
+            [] => Ok(String::new()),
+            pending => transcriber.transcribe_partial(&resample_linear(pending, rate, model_rate)).map(|t| t.text),
+        };
+        match partial {
+            Ok(partial) => {
+                let raw = [committed.text.as_str(), partial.as_str()]
+                    .into_iter()
+                    .filter(|t| !t.is_empty())
+                    .collect::<Vec<_>>()
+                    .join(" ");
+                if raw.is_empty() {
+                    continue;
+                }
+                let text = if options.post_process { post_process_partial(&raw, &config, None, None) } else { raw };
+                if text != last_partial {
+                    emit(json!({ "type": "partial", "text": text, "audio_ms": audio_ms }));
+                    last_partial = text;
+                }
+            }
+            Err(e) => emit(json!({ "type": "error", "message": e.to_string() })),
+        }
+    }
+}
+
+/// A pause after speech this long ends a stream segment
+const SEGMENT_PAUSE_MS: u64 = 700;
+
+/// Cuts stdin audio into segments that are transcribed once and then dropped, so a long stream
+/// costs neither ever more memory nor ever longer partial passes. A segment ends at a pause
+/// after speech, or at the quietest point before it grows past `max_ms`.
+struct Segmenter {
+    rate: u32,
+    /// RMS level counted as speech (`[audio.vad] threshold`)
+    threshold: f32,
+    max_ms: u64,
+    /// Input-rate samples of the segment still being heard
+    segment: Vec<f32>,
+    /// Samples before `segment`, already handed out
+    offset: u64,
+    heard_speech: bool,
+}
+
+impl Segmenter {
+    fn new(rate: u32, threshold: f32, max_ms: u64) -> Self {
+        Self { rate, threshold, max_ms, segment: Vec::new(), offset: 0, heard_speech: false }
+    }
+
+    fn push(&mut self, samples: &[f32]) {
+        self.heard_speech |= self.has_speech(samples);
+        self.segment.extend_from_slice(samples);
+    }
+
+    /// The segment still being heard
+    fn pending(&self) -> &[f32] {
+        &self.segment
+    }
+
+    /// Everything pushed so far, in ms
+    fn total_ms(&self) -> u64 {
+        (self.offset + self.segment.len() as u64) * 1000 / self.rate as u64
+    }
+
+    /// The next segment that has ended and where it starts (ms), if one has
+    fn next_segment(&mut self) -> Option<(u64, Vec<f32>)> {
+        // Only the first cut matters, so look no further than just past it
+        let max_len = (self.max_ms.max(MIN_CHUNK_MS) * self.rate as u64 / 1000) as usize;
+        let head = &self.segment[..self.segment.len().min(max_len + 1)];
+        let ranges = chunk_ranges(head, self.rate, self.max_ms);
+        if ranges.len() > 1 {
+            return Some(self.split(ranges[0].end));
+        }
+        let pause = (SEGMENT_PAUSE_MS * self.rate as u64 / 1000) as usize;
+        (self.heard_speech && self.trailing_silence() >= pause).then(|| self.split(self.segment.len()))
+    }
+
+    /// At the end of the input: what `next_segment` would hand out, then whatever is left
+    fn finish(&mut self) -> Option<(u64, Vec<f32>)> {
+        self.next_segment().or_else(|| (!self.segment.is_empty()).then(|| self.split(self.segment.len())))
+    }
+
+    fn split(&mut self, end: usize) -> (u64, Vec<f32>) {
+        let rest = self.segment.split_off(end);
+        let segment = std::mem::replace(&mut self.segment, rest);
+        let start_ms = self.offset * 1000 / self.rate as u64;
+        self.offset += segment.len() as u64;
+        self.heard_speech = self.has_speech(&self.segment);
+        (start_ms, segment)
+    }
+
+    fn frame(&self) -> usize {
+        (self.rate as usize / 50).max(1)
+    }
+
+    fn has_speech(&self, samples: &[f32]) -> bool {
+        samples.chunks(self.frame()).any(|frame| rms(frame) > self.threshold)
+    }
+
+    /// Samples at the end of the segment, in whole 20 ms frames, below the speech level
+    fn trailing_silence(&self) -> usize {
+        let frame = self.frame();
+        self.segment.rchunks_exact(frame).take_while(|f| rms(f) <= self.threshold).count() * frame
+    }
+}
+
+fn rms(samples: &[f32]) -> f32 {
+    (samples.iter().map(|s| s * s).sum::<f32>() / samples.len().max(1) as f32).sqrt()
+}
+
+/// Add `part`, which starts `offset_ms` into the stream, to `transcript`
+fn append_transcript(transcript: &mut Transcript, part: Transcript, offset_ms: u64) {
+    if !part.text.is_empty() {
+        if !transcript.text.is_empty() {
+            transcript.text.push(' ');
+        }
+        transcript.text.push_str(&part.text);
+    }
+    transcript.tokens.extend(part.tokens.into_iter().map(|mut token| {
+        token.start_ms += offset_ms;
+        token.end_ms += offset_ms;
+        token
+    }));
+    transcript.duration_ms = offset_ms + part.duration_ms;
+}
+
+/// (file name, title, description, URL action, bring Typeswift to the front)
+const RAYCAST_SCRIPTS: &[(&str, &str, &str, &str, bool)] = &[
+    (
+        "typeswift-dictate-to-clipboard.sh",
+        "Dictate to Clipboard",
+        "Start dictating; run again to stop and copy the text",
+        "dictate-to-clipboard",
+        false,
+    ),
+    ("typeswift-toggle-hands-free.sh", "Toggle Hands-Free", "Tap to talk instead of hold", "toggle-hands-free", false),
+    ("typeswift-toggle-typing.sh", "Toggle Typing", "Turn simulated typing on or off", "toggle-typing", false),
+    ("typeswift-stop-typing.sh", "Stop Typing", "Stop typing and copy the rest", "stop-typing", false),
+    ("typeswift-speak-last.sh", "Speak Last Result", "Read the last dictation aloud", "speak-last", false),
+    ("typeswift-show-history.sh", "Show History", "Open dictation history", "show-history", true),
+    ("typeswift-preferences.sh", "Open Preferences", "Open Typeswift Preferences", "preferences", true),
+];
+
+fn raycast_script(title: &str, description: &str, action: &str, foreground: bool) -> String {
+    // -g opens the link without activating Typeswift, so focus stays in the current app
+    let open = if foreground { "open" } else { "open -g" };
+    format!(
+        "#!/bin/bash\n\n\
+         # Required parameters:\n\
+         # @raycast.schemaVersion 1\n\
+         # @raycast.title {title}\n\
+         # @raycast.mode silent\n\n\
+         # Optional parameters:\n\
+         # @raycast.icon 🎙️\n\
+         # @raycast.packageName Typeswift\n\
+         # @raycast.description {description}\n\n\
+         {open} \"typeswift://{action}\"\n"
+    )
+}
+
+/// `typeswift raycast-install [--dir DIR]`: write script commands that drive the
+/// running app through `typeswift://` links
+fn raycast_install(args: &[String]) -> i32 {
+    use std::os::unix::fs::PermissionsExt;
+    let dir = match args {
+        [] => match std::env::var("HOME") {
+            Ok(home) => std::path::PathBuf::from(home).join(".config").join("raycast").join("scripts"),
+            Err(_) => {
+                eprintln!("typeswift raycast-install: HOME is not set; pass --dir DIR");
+                return 1;
+            }
+        },
+        [flag, dir] if flag == "--dir" => std::path::PathBuf::from(dir),
+        _ => {
+            eprintln!("Usage: typeswift raycast-install [--dir DIR]");
+            return 2;
+        }
+    };
+    if let Err(e) = std::fs::create_dir_all(&dir) {
+        eprintln!("typeswift raycast-install: {}: {}", dir.display(), e);
+        return 1;
+    }
+    for (file, title, description, action, foreground) in RAYCAST_SCRIPTS {
+        let path = dir.join(file);
+        let result = std::fs::write(&path, raycast_script(title, description, action, *foreground))
+            .and_then(|_| std::fs::set_permissions(&path, std::fs::Permissions::from_mode(0o755)));
+        if let Err(e) = result {
+            eprintln!("typeswift raycast-install: {}: {}", path.display(), e);
+            return 1;
+        }
+        println!("Wrote {}", path.display());
+    }
+    println!(
+        "\nIn Raycast, open Settings → Extensions → Script Commands → Add Directories and choose\n{}",
+        dir.display()
+    );
+    0
+}
+
+/// Pass/fail lines of `typeswift doctor`, under a heading per area
+#[derive(Default)]
+struct Report {
+    passed: usize,
+    failed: usize,
+}
+
+impl Report {
+    fn section(&self, title: &str) {
+        println!("\n{}", title);
+    }
+
+    fn check(&mut self, ok: bool, what: &str, detail: &str) {
+        if ok {
+            self.passed += 1;
+        } else {
+            self.failed += 1;
+        }
+        println!("  {}  {}: {}", if ok { "PASS" } else { "FAIL" }, what, detail);
+    }
+
+    fn info(&self, what: &str, detail: &str) {
+        println!("        {}: {}", what, detail);
+    }
+}
+
+/// `typeswift config`: print `config` as a commented config file
+/// `--set section.key=value` arguments as (key path, raw value)
+fn parse_settings(args: &[String]) -> Result<Vec<(Vec<String>, String)>, String> {
+    let mut settings = Vec::new();
+    let mut args = args.iter();
+    while let Some(arg) = args.next() {
+        let setting = match arg.strip_prefix("--set=") {
+            Some(setting) => setting,
+            None if arg == "--set" => args.next().map(String::as_str).ok_or("--set needs SECTION.KEY=VALUE")?,
+            None => return Err(format!("unknown option {}", arg)),
+        };
+        let (key, value) = setting.split_once('=').ok_or_else(|| format!("--set {}: expected KEY=VALUE", setting))?;
+        let path: Vec<String> = key.trim().split('.').map(str::to_string).collect();
+        if path.iter().any(String::is_empty) {
+            return Err(format!("--set {}: not a setting name", setting));
+        }
+        settings.push((path, value.to_string()));
+    }
+    Ok(settings)
+}
+
+fn print_config(config: Config) -> i32 {
+    match config.to_commented_toml() {
+        Ok(toml) => {
+            print!("{}", toml);
+            0
+        }
+        Err(e) => {
+            eprintln!("typeswift config: {}", e);
+            1
+        }
+    }
+}
+
+/// `typeswift doctor`: try each thing dictation depends on and print what works
+fn doctor(type_test: bool) -> i32 {
+    use crate::config::TranscriberBackend;
+    use crate::platform::macos::ffi as menubar_ffi;
+
+    let config = match Config::load() {
+        Ok(config) => config,
+        Err(e) => {
+            println!("Config: {} (checking with defaults)", e);
+            Config::default()
+        }
+    };
+    let mut report = Report::default();
+    println!("Typeswift {} doctor", env!("CARGO_PKG_VERSION"));
+
+    report.section("Permissions");
+    let trusted = menubar_ffi::accessibility_trusted();
+    report.check(trusted, "Accessibility", if trusted { "granted" } else { "not granted; needed to type and for Fn" });
+    let microphone = menubar_ffi::microphone_authorization();
+    // Not asked yet is fine: macOS asks on the first recording
+    report.check(!matches!(microphone, "denied" | "restricted"), "Microphone", microphone);
+
+    report.section("Audio devices");
+    let devices = crate::services::audio::input_devices();
+    report.check(!devices.is_empty(), "Input devices", &format!("{} found", devices.len()));
+    for (i, (device, channels)) in devices.iter().enumerate() {
+        let mut detail = format!("{} Hz, {} channel(s)", device.sample_rate, channels);
+        if i == 0 {
+            detail.push_str(", default");
+        }
+        if device.is_low_quality_headset() {
+            detail.push_str(", Bluetooth headset profile (lower accuracy)");
+        }
+        report.info(&device.name, &detail);
+    }
+
+    report.section("Model");
+    match crate::services::models::resolve_preset(&config.model) {
+        Err(e) => report.check(false, "Model files", &e.to_string()),
+        Ok(model) => {
+            match model.backend {
+                TranscriberBackend::Whisper => match model.whisper.model_path {
+                    Some(ref path) => {
+                        let size = std::fs::metadata(path).ok().filter(|m| m.is_file()).map(|m| m.len());
+                        let detail = match size {
+                            Some(bytes) => format!("{} ({} MB)", path.display(), bytes / 1_000_000),
+                            None => format!("{} not found", path.display()),
+                        };
+                        report.check(size.is_some(), "Model file", &detail);
+                    }
+                    None => report.check(false, "Model file", "set [model.whisper] model_path"),
+                },
+                TranscriberBackend::Swift if model.model_name.starts_with('/') => {
+                    let path = std::path::Path::new(&model.model_name);
+                    report.check(path.exists(), "Model folder", &path.display().to_string());
+                }
+                TranscriberBackend::Swift | TranscriberBackend::Mlx => {
+                    report.info("Model", &format!("{} (downloaded on first load)", model.model_name));
+                }
+                TranscriberBackend::Mock => report.info("Model", "mock backend; no model needed"),
+                TranscriberBackend::Remote => report.info("Model", &format!("served by {}", model.remote.address)),
+            }
+            let started = std::time::Instant::now();
+            match Transcriber::new(model) {
+                Ok(transcriber) => report.check(
+                    true,
+                    "Backend load",
+                    &format!("{} in {:.1} s", transcriber.description(), started.elapsed().as_secs_f32()),
+                ),
+                Err(e) => report.check(false, "Backend load", &e.to_string()),
+            }
+        }
+    }
+
+    report.section("Hotkeys");
+    match crate::input::HotkeyHandler::new() {
+        Err(e) => report.check(false, "Hotkey manager", &e.to_string()),
+        Ok(mut hotkeys) => {
+            hotkeys.set_profile_bindings(&config.profiles);
+            match hotkeys.apply_hotkeys(&config.hotkeys) {
+                Ok(()) => {
+                    let push_to_talk = &config.hotkeys.push_to_talk;
+                    let detail = if crate::input::parse_modifier_chord(push_to_talk).is_some() {
+                        format!("push-to-talk {} (watched by the keyboard monitor)", push_to_talk)
+                    } else {
+                        format!("push-to-talk {}", push_to_talk)
+                    };
+                    report.check(true, "Registration", &detail);
+                }
+                Err(conflict) => report.check(false, "Registration", &conflict.user_message().replace("\n\n", " ")),
+            }
+        }
+    }
+
+    report.section("Typing");
+    let text = type_test.then_some("Hello from Typeswift doctor");
+    if text.is_some() {
+        eprintln!("Switch to a text editor; typing starts in 5 seconds...");
+        for i in (1..=5).rev() {
+            eprintln!("{}...", i);
+            std::thread::sleep(Duration::from_secs(1));
+        }
+    }
+    match crate::output::run_typing_diagnostic(text) {
+        Ok(done) => report.check(true, "Key events", &done),
+        Err(e) => report.check(false, "Key events", &e),
+    }
+
+    println!("\n{} passed, {} failed", report.passed, report.failed);
+    if report.failed > 0 {
+        1
+    } else {
+        0
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn args(args: &[&str]) -> Vec<String> {
+        args.iter().map(|a| a.to_string()).collect()
+    }
+
+    #[test]
+    fn set_arguments() {
+        let settings = parse_settings(&args(&["--set", "output.enable_typing=false", "--set=model.mock.delay_ms=5"]));
+        assert_eq!(
+            settings.unwrap(),
+            vec![
+                (args(&["output", "enable_typing"]), "false".to_string()),
+                (args(&["model", "mock", "delay_ms"]), "5".to_string()),
+            ]
+        );
+        for bad in [&["--set"][..], &["--set", "output.enable_typing"], &["--set", "output..x=1"], &["--raw"]] {
+            assert!(parse_settings(&args(bad)).is_err(), "{:?} accepted", bad);
+        }
+    }
+
+    fn tone(ms: usize, level: f32) -> Vec<f32> {
+        (0..ms * 16).map(|i| if i % 2 == 0 { level } else { -level }).collect()
+    }
+
+    #[test]
+    fn segment_ends_at_a_pause_after_speech() {
+        let mut segmenter = Segmenter::new(16000, 0.01, 30_000);
+        segmenter.push(&tone(500, 0.0));
+        segmenter.push(&tone(1000, 0.2));
+        segmenter.push(&tone(500, 0.0));
+        assert!(segmenter.next_segment().is_none(), "pause too short");
+
+        segmenter.push(&tone(300, 0.0));
+        let (start_ms, segment) = segmenter.next_segment().unwrap();
+        assert_eq!((start_ms, segment.len()), (0, 2300 * 16));
+        assert!(segmenter.pending().is_empty());
+
+        // Silence alone never ends a segment before the length cap
+        segmenter.push(&tone(2000, 0.0));
+        assert!(segmenter.next_segment().is_none());
+        assert_eq!(segmenter.total_ms(), 4300);
+    }
+
+    #[test]
+    fn long_speech_is_cut_at_the_cap() {
+        let mut segmenter = Segmenter::new(16000, 0.01, 10_000);
+        let mut speech = tone(20_000, 0.2);
+        // A quiet moment shortly before the cap, where the cut should fall
+        speech[8_000 * 16..8_020 * 16].fill(0.0);
+        segmenter.push(&speech);
+
+        let (start_ms, first) = segmenter.next_segment().unwrap();
+        assert_eq!(start_ms, 0);
+        assert!((8_000 * 16..=8_020 * 16).contains(&first.len()), "cut at {}", first.len());
+        let (start_ms, second) = segmenter.next_segment().unwrap();
+        assert_eq!(start_ms, first.len() as u64 / 16);
+        assert!(second.len() <= 10_000 * 16);
+        assert!(segmenter.next_segment().is_none());
+
+        let (_, rest) = segmenter.finish().unwrap();
+        assert_eq!(first.len() + second.len() + rest.len(), speech.len());
+        assert!(segmenter.finish().is_none());
+    }
+
+    #[test]
+    fn appended_segments_keep_stream_timings() {
+        let mut transcript = Transcript::default();
+        append_transcript(&mut transcript, Transcript::with_even_timings("hello there".into(), 1000), 0);
+        append_transcript(&mut transcript, Transcript::default(), 1000);
+        append_transcript(&mut transcript, Transcript::with_even_timings("world".into(), 500), 3000);
+        assert_eq!(transcript.text, "hello there world");
+        assert_eq!(transcript.duration_ms, 3500);
+        assert_eq!((transcript.tokens[2].start_ms, transcript.tokens[2].end_ms), (3000, 3500));
+    }
+}
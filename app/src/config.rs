@@ -1,7 +1,11 @@
 use anyhow::Result;
 use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
 use std::path::PathBuf;
 
+/// Every setting with its default and what it does; the values are filled in from `Config`
+const COMMENTED_DEFAULTS: &str = include_str!("../default-config.toml");
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Config {
     pub audio: AudioConfig,
@@ -9,18 +13,782 @@ pub struct Config {
     pub ui: UiConfig,
     pub output: OutputConfig,
     pub hotkeys: HotkeyConfig,
+    #[serde(default)]
+    pub commands: CommandsConfig,
+    #[serde(default)]
+    pub history: HistoryConfig,
+    #[serde(default)]
+    pub streaming: StreamingConfig,
+    #[serde(default)]
+    pub focus: FocusConfig,
+    #[serde(default)]
+    pub pipeline: PipelineConfig,
+    #[serde(default)]
+    pub mcp: McpConfig,
+    #[serde(default)]
+    pub ipc: IpcConfig,
+    #[serde(default)]
+    pub meetings: MeetingsConfig,
+    /// Profile used when no profile matches the frontmost app
+    #[serde(default)]
+    pub active_profile: Option<String>,
+    #[serde(default)]
+    pub profiles: Vec<Profile>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct HistoryConfig {
+    /// Keep finished dictations in ~/.typeswift/history.jsonl
+    #[serde(default = "default_true")]
+    pub enabled: bool,
+    #[serde(default)]
+    pub retention: HistoryRetention,
+    /// Age limit when `retention = "days"`
+    #[serde(default = "default_keep_days")]
+    pub keep_days: u32,
+    /// Count limit when `retention = "entries"`
+    #[serde(default = "default_keep_entries")]
+    pub keep_entries: usize,
+    /// Encrypt each entry with a key stored in the login keychain
+    #[serde(default)]
+    pub encrypt: bool,
+    /// Also keep each dictation's audio (AAC, ~/.typeswift/recordings) for playback in History.
+    /// Recordings follow the retention of their entries; not kept when `encrypt` is on.
+    #[serde(default)]
+    pub keep_audio: bool,
+    /// Codec for kept audio
+    #[serde(default)]
+    pub audio_format: AudioCodec,
+    /// Dictations into the same app at most this many seconds apart form one session
+    #[serde(default = "default_session_gap_secs")]
+    pub session_gap_secs: u64,
+    /// Session exports also quote what the model heard under each dictation formatting changed
+    #[serde(default)]
+    pub export_raw_text: bool,
+    /// Offer to add a word swap made while editing entries to the replacements after it's been
+    /// made this many times; 0 turns the offer off
+    #[serde(default = "default_suggest_replacement_after")]
+    pub suggest_replacement_after: u32,
+}
+
+impl Default for HistoryConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            retention: HistoryRetention::default(),
+            keep_days: default_keep_days(),
+            keep_entries: default_keep_entries(),
+            encrypt: false,
+            keep_audio: false,
+            audio_format: AudioCodec::default(),
+            session_gap_secs: default_session_gap_secs(),
+            export_raw_text: false,
+            suggest_replacement_after: default_suggest_replacement_after(),
+        }
+    }
+}
+
+/// Compressed audio formats (see `services::encode`)
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum AudioCodec {
+    /// Lossy, plays everywhere
+    #[default]
+    Aac,
+    /// Lossy, smallest at speech bitrates
+    Opus,
+    /// Lossless, about half the size of WAV
+    Flac,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum HistoryRetention {
+    #[default]
+    Forever,
+    Days,
+    Entries,
+    /// Don't store dictations (and erase any stored history)
+    Never,
+}
+
+fn default_keep_days() -> u32 {
+    30
+}
+
+fn default_keep_entries() -> usize {
+    1000
+}
+
+fn default_session_gap_secs() -> u64 {
+    120
+}
+
+fn default_suggest_replacement_after() -> u32 {
+    3
+}
+
+fn default_debounce_ms() -> u64 {
+    40
+}
+
+fn default_tap_lock_ms() -> u64 {
+    300
+}
+
+fn default_lock_timeout_minutes() -> u64 {
+    30
+}
+
+/// Type partial transcripts while recording, correcting them as the model revises
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct StreamingConfig {
+    #[serde(default)]
+    pub enabled: bool,
+    /// How often the recording so far is re-transcribed
+    #[serde(default = "default_process_interval_ms")]
+    pub process_interval_ms: u64,
+    /// Audio needed before the first partial is typed
+    #[serde(default = "default_min_initial_audio_ms")]
+    pub min_initial_audio_ms: u64,
+    /// Furthest back (in characters) a revision may backspace; older mistakes are left for you
+    #[serde(default = "default_max_correction_distance")]
+    pub max_correction_distance: usize,
+    /// Only type words that ended at least this long ago; 0 types partials as soon as they arrive
+    #[serde(default = "default_stabilization_ms")]
+    pub stabilization_ms: u64,
+    /// Longest piece of a recording transcribed in one call; longer ones are split (at least 10 s)
+    #[serde(default = "default_chunk_duration_ms")]
+    pub chunk_duration_ms: u64,
+}
+
+impl Default for StreamingConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            process_interval_ms: default_process_interval_ms(),
+            min_initial_audio_ms: default_min_initial_audio_ms(),
+            max_correction_distance: default_max_correction_distance(),
+            stabilization_ms: default_stabilization_ms(),
+            chunk_duration_ms: default_chunk_duration_ms(),
+        }
+    }
+}
+
+fn default_process_interval_ms() -> u64 {
+    700
+}
+
+fn default_min_initial_audio_ms() -> u64 {
+    1000
+}
+
+fn default_chunk_duration_ms() -> u64 {
+    30_000
+}
+
+fn default_max_correction_distance() -> usize {
+    40
+}
+
+fn default_stabilization_ms() -> u64 {
+    1500
+}
+
+/// Spoken commands recognised in final transcripts
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CommandsConfig {
+    #[serde(default = "default_true")]
+    pub enabled: bool,
+    /// Leading phrases that switch the utterance into letter-by-letter spelling
+    #[serde(default = "default_spell_prefixes")]
+    pub spell_prefixes: Vec<String>,
+    /// Emit spelled letters in upper case (license plates, codes)
+    #[serde(default)]
+    pub spell_uppercase: bool,
+    /// Utterances that control the Mac instead of being typed: "switch to Safari",
+    /// "press command shift t", "minimize window", "quit Notes"
+    #[serde(default)]
+    pub system: bool,
+    /// Ask before a system command that can lose work (closing a window, quitting, Cmd+Q/W)
+    #[serde(default = "default_true")]
+    pub confirm_destructive: bool,
+}
+
+impl Default for CommandsConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            spell_prefixes: default_spell_prefixes(),
+            spell_uppercase: false,
+            system: false,
+            confirm_destructive: true,
+        }
+    }
+}
+
+fn default_spell_prefixes() -> Vec<String> {
+    vec!["spell that".to_string(), "spell it".to_string(), "spell".to_string()]
+}
+
+/// Behaviour while a macOS Focus mode is on
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct FocusConfig {
+    /// Keep dictating but don't show the popup or notifications
+    #[serde(default)]
+    pub quiet: bool,
+    /// Focus mode names (e.g. "Work") that pause push-to-talk entirely; "*" matches any Focus
+    #[serde(default)]
+    pub pause_modes: Vec<String>,
+}
+
+impl FocusConfig {
+    pub fn is_enabled(&self) -> bool {
+        self.quiet || !self.pause_modes.is_empty()
+    }
+
+    /// Whether `mode` (None when the name can't be read) should pause push-to-talk
+    pub fn pauses(&self, mode: Option<&str>) -> bool {
+        self.pause_modes
+            .iter()
+            .any(|m| m == "*" || mode.is_some_and(|name| m.eq_ignore_ascii_case(name)))
+    }
+}
+
+/// Post-processing applied to each final transcript (after spoken commands), in order
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PipelineConfig {
+    #[serde(default = "default_pipeline_stages")]
+    pub stages: Vec<StageConfig>,
+}
+
+impl Default for PipelineConfig {
+    fn default() -> Self {
+        Self { stages: default_pipeline_stages() }
+    }
+}
+
+fn default_pipeline_stages() -> Vec<StageConfig> {
+    vec![StageConfig::Vocabulary, StageConfig::Format]
+}
+
+/// One post-processing stage; `type` selects the kind
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum StageConfig {
+    /// Restore the spelling of bias phrases (the profile's, else `model.bias_phrases`)
+    Vocabulary,
+    /// Quote, dash and ellipsis style (the profile's, else `output.format`)
+    Format,
+    /// Whole-word, case-insensitive find and replace, applied in order
+    Replacements { rules: Vec<Replacement> },
+    /// Drop hesitation words ("um", "uh")
+    FillerRemoval {
+        #[serde(default = "default_filler_words")]
+        words: Vec<String>,
+    },
+    /// Pipe the text through a program: text on stdin, result on stdout
+    Command {
+        command: Vec<String>,
+        #[serde(default = "default_stage_timeout_ms")]
+        timeout_ms: u64,
+    },
+    /// Like `command`, with `prompt` sent ahead of the text, for local LLM CLIs (e.g. `ollama run`)
+    Llm {
+        command: Vec<String>,
+        prompt: String,
+        #[serde(default = "default_llm_timeout_ms")]
+        timeout_ms: u64,
+    },
+    /// A dynamic library exporting `typeswift_process` (see README)
+    Dylib { path: PathBuf },
+    /// A Rhai script evaluated per utterance (requires the `scripting` feature)
+    Script { path: PathBuf },
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Replacement {
+    pub from: String,
+    pub to: String,
+}
+
+fn default_filler_words() -> Vec<String> {
+    ["um", "uh", "erm", "uhm", "hmm"].iter().map(|w| w.to_string()).collect()
+}
+
+fn default_stage_timeout_ms() -> u64 {
+    2000
+}
+
+fn default_llm_timeout_ms() -> u64 {
+    15000
+}
+
+/// Model Context Protocol server for agents and editors, on localhost only
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct McpConfig {
+    #[serde(default)]
+    pub enabled: bool,
+    #[serde(default = "default_mcp_port")]
+    pub port: u16,
+}
+
+impl Default for McpConfig {
+    fn default() -> Self {
+        Self { enabled: false, port: default_mcp_port() }
+    }
+}
+
+fn default_mcp_port() -> u16 {
+    7723
+}
+
+/// Unix-socket API for editor plugins (schema in `proto/ipc.proto`)
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct IpcConfig {
+    #[serde(default)]
+    pub enabled: bool,
+    /// Defaults to ~/.typeswift/typeswift.sock
+    #[serde(default)]
+    pub socket: Option<PathBuf>,
+}
+
+impl IpcConfig {
+    pub fn socket_path(&self) -> Option<PathBuf> {
+        self.socket.clone().or_else(|| {
+            std::env::var("HOME")
+                .ok()
+                .map(|home| PathBuf::from(home).join(".typeswift").join("typeswift.sock"))
+        })
+    }
+}
+
+/// Meeting transcription: calendar prompts, call detection and where transcripts go
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct MeetingsConfig {
+    /// Watch the calendar (EventKit) and prompt at each meeting's start time
+    #[serde(default)]
+    pub calendar_prompt: bool,
+    /// Offer "Transcribe … Call" in the menu bar while Zoom, Meet, Teams etc. are in a call
+    #[serde(default = "default_true")]
+    pub suggest_calls: bool,
+    /// Mix in what the Mac plays, so the other side of the call is transcribed too
+    /// (needs Screen Recording permission)
+    #[serde(default = "default_true")]
+    pub system_audio: bool,
+    /// Take the speakers' sound out of the microphone before mixing in system audio, so the
+    /// other side isn't transcribed twice when not using headphones
+    #[serde(default = "default_true")]
+    pub echo_cancellation: bool,
+    /// With system audio, start each line of the transcript with "Me:" or "Others:" by which
+    /// source was louder while it was said
+    #[serde(default = "default_true")]
+    pub label_speakers: bool,
+    /// Where transcripts are saved; defaults to ~/.typeswift/meetings
+    #[serde(default)]
+    pub folder: Option<PathBuf>,
+}
+
+impl Default for MeetingsConfig {
+    fn default() -> Self {
+        Self {
+            calendar_prompt: false,
+            suggest_calls: true,
+            system_audio: true,
+            echo_cancellation: true,
+            label_speakers: true,
+            folder: None,
+        }
+    }
+}
+
+impl MeetingsConfig {
+    pub fn folder_path(&self) -> Option<PathBuf> {
+        self.folder.clone().or_else(|| {
+            std::env::var("HOME")
+                .ok()
+                .map(|home| PathBuf::from(home).join(".typeswift").join("meetings"))
+        })
+    }
+}
+
+/// Named overrides, selected by frontmost app or `active_profile`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Profile {
+    pub name: String,
+    /// Bundle identifiers that select this profile automatically
+    #[serde(default)]
+    pub apps: Vec<String>,
+    #[serde(default)]
+    pub format: Option<FormatOptions>,
+    /// Replaces `model.bias_phrases` while this profile is active
+    #[serde(default)]
+    pub bias_phrases: Option<Vec<String>>,
+    /// Send dictations here instead of typing them
+    #[serde(default)]
+    pub sink: Option<SinkConfig>,
+    /// Replaces `model.language` while this profile is active
+    #[serde(default)]
+    pub language: Option<String>,
+    /// Push-to-talk binding of this profile's own (same syntax as `hotkeys.push_to_talk`):
+    /// dictations started with it use this profile whatever app is in front
+    #[serde(default)]
+    pub push_to_talk: Option<String>,
+    /// Shapes each dictation before it's typed or sent to the sink, e.g. "- [ ] {text}";
+    /// `{text}`, `{timestamp}`, `{date}`, `{time}` and `{app}` are filled in
+    #[serde(default)]
+    pub template: Option<String>,
+}
+
+/// A destination for finished dictations other than the cursor
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum SinkConfig {
+    /// Append to a note in an Obsidian vault
+    Obsidian(ObsidianSink),
+    /// Append to a plain text or Markdown file
+    File(FileSink),
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct FileSink {
+    /// Absolute path; created on the first dictation if it doesn't exist
+    pub path: PathBuf,
+    /// One line per dictation; `{text}`, `{time}`, `{date}` and `{app}` are filled in
+    #[serde(default = "default_file_entry")]
+    pub entry: String,
+}
+
+fn default_file_entry() -> String {
+    "- {text}".to_string()
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ObsidianSink {
+    /// Vault folder (absolute path); its name is the vault name for `method = "url"`
+    pub vault: PathBuf,
+    /// Note path inside the vault; `{date}` gives one note per day
+    #[serde(default = "default_obsidian_note")]
+    pub note: String,
+    /// Line added before the first entry of each day (empty = none)
+    #[serde(default = "default_obsidian_header")]
+    pub header: String,
+    /// One line per dictation; `{text}`, `{time}`, `{date}` and `{app}` are filled in
+    #[serde(default = "default_obsidian_entry")]
+    pub entry: String,
+    #[serde(default)]
+    pub method: ObsidianMethod,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ObsidianMethod {
+    /// Append to the Markdown file directly; Obsidian picks up the change
+    #[default]
+    File,
+    /// Hand the text to Obsidian with an obsidian:// link (Obsidian must be installed)
+    Url,
+}
+
+fn default_obsidian_note() -> String {
+    "Dictation/{date}.md".to_string()
+}
+
+fn default_obsidian_header() -> String {
+    "## {date}".to_string()
+}
+
+fn default_obsidian_entry() -> String {
+    "- {time} {text}".to_string()
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct AudioConfig {
     pub target_sample_rate: u32,
+    #[serde(default)]
+    pub vad: VadConfig,
+    /// Input devices the Bluetooth quality warning was dismissed for
+    #[serde(default)]
+    pub suppressed_device_warnings: Vec<String>,
+    /// Software gain applied to captured audio (1.0 = unchanged)
+    #[serde(default = "default_input_gain")]
+    pub input_gain: f32,
+}
+
+fn default_input_gain() -> f32 {
+    1.0
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct VadConfig {
+    #[serde(default)]
+    pub engine: VadEngine,
+    /// RMS level counted as speech by the legacy detector
+    #[serde(default = "default_vad_threshold")]
+    pub threshold: f32,
+    /// Path to the Silero VAD ONNX model (requires the `silero-vad` feature)
+    #[serde(default)]
+    pub silero_model: Option<PathBuf>,
+    #[serde(default = "default_silero_threshold")]
+    pub silero_threshold: f32,
+    /// Drop leading/trailing silence before transcription
+    #[serde(default)]
+    pub trim_silence: bool,
+    /// Raise the RMS threshold in noisy rooms: measured for a moment when hands-free mode
+    /// starts, then followed from the quiet parts of each recording
+    #[serde(default)]
+    pub adaptive: bool,
+    /// With `adaptive`, speech must be this many times louder than the background
+    #[serde(default = "default_noise_margin")]
+    pub noise_margin: f32,
+}
+
+impl Default for VadConfig {
+    fn default() -> Self {
+        Self {
+            engine: VadEngine::Rms,
+            threshold: default_vad_threshold(),
+            silero_model: None,
+            silero_threshold: default_silero_threshold(),
+            trim_silence: false,
+            adaptive: false,
+            noise_margin: default_noise_margin(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum VadEngine {
+    /// Energy threshold
+    #[default]
+    Rms,
+    /// Silero neural VAD (ONNX)
+    Silero,
+}
+
+fn default_vad_threshold() -> f32 {
+    0.01
+}
+
+fn default_noise_margin() -> f32 {
+    3.0
+}
+
+fn default_silero_threshold() -> f32 {
+    0.5
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct ModelConfig {
     pub model_name: String,
+    /// Pick the model by speed and accuracy instead: `model_name` (or the whisper model path)
+    /// is then chosen for the backend when the model loads
+    #[serde(default)]
+    pub preset: Option<ModelPreset>,
     pub left_context_seconds: usize,
     pub right_context_seconds: usize,
+    #[serde(default)]
+    pub backend: TranscriberBackend,
+    #[serde(default)]
+    pub mock: MockBackendConfig,
+    #[serde(default)]
+    pub whisper: WhisperBackendConfig,
+    #[serde(default)]
+    pub mlx: MlxBackendConfig,
+    #[serde(default)]
+    pub remote: RemoteBackendConfig,
+    /// Run the backend in a supervised child process (`typeswift backend-worker`) that is
+    /// restarted if it crashes; the mlx backend always runs this way
+    #[serde(default)]
+    pub isolate: bool,
+    /// Pass recently typed words to backends that accept a prompt; can bias output
+    #[serde(default)]
+    pub context_continuation: bool,
+    #[serde(default = "default_context_words")]
+    pub context_words: usize,
+    /// Names, jargon and product terms to favour in recognition
+    #[serde(default)]
+    pub bias_phrases: Vec<String>,
+    /// Spoken language code (e.g. "de"); `None` auto-detects. Passed to backends that take a hint
+    #[serde(default)]
+    pub language: Option<String>,
+    /// A dictation stuck in Processing this long without progress is given up on and the app
+    /// returns to Idle; 0 = never
+    #[serde(default = "default_processing_timeout_secs")]
+    pub processing_timeout_secs: u64,
+}
+
+/// How a model preset trades speed against accuracy; see `services::models::resolve_preset`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ModelPreset {
+    Fast,
+    Balanced,
+    Accurate,
+}
+
+impl ModelPreset {
+    pub const ALL: [ModelPreset; 3] = [ModelPreset::Fast, ModelPreset::Balanced, ModelPreset::Accurate];
+
+    pub fn label(self) -> &'static str {
+        match self {
+            ModelPreset::Fast => "Fast",
+            ModelPreset::Balanced => "Balanced",
+            ModelPreset::Accurate => "Accurate",
+        }
+    }
+}
+
+/// Parakeet TDT v3, which the swift backend downloads and manages itself
+pub const DEFAULT_MODEL_NAME: &str = "mlx-community/parakeet-tdt-0.6b-v3";
+
+/// Languages Parakeet TDT v3 recognises, as (code, name)
+pub const SUPPORTED_LANGUAGES: &[(&str, &str)] = &[
+    ("bg", "Bulgarian"),
+    ("hr", "Croatian"),
+    ("cs", "Czech"),
+    ("da", "Danish"),
+    ("nl", "Dutch"),
+    ("en", "English"),
+    ("et", "Estonian"),
+    ("fi", "Finnish"),
+    ("fr", "French"),
+    ("de", "German"),
+    ("el", "Greek"),
+    ("hu", "Hungarian"),
+    ("it", "Italian"),
+    ("lv", "Latvian"),
+    ("lt", "Lithuanian"),
+    ("mt", "Maltese"),
+    ("pl", "Polish"),
+    ("pt", "Portuguese"),
+    ("ro", "Romanian"),
+    ("ru", "Russian"),
+    ("sk", "Slovak"),
+    ("sl", "Slovenian"),
+    ("es", "Spanish"),
+    ("sv", "Swedish"),
+    ("uk", "Ukrainian"),
+];
+
+fn default_context_words() -> usize {
+    30
+}
+
+fn default_processing_timeout_secs() -> u64 {
+    120
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum TranscriberBackend {
+    /// Core ML (FluidAudio) via the Swift bridge
+    #[default]
+    Swift,
+    /// Scripted responses; no model required (tests and demo mode)
+    Mock,
+    /// whisper.cpp with a GGML model file (`whisper` feature)
+    Whisper,
+    /// Parakeet on MLX in a separate Python server process (`mlx` feature)
+    Mlx,
+    /// A `typeswift backend-worker --listen` on another machine, over TCP (`remote` feature)
+    Remote,
+}
+
+#[derive(Debug, Clone, PartialEq, Default, Serialize, Deserialize)]
+pub struct MockBackendConfig {
+    /// Responses returned in order, cycling; empty describes the received audio instead
+    #[serde(default)]
+    pub responses: Vec<String>,
+    /// Simulated transcription latency
+    #[serde(default)]
+    pub delay_ms: u64,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct WhisperBackendConfig {
+    /// GGML model file (e.g. ggml-large-v3-turbo.bin)
+    #[serde(default)]
+    pub model_path: Option<PathBuf>,
+    /// Run on the GPU through Metal when the build has it (`whisper-metal` feature)
+    #[serde(default = "default_true")]
+    pub use_metal: bool,
+    /// CPU threads for decoding; 0 picks from the core count
+    #[serde(default)]
+    pub threads: usize,
+}
+
+impl Default for WhisperBackendConfig {
+    fn default() -> Self {
+        Self { model_path: None, use_metal: true, threads: 0 }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct MlxBackendConfig {
+    /// Python with `parakeet-mlx` installed, e.g. a virtualenv's bin/python
+    #[serde(default = "default_mlx_python")]
+    pub python: String,
+    /// How long the server may take to load (and on first use, download) the model
+    #[serde(default = "default_mlx_startup_secs")]
+    pub startup_timeout_secs: u64,
+}
+
+fn default_mlx_python() -> String {
+    "python3".to_string()
+}
+
+fn default_mlx_startup_secs() -> u64 {
+    300
+}
+
+impl Default for MlxBackendConfig {
+    fn default() -> Self {
+        Self { python: default_mlx_python(), startup_timeout_secs: default_mlx_startup_secs() }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RemoteBackendConfig {
+    /// "host:port" of the machine running `typeswift backend-worker --listen`
+    #[serde(default = "default_remote_address")]
+    pub address: String,
+    /// Longest one transcription may take, network included
+    #[serde(default = "default_remote_timeout_secs")]
+    pub timeout_secs: u64,
+    /// Shared secret the worker requires of clients; set the same on both Macs. A worker
+    /// without one only listens on loopback.
+    #[serde(default)]
+    pub token: Option<String>,
+    /// How recordings are compressed for the trip, if the worker can decode it; raw samples
+    /// (four bytes each) otherwise
+    #[serde(default = "default_remote_codec")]
+    pub codec: AudioCodec,
+}
+
+fn default_remote_codec() -> AudioCodec {
+    // Lossless, so the worker hears exactly what a local model would
+    AudioCodec::Flac
+}
+
+fn default_remote_address() -> String {
+    "127.0.0.1:7724".to_string()
+}
+
+fn default_remote_timeout_secs() -> u64 {
+    120
+}
+
+impl Default for RemoteBackendConfig {
+    fn default() -> Self {
+        Self {
+            address: default_remote_address(),
+            timeout_secs: default_remote_timeout_secs(),
+            token: None,
+            codec: default_remote_codec(),
+        }
+    }
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -28,12 +796,212 @@ pub struct UiConfig {
     pub window_width: f32,
     pub window_height: f32,
     pub gap_from_bottom: f32,
+    /// Have VoiceOver announce when recording starts and stops
+    #[serde(default = "default_true")]
+    pub announce_recording: bool,
+    /// Hide the popup, menu bar recording indicator and notifications (for shared screens)
+    #[serde(default)]
+    pub presentation_mode: bool,
+    /// Stronger popup colors and borders; also on while macOS "Increase contrast" is set
+    #[serde(default)]
+    pub high_contrast: bool,
+    /// No popup animations; also on while macOS "Reduce motion" is set
+    #[serde(default)]
+    pub reduce_motion: bool,
+    /// Popup positions saved by earlier versions; moved to ui-state.json at launch
+    #[serde(default, skip_serializing)]
+    pub popup_positions: BTreeMap<String, PopupPosition>,
+    /// Start in hands-free mode if it was on when Typeswift quit (it's toggled from the menu
+    /// bar into ui-state.json); off starts from `hotkeys.hands_free` every launch
+    #[serde(default = "default_true")]
+    pub remember_hands_free: bool,
+    /// Lines of streaming text the popup shows before dropping the oldest words for "…"
+    #[serde(default = "default_popup_max_lines")]
+    pub popup_max_lines: usize,
+    /// Let the popup grow taller for wrapped text, up to `popup_max_height`
+    #[serde(default = "default_true")]
+    pub popup_auto_grow: bool,
+    #[serde(default = "default_popup_max_height")]
+    pub popup_max_height: f32,
+}
+
+fn default_popup_max_lines() -> usize {
+    3
+}
+
+fn default_popup_max_height() -> f32 {
+    120.0
+}
+
+/// Popup origin relative to the bottom-left of the display's usable area (points)
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct PopupPosition {
+    pub x: f64,
+    pub y: f64,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct OutputConfig {
     pub enable_typing: bool,
     pub add_space_between_utterances: bool,
+    #[serde(default)]
+    pub target: OutputTarget,
+    /// With `target = "cursor"`, collect text in the scratchpad when nothing typeable is focused
+    #[serde(default = "default_true")]
+    pub scratchpad_fallback: bool,
+    #[serde(default)]
+    pub format: FormatOptions,
+    /// Extra attempts after a typing operation fails
+    #[serde(default = "default_typing_retries")]
+    pub typing_retries: u32,
+    /// Failed operations in a row before Typeswift warns and copies text to the clipboard instead
+    #[serde(default = "default_max_typing_failures")]
+    pub max_typing_failures: u32,
+    /// Typing speed cap in characters per second (0 = unlimited), so a runaway transcript can't flood the app
+    #[serde(default = "default_max_chars_per_second")]
+    pub max_chars_per_second: u32,
+    /// Decide the leading space from the text before the cursor (via Accessibility) when possible
+    #[serde(default = "default_true")]
+    pub smart_spacing: bool,
+    /// Capitalize or lowercase the first word to fit the text before the cursor (via Accessibility)
+    #[serde(default = "default_true")]
+    pub context_capitalization: bool,
+    /// Also put every final transcript on the clipboard, even when it is typed
+    #[serde(default)]
+    pub mirror_to_clipboard: bool,
+    /// How to get text past an input method (Chinese, Japanese, Korean...) that is active in
+    /// the target app, since it can swallow or recompose typed keystrokes
+    #[serde(default)]
+    pub input_method: InputMethodTyping,
+    /// Where dictations go unless a profile sends them to its sink; switched from the menu bar
+    #[serde(default)]
+    pub destination: OutputDestination,
+    /// Named sinks the destination can be switched to
+    #[serde(default)]
+    pub sinks: Vec<NamedSink>,
+    /// Show and log dictations without typing them (or sending them to a sink or the clipboard),
+    /// for trying out models and settings on a shared screen
+    #[serde(default)]
+    pub rehearsal: bool,
+}
+
+/// The output the menu bar's Output submenu (and `hotkeys.cycle_output`) switches between
+#[derive(Debug, Clone, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum OutputDestination {
+    /// Type at the cursor (or as `target` and the frontmost app's profile say)
+    #[default]
+    Cursor,
+    /// Send every dictation to the `output.sinks` entry of this name, whatever app is in front
+    Sink(String),
+    /// Only copy dictations to the clipboard
+    Clipboard,
+}
+
+impl OutputDestination {
+    /// Menu and notification label
+    pub fn label(&self) -> String {
+        match self {
+            OutputDestination::Cursor => "Type at cursor".to_string(),
+            OutputDestination::Sink(name) => format!("Append to {}", name),
+            OutputDestination::Clipboard => "Clipboard only".to_string(),
+        }
+    }
+}
+
+/// A sink listed in the Output submenu under its name
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct NamedSink {
+    pub name: String,
+    #[serde(flatten)]
+    pub sink: SinkConfig,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum InputMethodTyping {
+    /// Paste the text instead of typing it, then restore the clipboard
+    #[default]
+    Paste,
+    /// Switch to an ASCII keyboard layout while typing, then back
+    SwitchLayout,
+    /// Type as usual
+    Type,
+}
+
+/// Punctuation (and text direction) rewriting applied to final text
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct FormatOptions {
+    #[serde(default)]
+    pub quotes: QuoteStyle,
+    #[serde(default)]
+    pub dashes: DashStyle,
+    #[serde(default)]
+    pub ellipsis: EllipsisStyle,
+    /// Wrap right-to-left (Arabic, Hebrew...) text in directional isolates, so it keeps its
+    /// direction when typed next to left-to-right text
+    #[serde(default)]
+    pub rtl_isolates: bool,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum QuoteStyle {
+    /// Leave the model's quotes as-is
+    #[default]
+    Keep,
+    /// ASCII " and ' (code, terminals)
+    Straight,
+    /// Typographic “ ” ‘ ’ (prose)
+    Smart,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum DashStyle {
+    #[default]
+    Keep,
+    /// — and – become -- and -
+    Ascii,
+    /// -- and spaced hyphens become —
+    Typographic,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum EllipsisStyle {
+    #[default]
+    Keep,
+    /// … becomes ...
+    Dots,
+    /// ... becomes …
+    Character,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum OutputTarget {
+    /// Type into the focused app
+    #[default]
+    Cursor,
+    /// Collect dictations in the Typeswift scratchpad window
+    Scratchpad,
+}
+
+fn default_typing_retries() -> u32 {
+    2
+}
+
+fn default_max_typing_failures() -> u32 {
+    5
+}
+
+fn default_max_chars_per_second() -> u32 {
+    400
+}
+
+fn default_true() -> bool {
+    true
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -41,59 +1009,288 @@ pub struct OutputConfig {
         pub toggle_window: Option<String>, // Optional separate toggle
         pub push_to_talk: String,          // Main push-to-talk hotkey
         pub preferences: Option<String>,   // Open preferences/settings
+        /// Read the last transcription aloud
+        #[serde(default)]
+        pub speak_last: Option<String>,
+        /// Stop typing immediately; untyped text goes to the clipboard
+        #[serde(default)]
+        pub stop_typing: Option<String>,
+        /// Flag the current moment while a meeting is being transcribed
+        #[serde(default)]
+        pub mark_moment: Option<String>,
+        /// Open the command palette, a searchable list of every action
+        #[serde(default)]
+        pub command_palette: Option<String>,
+        /// Switch to the next output destination (cursor, each of `output.sinks`, clipboard)
+        #[serde(default)]
+        pub cycle_output: Option<String>,
+        /// Tap push-to-talk to start recording and tap again to stop, instead of holding
+        #[serde(default)]
+        pub hands_free: bool,
+        /// A quick tap of push-to-talk locks recording on until the next press; holding
+        /// it still records only while held
+        #[serde(default)]
+        pub tap_to_lock: bool,
+        /// With `tap_to_lock`, a press released within this many ms counts as a tap
+        #[serde(default = "default_tap_lock_ms")]
+        pub tap_lock_ms: u64,
+        /// A locked (tapped or hands-free) recording stops itself after this many minutes;
+        /// 0 lets it run until stopped
+        #[serde(default = "default_lock_timeout_minutes")]
+        pub lock_timeout_minutes: u64,
+        /// A release followed by a press within this many ms is key bounce and ignored
+        #[serde(default = "default_debounce_ms")]
+        pub debounce_ms: u64,
+        /// With push-to-talk on Fn, stop macOS also running its own Fn/Globe action
+        /// (dictation, emoji picker, input source) while Typeswift runs
+        #[serde(default)]
+        pub suppress_system_fn: bool,
+        /// Don't warn at launch when Fn/Globe also has a macOS action
+        #[serde(default)]
+        pub ignore_fn_conflict: bool,
+        /// While recording, swallow repeats of the held push-to-talk key so it never types
+        #[serde(default = "default_true")]
+        pub suppress_key_repeat: bool,
+        /// Modifiers held when push-to-talk is released that change what happens to that
+        /// dictation; the most specific match wins
+        #[serde(default)]
+        pub release_variants: Vec<ReleaseVariant>,
     }
 
+/// What to do differently with a dictation released while `modifiers` are held
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ReleaseVariant {
+    /// Held modifiers in push-to-talk syntax, e.g. "shift" or "right_opt+shift"
+    pub modifiers: String,
+    /// Copy the text to the clipboard instead of typing it
+    #[serde(default)]
+    pub clipboard: bool,
+    /// Extra post-processing stages run after `pipeline`, e.g. an `llm` cleanup
+    #[serde(default)]
+    pub stages: Vec<StageConfig>,
+}
+
 impl Default for Config {
     fn default() -> Self {
         Self {
             audio: AudioConfig {
                 target_sample_rate: 16000,
+                vad: VadConfig::default(),
+                suppressed_device_warnings: Vec::new(),
+                input_gain: default_input_gain(),
             },
             model: ModelConfig {
-                model_name: "mlx-community/parakeet-tdt-0.6b-v3".to_string(),
+                model_name: DEFAULT_MODEL_NAME.to_string(),
+                preset: None,
                 left_context_seconds: 5,
                 right_context_seconds: 3,
+                backend: TranscriberBackend::Swift,
+                mock: MockBackendConfig::default(),
+                whisper: WhisperBackendConfig::default(),
+                mlx: MlxBackendConfig::default(),
+                remote: RemoteBackendConfig::default(),
+                isolate: false,
+                context_continuation: false,
+                context_words: default_context_words(),
+                bias_phrases: Vec::new(),
+                language: None,
+                processing_timeout_secs: default_processing_timeout_secs(),
             },
             ui: UiConfig {
                 window_width: 90.0,
                 window_height: 39.0,
                 gap_from_bottom: 70.0,
+                announce_recording: true,
+                presentation_mode: false,
+                high_contrast: false,
+                reduce_motion: false,
+                popup_positions: BTreeMap::new(),
+                remember_hands_free: true,
+                popup_max_lines: default_popup_max_lines(),
+                popup_auto_grow: true,
+                popup_max_height: default_popup_max_height(),
             },
             output: OutputConfig {
                 enable_typing: true,
                 add_space_between_utterances: true,
+                target: OutputTarget::Cursor,
+                scratchpad_fallback: true,
+                format: FormatOptions::default(),
+                typing_retries: default_typing_retries(),
+                max_typing_failures: default_max_typing_failures(),
+                max_chars_per_second: default_max_chars_per_second(),
+                smart_spacing: true,
+                context_capitalization: true,
+                mirror_to_clipboard: false,
+                input_method: InputMethodTyping::Paste,
+                destination: OutputDestination::Cursor,
+                sinks: Vec::new(),
+                rehearsal: false,
             },
             hotkeys: HotkeyConfig {
                 toggle_window: None, // Disabled by default
                 push_to_talk: "fn".to_string(), // Use fn key on macOS (requires accessibility permissions)
                                                 // Alternative: "cmd+space" or "opt+space"
                 preferences: None,
+                speak_last: None,
+                stop_typing: None,
+                mark_moment: None,
+                command_palette: None,
+                cycle_output: None,
+                hands_free: false,
+                tap_to_lock: false,
+                tap_lock_ms: default_tap_lock_ms(),
+                lock_timeout_minutes: default_lock_timeout_minutes(),
+                debounce_ms: default_debounce_ms(),
+                suppress_system_fn: false,
+                ignore_fn_conflict: false,
+                suppress_key_repeat: true,
+                release_variants: Vec::new(),
             },
+            commands: CommandsConfig::default(),
+            streaming: StreamingConfig::default(),
+            focus: FocusConfig::default(),
+            history: HistoryConfig::default(),
+            pipeline: PipelineConfig::default(),
+            mcp: McpConfig::default(),
+            ipc: IpcConfig::default(),
+            meetings: MeetingsConfig::default(),
+            active_profile: None,
+            profiles: Vec::new(),
         }
     }
 }
 
 impl Config {
+    /// The config file over the defaults, with `TYPESWIFT_*` environment overrides on top
     pub fn load() -> Result<Self> {
-        // Load Typeswift config only
-        if let Ok(home) = std::env::var("HOME") {
-            let typeswift_path = PathBuf::from(&home).join(".typeswift").join("config.toml");
-            if typeswift_path.exists() {
-                let contents = std::fs::read_to_string(typeswift_path)?;
-                return Ok(toml::from_str(&contents)?);
+        Self::load_with(&[])
+    }
+
+    /// `load`, then `settings` (key path, raw value) from the command line over everything else.
+    /// Unlike an environment override, a command-line value that doesn't fit is an error.
+    pub fn load_with(settings: &[(Vec<String>, String)]) -> Result<Self> {
+        let overrides = env_overrides();
+        let file = Self::config_path().filter(|path| path.exists());
+        if file.is_none() && overrides.is_empty() && settings.is_empty() {
+            return Ok(Self::default());
+        }
+        let mut table = match file {
+            Some(path) => std::fs::read_to_string(path)?.parse::<toml::Table>()?,
+            None => toml::Table::try_from(Self::default())?,
+        };
+        let mut config: Self = toml::Value::Table(table.clone()).try_into()?;
+        // One at a time, so a bad value only loses its own override
+        for (path, raw) in overrides {
+            let name = format!("{}{}", ENV_PREFIX, path.join("__").to_uppercase());
+            match with_override(&table, &path, &raw) {
+                Some((candidate, parsed)) => {
+                    tracing::info!("{} set from {}", path.join("."), name);
+                    table = candidate;
+                    config = parsed;
+                }
+                None => tracing::warn!("Ignoring {}={:?}: not a valid value for {}", name, raw, path.join(".")),
             }
         }
-        // Return default if no config file
-        Ok(Self::default())
+        for (path, raw) in settings {
+            let (candidate, parsed) = with_override(&table, path, raw)
+                .ok_or_else(|| anyhow::anyhow!("{:?} is not a valid value for {}", raw, path.join(".")))?;
+            table = candidate;
+            config = parsed;
+        }
+        Ok(config)
     }
 
+    /// Write the config to `path`, replacing the file in one step (a temp file renamed over it).
+    /// Only settings that changed are rewritten: the existing file's comments, formatting, key
+    /// order and keys this version doesn't know are kept, and so are settings overridden from
+    /// the environment. Use `services::config_store::save`
+    /// from the app, so concurrent changes don't race.
     pub fn save(&self, path: PathBuf) -> Result<()> {
-        let toml_string = toml::to_string_pretty(self)?;
-        std::fs::create_dir_all(path.parent().unwrap())?;
-        std::fs::write(path, toml_string)?;
+        let saved: toml_edit::DocumentMut = toml::to_string_pretty(self)?.parse()?;
+        let existing = std::fs::read_to_string(&path).ok();
+        let (mut document, mut keep) = match existing.as_deref().map(|e| (e, e.parse::<toml_edit::DocumentMut>())) {
+            Some((existing, Ok(document))) => (document, unknown_keys(existing)),
+            // No file to keep anything of: start from the defaults, which is what it would hold
+            _ => (toml::to_string_pretty(&Self::default())?.parse()?, Vec::new()),
+        };
+        // Environment overrides apply to this run only; the file keeps its own values
+        keep.extend(env_overrides().into_iter().map(|(path, _)| path.into_iter().map(TomlStep::Key).collect()));
+        merge_table(document.as_table_mut(), saved.as_table(), &keep, &mut Vec::new());
+        let toml_string = document.to_string();
+        let dir = path.parent().ok_or_else(|| anyhow::anyhow!("No directory in {}", path.display()))?;
+        std::fs::create_dir_all(dir)?;
+        let tmp = path.with_extension("toml.tmp");
+        std::fs::write(&tmp, toml_string)?;
+        std::fs::rename(&tmp, &path)?;
         Ok(())
     }
 
+    /// The config as TOML laid out and commented like `default-config.toml`, for `typeswift
+    /// config`. Settings the template doesn't mention are added to the end of their section.
+    pub fn to_commented_toml(&self) -> Result<String> {
+        let mut document: toml_edit::DocumentMut = COMMENTED_DEFAULTS.parse()?;
+        let saved: toml_edit::DocumentMut = toml::to_string_pretty(self)?.parse()?;
+        merge_table(document.as_table_mut(), saved.as_table(), &[], &mut Vec::new());
+        Ok(document.to_string())
+    }
+
+    /// The profile named by the dictation's push-to-talk binding, else the one for the given
+    /// frontmost app, falling back to `active_profile`
+    pub fn profile_for(&self, profile: Option<&str>, bundle_id: Option<&str>) -> Option<&Profile> {
+        if let Some(name) = profile {
+            if let Some(profile) = self.profiles.iter().find(|p| p.name == name) {
+                return Some(profile);
+            }
+        }
+        bundle_id
+            .and_then(|id| self.profiles.iter().find(|p| p.apps.iter().any(|a| a.eq_ignore_ascii_case(id))))
+            .or_else(|| {
+                let name = self.active_profile.as_deref()?;
+                self.profiles.iter().find(|p| p.name == name)
+            })
+    }
+
+    pub fn format_options_for(&self, profile: Option<&str>, bundle_id: Option<&str>) -> FormatOptions {
+        self.profile_for(profile, bundle_id)
+            .and_then(|p| p.format.clone())
+            .unwrap_or_else(|| self.output.format.clone())
+    }
+
+    pub fn template_for(&self, profile: Option<&str>, bundle_id: Option<&str>) -> Option<&str> {
+        self.profile_for(profile, bundle_id).and_then(|p| p.template.as_deref())
+    }
+
+    /// The sink chosen as output destination, else the profile's
+    pub fn sink_for(&self, profile: Option<&str>, bundle_id: Option<&str>) -> Option<&SinkConfig> {
+        self.destination_sink().or_else(|| self.profile_for(profile, bundle_id).and_then(|p| p.sink.as_ref()))
+    }
+
+    fn destination_sink(&self) -> Option<&SinkConfig> {
+        let OutputDestination::Sink(ref name) = self.output.destination else { return None };
+        self.output.sinks.iter().find(|s| &s.name == name).map(|s| &s.sink)
+    }
+
+    /// Every destination the output can be switched to, in menu (and cycling) order
+    pub fn output_destinations(&self) -> Vec<OutputDestination> {
+        std::iter::once(OutputDestination::Cursor)
+            .chain(self.output.sinks.iter().map(|s| OutputDestination::Sink(s.name.clone())))
+            .chain(std::iter::once(OutputDestination::Clipboard))
+            .collect()
+    }
+
+    pub fn bias_phrases_for(&self, profile: Option<&str>, bundle_id: Option<&str>) -> Vec<String> {
+        self.profile_for(profile, bundle_id)
+            .and_then(|p| p.bias_phrases.clone())
+            .unwrap_or_else(|| self.model.bias_phrases.clone())
+    }
+
+    pub fn language_for(&self, profile: Option<&str>, bundle_id: Option<&str>) -> Option<String> {
+        self.profile_for(profile, bundle_id)
+            .and_then(|p| p.language.clone())
+            .or_else(|| self.model.language.clone())
+    }
+
     pub fn config_path() -> Option<PathBuf> {
         if let Ok(home) = std::env::var("HOME") {
             Some(PathBuf::from(home).join(".typeswift").join("config.toml"))
@@ -102,3 +1299,259 @@ impl Config {
         }
     }
 }
+
+/// One step into a TOML document: a table key or an array index
+#[derive(Debug, Clone, PartialEq)]
+enum TomlStep {
+    Key(String),
+    Index(usize),
+}
+
+/// Paths of the keys in `existing` that `Config` ignores: settings from a newer or experimental
+/// build, or typos the user will want to find again
+fn unknown_keys(existing: &str) -> Vec<Vec<TomlStep>> {
+    let mut unknown = Vec::new();
+    let _: Result<Config, _> = serde_ignored::deserialize(toml::Deserializer::new(existing), |path| {
+        let mut steps = Vec::new();
+        toml_steps(&path, &mut steps);
+        unknown.push(steps);
+    });
+    unknown
+}
+
+fn toml_steps(path: &serde_ignored::Path, steps: &mut Vec<TomlStep>) {
+    use serde_ignored::Path;
+    match path {
+        Path::Root => {}
+        Path::Seq { parent, index } => {
+            toml_steps(parent, steps);
+            steps.push(TomlStep::Index(*index));
+        }
+        Path::Map { parent, key } => {
+            toml_steps(parent, steps);
+            steps.push(TomlStep::Key(key.clone()));
+        }
+        Path::Some { parent } | Path::NewtypeStruct { parent } | Path::NewtypeVariant { parent } => {
+            toml_steps(parent, steps)
+        }
+    }
+}
+
+/// Bring `document` (the file as the user left it) in line with `saved` (the config as it is
+/// now), touching only what differs. Keys at `keep` paths are left as they are in `document`;
+/// other keys `saved` lacks go.
+fn merge_table(
+    document: &mut toml_edit::Table,
+    saved: &toml_edit::Table,
+    keep: &[Vec<TomlStep>],
+    path: &mut Vec<TomlStep>,
+) {
+    for (key, item) in saved.iter() {
+        path.push(TomlStep::Key(key.to_string()));
+        if !keep.contains(path) {
+            match document.get_mut(key) {
+                Some(existing) => merge_item(existing, item, keep, path),
+                None => {
+                    document.insert(key, unplaced(item));
+                }
+            }
+        }
+        path.pop();
+    }
+    let removed: Vec<String> = document
+        .iter()
+        .map(|(key, _)| key.to_string())
+        .filter(|key| !saved.contains_key(key))
+        .filter(|key| {
+            path.push(TomlStep::Key(key.clone()));
+            let kept = keep.contains(path);
+            path.pop();
+            !kept
+        })
+        .collect();
+    for key in removed {
+        document.remove(&key);
+    }
+}
+
+fn merge_item(
+    document: &mut toml_edit::Item,
+    saved: &toml_edit::Item,
+    keep: &[Vec<TomlStep>],
+    path: &mut Vec<TomlStep>,
+) {
+    use toml_edit::Item;
+    match (&mut *document, saved) {
+        (Item::Table(existing), Item::Table(table)) => merge_table(existing, table, keep, path),
+        (Item::ArrayOfTables(existing), Item::ArrayOfTables(tables)) => {
+            for (index, table) in tables.iter().enumerate() {
+                path.push(TomlStep::Index(index));
+                match existing.get_mut(index) {
+                    Some(existing) => merge_table(existing, table, keep, path),
+                    None => {
+                        if let Item::Table(table) = unplaced(&Item::Table(table.clone())) {
+                            existing.push(table);
+                        }
+                    }
+                }
+                path.pop();
+            }
+            while existing.len() > tables.len() {
+                existing.remove(existing.len() - 1);
+            }
+        }
+        (Item::Value(existing), Item::Value(value)) => {
+            if !same_value(existing, value) {
+                // Keep the comment after the old value
+                let decor = existing.decor().clone();
+                *existing = value.clone();
+                *existing.decor_mut() = decor;
+            }
+        }
+        (Item::Value(existing), Item::Table(table)) => {
+            // Written inline by hand; keep it that way if nothing in it changed
+            let inline = toml_edit::Value::InlineTable(table.clone().into_inline_table());
+            if !same_value(existing, &inline) {
+                *document = unplaced(saved);
+            }
+        }
+        _ => *document = unplaced(saved),
+    }
+}
+
+/// A copy of `item` without document positions, so new tables are written after the ones
+/// already in the file instead of wherever they were in the freshly serialized config
+fn unplaced(item: &toml_edit::Item) -> toml_edit::Item {
+    use toml_edit::Item;
+    match item {
+        Item::Table(table) => {
+            let mut copy = toml_edit::Table::new();
+            for (key, item) in table.iter() {
+                copy.insert(key, unplaced(item));
+            }
+            Item::Table(copy)
+        }
+        Item::ArrayOfTables(tables) => {
+            let mut copy = toml_edit::ArrayOfTables::new();
+            for table in tables.iter() {
+                if let Item::Table(table) = unplaced(&Item::Table(table.clone())) {
+                    copy.push(table);
+                }
+            }
+            Item::ArrayOfTables(copy)
+        }
+        other => other.clone(),
+    }
+}
+
+/// Whether two values are the same setting however they're written. Floats compare at `f32`,
+/// the precision the config holds them in, so a hand-written `0.1` isn't rewritten.
+fn same_value(a: &toml_edit::Value, b: &toml_edit::Value) -> bool {
+    fn plain(value: &toml_edit::Value) -> Option<toml::Value> {
+        format!("v = {}", value).parse::<toml::Table>().ok()?.remove("v")
+    }
+    fn same(a: &toml::Value, b: &toml::Value) -> bool {
+        use toml::Value;
+        match (a, b) {
+            (Value::Float(a), Value::Float(b)) => *a as f32 == *b as f32,
+            (Value::Integer(i), Value::Float(f)) | (Value::Float(f), Value::Integer(i)) => *i as f32 == *f as f32,
+            (Value::Array(a), Value::Array(b)) => a.len() == b.len() && a.iter().zip(b).all(|(a, b)| same(a, b)),
+            (Value::Table(a), Value::Table(b)) => {
+                a.len() == b.len() && a.iter().all(|(key, a)| b.get(key).is_some_and(|b| same(a, b)))
+            }
+            _ => a == b,
+        }
+    }
+    matches!((plain(a), plain(b)), (Some(a), Some(b)) if same(&a, &b))
+}
+
+/// Environment variables that override settings: `TYPESWIFT_OUTPUT__ENABLE_TYPING=false` sets
+/// `[output] enable_typing`, with `__` between each table and key
+const ENV_PREFIX: &str = "TYPESWIFT_";
+
+/// `TYPESWIFT_*` variables that mean something else: model and asset folders, and the target app
+/// passed to pipeline commands
+const NON_CONFIG_ENV: &[&str] = &["TYPESWIFT_MODELS", "TYPESWIFT_ASSETS", "TYPESWIFT_APP"];
+
+/// The settings overridden from the environment, as (lowercase key path, raw value). Only
+/// `TYPESWIFT_SECTION__KEY` names count; a bare `TYPESWIFT_NAME` is never a setting.
+fn env_overrides() -> Vec<(Vec<String>, String)> {
+    std::env::vars_os()
+        .filter_map(|(name, value)| {
+            let name = name.into_string().ok()?;
+            if NON_CONFIG_ENV.contains(&name.as_str()) {
+                return None;
+            }
+            let path: Vec<String> = name.strip_prefix(ENV_PREFIX)?.split("__").map(str::to_lowercase).collect();
+            if path.len() < 2 || path.iter().any(String::is_empty) {
+                return None;
+            }
+            Some((path, value.into_string().ok()?))
+        })
+        .collect()
+}
+
+/// The TOML value `raw` spells (`false`, `400`, `0.5`, `["a", "b"]`), else `raw` as a string
+fn env_value(raw: &str) -> toml::Value {
+    format!("v = {}", raw)
+        .parse::<toml::Table>()
+        .ok()
+        .and_then(|mut table| table.remove("v"))
+        .unwrap_or_else(|| toml::Value::String(raw.to_string()))
+}
+
+/// `table` with `raw` set at `path`, and the config it makes, if it makes one. A value that reads
+/// as a number or bool is tried as a string too (e.g. a hotkey "1").
+fn with_override(table: &toml::Table, path: &[String], raw: &str) -> Option<(toml::Table, Config)> {
+    [env_value(raw), toml::Value::String(raw.to_string())].into_iter().find_map(|value| {
+        let mut candidate = table.clone();
+        set_toml_path(&mut candidate, path, value)?;
+        let parsed = toml::Value::Table(candidate.clone()).try_into::<Config>().ok()?;
+        Some((candidate, parsed))
+    })
+}
+
+/// Set the key at `path` in `table`, creating the tables on the way; `None` when something on
+/// the way is a value rather than a table
+fn set_toml_path(table: &mut toml::Table, path: &[String], value: toml::Value) -> Option<()> {
+    let (key, parents) = path.split_last()?;
+    let mut table = table;
+    for parent in parents {
+        let entry = table.entry(parent.clone()).or_insert_with(|| toml::Value::Table(toml::Table::new()));
+        table = entry.as_table_mut()?;
+    }
+    table.insert(key.clone(), value);
+    Some(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn profile(name: &str, apps: &[&str]) -> Profile {
+        Profile {
+            name: name.to_string(),
+            apps: apps.iter().map(|a| a.to_string()).collect(),
+            format: None,
+            bias_phrases: Some(vec![name.to_string()]),
+            sink: None,
+            language: None,
+            push_to_talk: None,
+            template: None,
+        }
+    }
+
+    #[test]
+    fn dictation_profile_beats_the_frontmost_app_only_when_given() {
+        let mut config = Config::default();
+        config.profiles = vec![profile("Notes", &[]), profile("Code", &["com.microsoft.VSCode"])];
+        let code = Some("com.microsoft.VSCode");
+
+        assert_eq!(config.profile_for(Some("Notes"), code).map(|p| p.name.as_str()), Some("Notes"));
+        assert_eq!(config.profile_for(None, code).map(|p| p.name.as_str()), Some("Code"));
+        assert_eq!(config.bias_phrases_for(None, code), vec!["Code".to_string()]);
+        // A profile that has since been removed falls back to the app's
+        assert_eq!(config.profile_for(Some("Gone"), code).map(|p| p.name.as_str()), Some("Code"));
+        assert!(config.profile_for(None, None).is_none());
+    }
+}
@@ -1,16 +1,214 @@
-use crate::services::audio::ImprovedAudioProcessor as AudioProcessor;
-use crate::config::Config;
+use crate::services::audio::{AudioProcessor, InputDeviceInfo, Transcriber, Transcript, TranscriptionPrompt};
+use crate::services::backend::TranscriberCapabilities;
+use crate::services::commands::{parse_command, run_system_command, Command};
+use crate::services::config_store;
+use crate::services::models::installed_models;
+use crate::services::format::{needs_leading_space, starts_sentence, CursorContext};
+use crate::services::pipeline::{Pipeline, StageContext};
+use crate::services::streaming::{stable_text, StreamingManager};
+use crate::services::tracks::SourceTracks;
+use crate::config::{
+    Config, ModelConfig, ModelPreset, OutputDestination, OutputTarget, TranscriberBackend, SUPPORTED_LANGUAGES,
+};
 use crate::error::VoicyResult;
-use crate::input::HotkeyEvent;
-use crate::output::TypingQueue;
-use crate::state::{AppStateManager, RecordingState};
+use crate::history::HistoryStore;
+use crate::input::{HotkeyEvent, QuickSetting};
+use crate::output::{TypingPolicy, TypingQueue};
+use crate::state::{AppStateManager, LiveTranscript, ModelStatus, OutputOverride, RecordingEvent, RecordingState, TranscriptUpdate};
 use crate::window::WindowManager;
 use crate::platform::macos::ffi as menubar_ffi;
-use crossbeam_channel::Receiver;
+use crate::platform::macos::relaunch;
+use crate::runtime;
+use crossbeam_channel::{Receiver, RecvTimeoutError};
+use smol::Timer;
+use once_cell::sync::Lazy;
+use std::sync::atomic::{AtomicU64, Ordering};
 use std::sync::{Arc, Mutex};
 use tracing::{info, warn, error, debug};
 use crate::mem::current_rss_mb;
 
+/// Turn a raw transcript into emitted text: spoken commands, then the configured
+/// post-processing pipeline for the target app. Also used to re-process history entries.
+pub fn post_process(raw_text: &str, config: &Config, profile: Option<&str>, bundle_id: Option<&str>) -> String {
+    run_post_processing(raw_text, config, profile, bundle_id, false)
+}
+
+/// `post_process` for streaming partials: external stages only run on the final text
+pub fn post_process_partial(raw_text: &str, config: &Config, profile: Option<&str>, bundle_id: Option<&str>) -> String {
+    run_post_processing(raw_text, config, profile, bundle_id, true)
+}
+
+fn run_post_processing(
+    raw_text: &str,
+    config: &Config,
+    profile: Option<&str>,
+    bundle_id: Option<&str>,
+    local_only: bool,
+) -> String {
+    match parse_command(raw_text, &config.commands) {
+        // Only a finished dictation acts on system commands; anywhere else they're text
+        Command::Text(_) | Command::System(_) => {
+            let pipeline = if local_only {
+                Pipeline::local_from_config(&config.pipeline)
+            } else {
+                Pipeline::from_config(&config.pipeline)
+            };
+            pipeline.run(raw_text, &StageContext { config, raw_text, bundle_id, profile })
+        }
+        Command::Spelled(spelled) => {
+            info!("Spelling mode: {} chars", spelled.len());
+            spelled
+        }
+    }
+}
+
+/// Orders push-to-talk presses and releases before they reach the controller. A release is
+/// held back for the debounce interval so a bounce (release + press) can be dropped as a
+/// pair, and a press made while the last dictation is still being finalized waits for it
+/// rather than being rejected (or is dropped with its release if let go before then).
+#[derive(Default)]
+struct PressGate {
+    /// When a release arrived that may still turn out to be a bounce
+    held_release: Option<std::time::Instant>,
+    /// A press waiting for the previous dictation to finish, with the profile it picked
+    waiting_press: Option<Option<usize>>,
+}
+
+impl PressGate {
+    const POLL: std::time::Duration = std::time::Duration::from_millis(20);
+
+    /// How long the controller may block for the next event, `None` for indefinitely
+    fn wait(&self) -> Option<std::time::Duration> {
+        (self.held_release.is_some() || self.waiting_press.is_some()).then_some(Self::POLL)
+    }
+
+    /// Events whose wait is over
+    fn due(&mut self, state: &AppStateManager, debounce: std::time::Duration) -> Vec<HotkeyEvent> {
+        let mut events = Vec::new();
+        if self.held_release.is_some_and(|at| at.elapsed() >= debounce) {
+            self.held_release = None;
+            events.push(HotkeyEvent::PushToTalkReleased);
+        }
+        if self.waiting_press.is_some() && state.get_recording_state() == RecordingState::Idle {
+            events.extend(self.waiting_press.take().map(HotkeyEvent::PushToTalkPressed));
+        }
+        events
+    }
+
+    /// Route a new event: push-to-talk may be held back, anything else passes straight through
+    fn accept(&mut self, event: HotkeyEvent, state: &AppStateManager) -> Vec<HotkeyEvent> {
+        match event {
+            HotkeyEvent::PushToTalkPressed(_) if self.held_release.take().is_some() => {
+                debug!("Push-to-talk bounce ignored");
+                Vec::new()
+            }
+            HotkeyEvent::PushToTalkPressed(profile) if state.get_recording_state() == RecordingState::Processing => {
+                info!("Push-to-talk pressed while finishing the last dictation; starting when it's done");
+                self.waiting_press = Some(profile);
+                Vec::new()
+            }
+            HotkeyEvent::PushToTalkReleased if self.waiting_press.is_some() => {
+                info!("Push-to-talk released before the last dictation finished; press dropped");
+                self.waiting_press = None;
+                Vec::new()
+            }
+            HotkeyEvent::PushToTalkReleased if state.get_recording_state() == RecordingState::Recording => {
+                self.held_release = Some(std::time::Instant::now());
+                Vec::new()
+            }
+            other => vec![other],
+        }
+    }
+}
+
+/// How often Accessibility permission is re-checked
+const PERMISSION_POLL_INTERVAL: std::time::Duration = std::time::Duration::from_secs(5);
+
+/// How often the push-to-talk event tap is checked for having been switched off
+const TAP_POLL_INTERVAL: std::time::Duration = std::time::Duration::from_secs(2);
+
+/// How often Focus status is re-checked
+const FOCUS_POLL_INTERVAL: std::time::Duration = std::time::Duration::from_secs(3);
+
+/// How often a locked recording is checked against `lock_timeout_minutes`
+const LOCK_POLL_INTERVAL: std::time::Duration = std::time::Duration::from_secs(1);
+
+/// Longest a recording made during launch waits for the model before it's given up on
+const MODEL_WAIT_TIMEOUT: std::time::Duration = std::time::Duration::from_secs(120);
+
+/// How often a dictation in Processing is checked against `processing_timeout_secs`
+const PROCESSING_POLL_INTERVAL: std::time::Duration = std::time::Duration::from_secs(5);
+
+/// How often a picked model waits for dictation to go idle before it's swapped in
+const MODEL_SWAP_POLL_INTERVAL: std::time::Duration = std::time::Duration::from_millis(250);
+
+/// Background audio measured when hands-free mode starts, for the adaptive VAD
+const NOISE_CALIBRATION: std::time::Duration = std::time::Duration::from_secs(2);
+
+/// How often a meeting recording is moved out of the (30 s) capture ring buffer
+const CAPTURE_DRAIN_INTERVAL: std::time::Duration = std::time::Duration::from_secs(5);
+
+/// Streamed text for an utterance, including the separating space the final text would get
+fn streamed_text(text: &str, add_space: bool) -> String {
+    if add_space && !text.is_empty() { format!(" {}", text) } else { text.to_string() }
+}
+
+/// Spacing and casing for the next utterance from the text before the cursor. Without AX
+/// the space follows the setting alone and casing is left as transcribed.
+fn cursor_context(config: &Config) -> CursorContext {
+    let out = &config.output;
+    let smart_space = out.add_space_between_utterances && out.smart_spacing;
+    let before = if smart_space || out.context_capitalization {
+        menubar_ffi::text_before_cursor(64)
+    } else {
+        None
+    };
+    CursorContext {
+        leading_space: out.add_space_between_utterances
+            && (!smart_space || before.as_deref().is_none_or(needs_leading_space)),
+        sentence_start: before.as_deref().filter(|_| out.context_capitalization).map(starts_sentence),
+    }
+}
+
+/// Write config changes made outside Preferences to the config file
+fn save_config(config: &Config) {
+    config_store::save(config);
+}
+
+/// Hide (or restore) everything that shows dictation on screen
+fn set_presentation_mode(on: bool) {
+    info!("Presentation mode {}", if on { "on" } else { "off" });
+    if on {
+        menubar_ffi::MenuBarController::set_recording(false);
+        menubar_ffi::MenuBarController::set_status("");
+    }
+    menubar_ffi::set_quiet(menubar_ffi::QuietReason::Presentation, on);
+    if !on {
+        menubar_ffi::set_caps_lock_led(false);
+    }
+}
+
+/// Scratchpad text saved across a relaunch
+fn pending_scratchpad_path() -> Option<std::path::PathBuf> {
+    std::env::var("HOME")
+        .ok()
+        .map(|home| std::path::PathBuf::from(home).join(".typeswift").join("pending-scratchpad.json"))
+}
+
+/// Put scratchpad text saved by `relaunch` back into the scratchpad
+fn restore_pending_scratchpad(state: &AppStateManager) {
+    let Some(path) = pending_scratchpad_path() else { return };
+    let Ok(json) = std::fs::read_to_string(&path) else { return };
+    let _ = std::fs::remove_file(&path);
+    match serde_json::from_str::<Vec<String>>(&json) {
+        Ok(entries) => {
+            info!("Restoring {} scratchpad entries from before relaunch", entries.len());
+            entries.into_iter().for_each(|e| state.push_scratchpad(e));
+        }
+        Err(e) => warn!("Discarding unreadable scratchpad file: {}", e),
+    }
+}
+
 /// Central controller that owns the app orchestration and processes events.
 pub struct AppController {
     state: AppStateManager,
@@ -18,33 +216,107 @@ pub struct AppController {
     typing_queue: TypingQueue,
     audio_processor: Arc<Mutex<AudioProcessor>>,
     config: Arc<parking_lot::RwLock<Config>>,
+    history: HistoryStore,
+    /// Text typed by streaming mode for the utterance in progress
+    streaming: Arc<parking_lot::Mutex<Option<StreamingManager>>>,
 }
 
 impl AppController {
     pub fn new(config: Config) -> Self {
         let state = AppStateManager::new();
+        restore_pending_scratchpad(&state);
+        if config.ui.presentation_mode {
+            set_presentation_mode(true);
+        }
 
-        // Initialize audio processor early so errors surface, but don't crash the app
+        // The model is loaded by spawn_model_load, off the launch path; until then
+        // recordings capture the microphone alone and wait for it
         let mut audio_processor = AudioProcessor::new(config.clone());
-        info!("Initializing audio system...");
-        if let Err(e) = audio_processor.initialize() {
-            error!(
-                "Failed to initialize audio system: {}. Typeswift will still start but recording won't work until model loads",
-                e
-            );
-        } else {
-            info!("Audio system initialized successfully");
+        audio_processor.set_model_loading(true);
+
+        // Long recordings are transcribed in chunks; show progress in the popup and menu bar
+        let shared_config = Arc::new(parking_lot::RwLock::new(config.clone()));
+        let window_manager = WindowManager::new(Arc::clone(&shared_config));
+        {
+            let state = state.clone();
+            let window_manager = window_manager.clone();
+            audio_processor.set_progress_callback(Arc::new(move |done, total| {
+                // Single-chunk recordings finish too quickly to be worth showing
+                if total < 2 {
+                    return;
+                }
+                if done == 0 {
+                    let _ = window_manager.show_without_focus();
+                }
+                state.set_progress(done, total);
+                menubar_ffi::MenuBarController::set_status(&format!("{}%", done * 100 / total));
+            }));
         }
 
-        let typing_queue = TypingQueue::new(true);
+        let typing_queue = TypingQueue::with_policy(
+            true,
+            TypingPolicy {
+                retries: config.output.typing_retries,
+                max_consecutive_failures: config.output.max_typing_failures.max(1),
+                max_chars_per_second: config.output.max_chars_per_second,
+                input_method: config.output.input_method,
+            },
+        );
+        let history = HistoryStore::open_default(&config.history);
 
-        Self {
+        let controller = Self {
             state,
-            window_manager: WindowManager::new(),
+            window_manager,
             typing_queue,
             audio_processor: Arc::new(Mutex::new(audio_processor)),
-            config: Arc::new(parking_lot::RwLock::new(config)),
-        }
+            config: shared_config,
+            history,
+            streaming: Arc::new(parking_lot::Mutex::new(None)),
+        };
+        Self::spawn_model_load(&controller.state, &controller.audio_processor, &controller.config);
+        controller
+    }
+
+    /// Load the speech model in the background so launch (and hotkey registration) doesn't
+    /// wait on it. The processor isn't held meanwhile, so an early press can start recording.
+    fn spawn_model_load(
+        state: &AppStateManager,
+        audio_processor: &Arc<Mutex<AudioProcessor>>,
+        config: &Arc<parking_lot::RwLock<Config>>,
+    ) {
+        let state = state.clone();
+        let audio_processor = Arc::clone(audio_processor);
+        let config = Arc::clone(config);
+        std::thread::spawn(move || {
+            info!("Initializing audio system...");
+            menubar_ffi::MenuBarController::set_status("Loading…");
+            let started = std::time::Instant::now();
+            let Some(model_config) = audio_processor.lock().ok().map(|audio| audio.model_config().clone()) else {
+                return;
+            };
+            let loaded = Transcriber::new(model_config);
+            let result = match audio_processor.lock() {
+                Ok(mut audio) => {
+                    audio.set_model_loading(false);
+                    loaded.and_then(|transcriber| audio.install_transcriber(transcriber))
+                }
+                Err(_) => Err(crate::error::VoicyError::ModelLoadFailed("audio processor lock poisoned".to_string())),
+            };
+            menubar_ffi::MenuBarController::set_status("");
+            match result {
+                Ok(()) => {
+                    info!("Audio system initialized in {:?}", started.elapsed());
+                    state.set_model_status(ModelStatus::Ready);
+                    if config.read().hotkeys.hands_free {
+                        Self::calibrate_noise(&state, &audio_processor);
+                    }
+                }
+                Err(e) => {
+                    menubar_ffi::MenuBarController::report_error(&e);
+                    state.set_model_status(ModelStatus::Failed(e.to_string()));
+                }
+            }
+        });
     }
 
     pub fn state(&self) -> AppStateManager { self.state.clone() }
@@ -53,6 +325,12 @@ impl AppController {
 
     pub fn config_handle(&self) -> Arc<parking_lot::RwLock<Config>> { self.config.clone() }
 
+    pub fn typing_queue(&self) -> TypingQueue { self.typing_queue.clone() }
+
+    pub fn audio_processor(&self) -> Arc<Mutex<AudioProcessor>> { Arc::clone(&self.audio_processor) }
+
+    pub fn history(&self) -> HistoryStore { self.history.clone() }
+
     pub fn start(self, receiver: Receiver<HotkeyEvent>) {
         // Spawn worker thread to process events and periodic tasks
         let AppController {
@@ -61,30 +339,407 @@ impl AppController {
             typing_queue,
             audio_processor,
             config,
+            history,
+            streaming,
         } = self;
 
+        // The keyboard monitor swallows a held push-to-talk key's repeats only while recording
+        let watched = state.clone();
+        state.add_listener(move || {
+            menubar_ffi::set_recording_active(watched.get_recording_state() == RecordingState::Recording)
+        });
+        Self::spawn_permission_watch(&state, &config);
+        Self::spawn_focus_watch(&state, &config);
+        Self::spawn_fn_conflict_check(&config);
+        Self::spawn_tap_watch();
+        Self::spawn_processing_watch(&state, &config, &window_manager, &streaming);
+
         std::thread::spawn(move || {
             info!("Controller started");
+            let mut gate = PressGate::default();
             loop {
-                match receiver.recv() {
-                    Ok(event) => {
-                        if let Err(e) = Self::handle_event(
-                            &state,
-                            &window_manager,
-                            &typing_queue,
-                            &audio_processor,
-                            &config,
-                            event,
-                        ) {
-                            error!("Failed to handle event: {}", e);
+                // Wake up for held-back presses/releases and the lock timeout as well as new events
+                let wait = match (gate.wait(), state.is_recording_locked()) {
+                    (Some(poll), true) => Some(poll.min(LOCK_POLL_INTERVAL)),
+                    (None, true) => Some(LOCK_POLL_INTERVAL),
+                    (poll, false) => poll,
+                };
+                let received = match wait {
+                    Some(timeout) => match receiver.recv_timeout(timeout) {
+                        Ok(event) => Some(event),
+                        Err(RecvTimeoutError::Timeout) => None,
+                        Err(RecvTimeoutError::Disconnected) => {
+                            warn!("Event channel disconnected, controller stopping");
+                            break;
+                        }
+                    },
+                    None => match receiver.recv() {
+                        Ok(event) => Some(event),
+                        Err(_) => {
+                            warn!("Event channel disconnected, controller stopping");
+                            break;
                         }
+                    },
+                };
+                let debounce = std::time::Duration::from_millis(config.read().hotkeys.debounce_ms);
+                let mut ready = gate.due(&state, debounce);
+                if let Some(event) = received {
+                    ready.extend(gate.accept(event, &state));
+                }
+                let mut ready: Vec<HotkeyEvent> = ready
+                    .into_iter()
+                    .filter_map(|event| Self::hands_free_event(&state, &config, event))
+                    .collect();
+                if Self::lock_timed_out(&state, &config) {
+                    ready.push(HotkeyEvent::PushToTalkReleased);
+                }
+                for event in ready {
+                    if let Err(e) = Self::handle_event(
+                        &state,
+                        &window_manager,
+                        &typing_queue,
+                        &audio_processor,
+                        &config,
+                        &history,
+                        &streaming,
+                        event,
+                    ) {
+                        menubar_ffi::MenuBarController::report_error(&e);
                     }
-                    Err(_) => {
-                        warn!("Event channel disconnected, controller stopping");
-                        break;
+                }
+            }
+        });
+    }
+
+    /// In hands-free mode a tap starts recording and the next tap stops it. With tap-to-lock,
+    /// a quick tap does the same while a longer hold is ordinary push-to-talk.
+    /// API start/stop requests map straight to press/release, and Dictate to
+    /// Clipboard toggles a recording whose text is copied instead of typed; Transcribe
+    /// Call does the same for a meeting transcript named after the detected call. Where the
+    /// text goes is only set once a start is accepted here, so a refused one can't redirect
+    /// the next dictation.
+    fn hands_free_event(
+        state: &AppStateManager,
+        config: &Arc<parking_lot::RwLock<Config>>,
+        event: HotkeyEvent,
+    ) -> Option<HotkeyEvent> {
+        match event {
+            HotkeyEvent::StartDictation => return Some(HotkeyEvent::PushToTalkPressed(None)),
+            HotkeyEvent::StopDictation => return Some(HotkeyEvent::PushToTalkReleased),
+            HotkeyEvent::StartClientDictation => {
+                if state.get_recording_state() != RecordingState::Idle {
+                    return None;
+                }
+                state.set_output_override(OutputOverride::Client);
+                return Some(HotkeyEvent::PushToTalkPressed(None));
+            }
+            HotkeyEvent::DictateToClipboard => {
+                return match state.get_recording_state() {
+                    RecordingState::Idle => {
+                        state.set_output_override(OutputOverride::Clipboard);
+                        Some(HotkeyEvent::PushToTalkPressed(None))
+                    }
+                    RecordingState::Recording => Some(HotkeyEvent::PushToTalkReleased),
+                    RecordingState::Processing => None,
+                };
+            }
+            HotkeyEvent::TranscribeCall => {
+                return match state.get_recording_state() {
+                    RecordingState::Idle => {
+                        let call = state.call_suggestion().unwrap_or_else(|| "Call".to_string());
+                        state.set_output_override(OutputOverride::Meeting(call));
+                        Some(HotkeyEvent::PushToTalkPressed(None))
+                    }
+                    RecordingState::Recording => Some(HotkeyEvent::PushToTalkReleased),
+                    RecordingState::Processing => None,
+                };
+            }
+            _ => {}
+        }
+        let (hands_free, tap_to_lock, tap_lock_ms) = {
+            let cfg = config.read();
+            (cfg.hotkeys.hands_free, cfg.hotkeys.tap_to_lock, cfg.hotkeys.tap_lock_ms)
+        };
+        let recording = state.get_recording_state() == RecordingState::Recording;
+        if hands_free {
+            return match event {
+                HotkeyEvent::PushToTalkReleased => None,
+                HotkeyEvent::PushToTalkPressed(_) if recording => Some(HotkeyEvent::PushToTalkReleased),
+                other => Some(other),
+            };
+        }
+        if !tap_to_lock {
+            return Some(event);
+        }
+        let tap = std::time::Duration::from_millis(tap_lock_ms);
+        match event {
+            HotkeyEvent::PushToTalkReleased
+                if recording
+                    && !state.is_recording_locked()
+                    && state.recording_elapsed().is_some_and(|held| held < tap) =>
+            {
+                info!("Push-to-talk tapped; recording locked until the next press");
+                state.set_recording_locked(true);
+                menubar_ffi::MenuBarController::set_recording_locked(true);
+                None
+            }
+            HotkeyEvent::PushToTalkReleased if state.is_recording_locked() => None,
+            HotkeyEvent::PushToTalkPressed(_) if recording && state.is_recording_locked() => {
+                Some(HotkeyEvent::PushToTalkReleased)
+            }
+            other => Some(other),
+        }
+    }
+
+    /// A locked recording has run past `lock_timeout_minutes`; tells the user it's being stopped
+    fn lock_timed_out(state: &AppStateManager, config: &Arc<parking_lot::RwLock<Config>>) -> bool {
+        let minutes = config.read().hotkeys.lock_timeout_minutes;
+        if minutes == 0 || !state.is_recording_locked() || state.get_recording_state() != RecordingState::Recording {
+            return false;
+        }
+        let limit = std::time::Duration::from_secs(minutes * 60);
+        if !state.recording_elapsed().is_some_and(|elapsed| elapsed >= limit) {
+            return false;
+        }
+        warn!("Locked recording reached {} min; stopping it", minutes);
+        menubar_ffi::MenuBarController::show_notification(
+            "Recording stopped",
+            &format!(
+                "Locked recordings stop after {} min. What you said is transcribed as usual; \
+                 change this with lock_timeout_minutes in settings.",
+                minutes
+            ),
+        );
+        true
+    }
+
+    /// Block until the background model load has finished, or `MODEL_WAIT_TIMEOUT` passes
+    /// Take the stopped recording out of the processor and transcribe it there, without holding
+    /// the processor: a model that hangs on it then leaves the next press free to record once
+    /// the watchdog has reset the dictation. Returns the transcript, the audio if `keep_audio`,
+    /// and the source tracks of a meeting.
+    fn transcribe_taken(
+        audio_processor: &Arc<Mutex<AudioProcessor>>,
+        prompt: TranscriptionPrompt,
+        keep_audio: bool,
+    ) -> (Transcript, Option<(Vec<f32>, u32)>, Option<SourceTracks>) {
+        let taken = audio_processor.lock().map(|mut audio| {
+            audio.set_prompt(prompt);
+            audio.take_recording()
+        });
+        let Ok(mut job) = taken else {
+            return (Transcript::default(), None, None);
+        };
+        let transcript = job.transcribe().unwrap_or_else(|e| {
+            menubar_ffi::MenuBarController::report_error(&e);
+            Transcript::default()
+        });
+        let recording = keep_audio.then(|| (job.recording().to_vec(), job.sample_rate()));
+        let tracks = job.source_tracks().cloned();
+        (transcript, recording, tracks)
+    }
+
+    fn wait_for_model(state: &AppStateManager) {
+        if state.model_status() == ModelStatus::Initializing {
+            info!("Waiting for the model to load before transcribing");
+        }
+        state.wait_for_model(MODEL_WAIT_TIMEOUT);
+    }
+
+    /// Measure the room's background noise for the adaptive VAD while nothing is being recorded.
+    /// Runs when hands-free mode starts, since a recording left to run is where noise matters.
+    /// The processor isn't held while the room is listened to, so a dictation started meanwhile
+    /// goes ahead at once and the calibration is dropped.
+    fn calibrate_noise(state: &AppStateManager, audio_processor: &Arc<Mutex<AudioProcessor>>) {
+        let state = state.clone();
+        let audio_processor = Arc::clone(audio_processor);
+        runtime::spawn(async move {
+            if state.get_recording_state() != RecordingState::Idle {
+                return;
+            }
+            let processor = Arc::clone(&audio_processor);
+            let started = smol::unblock(move || match processor.lock() {
+                Ok(mut audio) => audio.start_noise_calibration(),
+                Err(_) => Ok(false),
+            })
+            .await;
+            match started {
+                Ok(true) => {}
+                Ok(false) => return,
+                Err(e) => {
+                    warn!("Noise calibration skipped: {}", e);
+                    return;
+                }
+            }
+            Timer::after(NOISE_CALIBRATION).await;
+            let calibrated = smol::unblock(move || match audio_processor.lock() {
+                Ok(mut audio) => audio.finish_noise_calibration(),
+                Err(_) => Ok(None),
+            })
+            .await;
+            match calibrated {
+                Ok(Some(threshold)) => info!("Calibrated to background noise; VAD threshold {:.4}", threshold),
+                Ok(None) => {}
+                Err(e) => warn!("Noise calibration skipped: {}", e),
+            }
+        });
+    }
+
+    /// Apply a Quick Settings menu choice and save it; the UI loop refreshes the menu
+    fn apply_quick_setting(config: &Arc<parking_lot::RwLock<Config>>, setting: QuickSetting) {
+        let mut cfg = config.write();
+        match setting {
+            QuickSetting::ToggleTyping => cfg.output.enable_typing = !cfg.output.enable_typing,
+            QuickSetting::ToggleStreaming => cfg.streaming.enabled = !cfg.streaming.enabled,
+            QuickSetting::ToggleHandsFree => {
+                cfg.hotkeys.hands_free = !cfg.hotkeys.hands_free;
+                // Where the user left it rather than a setting, so it goes to ui-state.json
+                let hands_free = cfg.hotkeys.hands_free;
+                crate::ui_state::update(|state| state.hands_free = Some(hands_free));
+                return;
+            }
+            QuickSetting::TogglePresentation => {
+                cfg.ui.presentation_mode = !cfg.ui.presentation_mode;
+                set_presentation_mode(cfg.ui.presentation_mode);
+            }
+            QuickSetting::ToggleRehearsal => {
+                cfg.output.rehearsal = !cfg.output.rehearsal;
+                info!("Rehearsal {}", if cfg.output.rehearsal { "on" } else { "off" });
+            }
+            QuickSetting::Profile(index) => {
+                cfg.active_profile = index.and_then(|i| cfg.profiles.get(i)).map(|p| p.name.clone());
+            }
+            QuickSetting::Language(index) => {
+                cfg.model.language = index.and_then(|i| SUPPORTED_LANGUAGES.get(i)).map(|(code, _)| code.to_string());
+            }
+            QuickSetting::Output(_) | QuickSetting::CycleOutput => {
+                let destinations = cfg.output_destinations();
+                let index = match setting {
+                    QuickSetting::Output(index) => index,
+                    _ => destinations.iter().position(|d| *d == cfg.output.destination).map_or(0, |i| i + 1),
+                };
+                cfg.output.destination = destinations[index % destinations.len()].clone();
+                info!("Output: {}", cfg.output.destination.label());
+                menubar_ffi::MenuBarController::show_notification("Output", &cfg.output.destination.label());
+            }
+            // Loaded first and saved once swapped in, by switch_model
+            QuickSetting::Model(_) | QuickSetting::Preset(_) => return,
+        }
+        let to_save = cfg.clone();
+        drop(cfg);
+        save_config(&to_save);
+    }
+
+    /// Switch to the model `target` makes of the current one (an installed model or a preset,
+    /// shown as `label`). The new model loads in the background while dictation keeps using
+    /// the current one, then replaces it between dictations. The swift backend holds one model
+    /// per process, so swift to swift unloads first instead and recordings meanwhile wait for
+    /// the load, as at launch. A later pick supersedes this one.
+    fn switch_model(
+        state: &AppStateManager,
+        audio_processor: &Arc<Mutex<AudioProcessor>>,
+        config: &Arc<parking_lot::RwLock<Config>>,
+        label: String,
+        target: impl FnOnce(&ModelConfig) -> ModelConfig + Send + 'static,
+    ) {
+        static SWITCH: AtomicU64 = AtomicU64::new(0);
+        // One switch at a time, so two swift models are never loading at once
+        static SWITCHING: Lazy<smol::lock::Mutex<()>> = Lazy::new(|| smol::lock::Mutex::new(()));
+        let switch = SWITCH.fetch_add(1, Ordering::SeqCst) + 1;
+        let superseded = move || SWITCH.load(Ordering::SeqCst) != switch;
+
+        let state = state.clone();
+        let audio_processor = Arc::clone(audio_processor);
+        let config = Arc::clone(config);
+        runtime::spawn(async move {
+            let _switching = SWITCHING.lock().await;
+            let previous = config.read().model.clone();
+            let model = target(&previous);
+            if superseded() || model == previous {
+                return;
+            }
+            info!("Switching to {}", label);
+            let unload_first =
+                previous.backend == TranscriberBackend::Swift && model.backend == TranscriberBackend::Swift;
+            if unload_first {
+                // Between dictations, so none is split across models
+                loop {
+                    if superseded() {
+                        return;
+                    }
+                    if state.get_recording_state() == RecordingState::Idle {
+                        let processor = Arc::clone(&audio_processor);
+                        let target = model.clone();
+                        let unloaded = smol::unblock(move || match processor.lock() {
+                            Ok(mut audio) => Some(audio.unload_transcriber(target)),
+                            Err(_) => None,
+                        })
+                        .await;
+                        match unloaded {
+                            Some(true) => break,
+                            Some(false) => {}
+                            None => return,
+                        }
+                    }
+                    Timer::after(MODEL_SWAP_POLL_INTERVAL).await;
+                }
+                state.set_model_status(ModelStatus::Initializing);
+            }
+            menubar_ffi::MenuBarController::set_status(&format!("Loading {}…", label));
+            let loaded = {
+                let model = model.clone();
+                smol::unblock(move || Transcriber::new(model)).await
+            };
+            menubar_ffi::MenuBarController::set_status("");
+            let mut transcriber = match loaded {
+                Ok(transcriber) => transcriber,
+                Err(e) => {
+                    menubar_ffi::MenuBarController::report_error(&e);
+                    if unload_first {
+                        // Back to the old model, which the next recording loads
+                        let processor = Arc::clone(&audio_processor);
+                        smol::unblock(move || {
+                            if let Ok(mut audio) = processor.lock() {
+                                audio.restore_model(previous);
+                            }
+                        })
+                        .await;
+                        state.set_model_status(ModelStatus::Failed(e.to_string()));
+                    }
+                    return;
+                }
+            };
+            loop {
+                if superseded() && !unload_first {
+                    // Not both swift, so dropping it leaves the current model loaded
+                    info!("Dropping {}; another model was picked", label);
+                    return;
+                }
+                // Waiting for Idle lets an in-flight job finish on the old model
+                if unload_first || state.get_recording_state() == RecordingState::Idle {
+                    let processor = Arc::clone(&audio_processor);
+                    let target = model.clone();
+                    let swapped = smol::unblock(move || match processor.lock() {
+                        Ok(mut audio) => Some(audio.swap_transcriber(transcriber, target)),
+                        Err(_) => None,
+                    })
+                    .await;
+                    match swapped {
+                        Some(Ok(())) => break,
+                        Some(Err(refused)) => transcriber = refused,
+                        None => return,
                     }
                 }
+                Timer::after(MODEL_SWAP_POLL_INTERVAL).await;
             }
+            state.set_model_status(ModelStatus::Ready);
+            let to_save = {
+                let mut cfg = config.write();
+                cfg.model = model;
+                cfg.clone()
+            };
+            save_config(&to_save);
+            menubar_ffi::MenuBarController::show_notification("Model switched", &label);
         });
     }
 
@@ -94,70 +749,473 @@ impl AppController {
         typing_queue: &TypingQueue,
         audio_processor: &Arc<Mutex<AudioProcessor>>,
         config: &Arc<parking_lot::RwLock<Config>>,
+        history: &HistoryStore,
+        streaming: &Arc<parking_lot::Mutex<Option<StreamingManager>>>,
         event: HotkeyEvent,
     ) -> VoicyResult<()> {
         info!("Controller handling event: {:?}", event);
         match event {
-            HotkeyEvent::OpenPreferences => {
+            HotkeyEvent::Relaunch => {
+                info!("Relaunching");
+                Self::relaunch(state, config);
+            }
+            HotkeyEvent::QuickSetting(QuickSetting::Model(index)) => {
+                match installed_models(&config.read().model).into_iter().nth(index) {
+                    Some(choice) => {
+                        let label = choice.label.clone();
+                        Self::switch_model(state, audio_processor, config, label, move |model| choice.apply(model))
+                    }
+                    None => warn!("No installed model #{} to switch to", index),
+                }
+            }
+            HotkeyEvent::QuickSetting(QuickSetting::Preset(index)) => {
+                if let Some(&preset) = ModelPreset::ALL.get(index) {
+                    let label = format!("{} preset", preset.label());
+                    Self::switch_model(state, audio_processor, config, label, move |model| ModelConfig {
+                        preset: Some(preset),
+                        ..model.clone()
+                    })
+                }
+            }
+            HotkeyEvent::QuickSetting(setting) => {
+                Self::apply_quick_setting(config, setting);
+                if setting == QuickSetting::ToggleHandsFree && config.read().hotkeys.hands_free {
+                    Self::calibrate_noise(state, audio_processor);
+                }
+            }
+            HotkeyEvent::SpeakLast => match state.last_output() {
+                Some(text) => menubar_ffi::speak(&text),
+                None => menubar_ffi::speak("Nothing transcribed yet."),
+            },
+            HotkeyEvent::StopTyping => typing_queue.abort(),
+            HotkeyEvent::MarkMoment => {
+                if !matches!(state.output_override(), OutputOverride::Meeting(_)) {
+                    debug!("Marker ignored: no meeting is being transcribed");
+                } else if let Some(at) = state.add_marker() {
+                    info!("Marker at {}", crate::history::format_offset(at));
+                    menubar_ffi::MenuBarController::show_notification(
+                        "Moment marked",
+                        &format!("At {} into the meeting", crate::history::format_offset(at)),
+                    );
+                }
+            }
+            HotkeyEvent::GenerateSupportBundle => {
+                let snapshot = config.read().clone();
+                std::thread::spawn(move || match crate::support::generate_support_bundle(&snapshot) {
+                    Ok(path) => menubar_ffi::reveal_in_finder(&path),
+                    Err(e) => {
+                        error!("Could not create support bundle: {}", e);
+                        menubar_ffi::MenuBarController::show_alert(
+                            "Couldn't create support bundle",
+                            &e.to_string(),
+                        );
+                    }
+                });
+            }
+            HotkeyEvent::ResetPopupPosition => window_manager.reset_popup_position(),
+            HotkeyEvent::StartDictation
+            | HotkeyEvent::StopDictation
+            | HotkeyEvent::StartClientDictation
+            | HotkeyEvent::DictateToClipboard
+            | HotkeyEvent::TranscribeCall => {
+                // Mapped to push-to-talk events by hands_free_event
+            }
+            HotkeyEvent::OpenPreferences | HotkeyEvent::OpenHistory | HotkeyEvent::OpenCommandPalette => {
                 // Handled by UI layer to open a separate GPUI window.
                 // No changes to the main status window here.
             }
-            HotkeyEvent::PushToTalkPressed => {
-                if state.can_start_recording() {
+            HotkeyEvent::PushToTalkPressed(_) if state.is_paused() => {
+                info!("Push-to-talk ignored while paused for Focus");
+                state.set_output_override(OutputOverride::None);
+            }
+            HotkeyEvent::PushToTalkPressed(profile) => {
+                if state.transition(RecordingEvent::Start).is_ok() {
                     info!("Push-to-talk PRESSED - Starting recording");
-                    state.set_recording_state(RecordingState::Recording);
                     state.clear_transcription();
+                    // A profile's own binding applies that profile to this dictation
+                    let profile = profile.and_then(|i| config.read().profiles.get(i).map(|p| p.name.clone()));
+                    if let Some(ref name) = profile {
+                        info!("Dictating with profile \"{}\"", name);
+                    }
+                    state.set_dictation_profile(profile);
                     window_manager.show_without_focus()?;
 
                     // Update menu bar icon
                     menubar_ffi::MenuBarController::set_recording(true);
+                    // Hands-free recordings run on their own like tapped ones (meetings aside)
+                    if config.read().hotkeys.hands_free && !matches!(state.output_override(), OutputOverride::Meeting(_)) {
+                        state.set_recording_locked(true);
+                        menubar_ffi::MenuBarController::set_recording_locked(true);
+                    }
+                    if config.read().ui.announce_recording {
+                        menubar_ffi::announce("Recording");
+                    }
 
+                    if state.model_status() == ModelStatus::Initializing {
+                        info!("Recording while the model loads; it will be transcribed once ready");
+                    }
                     if let Ok(mut audio) = audio_processor.lock() {
-                        audio.start_recording()?;
+                        let meeting = matches!(state.output_override(), OutputOverride::Meeting(_));
+                        let meetings = config.read().meetings.clone();
+                        audio.set_system_audio(meeting && meetings.system_audio, meetings.echo_cancellation);
+                        if let Err(e) = audio.start_recording() {
+                            // Roll back so the next press can retry
+                            let _ = state.transition(RecordingEvent::Cancel);
+                            state.set_output_override(OutputOverride::None);
+                            menubar_ffi::MenuBarController::set_recording(false);
+                            window_manager.hide()?;
+                            return Err(e);
+                        }
+                        // start_recording retried a failed load
+                        if matches!(state.model_status(), ModelStatus::Failed(_)) {
+                            state.set_model_status(ModelStatus::Ready);
+                        }
+                        if let Some(device) = audio.input_device() {
+                            Self::preflight_input_device(&device, state, config);
+                        }
+                    }
+                    let streams = {
+                        let cfg = config.read();
+                        cfg.streaming.enabled
+                            && TranscriberCapabilities::of(cfg.model.backend).streaming
+                            && cfg.output.enable_typing
+                            && cfg.output.target == OutputTarget::Cursor
+                            && cfg.output.destination == OutputDestination::Cursor
+                            && !cfg.output.rehearsal
+                            && cfg
+                                .sink_for(
+                                    state.dictation_profile().as_deref(),
+                                    menubar_ffi::frontmost_bundle_id().as_deref(),
+                                )
+                                .is_none()
+                    } && state.output_override() == OutputOverride::None
+                        && !menubar_ffi::own_app_frontmost();
+                    // Rehearsals still preview the words in the popup as they come
+                    let previews = {
+                        let cfg = config.read();
+                        cfg.output.rehearsal
+                            && cfg.streaming.enabled
+                            && TranscriberCapabilities::of(cfg.model.backend).streaming
+                    };
+                    // API subscribers get partials even when nothing is typed as you speak
+                    if streams || previews || state.has_subscribers() {
+                        Self::spawn_streaming(state, typing_queue, audio_processor, config, streaming, streams);
+                    }
+                    if matches!(state.output_override(), OutputOverride::Meeting(_)) {
+                        Self::spawn_capture_drain(state, audio_processor);
                     }
                 } else {
                     warn!("Cannot start recording, state: {:?}", state.get_recording_state());
+                    state.set_output_override(OutputOverride::None);
                 }
             }
             HotkeyEvent::PushToTalkReleased => {
-                if state.can_stop_recording() {
+                if state.transition(RecordingEvent::Stop).is_ok() {
                     info!("Push-to-talk RELEASED - Stopping recording");
-                    state.set_recording_state(RecordingState::Processing);
-                    // Ensure our window is hidden and focus returns before typing
-                    window_manager.hide_and_deactivate_blocking()?;
+                    let profile = state.dictation_profile();
+                    // Modifiers still held as push-to-talk is let go pick a variant of this dictation
+                    let variant = {
+                        let cfg = config.read();
+                        let binding = profile
+                            .as_deref()
+                            .and_then(|name| cfg.profiles.iter().find(|p| p.name == name))
+                            .and_then(|p| p.push_to_talk.as_deref())
+                            .unwrap_or(&cfg.hotkeys.push_to_talk);
+                        crate::input::release_variant(&cfg.hotkeys.release_variants, binding).cloned()
+                    };
+                    if let Some(ref variant) = variant {
+                        info!("Released with {} held", variant.modifiers);
+                        if variant.clipboard && state.output_override() == OutputOverride::None {
+                            state.set_output_override(OutputOverride::Clipboard);
+                        }
+                    }
+                    // With Preferences or History in front, typing would go into Typeswift itself;
+                    // leave focus where the user is and don't type
+                    let into_self = menubar_ffi::own_app_frontmost();
+                    if into_self {
+                        info!("Typeswift is frontmost; text won't be typed");
+                        window_manager.hide()?;
+                    } else {
+                        // Ensure our window is hidden and focus returns before typing
+                        window_manager.hide_and_deactivate_blocking()?;
+                    }
 
                     // Update menu bar icon
                     menubar_ffi::MenuBarController::set_recording(false);
+                    if config.read().ui.announce_recording {
+                        menubar_ffi::announce("Recording stopped");
+                    }
 
                     // Offload finalization to a background thread to keep controller responsive
                     let typing_queue = typing_queue.clone();
                     let audio_processor = Arc::clone(audio_processor);
                     let config = Arc::clone(config);
                     let state = state.clone();
+                    let history = history.clone();
+                    let window_manager = window_manager.clone();
+                    let streaming = Arc::clone(streaming);
+                    let recorded_ms = state.recording_elapsed().map_or(0, |d| d.as_millis() as u64);
+                    let job = state.processing_job();
                     std::thread::spawn(move || {
                         let before_mb = current_rss_mb();
-                        let final_text = if let Ok(mut audio) = audio_processor.lock() {
-                            audio.stop_recording().unwrap_or_default()
-                        } else {
-                            String::new()
+                        // Punctuation style and continuation context follow the app we're typing into
+                        let bundle_id = menubar_ffi::frontmost_bundle_id();
+                        let prompt = {
+                            let cfg = config.read();
+                            let capabilities = TranscriberCapabilities::of(cfg.model.backend);
+                            TranscriptionPrompt {
+                                context: cfg.model.context_continuation
+                                    .then(|| state.recent_context(bundle_id.as_deref(), cfg.model.context_words))
+                                    .flatten(),
+                                bias_phrases: cfg.bias_phrases_for(profile.as_deref(), bundle_id.as_deref()),
+                                language: cfg.language_for(profile.as_deref(), bundle_id.as_deref()).filter(|code| {
+                                    let supported = capabilities.supports_language(code);
+                                    if !supported {
+                                        warn!("Language {:?} isn't supported by this model; auto-detecting", code);
+                                    }
+                                    supported
+                                }),
+                            }
+                        };
+                        let (keep_audio, audio_format) = {
+                            let cfg = config.read();
+                            (cfg.history.keep_audio, cfg.history.audio_format)
+                        };
+                        // Stop the microphone now; a recording made during launch then waits for the model
+                        if let Ok(mut audio) = audio_processor.lock() {
+                            if let Err(e) = audio.stop_capture() {
+                                warn!("Failed to stop capture: {}", e);
+                            }
+                        }
+                        Self::wait_for_model(&state);
+                        let (transcript, recording, tracks) =
+                            Self::transcribe_taken(&audio_processor, prompt, keep_audio);
+                        if state.progress().is_some() {
+                            state.clear_progress();
+                            menubar_ffi::MenuBarController::set_status("");
+                            let _ = window_manager.hide();
+                        }
+                        let system_command = match parse_command(&transcript.text, &config.read().commands) {
+                            Command::System(command) if state.output_override() == OutputOverride::None => Some(command),
+                            _ => None,
                         };
+                        let mut final_text =
+                            post_process(&transcript.text, &config.read(), profile.as_deref(), bundle_id.as_deref());
+                        if let Some(variant) = variant.filter(|v| !v.stages.is_empty()) {
+                            let cfg = config.read();
+                            let ctx = StageContext {
+                                config: &cfg,
+                                raw_text: &transcript.text,
+                                bundle_id: bundle_id.as_deref(),
+                                profile: profile.as_deref(),
+                            };
+                            final_text = Pipeline::from_stages(&variant.stages).run(&final_text, &ctx);
+                        }
+                        // The profile's shape for its output, such as a checklist item; meetings keep their own
+                        let meeting = matches!(state.output_override(), OutputOverride::Meeting(_));
+                        if !final_text.is_empty() && !meeting && system_command.is_none() {
+                            let cfg = config.read();
+                            if let Some(template) = cfg.template_for(profile.as_deref(), bundle_id.as_deref()) {
+                                final_text = crate::sink::apply_template(template, &final_text, bundle_id.as_deref());
+                            }
+                        }
+                        // Stops the streaming loop from typing further partials
+                        let streamed = streaming.lock().take();
 
                         // Ensure PTT modifiers are fully released and focus returned before typing
                             info!("Waiting for modifier release before typing...");
                             let _ = menubar_ffi::wait_modifiers_released(300);
                         // Small delay for app focus settle
                         std::thread::sleep(std::time::Duration::from_millis(80));
-                        info!("Queueing typing: len={}, add_space={} ", final_text.len(), config.read().output.add_space_between_utterances);
+                        info!("Queueing typing: len={}", final_text.len());
+
+                        let (typing_enabled, target, fallback, sink, clipboard_only) = {
+                            let cfg = config.read();
+                            let sink = cfg.sink_for(profile.as_deref(), bundle_id.as_deref()).cloned();
+                            let clipboard_only = cfg.output.destination == OutputDestination::Clipboard;
+                            let output = &cfg.output;
+                            (output.enable_typing, output.target, output.scratchpad_fallback, sink, clipboard_only)
+                        };
+                        debug!("Typing decision -> enabled: {}, target: {:?}, text_len: {}", typing_enabled, target, final_text.len());
 
-                        let typing_enabled = config.read().output.enable_typing;
-                        debug!("Typing decision -> enabled: {}, text_len: {}", typing_enabled, final_text.len());
+                        // Keep the text in the scratchpad rather than typing into nothing (or into Typeswift)
+                        let to_scratchpad = !final_text.is_empty()
+                            && match target {
+                                OutputTarget::Scratchpad => true,
+                                OutputTarget::Cursor => {
+                                    typing_enabled
+                                        && fallback
+                                        && (into_self || menubar_ffi::focused_text_target() == Some(false))
+                                }
+                            };
+
+                        let markers = state.take_markers();
+                        let markers_md = crate::history::format_markers(&markers, &transcript.tokens);
+                        // Kept beside the emitted text, so what formatting changed stays visible
+                        let transcript_text = transcript.text.clone();
+                        // Who said what in a meeting, while the word timings are at hand
+                        let speakers = tracks
+                            .filter(|_| config.read().meetings.label_speakers)
+                            .map(|tracks| tracks.attribute(&transcript.tokens))
+                            .filter(|segments| !segments.is_empty());
+                        if !final_text.is_empty() {
+                            match history.append(final_text.clone(), transcript, bundle_id.clone(), recorded_ms, markers) {
+                                Ok(Some(entry)) => {
+                                    if let Some((samples, rate)) = recording {
+                                        // Encoding takes a moment; keep it off the typing path
+                                        let history = history.clone();
+                                        std::thread::spawn(move || {
+                                            if let Err(e) = history.save_audio(entry.id, &samples, rate, audio_format) {
+                                                warn!("Failed to archive audio: {}", e);
+                                            }
+                                        });
+                                    }
+                                }
+                                Ok(None) => {}
+                                Err(e) => warn!("Failed to record history: {}", e),
+                            }
+                        }
+                        // The watchdog gave up on this dictation and the app has moved on
+                        if state.processing_job() != job || state.get_recording_state() != RecordingState::Processing {
+                            warn!("Dictation finished {:?} after it was reset; not typing it", state.state_elapsed());
+                            return;
+                        }
+                        let rehearsal = config.read().output.rehearsal;
+                        // Falls through to typing the words if the command named an app that isn't there
+                        let ran_command = !rehearsal && system_command.as_ref().is_some_and(|command| {
+                            let commands = config.read().commands.clone();
+                            run_system_command(command, &commands, &typing_queue)
+                        });
+                        if !final_text.is_empty() && !ran_command {
+                            state.record_output(bundle_id.clone(), &final_text, &transcript_text);
+                        }
+                        state.publish(TranscriptUpdate::Final {
+                            text: final_text.clone(),
+                            raw_text: transcript_text,
+                        });
+                        if !final_text.is_empty() {
+                            // Processing started on release
+                            crate::metrics::record_utterance(state.state_elapsed());
+                        }
+                        let output_override = state.output_override();
+                        // A copy on the pasteboard recovers text a flaky app dropped, with Cmd+V
+                        if !final_text.is_empty()
+                            && !ran_command
+                            && !rehearsal
+                            && output_override == OutputOverride::None
+                            && config.read().output.mirror_to_clipboard
+                        {
+                            menubar_ffi::set_clipboard_text(&final_text);
+                        }
 
-                        if !final_text.is_empty() && typing_enabled {
-                            let add_space = config.read().output.add_space_between_utterances;
-                            info!("Typing final text ({} chars)", final_text.len());
-                            match typing_queue.queue_typing(final_text.clone(), add_space) {
+                        if ran_command {
+                            // Take back what streaming typed of the command's words
+                            if let Some(edit) = streamed.and_then(|mut streamed| streamed.update("")) {
+                                if let Err(e) = typing_queue.queue_edit(edit.backspaces, edit.insert) {
+                                    error!("Failed to queue correction: {}", e);
+                                }
+                            }
+                        } else if let OutputOverride::Meeting(title) = &output_override {
+                            if !final_text.is_empty() {
+                                let meetings = config.read().meetings.clone();
+                                let text = match speakers {
+                                    Some(ref segments) => crate::meetings::speaker_transcript(segments, |raw| {
+                                        post_process(raw, &config.read(), profile.as_deref(), bundle_id.as_deref())
+                                    }),
+                                    None => final_text.clone(),
+                                };
+                                match crate::meetings::save_transcript(&meetings, title, &text, &markers_md) {
+                                    Ok(path) => {
+                                        info!("Saved meeting transcript to {}", path.display());
+                                        menubar_ffi::MenuBarController::show_notification(
+                                            "Meeting transcript saved",
+                                            &path.display().to_string(),
+                                        );
+                                    }
+                                    Err(e) => {
+                                        warn!("{}", e);
+                                        menubar_ffi::set_clipboard_text(&final_text);
+                                        menubar_ffi::MenuBarController::show_notification(
+                                            "Copied to clipboard",
+                                            &format!("Couldn't save the meeting transcript: {}", e),
+                                        );
+                                    }
+                                }
+                            }
+                        } else if output_override == OutputOverride::Client {
+                            info!("Final text delivered to API client ({} chars)", final_text.len());
+                        } else if rehearsal {
+                            // Shown and kept in History, but nothing is typed, sent or copied
+                            info!("Rehearsal: not delivering {} chars", final_text.len());
+                            if let Some(command) = system_command {
+                                info!("Rehearsal: would have run {:?}", command);
+                            }
+                            if !final_text.is_empty() {
+                                let shown = LiveTranscript { stable: final_text.clone(), draft: String::new() };
+                                state.set_live_transcript(shown);
+                                menubar_ffi::MenuBarController::show_notification("Rehearsal (not typed)", &final_text);
+                            }
+                        } else if output_override == OutputOverride::Clipboard || clipboard_only {
+                            if !final_text.is_empty() {
+                                menubar_ffi::set_clipboard_text(&final_text);
+                                menubar_ffi::MenuBarController::show_notification("Copied to clipboard", &final_text);
+                            }
+                        } else if let Some(sink) = sink {
+                            if !final_text.is_empty() {
+                                if let Err(e) = crate::sink::deliver(&sink, &final_text, bundle_id.as_deref()) {
+                                    // Keep the text somewhere the user can get it back
+                                    warn!("{} failed: {}", crate::sink::describe(&sink), e);
+                                    menubar_ffi::set_clipboard_text(&final_text);
+                                    menubar_ffi::MenuBarController::show_notification(
+                                        "Copied to clipboard",
+                                        &format!("Couldn't save to {}: {}", crate::sink::describe(&sink), e),
+                                    );
+                                }
+                            }
+                        } else if to_scratchpad {
+                            info!("Adding final text to scratchpad ({} chars)", final_text.len());
+                            state.push_scratchpad(final_text);
+                        } else if into_self && typing_enabled && target == OutputTarget::Cursor {
+                            if !final_text.is_empty() {
+                                menubar_ffi::set_clipboard_text(&final_text);
+                                menubar_ffi::MenuBarController::show_notification(
+                                    "Copied to clipboard",
+                                    "A Typeswift window was in front, so the text wasn't typed.",
+                                );
+                            }
+                        } else if let Some(mut streamed) = streamed.filter(|m| !m.typed().is_empty()) {
+                            // Streaming already typed a draft; correct it to the final text
+                            let context = streamed.context();
+                            let keep = config.read().bias_phrases_for(profile.as_deref(), bundle_id.as_deref());
+                            let text = context.apply_case(&final_text, &keep);
+                            let target = streamed_text(&text, context.leading_space);
+                            if let Some(edit) = streamed.update(&target) {
+                                info!("Correcting streamed text: {} backspaces, {} chars", edit.backspaces, edit.insert.len());
+                                if let Err(e) = typing_queue.queue_edit(edit.backspaces, edit.insert) {
+                                    error!("Failed to queue correction: {}", e);
+                                }
+                            } else if streamed.typed() != target {
+                                warn!("Final text differs beyond max_correction_distance; leaving streamed text as typed");
+                            }
+                        } else if !final_text.is_empty() && typing_enabled && !menubar_ffi::accessibility_trusted() {
+                            // Typing would fail without permission; don't lose the text
+                            warn!("Accessibility permission missing; copying text to clipboard");
+                            menubar_ffi::set_clipboard_text(&final_text);
+                            menubar_ffi::MenuBarController::show_notification(
+                                "Copied to clipboard",
+                                "Typeswift needs Accessibility permission to type.",
+                            );
+                        } else if !final_text.is_empty() && typing_enabled {
+                            let (context, text) = {
+                                let cfg = config.read();
+                                let context = cursor_context(&cfg);
+                                let keep = cfg.bias_phrases_for(profile.as_deref(), bundle_id.as_deref());
+                                (context, context.apply_case(&final_text, &keep))
+                            };
+                            info!("Typing final text ({} chars, {:?})", text.len(), context);
+                            match typing_queue.queue_typing(text, context.leading_space) {
                                 Ok(()) => info!("Typing queued successfully"),
-                                Err(e) => error!("Failed to queue typing: {}", e),
+                                Err(e) => menubar_ffi::MenuBarController::report_error(&e),
                             }
                         }
 
@@ -166,7 +1224,8 @@ impl AppController {
                             let delta = a - b;
                             info!("Memory RSS before: {:.2} MB, after: {:.2} MB, delta: {:+.2} MB", b, a, delta);
                         }
-                        state.set_recording_state(RecordingState::Idle);
+                        state.set_output_override(OutputOverride::None);
+                        let _ = state.transition(RecordingEvent::Finish);
                         info!("Processing complete; state=Idle");
                     });
                 } else {
@@ -186,4 +1245,504 @@ impl AppController {
 
         Ok(())
     }
+
+    /// With push-to-talk on Fn/Globe, warn once at launch if macOS also acts on the key
+    /// and offer to change that setting or have the event tap swallow the key
+    fn spawn_fn_conflict_check(config: &Arc<parking_lot::RwLock<Config>>) {
+        let hotkeys = config.read().hotkeys.clone();
+        let on_fn = crate::input::parse_modifier_chord(&hotkeys.push_to_talk)
+            .is_some_and(|chord| chord.is_fn_only());
+        if !on_fn || hotkeys.suppress_system_fn || hotkeys.ignore_fn_conflict {
+            return;
+        }
+        let usage = menubar_ffi::fn_key_usage();
+        if usage == menubar_ffi::FnKeyUsage::Nothing {
+            return;
+        }
+        warn!("Fn/Globe is push-to-talk but macOS is set to \"{}\" on Fn", usage.label());
+        let config = Arc::clone(config);
+        std::thread::spawn(move || {
+            let choice = menubar_ffi::MenuBarController::choose(
+                "macOS also uses the Fn key",
+                &format!(
+                    "Pressing Fn (🌐) is set to “{}” in System Settings → Keyboard, so it may fire along with \
+                     push-to-talk. Set “Press 🌐 key to” to “Do Nothing”, or let Typeswift block the system \
+                     action while it runs.",
+                    usage.label()
+                ),
+                &["Open Keyboard Settings", "Block While Typeswift Runs", "Ignore"],
+            );
+            let mut cfg = config.write();
+            match choice {
+                0 => {
+                    menubar_ffi::open_keyboard_settings();
+                    return;
+                }
+                1 => {
+                    cfg.hotkeys.suppress_system_fn = true;
+                    menubar_ffi::set_suppress_system_fn(true);
+                }
+                _ => cfg.hotkeys.ignore_fn_conflict = true,
+            }
+            let to_save = cfg.clone();
+            drop(cfg);
+            save_config(&to_save);
+        });
+    }
+
+    /// Poll Accessibility permission so a revocation (e.g. after an update) is reported once,
+    /// with steps to fix it, instead of every typing operation failing silently
+    fn spawn_permission_watch(state: &AppStateManager, config: &Arc<parking_lot::RwLock<Config>>) {
+        let state = state.clone();
+        let config = Arc::clone(config);
+        runtime::spawn(async move {
+            let mut trusted = menubar_ffi::accessibility_trusted();
+            if !trusted {
+                warn!("Accessibility permission not granted at startup");
+                menubar_ffi::MenuBarController::set_typing_health(false);
+            }
+            loop {
+                Timer::after(PERMISSION_POLL_INTERVAL).await;
+                let now = menubar_ffi::accessibility_trusted();
+                if now == trusted {
+                    continue;
+                }
+                trusted = now;
+                menubar_ffi::MenuBarController::set_typing_health(now);
+                if now {
+                    info!("Accessibility permission restored");
+                    // The keyboard monitor only picks up the grant after a restart
+                    let relaunch = smol::unblock(|| {
+                        menubar_ffi::MenuBarController::confirm(
+                            "Accessibility restored",
+                            "Relaunch Typeswift so the push-to-talk shortcut works again?",
+                            "Relaunch",
+                        )
+                    });
+                    if relaunch.await {
+                        let (state, config) = (state.clone(), Arc::clone(&config));
+                        smol::unblock(move || Self::relaunch(&state, &config)).await;
+                    }
+                } else {
+                    warn!("Accessibility permission revoked");
+                    smol::unblock(|| {
+                        menubar_ffi::MenuBarController::show_alert(
+                            "Typeswift lost Accessibility permission",
+                            "macOS sometimes resets this after an update.\n\n\
+                             1. Open System Settings → Privacy & Security → Accessibility.\n\
+                             2. Remove Typeswift with “–”, then add it again and switch it on.\n\
+                             3. Relaunch Typeswift.\n\n\
+                             Until then, dictations are copied to the clipboard instead of typed.",
+                        )
+                    })
+                    .await;
+                }
+            }
+        });
+    }
+
+    /// Watch the modifier-chord event tap and reinstall it when it stays dead. macOS switches it
+    /// off under load or around security prompts; the monitor re-enables it when told, but a tap
+    /// can die without any event saying so. Tells the user if push-to-talk is gone.
+    fn spawn_tap_watch() {
+        let (sender, receiver) = std::sync::mpsc::channel();
+        menubar_ffi::register_tap_health_callback(sender);
+        runtime::spawn(async move {
+            let mut notified = false;
+            loop {
+                Timer::after(TAP_POLL_INTERVAL).await;
+                loop {
+                    match receiver.try_recv() {
+                        Ok(reason) => warn!("Keyboard event tap disabled ({:?}); re-enabled", reason),
+                        Err(std::sync::mpsc::TryRecvError::Empty) => break,
+                        Err(std::sync::mpsc::TryRecvError::Disconnected) => return,
+                    }
+                }
+                if menubar_ffi::keyboard_tap_status() != menubar_ffi::TapStatus::Disabled {
+                    continue;
+                }
+                warn!("Keyboard event tap is disabled; reinstalling");
+                if menubar_ffi::reinstall_keyboard_tap()
+                    && menubar_ffi::keyboard_tap_status() == menubar_ffi::TapStatus::Enabled
+                {
+                    info!("Keyboard event tap reinstalled");
+                    notified = false;
+                } else if !notified {
+                    error!("Keyboard event tap could not be reinstalled");
+                    menubar_ffi::MenuBarController::show_notification(
+                        "Push-to-talk stopped working",
+                        "macOS switched off Typeswift's keyboard monitor. Check Accessibility in \
+                         System Settings, then relaunch Typeswift.",
+                    );
+                    notified = true;
+                }
+            }
+        });
+    }
+
+    /// Give up on a dictation that has sat in Processing for `processing_timeout_secs` without
+    /// progress (a hung backend or pipeline stage), so push-to-talk works again. Whatever the
+    /// streaming preview had is copied to the clipboard; the job's own result, if it ever
+    /// arrives, goes to History only.
+    fn spawn_processing_watch(
+        state: &AppStateManager,
+        config: &Arc<parking_lot::RwLock<Config>>,
+        window_manager: &WindowManager,
+        streaming: &Arc<parking_lot::Mutex<Option<StreamingManager>>>,
+    ) {
+        let state = state.clone();
+        let config = Arc::clone(config);
+        let window_manager = window_manager.clone();
+        let streaming = Arc::clone(streaming);
+        runtime::spawn(async move {
+            // The job being watched, its last progress and when that last changed
+            let mut watched: Option<(u64, Option<(usize, usize)>, std::time::Instant)> = None;
+            loop {
+                Timer::after(PROCESSING_POLL_INTERVAL).await;
+                if state.get_recording_state() != RecordingState::Processing {
+                    watched = None;
+                    continue;
+                }
+                let job = state.processing_job();
+                let progress = state.progress();
+                // Chunks finishing and the model still loading both count as progress
+                let loading = state.model_status() == ModelStatus::Initializing;
+                let since = match watched {
+                    Some((j, p, since)) if j == job && p == progress && !loading => since,
+                    _ => {
+                        watched = Some((job, progress, std::time::Instant::now()));
+                        continue;
+                    }
+                };
+                let timeout = config.read().model.processing_timeout_secs;
+                if timeout == 0 || since.elapsed() < std::time::Duration::from_secs(timeout) {
+                    continue;
+                }
+                watched = None;
+                Self::reset_stuck_processing(&state, &config, &window_manager, &streaming);
+            }
+        });
+    }
+
+    fn reset_stuck_processing(
+        state: &AppStateManager,
+        config: &Arc<parking_lot::RwLock<Config>>,
+        window_manager: &WindowManager,
+        streaming: &Arc<parking_lot::Mutex<Option<StreamingManager>>>,
+    ) {
+        let live = state.live_transcript();
+        error!(
+            "Dictation stuck in Processing for {:?}; resetting to Idle. job={}, model={:?}, backend={:?}, \
+             output={:?}, progress={:?}, streamed={}, preview={} chars, recorded={:?}, rss={:?} MB",
+            state.state_elapsed(),
+            state.processing_job(),
+            state.model_status(),
+            config.read().model.backend,
+            state.output_override(),
+            state.progress(),
+            streaming.lock().is_some(),
+            live.text().0.len(),
+            state.recording_elapsed(),
+            current_rss_mb(),
+        );
+        // Stops the streaming loop from typing further partials
+        streaming.lock().take();
+        if state.transition(RecordingEvent::Cancel).is_err() {
+            return;
+        }
+        state.clear_progress();
+        state.set_output_override(OutputOverride::None);
+        state.set_dictation_profile(None);
+        menubar_ffi::MenuBarController::set_status("");
+        let _ = window_manager.hide();
+        let (text, _) = live.text();
+        if text.trim().is_empty() {
+            menubar_ffi::MenuBarController::show_notification(
+                "Dictation timed out",
+                "Transcription stopped responding and was cancelled.",
+            );
+        } else {
+            menubar_ffi::set_clipboard_text(text.trim());
+            menubar_ffi::MenuBarController::show_notification(
+                "Dictation timed out",
+                "Transcription stopped responding. What was heard so far is on the clipboard.",
+            );
+        }
+    }
+
+    /// Follow macOS Focus: go quiet and/or pause push-to-talk as configured in `[focus]`
+    fn spawn_focus_watch(state: &AppStateManager, config: &Arc<parking_lot::RwLock<Config>>) {
+        let state = state.clone();
+        let config = Arc::clone(config);
+        runtime::spawn(async move {
+            loop {
+                let focus = config.read().focus.clone();
+                let (quiet, paused) = if focus.is_enabled() && menubar_ffi::focus_active() == Some(true) {
+                    let mode = menubar_ffi::focus_mode_name();
+                    (focus.quiet, focus.pauses(mode.as_deref()))
+                } else {
+                    (false, false)
+                };
+                if quiet != menubar_ffi::is_quiet_for(menubar_ffi::QuietReason::Focus) {
+                    info!("Focus quiet mode {}", if quiet { "on" } else { "off" });
+                    menubar_ffi::set_quiet(menubar_ffi::QuietReason::Focus, quiet);
+                }
+                if paused != state.is_paused() {
+                    info!("Push-to-talk {} for Focus", if paused { "paused" } else { "resumed" });
+                    state.set_paused(paused);
+                    menubar_ffi::MenuBarController::set_status(if paused { "Paused" } else { "" });
+                }
+                Timer::after(FOCUS_POLL_INTERVAL).await;
+            }
+        });
+    }
+
+    /// Save config and any scratchpad text, start a successor process and quit
+    fn relaunch(state: &AppStateManager, config: &Arc<parking_lot::RwLock<Config>>) {
+        config_store::save(&config.read());
+        config_store::flush();
+        let pending = state.scratchpad_entries();
+        if !pending.is_empty() {
+            if let Some(path) = pending_scratchpad_path() {
+                match serde_json::to_string(&pending) {
+                    Ok(json) => {
+                        if let Err(e) = std::fs::write(&path, json) {
+                            warn!("Failed to save scratchpad before relaunch: {}", e);
+                        }
+                    }
+                    Err(e) => warn!("Failed to encode scratchpad: {}", e),
+                }
+            }
+        }
+        match relaunch::spawn_successor() {
+            Ok(()) => menubar_ffi::MenuBarController::quit(),
+            Err(e) => {
+                error!("Relaunch failed: {}", e);
+                menubar_ffi::MenuBarController::show_alert(
+                    "Couldn't relaunch Typeswift",
+                    &format!("Quit and reopen it manually ({}).", e),
+                );
+            }
+        }
+    }
+
+    /// Keep a long (meeting) recording from overflowing the capture buffer; streaming
+    /// drains it as a side effect, but meetings usually run without streaming
+    fn spawn_capture_drain(state: &AppStateManager, audio_processor: &Arc<Mutex<AudioProcessor>>) {
+        let state = state.clone();
+        let audio_processor = Arc::clone(audio_processor);
+        runtime::spawn(async move {
+            loop {
+                Timer::after(CAPTURE_DRAIN_INTERVAL).await;
+                if state.get_recording_state() != RecordingState::Recording {
+                    break;
+                }
+                // The processor may be held for a while (e.g. a partial transcription); wait off
+                // the executor so the other background tasks keep running
+                let processor = Arc::clone(&audio_processor);
+                let drained = smol::unblock(move || processor.lock().map(|mut audio| audio.drain_capture()).is_ok());
+                if !drained.await {
+                    break;
+                }
+            }
+        });
+    }
+
+    /// While recording, periodically re-transcribe the audio so far, publish it to API
+    /// subscribers and, with `type_partials`, type the revision as a minimal correction
+    /// of what was typed before
+    fn spawn_streaming(
+        state: &AppStateManager,
+        typing_queue: &TypingQueue,
+        audio_processor: &Arc<Mutex<AudioProcessor>>,
+        config: &Arc<parking_lot::RwLock<Config>>,
+        streaming: &Arc<parking_lot::Mutex<Option<StreamingManager>>>,
+        type_partials: bool,
+    ) {
+        let cfg = config.read().streaming.clone();
+        if type_partials {
+            let context = cursor_context(&config.read());
+            *streaming.lock() = Some(StreamingManager::new(cfg.max_correction_distance, context));
+        }
+        let state = state.clone();
+        let typing_queue = typing_queue.clone();
+        let audio_processor = Arc::clone(audio_processor);
+        let config = Arc::clone(config);
+        let streaming = Arc::clone(streaming);
+        runtime::spawn(async move {
+            let bundle_id = menubar_ffi::frontmost_bundle_id();
+            let profile = state.dictation_profile();
+            loop {
+                // Re-read each round so edits in Preferences apply to the recording in progress
+                let cfg = config.read().streaming.clone();
+                Timer::after(std::time::Duration::from_millis(cfg.process_interval_ms.max(100))).await;
+                if state.get_recording_state() != RecordingState::Recording {
+                    break;
+                }
+                let audio = Arc::clone(&audio_processor);
+                let min_audio_ms = cfg.min_initial_audio_ms;
+                let partial = smol::unblock(move || {
+                    audio.lock().ok().map(|mut audio| audio.partial_transcript(min_audio_ms))
+                })
+                .await;
+                let partial = match partial {
+                    None => break,
+                    Some(Ok(Some(t))) if !t.text.is_empty() => t,
+                    Some(Ok(_)) => continue,
+                    Some(Err(e)) => {
+                        warn!("Streaming partial failed: {}", e);
+                        continue;
+                    }
+                };
+                state.set_transcription(partial.text.clone());
+                // Recent words are still likely to change; the popup dims them and typing
+                // holds them back until they settle
+                let stable = stable_text(&partial, cfg.stabilization_ms);
+                state.set_live_transcript(LiveTranscript::split(&partial.text, &stable));
+                if state.has_subscribers() {
+                    let text =
+                        post_process_partial(&partial.text, &config.read(), profile.as_deref(), bundle_id.as_deref());
+                    state.publish(TranscriptUpdate::Partial(text));
+                }
+                if !type_partials {
+                    continue;
+                }
+                // Keystrokes sent while the hotkey's modifiers are held would become shortcuts
+                if menubar_ffi::any_modifier_down() {
+                    continue;
+                }
+                if stable.is_empty() {
+                    continue;
+                }
+                let (text, keep) = {
+                    let cfg = config.read();
+                    (
+                        post_process_partial(&stable, &cfg, profile.as_deref(), bundle_id.as_deref()),
+                        cfg.bias_phrases_for(profile.as_deref(), bundle_id.as_deref()),
+                    )
+                };
+                let mut guard = streaming.lock();
+                let Some(manager) = guard.as_mut() else { break };
+                let context = manager.context();
+                let target = streamed_text(&context.apply_case(&text, &keep), context.leading_space);
+                if let Some(edit) = manager.update(&target) {
+                    debug!("Streaming edit: {} backspaces, {} chars", edit.backspaces, edit.insert.len());
+                    if let Err(e) = typing_queue.queue_edit(edit.backspaces, edit.insert) {
+                        error!("Failed to queue streaming edit: {}", e);
+                    }
+                }
+            }
+            debug!("Streaming loop finished");
+        });
+    }
+
+    /// Warn once per run about Bluetooth headset mics (HFP), unless dismissed for good
+    fn preflight_input_device(
+        device: &InputDeviceInfo,
+        state: &AppStateManager,
+        config: &Arc<parking_lot::RwLock<Config>>,
+    ) {
+        static WARNED: Mutex<Vec<String>> = Mutex::new(Vec::new());
+
+        if !device.is_low_quality_headset()
+            || config.read().audio.suppressed_device_warnings.contains(&device.name)
+        {
+            return;
+        }
+        if let Ok(mut warned) = WARNED.lock() {
+            if warned.contains(&device.name) {
+                return;
+            }
+            warned.push(device.name.clone());
+        }
+        warn!("Bluetooth headset input at {} Hz: {}", device.sample_rate, device.name);
+
+        let name = device.name.clone();
+        let message = format!(
+            "\"{}\" is recording in Bluetooth headset mode ({} kHz), which noticeably lowers accuracy. \
+             For best results, switch the input to your Mac's built-in microphone in System Settings → Sound.",
+            name,
+            device.sample_rate / 1000
+        );
+        let config = Arc::clone(config);
+        let state = state.clone();
+        runtime::spawn(async move {
+            // Don't steal focus from the app this dictation is about to be typed into
+            while state.get_recording_state() != RecordingState::Idle {
+                Timer::after(std::time::Duration::from_millis(100)).await;
+            }
+            Timer::after(std::time::Duration::from_millis(1000)).await;
+            // The alert blocks until dismissed
+            let suppress = smol::unblock(move || {
+                menubar_ffi::MenuBarController::show_suppressible_alert("Low-quality microphone", &message)
+            });
+            if suppress.await {
+                let mut cfg = config.write();
+                cfg.audio.suppressed_device_warnings.push(name);
+                let to_save = cfg.clone();
+                drop(cfg);
+                save_config(&to_save);
+            }
+        });
+    }
+}
+
+#[cfg(all(test, feature = "mock"))]
+mod tests {
+    use super::*;
+    use crate::services::audio::MockAudioSource;
+    use std::time::Duration;
+
+    #[test]
+    fn press_after_a_stuck_transcription_is_reset_records_at_once() {
+        let path = std::path::Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures/tone-16k-mono.wav");
+        let source = MockAudioSource::from_wav_file(&path, 16000).unwrap();
+        let mut config = Config::default();
+        config.model.backend = TranscriberBackend::Mock;
+        config.model.preset = None;
+        // Long past any test run: the transcription never comes back
+        config.model.mock.delay_ms = 600_000;
+        let mut processor = AudioProcessor::with_source(config, Box::new(source));
+        let (started_tx, started) = std::sync::mpsc::channel();
+        let started_tx = parking_lot::Mutex::new(started_tx);
+        processor.set_progress_callback(Arc::new(move |_: usize, _: usize| {
+            let _ = started_tx.lock().send(());
+        }));
+        let audio_processor = Arc::new(Mutex::new(processor));
+        let state = AppStateManager::new();
+
+        state.transition(RecordingEvent::Start).unwrap();
+        audio_processor.lock().unwrap().start_recording().unwrap();
+        state.transition(RecordingEvent::Stop).unwrap();
+        audio_processor.lock().unwrap().stop_capture().unwrap();
+        let stuck = Arc::clone(&audio_processor);
+        std::thread::spawn(move || AppController::transcribe_taken(&stuck, TranscriptionPrompt::default(), false));
+        started.recv_timeout(Duration::from_secs(5)).expect("transcription didn't start");
+
+        // What the watchdog does to a dictation stuck in Processing
+        state.transition(RecordingEvent::Cancel).unwrap();
+
+        state.transition(RecordingEvent::Start).unwrap();
+        let (pressed_tx, pressed) = std::sync::mpsc::channel();
+        let processor = Arc::clone(&audio_processor);
+        std::thread::spawn(move || {
+            let _ = pressed_tx.send(processor.lock().unwrap().start_recording().is_ok());
+        });
+        assert_eq!(pressed.recv_timeout(Duration::from_secs(5)), Ok(true), "the press blocked on the stuck job");
+    }
+
+    #[test]
+    fn client_output_is_set_only_when_the_start_is_taken() {
+        let state = AppStateManager::new();
+        let config = Arc::new(parking_lot::RwLock::new(Config::default()));
+        let start = HotkeyEvent::StartClientDictation;
+
+        state.transition(RecordingEvent::Start).unwrap();
+        assert_eq!(AppController::hands_free_event(&state, &config, start), None);
+        assert_eq!(state.output_override(), OutputOverride::None);
+
+        state.transition(RecordingEvent::Cancel).unwrap();
+        assert_eq!(AppController::hands_free_event(&state, &config, start), Some(HotkeyEvent::PushToTalkPressed(None)));
+        assert_eq!(state.output_override(), OutputOverride::Client);
+    }
 }
@@ -0,0 +1,210 @@
+//! Correction memory: the word swaps made when editing dictations in History. A swap that keeps
+//! coming back is offered as a replacement rule, so the next dictation gets it right before it's
+//! typed.
+
+use crate::config::{Config, HistoryConfig, Replacement, StageConfig};
+use crate::platform::macos::ffi as menubar_ffi;
+use parking_lot::RwLock;
+use serde::{Deserialize, Serialize};
+use std::path::PathBuf;
+use std::sync::Arc;
+use tracing::{info, warn};
+
+/// Longest phrase (in words, either side) remembered as one correction; bigger edits are rewrites
+const MAX_PHRASE_WORDS: usize = 4;
+
+/// Longer texts aren't diffed word by word
+const MAX_DIFF_WORDS: usize = 400;
+
+/// Punctuation ignored at the edges of words, so adding a comma isn't a correction
+const EDGE_PUNCTUATION: &[char] = &['.', ',', '!', '?', ';', ':', '"', '“', '”', '‘', '’', '(', ')'];
+
+/// One remembered swap and how often it was made
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Correction {
+    pub heard: String,
+    pub corrected: String,
+    pub count: u32,
+}
+
+/// Counts of corrections made so far, kept in ~/.typeswift/corrections.json
+#[derive(Clone)]
+pub struct CorrectionMemory {
+    path: Option<PathBuf>,
+    corrections: Arc<RwLock<Vec<Correction>>>,
+}
+
+impl CorrectionMemory {
+    /// Open the default file. With encrypted history, corrections stay in memory: they quote
+    /// dictated words.
+    pub fn open_default(config: &HistoryConfig) -> Self {
+        let path = if config.encrypt || !config.enabled { None } else { Self::default_path() };
+        let corrections = path
+            .as_ref()
+            .and_then(|p| std::fs::read_to_string(p).ok())
+            .and_then(|contents| match serde_json::from_str(&contents) {
+                Ok(corrections) => Some(corrections),
+                Err(e) => {
+                    warn!("Ignoring unreadable correction memory: {}", e);
+                    None
+                }
+            })
+            .unwrap_or_default();
+        Self { path, corrections: Arc::new(RwLock::new(corrections)) }
+    }
+
+    fn default_path() -> Option<PathBuf> {
+        std::env::var("HOME")
+            .ok()
+            .map(|home| PathBuf::from(home).join(".typeswift").join("corrections.json"))
+    }
+
+    /// Count the corrections that turned `before` into `after`. Returns those that have now been
+    /// made a multiple of `suggest_after` times, so declining an offer repeats it later.
+    pub fn record(&self, before: &str, after: &str, suggest_after: u32) -> Vec<Correction> {
+        let pairs = corrections(before, after);
+        if pairs.is_empty() {
+            return Vec::new();
+        }
+        let mut due = Vec::new();
+        {
+            let mut corrections = self.corrections.write();
+            for (heard, corrected) in pairs {
+                let index = match corrections.iter().position(|c| c.heard == heard && c.corrected == corrected) {
+                    Some(index) => index,
+                    None => {
+                        corrections.push(Correction { heard, corrected, count: 0 });
+                        corrections.len() - 1
+                    }
+                };
+                let correction = &mut corrections[index];
+                correction.count += 1;
+                let (heard, corrected, count) = (&correction.heard, &correction.corrected, correction.count);
+                info!("Correction \"{}\" -> \"{}\" made {} time(s)", heard, corrected, count);
+                if suggest_after > 0 && correction.count % suggest_after == 0 {
+                    due.push(correction.clone());
+                }
+            }
+        }
+        self.save();
+        due
+    }
+
+    /// Stop counting a correction, once it's a replacement rule
+    pub fn forget(&self, correction: &Correction) {
+        self.corrections
+            .write()
+            .retain(|c| !(c.heard == correction.heard && c.corrected == correction.corrected));
+        self.save();
+    }
+
+    fn save(&self) {
+        let Some(ref path) = self.path else { return };
+        let result = serde_json::to_string_pretty(&*self.corrections.read())
+            .map_err(std::io::Error::other)
+            .and_then(|json| {
+                if let Some(dir) = path.parent() {
+                    std::fs::create_dir_all(dir)?;
+                }
+                std::fs::write(path, json)
+            });
+        if let Err(e) = result {
+            warn!("Failed to save correction memory: {}", e);
+        }
+    }
+}
+
+/// The (heard, corrected) phrases that differ between `before` and `after`, word by word.
+/// Insertions, deletions and punctuation-only changes aren't corrections.
+pub fn corrections(before: &str, after: &str) -> Vec<(String, String)> {
+    let before: Vec<&str> = before.split_whitespace().map(bare_word).collect();
+    let after: Vec<&str> = after.split_whitespace().map(bare_word).collect();
+    if before.len() > MAX_DIFF_WORDS || after.len() > MAX_DIFF_WORDS {
+        return Vec::new();
+    }
+
+    // Longest common subsequence of words, filled from the end so the walk below goes forwards
+    let (n, m) = (before.len(), after.len());
+    let mut lcs = vec![vec![0u16; m + 1]; n + 1];
+    for i in (0..n).rev() {
+        for j in (0..m).rev() {
+            lcs[i][j] = if before[i] == after[j] { lcs[i + 1][j + 1] + 1 } else { lcs[i + 1][j].max(lcs[i][j + 1]) };
+        }
+    }
+
+    let mut pairs = Vec::new();
+    let (mut i, mut j) = (0, 0);
+    while i < n || j < m {
+        if i < n && j < m && before[i] == after[j] {
+            i += 1;
+            j += 1;
+            continue;
+        }
+        // One run of changed words on each side
+        let (start_i, start_j) = (i, j);
+        while (i < n || j < m) && !(i < n && j < m && before[i] == after[j]) {
+            if j >= m || (i < n && lcs[i + 1][j] >= lcs[i][j + 1]) {
+                i += 1;
+            } else {
+                j += 1;
+            }
+        }
+        let heard = &before[start_i..i];
+        let corrected = &after[start_j..j];
+        let phrase = |words: &[&str]| !words.is_empty() && words.len() <= MAX_PHRASE_WORDS;
+        if phrase(heard) && phrase(corrected) {
+            pairs.push((heard.join(" "), corrected.join(" ")));
+        }
+    }
+    pairs
+}
+
+fn bare_word(word: &str) -> &str {
+    word.trim_matches(EDGE_PUNCTUATION)
+}
+
+/// Add `heard` → `corrected` to the first replacements stage, creating one at the front of the
+/// pipeline if there's none. False if a rule for `heard` is already there.
+pub fn add_replacement(config: &mut Config, heard: &str, corrected: &str) -> bool {
+    let stages = &mut config.pipeline.stages;
+    let is_replacements = |stage: &StageConfig| matches!(stage, StageConfig::Replacements { .. });
+    if !stages.iter().any(is_replacements) {
+        stages.insert(0, StageConfig::Replacements { rules: Vec::new() });
+    }
+    let Some(StageConfig::Replacements { rules }) = stages.iter_mut().find(|s| is_replacements(s)) else {
+        return false;
+    };
+    if rules.iter().any(|r| r.from.eq_ignore_ascii_case(heard)) {
+        return false;
+    }
+    rules.push(Replacement { from: heard.to_string(), to: corrected.to_string() });
+    true
+}
+
+/// Ask whether to turn `correction` into a replacement rule, and save the config if so.
+/// Blocks on a dialog; call off the UI thread.
+pub fn offer_replacement(config: &Arc<RwLock<Config>>, memory: &CorrectionMemory, correction: &Correction) {
+    let accepted = menubar_ffi::MenuBarController::confirm(
+        &format!("Always write “{}”?", correction.corrected),
+        &format!(
+            "You've corrected “{}” to “{}” {} times. Typeswift can add it to your replacements \
+             and fix it before typing from now on.",
+            correction.heard, correction.corrected, correction.count
+        ),
+        "Add Replacement",
+    );
+    if !accepted {
+        return;
+    }
+    let to_save = {
+        let mut cfg = config.write();
+        if !add_replacement(&mut cfg, &correction.heard, &correction.corrected) {
+            info!("A replacement for \"{}\" already exists", correction.heard);
+            return;
+        }
+        cfg.clone()
+    };
+    info!("Added replacement \"{}\" -> \"{}\"", correction.heard, correction.corrected);
+    memory.forget(correction);
+    crate::services::config_store::save(&to_save);
+}
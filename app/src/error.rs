@@ -3,23 +3,124 @@ use std::fmt;
 
 #[derive(Debug)]
 pub enum VoicyError {
+    /// A macOS permission (Microphone, Accessibility, ...) hasn't been granted
+    PermissionDenied(String),
     AudioInitFailed(String),
     ModelLoadFailed(String),
     TranscriptionFailed(String),
     HotkeyRegistrationFailed(String),
     WindowOperationFailed(String),
     ConfigLoadFailed(String),
+    PostProcessingFailed(String),
+    OutputFailed(String),
+}
+
+/// What kind of problem an error is, which decides what the user is told to do about it
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ErrorCategory {
+    /// Something needs granting in System Settings
+    Permissions,
+    /// Microphone, audio hardware or keyboard shortcuts
+    Device,
+    /// Loading the speech model or transcribing with it
+    Model,
+    /// Getting the text where it was meant to go
+    Output,
+    /// Settings, windows and anything else on our side
+    Internal,
+}
+
+impl ErrorCategory {
+    pub fn label(self) -> &'static str {
+        match self {
+            ErrorCategory::Permissions => "Permissions",
+            ErrorCategory::Device => "Device",
+            ErrorCategory::Model => "Model",
+            ErrorCategory::Output => "Output",
+            ErrorCategory::Internal => "Internal",
+        }
+    }
+}
+
+impl VoicyError {
+    pub fn category(&self) -> ErrorCategory {
+        match self {
+            VoicyError::PermissionDenied(_) => ErrorCategory::Permissions,
+            VoicyError::AudioInitFailed(_) | VoicyError::HotkeyRegistrationFailed(_) => ErrorCategory::Device,
+            VoicyError::ModelLoadFailed(_) | VoicyError::TranscriptionFailed(_) => ErrorCategory::Model,
+            VoicyError::PostProcessingFailed(_) | VoicyError::OutputFailed(_) => ErrorCategory::Output,
+            VoicyError::WindowOperationFailed(_) | VoicyError::ConfigLoadFailed(_) => ErrorCategory::Internal,
+        }
+    }
+
+    /// Short headline for notifications and the menu bar's "Last error" item
+    pub fn title(&self) -> &'static str {
+        match self {
+            VoicyError::PermissionDenied(_) => "Permission needed",
+            VoicyError::AudioInitFailed(_) => "Microphone problem",
+            VoicyError::ModelLoadFailed(_) => "Speech model failed to load",
+            VoicyError::TranscriptionFailed(_) => "Transcription failed",
+            VoicyError::HotkeyRegistrationFailed(_) => "Shortcut unavailable",
+            VoicyError::WindowOperationFailed(_) => "Window problem",
+            VoicyError::ConfigLoadFailed(_) => "Settings couldn't be read",
+            VoicyError::PostProcessingFailed(_) => "Post-processing failed",
+            VoicyError::OutputFailed(_) => "Couldn't deliver the text",
+        }
+    }
+
+    /// What the user can do about it
+    pub fn remediation(&self) -> &'static str {
+        match self {
+            VoicyError::PermissionDenied(_) => {
+                "Allow Typeswift in System Settings → Privacy & Security, then relaunch it."
+            }
+            VoicyError::AudioInitFailed(_) => {
+                "Check that a microphone is connected and selected in System Settings → Sound → Input."
+            }
+            VoicyError::ModelLoadFailed(_) => {
+                "Typeswift tries again when you next dictate. If it keeps failing, check [model] in config.toml."
+            }
+            VoicyError::TranscriptionFailed(_) => "Try again; if it keeps happening, relaunch Typeswift.",
+            VoicyError::HotkeyRegistrationFailed(_) => {
+                "Another app may be using it; pick a different shortcut in Preferences."
+            }
+            VoicyError::WindowOperationFailed(_) => "Relaunch Typeswift if its windows stop responding.",
+            VoicyError::ConfigLoadFailed(_) => {
+                "Check ~/.typeswift/config.toml for mistakes, or move it aside to start from defaults."
+            }
+            VoicyError::PostProcessingFailed(_) => "Check the [pipeline] stages in config.toml.",
+            VoicyError::OutputFailed(_) => "The text is in History; copy it from there.",
+        }
+    }
+
+    /// The underlying message, without the headline
+    pub fn detail(&self) -> &str {
+        match self {
+            VoicyError::PermissionDenied(msg)
+            | VoicyError::AudioInitFailed(msg)
+            | VoicyError::ModelLoadFailed(msg)
+            | VoicyError::TranscriptionFailed(msg)
+            | VoicyError::HotkeyRegistrationFailed(msg)
+            | VoicyError::WindowOperationFailed(msg)
+            | VoicyError::ConfigLoadFailed(msg)
+            | VoicyError::PostProcessingFailed(msg)
+            | VoicyError::OutputFailed(msg) => msg,
+        }
+    }
 }
 
 impl fmt::Display for VoicyError {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         match self {
+            VoicyError::PermissionDenied(msg) => write!(f, "Permission denied: {}", msg),
             VoicyError::AudioInitFailed(msg) => write!(f, "Audio initialization failed: {}", msg),
             VoicyError::ModelLoadFailed(msg) => write!(f, "Model load failed: {}", msg),
             VoicyError::TranscriptionFailed(msg) => write!(f, "Transcription failed: {}", msg),
             VoicyError::HotkeyRegistrationFailed(msg) => write!(f, "Hotkey registration failed: {}", msg),
             VoicyError::WindowOperationFailed(msg) => write!(f, "Window operation failed: {}", msg),
             VoicyError::ConfigLoadFailed(msg) => write!(f, "Config load failed: {}", msg),
+            VoicyError::PostProcessingFailed(msg) => write!(f, "Post-processing failed: {}", msg),
+            VoicyError::OutputFailed(msg) => write!(f, "Output failed: {}", msg),
         }
     }
 }
@@ -32,4 +133,4 @@ impl From<anyhow::Error> for VoicyError {
     fn from(err: anyhow::Error) -> Self {
         VoicyError::ConfigLoadFailed(format!("Anyhow error: {}", err))
     }
-}
\ No newline at end of file
+}
@@ -0,0 +1,703 @@
+use crate::config::{AudioCodec, HistoryConfig, HistoryRetention};
+use crate::error::{VoicyError, VoicyResult};
+use crate::services::audio::{Token, Transcript};
+use parking_lot::RwLock;
+use serde::{Deserialize, Serialize};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::{SystemTime, UNIX_EPOCH};
+use tracing::{error, info, warn};
+
+/// One finished dictation
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct HistoryEntry {
+    pub id: u64,
+    /// Unix seconds
+    pub timestamp: u64,
+    /// Text as emitted (after commands and formatting)
+    pub text: String,
+    /// Transcript as returned by the model
+    #[serde(default)]
+    pub raw_text: String,
+    /// Bundle identifier of the app the text went to
+    #[serde(default)]
+    pub app: Option<String>,
+    #[serde(default)]
+    pub duration_ms: u64,
+    /// Word timings for `raw_text`, when the backend reported them
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub tokens: Vec<Token>,
+    /// Moments flagged with the marker hotkey, in ms from the start of the recording
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub markers: Vec<u64>,
+}
+
+impl HistoryEntry {
+    /// Short app label from the bundle id ("com.apple.Notes" -> "Notes")
+    pub fn app_label(&self) -> Option<&str> {
+        self.app.as_deref().map(|id| id.rsplit('.').next().unwrap_or(id))
+    }
+
+    /// What the model heard, when commands or formatting changed it on the way out
+    pub fn changed_raw_text(&self) -> Option<&str> {
+        let raw = self.raw_text.trim();
+        (!raw.is_empty() && raw != self.text.trim()).then_some(raw)
+    }
+}
+
+/// Consecutive dictations into the same app with short pauses between them,
+/// e.g. a long email dictated in several bursts
+#[derive(Debug, Clone)]
+pub struct HistorySession {
+    /// Oldest first
+    pub entries: Vec<HistoryEntry>,
+}
+
+impl HistorySession {
+    pub fn start(&self) -> u64 {
+        self.entries.first().map_or(0, |e| e.timestamp.saturating_sub(e.duration_ms / 1000))
+    }
+
+    pub fn app_label(&self) -> Option<&str> {
+        self.entries.first().and_then(HistoryEntry::app_label)
+    }
+
+    /// The session's text as one document, one paragraph per dictation,
+    /// each followed by its markers
+    pub fn text(&self) -> String {
+        self.document(false)
+    }
+
+    /// `text`, optionally quoting the model's own words under each paragraph formatting changed
+    fn document(&self, include_raw: bool) -> String {
+        self.entries
+            .iter()
+            .filter(|e| !e.text.trim().is_empty())
+            .map(|e| {
+                let mut paragraph = e.text.trim().to_string();
+                if let Some(raw) = e.changed_raw_text().filter(|_| include_raw) {
+                    paragraph.push_str(&format!("\n\n> Transcribed: {}", raw));
+                }
+                let markers = format_markers(&e.markers, &e.tokens);
+                if !markers.is_empty() {
+                    paragraph.push_str(&format!("\n\n{}", markers));
+                }
+                paragraph
+            })
+            .collect::<Vec<_>>()
+            .join("\n\n")
+    }
+
+    /// Write the session as Markdown into `dir`; returns the file path. With `include_raw`,
+    /// the model's transcript is quoted under each dictation post-processing changed.
+    pub fn export(&self, dir: &Path, include_raw: bool) -> VoicyResult<PathBuf> {
+        let title = match self.app_label() {
+            Some(app) => format!("Dictation {} ({})", format_timestamp(self.start()), app),
+            None => format!("Dictation {}", format_timestamp(self.start())),
+        };
+        let stamp: String = format_timestamp(self.start()).chars().filter(char::is_ascii_digit).collect();
+        let path = dir.join(format!("typeswift-session-{}.md", stamp));
+        std::fs::create_dir_all(dir)
+            .and_then(|_| std::fs::write(&path, format!("# {}\n\n{}\n", title, self.document(include_raw))))
+            .map_err(|e| VoicyError::ConfigLoadFailed(format!("Session export failed: {}", e)))?;
+        Ok(path)
+    }
+}
+
+/// Where session exports go: ~/Documents/Typeswift
+pub fn default_export_dir() -> Option<PathBuf> {
+    std::env::var("HOME")
+        .ok()
+        .map(|home| PathBuf::from(home).join("Documents").join("Typeswift"))
+}
+
+/// Whether `next` continues the session that `prev` belongs to
+fn same_session(prev: &HistoryEntry, next: &HistoryEntry, gap_secs: u64) -> bool {
+    // Measure from the end of `prev` to the start of `next`
+    let next_start = next.timestamp.saturating_sub(next.duration_ms / 1000);
+    prev.app == next.app && next_start.saturating_sub(prev.timestamp) <= gap_secs
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum DateRange {
+    #[default]
+    All,
+    Today,
+    Week,
+    Month,
+}
+
+impl DateRange {
+    pub fn next(self) -> Self {
+        match self {
+            DateRange::All => DateRange::Today,
+            DateRange::Today => DateRange::Week,
+            DateRange::Week => DateRange::Month,
+            DateRange::Month => DateRange::All,
+        }
+    }
+
+    pub fn label(self) -> &'static str {
+        match self {
+            DateRange::All => "Any time",
+            DateRange::Today => "Last 24 hours",
+            DateRange::Week => "Last 7 days",
+            DateRange::Month => "Last 30 days",
+        }
+    }
+
+    /// Earliest timestamp included, relative to `now`
+    fn since(self, now: u64) -> Option<u64> {
+        let days = match self {
+            DateRange::All => return None,
+            DateRange::Today => 1,
+            DateRange::Week => 7,
+            DateRange::Month => 30,
+        };
+        Some(now.saturating_sub(days * 86_400))
+    }
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct HistoryQuery {
+    pub text: String,
+    pub range: DateRange,
+    /// Bundle identifier to restrict to
+    pub app: Option<String>,
+}
+
+/// How long dictations are kept
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Retention {
+    Forever,
+    Days(u32),
+    Entries(usize),
+    /// Don't store anything; existing history is erased
+    Never,
+    /// History is turned off: nothing new is recorded, what's stored is left alone
+    Paused,
+}
+
+impl Retention {
+    pub fn from_config(config: &HistoryConfig) -> Self {
+        if !config.enabled {
+            return Retention::Paused;
+        }
+        match config.retention {
+            HistoryRetention::Forever => Retention::Forever,
+            HistoryRetention::Days => Retention::Days(config.keep_days),
+            HistoryRetention::Entries => Retention::Entries(config.keep_entries),
+            HistoryRetention::Never => Retention::Never,
+        }
+    }
+
+    /// Drop entries outside the policy; returns whether anything was removed
+    fn prune(self, entries: &mut Vec<HistoryEntry>, now: u64) -> bool {
+        let before = entries.len();
+        match self {
+            Retention::Forever | Retention::Paused => {}
+            Retention::Never => entries.clear(),
+            Retention::Days(days) => {
+                let cutoff = now.saturating_sub(days as u64 * 86_400);
+                entries.retain(|e| e.timestamp >= cutoff);
+            }
+            Retention::Entries(max) => {
+                if entries.len() > max {
+                    entries.drain(..entries.len() - max);
+                }
+            }
+        }
+        entries.len() != before
+    }
+}
+
+/// Dictation history in `~/.typeswift/history.jsonl` (one JSON entry per line,
+/// optionally encrypted per line), mirrored in memory
+#[derive(Clone)]
+pub struct HistoryStore {
+    path: Option<PathBuf>,
+    entries: Arc<RwLock<Vec<HistoryEntry>>>,
+    retention: Arc<RwLock<Retention>>,
+    cipher: Option<Arc<HistoryCipher>>,
+    /// Lines of the file that couldn't be decrypted or parsed, written back as they were
+    unreadable: Arc<RwLock<Vec<String>>>,
+}
+
+impl HistoryStore {
+    /// Open the default history file with the configured retention and encryption.
+    /// If the keychain key can't be obtained, history stays in memory rather than on disk unencrypted.
+    pub fn open_default(config: &HistoryConfig) -> Self {
+        let retention = Retention::from_config(config);
+        if !config.encrypt {
+            return Self::open(Self::default_path(), retention, None);
+        }
+        match HistoryCipher::from_keychain() {
+            Ok(cipher) => Self::open(Self::default_path(), retention, Some(cipher)),
+            Err(e) => {
+                error!("History encryption unavailable ({}); not writing history to disk", e);
+                Self::open(None, retention, None)
+            }
+        }
+    }
+
+    pub fn default_path() -> Option<PathBuf> {
+        std::env::var("HOME")
+            .ok()
+            .map(|home| PathBuf::from(home).join(".typeswift").join("history.jsonl"))
+    }
+
+    /// Load entries from `path`; unreadable lines are skipped. `None` keeps history in memory only.
+    /// The file is rewritten if retention drops entries or its encryption doesn't match `cipher`.
+    /// Lines encrypted while encryption was on are read with the keychain key even when it's off
+    /// now; lines that still can't be read are kept as they are.
+    pub fn open(path: Option<PathBuf>, retention: Retention, cipher: Option<HistoryCipher>) -> Self {
+        let mut entries = Vec::new();
+        let mut unreadable = Vec::new();
+        let mut needs_rewrite = false;
+        // Only for reading, once an encrypted line turns up
+        let mut stored_key: Option<Option<HistoryCipher>> = None;
+        if let Some(contents) = path.as_ref().and_then(|p| std::fs::read_to_string(p).ok()) {
+            for line in contents.lines().filter(|l| !l.trim().is_empty()) {
+                let encrypted = line.starts_with(ENCRYPTED_PREFIX);
+                let json = if encrypted {
+                    let reader = match cipher.as_ref() {
+                        Some(cipher) => Some(cipher),
+                        None => stored_key.get_or_insert_with(HistoryCipher::stored_in_keychain).as_ref(),
+                    };
+                    match reader.and_then(|c| c.open(line)) {
+                        Some(json) => json,
+                        None => {
+                            warn!("Keeping history line that can't be decrypted");
+                            unreadable.push(line.to_string());
+                            continue;
+                        }
+                    }
+                } else {
+                    line.to_string()
+                };
+                match serde_json::from_str::<HistoryEntry>(&json) {
+                    Ok(entry) => {
+                        needs_rewrite |= encrypted != cipher.is_some();
+                        entries.push(entry);
+                    }
+                    Err(e) => {
+                        warn!("Keeping unreadable history line: {}", e);
+                        unreadable.push(line.to_string());
+                    }
+                }
+            }
+            info!("Loaded {} history entries", entries.len());
+        }
+        needs_rewrite |= retention.prune(&mut entries, now_secs());
+        if retention == Retention::Never && !unreadable.is_empty() {
+            unreadable.clear();
+            needs_rewrite = true;
+        }
+
+        let store = Self {
+            path,
+            entries: Arc::new(RwLock::new(entries)),
+            retention: Arc::new(RwLock::new(retention)),
+            cipher: cipher.map(Arc::new),
+            unreadable: Arc::new(RwLock::new(unreadable)),
+        };
+        if needs_rewrite {
+            if let Err(e) = store.rewrite(&store.entries.read()) {
+                warn!("Failed to compact history: {}", e);
+            }
+        }
+        store
+    }
+
+    /// Change the retention policy and apply it immediately
+    pub fn set_retention(&self, retention: Retention) {
+        *self.retention.write() = retention;
+        let mut entries = self.entries.write();
+        let mut pruned = retention.prune(&mut entries, now_secs());
+        if retention == Retention::Never {
+            let mut unreadable = self.unreadable.write();
+            pruned |= !unreadable.is_empty();
+            unreadable.clear();
+        }
+        if pruned {
+            if let Err(e) = self.rewrite(&entries) {
+                warn!("Failed to compact history: {}", e);
+            }
+        }
+    }
+
+    /// Record a dictation and persist it; returns `None` when retention is `Never` or `Paused`
+    pub fn append(
+        &self,
+        text: String,
+        transcript: Transcript,
+        app: Option<String>,
+        duration_ms: u64,
+        markers: Vec<u64>,
+    ) -> VoicyResult<Option<HistoryEntry>> {
+        let retention = *self.retention.read();
+        if matches!(retention, Retention::Never | Retention::Paused) {
+            return Ok(None);
+        }
+        let mut entries = self.entries.write();
+        let entry = HistoryEntry {
+            id: entries.last().map_or(1, |e| e.id + 1),
+            timestamp: now_secs(),
+            text,
+            raw_text: transcript.text,
+            app,
+            duration_ms,
+            tokens: transcript.tokens,
+            markers,
+        };
+        entries.push(entry.clone());
+        if retention.prune(&mut entries, entry.timestamp) {
+            self.rewrite(&entries)?;
+        } else if let Some(ref path) = self.path {
+            let line = self.encode_line(&entry)?;
+            if let Some(dir) = path.parent() {
+                let _ = std::fs::create_dir_all(dir);
+            }
+            std::fs::OpenOptions::new()
+                .create(true)
+                .append(true)
+                .open(path)
+                .and_then(|mut f| writeln!(f, "{}", line))
+                .map_err(|e| VoicyError::ConfigLoadFailed(format!("History write failed: {}", e)))?;
+        }
+        Ok(Some(entry))
+    }
+
+    /// Replace the emitted text of entry `id` (e.g. after re-running post-processing)
+    pub fn update_text(&self, id: u64, text: String) -> VoicyResult<Option<HistoryEntry>> {
+        let mut entries = self.entries.write();
+        let Some(entry) = entries.iter_mut().find(|e| e.id == id) else {
+            return Ok(None);
+        };
+        entry.text = text;
+        let updated = entry.clone();
+        self.rewrite(&entries)?;
+        Ok(Some(updated))
+    }
+
+    fn encode_line(&self, entry: &HistoryEntry) -> VoicyResult<String> {
+        let json = serde_json::to_string(entry)
+            .map_err(|e| VoicyError::ConfigLoadFailed(format!("History encode failed: {}", e)))?;
+        match self.cipher {
+            Some(ref cipher) => cipher.seal(&json),
+            None => Ok(json),
+        }
+    }
+
+    /// Folder of archived dictation audio, next to the history file
+    fn recordings_dir(&self) -> Option<PathBuf> {
+        self.path.as_ref()?.parent().map(|dir| dir.join("recordings"))
+    }
+
+    /// Archived audio of entry `id`, if it was kept (in whichever format was configured then)
+    pub fn audio_path(&self, id: u64) -> Option<PathBuf> {
+        let dir = self.recordings_dir()?;
+        AudioCodec::ALL
+            .iter()
+            .map(|codec| dir.join(format!("{}.{}", id, codec.extension())))
+            .find(|path| path.is_file())
+    }
+
+    /// Archive the audio of entry `id` with `codec`; returns `None` when history isn't on disk
+    /// or is encrypted (the recording would sit next to it in the clear)
+    pub fn save_audio(
+        &self,
+        id: u64,
+        samples: &[f32],
+        sample_rate: u32,
+        codec: AudioCodec,
+    ) -> VoicyResult<Option<PathBuf>> {
+        let Some(dir) = self.recordings_dir() else { return Ok(None) };
+        if self.cipher.is_some() || samples.is_empty() {
+            return Ok(None);
+        }
+        std::fs::create_dir_all(&dir)
+            .map_err(|e| VoicyError::ConfigLoadFailed(format!("Recording archive failed: {}", e)))?;
+        let path = dir.join(format!("{}.{}", id, codec.extension()));
+        crate::services::encode::encode_to_file(samples, sample_rate, codec, &path)?;
+        Ok(Some(path))
+    }
+
+    /// Delete archived audio whose entry is gone (all of it when history is encrypted)
+    fn prune_audio(&self, entries: &[HistoryEntry]) {
+        let Some(files) = self.recordings_dir().and_then(|dir| std::fs::read_dir(dir).ok()) else { return };
+        for file in files.flatten() {
+            let path = file.path();
+            let id = path.file_stem().and_then(|s| s.to_str()).and_then(|s| s.parse::<u64>().ok());
+            let keep = self.cipher.is_none() && id.is_some_and(|id| entries.iter().any(|e| e.id == id));
+            if !keep {
+                let _ = std::fs::remove_file(&path);
+            }
+        }
+    }
+
+    /// Replace the file with `entries` (temp file + rename so a crash can't truncate it)
+    fn rewrite(&self, entries: &[HistoryEntry]) -> VoicyResult<()> {
+        let Some(ref path) = self.path else { return Ok(()) };
+        self.prune_audio(entries);
+        let unreadable = self.unreadable.read();
+        if entries.is_empty() && unreadable.is_empty() {
+            let _ = std::fs::remove_file(path);
+            return Ok(());
+        }
+        let mut out = String::new();
+        for line in unreadable.iter() {
+            out.push_str(line);
+            out.push('\n');
+        }
+        for entry in entries {
+            out.push_str(&self.encode_line(entry)?);
+            out.push('\n');
+        }
+        let tmp = path.with_extension("jsonl.tmp");
+        std::fs::write(&tmp, out)
+            .and_then(|_| std::fs::rename(&tmp, path))
+            .map_err(|e| VoicyError::ConfigLoadFailed(format!("History write failed: {}", e)))
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.read().len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.read().is_empty()
+    }
+
+    /// Distinct app bundle ids, most recently used first
+    pub fn apps(&self) -> Vec<String> {
+        let entries = self.entries.read();
+        let mut apps: Vec<String> = Vec::new();
+        for app in entries.iter().rev().filter_map(|e| e.app.as_ref()) {
+            if !apps.contains(app) {
+                apps.push(app.clone());
+            }
+        }
+        apps
+    }
+
+    /// Entries matching `query`: best text match first, newest first otherwise
+    pub fn search(&self, query: &HistoryQuery, limit: usize) -> Vec<HistoryEntry> {
+        let since = query.range.since(now_secs());
+        let needle = query.text.trim().to_lowercase();
+        let entries = self.entries.read();
+        let mut matches: Vec<(i64, &HistoryEntry)> = entries
+            .iter()
+            .rev()
+            .filter(|e| since.is_none_or(|s| e.timestamp >= s))
+            .filter(|e| query.app.as_ref().is_none_or(|a| e.app.as_ref() == Some(a)))
+            .filter_map(|e| {
+                if needle.is_empty() {
+                    Some((0, e))
+                } else {
+                    match_score(&e.text.to_lowercase(), &needle).map(|score| (score, e))
+                }
+            })
+            .collect();
+        // Stable sort keeps newest-first among equal scores
+        matches.sort_by_key(|(score, _)| std::cmp::Reverse(*score));
+        matches.into_iter().take(limit).map(|(_, e)| e.clone()).collect()
+    }
+
+    /// The session containing entry `id`, found by walking outwards while entries stay within `gap_secs`
+    pub fn session_for(&self, id: u64, gap_secs: u64) -> Option<HistorySession> {
+        let entries = self.entries.read();
+        let index = entries.iter().position(|e| e.id == id)?;
+        let mut start = index;
+        while start > 0 && same_session(&entries[start - 1], &entries[start], gap_secs) {
+            start -= 1;
+        }
+        let mut end = index + 1;
+        while end < entries.len() && same_session(&entries[end - 1], &entries[end], gap_secs) {
+            end += 1;
+        }
+        Some(HistorySession { entries: entries[start..end].to_vec() })
+    }
+}
+
+/// Substring matches rank above fuzzy (in-order subsequence) matches; earlier and tighter is better
+fn match_score(haystack: &str, needle: &str) -> Option<i64> {
+    if let Some(pos) = haystack.find(needle) {
+        return Some(1_000_000 - pos as i64);
+    }
+    let needle: Vec<char> = needle.chars().filter(|c| !c.is_whitespace()).collect();
+    if needle.is_empty() {
+        return None;
+    }
+    let mut it = needle.iter().peekable();
+    let (mut first, mut last) = (None, 0usize);
+    for (i, c) in haystack.chars().enumerate() {
+        if it.peek() == Some(&&c) {
+            it.next();
+            first.get_or_insert(i);
+            last = i;
+            if it.peek().is_none() {
+                break;
+            }
+        }
+    }
+    if it.peek().is_some() {
+        return None;
+    }
+    let span = last - first.unwrap_or(0) + 1;
+    // Reject matches scattered across a long transcript
+    if span > needle.len() * 4 {
+        return None;
+    }
+    Some(100_000 - span as i64 * 100 - first.unwrap_or(0) as i64)
+}
+
+pub fn now_secs() -> u64 {
+    SystemTime::now().duration_since(UNIX_EPOCH).map(|d| d.as_secs()).unwrap_or(0)
+}
+
+/// "m:ss" (or "h:mm:ss") for an offset into a recording
+pub fn format_offset(ms: u64) -> String {
+    let secs = ms / 1000;
+    if secs >= 3600 {
+        format!("{}:{:02}:{:02}", secs / 3600, secs / 60 % 60, secs % 60)
+    } else {
+        format!("{}:{:02}", secs / 60, secs % 60)
+    }
+}
+
+/// How far back from a marker to quote what was said
+const MARKER_CONTEXT_MS: u64 = 8000;
+
+/// Markdown list of markers, each with the words spoken just before it, so a flagged
+/// moment can be found without listening again. Empty when there are no markers.
+pub fn format_markers(markers: &[u64], tokens: &[Token]) -> String {
+    markers
+        .iter()
+        .map(|&at| {
+            let said: Vec<&str> = tokens
+                .iter()
+                .filter(|t| t.start_ms <= at && t.start_ms + MARKER_CONTEXT_MS >= at)
+                .map(|t| t.text.trim())
+                .collect();
+            if said.is_empty() {
+                format!("- ⚑ {}", format_offset(at))
+            } else {
+                format!("- ⚑ {} — “…{}”", format_offset(at), said.join(" "))
+            }
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+/// Local "YYYY-MM-DD HH:MM" for a Unix timestamp
+pub fn format_timestamp(timestamp: u64) -> String {
+    let time = timestamp as libc::time_t;
+    let mut tm: libc::tm = unsafe { std::mem::zeroed() };
+    if unsafe { libc::localtime_r(&time, &mut tm) }.is_null() {
+        return timestamp.to_string();
+    }
+    format!(
+        "{:04}-{:02}-{:02} {:02}:{:02}",
+        tm.tm_year + 1900,
+        tm.tm_mon + 1,
+        tm.tm_mday,
+        tm.tm_hour,
+        tm.tm_min
+    )
+}
+
+// ===== Encryption at rest =====
+use aes::Aes256;
+use base64::{engine::general_purpose::STANDARD as BASE64, Engine as _};
+use cbc::cipher::{block_padding::Pkcs7, BlockDecryptMut, BlockEncryptMut, KeyIvInit};
+use hmac::{Hmac, Mac};
+use sha2::Sha256;
+
+const ENCRYPTED_PREFIX: &str = "enc1:";
+const KEYCHAIN_SERVICE: &str = "com.typeswift.app";
+const KEYCHAIN_ACCOUNT: &str = "history-key";
+
+/// AES-256-CBC + HMAC-SHA256 (encrypt-then-MAC) for individual history lines
+pub struct HistoryCipher {
+    enc_key: [u8; 32],
+    mac_key: [u8; 32],
+}
+
+impl HistoryCipher {
+    pub fn from_key(key: &[u8; 64]) -> Self {
+        let mut enc_key = [0u8; 32];
+        let mut mac_key = [0u8; 32];
+        enc_key.copy_from_slice(&key[..32]);
+        mac_key.copy_from_slice(&key[32..]);
+        Self { enc_key, mac_key }
+    }
+
+    /// The key already in the login keychain, if there is one; reads history written while
+    /// encryption was on without creating a key
+    pub fn stored_in_keychain() -> Option<Self> {
+        let stored = crate::platform::macos::ffi::keychain_get(KEYCHAIN_SERVICE, KEYCHAIN_ACCOUNT)?;
+        let key: [u8; 64] = BASE64.decode(stored.trim()).ok()?.try_into().ok()?;
+        Some(Self::from_key(&key))
+    }
+
+    /// Load the key from the login keychain, creating one on first use
+    pub fn from_keychain() -> VoicyResult<Self> {
+        use crate::platform::macos::ffi::{keychain_get, keychain_set};
+        let err = |msg: &str| VoicyError::ConfigLoadFailed(format!("History key: {}", msg));
+
+        if let Some(stored) = keychain_get(KEYCHAIN_SERVICE, KEYCHAIN_ACCOUNT) {
+            let bytes = BASE64.decode(stored.trim()).map_err(|_| err("keychain item is not base64"))?;
+            let key: [u8; 64] = bytes.try_into().map_err(|_| err("keychain item has the wrong length"))?;
+            return Ok(Self::from_key(&key));
+        }
+        let mut key = [0u8; 64];
+        getrandom::getrandom(&mut key).map_err(|e| err(&e.to_string()))?;
+        if !keychain_set(KEYCHAIN_SERVICE, KEYCHAIN_ACCOUNT, &BASE64.encode(key)) {
+            return Err(err("could not store key in keychain"));
+        }
+        info!("Created history encryption key in keychain");
+        Ok(Self::from_key(&key))
+    }
+
+    fn mac(&self) -> Hmac<Sha256> {
+        <Hmac<Sha256> as Mac>::new_from_slice(&self.mac_key).expect("HMAC accepts any key length")
+    }
+
+    fn seal(&self, plaintext: &str) -> VoicyResult<String> {
+        let mut iv = [0u8; 16];
+        getrandom::getrandom(&mut iv)
+            .map_err(|e| VoicyError::ConfigLoadFailed(format!("History IV: {}", e)))?;
+        let ciphertext = cbc::Encryptor::<Aes256>::new(&self.enc_key.into(), &iv.into())
+            .encrypt_padded_vec_mut::<Pkcs7>(plaintext.as_bytes());
+        let mut mac = self.mac();
+        mac.update(&iv);
+        mac.update(&ciphertext);
+        let tag = mac.finalize().into_bytes();
+
+        let mut blob = Vec::with_capacity(16 + ciphertext.len() + tag.len());
+        blob.extend_from_slice(&iv);
+        blob.extend_from_slice(&ciphertext);
+        blob.extend_from_slice(&tag);
+        Ok(format!("{}{}", ENCRYPTED_PREFIX, BASE64.encode(blob)))
+    }
+
+    fn open(&self, line: &str) -> Option<String> {
+        let blob = BASE64.decode(line.strip_prefix(ENCRYPTED_PREFIX)?.trim()).ok()?;
+        if blob.len() < 16 + 16 + 32 {
+            return None;
+        }
+        let (iv, rest) = blob.split_at(16);
+        let (ciphertext, tag) = rest.split_at(rest.len() - 32);
+        let mut mac = self.mac();
+        mac.update(iv);
+        mac.update(ciphertext);
+        mac.verify_slice(tag).ok()?;
+        let iv: [u8; 16] = iv.try_into().ok()?;
+        let plaintext = cbc::Decryptor::<Aes256>::new(&self.enc_key.into(), &iv.into())
+            .decrypt_padded_vec_mut::<Pkcs7>(ciphertext)
+            .ok()?;
+        String::from_utf8(plaintext).ok()
+    }
+}
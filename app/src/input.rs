@@ -1,4 +1,4 @@
-use crate::config::HotkeyConfig;
+use crate::config::{HotkeyConfig, Profile, ReleaseVariant};
 use crate::error::{VoicyError, VoicyResult};
 use global_hotkey::{
     GlobalHotKeyEvent, GlobalHotKeyManager, HotKeyState,
@@ -9,27 +9,141 @@ use std::sync::{Arc, Mutex};
 use std::thread;
 use std::time::Duration;
 
-use crate::platform::macos::ffi::{init_keyboard_monitor, shutdown_keyboard_monitor, register_push_to_talk_callback};
+use crate::platform::macos::ffi::{
+    held_modifiers, init_keyboard_monitor, register_push_to_talk_callback, set_profile_chords, set_push_to_talk_chord,
+    set_suppress_system_fn, set_swallowed_key, shutdown_keyboard_monitor,
+};
 use tracing::{info, warn, error, debug};
 
 #[derive(Debug, Clone, Copy, PartialEq)]
 pub enum HotkeyEvent {
-    PushToTalkPressed,
+    /// `Some(i)` when pressed with the push-to-talk binding of `Config::profiles[i]`, whose
+    /// settings then apply to this dictation whatever app is in front
+    PushToTalkPressed(Option<usize>),
     PushToTalkReleased,
     ToggleWindow,
     OpenPreferences,
+    OpenHistory,
+    /// Show the searchable list of every action
+    OpenCommandPalette,
+    /// Save state and restart the app (e.g. after granting Accessibility)
+    Relaunch,
+    /// Read the last transcription aloud
+    SpeakLast,
+    /// Abort typing in progress and copy what's left to the clipboard
+    StopTyping,
+    /// Flag this moment of a meeting transcription
+    MarkMoment,
+    /// Zip logs, sanitized config and permission states for a bug report
+    GenerateSupportBundle,
+    /// Forget where the popup was dragged and put it back at bottom-center
+    ResetPopupPosition,
+    /// A toggle or choice from the menu bar's Quick Settings submenu
+    QuickSetting(QuickSetting),
+    /// Start/stop recording on behalf of a local API client (MCP); unaffected by hands-free
+    StartDictation,
+    StopDictation,
+    /// Start recording for an editor plugin (IPC), which receives the text instead of it
+    /// being typed
+    StartClientDictation,
+    /// Start recording, or stop and copy the text instead of typing it (typeswift:// links)
+    DictateToClipboard,
+    /// Start or stop transcribing the detected call (menu bar suggestion)
+    TranscribeCall,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum QuickSetting {
+    ToggleTyping,
+    ToggleStreaming,
+    ToggleHandsFree,
+    TogglePresentation,
+    ToggleRehearsal,
+    /// Index into `Config::profiles`; `None` picks by frontmost app
+    Profile(Option<usize>),
+    /// Index into `SUPPORTED_LANGUAGES`; `None` auto-detects
+    Language(Option<usize>),
+    /// Index into `installed_models`
+    Model(usize),
+    /// Index into `Config::output_destinations`
+    Output(usize),
+    /// The next of `Config::output_destinations`, wrapping around
+    CycleOutput,
 }
 
 pub struct HotkeyHandler {
     manager: GlobalHotKeyManager,
     // Live-updated hotkeys shared with the event loop thread
     toggle_hotkey: Arc<Mutex<Option<HotKey>>>,
+    speak_last_hotkey: Arc<Mutex<Option<HotKey>>>,
+    stop_typing_hotkey: Arc<Mutex<Option<HotKey>>>,
+    mark_moment_hotkey: Arc<Mutex<Option<HotKey>>>,
+    command_palette_hotkey: Arc<Mutex<Option<HotKey>>>,
+    cycle_output_hotkey: Arc<Mutex<Option<HotKey>>>,
     push_to_talk_hotkey: Arc<Mutex<Option<HotKey>>>,
-    // Event sender for macOS fn-key callback registration (set by start_event_loop)
+    // Profiles' own push-to-talk bindings (profile index, binding), and those registered
+    // with global-hotkey (modifier chords go to the native monitor instead)
+    profile_bindings: Vec<(usize, String)>,
+    profile_hotkeys: Arc<Mutex<Vec<(usize, HotKey)>>>,
+    // Event sender for macOS modifier-monitor callback registration (set by start_event_loop)
     event_sender: Arc<Mutex<Option<Sender<HotkeyEvent>>>>,
-    uses_fn_key: Arc<Mutex<bool>>,
+    uses_modifier_monitor: Arc<Mutex<bool>>,
+    // Last configuration that registered successfully (fallback on conflicts)
+    active_config: Option<HotkeyConfig>,
+}
+
+/// A binding that could not be registered, with what we fell back to
+#[derive(Debug, Clone)]
+pub struct HotkeyConflict {
+    pub binding: String,
+    pub reason: String,
+    /// Binding that is active now (previous or default)
+    pub fallback: Option<String>,
+    /// Alternatives that registered successfully in a trial
+    pub suggestions: Vec<String>,
+}
+
+impl HotkeyConflict {
+    /// Text for the user-facing dialog
+    pub fn user_message(&self) -> String {
+        let mut msg = format!("Couldn't use \"{}\": {}.", self.binding, self.reason);
+        if let Some(ref fallback) = self.fallback {
+            msg.push_str(&format!("\n\nStill using \"{}\".", fallback));
+        }
+        if !self.suggestions.is_empty() {
+            msg.push_str(&format!("\n\nAvailable alternatives: {}", self.suggestions.join(", ")));
+        }
+        msg
+    }
 }
 
+// Shortcuts macOS assigns by default; another owner is the usual cause of a failed registration
+const SYSTEM_SHORTCUTS: &[(&str, &str)] = &[
+    ("cmd+space", "Spotlight"),
+    ("cmd+opt+space", "Finder search"),
+    ("ctrl+space", "Select previous input source"),
+    ("ctrl+opt+space", "Select next input source"),
+    ("cmd+tab", "App Switcher"),
+    ("cmd+shift+3", "Screenshot"),
+    ("cmd+shift+4", "Screenshot selection"),
+    ("cmd+shift+5", "Screenshot toolbar"),
+    ("ctrl+up", "Mission Control"),
+    ("ctrl+down", "Application windows"),
+    ("ctrl+left", "Move left a space"),
+    ("ctrl+right", "Move right a space"),
+];
+
+// Candidates offered when a binding is taken, in order of preference
+const ALTERNATIVE_BINDINGS: &[&str] = &[
+    "fn",
+    "right_opt",
+    "ctrl+opt",
+    "cmd+shift+space",
+    "ctrl+opt+cmd+space",
+    "f13",
+    "f18",
+];
+
 impl HotkeyHandler {
     pub fn new() -> VoicyResult<Self> {
         let manager = GlobalHotKeyManager::new()
@@ -38,51 +152,165 @@ impl HotkeyHandler {
         Ok(Self {
             manager,
             toggle_hotkey: Arc::new(Mutex::new(None)),
+            speak_last_hotkey: Arc::new(Mutex::new(None)),
+            stop_typing_hotkey: Arc::new(Mutex::new(None)),
+            mark_moment_hotkey: Arc::new(Mutex::new(None)),
+            command_palette_hotkey: Arc::new(Mutex::new(None)),
+            cycle_output_hotkey: Arc::new(Mutex::new(None)),
             push_to_talk_hotkey: Arc::new(Mutex::new(None)),
+            profile_bindings: Vec::new(),
+            profile_hotkeys: Arc::new(Mutex::new(Vec::new())),
             event_sender: Arc::new(Mutex::new(None)),
-            uses_fn_key: Arc::new(Mutex::new(false)),
+            uses_modifier_monitor: Arc::new(Mutex::new(false)),
+            active_config: None,
         })
     }
 
+    /// Sender into the event loop's channel, once `start_event_loop` has run
+    pub fn event_sender(&self) -> Option<Sender<HotkeyEvent>> {
+        self.event_sender.lock().unwrap().clone()
+    }
+
+    /// Use the push-to-talk bindings of `profiles` from the next `apply_hotkeys` on
+    pub fn set_profile_bindings(&mut self, profiles: &[Profile]) {
+        self.profile_bindings = profiles
+            .iter()
+            .enumerate()
+            .filter_map(|(i, profile)| Some((i, profile.push_to_talk.clone()?)))
+            .collect();
+    }
+
+    /// Bindings currently in effect
+    pub fn active_hotkeys(&self) -> Option<HotkeyConfig> {
+        self.active_config.clone()
+    }
+
+    /// Register `config`; on failure restore the previous bindings (or the defaults
+    /// at startup) and describe the conflict instead of leaving hotkeys dead.
+    pub fn apply_hotkeys(&mut self, config: &HotkeyConfig) -> Result<(), HotkeyConflict> {
+        let failure = match find_duplicate_binding(config) {
+            Some(dup) => Err((dup.clone(), "it is assigned to more than one action".to_string())),
+            None => self.register_hotkeys(config).map_err(|e| {
+                let binding = failed_binding(config, &e);
+                let reason = match system_shortcut_owner(&binding) {
+                    Some(owner) => format!("it is already used by macOS ({})", owner),
+                    None => format!("it may already be in use by another app ({})", e),
+                };
+                (binding, reason)
+            }),
+        };
+        let (binding, reason) = match failure {
+            Ok(()) => return Ok(()),
+            Err(f) => f,
+        };
+        warn!("Hotkey conflict for {:?}: {}", binding, reason);
+
+        let fallback_config = self
+            .active_config
+            .clone()
+            .unwrap_or_else(|| crate::config::Config::default().hotkeys);
+        let fallback = match self.register_hotkeys(&fallback_config) {
+            Ok(()) => Some(fallback_config.push_to_talk.clone()),
+            Err(e) => {
+                error!("Failed to restore fallback hotkeys: {}", e);
+                None
+            }
+        };
+        let suggestions = self.suggest_alternatives(&binding, 3);
+        Err(HotkeyConflict { binding, reason, fallback, suggestions })
+    }
+
+    /// Trial-register candidate bindings and return the first `limit` that work
+    pub fn suggest_alternatives(&mut self, failed: &str, limit: usize) -> Vec<String> {
+        let failed = canonicalize_hotkey(failed).unwrap_or_else(|_| failed.to_string());
+        let active_ptt = self.active_config.as_ref().map(|c| c.push_to_talk.clone());
+        let mut out = Vec::new();
+        for candidate in ALTERNATIVE_BINDINGS {
+            if out.len() >= limit {
+                break;
+            }
+            if *candidate == failed || active_ptt.as_deref() == Some(*candidate) {
+                continue;
+            }
+            if parse_modifier_chord(candidate).is_some() {
+                // Native monitor bindings can't collide with other apps
+                out.push(candidate.to_string());
+                continue;
+            }
+            if system_shortcut_owner(candidate).is_some() {
+                continue;
+            }
+            if let Ok(hotkey) = parse_hotkey(candidate) {
+                if self.manager.register(hotkey.clone()).is_ok() {
+                    let _ = self.manager.unregister(hotkey);
+                    out.push(candidate.to_string());
+                }
+            }
+        }
+        out
+    }
+
     pub fn register_hotkeys(&mut self, config: &HotkeyConfig) -> VoicyResult<()> {
+        self.register_bindings(config)?;
+        self.active_config = Some(config.clone());
+        Ok(())
+    }
+
+    fn register_bindings(&mut self, config: &HotkeyConfig) -> VoicyResult<()> {
         // Clear existing hotkeys individually
-        if let Some(ref hotkey) = *self.toggle_hotkey.lock().unwrap() {
-            let _ = self.manager.unregister(hotkey.clone());
+        if let Some(hotkey) = self.toggle_hotkey.lock().unwrap().take() {
+            let _ = self.manager.unregister(hotkey);
+        }
+        if let Some(hotkey) = self.speak_last_hotkey.lock().unwrap().take() {
+            let _ = self.manager.unregister(hotkey);
+        }
+        if let Some(hotkey) = self.stop_typing_hotkey.lock().unwrap().take() {
+            let _ = self.manager.unregister(hotkey);
+        }
+        if let Some(hotkey) = self.mark_moment_hotkey.lock().unwrap().take() {
+            let _ = self.manager.unregister(hotkey);
+        }
+        if let Some(hotkey) = self.command_palette_hotkey.lock().unwrap().take() {
+            let _ = self.manager.unregister(hotkey);
         }
-        if let Some(ref hotkey) = *self.push_to_talk_hotkey.lock().unwrap() {
-            let _ = self.manager.unregister(hotkey.clone());
+        if let Some(hotkey) = self.cycle_output_hotkey.lock().unwrap().take() {
+            let _ = self.manager.unregister(hotkey);
+        }
+        if let Some(hotkey) = self.push_to_talk_hotkey.lock().unwrap().take() {
+            let _ = self.manager.unregister(hotkey);
+        }
+        for (_, hotkey) in self.profile_hotkeys.lock().unwrap().drain(..) {
+            let _ = self.manager.unregister(hotkey);
         }
         
 
-        // Check if trying to use fn key
-        if config.push_to_talk.to_lowercase() == "fn" || 
-           config.push_to_talk.to_lowercase() == "function" ||
-           config.push_to_talk.to_lowercase() == "globe" {
-            // Use native macOS keyboard monitor for fn key
+        // Modifier-only bindings (fn, ctrl+opt, right_opt, ...) can't go through
+        // global-hotkey, which requires a non-modifier key
+        if let Some(chord) = parse_modifier_chord(&config.push_to_talk) {
+            // Use native macOS keyboard monitor for the chord
+            set_push_to_talk_chord(chord.flags, chord.device_flags);
+            set_suppress_system_fn(config.suppress_system_fn);
+            set_swallowed_key(if config.suppress_key_repeat && chord.is_fn_only() { virtual_key_code(Code::Fn) } else { None });
             {
-                let mut uses_fn_key = self.uses_fn_key.lock().unwrap();
-                *uses_fn_key = true;
+                let mut uses_modifier_monitor = self.uses_modifier_monitor.lock().unwrap();
+                *uses_modifier_monitor = true;
             }
-            info!("Using native macOS monitor for fn key (hold to record)");
+            *self.push_to_talk_hotkey.lock().unwrap() = None;
+            info!("Using native macOS monitor for {} (hold to record)", format_modifier_chord(&chord));
             // If event sender is available (event loop started), ensure callback is registered
             if let Some(sender) = self.event_sender.lock().unwrap().clone() {
                 // Initialize the keyboard monitor (idempotent in Swift layer) and register callback
                 if init_keyboard_monitor() {
                     register_push_to_talk_callback(sender);
-                    info!("Registered fn key callback");
+                    info!("Registered modifier chord callback");
                 } else {
-                    error!("Failed to initialize fn key monitoring. Please grant accessibility permissions.");
+                    error!("Failed to initialize modifier key monitoring. Please grant accessibility permissions.");
                 }
             }
             
-            // Still register toggle window if specified
-            if let Some(ref toggle_key) = config.toggle_window {
-                let toggle_hotkey = parse_hotkey(toggle_key)?;
-                self.manager.register(toggle_hotkey.clone())
-                    .map_err(|e| VoicyError::HotkeyRegistrationFailed(format!("Failed to register toggle: {}", e)))?;
-                *self.toggle_hotkey.lock().unwrap() = Some(toggle_hotkey);
-                info!("Registered toggle window: {}", toggle_key);
-            }
+            self.register_profile_bindings(&config.push_to_talk);
+            // Still register toggle window (and other extras) if specified
+            self.register_extra_bindings(config)?;
             
             return Ok(());
         }
@@ -90,18 +318,88 @@ impl HotkeyHandler {
         let push_to_talk_hotkey = parse_hotkey(&config.push_to_talk)?;
         self.manager.register(push_to_talk_hotkey.clone())
             .map_err(|e| VoicyError::HotkeyRegistrationFailed(format!("Failed to register push-to-talk: {}", e)))?;
-        // If we are switching away from fn mode, shut down monitor
+        let swallowed = if config.suppress_key_repeat { virtual_key_code(push_to_talk_hotkey.key) } else { None };
+        set_swallowed_key(swallowed);
         {
-            let mut uses_fn_key = self.uses_fn_key.lock().unwrap();
-            if *uses_fn_key {
+            let mut uses_modifier_monitor = self.uses_modifier_monitor.lock().unwrap();
+            if swallowed.is_some() {
+                // Keep the event tap up without a chord, just to swallow the key's repeats
+                set_push_to_talk_chord(0, 0);
+                if !*uses_modifier_monitor && self.event_sender.lock().unwrap().is_some() && !init_keyboard_monitor() {
+                    warn!("Keyboard monitor unavailable; push-to-talk key repeats won't be suppressed");
+                }
+                *uses_modifier_monitor = true;
+            } else if *uses_modifier_monitor {
+                // If we are switching away from a modifier chord, shut down monitor
                 shutdown_keyboard_monitor();
-                *uses_fn_key = false;
-                info!("Disabled fn key monitor");
+                *uses_modifier_monitor = false;
+                info!("Disabled modifier key monitor");
             }
         }
         *self.push_to_talk_hotkey.lock().unwrap() = Some(push_to_talk_hotkey);
         info!("Registered push-to-talk: {} (hold to record)", config.push_to_talk);
 
+        self.register_profile_bindings(&config.push_to_talk);
+        self.register_extra_bindings(config)
+    }
+
+    /// Profiles' own push-to-talk bindings: modifier chords go to the native monitor next to
+    /// the main one, other keys to global-hotkey. A binding that is taken, or already used by
+    /// push-to-talk or another profile, is skipped with a warning.
+    fn register_profile_bindings(&mut self, main_binding: &str) {
+        let mut seen = vec![canonicalize_hotkey(main_binding).unwrap_or_else(|_| main_binding.to_string())];
+        let mut chords = Vec::new();
+        let mut hotkeys = Vec::new();
+        for (profile, binding) in &self.profile_bindings {
+            let canonical = canonicalize_hotkey(binding).unwrap_or_else(|_| binding.clone());
+            if seen.contains(&canonical) {
+                warn!("Profile push-to-talk \"{}\" is already in use; skipped", binding);
+                continue;
+            }
+            seen.push(canonical);
+            if let Some(chord) = parse_modifier_chord(binding) {
+                chords.push((chord.flags, chord.device_flags, *profile));
+                continue;
+            }
+            let registered = parse_hotkey(binding).and_then(|hotkey| {
+                self.manager
+                    .register(hotkey)
+                    .map(|()| hotkey)
+                    .map_err(|e| VoicyError::HotkeyRegistrationFailed(e.to_string()))
+            });
+            match registered {
+                Ok(hotkey) => {
+                    info!("Registered profile push-to-talk: {}", binding);
+                    hotkeys.push((*profile, hotkey));
+                }
+                Err(e) => warn!("Couldn't register profile push-to-talk \"{}\": {}", binding, e),
+            }
+        }
+        *self.profile_hotkeys.lock().unwrap() = hotkeys;
+
+        set_profile_chords(&chords);
+        if chords.is_empty() {
+            return;
+        }
+        let mut uses_modifier_monitor = self.uses_modifier_monitor.lock().unwrap();
+        if !*uses_modifier_monitor {
+            // Only the profile chords; push-to-talk itself is a global hotkey
+            set_push_to_talk_chord(0, 0);
+            if let Some(sender) = self.event_sender.lock().unwrap().clone() {
+                if init_keyboard_monitor() {
+                    register_push_to_talk_callback(sender);
+                } else {
+                    error!("Failed to initialize modifier key monitoring. Please grant accessibility permissions.");
+                }
+            }
+            *uses_modifier_monitor = true;
+        }
+        info!("Monitoring {} profile push-to-talk chord(s)", chords.len());
+    }
+
+    /// Bindings other than push-to-talk (toggle window, speak last result, stop typing, mark
+    /// moment, command palette)
+    fn register_extra_bindings(&mut self, config: &HotkeyConfig) -> VoicyResult<()> {
         if let Some(ref toggle_key) = config.toggle_window {
             let toggle_hotkey = parse_hotkey(toggle_key)?;
             self.manager.register(toggle_hotkey.clone())
@@ -110,7 +408,45 @@ impl HotkeyHandler {
             info!("Registered toggle window: {}", toggle_key);
         }
 
-        
+        if let Some(ref speak_key) = config.speak_last {
+            let speak_hotkey = parse_hotkey(speak_key)?;
+            self.manager.register(speak_hotkey.clone())
+                .map_err(|e| VoicyError::HotkeyRegistrationFailed(format!("Failed to register speak last: {}", e)))?;
+            *self.speak_last_hotkey.lock().unwrap() = Some(speak_hotkey);
+            info!("Registered speak last result: {}", speak_key);
+        }
+
+        if let Some(ref stop_key) = config.stop_typing {
+            let stop_hotkey = parse_hotkey(stop_key)?;
+            self.manager.register(stop_hotkey.clone())
+                .map_err(|e| VoicyError::HotkeyRegistrationFailed(format!("Failed to register stop typing: {}", e)))?;
+            *self.stop_typing_hotkey.lock().unwrap() = Some(stop_hotkey);
+            info!("Registered stop typing: {}", stop_key);
+        }
+
+        if let Some(ref mark_key) = config.mark_moment {
+            let mark_hotkey = parse_hotkey(mark_key)?;
+            self.manager.register(mark_hotkey.clone())
+                .map_err(|e| VoicyError::HotkeyRegistrationFailed(format!("Failed to register mark moment: {}", e)))?;
+            *self.mark_moment_hotkey.lock().unwrap() = Some(mark_hotkey);
+            info!("Registered mark moment: {}", mark_key);
+        }
+
+        if let Some(ref palette_key) = config.command_palette {
+            let palette_hotkey = parse_hotkey(palette_key)?;
+            self.manager.register(palette_hotkey.clone())
+                .map_err(|e| VoicyError::HotkeyRegistrationFailed(format!("Failed to register command palette: {}", e)))?;
+            *self.command_palette_hotkey.lock().unwrap() = Some(palette_hotkey);
+            info!("Registered command palette: {}", palette_key);
+        }
+
+        if let Some(ref cycle_key) = config.cycle_output {
+            let cycle_hotkey = parse_hotkey(cycle_key)?;
+            self.manager.register(cycle_hotkey.clone())
+                .map_err(|e| VoicyError::HotkeyRegistrationFailed(format!("Failed to register cycle output: {}", e)))?;
+            *self.cycle_output_hotkey.lock().unwrap() = Some(cycle_hotkey);
+            info!("Registered cycle output: {}", cycle_key);
+        }
 
         Ok(())
     }
@@ -123,22 +459,28 @@ impl HotkeyHandler {
             *slot = Some(sender.clone());
         }
         
-        // Setup fn key monitoring if needed
-        if *self.uses_fn_key.lock().unwrap() {
+        // Setup modifier chord monitoring if needed
+        if *self.uses_modifier_monitor.lock().unwrap() {
             let sender_clone = sender.clone();
             
             // Initialize the keyboard monitor
             if init_keyboard_monitor() {
                 // Register callback for push-to-talk events
                 register_push_to_talk_callback(sender_clone);
-                info!("macOS fn key monitoring initialized");
+                info!("macOS modifier key monitoring initialized");
             } else {
-                error!("Failed to initialize fn key monitoring. Please grant accessibility permissions.");
+                error!("Failed to initialize modifier key monitoring. Please grant accessibility permissions.");
             }
         }
         
         let toggle_hotkey = Arc::clone(&self.toggle_hotkey);
+        let speak_last_hotkey = Arc::clone(&self.speak_last_hotkey);
+        let stop_typing_hotkey = Arc::clone(&self.stop_typing_hotkey);
+        let mark_moment_hotkey = Arc::clone(&self.mark_moment_hotkey);
+        let command_palette_hotkey = Arc::clone(&self.command_palette_hotkey);
+        let cycle_output_hotkey = Arc::clone(&self.cycle_output_hotkey);
         let push_to_talk_hotkey = Arc::clone(&self.push_to_talk_hotkey);
+        let profile_hotkeys = Arc::clone(&self.profile_hotkeys);
         let is_push_to_talk_active = Arc::new(Mutex::new(false));
 
         thread::spawn(move || {
@@ -150,12 +492,22 @@ impl HotkeyHandler {
                         debug!("Received hotkey event: {:?}", event);
                         match event.state {
                             HotKeyState::Pressed => {
-                                if let Some(hotkey_event) = handle_hotkey_press(
-                                    event.id,
-                                    &toggle_hotkey,
-                                    &push_to_talk_hotkey,
-                                    &is_push_to_talk_active,
-                                ) {
+                                let hotkey_event =
+                                    handle_profile_hotkey(event.id, true, &profile_hotkeys, &is_push_to_talk_active)
+                                        .or_else(|| {
+                                            handle_hotkey_press(
+                                                event.id,
+                                                &toggle_hotkey,
+                                                &speak_last_hotkey,
+                                                &stop_typing_hotkey,
+                                                &mark_moment_hotkey,
+                                                &command_palette_hotkey,
+                                                &cycle_output_hotkey,
+                                                &push_to_talk_hotkey,
+                                                &is_push_to_talk_active,
+                                            )
+                                        });
+                                if let Some(hotkey_event) = hotkey_event {
                                     debug!("Sending event: {:?}", hotkey_event);
                                     if let Err(e) = sender.send(hotkey_event) {
                                         error!("Failed to send hotkey event: {}", e);
@@ -163,11 +515,12 @@ impl HotkeyHandler {
                                 }
                             }
                             HotKeyState::Released => {
-                                if let Some(hotkey_event) = handle_hotkey_release(
-                                    event.id,
-                                    &push_to_talk_hotkey,
-                                    &is_push_to_talk_active,
-                                ) {
+                                let hotkey_event =
+                                    handle_profile_hotkey(event.id, false, &profile_hotkeys, &is_push_to_talk_active)
+                                        .or_else(|| {
+                                            handle_hotkey_release(event.id, &push_to_talk_hotkey, &is_push_to_talk_active)
+                                        });
+                                if let Some(hotkey_event) = hotkey_event {
                                     debug!("Sending event: {:?}", hotkey_event);
                                     if let Err(e) = sender.send(hotkey_event) {
                                         error!("Failed to send hotkey event: {}", e);
@@ -192,6 +545,11 @@ impl HotkeyHandler {
 fn handle_hotkey_press(
     hotkey_id: u32,
     toggle_hotkey: &Arc<Mutex<Option<HotKey>>>,
+    speak_last_hotkey: &Arc<Mutex<Option<HotKey>>>,
+    stop_typing_hotkey: &Arc<Mutex<Option<HotKey>>>,
+    mark_moment_hotkey: &Arc<Mutex<Option<HotKey>>>,
+    command_palette_hotkey: &Arc<Mutex<Option<HotKey>>>,
+    cycle_output_hotkey: &Arc<Mutex<Option<HotKey>>>,
     push_to_talk_hotkey: &Arc<Mutex<Option<HotKey>>>,
     is_push_to_talk_active: &Arc<Mutex<bool>>,
 ) -> Option<HotkeyEvent> {
@@ -201,7 +559,7 @@ fn handle_hotkey_press(
             if !*is_active {
                 *is_active = true;
                 info!("Push-to-talk PRESSED");
-                return Some(HotkeyEvent::PushToTalkPressed);
+                return Some(HotkeyEvent::PushToTalkPressed(None));
             }
         }
     }
@@ -213,11 +571,68 @@ fn handle_hotkey_press(
         }
     }
 
+    if let Some(ref speak) = *speak_last_hotkey.lock().unwrap() {
+        if speak.id() == hotkey_id {
+            info!("Speak last result hotkey pressed");
+            return Some(HotkeyEvent::SpeakLast);
+        }
+    }
+
+    if let Some(ref stop) = *stop_typing_hotkey.lock().unwrap() {
+        if stop.id() == hotkey_id {
+            info!("Stop typing hotkey pressed");
+            return Some(HotkeyEvent::StopTyping);
+        }
+    }
+
+    if let Some(ref mark) = *mark_moment_hotkey.lock().unwrap() {
+        if mark.id() == hotkey_id {
+            info!("Mark moment hotkey pressed");
+            return Some(HotkeyEvent::MarkMoment);
+        }
+    }
+
+    if let Some(ref palette) = *command_palette_hotkey.lock().unwrap() {
+        if palette.id() == hotkey_id {
+            info!("Command palette hotkey pressed");
+            return Some(HotkeyEvent::OpenCommandPalette);
+        }
+    }
+
+    if let Some(ref cycle) = *cycle_output_hotkey.lock().unwrap() {
+        if cycle.id() == hotkey_id {
+            info!("Cycle output hotkey pressed");
+            return Some(HotkeyEvent::QuickSetting(QuickSetting::CycleOutput));
+        }
+    }
+
     
     
     None
 }
 
+/// Press or release of a profile's own push-to-talk key
+fn handle_profile_hotkey(
+    hotkey_id: u32,
+    pressed: bool,
+    profile_hotkeys: &Arc<Mutex<Vec<(usize, HotKey)>>>,
+    is_push_to_talk_active: &Arc<Mutex<bool>>,
+) -> Option<HotkeyEvent> {
+    let profile = profile_hotkeys.lock().unwrap().iter().find(|(_, hotkey)| hotkey.id() == hotkey_id)?.0;
+    let mut is_active = is_push_to_talk_active.lock().unwrap();
+    if *is_active == pressed {
+        return None;
+    }
+    *is_active = pressed;
+    if pressed {
+        info!("Push-to-talk PRESSED (profile {})", profile);
+        Some(HotkeyEvent::PushToTalkPressed(Some(profile)))
+    } else {
+        info!("Push-to-talk RELEASED (profile {})", profile);
+        Some(HotkeyEvent::PushToTalkReleased)
+    }
+}
+
 fn handle_hotkey_release(
     hotkey_id: u32,
     push_to_talk_hotkey: &Arc<Mutex<Option<HotKey>>>,
@@ -237,19 +652,75 @@ fn handle_hotkey_release(
     None
 }
 
-fn parse_hotkey(hotkey_str: &str) -> VoicyResult<HotKey> {
-    let parts: Vec<&str> = hotkey_str.split('+').collect();
+// Name of the macOS feature that owns this shortcut by default, if any
+fn system_shortcut_owner(binding: &str) -> Option<&'static str> {
+    let canonical = canonicalize_hotkey(binding).ok()?;
+    SYSTEM_SHORTCUTS
+        .iter()
+        .find(|(shortcut, _)| canonicalize_hotkey(shortcut).ok().as_deref() == Some(canonical.as_str()))
+        .map(|(_, owner)| *owner)
+}
+
+// Same binding used for several actions
+fn find_duplicate_binding(config: &HotkeyConfig) -> Option<&String> {
+    let bindings: Vec<&String> = std::iter::once(&config.push_to_talk)
+        .chain(config.toggle_window.iter())
+        .chain(config.preferences.iter())
+        .chain(config.speak_last.iter())
+        .chain(config.stop_typing.iter())
+        .chain(config.mark_moment.iter())
+        .chain(config.command_palette.iter())
+        .chain(config.cycle_output.iter())
+        .collect();
+    bindings.iter().enumerate().find_map(|(i, a)| {
+        let a_canon = canonicalize_hotkey(a).ok()?;
+        bindings[i + 1..]
+            .iter()
+            .any(|b| canonicalize_hotkey(b).ok().as_deref() == Some(a_canon.as_str()))
+            .then_some(*a)
+    })
+}
+
+// Best guess at which binding a registration error refers to
+fn failed_binding(config: &HotkeyConfig, err: &VoicyError) -> String {
+    let msg = err.to_string();
+    match config.toggle_window {
+        Some(ref toggle) if msg.contains("toggle") => toggle.clone(),
+        _ if msg.contains("speak last") => config.speak_last.clone().unwrap_or_default(),
+        _ if msg.contains("stop typing") => config.stop_typing.clone().unwrap_or_default(),
+        _ if msg.contains("mark moment") => config.mark_moment.clone().unwrap_or_default(),
+        _ if msg.contains("command palette") => config.command_palette.clone().unwrap_or_default(),
+        _ if msg.contains("cycle output") => config.cycle_output.clone().unwrap_or_default(),
+        _ => config.push_to_talk.clone(),
+    }
+}
+
+/// Parse a hotkey string like "cmd+shift+space" (case/whitespace insensitive).
+/// Exactly one non-modifier key is required.
+pub fn parse_hotkey(hotkey_str: &str) -> VoicyResult<HotKey> {
     let mut modifiers = Modifiers::empty();
     let mut key_code = None;
 
-    for part in parts {
-        match part.to_lowercase().as_str() {
-            "cmd" | "command" | "meta" => { modifiers |= Modifiers::SUPER }
-            "ctrl" | "control" => modifiers |= Modifiers::CONTROL,
-            "alt" | "option" | "opt" => modifiers |= Modifiers::ALT,  // Support Option key
-            "shift" => modifiers |= Modifiers::SHIFT,
-            "super" | "win" => modifiers |= Modifiers::SUPER,
+    for raw in hotkey_str.split('+') {
+        let part = raw.trim().to_lowercase();
+        match part.as_str() {
+            "" => {
+                return Err(VoicyError::HotkeyRegistrationFailed(format!(
+                    "Empty component in hotkey: {:?}",
+                    hotkey_str
+                )))
+            }
+            "cmd" | "command" | "meta" | "super" | "win" | "\u{2318}" => modifiers |= Modifiers::SUPER,
+            "ctrl" | "control" | "\u{2303}" => modifiers |= Modifiers::CONTROL,
+            "alt" | "option" | "opt" | "\u{2325}" => modifiers |= Modifiers::ALT, // Support Option key
+            "shift" | "\u{21e7}" => modifiers |= Modifiers::SHIFT,
             key => {
+                if key_code.is_some() {
+                    return Err(VoicyError::HotkeyRegistrationFailed(format!(
+                        "More than one key in hotkey: {:?}",
+                        hotkey_str
+                    )));
+                }
                 key_code = Some(parse_key_code(key)?);
             }
         }
@@ -262,66 +733,298 @@ fn parse_hotkey(hotkey_str: &str) -> VoicyResult<HotKey> {
     Ok(HotKey::new(Some(modifiers), key_code))
 }
 
+/// Format a hotkey in canonical form: modifiers in cmd, ctrl, opt, shift order,
+/// then the canonical key name. `parse_hotkey(&format_hotkey(h)?)` yields `h` again;
+/// None for a key the parser has no name for.
+pub fn format_hotkey(hotkey: &HotKey) -> Option<String> {
+    let key = key_code_name(hotkey.key)?;
+    let mut parts: Vec<&str> = Vec::new();
+    if hotkey.mods.contains(Modifiers::SUPER) { parts.push("cmd"); }
+    if hotkey.mods.contains(Modifiers::CONTROL) { parts.push("ctrl"); }
+    if hotkey.mods.contains(Modifiers::ALT) { parts.push("opt"); }
+    if hotkey.mods.contains(Modifiers::SHIFT) { parts.push("shift"); }
+    parts.push(key);
+    Some(parts.join("+"))
+}
+
+/// Canonical serialization of a hotkey string, as stored in config.
+/// Modifier-only chords (handled by the native monitor) use `format_modifier_chord`.
+pub fn canonicalize_hotkey(hotkey_str: &str) -> VoicyResult<String> {
+    if let Some(chord) = parse_modifier_chord(hotkey_str) {
+        return Ok(format_modifier_chord(&chord));
+    }
+    let hotkey = parse_hotkey(hotkey_str)?;
+    format_hotkey(&hotkey).ok_or_else(|| {
+        VoicyError::HotkeyRegistrationFailed(format!("No name for key {:?} in hotkey: {:?}", hotkey.key, hotkey_str))
+    })
+}
+
+/// Push-to-talk bound to held modifiers only, e.g. "fn", "ctrl+opt", "right_opt".
+/// Bits follow CGEventFlags so they can be handed straight to the Swift monitor.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ModifierChord {
+    /// Device-independent modifier flags that must be held (and nothing else)
+    pub flags: u64,
+    /// Device-dependent left/right bits that must also be set (0 = either side)
+    pub device_flags: u64,
+}
+
+impl ModifierChord {
+    /// Fn/Globe on its own, which macOS also has an action for
+    pub fn is_fn_only(&self) -> bool {
+        self.flags == FLAG_SECONDARY_FN
+    }
+}
+
+// CGEventFlags device-independent masks
+const FLAG_SHIFT: u64 = 0x0002_0000;
+const FLAG_CONTROL: u64 = 0x0004_0000;
+const FLAG_ALTERNATE: u64 = 0x0008_0000;
+const FLAG_COMMAND: u64 = 0x0010_0000;
+const FLAG_SECONDARY_FN: u64 = 0x0080_0000;
+
+// Device-dependent side masks (NX_DEVICE*KEYMASK in IOLLEvent.h)
+const DEVICE_LCTL: u64 = 0x0000_0001;
+const DEVICE_LSHIFT: u64 = 0x0000_0002;
+const DEVICE_RSHIFT: u64 = 0x0000_0004;
+const DEVICE_LCMD: u64 = 0x0000_0008;
+const DEVICE_RCMD: u64 = 0x0000_0010;
+const DEVICE_LALT: u64 = 0x0000_0020;
+const DEVICE_RALT: u64 = 0x0000_0040;
+const DEVICE_RCTL: u64 = 0x0000_2000;
+
+// (canonical name, aliases, flag, left bit, right bit) in canonical order
+const CHORD_MODIFIERS: &[(&str, &[&str], u64, u64, u64)] = &[
+    ("cmd", &["cmd", "command", "meta", "super", "win", "\u{2318}"], FLAG_COMMAND, DEVICE_LCMD, DEVICE_RCMD),
+    ("ctrl", &["ctrl", "control", "\u{2303}"], FLAG_CONTROL, DEVICE_LCTL, DEVICE_RCTL),
+    ("opt", &["opt", "option", "alt", "\u{2325}"], FLAG_ALTERNATE, DEVICE_LALT, DEVICE_RALT),
+    ("shift", &["shift", "\u{21e7}"], FLAG_SHIFT, DEVICE_LSHIFT, DEVICE_RSHIFT),
+    ("fn", &["fn", "function", "globe"], FLAG_SECONDARY_FN, 0, 0),
+];
+
+/// Parse a binding made only of modifiers. Returns None if any part is a regular key.
+pub fn parse_modifier_chord(hotkey_str: &str) -> Option<ModifierChord> {
+    let mut chord = ModifierChord { flags: 0, device_flags: 0 };
+    for raw in hotkey_str.split('+') {
+        let part = raw.trim().to_lowercase();
+        let (side, name) = if let Some(rest) = part.strip_prefix("left_").or_else(|| part.strip_prefix("left")) {
+            (Some(false), rest)
+        } else if let Some(rest) = part.strip_prefix("right_").or_else(|| part.strip_prefix("right")) {
+            (Some(true), rest)
+        } else if let Some(rest) = part.strip_prefix('l').filter(|r| !r.is_empty()) {
+            (Some(false), rest)
+        } else if let Some(rest) = part.strip_prefix('r').filter(|r| !r.is_empty()) {
+            (Some(true), rest)
+        } else {
+            (None, part.as_str())
+        };
+        let &(_, _, flag, left, right) = CHORD_MODIFIERS
+            .iter()
+            .find(|(_, aliases, ..)| aliases.contains(&name))?;
+        chord.flags |= flag;
+        match side {
+            None => {}
+            Some(_) if left == 0 => return None, // fn has no sides
+            Some(false) => chord.device_flags |= left,
+            Some(true) => chord.device_flags |= right,
+        }
+    }
+    if chord.flags == 0 { None } else { Some(chord) }
+}
+
+/// Canonical string for a modifier chord (round-trips through `parse_modifier_chord`)
+pub fn format_modifier_chord(chord: &ModifierChord) -> String {
+    let mut parts: Vec<String> = Vec::new();
+    for &(name, _, flag, left, right) in CHORD_MODIFIERS {
+        if chord.flags & flag == 0 {
+            continue;
+        }
+        let has_left = left != 0 && chord.device_flags & left != 0;
+        let has_right = right != 0 && chord.device_flags & right != 0;
+        if has_left {
+            parts.push(format!("left_{}", name));
+        }
+        if has_right {
+            parts.push(format!("right_{}", name));
+        }
+        if !has_left && !has_right {
+            parts.push(name.to_string());
+        }
+    }
+    parts.join("+")
+}
+
+/// The release variant for the modifiers held right now. Modifiers that are part of the
+/// `push_to_talk` binding don't count, so letting go of "ctrl+opt" one key at a time
+/// doesn't pick an "opt" variant.
+pub fn release_variant<'a>(variants: &'a [ReleaseVariant], push_to_talk: &str) -> Option<&'a ReleaseVariant> {
+    if variants.is_empty() {
+        return None;
+    }
+    let held = parse_modifier_chord(&held_modifiers().join("+"))?;
+    let binding = push_to_talk.split('+').filter_map(parse_modifier_chord).fold(0, |flags, c| flags | c.flags);
+    let flags = held.flags & !binding;
+    variants
+        .iter()
+        .filter_map(|variant| parse_modifier_chord(&variant.modifiers).map(|chord| (variant, chord)))
+        .filter(|(_, chord)| chord.flags & !flags == 0 && chord.device_flags & !held.device_flags == 0)
+        .max_by_key(|(_, chord)| chord.flags.count_ones())
+        .map(|(variant, _)| variant)
+}
+
 impl Drop for HotkeyHandler {
     fn drop(&mut self) {
-        if *self.uses_fn_key.lock().unwrap() {
+        if *self.uses_modifier_monitor.lock().unwrap() {
             shutdown_keyboard_monitor();
             info!("Cleaned up keyboard monitor");
         }
     }
 }
 
-fn parse_key_code(key: &str) -> VoicyResult<Code> {
-    let code = match key.to_lowercase().as_str() {
-        "a" => Code::KeyA, "b" => Code::KeyB, "c" => Code::KeyC, "d" => Code::KeyD,
-        "e" => Code::KeyE, "f" => Code::KeyF, "g" => Code::KeyG, "h" => Code::KeyH,
-        "i" => Code::KeyI, "j" => Code::KeyJ, "k" => Code::KeyK, "l" => Code::KeyL,
-        "m" => Code::KeyM, "n" => Code::KeyN, "o" => Code::KeyO, "p" => Code::KeyP,
-        "q" => Code::KeyQ, "r" => Code::KeyR, "s" => Code::KeyS, "t" => Code::KeyT,
-        "u" => Code::KeyU, "v" => Code::KeyV, "w" => Code::KeyW, "x" => Code::KeyX,
-        "y" => Code::KeyY, "z" => Code::KeyZ,
-        "0" => Code::Digit0, "1" => Code::Digit1, "2" => Code::Digit2, "3" => Code::Digit3,
-        "4" => Code::Digit4, "5" => Code::Digit5, "6" => Code::Digit6, "7" => Code::Digit7,
-        "8" => Code::Digit8, "9" => Code::Digit9,
-        "space" => Code::Space,
-        "enter" | "return" => Code::Enter,
-        "tab" => Code::Tab,
-        "escape" | "esc" => Code::Escape,
-        "backspace" => Code::Backspace,
-        "delete" => Code::Delete,
-        "f1" => Code::F1, "f2" => Code::F2, "f3" => Code::F3, "f4" => Code::F4,
-        "f5" => Code::F5, "f6" => Code::F6, "f7" => Code::F7, "f8" => Code::F8,
-        "f9" => Code::F9, "f10" => Code::F10, "f11" => Code::F11, "f12" => Code::F12,
-        "f13" => Code::F13, "f14" => Code::F14, "f15" => Code::F15, "f16" => Code::F16,
-        "f17" => Code::F17, "f18" => Code::F18, "f19" => Code::F19, "f20" => Code::F20,
-        "f21" => Code::F21, "f22" => Code::F22, "f23" => Code::F23, "f24" => Code::F24,
-        "globe" | "fn" | "function" => Code::Fn,
-        "left" | "arrowleft" => Code::ArrowLeft,
-        "right" | "arrowright" => Code::ArrowRight,
-        "up" | "arrowup" => Code::ArrowUp,
-        "down" | "arrowdown" => Code::ArrowDown,
-        "home" => Code::Home,
-        "end" => Code::End,
-        "pageup" => Code::PageUp,
-        "pagedown" => Code::PageDown,
-        "insert" => Code::Insert,
-        "capslock" => Code::CapsLock,
-        "numlock" => Code::NumLock,
-        "scrolllock" => Code::ScrollLock,
-        "pause" => Code::Pause,
-        "printscreen" => Code::PrintScreen,
-        "comma" | "," => Code::Comma,
-        "period" | "." => Code::Period,
-        "slash" | "/" => Code::Slash,
-        "semicolon" | ";" => Code::Semicolon,
-        "quote" | "'" => Code::Quote,
-        "bracket_left" | "[" => Code::BracketLeft,
-        "bracket_right" | "]" => Code::BracketRight,
-        "backslash" | "\\" => Code::Backslash,
-        "minus" | "-" => Code::Minus,
-        "equal" | "=" => Code::Equal,
-        "backquote" | "`" => Code::Backquote,
-        _ => return Err(VoicyError::HotkeyRegistrationFailed(format!("Unknown key: {}", key))),
+// Key names accepted by the parser. The first name listed for a code is canonical.
+const KEY_NAMES: &[(&str, Code)] = &[
+    ("a", Code::KeyA), ("b", Code::KeyB), ("c", Code::KeyC), ("d", Code::KeyD),
+    ("e", Code::KeyE), ("f", Code::KeyF), ("g", Code::KeyG), ("h", Code::KeyH),
+    ("i", Code::KeyI), ("j", Code::KeyJ), ("k", Code::KeyK), ("l", Code::KeyL),
+    ("m", Code::KeyM), ("n", Code::KeyN), ("o", Code::KeyO), ("p", Code::KeyP),
+    ("q", Code::KeyQ), ("r", Code::KeyR), ("s", Code::KeyS), ("t", Code::KeyT),
+    ("u", Code::KeyU), ("v", Code::KeyV), ("w", Code::KeyW), ("x", Code::KeyX),
+    ("y", Code::KeyY), ("z", Code::KeyZ),
+    ("0", Code::Digit0), ("1", Code::Digit1), ("2", Code::Digit2), ("3", Code::Digit3),
+    ("4", Code::Digit4), ("5", Code::Digit5), ("6", Code::Digit6), ("7", Code::Digit7),
+    ("8", Code::Digit8), ("9", Code::Digit9),
+    ("space", Code::Space),
+    ("enter", Code::Enter), ("return", Code::Enter),
+    ("tab", Code::Tab),
+    ("escape", Code::Escape), ("esc", Code::Escape),
+    ("backspace", Code::Backspace),
+    ("delete", Code::Delete),
+    ("f1", Code::F1), ("f2", Code::F2), ("f3", Code::F3), ("f4", Code::F4),
+    ("f5", Code::F5), ("f6", Code::F6), ("f7", Code::F7), ("f8", Code::F8),
+    ("f9", Code::F9), ("f10", Code::F10), ("f11", Code::F11), ("f12", Code::F12),
+    ("f13", Code::F13), ("f14", Code::F14), ("f15", Code::F15), ("f16", Code::F16),
+    ("f17", Code::F17), ("f18", Code::F18), ("f19", Code::F19), ("f20", Code::F20),
+    ("f21", Code::F21), ("f22", Code::F22), ("f23", Code::F23), ("f24", Code::F24),
+    ("fn", Code::Fn), ("globe", Code::Fn), ("function", Code::Fn),
+    ("left", Code::ArrowLeft), ("arrowleft", Code::ArrowLeft),
+    ("right", Code::ArrowRight), ("arrowright", Code::ArrowRight),
+    ("up", Code::ArrowUp), ("arrowup", Code::ArrowUp),
+    ("down", Code::ArrowDown), ("arrowdown", Code::ArrowDown),
+    ("home", Code::Home),
+    ("end", Code::End),
+    ("pageup", Code::PageUp),
+    ("pagedown", Code::PageDown),
+    ("insert", Code::Insert),
+    ("capslock", Code::CapsLock),
+    ("numlock", Code::NumLock),
+    ("scrolllock", Code::ScrollLock),
+    ("pause", Code::Pause),
+    ("printscreen", Code::PrintScreen),
+    ("comma", Code::Comma), (",", Code::Comma),
+    ("period", Code::Period), (".", Code::Period),
+    ("slash", Code::Slash), ("/", Code::Slash),
+    ("semicolon", Code::Semicolon), (";", Code::Semicolon),
+    ("quote", Code::Quote), ("'", Code::Quote),
+    ("bracket_left", Code::BracketLeft), ("[", Code::BracketLeft),
+    ("bracket_right", Code::BracketRight), ("]", Code::BracketRight),
+    ("backslash", Code::Backslash), ("\\", Code::Backslash),
+    ("minus", Code::Minus), ("-", Code::Minus),
+    ("equal", Code::Equal), ("=", Code::Equal),
+    ("backquote", Code::Backquote), ("`", Code::Backquote),
+];
+
+/// macOS virtual key code (kVK_*) for a key the parser supports, as seen by the event tap
+fn virtual_key_code(code: Code) -> Option<u16> {
+    let vk = match code {
+        Code::KeyA => 0x00, Code::KeyS => 0x01, Code::KeyD => 0x02, Code::KeyF => 0x03,
+        Code::KeyH => 0x04, Code::KeyG => 0x05, Code::KeyZ => 0x06, Code::KeyX => 0x07,
+        Code::KeyC => 0x08, Code::KeyV => 0x09, Code::KeyB => 0x0B, Code::KeyQ => 0x0C,
+        Code::KeyW => 0x0D, Code::KeyE => 0x0E, Code::KeyR => 0x0F, Code::KeyY => 0x10,
+        Code::KeyT => 0x11, Code::KeyO => 0x1F, Code::KeyU => 0x20, Code::KeyI => 0x22,
+        Code::KeyP => 0x23, Code::KeyL => 0x25, Code::KeyJ => 0x26, Code::KeyK => 0x28,
+        Code::KeyN => 0x2D, Code::KeyM => 0x2E,
+        Code::Digit1 => 0x12, Code::Digit2 => 0x13, Code::Digit3 => 0x14, Code::Digit4 => 0x15,
+        Code::Digit6 => 0x16, Code::Digit5 => 0x17, Code::Digit9 => 0x19, Code::Digit7 => 0x1A,
+        Code::Digit8 => 0x1C, Code::Digit0 => 0x1D,
+        Code::Equal => 0x18, Code::Minus => 0x1B, Code::BracketRight => 0x1E, Code::BracketLeft => 0x21,
+        Code::Quote => 0x27, Code::Semicolon => 0x29, Code::Backslash => 0x2A, Code::Comma => 0x2B,
+        Code::Slash => 0x2C, Code::Period => 0x2F, Code::Backquote => 0x32,
+        Code::Enter => 0x24, Code::Tab => 0x30, Code::Space => 0x31, Code::Backspace => 0x33,
+        Code::Escape => 0x35, Code::CapsLock => 0x39, Code::Fn => 0x3F,
+        Code::F1 => 0x7A, Code::F2 => 0x78, Code::F3 => 0x63, Code::F4 => 0x76,
+        Code::F5 => 0x60, Code::F6 => 0x61, Code::F7 => 0x62, Code::F8 => 0x64,
+        Code::F9 => 0x65, Code::F10 => 0x6D, Code::F11 => 0x67, Code::F12 => 0x6F,
+        Code::F13 => 0x69, Code::F14 => 0x6B, Code::F15 => 0x71, Code::F16 => 0x6A,
+        Code::F17 => 0x40, Code::F18 => 0x4F, Code::F19 => 0x50, Code::F20 => 0x5A,
+        Code::Insert => 0x72, Code::Home => 0x73, Code::PageUp => 0x74, Code::Delete => 0x75,
+        Code::End => 0x77, Code::PageDown => 0x79,
+        Code::ArrowLeft => 0x7B, Code::ArrowRight => 0x7C, Code::ArrowDown => 0x7D, Code::ArrowUp => 0x7E,
+        _ => return None,
     };
-    Ok(code)
+    Some(vk)
+}
+
+fn parse_key_code(key: &str) -> VoicyResult<Code> {
+    let key = key.trim().to_lowercase();
+    KEY_NAMES
+        .iter()
+        .find(|(name, _)| *name == key)
+        .map(|(_, code)| *code)
+        .ok_or_else(|| VoicyError::HotkeyRegistrationFailed(format!("Unknown key: {}", key)))
+}
+
+/// Canonical name for a key code, if the parser supports it
+pub fn key_code_name(code: Code) -> Option<&'static str> {
+    KEY_NAMES.iter().find(|(_, c)| *c == code).map(|(name, _)| *name)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use proptest::prelude::*;
+
+    fn hotkey_strategy() -> impl Strategy<Value = HotKey> {
+        (0..KEY_NAMES.len(), any::<[bool; 4]>()).prop_map(|(key, [cmd, ctrl, opt, shift])| {
+            let mut mods = Modifiers::empty();
+            mods.set(Modifiers::SUPER, cmd);
+            mods.set(Modifiers::CONTROL, ctrl);
+            mods.set(Modifiers::ALT, opt);
+            mods.set(Modifiers::SHIFT, shift);
+            HotKey::new(Some(mods), KEY_NAMES[key].1)
+        })
+    }
+
+    proptest! {
+        #[test]
+        fn format_then_parse_round_trips(hotkey in hotkey_strategy()) {
+            let formatted = format_hotkey(&hotkey).unwrap();
+            prop_assert_eq!(parse_hotkey(&formatted).unwrap(), hotkey);
+            prop_assert_eq!(canonicalize_hotkey(&formatted).unwrap(), formatted);
+        }
+
+        #[test]
+        fn canonical_form_is_stable(input in "[a-z0-9 +,./;'`=\\-\\[\\]\\\\]{0,24}") {
+            if let Ok(canonical) = canonicalize_hotkey(&input) {
+                prop_assert_eq!(canonicalize_hotkey(&canonical).unwrap(), canonical);
+            }
+        }
+    }
+
+    #[test]
+    fn aliases_format_canonically() {
+        assert_eq!(canonicalize_hotkey(" Shift + Command + ESC ").unwrap(), "cmd+shift+escape");
+        assert_eq!(canonicalize_hotkey("opt+return").unwrap(), "opt+enter");
+    }
+
+    #[test]
+    fn unnamed_key_has_no_format() {
+        assert_eq!(format_hotkey(&HotKey::new(None, Code::MediaPlayPause)), None);
+    }
+
+    #[test]
+    fn malformed_hotkeys_are_rejected() {
+        for bad in ["", "cmd+", "cmd+shift", "a+b", "cmd++a", "hyper+a"] {
+            assert!(parse_hotkey(bad).is_err(), "{:?} parsed", bad);
+        }
+    }
 }
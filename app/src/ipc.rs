@@ -0,0 +1,548 @@
+//! Unix-socket API for editor plugins that render dictation inline instead of
+//! receiving keystrokes. Messages follow `proto/ipc.proto`, length-delimited; the
+//! schema is small enough that the wire format is encoded by hand here.
+
+use crate::config::IpcConfig;
+use crate::input::HotkeyEvent;
+use crate::state::{AppStateManager, RecordingState, TranscriptUpdate};
+use crossbeam_channel::Sender;
+use std::io::{self, Read, Write};
+use std::os::unix::fs::{DirBuilderExt, PermissionsExt};
+use std::os::unix::net::{UnixListener, UnixStream};
+use std::path::Path;
+use std::sync::{Arc, Mutex};
+use tracing::{debug, info, warn};
+
+const MAX_MESSAGE_BYTES: u64 = 64 * 1024;
+
+const WIRE_VARINT: u64 = 0;
+const WIRE_FIXED64: u64 = 1;
+const WIRE_LEN: u64 = 2;
+const WIRE_FIXED32: u64 = 5;
+
+#[derive(Debug, Clone, PartialEq)]
+enum Request {
+    Start { type_text: bool },
+    Stop,
+    Subscribe,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Event {
+    Ack,
+    Error(String),
+    State(RecordingState),
+    Partial(String),
+    Final { text: String, raw_text: String },
+}
+
+// ===== Wire format =====
+
+fn put_varint(buf: &mut Vec<u8>, mut value: u64) {
+    while value >= 0x80 {
+        buf.push(value as u8 | 0x80);
+        value >>= 7;
+    }
+    buf.push(value as u8);
+}
+
+fn put_len(buf: &mut Vec<u8>, field: u64, bytes: &[u8]) {
+    put_varint(buf, field << 3 | WIRE_LEN);
+    put_varint(buf, bytes.len() as u64);
+    buf.extend_from_slice(bytes);
+}
+
+/// A message with a single string field 1 (empty strings are omitted, as in proto3)
+fn string_message(text: &str) -> Vec<u8> {
+    let mut buf = Vec::new();
+    if !text.is_empty() {
+        put_len(&mut buf, 1, text.as_bytes());
+    }
+    buf
+}
+
+fn encode_event(event: &Event) -> Vec<u8> {
+    let (field, body) = match event {
+        Event::Ack => (1, Vec::new()),
+        Event::Error(message) => (2, string_message(message)),
+        Event::State(state) => {
+            let value = match state {
+                RecordingState::Idle => 0,
+                RecordingState::Recording => 1,
+                RecordingState::Processing => 2,
+            };
+            let mut body = Vec::new();
+            if value != 0 {
+                put_varint(&mut body, 1 << 3 | WIRE_VARINT);
+                put_varint(&mut body, value);
+            }
+            (3, body)
+        }
+        Event::Partial(text) => (4, string_message(text)),
+        Event::Final { text, raw_text } => {
+            let mut body = string_message(text);
+            if !raw_text.is_empty() && raw_text != text {
+                put_len(&mut body, 2, raw_text.as_bytes());
+            }
+            (5, body)
+        }
+    };
+    let mut buf = Vec::new();
+    put_len(&mut buf, field, &body);
+    buf
+}
+
+struct Reader<'a> {
+    bytes: &'a [u8],
+    pos: usize,
+}
+
+impl<'a> Reader<'a> {
+    fn varint(&mut self) -> Option<u64> {
+        let mut value = 0u64;
+        for shift in (0..64).step_by(7) {
+            let byte = *self.bytes.get(self.pos)?;
+            self.pos += 1;
+            value |= u64::from(byte & 0x7f) << shift;
+            if byte & 0x80 == 0 {
+                return Some(value);
+            }
+        }
+        None
+    }
+
+    fn take(&mut self, len: usize) -> Option<&'a [u8]> {
+        let end = self.pos.checked_add(len).filter(|&end| end <= self.bytes.len())?;
+        let bytes = &self.bytes[self.pos..end];
+        self.pos = end;
+        Some(bytes)
+    }
+
+    /// Next (field number, wire type), or `None` at the end
+    fn tag(&mut self) -> Option<(u64, u64)> {
+        if self.pos >= self.bytes.len() {
+            return None;
+        }
+        let tag = self.varint()?;
+        Some((tag >> 3, tag & 7))
+    }
+
+    fn skip(&mut self, wire_type: u64) -> Option<()> {
+        match wire_type {
+            WIRE_VARINT => self.varint().map(|_| ()),
+            WIRE_FIXED64 => self.take(8).map(|_| ()),
+            WIRE_LEN => {
+                let len = self.varint()? as usize;
+                self.take(len).map(|_| ())
+            }
+            WIRE_FIXED32 => self.take(4).map(|_| ()),
+            _ => None,
+        }
+    }
+}
+
+fn decode_start(bytes: &[u8]) -> Option<Request> {
+    let mut reader = Reader { bytes, pos: 0 };
+    let mut type_text = false;
+    while let Some((field, wire_type)) = reader.tag() {
+        if field == 1 && wire_type == WIRE_VARINT {
+            type_text = reader.varint()? != 0;
+        } else {
+            reader.skip(wire_type)?;
+        }
+    }
+    Some(Request::Start { type_text })
+}
+
+/// The last recognized command wins, as with a proto3 `oneof`
+fn decode_request(bytes: &[u8]) -> Option<Request> {
+    let mut reader = Reader { bytes, pos: 0 };
+    let mut request = None;
+    while let Some((field, wire_type)) = reader.tag() {
+        if wire_type != WIRE_LEN {
+            reader.skip(wire_type)?;
+            continue;
+        }
+        let len = reader.varint()? as usize;
+        let body = reader.take(len)?;
+        match field {
+            1 => request = Some(decode_start(body)?),
+            2 => request = Some(Request::Stop),
+            3 => request = Some(Request::Subscribe),
+            _ => {}
+        }
+    }
+    request
+}
+
+/// Read one length-delimited message; `None` when the client hung up
+fn read_message(stream: &mut UnixStream) -> io::Result<Option<Vec<u8>>> {
+    let mut len = 0u64;
+    for shift in (0..64).step_by(7) {
+        let mut byte = [0u8];
+        if stream.read(&mut byte)? == 0 {
+            return if shift == 0 { Ok(None) } else { Err(io::ErrorKind::UnexpectedEof.into()) };
+        }
+        len |= u64::from(byte[0] & 0x7f) << shift;
+        if byte[0] & 0x80 == 0 {
+            break;
+        }
+    }
+    if len > MAX_MESSAGE_BYTES {
+        return Err(io::Error::other("message too large"));
+    }
+    let mut buf = vec![0; len as usize];
+    stream.read_exact(&mut buf)?;
+    Ok(Some(buf))
+}
+
+fn write_event(stream: &Mutex<UnixStream>, event: &Event) -> io::Result<()> {
+    let body = encode_event(event);
+    let mut frame = Vec::with_capacity(body.len() + 4);
+    put_varint(&mut frame, body.len() as u64);
+    frame.extend_from_slice(&body);
+    let mut stream = stream.lock().map_err(|_| io::Error::other("socket lock poisoned"))?;
+    stream.write_all(&frame)
+}
+
+// ===== Server =====
+
+/// What the API needs from the running app
+#[derive(Clone)]
+pub struct IpcContext {
+    pub events: Sender<HotkeyEvent>,
+    pub state: AppStateManager,
+}
+
+/// Listen on the configured socket on a background thread if `[ipc] enabled`
+pub fn start(config: &IpcConfig, ctx: IpcContext) {
+    if !config.enabled {
+        return;
+    }
+    let Some(path) = config.socket_path() else {
+        warn!("IPC socket path unavailable (HOME not set)");
+        return;
+    };
+    // A socket left by a previous run would make bind fail
+    let _ = std::fs::remove_file(&path);
+    if let Some(parent) = path.parent() {
+        let _ = std::fs::create_dir_all(parent);
+    }
+    let listener = match bind_private(&path) {
+        Ok(listener) => listener,
+        Err(e) => {
+            warn!("IPC socket {} unavailable: {}", path.display(), e);
+            return;
+        }
+    };
+    info!("IPC listening on {}", path.display());
+    std::thread::spawn(move || {
+        for stream in listener.incoming().flatten() {
+            let ctx = ctx.clone();
+            std::thread::spawn(move || {
+                if let Err(e) = handle_connection(stream, &ctx) {
+                    debug!("IPC connection closed: {}", e);
+                }
+            });
+        }
+    });
+}
+
+/// Bind a socket at `path` that only this user may connect to (it drives the microphone).
+/// It's bound and restricted inside a private directory beside `path`, then moved there, so
+/// it's never reachable with the default permissions.
+fn bind_private(path: &Path) -> io::Result<UnixListener> {
+    let staging = path.with_file_name(format!(".typeswift-ipc-{}", std::process::id()));
+    let _ = std::fs::remove_dir_all(&staging);
+    std::fs::DirBuilder::new().mode(0o700).create(&staging)?;
+    let staged = staging.join("socket");
+    let bound = UnixListener::bind(&staged).and_then(|listener| {
+        std::fs::set_permissions(&staged, std::fs::Permissions::from_mode(0o600))?;
+        std::fs::rename(&staged, path)?;
+        Ok(listener)
+    });
+    let _ = std::fs::remove_file(&staged);
+    let _ = std::fs::remove_dir(&staging);
+    bound
+}
+
+fn handle_connection(mut stream: UnixStream, ctx: &IpcContext) -> io::Result<()> {
+    let writer = Arc::new(Mutex::new(stream.try_clone()?));
+    // One stream of updates per connection, however often the client asks
+    let mut subscribed = false;
+    while let Some(message) = read_message(&mut stream)? {
+        let Some(request) = decode_request(&message) else {
+            write_event(&writer, &Event::Error("Unrecognized request".to_string()))?;
+            continue;
+        };
+        debug!("IPC request: {:?}", request);
+        let reply = match request {
+            Request::Start { type_text } => {
+                if ctx.state.get_recording_state() != RecordingState::Idle {
+                    Event::Error("Already recording or transcribing".to_string())
+                } else if type_text {
+                    send(ctx, HotkeyEvent::StartDictation)
+                } else {
+                    // The controller sends the text here only if it takes this start
+                    send(ctx, HotkeyEvent::StartClientDictation)
+                }
+            }
+            Request::Stop => {
+                if ctx.state.get_recording_state() != RecordingState::Recording {
+                    Event::Error("Not recording".to_string())
+                } else {
+                    send(ctx, HotkeyEvent::StopDictation)
+                }
+            }
+            Request::Subscribe if subscribed => Event::Ack,
+            Request::Subscribe => {
+                subscribed = true;
+                let updates = ctx.state.subscribe();
+                let writer = Arc::clone(&writer);
+                std::thread::spawn(move || {
+                    for update in updates {
+                        let event = match update {
+                            TranscriptUpdate::State(state) => Event::State(state),
+                            TranscriptUpdate::Partial(text) => Event::Partial(text),
+                            TranscriptUpdate::Final { text, raw_text } => Event::Final { text, raw_text },
+                        };
+                        // Dropping the receiver unsubscribes once the client is gone
+                        if write_event(&writer, &event).is_err() {
+                            break;
+                        }
+                    }
+                });
+                Event::Ack
+            }
+        };
+        write_event(&writer, &reply)?;
+    }
+    Ok(())
+}
+
+fn send(ctx: &IpcContext, event: HotkeyEvent) -> Event {
+    match ctx.events.send(event) {
+        Ok(()) => Event::Ack,
+        Err(_) => Event::Error("Typeswift is shutting down".to_string()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// What a client sends: `Request` as in `proto/ipc.proto`
+    fn encode_request(request: &Request) -> Vec<u8> {
+        let (field, body) = match request {
+            Request::Start { type_text } => {
+                let mut body = Vec::new();
+                if *type_text {
+                    put_varint(&mut body, 1 << 3 | WIRE_VARINT);
+                    put_varint(&mut body, 1);
+                }
+                (1, body)
+            }
+            Request::Stop => (2, Vec::new()),
+            Request::Subscribe => (3, Vec::new()),
+        };
+        let mut buf = Vec::new();
+        put_len(&mut buf, field, &body);
+        buf
+    }
+
+    fn string_field(bytes: &[u8], wanted: u64) -> Option<String> {
+        let mut reader = Reader { bytes, pos: 0 };
+        let mut text = String::new();
+        while let Some((field, wire_type)) = reader.tag() {
+            if field == wanted && wire_type == WIRE_LEN {
+                let len = reader.varint()? as usize;
+                text = String::from_utf8(reader.take(len)?.to_vec()).ok()?;
+            } else {
+                reader.skip(wire_type)?;
+            }
+        }
+        Some(text)
+    }
+
+    /// What a client reads back
+    fn decode_event(bytes: &[u8]) -> Option<Event> {
+        let mut reader = Reader { bytes, pos: 0 };
+        let (field, wire_type) = reader.tag()?;
+        assert_eq!(wire_type, WIRE_LEN);
+        let len = reader.varint()? as usize;
+        let body = reader.take(len)?;
+        Some(match field {
+            1 => Event::Ack,
+            2 => Event::Error(string_field(body, 1)?),
+            3 => {
+                let mut body = Reader { bytes: body, pos: 0 };
+                let mut state = RecordingState::Idle;
+                while let Some((field, wire_type)) = body.tag() {
+                    if field == 1 && wire_type == WIRE_VARINT {
+                        state = match body.varint()? {
+                            1 => RecordingState::Recording,
+                            2 => RecordingState::Processing,
+                            _ => RecordingState::Idle,
+                        };
+                    } else {
+                        body.skip(wire_type)?;
+                    }
+                }
+                Event::State(state)
+            }
+            4 => Event::Partial(string_field(body, 1)?),
+            5 => {
+                let text = string_field(body, 1)?;
+                let raw_text = string_field(body, 2)?;
+                let raw_text = if raw_text.is_empty() { text.clone() } else { raw_text };
+                Event::Final { text, raw_text }
+            }
+            _ => return None,
+        })
+    }
+
+    fn framed(message: &[u8]) -> Vec<u8> {
+        let mut frame = Vec::new();
+        put_varint(&mut frame, message.len() as u64);
+        frame.extend_from_slice(message);
+        frame
+    }
+
+    #[test]
+    fn requests_round_trip() {
+        let requests = [
+            Request::Start { type_text: true },
+            Request::Start { type_text: false },
+            Request::Stop,
+            Request::Subscribe,
+        ];
+        for request in requests {
+            assert_eq!(decode_request(&encode_request(&request)), Some(request.clone()), "{:?}", request);
+        }
+    }
+
+    #[test]
+    fn events_round_trip() {
+        let events = [
+            Event::Ack,
+            Event::Error("Not recording".to_string()),
+            Event::State(RecordingState::Idle),
+            Event::State(RecordingState::Recording),
+            Event::State(RecordingState::Processing),
+            Event::Partial(String::new()),
+            Event::Partial("héllo wörld".to_string()),
+            Event::Final { text: "Hello.".to_string(), raw_text: "hello".to_string() },
+            Event::Final { text: "same".to_string(), raw_text: "same".to_string() },
+            Event::Final { text: "x".repeat(300), raw_text: "y".repeat(300) },
+        ];
+        for event in events {
+            assert_eq!(decode_event(&encode_event(&event)), Some(event.clone()), "{:?}", event);
+        }
+    }
+
+    #[test]
+    fn varints_round_trip() {
+        for value in [0, 1, 127, 128, 300, u32::MAX as u64, u64::MAX] {
+            let mut buf = Vec::new();
+            put_varint(&mut buf, value);
+            let mut reader = Reader { bytes: &buf, pos: 0 };
+            assert_eq!(reader.varint(), Some(value));
+            assert_eq!(reader.pos, buf.len());
+        }
+    }
+
+    #[test]
+    fn truncated_varints_are_rejected() {
+        assert_eq!(Reader { bytes: &[0x80], pos: 0 }.varint(), None);
+        assert_eq!(Reader { bytes: &[0xff; 3], pos: 0 }.varint(), None);
+        // Longer than any u64
+        assert_eq!(Reader { bytes: &[0xff; 11], pos: 0 }.varint(), None);
+        // A Start whose length varint stops halfway
+        assert_eq!(decode_request(&[1 << 3 | WIRE_LEN as u8, 0x80]), None);
+        // Or whose body is shorter than it says
+        assert_eq!(decode_request(&[1 << 3 | WIRE_LEN as u8, 5, 8]), None);
+
+        let (mut client, mut server) = UnixStream::pair().unwrap();
+        client.write_all(&[0x80]).unwrap();
+        drop(client);
+        assert_eq!(read_message(&mut server).unwrap_err().kind(), io::ErrorKind::UnexpectedEof);
+    }
+
+    #[test]
+    fn oversized_messages_are_refused() {
+        let (mut client, mut server) = UnixStream::pair().unwrap();
+        let mut prefix = Vec::new();
+        put_varint(&mut prefix, MAX_MESSAGE_BYTES + 1);
+        client.write_all(&prefix).unwrap();
+        let refused = read_message(&mut server).unwrap_err();
+        assert!(refused.to_string().contains("too large"), "{}", refused);
+
+        // Exactly at the limit is fine
+        let message = vec![0u8; MAX_MESSAGE_BYTES as usize];
+        let writer = std::thread::spawn(move || client.write_all(&framed(&message)));
+        assert_eq!(read_message(&mut server).unwrap().map(|m| m.len()), Some(MAX_MESSAGE_BYTES as usize));
+        writer.join().unwrap().unwrap();
+    }
+
+    #[test]
+    fn unknown_fields_are_skipped() {
+        let mut unknown = Vec::new();
+        put_varint(&mut unknown, 9 << 3 | WIRE_VARINT);
+        put_varint(&mut unknown, 300);
+        put_varint(&mut unknown, 10 << 3 | WIRE_FIXED64);
+        unknown.extend_from_slice(&[0; 8]);
+        put_varint(&mut unknown, 11 << 3 | WIRE_FIXED32);
+        unknown.extend_from_slice(&[0; 4]);
+        put_len(&mut unknown, 12, b"future");
+
+        let mut stop = unknown.clone();
+        stop.extend(encode_request(&Request::Stop));
+        assert_eq!(decode_request(&stop), Some(Request::Stop));
+
+        // Inside Start as well
+        let mut start_body = unknown.clone();
+        put_varint(&mut start_body, 1 << 3 | WIRE_VARINT);
+        put_varint(&mut start_body, 1);
+        let mut start = Vec::new();
+        put_len(&mut start, 1, &start_body);
+        assert_eq!(decode_request(&start), Some(Request::Start { type_text: true }));
+
+        // Nothing recognized, or a wire type that can't be skipped (groups)
+        assert_eq!(decode_request(&unknown), None);
+        assert_eq!(decode_request(&[2 << 3 | 3]), None);
+    }
+
+    #[test]
+    fn subscribing_twice_delivers_each_update_once() {
+        let (events, _received) = crossbeam_channel::unbounded();
+        let ctx = IpcContext { events, state: AppStateManager::new() };
+        let state = ctx.state.clone();
+        let (mut client, server) = UnixStream::pair().unwrap();
+        std::thread::spawn(move || handle_connection(server, &ctx));
+
+        for _ in 0..2 {
+            client.write_all(&framed(&encode_request(&Request::Subscribe))).unwrap();
+            let reply = read_message(&mut client).unwrap().unwrap();
+            assert_eq!(decode_event(&reply), Some(Event::Ack));
+        }
+        state.publish(TranscriptUpdate::Partial("first".to_string()));
+        state.publish(TranscriptUpdate::Partial("second".to_string()));
+        for expected in ["first", "second"] {
+            let event = read_message(&mut client).unwrap().unwrap();
+            assert_eq!(decode_event(&event), Some(Event::Partial(expected.to_string())));
+        }
+    }
+
+    #[test]
+    fn socket_is_owner_only_once_reachable() {
+        let dir = std::env::temp_dir().join(format!("typeswift-ipc-test-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("typeswift.sock");
+        let _listener = bind_private(&path).unwrap();
+        assert_eq!(std::fs::metadata(&path).unwrap().permissions().mode() & 0o777, 0o600);
+        assert!(UnixStream::connect(&path).is_ok());
+        // Nothing but the socket is left beside it
+        assert_eq!(std::fs::read_dir(&dir).unwrap().count(), 1);
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
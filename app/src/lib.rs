@@ -10,13 +10,17 @@ pub mod controller;
 pub mod state;
 pub mod window;
 pub mod output;
+pub mod sink;
 pub mod mem;
-
-// Backward-compat shim: some modules may still refer to `crate::audio`.
-// Keep a thin module to avoid wide churn until all call sites are migrated.
-#[allow(dead_code)]
-pub mod audio {
-    pub use crate::services::audio::{ImprovedAudioProcessor, Transcriber, AudioCapture};
-}
-
+pub mod runtime;
 pub mod input;
+pub mod history;
+pub mod corrections;
+pub mod support;
+pub mod mcp;
+pub mod metrics;
+pub mod ipc;
+pub mod meetings;
+pub mod palette;
+pub mod ui_state;
+pub mod cli;
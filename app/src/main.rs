@@ -4,7 +4,7 @@ compile_error!("This crate supports only macOS (target_os = \"macos\").");
 
 // Use the library crate modules
 
-use typeswift::config::Config;
+use typeswift::config::{Config, OutputTarget};
 use gpui::{
     div, point, prelude::*, px, rgb, size, App, Application, Bounds, Context, Window, WindowBounds,
     WindowOptions, Timer,
@@ -14,12 +14,14 @@ use typeswift::controller::AppController;
 use typeswift::state::AppStateManager;
 // use std::sync::{Arc, Mutex};
 use typeswift::window::WindowManager;
+use typeswift::ui_state::PreferencesTab;
 use crossbeam_channel::bounded;
 use typeswift::platform::macos::ffi as menubar_ffi;
 use tracing::{info, warn, error};
 
 struct TypeswiftView {
-    _state: AppStateManager,
+    state: AppStateManager,
+    style: typeswift::window::PopupStyle,
 }
 
 struct PreferencesView {
@@ -29,9 +31,69 @@ struct PreferencesView {
     hotkeys: std::sync::Arc<std::sync::Mutex<typeswift::input::HotkeyHandler>>,
     capture_focus: gpui::FocusHandle,
     capturing_ptt: bool,
+    // Modifiers seen while capturing; committed as a chord if released without a key
+    capture_mods: gpui::Modifiers,
+    ptt_test: HotkeyTestState,
+    mic_test: typeswift::services::mic_test::MicTest,
+    audio_processor: std::sync::Arc<std::sync::Mutex<typeswift::services::audio::AudioProcessor>>,
+    /// Showing the Advanced tab rather than General
+    advanced: bool,
     rev: u64,
 }
 
+/// Values offered by the Preferences input gain slider
+const INPUT_GAIN_STEPS: &[f32] = &[0.5, 0.75, 1.0, 1.25, 1.5, 2.0, 2.5, 3.0, 4.0, 5.0, 6.0, 8.0];
+/// Hardware input volume slider resolution
+const INPUT_VOLUME_STEPS: usize = 10;
+/// Values offered by the Advanced tab's streaming sliders, in ms
+const PROCESS_INTERVAL_STEPS: &[u64] = &[300, 400, 500, 700, 1000, 1500, 2000, 3000];
+const MIN_INITIAL_AUDIO_STEPS: &[u64] = &[250, 500, 750, 1000, 1500, 2000, 3000];
+const CHUNK_DURATION_STEPS: &[u64] = &[10_000, 15_000, 20_000, 30_000, 45_000, 60_000, 90_000];
+
+/// Hand a Preferences choice to the controller the way the menu bar's Quick Settings do
+fn send_quick_setting(
+    hotkeys: &std::sync::Arc<std::sync::Mutex<typeswift::input::HotkeyHandler>>,
+    setting: typeswift::input::QuickSetting,
+) {
+    let sender = hotkeys.lock().unwrap().event_sender();
+    if let Some(sender) = sender {
+        let _ = sender.send(typeswift::input::HotkeyEvent::QuickSetting(setting));
+    }
+}
+
+/// Live push-to-talk events shown in the Preferences test row
+#[derive(Default)]
+struct HotkeyTestState {
+    pressed_at: Option<std::time::Instant>,
+    last_hold: Option<std::time::Duration>,
+    events: u32,
+}
+
+impl HotkeyTestState {
+    fn record(&mut self, event: HotkeyEvent) {
+        match event {
+            HotkeyEvent::PushToTalkPressed(_) => {
+                self.pressed_at = Some(std::time::Instant::now());
+            }
+            HotkeyEvent::PushToTalkReleased => {
+                self.last_hold = self.pressed_at.take().map(|t| t.elapsed());
+            }
+            _ => return,
+        }
+        self.events = self.events.wrapping_add(1);
+    }
+
+    fn label(&self) -> String {
+        if self.pressed_at.is_some() {
+            "Pressed (release to finish)".to_string()
+        } else if let Some(hold) = self.last_hold {
+            format!("Released after {:.2}s ({} events)", hold.as_secs_f32(), self.events)
+        } else {
+            "Hold your shortcut".to_string()
+        }
+    }
+}
+
 impl Drop for PreferencesView {
     fn drop(&mut self) {
         self.open_flag.store(false, std::sync::atomic::Ordering::SeqCst);
@@ -41,17 +103,795 @@ impl Drop for PreferencesView {
     }
 }
 
-impl Render for TypeswiftView {
+/// Hand focus back to the app behind our window and type `text` there
+fn type_into_previous_app(typing_queue: &typeswift::output::TypingQueue, text: String, add_space: bool) {
+    let _ = WindowManager::deactivate_app();
+    let typing_queue = typing_queue.clone();
+    std::thread::spawn(move || {
+        // Let the frontmost app regain focus before typing
+        let _ = menubar_ffi::wait_modifiers_released(300);
+        std::thread::sleep(std::time::Duration::from_millis(150));
+        if let Err(e) = typing_queue.queue_typing(text, add_space) {
+            error!("Failed to type into previous app: {}", e);
+        }
+    });
+}
+
+/// Floating window collecting dictations that had nowhere to be typed
+struct ScratchpadView {
+    state: AppStateManager,
+    config: std::sync::Arc<parking_lot::RwLock<typeswift::config::Config>>,
+    typing_queue: typeswift::output::TypingQueue,
+    handle_holder: std::sync::Arc<std::sync::Mutex<Option<gpui::WindowHandle<ScratchpadView>>>>,
+}
+
+impl Drop for ScratchpadView {
+    fn drop(&mut self) {
+        if let Ok(mut holder) = self.handle_holder.lock() {
+            *holder = None;
+        }
+    }
+}
+
+impl Render for ScratchpadView {
     fn render(&mut self, _window: &mut Window, _cx: &mut Context<Self>) -> impl IntoElement {
+        let entries = self.state.scratchpad_entries();
+        let add_space = self.config.read().output.add_space_between_utterances;
+        let joined = entries.join(if add_space { " " } else { "" });
+
+        let button = |label: &'static str| {
+            div()
+                .px(px(6.0))
+                .py(px(3.0))
+                .rounded_sm()
+                .border_1()
+                .border_color(rgb(0x374151))
+                .hover(|s| s.bg(rgb(0x1f2937)))
+                .child(label)
+        };
+
+        let copy_button = {
+            let text = joined.clone();
+            button("Copy all").on_mouse_down(gpui::MouseButton::Left, move |_, _window, _app_cx| {
+                if !text.is_empty() {
+                    menubar_ffi::set_clipboard_text(&text);
+                }
+            })
+        };
+
+        let send_button = {
+            let state = self.state.clone();
+            let typing_queue = self.typing_queue.clone();
+            button("Send to app").on_mouse_down(gpui::MouseButton::Left, move |_, _window, _app_cx| {
+                let entries = state.take_scratchpad();
+                if entries.is_empty() {
+                    return;
+                }
+                let text = entries.join(if add_space { " " } else { "" });
+                type_into_previous_app(&typing_queue, text, add_space);
+            })
+        };
+
+        let clear_button = {
+            let state = self.state.clone();
+            button("Clear").on_mouse_down(gpui::MouseButton::Left, move |_, _window, _app_cx| {
+                let _ = state.take_scratchpad();
+            })
+        };
+
+        let close_button = button("Close").on_mouse_down(gpui::MouseButton::Left, |_, window, _app_cx| {
+            window.remove_window();
+        });
+
+        div()
+            .id("typeswift-scratchpad")
+            .flex()
+            .flex_col()
+            .bg(rgb(0x111827))
+            .w_full()
+            .h_full()
+            .p(px(8.0))
+            .gap(px(6.0))
+            .rounded_md()
+            .border_1()
+            .border_color(rgb(0x374151))
+            .text_xs()
+            .text_color(rgb(0xffffff))
+            .child(div().text_color(rgb(0x9ca3af)).child("Scratchpad"))
+            .child(
+                div()
+                    .id("typeswift-scratchpad-entries")
+                    .flex_1()
+                    .flex()
+                    .flex_col()
+                    .gap(px(4.0))
+                    .overflow_y_scroll()
+                    .children(entries.into_iter().map(|entry| div().child(entry)))
+            )
+            .child(
+                div()
+                    .flex()
+                    .gap(px(6.0))
+                    .child(copy_button)
+                    .child(send_button)
+                    .child(clear_button)
+                    .child(close_button)
+            )
+    }
+}
+
+/// Searchable list of past dictations
+struct HistoryView {
+    history: typeswift::history::HistoryStore,
+    config: std::sync::Arc<parking_lot::RwLock<typeswift::config::Config>>,
+    typing_queue: typeswift::output::TypingQueue,
+    handle_holder: std::sync::Arc<std::sync::Mutex<Option<gpui::WindowHandle<HistoryView>>>>,
+    focus: gpui::FocusHandle,
+    scroll: gpui::ScrollHandle,
+    query: typeswift::history::HistoryQuery,
+    selected: usize,
+    /// Combined view of the selected entry's session, when open
+    session: Option<typeswift::history::HistorySession>,
+    /// The selected entry's text while it's being edited
+    editing: Option<String>,
+    corrections: typeswift::corrections::CorrectionMemory,
+    /// Last rendered window size, remembered in ui-state.json on close
+    window_size: Option<(f32, f32)>,
+}
+
+/// Rows rendered at once; search narrows beyond this
+const HISTORY_ROW_LIMIT: usize = 500;
+
+impl HistoryView {
+    fn results(&self) -> Vec<typeswift::history::HistoryEntry> {
+        self.history.search(&self.query, HISTORY_ROW_LIMIT)
+    }
+
+    fn selected_entry(&self) -> Option<typeswift::history::HistoryEntry> {
+        self.results().into_iter().nth(self.selected)
+    }
+
+    fn copy_selected(&self) {
+        if let Some(entry) = self.selected_entry() {
+            menubar_ffi::set_clipboard_text(&entry.text);
+        }
+    }
+
+    fn type_selected(&self) {
+        if let Some(entry) = self.selected_entry() {
+            let add_space = self.config.read().output.add_space_between_utterances;
+            type_into_previous_app(&self.typing_queue, entry.text, add_space);
+        }
+    }
+
+    /// Play the selected dictation's archived audio, to check a transcription against it
+    fn play_selected(&self) {
+        let Some(path) = self.selected_entry().and_then(|e| self.history.audio_path(e.id)) else { return };
+        if !menubar_ffi::play_audio_file(&path) {
+            warn!("Could not play {}", path.display());
+        }
+    }
+
+    /// Run the saved raw transcript through today's commands/vocabulary/punctuation settings
+    fn reprocess_selected(&self) {
+        let Some(entry) = self.selected_entry() else { return };
+        let raw = if entry.raw_text.is_empty() { &entry.text } else { &entry.raw_text };
+        let text = typeswift::controller::post_process(raw, &self.config.read(), None, entry.app.as_deref());
+        if text == entry.text {
+            return;
+        }
+        if let Err(e) = self.history.update_text(entry.id, text) {
+            warn!("Failed to update history entry: {}", e);
+        }
+    }
+
+    fn edit_selected(&mut self) {
+        self.editing = self.selected_entry().map(|entry| entry.text);
+    }
+
+    /// Save the edited text, and remember the words changed so a correction made often enough
+    /// can become a replacement
+    fn save_edit(&mut self) {
+        let Some(draft) = self.editing.take() else { return };
+        let Some(entry) = self.selected_entry() else { return };
+        let text = draft.trim().to_string();
+        if text.is_empty() || text == entry.text.trim() {
+            return;
+        }
+        if let Err(e) = self.history.update_text(entry.id, text.clone()) {
+            warn!("Failed to update history entry: {}", e);
+            return;
+        }
+        let suggest_after = self.config.read().history.suggest_replacement_after;
+        let due = self.corrections.record(&entry.text, &text, suggest_after);
+        if !due.is_empty() {
+            let config = self.config.clone();
+            let corrections = self.corrections.clone();
+            // The offer is a modal dialog; keep it off the UI thread
+            std::thread::spawn(move || {
+                for correction in due {
+                    typeswift::corrections::offer_replacement(&config, &corrections, &correction);
+                }
+            });
+        }
+    }
+
+    fn handle_edit_key(&mut self, event: &gpui::KeyDownEvent, cx: &mut Context<Self>) {
+        let ks = &event.keystroke;
+        let Some(ref mut draft) = self.editing else { return };
+        match ks.key.as_str() {
+            "enter" => self.save_edit(),
+            "escape" => self.editing = None,
+            "backspace" if ks.modifiers.platform => draft.clear(),
+            "backspace" if ks.modifiers.alt => {
+                let kept = draft.trim_end().rfind(char::is_whitespace).map_or(0, |i| i + 1);
+                draft.truncate(kept);
+            }
+            "backspace" => {
+                draft.pop();
+            }
+            _ => {
+                if ks.modifiers.platform || ks.modifiers.control {
+                    return;
+                }
+                match ks.key_char.as_deref() {
+                    Some(ch) if !ch.chars().any(char::is_control) => draft.push_str(ch),
+                    _ => return,
+                }
+            }
+        }
+        cx.notify();
+    }
+
+    fn open_session(&mut self) {
+        if let Some(entry) = self.selected_entry() {
+            let gap = self.config.read().history.session_gap_secs;
+            self.session = self.history.session_for(entry.id, gap);
+        }
+    }
+
+    fn export_session(&self) {
+        let Some(ref session) = self.session else { return };
+        let Some(dir) = typeswift::history::default_export_dir() else { return };
+        let include_raw = self.config.read().history.export_raw_text;
+        match session.export(&dir, include_raw) {
+            Ok(path) => {
+                info!("Exported session to {}", path.display());
+                menubar_ffi::reveal_in_finder(&path);
+            }
+            Err(e) => warn!("{}", e),
+        }
+    }
+
+    fn render_session(&self, session: &typeswift::history::HistorySession, cx: &mut Context<Self>) -> gpui::AnyElement {
+        let button = |id: &'static str, label: &'static str| {
+            div()
+                .id(id)
+                .px(px(6.0))
+                .py(px(3.0))
+                .rounded_sm()
+                .border_1()
+                .border_color(rgb(0x374151))
+                .hover(|s| s.bg(rgb(0x1f2937)))
+                .child(label)
+        };
+        let meta = format!(
+            "Session · {} · {} dictation{}{}",
+            typeswift::history::format_timestamp(session.start()),
+            session.entries.len(),
+            if session.entries.len() == 1 { "" } else { "s" },
+            session.app_label().map(|a| format!(" · {}", a)).unwrap_or_default(),
+        );
+        let text = session.text();
+
+        div()
+            .id("typeswift-history")
+            .track_focus(&self.focus)
+            .on_key_down(cx.listener(|this, event: &gpui::KeyDownEvent, _window, cx| {
+                if event.keystroke.key == "escape" {
+                    this.session = None;
+                    cx.notify();
+                }
+            }))
+            .flex()
+            .flex_col()
+            .bg(rgb(0x111827))
+            .w_full()
+            .h_full()
+            .p(px(8.0))
+            .pt(px(30.0))
+            .gap(px(6.0))
+            .text_xs()
+            .text_color(rgb(0xffffff))
+            .child(div().text_color(rgb(0x9ca3af)).child(meta))
+            .child(
+                div()
+                    .id("history-session-text")
+                    .flex_1()
+                    .overflow_y_scroll()
+                    .child(text.clone())
+            )
+            .child(
+                div()
+                    .flex()
+                    .gap(px(6.0))
+                    .child(button("history-session-back", "Back").on_click(cx.listener(|this, _, _window, cx| {
+                        this.session = None;
+                        cx.notify();
+                    })))
+                    .child(div().flex_1())
+                    .child(button("history-session-copy", "Copy all").on_click(move |_, _window, _cx| {
+                        menubar_ffi::set_clipboard_text(&text);
+                    }))
+                    .child(button("history-session-export", "Export").on_click(cx.listener(|this, _, _window, _cx| {
+                        this.export_session();
+                    })))
+            )
+            .into_any_element()
+    }
+
+    fn cycle_app_filter(&mut self) {
+        let apps = self.history.apps();
+        self.query.app = match self.query.app.as_ref().and_then(|a| apps.iter().position(|x| x == a)) {
+            None if !apps.is_empty() => Some(apps[0].clone()),
+            Some(i) if i + 1 < apps.len() => Some(apps[i + 1].clone()),
+            _ => None,
+        };
+        self.selected = 0;
+    }
+
+    fn handle_key(&mut self, event: &gpui::KeyDownEvent, cx: &mut Context<Self>) {
+        if self.editing.is_some() {
+            return self.handle_edit_key(event, cx);
+        }
+        let ks = &event.keystroke;
+        let count = self.results().len();
+        match ks.key.as_str() {
+            "up" => self.selected = self.selected.saturating_sub(1),
+            "e" if ks.modifiers.platform => self.edit_selected(),
+            "down" => self.selected = (self.selected + 1).min(count.saturating_sub(1)),
+            "enter" if ks.modifiers.platform => self.type_selected(),
+            "enter" => self.copy_selected(),
+            "p" if ks.modifiers.platform => self.play_selected(),
+            "escape" => {
+                self.query.text.clear();
+                self.selected = 0;
+            }
+            "backspace" => {
+                if ks.modifiers.platform {
+                    self.query.text.clear();
+                } else {
+                    self.query.text.pop();
+                }
+                self.selected = 0;
+            }
+            _ => {
+                if ks.modifiers.platform || ks.modifiers.control {
+                    return;
+                }
+                match ks.key_char.as_deref() {
+                    Some(ch) if !ch.chars().any(char::is_control) => {
+                        self.query.text.push_str(ch);
+                        self.selected = 0;
+                    }
+                    _ => return,
+                }
+            }
+        }
+        self.scroll.scroll_to_item(self.selected);
+        cx.notify();
+    }
+}
+
+impl Drop for HistoryView {
+    fn drop(&mut self) {
+        if let Ok(mut holder) = self.handle_holder.lock() {
+            *holder = None;
+        }
+        let (size, range) = (self.window_size, self.query.range);
+        typeswift::ui_state::update(|state| {
+            state.history_size = size.or(state.history_size);
+            state.history_range = range;
+        });
+    }
+}
+
+impl Render for HistoryView {
+    fn render(&mut self, window: &mut Window, cx: &mut Context<Self>) -> gpui::AnyElement {
+        if !self.focus.is_focused(window) {
+            self.focus.focus(window);
+        }
+        let viewport = window.viewport_size();
+        self.window_size = Some((viewport.width.into(), viewport.height.into()));
+        if let Some(session) = self.session.clone() {
+            return self.render_session(&session, cx);
+        }
+        let results = self.results();
+        self.selected = self.selected.min(results.len().saturating_sub(1));
+
+        let filter_button = |id: &'static str, label: String| {
+            div()
+                .id(id)
+                .px(px(6.0))
+                .py(px(3.0))
+                .rounded_sm()
+                .border_1()
+                .border_color(rgb(0x374151))
+                .hover(|s| s.bg(rgb(0x1f2937)))
+                .child(label)
+        };
+
+        let search_box = div()
+            .flex_1()
+            .px(px(6.0))
+            .py(px(3.0))
+            .rounded_sm()
+            .border_1()
+            .border_color(rgb(0x4b5563))
+            .text_color(if self.query.text.is_empty() { rgb(0x6b7280) } else { rgb(0xffffff) })
+            .child(if self.query.text.is_empty() { "Type to search".to_string() } else { self.query.text.clone() });
+
+        let range_button = filter_button("history-range", self.query.range.label().to_string())
+            .on_click(cx.listener(|this, _, _window, cx| {
+                this.query.range = this.query.range.next();
+                this.selected = 0;
+                cx.notify();
+            }));
+
+        let app_label = self
+            .query
+            .app
+            .as_deref()
+            .map(|id| id.rsplit('.').next().unwrap_or(id).to_string())
+            .unwrap_or_else(|| "All apps".to_string());
+        let app_button = filter_button("history-app", app_label).on_click(cx.listener(|this, _, _window, cx| {
+            this.cycle_app_filter();
+            cx.notify();
+        }));
+
+        let has_selection = !results.is_empty();
+        let action_button = |id: &'static str, label: &'static str| {
+            filter_button(id, label.to_string()).when(!has_selection, |d| d.text_color(rgb(0x6b7280)))
+        };
+        let type_button = action_button("history-type", "Type again").on_click(cx.listener(|this, _, _window, _cx| {
+            this.type_selected();
+        }));
+        let copy_button = action_button("history-copy", "Copy").on_click(cx.listener(|this, _, _window, _cx| {
+            this.copy_selected();
+        }));
+        let reprocess_button = action_button("history-reprocess", "Re-process").on_click(cx.listener(|this, _, _window, cx| {
+            this.reprocess_selected();
+            cx.notify();
+        }));
+        let has_audio = results.get(self.selected).is_some_and(|e| self.history.audio_path(e.id).is_some());
+        let play_button = filter_button("history-play", "Play".to_string())
+            .when(!has_audio, |d| d.text_color(rgb(0x6b7280)))
+            .on_click(cx.listener(|this, _, _window, _cx| {
+                this.play_selected();
+            }));
+        let session_button = action_button("history-session", "Session").on_click(cx.listener(|this, _, _window, cx| {
+            this.open_session();
+            cx.notify();
+        }));
+        let editing = self.editing.clone();
+        let edit_button = action_button("history-edit", if editing.is_some() { "Save" } else { "Edit" })
+            .on_click(cx.listener(|this, _, _window, cx| {
+                if this.editing.is_some() {
+                    this.save_edit();
+                } else {
+                    this.edit_selected();
+                }
+                cx.notify();
+            }));
+
+        let rows = results.into_iter().enumerate().map(|(i, entry)| {
+            let selected = i == self.selected;
+            let meta = match entry.app_label() {
+                Some(app) => format!("{}  ·  {}", typeswift::history::format_timestamp(entry.timestamp), app),
+                None => typeswift::history::format_timestamp(entry.timestamp),
+            };
+            div()
+                .id(("history-row", i))
+                .w_full()
+                .px(px(6.0))
+                .py(px(4.0))
+                .rounded_sm()
+                .when(selected, |d| d.bg(rgb(0x1e3a5f)))
+                .hover(|s| s.bg(rgb(0x1f2937)))
+                .child(div().text_color(rgb(0x9ca3af)).child(meta))
+                .child(match editing.as_ref().filter(|_| selected) {
+                    Some(draft) => div()
+                        .px(px(4.0))
+                        .rounded_sm()
+                        .border_1()
+                        .border_color(rgb(0x4b5563))
+                        .child(format!("{}▏", draft)),
+                    None => div().child(entry.text.clone()),
+                })
+                // What post-processing started from, to check what it changed
+                .when_some(entry.changed_raw_text().filter(|_| selected).map(str::to_string), |d, raw| {
+                    d.child(div().text_color(rgb(0x6b7280)).child(format!("Transcribed: {}", raw)))
+                })
+                .on_click(cx.listener(move |this, event: &gpui::ClickEvent, _window, cx| {
+                    if this.selected != i {
+                        this.editing = None;
+                    }
+                    this.selected = i;
+                    // Double-click copies, like Enter
+                    if event.click_count() >= 2 {
+                        menubar_ffi::set_clipboard_text(&entry.text);
+                    }
+                    cx.notify();
+                }))
+        });
+
+        div()
+            .id("typeswift-history")
+            .track_focus(&self.focus)
+            .on_key_down(cx.listener(|this, event: &gpui::KeyDownEvent, _window, cx| this.handle_key(event, cx)))
+            .flex()
+            .flex_col()
+            .bg(rgb(0x111827))
+            .w_full()
+            .h_full()
+            .p(px(8.0))
+            .pt(px(30.0))
+            .gap(px(6.0))
+            .text_xs()
+            .text_color(rgb(0xffffff))
+            .child(
+                div()
+                    .flex()
+                    .gap(px(6.0))
+                    .child(search_box)
+                    .child(range_button)
+                    .child(app_button)
+            )
+            .child(
+                div()
+                    .id("history-rows")
+                    .flex_1()
+                    .flex()
+                    .flex_col()
+                    .overflow_y_scroll()
+                    .track_scroll(&self.scroll)
+                    .children(rows)
+            )
+            .child(
+                div()
+                    .flex()
+                    .items_center()
+                    .gap(px(6.0))
+                    .child(
+                        div()
+                            .flex_1()
+                            .text_color(rgb(0x6b7280))
+                            .child(if self.editing.is_some() {
+                                "Enter saves  ·  Esc cancels".to_string()
+                            } else {
+                                format!("{} entries  ·  Enter copies  ·  ⌘Enter types  ·  ⌘E edits", self.history.len())
+                            })
+                    )
+                    .child(play_button)
+                    .child(type_button)
+                    .child(copy_button)
+                    .child(edit_button)
+                    .child(reprocess_button)
+                    .child(session_button)
+            )
+            .into_any_element()
+    }
+}
+
+/// Searchable list of every action (menu bar: Command Palette…)
+struct CommandPaletteView {
+    config: std::sync::Arc<parking_lot::RwLock<typeswift::config::Config>>,
+    state: AppStateManager,
+    /// Where chosen actions go: the hotkey channel, so they reach the controller and UI alike
+    events: std::sync::mpsc::Sender<HotkeyEvent>,
+    handle_holder: std::sync::Arc<std::sync::Mutex<Option<gpui::WindowHandle<CommandPaletteView>>>>,
+    focus: gpui::FocusHandle,
+    scroll: gpui::ScrollHandle,
+    query: String,
+    selected: usize,
+}
+
+impl CommandPaletteView {
+    fn results(&self) -> Vec<typeswift::palette::PaletteAction> {
+        let actions = typeswift::palette::actions(&self.config.read(), &self.state);
+        typeswift::palette::search(actions, &self.query)
+    }
+
+    /// Close the palette and run `action`; focus goes back to the app behind unless the
+    /// action opens one of our windows
+    fn run(&self, action: &typeswift::palette::PaletteAction, window: &mut Window) {
+        info!("Command palette: {}", action.title);
+        window.remove_window();
+        if !action.opens_window() {
+            let _ = WindowManager::deactivate_app();
+        }
+        let _ = self.events.send(action.event);
+    }
+
+    fn handle_key(&mut self, event: &gpui::KeyDownEvent, window: &mut Window, cx: &mut Context<Self>) {
+        let ks = &event.keystroke;
+        let count = self.results().len();
+        match ks.key.as_str() {
+            "up" => self.selected = self.selected.saturating_sub(1),
+            "down" => self.selected = (self.selected + 1).min(count.saturating_sub(1)),
+            "enter" => {
+                if let Some(action) = self.results().into_iter().nth(self.selected) {
+                    self.run(&action, window);
+                }
+                return;
+            }
+            "escape" if self.query.is_empty() => {
+                window.remove_window();
+                let _ = WindowManager::deactivate_app();
+                return;
+            }
+            "escape" => {
+                self.query.clear();
+                self.selected = 0;
+            }
+            "backspace" => {
+                if ks.modifiers.platform {
+                    self.query.clear();
+                } else {
+                    self.query.pop();
+                }
+                self.selected = 0;
+            }
+            _ => {
+                if ks.modifiers.platform || ks.modifiers.control {
+                    return;
+                }
+                match ks.key_char.as_deref() {
+                    Some(ch) if !ch.chars().any(char::is_control) => {
+                        self.query.push_str(ch);
+                        self.selected = 0;
+                    }
+                    _ => return,
+                }
+            }
+        }
+        self.scroll.scroll_to_item(self.selected);
+        cx.notify();
+    }
+}
+
+impl Drop for CommandPaletteView {
+    fn drop(&mut self) {
+        if let Ok(mut holder) = self.handle_holder.lock() {
+            *holder = None;
+        }
+    }
+}
+
+impl Render for CommandPaletteView {
+    fn render(&mut self, window: &mut Window, cx: &mut Context<Self>) -> impl IntoElement {
+        if !self.focus.is_focused(window) {
+            self.focus.focus(window);
+        }
+        let results = self.results();
+        self.selected = self.selected.min(results.len().saturating_sub(1));
+
+        let search_box = div()
+            .px(px(6.0))
+            .py(px(4.0))
+            .rounded_sm()
+            .border_1()
+            .border_color(rgb(0x4b5563))
+            .text_color(if self.query.is_empty() { rgb(0x6b7280) } else { rgb(0xffffff) })
+            .child(if self.query.is_empty() { "Type a command".to_string() } else { self.query.clone() });
+
+        let empty = results.is_empty();
+        let rows = results.into_iter().enumerate().map(|(i, action)| {
+            let selected = i == self.selected;
+            div()
+                .id(("palette-row", i))
+                .flex()
+                .w_full()
+                .px(px(6.0))
+                .py(px(4.0))
+                .rounded_sm()
+                .when(selected, |d| d.bg(rgb(0x1e3a5f)))
+                .hover(|s| s.bg(rgb(0x1f2937)))
+                .child(div().flex_1().child(action.title.clone()))
+                .child(div().text_color(rgb(0x9ca3af)).child(action.detail.clone()))
+                .on_click(cx.listener(move |this, _, window, _cx| this.run(&action, window)))
+        });
+
+        div()
+            .id("typeswift-command-palette")
+            .track_focus(&self.focus)
+            .on_key_down(cx.listener(|this, event: &gpui::KeyDownEvent, window, cx| this.handle_key(event, window, cx)))
+            .flex()
+            .flex_col()
+            .bg(rgb(0x111827))
+            .w_full()
+            .h_full()
+            .p(px(8.0))
+            .pt(px(30.0))
+            .gap(px(6.0))
+            .text_xs()
+            .text_color(rgb(0xffffff))
+            .child(search_box)
+            .child(
+                div()
+                    .id("palette-rows")
+                    .flex_1()
+                    .flex()
+                    .flex_col()
+                    .overflow_y_scroll()
+                    .track_scroll(&self.scroll)
+                    .children(rows)
+                    .when(empty, |d| d.child(div().text_color(rgb(0x6b7280)).child("No matching command")))
+            )
+            .child(div().text_color(rgb(0x6b7280)).child("↑↓ select  ·  Enter runs  ·  Esc closes"))
+    }
+}
+
+impl Render for TypeswiftView {
+    fn render(&mut self, window: &mut Window, _cx: &mut Context<Self>) -> impl IntoElement {
         {
             // Status view
             // Always present a neutral, "Ready" state without
             // reflecting internal recording/processing states.
-            let status_text = "Ready".to_string();
-            let bg_color = rgb(0x1f2937);
+            let status_text = match self.state.model_status() {
+                typeswift::state::ModelStatus::Initializing => "Loading model…".to_string(),
+                _ if self.state.is_recording_locked() => "Locked · press to stop".to_string(),
+                _ if self.style.rehearsal => "Rehearsal".to_string(),
+                _ => "Ready".to_string(),
+            };
+            let high_contrast = self.style.high_contrast;
+            let (bg_color, border_color, accent) = if high_contrast {
+                (rgb(0x000000), rgb(0xffffff), rgb(0xfacc15))
+            } else {
+                (rgb(0x1f2937), rgb(0x374151), rgb(0x3b82f6))
+            };
+            // Thin bar while a long recording is transcribed chunk by chunk.
+            // It jumps between chunks; anything animated here must check `self.style.reduce_motion`.
+            let progress = self.state.progress().map(|(done, total)| done as f32 / total.max(1) as f32);
+            let locked = self.state.is_recording_locked();
+            // While streaming: settled words in full white, the ones still changing dimmed.
+            // A rehearsal's result stays up afterwards, since it isn't typed anywhere
+            let live = Some(self.state.live_transcript()).filter(|live| {
+                !live.is_empty()
+                    && match self.state.get_recording_state() {
+                        typeswift::state::RecordingState::Recording => true,
+                        typeswift::state::RecordingState::Idle => self.style.rehearsal,
+                        typeswift::state::RecordingState::Processing => false,
+                    }
+            });
+            let draft_color = if high_contrast { rgb(0xd1d5db) } else { rgb(0x9ca3af) };
+            let max_lines = self.style.max_lines;
+            let live = live.map(|live| {
+                // Wraps, keeping the newest words when it runs past max_lines
+                let (text, draft_at) = live.text();
+                let width = f32::from(window.viewport_size().width);
+                let (start, _) = typeswift::window::fit_popup_text(&text, width, max_lines);
+                let (mut shown, mut draft_at) = if start > 0 {
+                    (format!("… {}", &text[start..]), draft_at.max(start) - start + "… ".len())
+                } else {
+                    (text, draft_at)
+                };
+                // A right-to-left mark makes the whole preview one RTL paragraph, so leading
+                // Latin words, digits or the "…" don't flip it to left-to-right
+                if typeswift::services::format::is_rtl(&shown) {
+                    shown.insert(0, '\u{200F}');
+                    draft_at += '\u{200F}'.len_utf8();
+                }
+                let draft = gpui::HighlightStyle { color: Some(draft_color.into()), ..Default::default() };
+                let end = shown.len();
+                gpui::StyledText::new(shown).with_highlights([(draft_at..end, draft)])
+            });
 
             div()
                 .id("typeswift-main")
+                .relative()
+                // Drag anywhere to move; the position is saved per display on drop
+                .on_mouse_down(gpui::MouseButton::Left, |_, window, _cx| window.start_window_move())
                 .flex()
                 .flex_col()
                 .bg(bg_color)
@@ -61,10 +901,45 @@ impl Render for TypeswiftView {
                 .items_center()
                 .rounded_md()
                 .border_1()
-                .border_color(rgb(0x374151))
+                .when(high_contrast, |d| d.border_2())
+                .border_color(border_color)
                 .text_xs()
                 .text_color(rgb(0xffffff))
-                .child(status_text)
+                .when(high_contrast, |d| d.font_weight(gpui::FontWeight::BOLD))
+                .map(|d| match live {
+                    Some(text) => d.child(
+                        div()
+                            .w_full()
+                            .px(px(typeswift::window::POPUP_TEXT_PADDING))
+                            .text_center()
+                            .line_clamp(max_lines)
+                            .child(text),
+                    ),
+                    None => d.child(status_text),
+                })
+                .when(locked, |d| {
+                    d.child(
+                        div()
+                            .absolute()
+                            .top(px(3.0))
+                            .right(px(4.0))
+                            .px(px(3.0))
+                            .rounded_sm()
+                            .bg(accent)
+                            .child("🔒")
+                    )
+                })
+                .when_some(progress, |d, fraction| {
+                    d.child(
+                        div()
+                            .absolute()
+                            .bottom_0()
+                            .left_0()
+                            .h(px(if high_contrast { 3.0 } else { 2.0 }))
+                            .w(gpui::relative(fraction))
+                            .bg(accent)
+                    )
+                })
         }
     }
 }
@@ -74,7 +949,15 @@ impl Render for PreferencesView {
         let cfg = self.config.read();
         let typing_enabled = cfg.output.enable_typing;
         let add_space = cfg.output.add_space_between_utterances;
+        let output_target = cfg.output.target;
         let ptt = cfg.hotkeys.push_to_talk.clone();
+        let input_gain = cfg.audio.input_gain;
+        let streaming = cfg.streaming.clone();
+        let capabilities = typeswift::services::backend::TranscriberCapabilities::of(cfg.model.backend);
+        let applied_calibration = typeswift::services::mic_test::Calibration {
+            input_gain: cfg.audio.input_gain,
+            vad_threshold: cfg.audio.vad.threshold,
+        };
         drop(cfg);
 
         // Query launch at login status
@@ -108,10 +991,7 @@ impl Render for PreferencesView {
                     cfg.output.enable_typing = !cfg.output.enable_typing;
                     let to_save = cfg.clone();
                     drop(cfg);
-                    // Save async
-                    if let Some(path) = typeswift::config::Config::config_path() {
-                        std::thread::spawn(move || { let _ = to_save.save(path); });
-                    }
+                    typeswift::services::config_store::save(&to_save);
                     // Re-render
                     if let Some(handle) = handle_holder.lock().unwrap().clone() {
                         let _ = handle.update(app_cx, |view, _w, _cx| { view.rev = view.rev.wrapping_add(1); });
@@ -146,15 +1026,51 @@ impl Render for PreferencesView {
                     cfg.output.add_space_between_utterances = !cfg.output.add_space_between_utterances;
                     let to_save = cfg.clone();
                     drop(cfg);
-                    if let Some(path) = typeswift::config::Config::config_path() {
-                        std::thread::spawn(move || { let _ = to_save.save(path); });
-                    }
+                    typeswift::services::config_store::save(&to_save);
                     if let Some(handle) = handle_holder2.lock().unwrap().clone() {
                         let _ = handle.update(app_cx, |view, _w, _cx| { view.rev = view.rev.wrapping_add(1); });
                     }
                 })
         };
 
+        let target_row = {
+            let config = self.config.clone();
+            let handle_holder = self.handle_holder.clone();
+            div()
+                .w_full()
+                .mt(px(3.0))
+                .px(px(6.0))
+                .pt(px(2.0))
+                .pb(px(1.0))
+                .rounded_md()
+                .hover(|s| s.bg(rgb(0x1f2937)))
+                .flex()
+                .items_center()
+                .justify_between()
+                .child(div().py(px(3.0)).child("Dictation target"))
+                .child(
+                    div()
+                        .text_color(rgb(0x9ca3af))
+                        .child(match output_target {
+                            OutputTarget::Cursor => "Cursor",
+                            OutputTarget::Scratchpad => "Scratchpad",
+                        })
+                )
+                .on_mouse_down(gpui::MouseButton::Left, move |_, _window, app_cx| {
+                    let mut cfg = config.write();
+                    cfg.output.target = match cfg.output.target {
+                        OutputTarget::Cursor => OutputTarget::Scratchpad,
+                        OutputTarget::Scratchpad => OutputTarget::Cursor,
+                    };
+                    let to_save = cfg.clone();
+                    drop(cfg);
+                    typeswift::services::config_store::save(&to_save);
+                    if let Some(handle) = handle_holder.lock().unwrap().clone() {
+                        let _ = handle.update(app_cx, |view, _w, _cx| { view.rev = view.rev.wrapping_add(1); });
+                    }
+                })
+        };
+
         // Launch at Login toggle
         let launch_row = {
             let handle_holder = self.handle_holder.clone();
@@ -217,6 +1133,7 @@ impl Render for PreferencesView {
                         return;
                     }
                     if key.is_empty() { return; }
+                    this.capture_mods = gpui::Modifiers::default();
                     let mut parts: Vec<&str> = Vec::new();
                     if ks.modifiers.platform { parts.push("cmd"); }
                     if ks.modifiers.control { parts.push("ctrl"); }
@@ -233,6 +1150,14 @@ impl Render for PreferencesView {
                     for (i, p) in parts.iter().enumerate() { if i > 0 { composed.push('+'); } composed.push_str(p); }
                     if !parts.is_empty() { composed.push('+'); }
                     composed.push_str(normalized_key);
+                    // Store the canonical form; keep listening if the key isn't supported
+                    let composed = match typeswift::input::canonicalize_hotkey(&composed) {
+                        Ok(canonical) => canonical,
+                        Err(e) => {
+                            warn!("Unsupported shortcut {:?}: {}", composed, e);
+                            return;
+                        }
+                    };
 
                     // Update in-memory config immediately
                     let to_save = {
@@ -249,18 +1174,43 @@ impl Render for PreferencesView {
                         });
                     }
                     // Offload I/O and hotkey re-registration so UI doesn't lag
-                    let hk_for_thread = hk_cap.clone();
-                    std::thread::spawn(move || {
-                        if let Ok(mut hk) = hk_for_thread.lock() {
-                            let _ = hk.register_hotkeys(&to_save.hotkeys);
-                        }
-                        if let Some(path) = typeswift::config::Config::config_path() {
-                            let _ = to_save.save(path);
-                        }
-                    });
+                    apply_hotkeys_and_save(hk_cap.clone(), cfg_arc_cap.clone(), to_save);
+                }))
+                .on_modifiers_changed(_cx.listener(|this, event: &gpui::ModifiersChangedEvent, _window, app_cx| {
+                    if !this.capturing_ptt { return; }
+                    let m = event.modifiers;
+                    if m.control || m.alt || m.shift || m.platform || m.function {
+                        // Still holding: accumulate the chord
+                        this.capture_mods.control |= m.control;
+                        this.capture_mods.alt |= m.alt;
+                        this.capture_mods.shift |= m.shift;
+                        this.capture_mods.platform |= m.platform;
+                        this.capture_mods.function |= m.function;
+                        return;
+                    }
+                    // All released without a regular key: bind the modifier chord itself
+                    let held = std::mem::take(&mut this.capture_mods);
+                    let mut parts: Vec<&str> = Vec::new();
+                    if held.platform { parts.push("cmd"); }
+                    if held.control { parts.push("ctrl"); }
+                    if held.alt { parts.push("opt"); }
+                    if held.shift { parts.push("shift"); }
+                    if held.function { parts.push("fn"); }
+                    if parts.is_empty() { return; }
+                    let Ok(chord) = typeswift::input::canonicalize_hotkey(&parts.join("+")) else { return; };
+                    let to_save = {
+                        let mut cfg = this.config.write();
+                        cfg.hotkeys.push_to_talk = chord;
+                        cfg.clone()
+                    };
+                    this.capturing_ptt = false;
+                    this.rev = this.rev.wrapping_add(1);
+                    app_cx.notify();
+                    apply_hotkeys_and_save(this.hotkeys.clone(), this.config.clone(), to_save);
                 }))
                 .on_mouse_down(gpui::MouseButton::Left, _cx.listener(|this, _event, window, _app_cx| {
                     this.capturing_ptt = true;
+                    this.capture_mods = gpui::Modifiers::default();
                     this.rev = this.rev.wrapping_add(1);
                     this.capture_focus.focus(window);
                 }))
@@ -272,6 +1222,23 @@ impl Render for PreferencesView {
                 )
         };
 
+        // Live press/release feedback for the bound shortcut
+        let test_row = div()
+            .w_full()
+            .mt(px(3.0))
+            .px(px(6.0))
+            .pt(px(2.0))
+            .pb(px(1.0))
+            .flex()
+            .items_center()
+            .justify_between()
+            .child(div().py(px(3.0)).child("Shortcut test"))
+            .child(
+                div()
+                    .text_color(if self.ptt_test.pressed_at.is_some() { rgb(0x10b981) } else { rgb(0x9ca3af) })
+                    .child(self.ptt_test.label())
+            );
+
         // Small helper for Fn-only capture
         let cfg_arc_fn = self.config.clone();
         let hk_fn = self.hotkeys.clone();
@@ -289,13 +1256,375 @@ impl Render for PreferencesView {
                 cfg.hotkeys.push_to_talk = "fn".to_string();
                 let to_save = cfg.clone();
                 drop(cfg);
-                if let Some(path) = typeswift::config::Config::config_path() { let _ = to_save.save(path); }
-                if let Ok(mut hk) = hk_fn.lock() { let _ = hk.register_hotkeys(&to_save.hotkeys); }
+                apply_hotkeys_and_save(hk_fn.clone(), cfg_arc_fn.clone(), to_save);
                 // Trigger a lightweight rerender via handle if present
                 // (Preferences window updates via view.rev changes on next interactions)
                 let _ = app_cx;
             });
 
+        // Software gain, applied as audio is captured; cells light up to the chosen value
+        let gain_index = INPUT_GAIN_STEPS
+            .iter()
+            .enumerate()
+            .min_by(|a, b| (a.1 - input_gain).abs().total_cmp(&(b.1 - input_gain).abs()))
+            .map_or(0, |(i, _)| i);
+        let slider_cell = |lit: bool| {
+            div()
+                .flex_1()
+                .h(px(8.0))
+                .rounded_sm()
+                .bg(if lit { rgb(0x3b82f6) } else { rgb(0x374151) })
+        };
+        let gain_row = div()
+            .w_full()
+            .mt(px(8.0))
+            .px(px(6.0))
+            .pt(px(2.0))
+            .pb(px(1.0))
+            .flex()
+            .flex_col()
+            .child(
+                div()
+                    .flex()
+                    .justify_between()
+                    .child(div().py(px(3.0)).child("Input gain"))
+                    .child(div().text_color(rgb(0x9ca3af)).child(format!("{:.2}×", input_gain))),
+            )
+            .child(div().flex().gap(px(2.0)).children(INPUT_GAIN_STEPS.iter().enumerate().map(|(i, &step)| {
+                slider_cell(i <= gain_index).on_mouse_down(
+                    gpui::MouseButton::Left,
+                    _cx.listener(move |this, _, _window, cx| {
+                        let to_save = {
+                            let mut cfg = this.config.write();
+                            cfg.audio.input_gain = step;
+                            cfg.clone()
+                        };
+                        let processor = this.audio_processor.clone();
+                        std::thread::spawn(move || {
+                            if let Ok(mut processor) = processor.lock() {
+                                processor.set_audio_config(to_save.audio.clone());
+                            }
+                            typeswift::services::config_store::save(&to_save);
+                        });
+                        this.rev = this.rev.wrapping_add(1);
+                        cx.notify();
+                    }),
+                )
+            })));
+
+        // Advanced tab: streaming timing, applied to the running processor and the next partial
+        let streaming_slider = |label: &'static str,
+                                steps: &'static [u64],
+                                value: u64,
+                                supported: bool,
+                                set: fn(&mut typeswift::config::StreamingConfig, u64)| {
+            let lit = steps
+                .iter()
+                .enumerate()
+                .min_by_key(|(_, step)| step.abs_diff(value))
+                .map_or(0, |(i, _)| i);
+            let shown = if value >= 10_000 { format!("{} s", value / 1000) } else { format!("{} ms", value) };
+            div()
+                .w_full()
+                .mt(px(8.0))
+                .px(px(6.0))
+                .pt(px(2.0))
+                .pb(px(1.0))
+                .flex()
+                .flex_col()
+                .when(!supported, |d| d.text_color(rgb(0x6b7280)))
+                .child(
+                    div()
+                        .flex()
+                        .justify_between()
+                        .child(div().py(px(3.0)).child(if supported {
+                            label.to_string()
+                        } else {
+                            format!("{} (not supported by this model)", label)
+                        }))
+                        .child(div().text_color(rgb(0x9ca3af)).child(shown)),
+                )
+                .child(div().flex().gap(px(2.0)).children(steps.iter().enumerate().map(|(i, &step)| {
+                    slider_cell(supported && i <= lit).when(supported, |cell| cell.on_mouse_down(
+                        gpui::MouseButton::Left,
+                        _cx.listener(move |this, _, _window, cx| {
+                            let to_save = {
+                                let mut cfg = this.config.write();
+                                set(&mut cfg.streaming, step);
+                                cfg.clone()
+                            };
+                            let processor = this.audio_processor.clone();
+                            std::thread::spawn(move || {
+                                if let Ok(mut processor) = processor.lock() {
+                                    processor.set_streaming_config(to_save.streaming.clone());
+                                }
+                                typeswift::services::config_store::save(&to_save);
+                            });
+                            this.rev = this.rev.wrapping_add(1);
+                            cx.notify();
+                        }),
+                    ))
+                })))
+        };
+        // Which model is loaded and where it runs; skipped while a recording holds the processor
+        let model_text = match self.audio_processor.try_lock() {
+            Ok(processor) => processor.model_description().unwrap_or_else(|| "Not loaded".to_string()),
+            Err(_) => "Busy".to_string(),
+        };
+        let model_row = div()
+            .w_full()
+            .mt(px(8.0))
+            .px(px(6.0))
+            .flex()
+            .justify_between()
+            .child(div().py(px(3.0)).child("Model"))
+            .child(div().py(px(3.0)).text_color(rgb(0x9ca3af)).child(model_text));
+        // Installed models to switch to; the controller loads the pick in the background
+        let model_choices = {
+            let cfg = self.config.read();
+            typeswift::services::models::installed_models(&cfg.model)
+                .into_iter()
+                .enumerate()
+                .map(|(i, model)| {
+                    let current = model.is_current(&cfg.model);
+                    div()
+                        .id(("model-choice", i))
+                        .w_full()
+                        .px(px(6.0))
+                        .py(px(2.0))
+                        .rounded_sm()
+                        .flex()
+                        .justify_between()
+                        .child(model.label)
+                        .child(div().text_color(rgb(0x9ca3af)).child(if current { "current" } else { "" }))
+                        .when(!current, |d| {
+                            d.hover(|s| s.bg(rgb(0x1f2937))).on_click(_cx.listener(move |this, _, _window, cx| {
+                                send_quick_setting(&this.hotkeys, typeswift::input::QuickSetting::Model(i));
+                                this.rev = this.rev.wrapping_add(1);
+                                cx.notify();
+                            }))
+                        })
+                })
+                .collect::<Vec<_>>()
+        };
+        // Speed/accuracy presets, which pick the model for the backend
+        let current_preset = self.config.read().model.preset;
+        let preset_row = div()
+            .w_full()
+            .mt(px(4.0))
+            .px(px(6.0))
+            .flex()
+            .justify_between()
+            .child(div().py(px(3.0)).child("Preset"))
+            .child(div().flex().gap(px(4.0)).children(
+                typeswift::config::ModelPreset::ALL.iter().enumerate().map(|(i, &preset)| {
+                    let selected = current_preset == Some(preset);
+                    div()
+                        .id(("model-preset", i))
+                        .px(px(8.0))
+                        .py(px(2.0))
+                        .rounded_sm()
+                        .when(selected, |d| d.bg(rgb(0x374151)))
+                        .when(!selected, |d| {
+                            d.text_color(rgb(0x9ca3af)).hover(|s| s.bg(rgb(0x1f2937))).on_click(_cx.listener(
+                                move |this, _, _window, cx| {
+                                    send_quick_setting(&this.hotkeys, typeswift::input::QuickSetting::Preset(i));
+                                    this.rev = this.rev.wrapping_add(1);
+                                    cx.notify();
+                                },
+                            ))
+                        })
+                        .child(preset.label())
+                }),
+            ));
+        let advanced_rows = [
+            streaming_slider(
+                "Streaming update interval",
+                PROCESS_INTERVAL_STEPS,
+                streaming.process_interval_ms,
+                capabilities.streaming,
+                |s, v| s.process_interval_ms = v,
+            ),
+            streaming_slider(
+                "Audio before first partial",
+                MIN_INITIAL_AUDIO_STEPS,
+                streaming.min_initial_audio_ms,
+                capabilities.streaming,
+                |s, v| s.min_initial_audio_ms = v,
+            ),
+            streaming_slider(
+                "Long recording chunk length",
+                CHUNK_DURATION_STEPS,
+                streaming.chunk_duration_ms,
+                true,
+                |s, v| s.chunk_duration_ms = v,
+            ),
+        ];
+
+        let tab = |id: &'static str, label: &'static str, selected: bool, advanced: bool| {
+            div()
+                .id(id)
+                .px(px(8.0))
+                .py(px(2.0))
+                .rounded_sm()
+                .when(selected, |d| d.bg(rgb(0x374151)))
+                .when(!selected, |d| d.text_color(rgb(0x9ca3af)).hover(|s| s.bg(rgb(0x1f2937))))
+                .child(label)
+                .on_click(_cx.listener(move |this, _, _window, cx| {
+                    this.advanced = advanced;
+                    let tab = if advanced { PreferencesTab::Advanced } else { PreferencesTab::General };
+                    typeswift::ui_state::update(|state| state.preferences_tab = tab);
+                    cx.notify();
+                }))
+        };
+        let tabs = div()
+            .flex()
+            .gap(px(4.0))
+            .child(tab("prefs-tab-general", "General", !self.advanced, false))
+            .child(tab("prefs-tab-advanced", "Advanced", self.advanced, true));
+
+        // Hardware input volume, only for devices whose driver exposes one (changes it system-wide)
+        let volume_row = menubar_ffi::input_volume().map(|volume| {
+            let lit = (volume * INPUT_VOLUME_STEPS as f32).round() as usize;
+            div()
+                .w_full()
+                .mt(px(3.0))
+                .px(px(6.0))
+                .pt(px(2.0))
+                .pb(px(1.0))
+                .flex()
+                .flex_col()
+                .child(
+                    div()
+                        .flex()
+                        .justify_between()
+                        .child(div().py(px(3.0)).child("Device input volume"))
+                        .child(div().text_color(rgb(0x9ca3af)).child(format!("{:.0}%", volume * 100.0))),
+                )
+                .child(div().flex().gap(px(2.0)).children((1..=INPUT_VOLUME_STEPS).map(|i| {
+                    slider_cell(i <= lit).on_mouse_down(
+                        gpui::MouseButton::Left,
+                        _cx.listener(move |this, _, _window, cx| {
+                            if !menubar_ffi::set_input_volume(i as f32 / INPUT_VOLUME_STEPS as f32) {
+                                warn!("Input device volume can't be changed");
+                            }
+                            this.rev = this.rev.wrapping_add(1);
+                            cx.notify();
+                        }),
+                    )
+                })))
+        });
+
+        // Microphone test: a short recording, its levels and transcript, and suggested settings
+        let mic_status = self.mic_test.status();
+        let mic_row = {
+            use typeswift::services::mic_test::MicTestStatus;
+            let mic_test = self.mic_test.clone();
+            div()
+                .w_full()
+                .mt(px(8.0))
+                .px(px(6.0))
+                .pt(px(2.0))
+                .pb(px(1.0))
+                .rounded_md()
+                .hover(|s| s.bg(rgb(0x1f2937)))
+                .flex()
+                .items_center()
+                .justify_between()
+                .child(div().py(px(3.0)).child("Test microphone"))
+                .child(
+                    div()
+                        .text_color(match mic_status {
+                            MicTestStatus::Recording => rgb(0xf59e0b),
+                            _ => rgb(0x9ca3af),
+                        })
+                        .child(match mic_status {
+                            MicTestStatus::Idle => "Record 3 s",
+                            MicTestStatus::Recording => "Speak now…",
+                            MicTestStatus::Transcribing => "Transcribing…",
+                            MicTestStatus::Done(_) | MicTestStatus::Failed(_) => "Test again",
+                        })
+                )
+                .on_mouse_down(gpui::MouseButton::Left, move |_, _window, _app_cx| mic_test.start())
+        };
+        let mic_result = match mic_status {
+            typeswift::services::mic_test::MicTestStatus::Failed(message) => Some(
+                div().px(px(6.0)).pt(px(2.0)).text_color(rgb(0xf87171)).child(message).into_any_element(),
+            ),
+            typeswift::services::mic_test::MicTestStatus::Done(report) => {
+                use typeswift::services::mic_test::dbfs;
+                let waveform = div()
+                    .h(px(24.0))
+                    .flex()
+                    .items_end()
+                    .gap(px(1.0))
+                    .children(report.waveform.iter().map(|&level| {
+                        div()
+                            .w(px(3.0))
+                            .h(px((level.sqrt() * 24.0).max(1.0)))
+                            .bg(if level >= 0.99 { rgb(0xef4444) } else { rgb(0x10b981) })
+                    }));
+                let levels = format!(
+                    "Level {:.0} dBFS · peak {:.0} · noise {:.0}",
+                    dbfs(report.rms),
+                    dbfs(report.peak),
+                    dbfs(report.noise_floor)
+                );
+                let heard = if report.transcript.is_empty() {
+                    "Nothing recognized".to_string()
+                } else {
+                    format!("“{}”", report.transcript)
+                };
+                let suggestion = match report.recommendation {
+                    Some(calibration) => {
+                        let label = format!(
+                            "Suggested: gain {:.2}×, VAD threshold {:.3}",
+                            calibration.input_gain, calibration.vad_threshold
+                        );
+                        let applied = calibration == applied_calibration;
+                        let apply_button = div()
+                            .id("mic-test-apply")
+                            .px(px(6.0))
+                            .py(px(2.0))
+                            .rounded_sm()
+                            .border_1()
+                            .border_color(rgb(0x374151))
+                            .hover(|s| s.bg(rgb(0x1f2937)))
+                            .when(applied, |d| d.text_color(rgb(0x6b7280)))
+                            .child(if applied { "Applied" } else { "Apply" })
+                            .on_click(_cx.listener(move |this, _, _window, cx| {
+                                if !applied {
+                                    this.mic_test.apply(calibration, &this.config);
+                                    this.rev = this.rev.wrapping_add(1);
+                                    cx.notify();
+                                }
+                            }));
+                        div()
+                            .flex()
+                            .items_center()
+                            .justify_between()
+                            .child(label)
+                            .child(apply_button)
+                    }
+                    None => div().text_color(rgb(0x9ca3af)).child("No speech detected; speak during the test"),
+                };
+                Some(
+                    div()
+                        .px(px(6.0))
+                        .pt(px(2.0))
+                        .flex()
+                        .flex_col()
+                        .gap(px(2.0))
+                        .child(waveform)
+                        .child(div().text_color(rgb(0x9ca3af)).child(levels))
+                        .child(div().overflow_hidden().child(heard))
+                        .child(suggestion)
+                        .into_any_element(),
+                )
+            }
+            _ => None,
+        };
+
         div()
             .id("typeswift-prefs-window")
             .flex()
@@ -316,41 +1645,93 @@ impl Render for PreferencesView {
                     .w_full()
                     .flex()
                     .pt(px(5.0))
-                    .justify_end()
-                    .text_color(rgb(0x596678))
-                    .child(div().text_xs().child("ashwwwin/typeswift"))
+                    .items_center()
+                    .justify_between()
+                    .child(tabs)
+                    .child(div().text_xs().text_color(rgb(0x596678)).child("ashwwwin/typeswift"))
             )
-            .child(typing_row)
-            .child(add_space_row)
-            .child(launch_row)
-            .child(ptt_row)
-            .child(set_fn_button)
+            .when(self.advanced, |d| d.child(model_row).child(preset_row).children(model_choices).children(advanced_rows))
+            .when(!self.advanced, |d| {
+                d.child(typing_row)
+                    .child(add_space_row)
+                    .child(target_row)
+                    .child(launch_row)
+                    .child(ptt_row)
+                    .child(test_row)
+                    .child(set_fn_button)
+                    .child(gain_row)
+                    .children(volume_row)
+                    .child(mic_row)
+                    .children(mic_result)
+            })
             // .child(div().mt(px(6.0)).child(
             //     "Tip: Click a row to toggle. Close this window when done.",
             // ))
     }
 }
 
+/// Register edited hotkeys off the UI thread and persist them. On a conflict the
+/// in-memory config is reverted to the bindings still in effect and the user is told why.
+fn apply_hotkeys_and_save(
+    hotkeys: std::sync::Arc<std::sync::Mutex<HotkeyHandler>>,
+    config: std::sync::Arc<parking_lot::RwLock<Config>>,
+    to_save: Config,
+) {
+    std::thread::spawn(move || {
+        let result = match hotkeys.lock() {
+            Ok(mut hk) => {
+                hk.set_profile_bindings(&to_save.profiles);
+                hk.apply_hotkeys(&to_save.hotkeys).map_err(|c| (c, hk.active_hotkeys()))
+            }
+            Err(_) => return,
+        };
+        match result {
+            Ok(()) => typeswift::services::config_store::save(&to_save),
+            Err((conflict, active)) => {
+                if let Some(active) = active {
+                    config.write().hotkeys = active;
+                }
+                menubar_ffi::MenuBarController::show_alert("Shortcut unavailable", &conflict.user_message());
+            }
+        }
+    });
+}
+
 fn main() {
+    // Command-line modes (`typeswift stream`, ...) run instead of the app
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    if let Some(code) = typeswift::cli::run(&args) {
+        std::process::exit(code);
+    }
+
     // Initialize logging
     {
         use tracing_subscriber::{EnvFilter, fmt};
         // Default to INFO if RUST_LOG is not set, so important logs (like memory) are visible.
         let filter = EnvFilter::try_from_default_env().unwrap_or_else(|_| EnvFilter::new("info"));
-        let _ = fmt().with_env_filter(filter).try_init();
+        // Recent lines are also kept in memory for support bundles
+        let _ = fmt().with_env_filter(filter).with_writer(typeswift::support::LogCapture).try_init();
     }
 
     // Load configuration
-    let config = Config::load().unwrap_or_default();
+    let mut config = Config::load().unwrap_or_default();
+    typeswift::ui_state::restore(&mut config);
 
     // Initialize hotkey handler
     let mut hotkey_handler = HotkeyHandler::new().expect("Failed to create hotkey handler");
 
-    // Register hotkeys
-    if let Err(e) = hotkey_handler.register_hotkeys(&config.hotkeys) {
-        error!("Failed to register hotkeys: {}", e);
-        return;
-    }
+    // Register hotkeys; on conflict keep running on the fallback binding and tell the user
+    hotkey_handler.set_profile_bindings(&config.profiles);
+    let startup_conflict = match hotkey_handler.apply_hotkeys(&config.hotkeys) {
+        Ok(()) => None,
+        Err(conflict) => {
+            error!("Failed to register hotkeys: {}", conflict.user_message());
+            if let Some(active) = hotkey_handler.active_hotkeys() {
+                config.hotkeys = active;
+            }
+            Some(conflict)
+        }
+    };
 
     // Wrap handler to allow live re-registration
     let hotkey_handler = std::sync::Arc::new(std::sync::Mutex::new(hotkey_handler));
@@ -363,7 +1744,14 @@ fn main() {
     // Set environment variable to hide dock icon
     std::env::set_var("GPUI_HIDE_DOCK", "1");
 
-    Application::new().run(move |cx: &mut App| {
+    let app = Application::new();
+    // typeswift:// links (Raycast/Alfred script commands) go through the menu action path
+    app.on_open_urls(|urls| {
+        for url in urls {
+            menubar_ffi::handle_url(&url);
+        }
+    });
+    app.run(move |cx: &mut App| {
         // Initialize menu bar and hide dock icon AFTER GPUI starts
         // Try multiple times to ensure it sticks
         std::thread::spawn(|| {
@@ -399,19 +1787,10 @@ fn main() {
         // Create event channels for the controller and UI
         let (event_tx, event_rx) = bounded::<HotkeyEvent>(256);
         let (ui_tx, ui_rx) = bounded::<HotkeyEvent>(64);
-        // Wire Preferences menu item to controller via callback
-        {
-            use std::sync::mpsc;
-            let (prefs_tx, prefs_rx) = mpsc::channel::<HotkeyEvent>();
-            menubar_ffi::register_preferences_callback(prefs_tx);
-            let event_tx_clone = event_tx.clone();
-            let ui_tx_prefs = ui_tx.clone();
-            std::thread::spawn(move || {
-                while let Ok(ev) = prefs_rx.recv() {
-                    let _ = event_tx_clone.send(ev);
-                    let _ = ui_tx_prefs.send(ev);
-                }
-            });
+        // Preferences and other menu items arrive with the hotkeys, through the same forwarder
+        if let Some(menu_tx) = hotkey_handler.lock().unwrap().event_sender() {
+            menubar_ffi::register_preferences_callback(menu_tx.clone());
+            menubar_ffi::register_menu_action_callback(menu_tx);
         }
 
         // Create controller before the window so we can pass its state/config directly,
@@ -419,11 +1798,19 @@ fn main() {
         let controller = AppController::new(config_clone.clone());
         let state_for_view = controller.state();
         let config_handle_for_view = controller.config_handle();
+        let state_for_scratchpad = controller.state();
+        let history_for_view = controller.history();
+        let corrections_for_view =
+            typeswift::corrections::CorrectionMemory::open_default(&config_clone.history);
+        let typing_queue_for_views = controller.typing_queue();
+        let audio_processor = controller.audio_processor();
+        let mic_test = typeswift::services::mic_test::MicTest::new(audio_processor.clone());
+        let popup_window_manager = controller.window_manager();
 
         let window = cx
             .open_window(
                 WindowOptions {
-                    is_movable: false,
+                    is_movable: true,
                     titlebar: None,
                     window_bounds: Some(WindowBounds::Windowed(bounds)),
                     display_id: Some(screen.id()),
@@ -433,15 +1820,17 @@ fn main() {
                     ..Default::default()
                 },
                 move |_window, cx| {
-                    let _state = state_for_view.clone();
-                    cx.new(|_cx| TypeswiftView { _state })
+                    let state = state_for_view.clone();
+                    cx.new(|_cx| TypeswiftView { state, style: typeswift::window::PopupStyle::default() })
                 },
             )
             .unwrap();
 
         let _window_for_callback = window.clone();
+        let popup_handle = window.clone();
 
-        // Forward hotkeys to controller and UI
+        // Forward hotkeys and menu actions to controller and UI. A thread of its own rather than
+        // a runtime task: it sleeps in recv until there's an event, and isn't delayed by tasks
         let tx_for_hotkeys = event_tx.clone();
         let ui_tx_hotkeys = ui_tx.clone();
         std::thread::spawn(move || {
@@ -457,6 +1846,7 @@ fn main() {
         if let Err(e) = WindowManager::setup_properties() {
             warn!("Failed to setup window properties: {}", e);
         }
+        let _ = WindowManager::describe_windows_for_accessibility();
 
         // Share state between UI and controller
         let prefs_config_handle = config_handle_for_view.clone();
@@ -467,11 +1857,37 @@ fn main() {
             "Typeswift started. Push-to-talk: {} (hold to record)",
             config_clone.hotkeys.push_to_talk
         );
+        if let Some(conflict) = startup_conflict {
+            menubar_ffi::MenuBarController::show_alert("Shortcut unavailable", &conflict.user_message());
+        }
         // Toggle window hotkey setting removed from Preferences UI; still supported if present in config file.
         info!("Hotkeys forwarding independently of UI");
 
         // Removed file watcher: config changes now apply immediately where edited (Preferences window and hotkey presets).
 
+        // Let MCP clients drive dictation through the same event channel as the hotkeys
+        typeswift::mcp::start(
+            &config_clone.mcp,
+            typeswift::mcp::McpContext {
+                events: event_tx.clone(),
+                state: controller.state(),
+                config: controller.config_handle(),
+                audio_processor: controller.audio_processor(),
+            },
+        );
+
+        // Editor plugins: start/stop and inline partial results over a unix socket
+        typeswift::ipc::start(
+            &config_clone.ipc,
+            typeswift::ipc::IpcContext { events: event_tx.clone(), state: controller.state() },
+        );
+
+        // Offer to transcribe calendar meetings as they start
+        typeswift::meetings::start(
+            &config_clone.meetings,
+            typeswift::meetings::MeetingsContext { events: event_tx.clone(), state: controller.state() },
+        );
+
         // Run controller in background, consuming forwarded events
         controller.start(event_rx);
 
@@ -480,26 +1896,264 @@ fn main() {
         let prefs_open = std::sync::Arc::new(std::sync::atomic::AtomicBool::new(false));
         let prefs_open_for_view = prefs_open.clone();
         let hotkey_handler_for_prefs_outer = hotkey_handler.clone();
+        let prefs_handle: std::sync::Arc<std::sync::Mutex<Option<gpui::WindowHandle<PreferencesView>>>> =
+            std::sync::Arc::new(std::sync::Mutex::new(None));
+        let scratchpad_handle: std::sync::Arc<std::sync::Mutex<Option<gpui::WindowHandle<ScratchpadView>>>> =
+            std::sync::Arc::new(std::sync::Mutex::new(None));
+        let ui_config = prefs_config_handle.clone();
+        let history_handle: std::sync::Arc<std::sync::Mutex<Option<gpui::WindowHandle<HistoryView>>>> =
+            std::sync::Arc::new(std::sync::Mutex::new(None));
+        let palette_handle: std::sync::Arc<std::sync::Mutex<Option<gpui::WindowHandle<CommandPaletteView>>>> =
+            std::sync::Arc::new(std::sync::Mutex::new(None));
+        let palette_events = hotkey_handler.lock().unwrap().event_sender();
         cx.spawn(async move |cx| {
             use std::time::Duration;
+            let mut scratchpad_rev = state_for_scratchpad.scratchpad_rev();
+            let mut popup_status = (
+                state_for_scratchpad.progress(),
+                state_for_scratchpad.model_status(),
+                state_for_scratchpad.is_recording_locked(),
+                state_for_scratchpad.get_recording_state(),
+                state_for_scratchpad.live_transcript(),
+            );
+            let mut quick_settings = None;
+            let mut popup_style = None;
+            let mut mic_test_rev = mic_test.rev();
             loop {
-                if let Ok(ev) = ui_rx.try_recv() {
+                // Keep the menu bar's Quick Settings in step with edits made anywhere
+                let current_settings = menubar_ffi::QuickSettings::from_config(&ui_config.read());
+                if quick_settings.as_ref() != Some(&current_settings) {
+                    menubar_ffi::MenuBarController::set_quick_settings(&current_settings);
+                    quick_settings = Some(current_settings);
+                }
+
+                // Follow the contrast/motion options and the matching system settings
+                let current_style = typeswift::window::PopupStyle::resolve(&ui_config.read());
+                if popup_style != Some(current_style) {
+                    popup_style = Some(current_style);
+                    popup_window_manager.set_popup_animations(!current_style.reduce_motion);
+                    let _ = cx.update(|cx| {
+                        let _ = popup_handle.update(cx, |view, _w, cx| {
+                            view.style = current_style;
+                            cx.notify();
+                        });
+                    });
+                }
+
+                // Snap and remember the popup once the user drops it somewhere new
+                popup_window_manager.track_popup_drag();
+
+                // Show microphone test progress and results in Preferences
+                let rev = mic_test.rev();
+                if rev != mic_test_rev {
+                    mic_test_rev = rev;
+                    let handle = prefs_handle.lock().unwrap().clone();
+                    if let Some(handle) = handle {
+                        let _ = cx.update(|cx| {
+                            let _ = handle.update(cx, |_view, _w, cx| cx.notify());
+                        });
+                    }
+                }
+
+                // Repaint the popup's progress bar, status line and streaming preview
+                let current = (
+                    state_for_scratchpad.progress(),
+                    state_for_scratchpad.model_status(),
+                    state_for_scratchpad.is_recording_locked(),
+                    state_for_scratchpad.get_recording_state(),
+                    state_for_scratchpad.live_transcript(),
+                );
+                if current != popup_status {
+                    // Grow the popup for wrapped streaming text; back to one line when it's gone
+                    let live = &current.4;
+                    let lines = if live.is_empty() || current.3 != typeswift::state::RecordingState::Recording {
+                        1
+                    } else {
+                        let cfg = ui_config.read();
+                        let max_lines = cfg.ui.popup_max_lines.max(1);
+                        typeswift::window::fit_popup_text(&live.text().0, cfg.ui.window_width, max_lines).1
+                    };
+                    popup_window_manager.fit_popup_height(lines);
+                    popup_status = current;
+                    let _ = cx.update(|cx| {
+                        let _ = popup_handle.update(cx, |_view, _w, cx| cx.notify());
+                    });
+                }
+
+                // Show or refresh the scratchpad when dictations land there
+                let rev = state_for_scratchpad.scratchpad_rev();
+                if rev != scratchpad_rev {
+                    scratchpad_rev = rev;
+                    let handle = scratchpad_handle.lock().unwrap().clone();
+                    if let Some(handle) = handle {
+                        let _ = cx.update(|cx| {
+                            let _ = handle.update(cx, |_view, _w, cx| cx.notify());
+                        });
+                    } else if !state_for_scratchpad.scratchpad_entries().is_empty() {
+                        let state = state_for_scratchpad.clone();
+                        let config = ui_config.clone();
+                        let typing_queue = typing_queue_for_views.clone();
+                        let holder = scratchpad_handle.clone();
+                        let _ = cx.update(|cx| {
+                            let scratch_size = size(
+                                px(typeswift::window::SCRATCHPAD_WIDTH as f32),
+                                px(typeswift::window::SCRATCHPAD_HEIGHT as f32),
+                            );
+                            let bounds = Bounds::centered(None, scratch_size, cx);
+                            let holder_for_view = holder.clone();
+                            // Non-activating pop-up so the previous app keeps focus for "Send to app"
+                            match cx.open_window(
+                                WindowOptions {
+                                    window_bounds: Some(WindowBounds::Windowed(bounds)),
+                                    titlebar: None,
+                                    is_movable: true,
+                                    focus: false,
+                                    kind: gpui::WindowKind::PopUp,
+                                    ..Default::default()
+                                },
+                                move |_, cx| {
+                                    cx.new(|_cx| ScratchpadView { state, config, typing_queue, handle_holder: holder_for_view })
+                                },
+                            ) {
+                                Ok(handle) => *holder.lock().unwrap() = Some(handle),
+                                Err(e) => warn!("Could not open scratchpad window: {}", e),
+                            }
+                        });
+                    }
+                }
+
+                while let Ok(ev) = ui_rx.try_recv() {
+                    if matches!(ev, HotkeyEvent::PushToTalkPressed(_) | HotkeyEvent::PushToTalkReleased) {
+                        // Feed the Preferences shortcut test row, if the window is open
+                        let handle = prefs_handle.lock().unwrap().clone();
+                        if let Some(handle) = handle {
+                            let _ = cx.update(|cx| {
+                                let _ = handle.update(cx, |view, _w, cx| {
+                                    view.ptt_test.record(ev);
+                                    cx.notify();
+                                });
+                            });
+                        }
+                    }
+                    if let HotkeyEvent::OpenHistory = ev {
+                        if history_handle.lock().unwrap().is_some() {
+                            if let Err(e) = typeswift::window::WindowManager::focus_window_titled(typeswift::window::HISTORY_TITLE) {
+                                warn!("Could not focus history window: {}", e);
+                            }
+                        } else {
+                            let history = history_for_view.clone();
+                            let corrections = corrections_for_view.clone();
+                            let config = ui_config.clone();
+                            let typing_queue = typing_queue_for_views.clone();
+                            let holder = history_handle.clone();
+                            let saved = typeswift::ui_state::get();
+                            let _ = cx.update(|cx| {
+                                let (width, height) = saved.history_size.unwrap_or((
+                                    typeswift::window::HISTORY_WIDTH as f32,
+                                    typeswift::window::HISTORY_HEIGHT as f32,
+                                ));
+                                let history_size = size(px(width), px(height));
+                                let bounds = Bounds::centered(None, history_size, cx);
+                                let holder_for_view = holder.clone();
+                                match cx.open_window(
+                                    WindowOptions {
+                                        window_bounds: Some(WindowBounds::Windowed(bounds)),
+                                        titlebar: Some(gpui::TitlebarOptions { title: Some(typeswift::window::HISTORY_TITLE.into()), appears_transparent: true, ..Default::default() }),
+                                        focus: true,
+                                        ..Default::default()
+                                    },
+                                    move |_, cx| {
+                                        cx.new(|cx| HistoryView {
+                                            history,
+                                            config,
+                                            typing_queue,
+                                            handle_holder: holder_for_view,
+                                            focus: cx.focus_handle(),
+                                            scroll: gpui::ScrollHandle::new(),
+                                            query: typeswift::history::HistoryQuery {
+                                                range: saved.history_range,
+                                                ..Default::default()
+                                            },
+                                            selected: 0,
+                                            session: None,
+                                            editing: None,
+                                            corrections,
+                                            window_size: None,
+                                        })
+                                    },
+                                ) {
+                                    Ok(handle) => *holder.lock().unwrap() = Some(handle),
+                                    Err(e) => warn!("Could not open history window: {}", e),
+                                }
+                            });
+                            let _ = typeswift::window::WindowManager::focus_window_titled(typeswift::window::HISTORY_TITLE);
+                            let _ = WindowManager::describe_windows_for_accessibility();
+                        }
+                    }
+                    if let HotkeyEvent::OpenCommandPalette = ev {
+                        if palette_handle.lock().unwrap().is_some() {
+                            let _ = typeswift::window::WindowManager::focus_window_titled(typeswift::window::COMMAND_PALETTE_TITLE);
+                        } else if let Some(ref events) = palette_events {
+                            let config = ui_config.clone();
+                            let state = state_for_scratchpad.clone();
+                            let events = events.clone();
+                            let holder = palette_handle.clone();
+                            let _ = cx.update(|cx| {
+                                let palette_size = size(
+                                    px(typeswift::window::COMMAND_PALETTE_WIDTH as f32),
+                                    px(typeswift::window::COMMAND_PALETTE_HEIGHT as f32),
+                                );
+                                let bounds = Bounds::centered(None, palette_size, cx);
+                                let holder_for_view = holder.clone();
+                                match cx.open_window(
+                                    WindowOptions {
+                                        window_bounds: Some(WindowBounds::Windowed(bounds)),
+                                        titlebar: Some(gpui::TitlebarOptions { title: Some(typeswift::window::COMMAND_PALETTE_TITLE.into()), appears_transparent: true, ..Default::default() }),
+                                        focus: true,
+                                        ..Default::default()
+                                    },
+                                    move |_, cx| {
+                                        cx.new(|cx| CommandPaletteView {
+                                            config,
+                                            state,
+                                            events,
+                                            handle_holder: holder_for_view,
+                                            focus: cx.focus_handle(),
+                                            scroll: gpui::ScrollHandle::new(),
+                                            query: String::new(),
+                                            selected: 0,
+                                        })
+                                    },
+                                ) {
+                                    Ok(handle) => *holder.lock().unwrap() = Some(handle),
+                                    Err(e) => warn!("Could not open command palette: {}", e),
+                                }
+                            });
+                            let _ = typeswift::window::WindowManager::focus_window_titled(typeswift::window::COMMAND_PALETTE_TITLE);
+                            let _ = WindowManager::describe_windows_for_accessibility();
+                        }
+                    }
                     if let HotkeyEvent::OpenPreferences = ev {
                         if !prefs_open.load(std::sync::atomic::Ordering::SeqCst) {
                             prefs_open.store(true, std::sync::atomic::Ordering::SeqCst);
                             let prefs_config = prefs_config.clone();
                             let prefs_open_for_view = prefs_open_for_view.clone();
                             let hk_for_update = hotkey_handler_for_prefs_outer.clone();
+                            let mic_test_for_prefs = mic_test.clone();
+                            let audio_processor_for_prefs = audio_processor.clone();
+                            let handle_holder_outer = prefs_handle.clone();
                             let _ = cx.update(|cx| {
-                                // Preferences window fixed size (320x203)
-                                let bounds = Bounds::centered(None, size(px(320.0), px(203.0)), cx);
-                                let handle_holder_outer: std::sync::Arc<std::sync::Mutex<Option<gpui::WindowHandle<PreferencesView>>>> =
-                                    std::sync::Arc::new(std::sync::Mutex::new(None));
+                                // Preferences window fixed size
+                                let prefs_size = size(
+                                    px(typeswift::window::PREFERENCES_WIDTH as f32),
+                                    px(typeswift::window::PREFERENCES_HEIGHT as f32),
+                                );
+                                let bounds = Bounds::centered(None, prefs_size, cx);
                                 let holder_for_create = handle_holder_outer.clone();
                                 let handle = cx.open_window(
                                     WindowOptions {
                                         window_bounds: Some(WindowBounds::Windowed(bounds)),
-                                        titlebar: Some(gpui::TitlebarOptions { appears_transparent: true, ..Default::default() }),
+                                        titlebar: Some(gpui::TitlebarOptions { title: Some(typeswift::window::PREFERENCES_TITLE.into()), appears_transparent: true, ..Default::default() }),
                                         focus: true,
                                         ..Default::default()
                                     },
@@ -507,7 +2161,10 @@ fn main() {
                                         let open_flag = prefs_open_for_view.clone();
                                         let holder = holder_for_create.clone();
                                         let hk = hk_for_update.clone();
-                                        cx.new(|cx| PreferencesView { config: prefs_config.clone(), open_flag, handle_holder: holder, hotkeys: hk, capture_focus: cx.focus_handle(), capturing_ptt: false, rev: 0 })
+                                        let mic_test = mic_test_for_prefs.clone();
+                                        let audio_processor = audio_processor_for_prefs.clone();
+                                        let tab = typeswift::ui_state::get().preferences_tab;
+                                        cx.new(|cx| PreferencesView { config: prefs_config.clone(), open_flag, handle_holder: holder, hotkeys: hk, capture_focus: cx.focus_handle(), capturing_ptt: false, capture_mods: gpui::Modifiers::default(), ptt_test: HotkeyTestState::default(), mic_test, audio_processor, advanced: tab == PreferencesTab::Advanced, rev: 0 })
                                     },
                                 )
                                 .unwrap();
@@ -516,6 +2173,7 @@ fn main() {
                                 if let Err(e) = typeswift::window::WindowManager::focus_preferences() {
                                     warn!("Could not focus preferences window: {}", e);
                                 }
+                                let _ = WindowManager::describe_windows_for_accessibility();
                             });
                         } else {
                             // Already open: bring the Preferences window to front
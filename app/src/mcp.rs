@@ -0,0 +1,310 @@
+//! Model Context Protocol server, so agents and editors can drive dictation.
+//!
+//! Speaks MCP's Streamable HTTP transport in its simplest form: each JSON-RPC
+//! request is a `POST /mcp` answered with one JSON body (no SSE streams or sessions).
+//! It listens on 127.0.0.1 only and rejects browser origins other than localhost.
+//! `GET /metrics` on the same port serves `crate::metrics` for Prometheus.
+
+use crate::config::{Config, McpConfig};
+use crate::controller::post_process;
+use crate::input::HotkeyEvent;
+use crate::services::audio::AudioProcessor;
+use crate::state::{AppStateManager, RecordingState};
+use crossbeam_channel::Sender;
+use serde_json::{json, Value};
+use std::io::{BufRead, BufReader, Read, Write};
+use std::net::{TcpListener, TcpStream};
+use std::path::Path;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+use tracing::{debug, info, warn};
+
+const PROTOCOL_VERSION: &str = "2025-03-26";
+/// Longest `start_dictation` recording, so a stray call can't leave the mic open
+const MAX_DICTATION_SECS: f64 = 120.0;
+/// How long to wait for a stopped recording to be transcribed
+const FINISH_TIMEOUT: Duration = Duration::from_secs(120);
+const MAX_BODY_BYTES: usize = 1024 * 1024;
+
+/// What the tools need from the running app
+#[derive(Clone)]
+pub struct McpContext {
+    pub events: Sender<HotkeyEvent>,
+    pub state: AppStateManager,
+    pub config: Arc<parking_lot::RwLock<Config>>,
+    pub audio_processor: Arc<Mutex<AudioProcessor>>,
+}
+
+/// Start the server on a background thread if `[mcp] enabled`
+pub fn start(config: &McpConfig, ctx: McpContext) {
+    if !config.enabled {
+        return;
+    }
+    let listener = match TcpListener::bind(("127.0.0.1", config.port)) {
+        Ok(listener) => listener,
+        Err(e) => {
+            warn!("MCP server could not listen on port {}: {}", config.port, e);
+            return;
+        }
+    };
+    info!("MCP server listening on http://127.0.0.1:{}/mcp (metrics at /metrics)", config.port);
+    std::thread::spawn(move || {
+        for stream in listener.incoming().flatten() {
+            let ctx = ctx.clone();
+            std::thread::spawn(move || {
+                if let Err(e) = handle_connection(stream, &ctx) {
+                    debug!("MCP connection closed: {}", e);
+                }
+            });
+        }
+    });
+}
+
+struct Request {
+    method: String,
+    path: String,
+    origin: Option<String>,
+    body: Vec<u8>,
+}
+
+fn read_request(reader: &mut BufReader<TcpStream>) -> std::io::Result<Option<Request>> {
+    let mut line = String::new();
+    if reader.read_line(&mut line)? == 0 {
+        return Ok(None);
+    }
+    let mut parts = line.split_whitespace();
+    let method = parts.next().unwrap_or_default().to_string();
+    let path = parts.next().unwrap_or_default().to_string();
+
+    let mut content_length = 0;
+    let mut origin = None;
+    loop {
+        line.clear();
+        if reader.read_line(&mut line)? == 0 || line.trim().is_empty() {
+            break;
+        }
+        if let Some((name, value)) = line.split_once(':') {
+            let value = value.trim();
+            if name.eq_ignore_ascii_case("content-length") {
+                content_length = value.parse().unwrap_or(0);
+            } else if name.eq_ignore_ascii_case("origin") {
+                origin = Some(value.to_string());
+            }
+        }
+    }
+    if content_length > MAX_BODY_BYTES {
+        return Err(std::io::Error::other("request body too large"));
+    }
+    let mut body = vec![0; content_length];
+    reader.read_exact(&mut body)?;
+    Ok(Some(Request { method, path, origin, body }))
+}
+
+fn write_response(stream: &mut TcpStream, status: &str, body: Option<&Value>) -> std::io::Result<()> {
+    let body = body.map(|b| b.to_string()).unwrap_or_default();
+    write!(
+        stream,
+        "HTTP/1.1 {}\r\nContent-Type: application/json\r\nContent-Length: {}\r\n\r\n{}",
+        status,
+        body.len(),
+        body
+    )?;
+    stream.flush()
+}
+
+fn write_metrics(stream: &mut TcpStream) -> std::io::Result<()> {
+    let body = crate::metrics::render();
+    write!(
+        stream,
+        "HTTP/1.1 200 OK\r\nContent-Type: text/plain; version=0.0.4\r\nContent-Length: {}\r\n\r\n{}",
+        body.len(),
+        body
+    )?;
+    stream.flush()
+}
+
+/// Browsers send an Origin; only pages served from this machine may call in
+fn origin_allowed(origin: Option<&str>) -> bool {
+    origin.is_none_or(|o| {
+        let host = o.split("://").nth(1).unwrap_or(o);
+        let host = host.split(':').next().unwrap_or(host);
+        matches!(host, "localhost" | "127.0.0.1" | "[::1]")
+    })
+}
+
+fn handle_connection(stream: TcpStream, ctx: &McpContext) -> std::io::Result<()> {
+    let mut writer = stream.try_clone()?;
+    let mut reader = BufReader::new(stream);
+    while let Some(request) = read_request(&mut reader)? {
+        if !origin_allowed(request.origin.as_deref()) {
+            write_response(&mut writer, "403 Forbidden", None)?;
+            continue;
+        }
+        let path = request.path.split('?').next();
+        if path == Some("/metrics") && request.method == "GET" {
+            write_metrics(&mut writer)?;
+            continue;
+        }
+        if path != Some("/mcp") {
+            write_response(&mut writer, "404 Not Found", None)?;
+            continue;
+        }
+        if request.method != "POST" {
+            // No server-initiated streams to offer
+            write_response(&mut writer, "405 Method Not Allowed", None)?;
+            continue;
+        }
+        let message: Value = match serde_json::from_slice(&request.body) {
+            Ok(message) => message,
+            Err(e) => {
+                let error = rpc_error(Value::Null, -32700, &format!("Parse error: {}", e));
+                write_response(&mut writer, "400 Bad Request", Some(&error))?;
+                continue;
+            }
+        };
+        match handle_message(&message, ctx) {
+            Some(response) => write_response(&mut writer, "200 OK", Some(&response))?,
+            None => write_response(&mut writer, "202 Accepted", None)?,
+        }
+    }
+    Ok(())
+}
+
+fn rpc_error(id: Value, code: i64, message: &str) -> Value {
+    json!({ "jsonrpc": "2.0", "id": id, "error": { "code": code, "message": message } })
+}
+
+/// Answer one JSON-RPC message; `None` for notifications
+fn handle_message(message: &Value, ctx: &McpContext) -> Option<Value> {
+    let id = message.get("id").cloned()?;
+    let method = message.get("method").and_then(Value::as_str).unwrap_or_default();
+    let params = message.get("params").cloned().unwrap_or(Value::Null);
+    debug!("MCP request: {}", method);
+    let result = match method {
+        "initialize" => json!({
+            "protocolVersion": params.get("protocolVersion").and_then(Value::as_str).unwrap_or(PROTOCOL_VERSION),
+            "capabilities": { "tools": {} },
+            "serverInfo": { "name": "typeswift", "version": env!("CARGO_PKG_VERSION") },
+        }),
+        "ping" => json!({}),
+        "tools/list" => json!({ "tools": tool_list() }),
+        "tools/call" => {
+            let name = params.get("name").and_then(Value::as_str).unwrap_or_default();
+            let args = params.get("arguments").cloned().unwrap_or(Value::Null);
+            let (text, is_error) = match call_tool(name, &args, ctx) {
+                Ok(text) => (text, false),
+                Err(text) => (text, true),
+            };
+            json!({ "content": [{ "type": "text", "text": text }], "isError": is_error })
+        }
+        _ => return Some(rpc_error(id, -32601, &format!("Method not found: {}", method))),
+    };
+    Some(json!({ "jsonrpc": "2.0", "id": id, "result": result }))
+}
+
+fn tool_list() -> Value {
+    json!([
+        {
+            "name": "start_dictation",
+            "description": "Start recording from the microphone, as if push-to-talk were pressed. \
+                With `seconds`, record that long, stop, and return the transcript. The text is also \
+                typed into the focused app if typing is enabled in Typeswift.",
+            "inputSchema": {
+                "type": "object",
+                "properties": { "seconds": { "type": "number", "description": "Record this long, then stop" } },
+            },
+        },
+        {
+            "name": "stop_dictation",
+            "description": "Stop the recording in progress and return its transcript.",
+            "inputSchema": { "type": "object", "properties": {} },
+        },
+        {
+            "name": "get_last_transcript",
+            "description": "The text of the most recent dictation.",
+            "inputSchema": {
+                "type": "object",
+                "properties": {
+                    "raw": { "type": "boolean", "description": "What the model heard, before post-processing" },
+                },
+            },
+        },
+        {
+            "name": "transcribe_file",
+            "description": "Transcribe a WAV file on this Mac and return the text. Nothing is typed.",
+            "inputSchema": {
+                "type": "object",
+                "properties": { "path": { "type": "string", "description": "Absolute path to a .wav file" } },
+                "required": ["path"],
+            },
+        },
+    ])
+}
+
+fn call_tool(name: &str, args: &Value, ctx: &McpContext) -> Result<String, String> {
+    match name {
+        "start_dictation" => {
+            if ctx.state.get_recording_state() != RecordingState::Idle {
+                return Err("Typeswift is already recording or transcribing.".to_string());
+            }
+            let seconds = args.get("seconds").and_then(Value::as_f64);
+            let rev = ctx.state.output_rev();
+            send(ctx, HotkeyEvent::StartDictation)?;
+            match seconds {
+                None => Ok("Recording started. Call stop_dictation to finish.".to_string()),
+                Some(seconds) => {
+                    std::thread::sleep(Duration::from_secs_f64(seconds.clamp(0.0, MAX_DICTATION_SECS)));
+                    send(ctx, HotkeyEvent::StopDictation)?;
+                    wait_for_transcript(ctx, rev)
+                }
+            }
+        }
+        "stop_dictation" => {
+            if ctx.state.get_recording_state() != RecordingState::Recording {
+                return Err("Typeswift is not recording.".to_string());
+            }
+            let rev = ctx.state.output_rev();
+            send(ctx, HotkeyEvent::StopDictation)?;
+            wait_for_transcript(ctx, rev)
+        }
+        "get_last_transcript" => {
+            let text = match args.get("raw").and_then(Value::as_bool) {
+                Some(true) => ctx.state.last_raw_output(),
+                _ => ctx.state.last_output(),
+            };
+            text.ok_or_else(|| "Nothing transcribed yet.".to_string())
+        }
+        "transcribe_file" => {
+            let path = args.get("path").and_then(Value::as_str).ok_or("`path` is required")?;
+            let transcript = ctx
+                .audio_processor
+                .lock()
+                .map_err(|_| "Audio processor unavailable".to_string())?
+                .transcribe_file(Path::new(path))
+                .map_err(|e| e.to_string())?;
+            Ok(post_process(&transcript.text, &ctx.config.read(), None, None))
+        }
+        _ => Err(format!("Unknown tool: {}", name)),
+    }
+}
+
+fn send(ctx: &McpContext, event: HotkeyEvent) -> Result<(), String> {
+    ctx.events.send(event).map_err(|_| "Typeswift is shutting down".to_string())
+}
+
+/// Wait for the recording stopped after `rev` to be transcribed
+fn wait_for_transcript(ctx: &McpContext, rev: u64) -> Result<String, String> {
+    let started = Instant::now();
+    // Give the controller a moment to pick up the stop before watching for Idle
+    std::thread::sleep(Duration::from_millis(100));
+    while started.elapsed() < FINISH_TIMEOUT {
+        if ctx.state.output_rev() != rev {
+            return Ok(ctx.state.last_output().unwrap_or_default());
+        }
+        if ctx.state.get_recording_state() == RecordingState::Idle {
+            return Err("Nothing was transcribed.".to_string());
+        }
+        std::thread::sleep(Duration::from_millis(50));
+    }
+    Err("Timed out waiting for the transcript.".to_string())
+}
@@ -0,0 +1,190 @@
+//! Meeting capture: when a meeting on the user's calendar starts, or a video call is
+//! detected, offer to record it and save the transcript to a file named after it.
+
+use crate::config::MeetingsConfig;
+use crate::error::{VoicyError, VoicyResult};
+use crate::history::{format_timestamp, now_secs};
+use crate::input::HotkeyEvent;
+use crate::platform::macos::ffi as menubar_ffi;
+use crate::services::tracks::Speaker;
+use crate::state::{AppStateManager, OutputOverride, RecordingState};
+use crossbeam_channel::Sender;
+use std::collections::HashSet;
+use std::path::PathBuf;
+use std::time::Duration;
+use tracing::{info, warn};
+
+const POLL_INTERVAL: Duration = Duration::from_secs(20);
+const CALL_POLL_INTERVAL: Duration = Duration::from_secs(3);
+/// A meeting is offered from its start time until this far in (or its end, if sooner)
+const PROMPT_WINDOW_SECS: f64 = 300.0;
+
+/// What the watcher needs from the running app
+#[derive(Clone)]
+pub struct MeetingsContext {
+    pub events: Sender<HotkeyEvent>,
+    pub state: AppStateManager,
+}
+
+/// Start the calendar and call watchers enabled in `[meetings]`, on background threads
+pub fn start(config: &MeetingsConfig, ctx: MeetingsContext) {
+    if config.suggest_calls {
+        spawn_call_watch(ctx.state.clone());
+    }
+    if config.calendar_prompt {
+        spawn_calendar_watch(config.folder_path(), ctx);
+    }
+}
+
+fn spawn_calendar_watch(folder: Option<PathBuf>, ctx: MeetingsContext) {
+    std::thread::spawn(move || {
+        if !menubar_ffi::calendar_request_access() {
+            warn!("Calendar access denied; meeting prompts are off");
+            return;
+        }
+        info!("Watching the calendar for meetings");
+        let mut prompted: HashSet<String> = HashSet::new();
+        loop {
+            let now = now_secs() as f64;
+            let events = menubar_ffi::calendar_events(PROMPT_WINDOW_SECS);
+            // Forget meetings that have dropped out of the window
+            prompted.retain(|id| events.iter().any(|e| &e.id == id));
+            for event in events {
+                let started = event.start <= now && now < (event.start + PROMPT_WINDOW_SECS).min(event.end);
+                if !started || !prompted.insert(event.id.clone()) {
+                    continue;
+                }
+                if ctx.state.get_recording_state() != RecordingState::Idle {
+                    info!("Meeting \"{}\" started while busy; not prompting", event.title);
+                    continue;
+                }
+                let title = if event.title.trim().is_empty() { "Meeting".to_string() } else { event.title.clone() };
+                let destination = folder.as_ref().map_or_else(String::new, |f| format!(" to {}", f.display()));
+                let accepted = menubar_ffi::MenuBarController::confirm(
+                    &format!("Start transcribing “{}”?", title),
+                    &format!(
+                        "Typeswift will record until you press push-to-talk again, then save the transcript{}.",
+                        destination
+                    ),
+                    "Transcribe",
+                );
+                // The dialog may have been open for a while
+                if accepted && ctx.state.get_recording_state() == RecordingState::Idle {
+                    info!("Transcribing meeting \"{}\"", title);
+                    ctx.state.set_output_override(OutputOverride::Meeting(title));
+                    if ctx.events.send(HotkeyEvent::StartDictation).is_err() {
+                        ctx.state.set_output_override(OutputOverride::None);
+                        return;
+                    }
+                }
+            }
+            std::thread::sleep(POLL_INTERVAL);
+        }
+    });
+}
+
+/// Conferencing apps by bundle identifier, with the name shown in the menu
+const CALL_APPS: &[(&str, &str)] = &[
+    ("us.zoom.xos", "Zoom"),
+    ("com.microsoft.teams2", "Teams"),
+    ("com.microsoft.teams", "Teams"),
+    ("com.cisco.webexmeetingsapp", "Webex"),
+    ("Cisco-Systems.Spark", "Webex"),
+    ("com.apple.FaceTime", "FaceTime"),
+    ("com.tinyspeck.slackmacgap", "Slack"),
+    ("com.hnc.Discord", "Discord"),
+];
+
+/// Browsers checked for a Google Meet tab
+const BROWSERS: &[&str] = &[
+    "com.google.Chrome",
+    "com.apple.Safari",
+    "company.thebrowser.Browser",
+    "com.microsoft.edgemac",
+    "com.brave.Browser",
+    "org.mozilla.firefox",
+];
+
+/// The conferencing app in front, if any
+fn frontmost_call_app() -> Option<&'static str> {
+    let bundle_id = menubar_ffi::frontmost_bundle_id()?;
+    if let Some((_, name)) = CALL_APPS.iter().find(|(id, _)| id.eq_ignore_ascii_case(&bundle_id)) {
+        return Some(*name);
+    }
+    if BROWSERS.contains(&bundle_id.as_str()) {
+        // Meet titles its tab "Meet - abc-defg-hij" during a call
+        let title = menubar_ffi::frontmost_window_title()?;
+        if title.starts_with("Meet - ") || title.starts_with("Meet – ") {
+            return Some("Google Meet");
+        }
+    }
+    None
+}
+
+/// Offer call transcription in the menu bar while a conferencing app is in a call:
+/// it has been frontmost while something holds the microphone
+fn spawn_call_watch(state: AppStateManager) {
+    std::thread::spawn(move || {
+        let mut call: Option<&'static str> = None;
+        let mut shown: Option<String> = None;
+        loop {
+            // While Typeswift records, the microphone is in use either way; keep what we had
+            if state.get_recording_state() == RecordingState::Idle {
+                let detected = if menubar_ffi::input_in_use() { frontmost_call_app().or(call) } else { None };
+                if let (Some(app), None) = (detected, call) {
+                    info!("{} call detected", app);
+                    menubar_ffi::MenuBarController::show_notification(
+                        &format!("{} call detected", app),
+                        &format!("Choose “Transcribe {} Call” in the Typeswift menu to transcribe it.", app),
+                    );
+                }
+                call = detected;
+            }
+            let transcribing = state.get_recording_state() == RecordingState::Recording
+                && matches!(state.output_override(), OutputOverride::Meeting(_));
+            let label = call.map(|app| {
+                if transcribing {
+                    format!("Stop Transcribing {} Call", app)
+                } else {
+                    format!("Transcribe {} Call", app)
+                }
+            });
+            if label != shown {
+                state.set_call_suggestion(call.map(|app| format!("{} call", app)));
+                menubar_ffi::MenuBarController::set_call_suggestion(label.as_deref());
+                shown = label;
+            }
+            std::thread::sleep(CALL_POLL_INTERVAL);
+        }
+    });
+}
+
+/// A meeting transcript with one paragraph per turn, each labelled "Me" or "Others" and run
+/// through `post_process` on its own
+pub fn speaker_transcript(segments: &[(Speaker, String)], post_process: impl Fn(&str) -> String) -> String {
+    segments
+        .iter()
+        .map(|(speaker, raw)| (speaker, post_process(raw)))
+        .filter(|(_, text)| !text.trim().is_empty())
+        .map(|(speaker, text)| format!("**{}:** {}", speaker.label(), text.trim()))
+        .collect::<Vec<_>>()
+        .join("\n\n")
+}
+
+/// Write a meeting transcript to "<date> <time> <title>.md" in the meetings folder,
+/// with `markers` (a Markdown list, see `history::format_markers`) after it
+pub fn save_transcript(config: &MeetingsConfig, title: &str, text: &str, markers: &str) -> VoicyResult<PathBuf> {
+    let err = |msg: String| VoicyError::OutputFailed(format!("Meeting transcript: {}", msg));
+    let folder = config.folder_path().ok_or_else(|| err("HOME not set".to_string()))?;
+    std::fs::create_dir_all(&folder).map_err(|e| err(format!("{}: {}", folder.display(), e)))?;
+    let stamp = format_timestamp(now_secs());
+    // Characters Finder or the filesystem won't take in a name
+    let name: String = title.chars().map(|c| if matches!(c, '/' | ':' | '\0') { '-' } else { c }).collect();
+    let path = folder.join(format!("{} {}.md", stamp.replace(':', "."), name.trim()));
+    let mut contents = format!("# {}\n\n{}\n\n{}\n", title, stamp, text);
+    if !markers.is_empty() {
+        contents.push_str(&format!("\n## Markers\n\n{}\n", markers));
+    }
+    std::fs::write(&path, contents).map_err(|e| err(format!("{}: {}", path.display(), e)))?;
+    Ok(path)
+}
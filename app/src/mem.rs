@@ -1,5 +1,9 @@
 // macOS-only: get current process resident memory (RSS) in MB.
 // Uses Mach task_info with MACH_TASK_BASIC_INFO flavor.
+// Also: a pool of reusable f32 buffers for the audio thread.
+
+use crossbeam_channel::{Receiver, Sender};
+use std::ops::{Deref, DerefMut};
 
 #[allow(non_camel_case_types)]
 type natural_t = u32;
@@ -68,3 +72,160 @@ pub fn current_rss_mb() -> Option<f64> {
     }
 }
 
+// ===== Reusable sample buffers =====
+
+/// A few preallocated f32 buffers handed out and returned without locking (the free ones sit
+/// in a bounded crossbeam channel), so the audio callback and resampler reuse memory instead
+/// of allocating on the real-time thread. If every buffer is out, `get` allocates a new one.
+pub struct BufferPool {
+    free_tx: Sender<Vec<f32>>,
+    free_rx: Receiver<Vec<f32>>,
+    capacity: usize,
+}
+
+impl BufferPool {
+    /// `buffers` buffers of `capacity` samples each
+    pub fn new(buffers: usize, capacity: usize) -> Self {
+        let (free_tx, free_rx) = crossbeam_channel::bounded(buffers);
+        for _ in 0..buffers {
+            let _ = free_tx.try_send(Vec::with_capacity(capacity));
+        }
+        Self { free_tx, free_rx, capacity }
+    }
+
+    /// An empty buffer, back in the pool when dropped
+    pub fn get(&self) -> PooledBuffer<'_> {
+        let buffer = self.free_rx.try_recv().unwrap_or_else(|_| Vec::with_capacity(self.capacity));
+        PooledBuffer { buffer, pool: self }
+    }
+}
+
+pub struct PooledBuffer<'a> {
+    buffer: Vec<f32>,
+    pool: &'a BufferPool,
+}
+
+impl Deref for PooledBuffer<'_> {
+    type Target = Vec<f32>;
+
+    fn deref(&self) -> &Vec<f32> {
+        &self.buffer
+    }
+}
+
+impl DerefMut for PooledBuffer<'_> {
+    fn deref_mut(&mut self) -> &mut Vec<f32> {
+        &mut self.buffer
+    }
+}
+
+impl Drop for PooledBuffer<'_> {
+    fn drop(&mut self) {
+        let mut buffer = std::mem::take(&mut self.buffer);
+        buffer.clear();
+        // Keeps whatever capacity it grew to; a surplus buffer (pool full) is freed
+        let _ = self.pool.free_tx.try_send(buffer);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::services::audio::CaptureCallback;
+    use parking_lot::Mutex;
+    use ringbuf::{traits::*, HeapRb};
+    use std::alloc::{GlobalAlloc, Layout, System};
+    use std::cell::Cell;
+    use std::sync::atomic::AtomicU32;
+    use std::sync::Arc;
+
+    /// Counts allocations made on threads that asked to be counted, so tests running in
+    /// parallel don't show up in each other's numbers
+    struct CountingAllocator;
+
+    thread_local! {
+        static COUNTING: Cell<bool> = const { Cell::new(false) };
+        static ALLOCATIONS: Cell<usize> = const { Cell::new(0) };
+    }
+
+    fn count() {
+        let _ = COUNTING.try_with(|counting| {
+            if counting.get() {
+                ALLOCATIONS.with(|n| n.set(n.get() + 1));
+            }
+        });
+    }
+
+    unsafe impl GlobalAlloc for CountingAllocator {
+        unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+            count();
+            System.alloc(layout)
+        }
+
+        unsafe fn alloc_zeroed(&self, layout: Layout) -> *mut u8 {
+            count();
+            System.alloc_zeroed(layout)
+        }
+
+        unsafe fn realloc(&self, ptr: *mut u8, layout: Layout, new_size: usize) -> *mut u8 {
+            count();
+            System.realloc(ptr, layout, new_size)
+        }
+
+        unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+            System.dealloc(ptr, layout)
+        }
+    }
+
+    #[global_allocator]
+    static ALLOCATOR: CountingAllocator = CountingAllocator;
+
+    /// Allocations `f` makes on this thread
+    fn allocations_in(f: impl FnOnce()) -> usize {
+        ALLOCATIONS.with(|n| n.set(0));
+        COUNTING.with(|counting| counting.set(true));
+        f();
+        COUNTING.with(|counting| counting.set(false));
+        ALLOCATIONS.with(Cell::get)
+    }
+
+    /// Run the capture callback over a second of device audio in cpal-sized buffers
+    fn capture_allocations(channels: usize, device_rate: u32) -> usize {
+        let (producer, consumer) = HeapRb::<f32>::new(device_rate as usize * 2).split();
+        let producer = Arc::new(Mutex::new(producer));
+        let gain = Arc::new(AtomicU32::new(0.5f32.to_bits()));
+        let mut callback = CaptureCallback::new(channels, device_rate, 16000, &producer, &gain).unwrap();
+        let frames = 512;
+        let data: Vec<f32> = (0..frames * channels).map(|i| (i as f32 * 0.01).sin()).collect();
+
+        let allocations = allocations_in(|| {
+            for _ in 0..device_rate as usize / frames {
+                callback.process(&data);
+            }
+        });
+        assert!(consumer.occupied_len() > 0, "nothing captured");
+        allocations
+    }
+
+    #[test]
+    fn pool_reuses_buffers() {
+        let pool = BufferPool::new(2, 1024);
+        let allocations = allocations_in(|| {
+            for _ in 0..100 {
+                let mut a = pool.get();
+                let mut b = pool.get();
+                a.extend_from_slice(&[0.0; 1024]);
+                b.push(1.0);
+            }
+        });
+        assert_eq!(allocations, 0);
+    }
+
+    #[test]
+    fn capture_callback_does_not_allocate() {
+        assert_eq!(capture_allocations(1, 16000), 0, "mono, no resampling");
+        assert_eq!(capture_allocations(2, 16000), 0, "stereo, no resampling");
+        assert_eq!(capture_allocations(1, 48000), 0, "mono, resampled");
+        assert_eq!(capture_allocations(2, 44100), 0, "stereo, resampled");
+    }
+}
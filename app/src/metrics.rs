@@ -0,0 +1,86 @@
+//! Counters and histograms for `GET /metrics` on the local HTTP server, in the Prometheus text
+//! format, so people running Typeswift all day can graph it. Kept in process memory only;
+//! they start from zero at each launch.
+
+use once_cell::sync::Lazy;
+use parking_lot::Mutex;
+use std::fmt::Write;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::Duration;
+
+/// Upper bounds (seconds) of the latency buckets, from release to text handed to the output
+const LATENCY_BUCKETS: &[f64] = &[0.1, 0.25, 0.5, 1.0, 2.0, 5.0, 10.0, 30.0];
+
+static METRICS: Lazy<Metrics> = Lazy::new(Metrics::default);
+
+#[derive(Default)]
+struct Metrics {
+    utterances: AtomicU64,
+    typing_failures: AtomicU64,
+    /// Written from the audio callback, so only ever an atomic add
+    overflowed_samples: AtomicU64,
+    latency: Mutex<Histogram>,
+}
+
+#[derive(Default)]
+struct Histogram {
+    /// Count per bucket of `LATENCY_BUCKETS` (not cumulative; summed when rendered)
+    buckets: [u64; LATENCY_BUCKETS.len()],
+    count: u64,
+    sum: f64,
+}
+
+/// A dictation finished and was handed to its output, `latency` after push-to-talk was released
+pub fn record_utterance(latency: Duration) {
+    METRICS.utterances.fetch_add(1, Ordering::Relaxed);
+    let seconds = latency.as_secs_f64();
+    let mut histogram = METRICS.latency.lock();
+    if let Some(bucket) = LATENCY_BUCKETS.iter().position(|bound| seconds <= *bound) {
+        histogram.buckets[bucket] += 1;
+    }
+    histogram.count += 1;
+    histogram.sum += seconds;
+}
+
+/// A typing operation failed after its retries
+pub fn record_typing_failure() {
+    METRICS.typing_failures.fetch_add(1, Ordering::Relaxed);
+}
+
+/// The capture buffer was full and a sample was dropped
+pub fn record_overflow() {
+    METRICS.overflowed_samples.fetch_add(1, Ordering::Relaxed);
+}
+
+/// Everything in the Prometheus text exposition format (version 0.0.4)
+pub fn render() -> String {
+    let mut out = String::new();
+    let counters = [
+        ("typeswift_utterances_total", "Dictations transcribed and delivered", &METRICS.utterances),
+        ("typeswift_typing_failures_total", "Typing operations that failed after retries", &METRICS.typing_failures),
+        (
+            "typeswift_audio_overflow_samples_total",
+            "Audio samples dropped because the capture buffer was full",
+            &METRICS.overflowed_samples,
+        ),
+    ];
+    for (name, help, value) in counters {
+        let _ = writeln!(out, "# HELP {} {}", name, help);
+        let _ = writeln!(out, "# TYPE {} counter", name);
+        let _ = writeln!(out, "{} {}", name, value.load(Ordering::Relaxed));
+    }
+
+    let name = "typeswift_utterance_latency_seconds";
+    let histogram = METRICS.latency.lock();
+    let _ = writeln!(out, "# HELP {} Time from releasing push-to-talk to the text being handed to its output", name);
+    let _ = writeln!(out, "# TYPE {} histogram", name);
+    let mut cumulative = 0;
+    for (bound, count) in LATENCY_BUCKETS.iter().zip(histogram.buckets) {
+        cumulative += count;
+        let _ = writeln!(out, "{}_bucket{{le=\"{}\"}} {}", name, bound, cumulative);
+    }
+    let _ = writeln!(out, "{}_bucket{{le=\"+Inf\"}} {}", name, histogram.count);
+    let _ = writeln!(out, "{}_sum {}", name, histogram.sum);
+    let _ = writeln!(out, "{}_count {}", name, histogram.count);
+    out
+}
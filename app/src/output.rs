@@ -1,39 +1,126 @@
+use crate::config::InputMethodTyping;
 use crate::error::{VoicyError, VoicyResult};
+use crate::platform::macos::ffi as menubar_ffi;
+use crate::services::commands::{Key, KeyChord, Modifier};
 use enigo::{Enigo, Keyboard, Settings};
 use std::sync::mpsc::{self, Receiver, Sender};
 use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
 use std::thread;
-use std::time::Duration;
+use std::time::{Duration, Instant};
 use tracing::{info, warn, error, debug};
 
+/// How hard to retry typing, and when to give up and fall back to the clipboard
+#[derive(Debug, Clone, Copy)]
+pub struct TypingPolicy {
+    /// Extra attempts per operation
+    pub retries: u32,
+    /// Consecutive failed operations before warning the user
+    pub max_consecutive_failures: u32,
+    /// Typing speed limit across all operations, in characters per second (0 = unlimited).
+    /// Up to a second's worth goes out at once, so ordinary dictations aren't slowed down.
+    /// Pastes are a single event and aren't limited.
+    pub max_chars_per_second: u32,
+    /// What to do when the target app has an input method (CJK) active
+    pub input_method: InputMethodTyping,
+}
+
+impl Default for TypingPolicy {
+    fn default() -> Self {
+        Self {
+            retries: 2,
+            max_consecutive_failures: 5,
+            max_chars_per_second: 400,
+            input_method: InputMethodTyping::Paste,
+        }
+    }
+}
+
+/// Characters handed to Enigo at a time, so the rate limit and aborts take effect mid-text
+const TYPING_CHUNK_CHARS: usize = 16;
+
+/// Token bucket shared by every typing operation on the worker
+struct RateLimiter {
+    rate: f64,
+    tokens: f64,
+    refilled_at: Instant,
+}
+
+impl RateLimiter {
+    fn new(max_chars_per_second: u32) -> Self {
+        let rate = max_chars_per_second as f64;
+        Self { rate, tokens: rate, refilled_at: Instant::now() }
+    }
+
+    /// Block until `chars` more characters may be typed
+    fn acquire(&mut self, chars: usize) {
+        if self.rate <= 0.0 {
+            return;
+        }
+        let now = Instant::now();
+        self.tokens = (self.tokens + now.duration_since(self.refilled_at).as_secs_f64() * self.rate).min(self.rate);
+        self.refilled_at = now;
+        self.tokens -= chars as f64;
+        if self.tokens < 0.0 {
+            thread::sleep(Duration::from_secs_f64(-self.tokens / self.rate));
+        }
+    }
+}
+
+/// Byte offsets splitting `text` into pieces of at most `TYPING_CHUNK_CHARS` characters
+fn chunk_starts(text: &str) -> Vec<usize> {
+    text.char_indices().step_by(TYPING_CHUNK_CHARS).map(|(i, _)| i).collect()
+}
+
 /// Optimized typing system with single worker thread
 pub struct TypingQueue {
     sender: Option<Sender<TypingCommand>>,
     worker_handle: Option<thread::JoinHandle<()>>,
     use_worker_thread: bool,
+    // Bumped by `abort`; the worker stops typing when it sees a new value
+    abort_generation: Arc<AtomicU64>,
 }
 
 #[derive(Debug)]
 enum TypingCommand {
     Type { op_id: u64, text: String, add_space: bool },
+    /// Erase `backspaces` characters, then type `text` (streaming corrections)
+    Edit { op_id: u64, backspaces: usize, text: String },
+    /// Press a key with modifiers held (spoken "press …" commands)
+    Keys { op_id: u64, chord: KeyChord },
+    /// Marks the end of the operations cancelled by an `abort`
+    Abort { generation: u64 },
+    Shutdown,
+}
+
+/// What the worker does after finding an abort: stop, or carry on with the next command
+enum AfterAbort {
+    Continue,
     Shutdown,
 }
 
 impl TypingQueue {
     pub fn new(use_worker_thread: bool) -> Self {
-        info!("TypingQueue init: worker_thread={}", use_worker_thread);
+        Self::with_policy(use_worker_thread, TypingPolicy::default())
+    }
+
+    pub fn with_policy(use_worker_thread: bool, policy: TypingPolicy) -> Self {
+        info!("TypingQueue init: worker_thread={}, policy={:?}", use_worker_thread, policy);
+        let abort_generation = Arc::new(AtomicU64::new(0));
         if use_worker_thread {
             // Worker thread mode: use a single background worker instead of spawning per-operation
             let (sender, receiver) = mpsc::channel();
             
+            let generation = Arc::clone(&abort_generation);
             let worker_handle = thread::spawn(move || {
-                Self::worker_loop(receiver);
+                Self::worker_loop(receiver, policy, generation);
             });
             
             Self {
                 sender: Some(sender),
                 worker_handle: Some(worker_handle),
                 use_worker_thread,
+                abort_generation,
             }
         } else {
             // Main thread mode: no worker needed
@@ -41,15 +128,19 @@ impl TypingQueue {
                 sender: None,
                 worker_handle: None,
                 use_worker_thread,
+                abort_generation,
             }
         }
     }
     
-    fn worker_loop(receiver: Receiver<TypingCommand>) {
+    fn worker_loop(receiver: Receiver<TypingCommand>, policy: TypingPolicy, abort_generation: Arc<AtomicU64>) {
         info!("Typing worker started");
-        // Track consecutive failures for diagnostics
+        // Track consecutive failures; past the limit, text goes to the clipboard and the menu warns
         let mut consecutive_failures = 0u32;
-        const MAX_CONSECUTIVE_FAILURES: u32 = 5;
+        let mut limiter = RateLimiter::new(policy.max_chars_per_second);
+        // Last abort the worker has dealt with
+        let mut handled_generation = 0u64;
+        let aborted = |handled: u64| abort_generation.load(Ordering::SeqCst) != handled;
 
         while let Ok(command) = receiver.recv() {
             match command {
@@ -68,28 +159,111 @@ impl TypingQueue {
                         }
                         Err(e) => {
                             error!("Failed to initialize Enigo (op_id={}): {}", op_id, e);
-                            consecutive_failures = consecutive_failures.saturating_add(1);
-                            if consecutive_failures >= MAX_CONSECUTIVE_FAILURES {
-                                warn!("Repeated typing failures ({})", consecutive_failures);
-                            }
+                            Self::record_failure(&mut consecutive_failures, &policy, &text);
                             continue;
                         }
                     };
 
-                    let success = Self::type_with_retry(&mut enigo, &text, add_space);
+                    let mut success = true;
+                    let mut unsent = None;
+                    let input_method = Self::input_method(&policy);
+                    if input_method == Some(InputMethodTyping::Paste) {
+                        // One atomic event, so it isn't rate limited; an abort queued before it skips it whole
+                        if aborted(handled_generation) {
+                            unsent = Some(text.as_str());
+                        } else {
+                            let pasted = if add_space { format!(" {}", text) } else { text.clone() };
+                            success = menubar_ffi::paste_text(&pasted);
+                            if !success {
+                                unsent = Some(text.as_str());
+                            }
+                        }
+                    } else {
+                        let restore = input_method.and_then(|_| menubar_ffi::select_ascii_input_source());
+                        let starts = chunk_starts(&text);
+                        for (i, &start) in starts.iter().enumerate() {
+                            if aborted(handled_generation) {
+                                unsent = Some(&text[start..]);
+                                break;
+                            }
+                            let chunk = &text[start..starts.get(i + 1).copied().unwrap_or(text.len())];
+                            limiter.acquire(chunk.chars().count());
+                            if !Self::type_with_retry(&mut enigo, chunk, add_space && i == 0, policy.retries) {
+                                success = false;
+                                unsent = Some(&text[start..]);
+                                break;
+                            }
+                        }
+                        if text.is_empty() && add_space && !aborted(handled_generation) {
+                            success = Self::type_with_retry(&mut enigo, "", true, policy.retries);
+                        }
+                        Self::restore_input_source(restore);
+                    }
                     debug!("op_id={} typing result: {}", op_id, success);
-                    if success {
+                    if aborted(handled_generation) {
+                        let rest = unsent.map(str::to_string).unwrap_or_default();
+                        handled_generation = abort_generation.load(Ordering::SeqCst);
+                        if let AfterAbort::Shutdown = Self::flush_aborted(&receiver, rest, handled_generation) {
+                            break;
+                        }
+                    } else if success {
                         info!("op_id={} typing complete", op_id);
-                    }
-                    if success {
+                        if consecutive_failures >= policy.max_consecutive_failures {
+                            info!("Typing recovered");
+                            menubar_ffi::MenuBarController::set_typing_health(true);
+                        }
                         consecutive_failures = 0;
                     } else {
-                        consecutive_failures = consecutive_failures.saturating_add(1);
-                        if consecutive_failures >= MAX_CONSECUTIVE_FAILURES {
-                            warn!("Repeated typing failures ({})", consecutive_failures);
+                        Self::record_failure(&mut consecutive_failures, &policy, unsent.unwrap_or(&text));
+                    }
+                }
+                TypingCommand::Edit { op_id, backspaces, text } => {
+                    debug!("Typing worker received edit op_id={}, backspaces={}, len={}", op_id, backspaces, text.len());
+                    if aborted(handled_generation) {
+                        handled_generation = abort_generation.load(Ordering::SeqCst);
+                        if let AfterAbort::Shutdown = Self::flush_aborted(&receiver, String::new(), handled_generation) {
+                            break;
                         }
+                        continue;
+                    }
+                    limiter.acquire(backspaces + text.chars().count());
+                    match Enigo::new(&Settings::default()) {
+                        Ok(mut enigo) => {
+                            let input_method = Self::input_method(&policy);
+                            let restore = match input_method {
+                                Some(InputMethodTyping::SwitchLayout) => menubar_ffi::select_ascii_input_source(),
+                                _ => None,
+                            };
+                            let paste = input_method == Some(InputMethodTyping::Paste);
+                            if let Err(e) = Self::apply_edit(&mut enigo, backspaces, &text, paste) {
+                                error!("op_id={} edit failed: {}", op_id, e);
+                            }
+                            Self::restore_input_source(restore);
+                        }
+                        Err(e) => error!("Failed to initialize Enigo (op_id={}): {}", op_id, e),
                     }
                 }
+                TypingCommand::Keys { op_id, chord } => {
+                    if aborted(handled_generation) {
+                        handled_generation = abort_generation.load(Ordering::SeqCst);
+                        if let AfterAbort::Shutdown = Self::flush_aborted(&receiver, String::new(), handled_generation) {
+                            break;
+                        }
+                        continue;
+                    }
+                    match Enigo::new(&Settings::default()) {
+                        Ok(mut enigo) => {
+                            if let Err(e) = Self::press_chord(&mut enigo, &chord) {
+                                error!("op_id={} pressing {} failed: {}", op_id, chord, e);
+                            }
+                        }
+                        Err(e) => error!("Failed to initialize Enigo (op_id={}): {}", op_id, e),
+                    }
+                }
+                TypingCommand::Abort { generation } => {
+                    // Nothing was in flight when the abort came in
+                    handled_generation = handled_generation.max(generation);
+                }
                 TypingCommand::Shutdown => {
                     info!("Typing worker shutting down");
                     break;
@@ -97,12 +271,63 @@ impl TypingQueue {
             }
         }
     }
+
+    /// Drop every operation queued up to the abort marker, putting the text that was
+    /// never typed (`rest` of the interrupted one, then the queued ones) on the clipboard
+    fn flush_aborted(receiver: &Receiver<TypingCommand>, rest: String, generation: u64) -> AfterAbort {
+        let mut unsent = rest;
+        let mut after = AfterAbort::Continue;
+        while let Ok(command) = receiver.try_recv() {
+            match command {
+                TypingCommand::Type { text, add_space, .. } => {
+                    if add_space && !unsent.is_empty() {
+                        unsent.push(' ');
+                    }
+                    unsent.push_str(&text);
+                }
+                TypingCommand::Edit { .. } | TypingCommand::Keys { .. } => {}
+                TypingCommand::Abort { generation: marker } if marker >= generation => break,
+                TypingCommand::Abort { .. } => {}
+                TypingCommand::Shutdown => {
+                    after = AfterAbort::Shutdown;
+                    break;
+                }
+            }
+        }
+        let unsent = unsent.trim();
+        warn!("Typing aborted; {} chars left untyped", unsent.chars().count());
+        if !unsent.is_empty() {
+            menubar_ffi::set_clipboard_text(unsent);
+            menubar_ffi::MenuBarController::show_notification(
+                "Typing stopped",
+                "The rest of the text was copied to the clipboard.",
+            );
+        }
+        after
+    }
     
-    fn type_with_retry(enigo: &mut Enigo, text: &str, add_space: bool) -> bool {
-        const MAX_RETRIES: u32 = 2;
-        
-        for attempt in 0..=MAX_RETRIES {
-            debug!("Typing attempt {}/{} (len={}, add_space={})", attempt + 1, MAX_RETRIES + 1, text.len(), add_space);
+    /// Count a failed operation. The text is put on the clipboard so it isn't lost;
+    /// once failures reach the limit the user is told and the menu shows a warning.
+    fn record_failure(consecutive_failures: &mut u32, policy: &TypingPolicy, text: &str) {
+        *consecutive_failures = consecutive_failures.saturating_add(1);
+        crate::metrics::record_typing_failure();
+        if text.trim().is_empty() {
+            return;
+        }
+        menubar_ffi::set_clipboard_text(text.trim_start());
+        if *consecutive_failures >= policy.max_consecutive_failures {
+            warn!("Repeated typing failures ({}); falling back to clipboard", consecutive_failures);
+            menubar_ffi::MenuBarController::set_typing_health(false);
+            menubar_ffi::MenuBarController::show_notification(
+                "Typing isn't working",
+                "Your text was copied to the clipboard. Check Accessibility permission for Typeswift.",
+            );
+        }
+    }
+
+    fn type_with_retry(enigo: &mut Enigo, text: &str, add_space: bool, max_retries: u32) -> bool {
+        for attempt in 0..=max_retries {
+            debug!("Typing attempt {}/{} (len={}, add_space={})", attempt + 1, max_retries + 1, text.len(), add_space);
             // Add space first if requested, but do not fail the whole operation on space failure
             if add_space {
                 if let Err(e) = enigo.text(" ") {
@@ -126,15 +351,151 @@ impl TypingQueue {
                 return true;
             }
             
-            // Exponential backoff before retry: 10ms, 20ms, 40ms
-            if attempt < MAX_RETRIES {
-                thread::sleep(Duration::from_millis(10 << attempt));
+            // Exponential backoff before retry: 10ms, 20ms, 40ms, ...
+            if attempt < max_retries {
+                thread::sleep(Duration::from_millis(10 << attempt.min(6)));
             }
         }
         
         false
     }
     
+    fn apply_edit(enigo: &mut Enigo, backspaces: usize, text: &str, paste: bool) -> Result<(), enigo::InputError> {
+        for _ in 0..backspaces {
+            enigo.key(enigo::Key::Backspace, enigo::Direction::Click)?;
+        }
+        if text.is_empty() {
+            return Ok(());
+        }
+        if paste {
+            if !menubar_ffi::paste_text(text) {
+                return Err(enigo::InputError::Simulate("paste failed"));
+            }
+            return Ok(());
+        }
+        enigo.text(text)
+    }
+
+    /// Hold the chord's modifiers, click its key, then let go in reverse order
+    fn press_chord(enigo: &mut Enigo, chord: &KeyChord) -> Result<(), enigo::InputError> {
+        let modifiers: Vec<enigo::Key> = chord
+            .modifiers
+            .iter()
+            .map(|modifier| match modifier {
+                Modifier::Command => enigo::Key::Meta,
+                Modifier::Shift => enigo::Key::Shift,
+                Modifier::Option => enigo::Key::Alt,
+                Modifier::Control => enigo::Key::Control,
+            })
+            .collect();
+        let key = match chord.key {
+            Key::Return => enigo::Key::Return,
+            Key::Tab => enigo::Key::Tab,
+            Key::Escape => enigo::Key::Escape,
+            Key::Space => enigo::Key::Space,
+            Key::Backspace => enigo::Key::Backspace,
+            Key::Up => enigo::Key::UpArrow,
+            Key::Down => enigo::Key::DownArrow,
+            Key::Left => enigo::Key::LeftArrow,
+            Key::Right => enigo::Key::RightArrow,
+            Key::Home => enigo::Key::Home,
+            Key::End => enigo::Key::End,
+            Key::PageUp => enigo::Key::PageUp,
+            Key::PageDown => enigo::Key::PageDown,
+            Key::Function(n) => match n {
+                1 => enigo::Key::F1,
+                2 => enigo::Key::F2,
+                3 => enigo::Key::F3,
+                4 => enigo::Key::F4,
+                5 => enigo::Key::F5,
+                6 => enigo::Key::F6,
+                7 => enigo::Key::F7,
+                8 => enigo::Key::F8,
+                9 => enigo::Key::F9,
+                10 => enigo::Key::F10,
+                11 => enigo::Key::F11,
+                _ => enigo::Key::F12,
+            },
+            Key::Char(c) => enigo::Key::Unicode(c),
+        };
+        for modifier in &modifiers {
+            enigo.key(*modifier, enigo::Direction::Press)?;
+        }
+        let result = enigo.key(key, enigo::Direction::Click);
+        for modifier in modifiers.iter().rev() {
+            enigo.key(*modifier, enigo::Direction::Release)?;
+        }
+        result
+    }
+
+    /// How to get past the input method active in the target app, if one is and the policy
+    /// doesn't just type anyway
+    fn input_method(policy: &TypingPolicy) -> Option<InputMethodTyping> {
+        if policy.input_method == InputMethodTyping::Type {
+            return None;
+        }
+        let source = menubar_ffi::active_input_method()?;
+        debug!("Input method {} is active; using {:?}", source, policy.input_method);
+        Some(policy.input_method)
+    }
+
+    /// Put back the input source `select_ascii_input_source` switched away from
+    fn restore_input_source(previous: Option<String>) {
+        if let Some(id) = previous {
+            if !menubar_ffi::select_input_source(&id) {
+                warn!("Couldn't switch back to input source {}", id);
+            }
+        }
+    }
+
+    /// Stop typing right away: the operation in progress is cut short, queued ones are
+    /// dropped, and whatever wasn't typed goes to the clipboard. Only affects the worker.
+    pub fn abort(&self) {
+        let Some(ref sender) = self.sender else { return };
+        let generation = self.abort_generation.fetch_add(1, Ordering::SeqCst) + 1;
+        info!("Aborting typing (generation {})", generation);
+        let _ = sender.send(TypingCommand::Abort { generation });
+    }
+
+    /// Queue a correction: `backspaces` presses of Backspace followed by `text`
+    pub fn queue_edit(&self, backspaces: usize, text: String) -> VoicyResult<()> {
+        if backspaces == 0 && text.is_empty() {
+            return Ok(());
+        }
+        if let Some(ref sender) = self.sender {
+            static NEXT_EDIT_ID: AtomicU64 = AtomicU64::new(1);
+            let op_id = NEXT_EDIT_ID.fetch_add(1, Ordering::Relaxed);
+            debug!("queue_edit op_id={}, backspaces={}, len={}", op_id, backspaces, text.len());
+            sender
+                .send(TypingCommand::Edit { op_id, backspaces, text })
+                .map_err(|e| VoicyError::WindowOperationFailed(
+                    format!("Typing worker disconnected: {}", e)
+                ))?;
+        } else {
+            let mut enigo = Enigo::new(&Settings::default())
+                .map_err(|e| VoicyError::WindowOperationFailed(
+                    format!("Failed to create Enigo: {}", e)
+                ))?;
+            // Without the worker there's no policy to consult; type as the direct path does
+            Self::apply_edit(&mut enigo, backspaces, &text, false)
+                .map_err(|e| VoicyError::WindowOperationFailed(format!("Failed to apply edit: {}", e)))?;
+        }
+        Ok(())
+    }
+
+    /// Queue a key press behind any text still being typed. Needs the worker thread.
+    pub fn queue_keys(&self, chord: KeyChord) -> VoicyResult<()> {
+        let Some(ref sender) = self.sender else {
+            return Err(VoicyError::OutputFailed("key presses need the typing worker".to_string()));
+        };
+        static NEXT_KEYS_ID: AtomicU64 = AtomicU64::new(1);
+        let op_id = NEXT_KEYS_ID.fetch_add(1, Ordering::Relaxed);
+        debug!("queue_keys op_id={}, chord={}", op_id, chord);
+        sender
+            .send(TypingCommand::Keys { op_id, chord })
+            .map_err(|e| VoicyError::WindowOperationFailed(format!("Typing worker disconnected: {}", e)))
+    }
+
     pub fn queue_typing(&self, text: String, add_space: bool) -> VoicyResult<()> {
         // Skip empty operations
         if text.is_empty() && !add_space {
@@ -237,59 +598,25 @@ impl Clone for TypingQueue {
             sender: self.sender.clone(),
             worker_handle: None, // Clones don't own the worker
             use_worker_thread: self.use_worker_thread,
+            abort_generation: Arc::clone(&self.abort_generation),
         }
     }
 }
 
-// Keep diagnostic function for compatibility
-pub fn run_typing_diagnostic() {
-    info!("Running typing diagnostic...");
-    
-    info!("1. Testing Enigo initialization...");
-    match Enigo::new(&Settings::default()) {
-        Ok(mut enigo) => {
-            info!("Enigo initialized successfully");
-            
-            info!("2. Testing basic typing (5-second delay)...");
-            info!("Please switch to a text editor (TextEdit, Notes, etc.)");
-            info!("Typing test will start in 5 seconds...");
-            
-            for i in (1..=5).rev() {
-                info!("{}...", i);
-                thread::sleep(Duration::from_secs(1));
-            }
-            
-            info!("Attempting to type...");
-            
-            match enigo.text("Hello from Typeswift diagnostic test!") {
-                Ok(()) => {
-                    info!("Enigo.text() returned successfully");
-                    warn!("If you don't see text in your editor, it's a permissions issue");
-                }
-                Err(e) => {
-                    error!("Enigo.text() failed with error: {}", e);
-                }
-            }
-            
-            info!("3. Testing individual key simulation...");
-            thread::sleep(Duration::from_millis(500));
-            
-            let test_chars = ['T', 'e', 's', 't'];
-            for ch in test_chars {
-                match enigo.key(enigo::Key::Unicode(ch), enigo::Direction::Click) {
-                    Ok(()) => info!("Key '{}' sent successfully", ch),
-                    Err(e) => error!("Key '{}' failed: {}", ch, e),
-                }
-                thread::sleep(Duration::from_millis(100));
-            }
-        }
-        Err(e) => {
-            error!("Failed to initialize Enigo: {}", e);
-        }
+/// Check that keystrokes reach other apps, for `typeswift doctor`: taps Shift, which types
+/// nothing. With `text`, also types it into whatever has focus. Returns what was done.
+pub fn run_typing_diagnostic(text: Option<&str>) -> Result<String, String> {
+    let mut enigo = Enigo::new(&Settings::default()).map_err(|e| format!("couldn't set up typing: {}", e))?;
+    enigo
+        .key(enigo::Key::Shift, enigo::Direction::Click)
+        .map_err(|e| format!("couldn't send a key press: {}", e))?;
+    // Events from an app without Accessibility are dropped without an error
+    if !menubar_ffi::accessibility_trusted() {
+        return Err("key presses are discarded until Accessibility is granted".to_string());
     }
-    
-    info!("4. System Information:");
-    info!("Platform: macOS");
-    info!("Accessibility permissions required");
-    info!("Diagnostic complete!");
+    let Some(text) = text else {
+        return Ok("sent a Shift key press".to_string());
+    };
+    enigo.text(text).map_err(|e| format!("typing failed: {}", e))?;
+    Ok(format!("typed {:?}; check that it arrived intact", text))
 }
@@ -0,0 +1,156 @@
+//! What the command palette offers: every Typeswift action as a searchable row, so features
+//! don't each need a shortcut of their own. Rows send the same events as the menu bar.
+
+use crate::config::{Config, SUPPORTED_LANGUAGES};
+use crate::input::{HotkeyEvent, QuickSetting};
+use crate::state::{AppStateManager, OutputOverride, RecordingState};
+
+/// One row of the palette
+#[derive(Debug, Clone, PartialEq)]
+pub struct PaletteAction {
+    pub title: String,
+    /// Shown dimmed after the title: the shortcut, or what's current
+    pub detail: String,
+    pub event: HotkeyEvent,
+}
+
+impl PaletteAction {
+    fn new(title: impl Into<String>, event: HotkeyEvent) -> Self {
+        Self { title: title.into(), detail: String::new(), event }
+    }
+
+    fn detail(mut self, detail: impl Into<String>) -> Self {
+        self.detail = detail.into();
+        self
+    }
+
+    /// Whether this row opens one of Typeswift's own windows (so focus should stay with us)
+    pub fn opens_window(&self) -> bool {
+        matches!(self.event, HotkeyEvent::OpenHistory | HotkeyEvent::OpenPreferences)
+    }
+}
+
+/// Every action that makes sense right now, dictation first
+pub fn actions(config: &Config, state: &AppStateManager) -> Vec<PaletteAction> {
+    let mut actions = Vec::new();
+    let meeting = matches!(state.output_override(), OutputOverride::Meeting(_));
+    match state.get_recording_state() {
+        RecordingState::Idle => {
+            actions.push(PaletteAction::new("Start Dictation", HotkeyEvent::StartDictation));
+            actions.push(PaletteAction::new("Dictate to Clipboard", HotkeyEvent::DictateToClipboard));
+            let call = state.call_suggestion().unwrap_or_default();
+            actions.push(PaletteAction::new("Start Meeting Transcript", HotkeyEvent::TranscribeCall).detail(call));
+        }
+        RecordingState::Recording if meeting => {
+            actions.push(PaletteAction::new("Stop Meeting Transcript", HotkeyEvent::TranscribeCall));
+            let shortcut = config.hotkeys.mark_moment.clone().unwrap_or_default();
+            actions.push(PaletteAction::new("Mark Moment", HotkeyEvent::MarkMoment).detail(shortcut));
+        }
+        RecordingState::Recording => {
+            actions.push(PaletteAction::new("Stop Dictation", HotkeyEvent::StopDictation));
+        }
+        RecordingState::Processing => {}
+    }
+
+    let on_off = |on: bool| if on { "on" } else { "off" };
+    actions.push(PaletteAction::new("Open History", HotkeyEvent::OpenHistory));
+    actions.push(
+        PaletteAction::new("Open Preferences", HotkeyEvent::OpenPreferences)
+            .detail(config.hotkeys.preferences.clone().unwrap_or_default()),
+    );
+    actions.push(
+        PaletteAction::new("Toggle Typing", HotkeyEvent::QuickSetting(QuickSetting::ToggleTyping))
+            .detail(on_off(config.output.enable_typing)),
+    );
+    actions.push(
+        PaletteAction::new("Toggle Streaming", HotkeyEvent::QuickSetting(QuickSetting::ToggleStreaming))
+            .detail(on_off(config.streaming.enabled)),
+    );
+    actions.push(
+        PaletteAction::new("Toggle Hands-free", HotkeyEvent::QuickSetting(QuickSetting::ToggleHandsFree))
+            .detail(on_off(config.hotkeys.hands_free)),
+    );
+    actions.push(
+        PaletteAction::new("Toggle Presentation Mode", HotkeyEvent::QuickSetting(QuickSetting::TogglePresentation))
+            .detail(on_off(config.ui.presentation_mode)),
+    );
+    actions.push(
+        PaletteAction::new("Toggle Rehearsal", HotkeyEvent::QuickSetting(QuickSetting::ToggleRehearsal))
+            .detail(on_off(config.output.rehearsal)),
+    );
+
+    let current = |on: bool| if on { "current" } else { "" };
+    if !config.profiles.is_empty() {
+        actions.push(
+            PaletteAction::new("Profile: Automatic (by app)", HotkeyEvent::QuickSetting(QuickSetting::Profile(None)))
+                .detail(current(config.active_profile.is_none())),
+        );
+    }
+    for (i, profile) in config.profiles.iter().enumerate() {
+        let active = config.active_profile.as_deref() == Some(profile.name.as_str());
+        actions.push(
+            PaletteAction::new(
+                format!("Profile: {}", profile.name),
+                HotkeyEvent::QuickSetting(QuickSetting::Profile(Some(i))),
+            )
+            .detail(current(active)),
+        );
+    }
+    actions.push(
+        PaletteAction::new("Language: Auto-detect", HotkeyEvent::QuickSetting(QuickSetting::Language(None)))
+            .detail(current(config.model.language.is_none())),
+    );
+    for (i, (code, name)) in SUPPORTED_LANGUAGES.iter().enumerate() {
+        let active = config.model.language.as_deref() == Some(*code);
+        actions.push(
+            PaletteAction::new(format!("Language: {}", name), HotkeyEvent::QuickSetting(QuickSetting::Language(Some(i))))
+                .detail(current(active)),
+        );
+    }
+
+    for (i, destination) in config.output_destinations().iter().enumerate() {
+        actions.push(
+            PaletteAction::new(
+                format!("Output: {}", destination.label()),
+                HotkeyEvent::QuickSetting(QuickSetting::Output(i)),
+            )
+            .detail(current(*destination == config.output.destination)),
+        );
+    }
+    actions.push(
+        PaletteAction::new("Next Output", HotkeyEvent::QuickSetting(QuickSetting::CycleOutput))
+            .detail(config.hotkeys.cycle_output.clone().unwrap_or_default()),
+    );
+
+    actions.push(
+        PaletteAction::new("Speak Last Result", HotkeyEvent::SpeakLast)
+            .detail(config.hotkeys.speak_last.clone().unwrap_or_default()),
+    );
+    actions.push(
+        PaletteAction::new("Stop Typing", HotkeyEvent::StopTyping)
+            .detail(config.hotkeys.stop_typing.clone().unwrap_or_default()),
+    );
+    actions.push(PaletteAction::new("Reset Popup Position", HotkeyEvent::ResetPopupPosition));
+    actions.push(PaletteAction::new("Generate Support Bundle", HotkeyEvent::GenerateSupportBundle));
+    actions.push(PaletteAction::new("Relaunch Typeswift", HotkeyEvent::Relaunch));
+    actions
+}
+
+/// The actions whose title has every word of `query` in it (any case), those starting with
+/// the query first
+pub fn search(actions: Vec<PaletteAction>, query: &str) -> Vec<PaletteAction> {
+    let query = query.trim().to_lowercase();
+    if query.is_empty() {
+        return actions;
+    }
+    let words: Vec<&str> = query.split_whitespace().collect();
+    let (mut prefixed, rest): (Vec<_>, Vec<_>) = actions
+        .into_iter()
+        .filter(|action| {
+            let title = action.title.to_lowercase();
+            words.iter().all(|word| title.contains(word))
+        })
+        .partition(|action| action.title.to_lowercase().starts_with(&query));
+    prefixed.extend(rest);
+    prefixed
+}
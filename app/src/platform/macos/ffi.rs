@@ -5,18 +5,29 @@ use std::ffi::CString;
 use std::os::raw::{c_char, c_float, c_int};
 
 // ===== Keyboard FFI =====
-use crate::input::HotkeyEvent;
+use crate::input::{HotkeyEvent, QuickSetting};
 
 #[link(name = "TypeswiftSwift")]
 unsafe extern "C" {
     fn swift_init_keyboard_monitor() -> bool;
     fn swift_shutdown_keyboard_monitor();
-    fn swift_register_push_to_talk_callback(callback: extern "C" fn(bool));
+    fn swift_set_push_to_talk_chord(flags: u64, device_flags: u64);
+    fn swift_set_profile_chords(flags: *const u64, device_flags: *const u64, profiles: *const i32, count: usize);
+    fn swift_set_suppress_system_fn(suppress: bool);
+    fn swift_set_swallowed_key(key_code: i32);
+    fn swift_set_recording_active(active: bool);
+    fn swift_keyboard_tap_status() -> i32;
+    fn swift_reinstall_keyboard_tap() -> bool;
+    fn swift_register_tap_health_callback(callback: extern "C" fn(i32));
+    fn swift_register_push_to_talk_callback(callback: extern "C" fn(bool, i32));
     fn swift_register_preferences_callback(callback: extern "C" fn());
+    fn swift_register_menu_action_callback(callback: extern "C" fn(*const c_char));
 }
 
 static PUSH_TO_TALK_SENDER: Lazy<ParkingMutex<Option<Sender<HotkeyEvent>>>> = Lazy::new(|| ParkingMutex::new(None));
 static PREFERENCES_SENDER: Lazy<ParkingMutex<Option<Sender<HotkeyEvent>>>> = Lazy::new(|| ParkingMutex::new(None));
+static MENU_ACTION_SENDER: Lazy<ParkingMutex<Option<Sender<HotkeyEvent>>>> = Lazy::new(|| ParkingMutex::new(None));
+static TAP_HEALTH_SENDER: Lazy<ParkingMutex<Option<Sender<TapDisabled>>>> = Lazy::new(|| ParkingMutex::new(None));
 
 pub fn init_keyboard_monitor() -> bool {
     unsafe { swift_init_keyboard_monitor() }
@@ -27,6 +38,84 @@ pub fn shutdown_keyboard_monitor() {
     PUSH_TO_TALK_SENDER.lock().take();
 }
 
+/// Set the modifier chord the native monitor treats as push-to-talk
+/// (CGEventFlags device-independent bits plus optional left/right device bits).
+pub fn set_push_to_talk_chord(flags: u64, device_flags: u64) {
+    unsafe { swift_set_push_to_talk_chord(flags, device_flags) }
+}
+
+/// Further chords that act as push-to-talk for a profile: (flags, device flags, profile index).
+/// Their presses arrive as `PushToTalkPressed(Some(profile))`.
+pub fn set_profile_chords(chords: &[(u64, u64, usize)]) {
+    let flags: Vec<u64> = chords.iter().map(|c| c.0).collect();
+    let device_flags: Vec<u64> = chords.iter().map(|c| c.1).collect();
+    let profiles: Vec<i32> = chords.iter().map(|c| c.2 as i32).collect();
+    unsafe { swift_set_profile_chords(flags.as_ptr(), device_flags.as_ptr(), profiles.as_ptr(), chords.len()) }
+}
+
+/// Have the event tap swallow Fn/Globe presses (when Fn alone is push-to-talk) so macOS
+/// doesn't also run its own Fn action
+pub fn set_suppress_system_fn(suppress: bool) {
+    unsafe { swift_set_suppress_system_fn(suppress) }
+}
+
+/// Have the event tap swallow key-downs of `key_code` (a macOS virtual key code) while
+/// recording, so a held push-to-talk key doesn't auto-repeat into the focused app
+pub fn set_swallowed_key(key_code: Option<u16>) {
+    unsafe { swift_set_swallowed_key(key_code.map_or(-1, i32::from)) }
+}
+
+/// Tell the keyboard monitor whether a recording is in progress
+pub fn set_recording_active(active: bool) {
+    unsafe { swift_set_recording_active(active) }
+}
+
+/// State of the native keyboard monitor behind modifier-chord push-to-talk
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TapStatus {
+    /// No modifier chord is bound
+    Off,
+    Enabled,
+    /// macOS switched the event tap off; push-to-talk is dead until it's re-enabled
+    Disabled,
+    /// No event tap (no Accessibility permission at launch); NSEvent monitoring instead
+    Fallback,
+}
+
+pub fn keyboard_tap_status() -> TapStatus {
+    match unsafe { swift_keyboard_tap_status() } {
+        1 => TapStatus::Enabled,
+        2 => TapStatus::Disabled,
+        3 => TapStatus::Fallback,
+        _ => TapStatus::Off,
+    }
+}
+
+/// Recreate the event tap from scratch; false if it couldn't be created
+pub fn reinstall_keyboard_tap() -> bool {
+    unsafe { swift_reinstall_keyboard_tap() }
+}
+
+/// Why macOS disabled the event tap (the monitor re-enables it straight away)
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TapDisabled {
+    /// The callback took too long, e.g. under heavy load
+    Timeout,
+    /// Secure input or a security prompt
+    UserInput,
+}
+
+pub fn register_tap_health_callback(sender: Sender<TapDisabled>) {
+    *TAP_HEALTH_SENDER.lock() = Some(sender);
+    unsafe { swift_register_tap_health_callback(handle_tap_disabled) };
+}
+
+extern "C" fn handle_tap_disabled(reason: i32) {
+    if let Some(ref sender) = *TAP_HEALTH_SENDER.lock() {
+        let _ = sender.send(if reason == 0 { TapDisabled::Timeout } else { TapDisabled::UserInput });
+    }
+}
+
 pub fn register_push_to_talk_callback(sender: Sender<HotkeyEvent>) {
     {
         *PUSH_TO_TALK_SENDER.lock() = Some(sender);
@@ -34,10 +123,11 @@ pub fn register_push_to_talk_callback(sender: Sender<HotkeyEvent>) {
     unsafe { swift_register_push_to_talk_callback(handle_push_to_talk_event) };
 }
 
-extern "C" fn handle_push_to_talk_event(is_pressed: bool) {
+/// `profile` is the profile index of the chord that was pressed, -1 for the main chord
+extern "C" fn handle_push_to_talk_event(is_pressed: bool, profile: i32) {
     if let Some(ref sender) = *PUSH_TO_TALK_SENDER.lock() {
         let event = if is_pressed {
-            HotkeyEvent::PushToTalkPressed
+            HotkeyEvent::PushToTalkPressed(usize::try_from(profile).ok())
         } else {
             HotkeyEvent::PushToTalkReleased
         };
@@ -58,6 +148,155 @@ extern "C" fn handle_open_preferences() {
     }
 }
 
+/// Route named menu bar actions (e.g. "history") to `sender`
+pub fn register_menu_action_callback(sender: Sender<HotkeyEvent>) {
+    {
+        *MENU_ACTION_SENDER.lock() = Some(sender);
+    }
+    unsafe { swift_register_menu_action_callback(handle_menu_action) };
+}
+
+extern "C" fn handle_menu_action(action: *const c_char) {
+    if action.is_null() {
+        return;
+    }
+    let action = unsafe { std::ffi::CStr::from_ptr(action) }.to_string_lossy();
+    post_action(&action);
+}
+
+/// Handle a `typeswift://<action>` link (Raycast/Alfred script commands), e.g.
+/// `typeswift://dictate-to-clipboard` or `typeswift://toggle-hands-free`
+pub fn handle_url(url: &str) {
+    let Some(name) = url.strip_prefix("typeswift://") else {
+        tracing::warn!("Ignoring URL {}", url);
+        return;
+    };
+    let name = name.split(['?', '#']).next().unwrap_or_default().trim_matches('/').replace('-', "_");
+    let action = match name.as_str() {
+        "show_history" => "history",
+        "toggle_typing" => "quick:typing",
+        "toggle_streaming" => "quick:streaming",
+        "toggle_hands_free" => "quick:hands_free",
+        "toggle_presentation" => "quick:presentation",
+        "toggle_rehearsal" => "quick:rehearsal",
+        "cycle_output" => "quick:cycle_output",
+        other => other,
+    };
+    tracing::info!("URL action: {}", action);
+    post_action(action);
+}
+
+/// Send the event for a named action to the menu action receiver
+fn post_action(action: &str) {
+    let event = match action {
+        "history" => HotkeyEvent::OpenHistory,
+        "preferences" => HotkeyEvent::OpenPreferences,
+        "command_palette" => HotkeyEvent::OpenCommandPalette,
+        "dictate_to_clipboard" => HotkeyEvent::DictateToClipboard,
+        "transcribe_call" => HotkeyEvent::TranscribeCall,
+        "relaunch" => HotkeyEvent::Relaunch,
+        "speak_last" => HotkeyEvent::SpeakLast,
+        "stop_typing" => HotkeyEvent::StopTyping,
+        "mark_moment" => HotkeyEvent::MarkMoment,
+        "support_bundle" => HotkeyEvent::GenerateSupportBundle,
+        "reset_popup_position" => HotkeyEvent::ResetPopupPosition,
+        other if other.starts_with("quick:") => match parse_quick_setting(&other["quick:".len()..]) {
+            Some(setting) => HotkeyEvent::QuickSetting(setting),
+            None => {
+                tracing::warn!("Unknown quick setting: {}", other);
+                return;
+            }
+        },
+        other => {
+            tracing::warn!("Unknown menu action: {}", other);
+            return;
+        }
+    };
+    if let Some(ref sender) = *MENU_ACTION_SENDER.lock() {
+        let _ = sender.send(event);
+    }
+}
+
+/// "typing", "streaming", "hands_free", "presentation", "rehearsal", "profile[:index]", "language[:index]",
+/// "output:index" or "cycle_output"
+fn parse_quick_setting(action: &str) -> Option<QuickSetting> {
+    let (name, index) = match action.split_once(':') {
+        Some((name, index)) => (name, Some(index.parse::<usize>().ok()?)),
+        None => (action, None),
+    };
+    match name {
+        "typing" => Some(QuickSetting::ToggleTyping),
+        "streaming" => Some(QuickSetting::ToggleStreaming),
+        "hands_free" => Some(QuickSetting::ToggleHandsFree),
+        "presentation" => Some(QuickSetting::TogglePresentation),
+        "rehearsal" => Some(QuickSetting::ToggleRehearsal),
+        "profile" => Some(QuickSetting::Profile(index)),
+        "language" => Some(QuickSetting::Language(index)),
+        "model" => index.map(QuickSetting::Model),
+        "preset" => index.map(QuickSetting::Preset),
+        "output" => index.map(QuickSetting::Output),
+        "cycle_output" => Some(QuickSetting::CycleOutput),
+        _ => None,
+    }
+}
+
+/// What the menu bar's Quick Settings submenu shows
+#[derive(Debug, Clone, PartialEq, serde::Serialize)]
+pub struct QuickSettings {
+    pub typing: bool,
+    pub streaming: bool,
+    pub hands_free: bool,
+    pub presentation: bool,
+    pub rehearsal: bool,
+    pub profiles: Vec<String>,
+    pub active_profile: Option<usize>,
+    /// (code, name) pairs
+    pub languages: Vec<(String, String)>,
+    pub language: Option<usize>,
+    /// Labels of `installed_models`
+    pub models: Vec<String>,
+    pub model: Option<usize>,
+    /// Labels of `ModelPreset::ALL`
+    pub presets: Vec<String>,
+    pub preset: Option<usize>,
+    /// Labels of `Config::output_destinations`
+    pub outputs: Vec<String>,
+    pub output: usize,
+}
+
+impl QuickSettings {
+    pub fn from_config(config: &crate::config::Config) -> Self {
+        use crate::config::ModelPreset;
+        let languages = crate::config::SUPPORTED_LANGUAGES;
+        let models = crate::services::models::installed_models(&config.model);
+        let outputs = config.output_destinations();
+        Self {
+            typing: config.output.enable_typing,
+            streaming: config.streaming.enabled,
+            hands_free: config.hotkeys.hands_free,
+            presentation: config.ui.presentation_mode,
+            rehearsal: config.output.rehearsal,
+            profiles: config.profiles.iter().map(|p| p.name.clone()).collect(),
+            active_profile: config
+                .active_profile
+                .as_deref()
+                .and_then(|name| config.profiles.iter().position(|p| p.name == name)),
+            languages: languages.iter().map(|(code, name)| (code.to_string(), name.to_string())).collect(),
+            language: config
+                .model
+                .language
+                .as_deref()
+                .and_then(|code| languages.iter().position(|(c, _)| *c == code)),
+            model: models.iter().position(|m| m.is_current(&config.model)),
+            models: models.into_iter().map(|m| m.label).collect(),
+            presets: ModelPreset::ALL.iter().map(|p| p.label().to_string()).collect(),
+            preset: config.model.preset.and_then(|preset| ModelPreset::ALL.iter().position(|p| *p == preset)),
+            output: outputs.iter().position(|d| *d == config.output.destination).unwrap_or(0),
+            outputs: outputs.iter().map(|d| d.label()).collect(),
+        }
+    }
+}
+
 // ===== Menubar FFI =====
 
 unsafe extern "C" {
@@ -66,7 +305,16 @@ unsafe extern "C" {
     fn typeswift_show_dock_icon();
     fn typeswift_set_menu_status(text: *const c_char);
     fn typeswift_show_notification(title: *const c_char, message: *const c_char);
+    fn typeswift_show_alert(title: *const c_char, message: *const c_char);
+    fn typeswift_show_suppressible_alert(title: *const c_char, message: *const c_char) -> bool;
+    fn typeswift_confirm(title: *const c_char, message: *const c_char, confirm_label: *const c_char) -> bool;
+    fn typeswift_choose(title: *const c_char, message: *const c_char, labels: *const c_char) -> c_int;
     fn typeswift_set_recording_state(is_recording: bool);
+    fn typeswift_set_recording_locked(locked: bool);
+    fn typeswift_set_typing_health(ok: bool);
+    fn typeswift_set_quick_settings(json: *const c_char);
+    fn typeswift_set_call_suggestion(title: *const c_char);
+    fn typeswift_set_last_error(title: *const c_char, message: *const c_char);
     fn typeswift_run_app();
     fn typeswift_terminate_app();
     fn typeswift_is_launch_at_login_enabled() -> bool;
@@ -86,21 +334,102 @@ impl MenuBarController {
         unsafe { typeswift_show_dock_icon() }
     }
     pub fn set_status(text: &str) {
+        if is_quiet_for(QuietReason::Presentation) {
+            return;
+        }
         let c_text = CString::new(text).unwrap();
         unsafe { typeswift_set_menu_status(c_text.as_ptr()) }
     }
     pub fn show_notification(title: &str, message: &str) {
+        if is_quiet() {
+            tracing::debug!("Notification suppressed: {}", title);
+            return;
+        }
         let c_title = CString::new(title).unwrap();
         let c_message = CString::new(message).unwrap();
         unsafe { typeswift_show_notification(c_title.as_ptr(), c_message.as_ptr()) }
     }
+    /// Modal warning dialog (shown asynchronously on the main thread)
+    pub fn show_alert(title: &str, message: &str) {
+        let c_title = CString::new(title).unwrap();
+        let c_message = CString::new(message).unwrap();
+        unsafe { typeswift_show_alert(c_title.as_ptr(), c_message.as_ptr()) }
+    }
+    /// Blocking alert with a "Don't warn again" checkbox; returns true if it was ticked.
+    /// Must be called off the main thread.
+    pub fn show_suppressible_alert(title: &str, message: &str) -> bool {
+        let c_title = CString::new(title).unwrap();
+        let c_message = CString::new(message).unwrap();
+        unsafe { typeswift_show_suppressible_alert(c_title.as_ptr(), c_message.as_ptr()) }
+    }
+    /// Blocking two-button dialog; returns true if `confirm_label` was chosen.
+    /// Must be called off the main thread.
+    pub fn confirm(title: &str, message: &str, confirm_label: &str) -> bool {
+        let c_title = CString::new(title).unwrap();
+        let c_message = CString::new(message).unwrap();
+        let c_label = CString::new(confirm_label).unwrap();
+        unsafe { typeswift_confirm(c_title.as_ptr(), c_message.as_ptr(), c_label.as_ptr()) }
+    }
+    /// Blocking dialog with a button per label; returns the index of the one chosen.
+    /// Must be called off the main thread.
+    pub fn choose(title: &str, message: &str, labels: &[&str]) -> usize {
+        let c_title = CString::new(title).unwrap();
+        let c_message = CString::new(message).unwrap();
+        let c_labels = CString::new(labels.join("\n")).unwrap();
+        let index = unsafe { typeswift_choose(c_title.as_ptr(), c_message.as_ptr(), c_labels.as_ptr()) };
+        usize::try_from(index).unwrap_or(0)
+    }
+    /// Recording indicator; in presentation mode only the Caps Lock LED shows it
     pub fn set_recording(is_recording: bool) {
+        if is_quiet_for(QuietReason::Presentation) {
+            set_caps_lock_led(is_recording);
+            return;
+        }
         unsafe { typeswift_set_recording_state(is_recording) }
     }
+    /// Lock badge beside the recording icon; `set_recording(false)` clears it
+    pub fn set_recording_locked(locked: bool) {
+        if is_quiet_for(QuietReason::Presentation) {
+            return;
+        }
+        unsafe { typeswift_set_recording_locked(locked) }
+    }
+    /// Show (false) or hide (true) the "Typing is failing" menu item linking to Accessibility settings
+    pub fn set_typing_health(ok: bool) {
+        unsafe { typeswift_set_typing_health(ok) }
+    }
+    /// Refresh the Quick Settings submenu
+    pub fn set_quick_settings(settings: &QuickSettings) {
+        let Ok(json) = serde_json::to_string(settings) else { return };
+        let c_json = CString::new(json).unwrap();
+        unsafe { typeswift_set_quick_settings(c_json.as_ptr()) }
+    }
+    /// Tell the user about `err`: a notification with what to do about it, also kept in the
+    /// menu bar's "Last error" item (which shows it again in an alert)
+    pub fn report_error(err: &crate::error::VoicyError) {
+        tracing::error!("{} error: {}", err.category().label(), err);
+        let message = format!("{}. {}", err.detail().trim_end_matches('.'), err.remediation()).replace('\0', "");
+        Self::show_notification(err.title(), &message);
+        let c_title = CString::new(err.title()).unwrap();
+        let c_message = CString::new(message).unwrap();
+        unsafe { typeswift_set_last_error(c_title.as_ptr(), c_message.as_ptr()) }
+    }
+    /// Show the menu item suggesting call transcription with `title`, or hide it
+    pub fn set_call_suggestion(title: Option<&str>) {
+        match title {
+            Some(title) => {
+                let c_title = CString::new(title).unwrap();
+                unsafe { typeswift_set_call_suggestion(c_title.as_ptr()) }
+            }
+            None => unsafe { typeswift_set_call_suggestion(std::ptr::null()) },
+        }
+    }
     pub fn run_app() {
         unsafe { typeswift_run_app() }
     }
     pub fn quit() {
+        crate::services::config_store::flush();
+        crate::runtime::shutdown();
         unsafe { typeswift_terminate_app() }
     }
     pub fn is_launch_at_login_enabled() -> bool {
@@ -112,17 +441,496 @@ impl MenuBarController {
 
 }
 
+// ===== System FFI =====
+
+unsafe extern "C" {
+    fn typeswift_set_clipboard_text(text: *const c_char);
+    fn typeswift_paste_text(text: *const c_char) -> bool;
+    fn typeswift_active_input_method() -> *mut c_char;
+    fn typeswift_select_ascii_input_source() -> *mut c_char;
+    fn typeswift_select_input_source(id: *const c_char) -> bool;
+    fn typeswift_focused_text_target() -> c_int;
+    fn typeswift_text_before_cursor(max_chars: c_int) -> *mut c_char;
+    fn typeswift_frontmost_bundle_id() -> *mut c_char;
+    fn typeswift_own_app_frontmost() -> bool;
+    fn typeswift_fn_key_usage() -> c_int;
+    fn typeswift_open_keyboard_settings();
+    fn typeswift_default_input_is_bluetooth() -> c_int;
+    fn typeswift_input_in_use() -> bool;
+    fn typeswift_frontmost_window_title() -> *mut c_char;
+    fn typeswift_activate_app(name: *const c_char) -> bool;
+    fn typeswift_running_app_name(name: *const c_char) -> *mut c_char;
+    fn typeswift_quit_app(name: *const c_char) -> bool;
+    fn typeswift_window_action(action: c_int) -> bool;
+    fn typeswift_system_audio_start(sample_rate: i32) -> bool;
+    fn typeswift_system_audio_read(buffer: *mut f32, max: usize) -> usize;
+    fn typeswift_system_audio_stop();
+    fn typeswift_write_audio_file(
+        samples: *const f32,
+        count: usize,
+        sample_rate: i32,
+        codec: i32,
+        path: *const c_char,
+    ) -> bool;
+    fn typeswift_read_audio_file(path: *const c_char, sample_rate: i32, buffer: *mut f32, max: usize) -> isize;
+    fn typeswift_play_audio_file(path: *const c_char) -> bool;
+    fn typeswift_stop_audio();
+    fn typeswift_compile_coreml_model(source: *const c_char, destination: *const c_char) -> *mut c_char;
+    fn typeswift_check_coreml_model(path: *const c_char) -> *mut c_char;
+    fn typeswift_accessibility_trusted() -> bool;
+    fn typeswift_microphone_authorization() -> c_int;
+    fn typeswift_input_volume() -> c_float;
+    fn typeswift_set_input_volume(volume: c_float) -> bool;
+    fn typeswift_reveal_in_finder(path: *const c_char);
+    fn typeswift_keychain_get(service: *const c_char, account: *const c_char) -> *mut c_char;
+    fn typeswift_keychain_set(service: *const c_char, account: *const c_char, value: *const c_char) -> bool;
+    fn typeswift_focus_active() -> c_int;
+    fn typeswift_focus_mode_name() -> *mut c_char;
+    fn typeswift_calendar_request_access() -> bool;
+    fn typeswift_calendar_events(window_secs: f64) -> *mut c_char;
+    fn typeswift_set_caps_lock_led(on: bool);
+    fn typeswift_speak(text: *const c_char);
+    fn typeswift_announce(text: *const c_char);
+    fn typeswift_reduce_motion() -> bool;
+    fn typeswift_increase_contrast() -> bool;
+}
+
+/// Why the popup and notifications are hidden; several can apply at once
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum QuietReason {
+    /// A Focus mode with `[focus] quiet = true`
+    Focus = 1,
+    /// Presentation mode, which also hides the menu bar recording indicator
+    Presentation = 2,
+}
+
+// Bit set of active QuietReasons
+static QUIET: std::sync::atomic::AtomicU8 = std::sync::atomic::AtomicU8::new(0);
+
+/// Suppress notifications (and, via `WindowManager`, the popup) for `reason`; alerts still show
+pub fn set_quiet(reason: QuietReason, quiet: bool) {
+    if quiet {
+        QUIET.fetch_or(reason as u8, std::sync::atomic::Ordering::Relaxed);
+    } else {
+        QUIET.fetch_and(!(reason as u8), std::sync::atomic::Ordering::Relaxed);
+    }
+}
+
+pub fn is_quiet() -> bool {
+    QUIET.load(std::sync::atomic::Ordering::Relaxed) != 0
+}
+
+pub fn is_quiet_for(reason: QuietReason) -> bool {
+    QUIET.load(std::sync::atomic::Ordering::Relaxed) & reason as u8 != 0
+}
+
+/// Speak `text` with the system voice, interrupting any earlier read-back
+pub fn speak(text: &str) {
+    let c_text = CString::new(text.replace('\0', "")).unwrap();
+    unsafe { typeswift_speak(c_text.as_ptr()) }
+}
+
+/// Ask VoiceOver to speak `text`; nothing happens when VoiceOver is off
+pub fn announce(text: &str) {
+    let c_text = CString::new(text.replace('\0', "")).unwrap();
+    unsafe { typeswift_announce(c_text.as_ptr()) }
+}
+
+/// Whether "Reduce motion" is on in System Settings → Accessibility → Display
+pub fn system_reduce_motion() -> bool {
+    unsafe { typeswift_reduce_motion() }
+}
+
+/// Whether "Increase contrast" is on in System Settings → Accessibility → Display
+pub fn system_increase_contrast() -> bool {
+    unsafe { typeswift_increase_contrast() }
+}
+
+/// Light the Caps Lock LED without toggling Caps Lock (off restores the real state)
+pub fn set_caps_lock_led(on: bool) {
+    unsafe { typeswift_set_caps_lock_led(on) }
+}
+
+/// Whether a Focus mode is on; None when macOS won't tell us (Focus status not authorized)
+pub fn focus_active() -> Option<bool> {
+    match unsafe { typeswift_focus_active() } {
+        1 => Some(true),
+        0 => Some(false),
+        _ => None,
+    }
+}
+
+/// Name of the active Focus mode; needs Full Disk Access to read the Focus database
+pub fn focus_mode_name() -> Option<String> {
+    let c_str = unsafe { typeswift_focus_mode_name() };
+    if c_str.is_null() {
+        return None;
+    }
+    unsafe {
+        let name = std::ffi::CStr::from_ptr(c_str).to_string_lossy().into_owned();
+        typeswift_free_string(c_str);
+        Some(name)
+    }
+}
+
+/// Whether calendar events may be read, prompting for permission the first time.
+/// Blocks until the prompt is answered; call off the main thread.
+pub fn calendar_request_access() -> bool {
+    unsafe { typeswift_calendar_request_access() }
+}
+
+/// A timed calendar event, with times in Unix seconds
+#[derive(Debug, Clone, serde::Deserialize)]
+pub struct CalendarEvent {
+    /// Distinguishes occurrences of a recurring event
+    pub id: String,
+    pub title: String,
+    pub start: f64,
+    pub end: f64,
+}
+
+/// Events overlapping now ± `window_secs`
+pub fn calendar_events(window_secs: f64) -> Vec<CalendarEvent> {
+    let c_str = unsafe { typeswift_calendar_events(window_secs) };
+    if c_str.is_null() {
+        return Vec::new();
+    }
+    let json = unsafe {
+        let json = std::ffi::CStr::from_ptr(c_str).to_string_lossy().into_owned();
+        typeswift_free_string(c_str);
+        json
+    };
+    serde_json::from_str(&json).unwrap_or_default()
+}
+
+/// Replace the general pasteboard contents with `text`
+pub fn set_clipboard_text(text: &str) {
+    // Interior NULs can't cross the C boundary; drop them rather than fail
+    let c_text = CString::new(text.replace('\0', "")).unwrap();
+    unsafe { typeswift_set_clipboard_text(c_text.as_ptr()) }
+}
+
+/// Insert `text` into the focused app by pasting it (Cmd+V), then restore the pasteboard
+pub fn paste_text(text: &str) -> bool {
+    let c_text = CString::new(text.replace('\0', "")).unwrap();
+    unsafe { typeswift_paste_text(c_text.as_ptr()) }
+}
+
+/// ID of the active input source if it is an input method (CJK and the like), which may
+/// swallow or recompose synthesized keystrokes; None for a plain keyboard layout
+pub fn active_input_method() -> Option<String> {
+    swift_string(unsafe { typeswift_active_input_method() })
+}
+
+/// Switch to an ASCII-capable keyboard layout (ABC, U.S.), returning the ID of the input
+/// source to restore with `select_input_source`
+pub fn select_ascii_input_source() -> Option<String> {
+    swift_string(unsafe { typeswift_select_ascii_input_source() })
+}
+
+pub fn select_input_source(id: &str) -> bool {
+    let Ok(c_id) = CString::new(id) else { return false };
+    unsafe { typeswift_select_input_source(c_id.as_ptr()) }
+}
+
+/// Whether the focused UI element accepts text: Some(true) text input,
+/// Some(false) nothing typeable (e.g. desktop), None when AX can't tell.
+pub fn focused_text_target() -> Option<bool> {
+    match unsafe { typeswift_focused_text_target() } {
+        1 => Some(true),
+        0 => Some(false),
+        _ => None,
+    }
+}
+
+/// Up to `max_chars` UTF-16 units before the insertion point of the focused text element
+/// (empty at the start of a document); None when the app doesn't expose it via AX
+pub fn text_before_cursor(max_chars: usize) -> Option<String> {
+    let c_str = unsafe { typeswift_text_before_cursor(max_chars.min(c_int::MAX as usize) as c_int) };
+    if c_str.is_null() {
+        return None;
+    }
+    unsafe {
+        let text = std::ffi::CStr::from_ptr(c_str).to_string_lossy().into_owned();
+        typeswift_free_string(c_str);
+        Some(text)
+    }
+}
+
+/// Bundle identifier of the frontmost application, if any
+pub fn frontmost_bundle_id() -> Option<String> {
+    let c_str = unsafe { typeswift_frontmost_bundle_id() };
+    if c_str.is_null() {
+        return None;
+    }
+    unsafe {
+        let id = std::ffi::CStr::from_ptr(c_str).to_string_lossy().into_owned();
+        typeswift_free_string(c_str);
+        Some(id)
+    }
+}
+
+/// What macOS does when Fn/Globe is pressed on its own
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FnKeyUsage {
+    Nothing,
+    ChangeInputSource,
+    EmojiAndSymbols,
+    StartDictation,
+}
+
+impl FnKeyUsage {
+    pub fn label(self) -> &'static str {
+        match self {
+            FnKeyUsage::Nothing => "Do Nothing",
+            FnKeyUsage::ChangeInputSource => "Change Input Source",
+            FnKeyUsage::EmojiAndSymbols => "Show Emoji & Symbols",
+            FnKeyUsage::StartDictation => "Start Dictation",
+        }
+    }
+}
+
+/// The "Press 🌐 key to" setting; unset means the system default (emoji & symbols)
+pub fn fn_key_usage() -> FnKeyUsage {
+    match unsafe { typeswift_fn_key_usage() } {
+        0 => FnKeyUsage::Nothing,
+        1 => FnKeyUsage::ChangeInputSource,
+        3 => FnKeyUsage::StartDictation,
+        _ => FnKeyUsage::EmojiAndSymbols,
+    }
+}
+
+/// Open System Settings → Keyboard, where the Fn/Globe key's action is chosen
+pub fn open_keyboard_settings() {
+    unsafe { typeswift_open_keyboard_settings() }
+}
+
+/// Whether one of Typeswift's own windows has focus, so typing would land in Typeswift
+pub fn own_app_frontmost() -> bool {
+    unsafe { typeswift_own_app_frontmost() }
+}
+
+/// Whether any process, Typeswift included, is recording from the default input device
+pub fn input_in_use() -> bool {
+    unsafe { typeswift_input_in_use() }
+}
+
+/// Title of the frontmost app's focused window (needs Accessibility)
+pub fn frontmost_window_title() -> Option<String> {
+    let c_str = unsafe { typeswift_frontmost_window_title() };
+    if c_str.is_null() {
+        return None;
+    }
+    unsafe {
+        let title = std::ffi::CStr::from_ptr(c_str).to_string_lossy().into_owned();
+        typeswift_free_string(c_str);
+        Some(title)
+    }
+}
+
+/// Bring the app called `name` (any case) to the front, launching it if it isn't running.
+/// False if there's no app by that name.
+pub fn activate_app(name: &str) -> bool {
+    let Ok(c_name) = CString::new(name) else { return false };
+    unsafe { typeswift_activate_app(c_name.as_ptr()) }
+}
+
+/// Name of the running app called `name`, or of the frontmost app for `None`; None if there's
+/// no such app (Typeswift itself doesn't count)
+pub fn running_app_name(name: Option<&str>) -> Option<String> {
+    let c_name = name.map(CString::new).transpose().ok()?;
+    swift_string(unsafe { typeswift_running_app_name(c_name.as_ref().map_or(std::ptr::null(), |n| n.as_ptr())) })
+}
+
+/// Ask the app called `name` (the frontmost one for `None`) to quit
+pub fn quit_app(name: Option<&str>) -> bool {
+    let Ok(c_name) = name.map(CString::new).transpose() else { return false };
+    unsafe { typeswift_quit_app(c_name.as_ref().map_or(std::ptr::null(), |n| n.as_ptr())) }
+}
+
+/// Act on the frontmost window through Accessibility
+pub fn window_action(action: crate::services::commands::WindowAction) -> bool {
+    use crate::services::commands::WindowAction;
+    let code = match action {
+        WindowAction::Close => 0,
+        WindowAction::Minimize => 1,
+        WindowAction::Maximize => 2,
+        WindowAction::ToggleFullScreen => 3,
+    };
+    unsafe { typeswift_window_action(code) }
+}
+
+/// Start capturing system audio (mono, `sample_rate`) with ScreenCaptureKit.
+/// False without Screen Recording permission. Blocks briefly; call off the main thread.
+pub fn system_audio_start(sample_rate: u32) -> bool {
+    unsafe { typeswift_system_audio_start(sample_rate as i32) }
+}
+
+/// System audio captured since the last read, up to `max_samples`
+pub fn system_audio_read(max_samples: usize) -> Vec<f32> {
+    let mut samples = vec![0.0; max_samples];
+    let count = unsafe { typeswift_system_audio_read(samples.as_mut_ptr(), max_samples) };
+    samples.truncate(count.min(max_samples));
+    samples
+}
+
+pub fn system_audio_stop() {
+    unsafe { typeswift_system_audio_stop() }
+}
+
+/// Encode mono `samples` at `sample_rate` to `path` with AudioToolbox codec `codec`
+/// (0 = AAC, 1 = Opus, 2 = FLAC); the container follows the extension
+pub fn write_audio_file(samples: &[f32], sample_rate: u32, codec: i32, path: &std::path::Path) -> bool {
+    let Ok(c_path) = CString::new(path.to_string_lossy().as_bytes()) else { return false };
+    unsafe { typeswift_write_audio_file(samples.as_ptr(), samples.len(), sample_rate as i32, codec, c_path.as_ptr()) }
+}
+
+/// Decode at most `max` samples from a mono file at `sample_rate` written by `write_audio_file`;
+/// `None` if it can't be read or has another channel count or rate
+pub fn read_audio_file(path: &std::path::Path, sample_rate: u32, max: usize) -> Option<Vec<f32>> {
+    let c_path = CString::new(path.to_string_lossy().as_bytes()).ok()?;
+    let mut samples = vec![0.0f32; max];
+    let read = unsafe { typeswift_read_audio_file(c_path.as_ptr(), sample_rate as i32, samples.as_mut_ptr(), max) };
+    samples.truncate(usize::try_from(read).ok()?);
+    Some(samples)
+}
+
+/// Play an audio file, stopping whatever was playing; false if it can't be opened
+pub fn play_audio_file(path: &std::path::Path) -> bool {
+    let Ok(c_path) = CString::new(path.to_string_lossy().as_bytes()) else { return false };
+    unsafe { typeswift_play_audio_file(c_path.as_ptr()) }
+}
+
+pub fn stop_audio() {
+    unsafe { typeswift_stop_audio() }
+}
+
+/// Take ownership of a string returned by Swift, if any
+fn swift_string(c_str: *mut c_char) -> Option<String> {
+    if c_str.is_null() {
+        return None;
+    }
+    unsafe {
+        let text = std::ffi::CStr::from_ptr(c_str).to_string_lossy().into_owned();
+        typeswift_free_string(c_str);
+        Some(text)
+    }
+}
+
+/// Turn an error string returned by Swift (null for success) into a Result
+fn swift_result(c_str: *mut c_char) -> Result<(), String> {
+    if c_str.is_null() {
+        return Ok(());
+    }
+    unsafe {
+        let message = std::ffi::CStr::from_ptr(c_str).to_string_lossy().into_owned();
+        typeswift_free_string(c_str);
+        Err(message)
+    }
+}
+
+/// Compile a Core ML model source (.mlpackage or .mlmodel) into `destination` (.mlmodelc).
+/// Can take minutes for large models; call off the main thread.
+pub fn compile_coreml_model(source: &std::path::Path, destination: &std::path::Path) -> Result<(), String> {
+    let c_source = CString::new(source.to_string_lossy().as_bytes()).map_err(|e| e.to_string())?;
+    let c_destination = CString::new(destination.to_string_lossy().as_bytes()).map_err(|e| e.to_string())?;
+    swift_result(unsafe { typeswift_compile_coreml_model(c_source.as_ptr(), c_destination.as_ptr()) })
+}
+
+/// Load a compiled Core ML model to see whether it works on this Mac. The first load
+/// compiles it for the device, which can be slow; call off the main thread.
+pub fn check_coreml_model(path: &std::path::Path) -> Result<(), String> {
+    let c_path = CString::new(path.to_string_lossy().as_bytes()).map_err(|e| e.to_string())?;
+    swift_result(unsafe { typeswift_check_coreml_model(c_path.as_ptr()) })
+}
+
+/// Whether the default input device uses a Bluetooth transport; None if CoreAudio can't tell
+pub fn default_input_is_bluetooth() -> Option<bool> {
+    match unsafe { typeswift_default_input_is_bluetooth() } {
+        1 => Some(true),
+        0 => Some(false),
+        _ => None,
+    }
+}
+
+/// Whether Typeswift currently has Accessibility permission (needed to type and watch hotkeys)
+pub fn accessibility_trusted() -> bool {
+    unsafe { typeswift_accessibility_trusted() }
+}
+
+/// Microphone permission as shown to the user ("granted", "denied", ...)
+pub fn microphone_authorization() -> &'static str {
+    match unsafe { typeswift_microphone_authorization() } {
+        0 => "not requested",
+        1 => "restricted",
+        2 => "denied",
+        3 => "granted",
+        _ => "unknown",
+    }
+}
+
+/// Hardware input volume (0.0–1.0) of the default input device, if CoreAudio exposes one
+pub fn input_volume() -> Option<f32> {
+    let volume = unsafe { typeswift_input_volume() };
+    (volume >= 0.0).then_some(volume)
+}
+
+/// Change the default input device's hardware volume (system-wide); false if it can't be set
+pub fn set_input_volume(volume: f32) -> bool {
+    unsafe { typeswift_set_input_volume(volume.clamp(0.0, 1.0)) }
+}
+
+/// Show `path` selected in Finder
+pub fn reveal_in_finder(path: &std::path::Path) {
+    if let Ok(c_path) = CString::new(path.to_string_lossy().as_bytes()) {
+        unsafe { typeswift_reveal_in_finder(c_path.as_ptr()) }
+    }
+}
+
+/// Generic password stored in the login keychain, if present
+pub fn keychain_get(service: &str, account: &str) -> Option<String> {
+    let c_service = CString::new(service).ok()?;
+    let c_account = CString::new(account).ok()?;
+    let c_str = unsafe { typeswift_keychain_get(c_service.as_ptr(), c_account.as_ptr()) };
+    if c_str.is_null() {
+        return None;
+    }
+    unsafe {
+        let value = std::ffi::CStr::from_ptr(c_str).to_string_lossy().into_owned();
+        typeswift_free_string(c_str);
+        Some(value)
+    }
+}
+
+/// Store a generic password in the login keychain, replacing any existing item
+pub fn keychain_set(service: &str, account: &str, value: &str) -> bool {
+    let (Ok(c_service), Ok(c_account), Ok(c_value)) = (CString::new(service), CString::new(account), CString::new(value)) else {
+        return false;
+    };
+    unsafe { typeswift_keychain_set(c_service.as_ptr(), c_account.as_ptr(), c_value.as_ptr()) }
+}
+
 // ===== Swift Transcriber FFI =====
 
+use crate::services::audio::{Token, Transcript};
+
 #[link(name = "TypeswiftSwift")]
 unsafe extern "C" {
     fn typeswift_init(model_path: *const c_char) -> c_int;
     fn typeswift_transcribe(samples: *const c_float, sample_count: c_int) -> *mut c_char;
+    fn typeswift_session_begin(capacity: usize);
+    fn typeswift_session_add_samples(samples: *const c_float, count: usize);
+    fn typeswift_session_transcribe_with_timings() -> *mut c_char;
     fn typeswift_free_string(str: *mut c_char);
     fn typeswift_cleanup();
     fn typeswift_is_ready() -> bool;
 }
 
+/// Samples per `typeswift_session_add_samples` call (10 s at 16 kHz). Recordings go over in
+/// slices of this size, so a long one in a scratch file is read a window at a time and Swift
+/// builds its single copy without a second full-length temporary.
+const HANDOFF_SLICE: usize = 160_000;
+
 pub struct SwiftTranscriber {
     initialized: bool,
 }
@@ -166,6 +974,33 @@ impl SwiftTranscriber {
         Ok(result)
     }
 
+    /// Transcribe and return word timings merged from the model's subword tokens
+    pub fn transcribe_with_timings(&self, samples: &[f32]) -> Result<Transcript, String> {
+        if !self.initialized {
+            return Err("Transcriber not initialized".to_string());
+        }
+        if samples.is_empty() {
+            return Ok(Transcript::default());
+        }
+        let c_str = unsafe {
+            typeswift_session_begin(samples.len());
+            for slice in samples.chunks(HANDOFF_SLICE) {
+                typeswift_session_add_samples(slice.as_ptr() as *const c_float, slice.len());
+            }
+            typeswift_session_transcribe_with_timings()
+        };
+        if c_str.is_null() {
+            return Err("Transcription failed".to_string());
+        }
+        let json = unsafe {
+            let rust_str = std::ffi::CStr::from_ptr(c_str).to_string_lossy().into_owned();
+            typeswift_free_string(c_str);
+            rust_str
+        };
+        let raw: SwiftTimedResult = serde_json::from_str(&json).map_err(|e| format!("Bad timing payload: {}", e))?;
+        Ok(Transcript { tokens: words_from_subwords(&raw.tokens), text: raw.text, ..Default::default() })
+    }
+
     pub fn is_ready(&self) -> bool {
         unsafe { typeswift_is_ready() }
     }
@@ -184,6 +1019,53 @@ impl Drop for SwiftTranscriber {
     }
 }
 
+/// JSON shape returned by `typeswift_session_transcribe_with_timings`
+#[derive(serde::Deserialize)]
+struct SwiftTimedResult {
+    text: String,
+    #[serde(default)]
+    tokens: Vec<SwiftToken>,
+}
+
+#[derive(serde::Deserialize)]
+struct SwiftToken {
+    token: String,
+    /// Seconds
+    start: f64,
+    end: f64,
+    #[serde(default)]
+    confidence: Option<f32>,
+}
+
+/// Merge SentencePiece subwords into words: "▁" (or a leading space) starts a new word.
+/// A word's confidence is its least confident piece.
+fn words_from_subwords(pieces: &[SwiftToken]) -> Vec<Token> {
+    let mut words: Vec<Token> = Vec::new();
+    let mut new_word = true;
+    for piece in pieces {
+        new_word |= piece.token.starts_with('\u{2581}') || piece.token.starts_with(' ');
+        let text = piece.token.trim_start_matches(['\u{2581}', ' ']);
+        if text.is_empty() {
+            continue;
+        }
+        let start_ms = (piece.start.max(0.0) * 1000.0) as u64;
+        let end_ms = (piece.end.max(0.0) * 1000.0) as u64;
+        match words.last_mut() {
+            Some(word) if !new_word => {
+                word.text.push_str(text);
+                word.end_ms = end_ms.max(word.end_ms);
+                word.confidence = match (word.confidence, piece.confidence) {
+                    (Some(a), Some(b)) => Some(a.min(b)),
+                    (a, b) => a.or(b),
+                };
+            }
+            _ => words.push(Token { text: text.to_string(), start_ms, end_ms, confidence: piece.confidence }),
+        }
+        new_word = false;
+    }
+    words
+}
+
 use parking_lot::Mutex;
 use std::sync::Arc;
 
@@ -201,6 +1083,9 @@ impl SharedSwiftTranscriber {
     pub fn transcribe(&self, samples: &[f32]) -> Result<String, String> {
         self.inner.lock().transcribe(samples)
     }
+    pub fn transcribe_with_timings(&self, samples: &[f32]) -> Result<Transcript, String> {
+        self.inner.lock().transcribe_with_timings(samples)
+    }
     pub fn is_ready(&self) -> bool { self.inner.lock().is_ready() }
     pub fn cleanup(&self) { self.inner.lock().cleanup() }
 }
@@ -266,6 +1151,18 @@ mod modifiers {
         if pressed.is_empty() { "<none>".to_string() } else { pressed.join(",") }
     }
 
+    pub fn any_modifier_down() -> bool {
+        any_down(&snapshot())
+    }
+
+    pub fn held_modifiers() -> Vec<&'static str> {
+        let names = [
+            "left_cmd", "right_cmd", "left_shift", "right_shift",
+            "left_opt", "right_opt", "left_ctrl", "right_ctrl",
+        ];
+        names.iter().zip(snapshot()).filter_map(|(name, down)| down.then_some(*name)).collect()
+    }
+
     pub fn wait_modifiers_released(timeout_ms: u64) -> bool {
         let start = Instant::now();
         let initial = snapshot();
@@ -300,3 +1197,13 @@ mod modifiers {
 pub fn wait_modifiers_released(timeout_ms: u64) -> bool {
     modifiers::wait_modifiers_released(timeout_ms)
 }
+
+/// Whether any Command/Shift/Option/Control key is currently held
+pub fn any_modifier_down() -> bool {
+    modifiers::any_modifier_down()
+}
+
+/// Command/Shift/Option/Control keys held right now, by side ("left_shift", "right_opt", ...)
+pub fn held_modifiers() -> Vec<&'static str> {
+    modifiers::held_modifiers()
+}
@@ -0,0 +1,42 @@
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+/// The enclosing `.app` bundle when running from one (…/Typeswift.app/Contents/MacOS/typeswift)
+fn app_bundle(exe: &Path) -> Option<PathBuf> {
+    exe.ancestors()
+        .find(|p| p.extension().is_some_and(|ext| ext == "app"))
+        .map(Path::to_path_buf)
+}
+
+fn shell_quote(s: &str) -> String {
+    format!("'{}'", s.replace('\'', r"'\''"))
+}
+
+/// Start a detached successor that waits for this process to exit, then launches Typeswift
+/// again: the bundle via `open` (so TCC sees a fresh app launch), or the bare binary with the
+/// same arguments during development. The caller should quit right after this returns.
+pub fn spawn_successor() -> std::io::Result<()> {
+    let exe = std::env::current_exe()?;
+    let launch = match app_bundle(&exe) {
+        Some(bundle) => format!("/usr/bin/open -n {}", shell_quote(&bundle.to_string_lossy())),
+        None => std::iter::once(exe.to_string_lossy().into_owned())
+            .chain(std::env::args().skip(1))
+            .map(|a| shell_quote(&a))
+            .collect::<Vec<_>>()
+            .join(" "),
+    };
+    let script = format!(
+        "while kill -0 {pid} 2>/dev/null; do sleep 0.2; done; exec {launch}",
+        pid = std::process::id(),
+        launch = launch
+    );
+    Command::new("/bin/sh")
+        .arg("-c")
+        .arg(script)
+        .stdin(std::process::Stdio::null())
+        .stdout(std::process::Stdio::null())
+        .stderr(std::process::Stdio::null())
+        .spawn()?;
+    tracing::info!("Relaunch scheduled");
+    Ok(())
+}
@@ -0,0 +1,69 @@
+//! Executor for the controller's background services: the permission, Focus and event-tap
+//! watches, the per-recording streaming and capture-drain loops, and the alerts they raise.
+//! They share one thread and sleep on timers instead of each owning a thread that wakes on a
+//! fixed schedule. Blocking calls (modal alerts, transcription, the audio processor's lock) go
+//! through `smol::unblock`. Hotkey delivery stays on its own threads: those park on channels
+//! without waking until a key is pressed, and a press must never queue behind a task here.
+
+use once_cell::sync::Lazy;
+use parking_lot::Mutex;
+use smol::{channel, Executor, Task};
+use std::future::Future;
+use std::sync::Arc;
+use std::thread::{JoinHandle, ThreadId};
+use tracing::{debug, info};
+
+struct Runtime {
+    executor: Arc<Executor<'static>>,
+    tasks: Mutex<Vec<Task<()>>>,
+    stop: channel::Sender<()>,
+    thread: Mutex<Option<JoinHandle<()>>>,
+    thread_id: ThreadId,
+}
+
+static RUNTIME: Lazy<Runtime> = Lazy::new(|| {
+    let executor = Arc::new(Executor::new());
+    let (stop, stopped) = channel::bounded::<()>(1);
+    let runner = Arc::clone(&executor);
+    let thread = std::thread::Builder::new()
+        .name("typeswift-background".to_string())
+        .spawn(move || {
+            // Returns once `shutdown` closes the channel
+            smol::block_on(runner.run(async { let _ = stopped.recv().await; }));
+            debug!("Background executor stopped");
+        })
+        .expect("failed to start the background executor thread");
+    Runtime {
+        executor,
+        tasks: Mutex::new(Vec::new()),
+        stop,
+        thread_id: thread.thread().id(),
+        thread: Mutex::new(Some(thread)),
+    }
+});
+
+/// Run `future` on the background executor until it finishes or `shutdown` cancels it
+pub fn spawn(future: impl Future<Output = ()> + Send + 'static) {
+    let task = RUNTIME.executor.spawn(future);
+    let mut tasks = RUNTIME.tasks.lock();
+    tasks.retain(|task| !task.is_finished());
+    tasks.push(task);
+}
+
+/// Cancel every background task at its next await and stop the executor thread. Tasks
+/// blocked inside `smol::unblock` finish that call first; quitting doesn't wait for them.
+pub fn shutdown() {
+    if Lazy::get(&RUNTIME).is_none() {
+        return;
+    }
+    let tasks = std::mem::take(&mut *RUNTIME.tasks.lock());
+    info!("Stopping {} background task(s)", tasks.len());
+    drop(tasks);
+    RUNTIME.stop.close();
+    // From a task itself the thread can't be joined; it exits once this task returns
+    if std::thread::current().id() != RUNTIME.thread_id {
+        if let Some(thread) = RUNTIME.thread.lock().take() {
+            let _ = thread.join();
+        }
+    }
+}
@@ -1,21 +1,82 @@
-use crate::config::Config;
+use crate::config::{AudioConfig, Config, StreamingConfig};
 use crate::error::{VoicyError, VoicyResult};
+use crate::mem::BufferPool;
 use parking_lot::RwLock;
-use ringbuf::{traits::*, HeapCons, HeapRb};
+use ringbuf::{traits::*, HeapCons, HeapProd, HeapRb};
 use rubato::{Resampler, SincFixedIn, SincInterpolationParameters, SincInterpolationType, WindowFunction};
 use std::sync::Arc;
 
 // ===== Audio capture (cpal) =====
 use cpal::traits::{DeviceTrait, HostTrait, StreamTrait};
-use std::sync::mpsc::{channel, Sender};
+use std::sync::atomic::{AtomicBool, AtomicU32, Ordering};
+use std::sync::mpsc::{channel, RecvTimeoutError, Sender};
+use std::time::Duration;
 use std::thread::JoinHandle;
-use tracing::{info, warn, error};
+use tracing::{info, warn, error, debug};
+
+/// Source of mono f32 samples at a fixed rate.
+/// Implemented by the cpal microphone capture and by `MockAudioSource` for tests.
+pub trait AudioSource: Send {
+    fn start_recording(&mut self) -> VoicyResult<()>;
+    fn stop_recording(&mut self) -> VoicyResult<()>;
+    fn read_audio(&self, max_samples: usize) -> Vec<f32>;
+    fn is_recording(&self) -> bool;
+    fn get_sample_rate(&self) -> u32;
+    /// Device opened by the most recent `start_recording`, if this source has one
+    fn input_device(&self) -> Option<InputDeviceInfo> {
+        None
+    }
+    /// Software gain for captured samples; sources that replay fixed audio ignore it
+    fn set_gain(&self, _gain: f32) {}
+}
+
+/// What the capture thread found when opening the input device
+#[derive(Debug, Clone, PartialEq)]
+pub struct InputDeviceInfo {
+    pub name: String,
+    pub sample_rate: u32,
+    pub is_bluetooth: Option<bool>,
+}
+
+impl InputDeviceInfo {
+    /// Bluetooth at a telephony rate (HFP/HSP): accuracy drops sharply
+    pub fn is_low_quality_headset(&self) -> bool {
+        self.is_bluetooth == Some(true) && self.sample_rate <= 24000
+    }
+}
+
+/// Every input device the system offers and its default format (the default device first, and
+/// the only one Bluetooth is known for), for `typeswift doctor`
+pub fn input_devices() -> Vec<(InputDeviceInfo, u16)> {
+    let host = cpal::default_host();
+    let default_name = host.default_input_device().and_then(|d| d.name().ok());
+    let Ok(devices) = host.input_devices() else {
+        return Vec::new();
+    };
+    let mut found: Vec<(InputDeviceInfo, u16)> = devices
+        .filter_map(|device| {
+            let name = device.name().ok()?;
+            let config = device.default_input_config().ok()?;
+            let is_default = default_name.as_deref() == Some(name.as_str());
+            let is_bluetooth = is_default.then(crate::platform::macos::ffi::default_input_is_bluetooth).flatten();
+            Some((InputDeviceInfo { name, sample_rate: config.sample_rate().0, is_bluetooth }, config.channels()))
+        })
+        .collect();
+    if let Some(index) = found.iter().position(|(d, _)| default_name.as_deref() == Some(d.name.as_str())) {
+        let default = found.remove(index);
+        found.insert(0, default);
+    }
+    found
+}
 
 pub struct AudioCapture {
     consumer: Arc<parking_lot::Mutex<HeapCons<f32>>>,
     is_recording: Arc<RwLock<bool>>,
     sample_rate: u32,
     thread: parking_lot::Mutex<Option<AudioThread>>, // Spawned only while recording
+    device: Option<InputDeviceInfo>,
+    // f32 bits, read by the capture callback
+    gain: Arc<AtomicU32>,
 }
 
 struct AudioThread {
@@ -42,7 +103,7 @@ pub struct AudioReader {
 }
 
 impl AudioCapture {
-    pub fn new(target_sample_rate: u32) -> VoicyResult<Self> {
+    pub fn new(target_sample_rate: u32, gain: f32) -> VoicyResult<Self> {
         // Create an empty ring buffer; the active session buffer will be created on start
         let rb = HeapRb::<f32>::new(target_sample_rate as usize); // minimal buffer
         let (_producer_unused, consumer) = rb.split();
@@ -52,9 +113,16 @@ impl AudioCapture {
             is_recording,
             sample_rate: target_sample_rate,
             thread: parking_lot::Mutex::new(None),
+            device: None,
+            gain: Arc::new(AtomicU32::new(gain.to_bits())),
         })
     }
 
+    /// Takes effect immediately, including mid-recording
+    pub fn set_gain(&self, gain: f32) {
+        self.gain.store(gain.to_bits(), Ordering::Relaxed);
+    }
+
     pub fn start_recording(&mut self) -> VoicyResult<()> {
         // Fresh ring buffer per session (30s at target rate)
         let ring_buffer_size = self.sample_rate as usize * 30;
@@ -68,155 +136,81 @@ impl AudioCapture {
 
         let is_recording_clone = self.is_recording.clone();
         let target_sample_rate = self.sample_rate;
+        let gain = Arc::clone(&self.gain);
 
         // Channel to keep the stream thread alive and signal shutdown
         let (stop_tx, stop_rx) = channel::<()>();
-        let (ready_tx, ready_rx) = channel::<Result<(), String>>();
+        let (ready_tx, ready_rx) = channel::<Result<InputDeviceInfo, String>>();
 
         let handle = std::thread::spawn(move || {
-            // Set up CPAL on this thread; the stream lives and dies here
-            let host = cpal::default_host();
-            let device = match host.default_input_device() {
-                Some(d) => d,
-                None => {
-                    let _ = ready_tx.send(Err("No input device available".to_string()));
-                    return;
-                }
-            };
+            // Set up CPAL on this thread; the stream lives and dies here.
+            // The producer is shared so the stream can be rebuilt mid-session.
+            let producer = Arc::new(parking_lot::Mutex::new(producer));
+            let stream_failed = Arc::new(AtomicBool::new(false));
 
-            let supported_config = match device.default_input_config() {
-                Ok(c) => c,
+            let (mut stream, mut device, mut device_info) = match open_input_stream(
+                target_sample_rate,
+                &producer,
+                &is_recording_clone,
+                &stream_failed,
+                &gain,
+            ) {
+                Ok(opened) => opened,
                 Err(e) => {
-                    let _ = ready_tx.send(Err(format!("Failed to get device config: {}", e)));
+                    let _ = ready_tx.send(Err(e));
                     return;
                 }
             };
 
-            let device_sample_rate = supported_config.sample_rate().0;
-            let channels = supported_config.channels() as usize;
-
-            info!(
-                "Audio device: {} Hz, {} channels → {} Hz",
-                device_sample_rate, channels, target_sample_rate
-            );
-
-            let config: cpal::StreamConfig = supported_config.into();
-
-            // Setup resampler if needed
-            let needs_resampling = device_sample_rate != target_sample_rate;
-            let resample_ratio = target_sample_rate as f64 / device_sample_rate as f64;
-
-            let mut resampler = if needs_resampling {
-                let params = SincInterpolationParameters {
-                    sinc_len: 128,
-                    f_cutoff: 0.95,
-                    interpolation: SincInterpolationType::Linear,
-                    oversampling_factor: 128,
-                    window: WindowFunction::BlackmanHarris2,
-                };
-
-                match SincFixedIn::<f32>::new(resample_ratio, 2.0, params, 1024, 1) {
-                    Ok(r) => Some(r),
-                    Err(e) => {
-                        let _ = ready_tx.send(Err(format!("Failed to create resampler: {}", e)));
-                        return;
-                    }
+            // Signal ready and keep the stream alive until stop signal
+            let _ = ready_tx.send(Ok(device_info.clone()));
+            loop {
+                match stop_rx.recv_timeout(RATE_CHECK_INTERVAL) {
+                    Err(RecvTimeoutError::Timeout) => {}
+                    // Stop requested or owner gone
+                    _ => break,
                 }
-            } else {
-                None
-            };
-
-            let mut input_buffer = Vec::with_capacity(2048);
-            let mut mono_scratch = Vec::with_capacity(2048);
-            let mut overflow_count = 0usize;
 
-            // The audio producer is not Send; but it's fine to move into the closure via move
-            let mut producer = producer;
-
-            let stream = match device.build_input_stream(
-                &config,
-                move |data: &[f32], _: &_| {
-                    if !*is_recording_clone.read() {
-                        return;
-                    }
-
-                    // Convert to mono into a reusable scratch buffer
-                    mono_scratch.clear();
-                    if channels > 1 {
-                        mono_scratch.reserve(data.len() / channels);
-                        for frame in data.chunks(channels) {
-                            let sum: f32 = frame.iter().copied().sum();
-                            mono_scratch.push(sum / channels as f32);
-                        }
-                    } else {
-                        mono_scratch.extend_from_slice(data);
+                // Aggregate devices and Bluetooth profile switches change the rate under us;
+                // a stale resampler would pitch-shift everything ("chipmunk" audio).
+                let current_rate = device.default_input_config().ok().map(|c| c.sample_rate().0);
+                let failed = stream_failed.swap(false, Ordering::SeqCst);
+                if !failed && current_rate == Some(device_info.sample_rate) {
+                    continue;
+                }
+                warn!(
+                    "Input changed (rate {:?} -> {:?}, stream error: {}); rebuilding capture stream",
+                    device_info.sample_rate, current_rate, failed
+                );
+                drop(stream);
+                match open_input_stream(target_sample_rate, &producer, &is_recording_clone, &stream_failed, &gain) {
+                    Ok((s, d, info)) => {
+                        info!("Capture stream rebuilt: {} @ {} Hz", info.name, info.sample_rate);
+                        stream = s;
+                        device = d;
+                        device_info = info;
                     }
-
-                    // Handle resampling if needed
-                    if let Some(ref mut resampler) = resampler {
-                        input_buffer.extend_from_slice(&mono_scratch);
-
-                        while input_buffer.len() >= 1024 {
-                            let input_chunk: Vec<f32> = input_buffer.drain(..1024).collect();
-
-                            if let Ok(resampled) = resampler.process(&[input_chunk], None) {
-                                for &sample in &resampled[0] {
-                                    if producer.try_push(sample).is_err() {
-                                        overflow_count += 1;
-                                        if overflow_count % 10000 == 0 {
-                                            warn!(
-                                                "Audio buffer overflow: {} samples dropped",
-                                                overflow_count
-                                            );
-                                        }
-                                    }
-                                }
-                            }
-                        }
-                    } else {
-                        // No resampling needed, direct copy
-                        for &sample in &mono_scratch {
-                            if producer.try_push(sample).is_err() {
-                                overflow_count += 1;
-                                if overflow_count % 10000 == 0 {
-                                    warn!(
-                                        "Audio buffer overflow: {} samples dropped",
-                                        overflow_count
-                                    );
-                                }
-                            }
-                        }
+                    Err(e) => {
+                        error!("Failed to rebuild capture stream: {}", e);
+                        return;
                     }
-                },
-                |err| error!("Audio stream error: {}", err),
-                None,
-            ) {
-                Ok(s) => s,
-                Err(e) => {
-                    let _ = ready_tx.send(Err(format!("Failed to build stream: {}", e)));
-                    return;
                 }
-            };
-
-            if let Err(e) = stream.play() {
-                let _ = ready_tx.send(Err(format!("Failed to start stream: {}", e)));
-                return;
             }
-
-            // Signal ready and keep the stream alive until stop signal
-            let _ = ready_tx.send(Ok(()));
-            // Keep stream in scope until stop signal is received
-            let _ = stop_rx.recv();
             drop(stream);
         });
 
         // Wait for the audio thread to confirm readiness
         match ready_rx.recv() {
-            Ok(Ok(())) => {
+            Ok(Ok(device)) => {
+                self.device = Some(device);
                 *self.thread.lock() = Some(AudioThread { stop_tx: parking_lot::Mutex::new(Some(stop_tx)), handle: parking_lot::Mutex::new(Some(handle)) });
                 info!("Audio capture started");
                 Ok(())
             }
+            // A denied microphone usually shows up as a device error; say what's really wrong
+            Ok(Err(e)) if matches!(crate::platform::macos::ffi::microphone_authorization(), "denied" | "restricted") => {
+                Err(VoicyError::PermissionDenied(format!("Microphone access is off ({})", e)))
+            }
             Ok(Err(e)) => Err(VoicyError::AudioInitFailed(e)),
             Err(e) => Err(VoicyError::AudioInitFailed(format!("Audio thread error: {}", e))),
         }
@@ -269,6 +263,201 @@ impl AudioCapture {
     }
 }
 
+const RATE_CHECK_INTERVAL: Duration = Duration::from_millis(500);
+const RESAMPLER_CHUNK: usize = 1024;
+/// Buffers the capture callback takes from its pool at once (mono frame, resampler input)
+const CAPTURE_POOL_BUFFERS: usize = 4;
+
+/// Open the default input device and start a stream that downmixes and resamples
+/// into `producer` at `target_sample_rate`, scaled by `gain` (f32 bits). Stream errors raise `failed`.
+fn open_input_stream(
+    target_sample_rate: u32,
+    producer: &Arc<parking_lot::Mutex<HeapProd<f32>>>,
+    is_recording: &Arc<RwLock<bool>>,
+    failed: &Arc<AtomicBool>,
+    gain: &Arc<AtomicU32>,
+) -> Result<(cpal::Stream, cpal::Device, InputDeviceInfo), String> {
+    let host = cpal::default_host();
+    let device = host
+        .default_input_device()
+        .ok_or_else(|| "No input device available".to_string())?;
+
+    let supported_config = device
+        .default_input_config()
+        .map_err(|e| format!("Failed to get device config: {}", e))?;
+
+    let device_sample_rate = supported_config.sample_rate().0;
+    let channels = supported_config.channels() as usize;
+    let device_info = InputDeviceInfo {
+        name: device.name().unwrap_or_else(|_| "Unknown input".to_string()),
+        sample_rate: device_sample_rate,
+        is_bluetooth: crate::platform::macos::ffi::default_input_is_bluetooth(),
+    };
+
+    info!(
+        "Audio device: {} Hz, {} channels → {} Hz",
+        device_sample_rate, channels, target_sample_rate
+    );
+
+    let config: cpal::StreamConfig = supported_config.into();
+    let mut callback = CaptureCallback::new(channels, device_sample_rate, target_sample_rate, producer, gain)?;
+    let is_recording = Arc::clone(is_recording);
+    let failed_flag = Arc::clone(failed);
+
+    let stream = device
+        .build_input_stream(
+            &config,
+            move |data: &[f32], _: &_| {
+                if *is_recording.read() {
+                    callback.process(data);
+                }
+            },
+            move |err| {
+                error!("Audio stream error: {}", err);
+                failed_flag.store(true, Ordering::SeqCst);
+            },
+            None,
+        )
+        .map_err(|e| format!("Failed to build stream: {}", e))?;
+
+    stream.play().map_err(|e| format!("Failed to start stream: {}", e))?;
+
+    Ok((stream, device, device_info))
+}
+
+/// What the input stream's callback does with each buffer: downmix, apply gain, resample and
+/// push into the ring buffer. Everything it needs is allocated in `new`, not on the audio thread.
+pub(crate) struct CaptureCallback {
+    channels: usize,
+    pool: BufferPool,
+    input_buffer: Vec<f32>,
+    resampler: Option<SincFixedIn<f32>>,
+    resampled: Option<Vec<Vec<f32>>>,
+    overflow_count: usize,
+    producer: Arc<parking_lot::Mutex<HeapProd<f32>>>,
+    gain: Arc<AtomicU32>,
+}
+
+impl CaptureCallback {
+    pub(crate) fn new(
+        channels: usize,
+        device_sample_rate: u32,
+        target_sample_rate: u32,
+        producer: &Arc<parking_lot::Mutex<HeapProd<f32>>>,
+        gain: &Arc<AtomicU32>,
+    ) -> Result<Self, String> {
+        let resampler = if device_sample_rate != target_sample_rate {
+            let params = SincInterpolationParameters {
+                sinc_len: 128,
+                f_cutoff: 0.95,
+                interpolation: SincInterpolationType::Linear,
+                oversampling_factor: 128,
+                window: WindowFunction::BlackmanHarris2,
+            };
+            let ratio = target_sample_rate as f64 / device_sample_rate as f64;
+            Some(
+                SincFixedIn::<f32>::new(ratio, 2.0, params, RESAMPLER_CHUNK, 1)
+                    .map_err(|e| format!("Failed to create resampler: {}", e))?,
+            )
+        } else {
+            None
+        };
+        Ok(Self {
+            channels: channels.max(1),
+            pool: BufferPool::new(CAPTURE_POOL_BUFFERS, 4096),
+            input_buffer: Vec::with_capacity(4096),
+            resampled: resampler.as_ref().map(|r| r.output_buffer_allocate(true)),
+            resampler,
+            overflow_count: 0,
+            producer: Arc::clone(producer),
+            gain: Arc::clone(gain),
+        })
+    }
+
+    /// Handle one buffer of interleaved device samples
+    pub(crate) fn process(&mut self, data: &[f32]) {
+        // Convert to mono into a pooled buffer
+        let mut mono_scratch = self.pool.get();
+        if self.channels > 1 {
+            for frame in data.chunks(self.channels) {
+                let sum: f32 = frame.iter().copied().sum();
+                mono_scratch.push(sum / self.channels as f32);
+            }
+        } else {
+            mono_scratch.extend_from_slice(data);
+        }
+        let gain = f32::from_bits(self.gain.load(Ordering::Relaxed));
+        if gain != 1.0 {
+            for sample in mono_scratch.iter_mut() {
+                *sample = (*sample * gain).clamp(-1.0, 1.0);
+            }
+        }
+
+        let mut producer = self.producer.lock();
+        let overflow_count = &mut self.overflow_count;
+        let mut push = |sample: f32| {
+            if producer.try_push(sample).is_err() {
+                crate::metrics::record_overflow();
+                *overflow_count += 1;
+                if *overflow_count % 10000 == 0 {
+                    warn!("Audio buffer overflow: {} samples dropped", overflow_count);
+                }
+            }
+        };
+
+        // Handle resampling if needed
+        if let (Some(resampler), Some(output)) = (self.resampler.as_mut(), self.resampled.as_mut()) {
+            self.input_buffer.extend_from_slice(&mono_scratch);
+
+            while self.input_buffer.len() >= RESAMPLER_CHUNK {
+                let mut input_chunk = self.pool.get();
+                input_chunk.extend(self.input_buffer.drain(..RESAMPLER_CHUNK));
+
+                if let Ok((_, written)) = resampler.process_into_buffer(&[&input_chunk[..]], output, None) {
+                    for &sample in &output[0][..written] {
+                        push(sample);
+                    }
+                }
+            }
+        } else {
+            // No resampling needed, direct copy
+            for &sample in mono_scratch.iter() {
+                push(sample);
+            }
+        }
+    }
+}
+
+impl AudioSource for AudioCapture {
+    fn start_recording(&mut self) -> VoicyResult<()> {
+        AudioCapture::start_recording(self)
+    }
+
+    fn stop_recording(&mut self) -> VoicyResult<()> {
+        AudioCapture::stop_recording(self)
+    }
+
+    fn read_audio(&self, max_samples: usize) -> Vec<f32> {
+        AudioCapture::read_audio(self, max_samples)
+    }
+
+    fn is_recording(&self) -> bool {
+        AudioCapture::is_recording(self)
+    }
+
+    fn get_sample_rate(&self) -> u32 {
+        AudioCapture::get_sample_rate(self)
+    }
+
+    fn input_device(&self) -> Option<InputDeviceInfo> {
+        self.device.clone()
+    }
+
+    fn set_gain(&self, gain: f32) {
+        AudioCapture::set_gain(self, gain)
+    }
+}
+
 impl Clone for AudioCapture {
     fn clone(&self) -> Self {
         Self {
@@ -276,6 +465,8 @@ impl Clone for AudioCapture {
             is_recording: Arc::clone(&self.is_recording),
             sample_rate: self.sample_rate,
             thread: parking_lot::Mutex::new(None),
+            device: self.device.clone(),
+            gain: Arc::clone(&self.gain),
         }
     }
 }
@@ -301,48 +492,182 @@ impl AudioReader {
     pub fn get_sample_rate(&self) -> u32 {
         self.sample_rate
     }
+
+    /// Split recordings longer than `ms` (at least `MIN_CHUNK_MS`) before transcribing
+    pub fn set_chunk_duration_ms(&mut self, ms: u64) {
+        self.chunk_duration_ms = ms.max(MIN_CHUNK_MS);
+    }
+}
+
+// ===== Mock capture (fixture replay) =====
+
+/// Replays a fixed sample buffer on every recording session.
+/// Each session exposes the whole fixture, so results don't depend on timing.
+pub struct MockAudioSource {
+    samples: Arc<Vec<f32>>,
+    cursor: parking_lot::Mutex<usize>,
+    is_recording: bool,
+    sample_rate: u32,
+}
+
+impl MockAudioSource {
+    pub fn new(samples: Vec<f32>, sample_rate: u32) -> Self {
+        Self {
+            samples: Arc::new(samples),
+            cursor: parking_lot::Mutex::new(0),
+            is_recording: false,
+            sample_rate,
+        }
+    }
+
+    /// Load a WAV fixture, resampled to `target_sample_rate`
+    pub fn from_wav_file(path: &std::path::Path, target_sample_rate: u32) -> VoicyResult<Self> {
+        let wav = crate::services::wav::read_wav_file(path)?;
+        let samples = crate::services::wav::resample_linear(&wav.samples, wav.sample_rate, target_sample_rate);
+        info!(
+            "Mock audio source: {} ({} samples @ {} Hz)",
+            path.display(),
+            samples.len(),
+            target_sample_rate
+        );
+        Ok(Self::new(samples, target_sample_rate))
+    }
+}
+
+impl AudioSource for MockAudioSource {
+    fn start_recording(&mut self) -> VoicyResult<()> {
+        *self.cursor.lock() = 0;
+        self.is_recording = true;
+        Ok(())
+    }
+
+    fn stop_recording(&mut self) -> VoicyResult<()> {
+        self.is_recording = false;
+        Ok(())
+    }
+
+    fn read_audio(&self, max_samples: usize) -> Vec<f32> {
+        let mut cursor = self.cursor.lock();
+        let end = (*cursor + max_samples).min(self.samples.len());
+        let chunk = self.samples[*cursor..end].to_vec();
+        *cursor = end;
+        chunk
+    }
+
+    fn is_recording(&self) -> bool {
+        self.is_recording
+    }
+
+    fn get_sample_rate(&self) -> u32 {
+        self.sample_rate
+    }
+}
+
+// ===== Transcript types shared by all backends =====
+
+/// A recognised word and where it falls in the recording
+#[derive(Debug, Clone, PartialEq, serde::Serialize, serde::Deserialize)]
+pub struct Token {
+    pub text: String,
+    pub start_ms: u64,
+    pub end_ms: u64,
+    /// Model confidence in [0, 1], when the backend reports one
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub confidence: Option<f32>,
+}
+
+/// Final transcript with word timings (empty when the backend has none)
+#[derive(Debug, Clone, Default)]
+pub struct Transcript {
+    pub text: String,
+    pub tokens: Vec<Token>,
+    /// Length of the audio that was transcribed
+    pub duration_ms: u64,
+}
+
+impl Transcript {
+    /// Spread the words of `text` evenly over `duration_ms`, for backends without timings
+    pub fn with_even_timings(text: String, duration_ms: u64) -> Self {
+        let words: Vec<&str> = text.split_whitespace().collect();
+        let step = duration_ms / words.len().max(1) as u64;
+        let tokens = words
+            .iter()
+            .enumerate()
+            .map(|(i, w)| Token {
+                text: w.to_string(),
+                start_ms: i as u64 * step,
+                end_ms: (i as u64 + 1) * step,
+                confidence: None,
+            })
+            .collect();
+        Self { text, tokens, duration_ms }
+    }
 }
 
 // ===== Swift transcriber wrapper =====
-use crate::platform::macos::ffi::SharedSwiftTranscriber;
+
+/// Text hints for backends that can condition on a prompt
+#[derive(Debug, Clone, Default)]
+pub struct TranscriptionPrompt {
+    /// Recently typed text, for continuation
+    pub context: Option<String>,
+    /// Domain vocabulary (names, jargon) to favour
+    pub bias_phrases: Vec<String>,
+    /// Expected language code; `None` lets the model detect it
+    pub language: Option<String>,
+}
+
+impl TranscriptionPrompt {
+    pub fn is_empty(&self) -> bool {
+        self.context.is_none() && self.bias_phrases.is_empty() && self.language.is_none()
+    }
+}
+
 use crate::config::ModelConfig;
+use crate::services::backend::{create_backend, TranscriberCapabilities, TranscriptionBackend};
 
 pub struct Transcriber {
-    swift_transcriber: SharedSwiftTranscriber,
+    backend: Arc<dyn TranscriptionBackend>,
     sample_rate: u32,
     model_config: ModelConfig,
     audio_buffer: Arc<parking_lot::Mutex<Vec<f32>>>,
+    // Prompt for the next session, for backends that accept one
+    prompt: Arc<parking_lot::Mutex<TranscriptionPrompt>>,
+    /// Longest piece transcribed in one call (`streaming.chunk_duration_ms`)
+    chunk_duration_ms: u64,
 }
 
 impl Transcriber {
     pub fn new(model_config: ModelConfig) -> VoicyResult<Self> {
-        let swift_transcriber = SharedSwiftTranscriber::new();
-
-        // Initialize with model path if provided
-        let model_path = if model_config.model_name.starts_with('/') {
-            Some(model_config.model_name.as_str())
-        } else {
-            None // Use default path
-        };
-
-        swift_transcriber.initialize(model_path).map_err(|e| {
-            VoicyError::ModelLoadFailed(format!("Swift transcriber init failed: {}", e))
-        })?;
-
-        // FluidAudio works at 16kHz
-        let sample_rate = 16000;
-        info!("Swift transcriber initialized ({}Hz)", sample_rate);
+        let backend: Arc<dyn TranscriptionBackend> = Arc::from(create_backend(&model_config)?);
+        let sample_rate = backend.sample_rate();
 
         Ok(Self {
-            swift_transcriber,
+            backend,
             sample_rate,
             model_config,
             audio_buffer: Arc::new(parking_lot::Mutex::new(Vec::with_capacity(
                 sample_rate as usize * 30,
             ))),
+            prompt: Arc::new(parking_lot::Mutex::new(TranscriptionPrompt::default())),
+            chunk_duration_ms: StreamingConfig::default().chunk_duration_ms,
         })
     }
 
+    pub fn capabilities(&self) -> TranscriberCapabilities {
+        self.backend.capabilities()
+    }
+
+    /// Backend, model and device, e.g. "Whisper ggml-base.bin (Metal)"
+    pub fn description(&self) -> String {
+        self.backend.description()
+    }
+
+    /// Prompt for the next session; ignored by backends without prompt support
+    pub fn set_prompt(&self, prompt: TranscriptionPrompt) {
+        *self.prompt.lock() = prompt;
+    }
+
     pub fn start_session(&self) -> VoicyResult<()> {
         self.audio_buffer.lock().clear();
         info!("Transcription session started (batch mode)");
@@ -352,9 +677,7 @@ impl Transcriber {
     pub fn process_audio(&self, audio: &[f32]) -> VoicyResult<()> {
         // Accumulate audio; Swift side is batch-only for now
         let mut buffer = self.audio_buffer.lock();
-        let max_amp = audio.iter().copied().map(f32::abs).fold(0.0f32, f32::max);
-        if max_amp > 1.5 {
-            let scale = 0.99 / max_amp;
+        if let Some(scale) = peak_scale(audio) {
             buffer.reserve(audio.len());
             for &sample in audio.iter() {
                 buffer.push(sample * scale);
@@ -366,6 +689,16 @@ impl Transcriber {
     }
 
     pub fn end_session(&self) -> VoicyResult<String> {
+        self.end_session_detailed().map(|t| t.text)
+    }
+
+    /// End the session and return the transcript with word timings
+    pub fn end_session_detailed(&self) -> VoicyResult<Transcript> {
+        self.end_session_with_progress(&|_, _| {})
+    }
+
+    /// Like `end_session_detailed`, reporting (chunks done, total) as long recordings are processed
+    pub fn end_session_with_progress(&self, progress: &dyn Fn(usize, usize)) -> VoicyResult<Transcript> {
         let audio = {
             let mut buffer = self.audio_buffer.lock();
             // Move out accumulated audio without cloning
@@ -374,21 +707,80 @@ impl Transcriber {
 
         if audio.is_empty() {
             info!("Transcription session ended (no audio)");
-            return Ok(String::new());
+            return Ok(Transcript::default());
         }
+        self.transcribe_recording(&audio, progress)
+    }
 
+    /// Transcribe a finished recording where it lies, with the session prompt, instead of
+    /// copying it into the session buffer first. Reads it a chunk at a time, so a recording in
+    /// a scratch file (`SampleBuffer`) is never all resident at once. Peaks should already be
+    /// limited (`limit_peaks`).
+    pub fn transcribe_recording(&self, audio: &[f32], progress: &dyn Fn(usize, usize)) -> VoicyResult<Transcript> {
         info!(
             "Processing {} samples ({}s)",
             audio.len(),
             audio.len() / self.sample_rate as usize
         );
 
-        let text = self.swift_transcriber.transcribe(&audio).map_err(|e| {
-            VoicyError::TranscriptionFailed(format!("Swift transcription failed: {}", e))
-        })?;
+        let prompt = std::mem::take(&mut *self.prompt.lock());
+        let (transcript, total) = self.transcribe_chunked(audio, &prompt, progress)?;
+        info!("Transcription session ended ({} chunks, {} timed words)", total, transcript.tokens.len());
+        Ok(transcript)
+    }
+
+    /// Transcribe a complete recording (e.g. a file) without touching the session buffer
+    pub fn transcribe_audio(&self, audio: &[f32]) -> VoicyResult<Transcript> {
+        self.transcribe_chunked(audio, &TranscriptionPrompt::default(), &|_, _| {}).map(|(t, _)| t)
+    }
+
+    /// Transcribe `audio` chunk by chunk; returns the joined transcript and the chunk count
+    fn transcribe_chunked(
+        &self,
+        audio: &[f32],
+        prompt: &TranscriptionPrompt,
+        progress: &dyn Fn(usize, usize),
+    ) -> VoicyResult<(Transcript, usize)> {
+        let max_ms = match self.capabilities().max_duration {
+            Some(limit) => self.chunk_duration_ms.min(limit.as_millis() as u64),
+            None => self.chunk_duration_ms,
+        };
+        let chunks = chunk_ranges(audio, self.sample_rate, max_ms);
+        let total = chunks.len();
+        progress(0, total);
+        let mut transcript = Transcript::default();
+        for (i, range) in chunks.into_iter().enumerate() {
+            let offset_ms = range.start as u64 * 1000 / self.sample_rate as u64;
+            let part = self.run(&audio[range], prompt)?;
+            if !part.text.is_empty() {
+                if !transcript.text.is_empty() {
+                    transcript.text.push(' ');
+                }
+                transcript.text.push_str(&part.text);
+            }
+            transcript.tokens.extend(part.tokens.into_iter().map(|mut t| {
+                t.start_ms += offset_ms;
+                t.end_ms += offset_ms;
+                t
+            }));
+            transcript.duration_ms = offset_ms + part.duration_ms;
+            progress(i + 1, total);
+        }
+        Ok((transcript, total))
+    }
+
+    /// Transcribe audio recorded so far without ending the session (streaming partials).
+    /// The prompt is left in place for the final pass.
+    pub fn transcribe_partial(&self, audio: &[f32]) -> VoicyResult<Transcript> {
+        let prompt = self.prompt.lock().clone();
+        self.run(audio, &prompt)
+    }
 
-        info!("Transcription session ended");
-        Ok(text.trim().to_string())
+    fn run(&self, audio: &[f32], prompt: &TranscriptionPrompt) -> VoicyResult<Transcript> {
+        let mut transcript = self.backend.transcribe(audio, prompt)?;
+        transcript.text = transcript.text.trim().to_string();
+        transcript.duration_ms = audio.len() as u64 * 1000 / self.sample_rate as u64;
+        Ok(transcript)
     }
 
     pub fn get_sample_rate(&self) -> u32 {
@@ -396,58 +788,391 @@ impl Transcriber {
     }
 }
 
+/// Scale that brings `audio` back under full scale when it peaks well above it
+fn peak_scale(audio: &[f32]) -> Option<f32> {
+    let max_amp = audio.iter().copied().map(f32::abs).fold(0.0f32, f32::max);
+    (max_amp > 1.5).then(|| 0.99 / max_amp)
+}
+
+/// Scale `audio` in place the way `Transcriber::process_audio` does
+pub fn limit_peaks(audio: &mut [f32]) {
+    if let Some(scale) = peak_scale(audio) {
+        audio.iter_mut().for_each(|sample| *sample *= scale);
+    }
+}
+
+/// Shortest allowed chunk; longer recordings are split so progress can be reported,
+/// but not so finely that the split search dominates
+pub(crate) const MIN_CHUNK_MS: u64 = 10_000;
+/// How far before each chunk boundary to look for a quiet split point
+const SPLIT_SEARCH_SECS: usize = 5;
+
+/// Split `audio` into chunks of at most `max_ms`, cutting at the quietest
+/// 20 ms frame near each boundary so words aren't split
+pub(crate) fn chunk_ranges(audio: &[f32], sample_rate: u32, max_ms: u64) -> Vec<std::ops::Range<usize>> {
+    let rate = sample_rate as usize;
+    let max_len = (max_ms.max(MIN_CHUNK_MS) as usize * rate) / 1000;
+    let search = SPLIT_SEARCH_SECS * rate;
+    let frame = (rate / 50).max(1);
+    let mut ranges = Vec::new();
+    let mut start = 0;
+    while audio.len() - start > max_len {
+        let window_end = start + max_len;
+        let split = (window_end - search..window_end - frame)
+            .step_by(frame)
+            .map(|i| (i, audio[i..i + frame].iter().map(|s| s * s).sum::<f32>()))
+            .min_by(|a, b| a.1.total_cmp(&b.1))
+            .map_or(window_end, |(i, _)| i + frame / 2);
+        ranges.push(start..split);
+        start = split;
+    }
+    ranges.push(start..audio.len());
+    ranges
+}
+
 impl Clone for Transcriber {
     fn clone(&self) -> Self {
         Self {
-            swift_transcriber: self.swift_transcriber.clone(),
+            backend: Arc::clone(&self.backend),
             sample_rate: self.sample_rate,
             model_config: self.model_config.clone(),
             audio_buffer: Arc::clone(&self.audio_buffer),
+            prompt: Arc::clone(&self.prompt),
+            chunk_duration_ms: self.chunk_duration_ms,
         }
     }
 }
 
 // ===== Audio processor (orchestrates capture + transcriber) =====
+use crate::services::echo::EchoCanceller;
+use crate::services::scratch::SampleBuffer;
+use crate::services::tracks::SourceTracks;
+use crate::services::vad::{create_vad, speech_range, Vad};
+
+/// Recordings longer than this (two minutes at 16 kHz) continue in a memory-mapped scratch file
+const SPILL_AFTER_SAMPLES: usize = 16000 * 120;
+
+/// Receives (chunks done, total) while a recording is transcribed
+pub type ProgressCallback = Arc<dyn Fn(usize, usize) + Send + Sync>;
+
 pub struct AudioProcessor {
     config: Config,
-    audio_capture: Option<AudioCapture>,
+    audio_capture: Option<Box<dyn AudioSource>>,
     transcriber: Option<Transcriber>,
-    audio_buffer: Vec<f32>,
+    vad: Option<Box<dyn Vad>>,
+    audio_buffer: SampleBuffer,
+    progress: Option<ProgressCallback>,
+    /// Mix system audio into the next recording (meetings)
+    want_system_audio: bool,
+    want_echo_cancellation: bool,
+    system_audio: bool,
+    /// System audio samples mixed into `audio_buffer` so far
+    system_pos: usize,
+    /// System audio captured ahead of the microphone, waiting for the samples it mixes onto
+    system_pending: Vec<f32>,
+    /// Takes the speakers' echo out of the microphone before system audio is mixed in
+    echo: Option<EchoCanceller>,
+    /// How loud each source was, for telling who spoke in the mixed recording
+    tracks: Option<SourceTracks>,
+    /// A transcriber is being loaded elsewhere; record without one meanwhile
+    model_loading: bool,
+    /// The microphone is capturing room noise for `finish_noise_calibration`
+    calibrating: bool,
 }
 
 impl AudioProcessor {
     pub fn new(config: Config) -> Self {
         // Pre-allocate buffer for 30 seconds of audio at 16kHz
         let buffer_capacity = 16000 * 30;
-        Self { config, audio_capture: None, transcriber: None, audio_buffer: Vec::with_capacity(buffer_capacity) }
+        Self {
+            config,
+            audio_capture: None,
+            transcriber: None,
+            vad: None,
+            audio_buffer: SampleBuffer::with_capacity(buffer_capacity, SPILL_AFTER_SAMPLES),
+            progress: None,
+            want_system_audio: false,
+            want_echo_cancellation: false,
+            system_audio: false,
+            system_pos: 0,
+            system_pending: Vec::new(),
+            echo: None,
+            tracks: None,
+            model_loading: false,
+            calibrating: false,
+        }
+    }
+
+    /// Use a custom audio source (e.g. `MockAudioSource`) instead of the microphone
+    pub fn with_source(config: Config, source: Box<dyn AudioSource>) -> Self {
+        let mut processor = Self::new(config);
+        processor.audio_capture = Some(source);
+        processor
     }
 
     pub fn initialize(&mut self) -> VoicyResult<()> {
         let transcriber = Transcriber::new(self.config.model.clone())?;
+        self.install_transcriber(transcriber)
+    }
+
+    /// Finish `initialize` with a transcriber loaded without holding this processor
+    /// (see `set_model_loading`)
+    pub fn install_transcriber(&mut self, mut transcriber: Transcriber) -> VoicyResult<()> {
+        transcriber.set_chunk_duration_ms(self.config.streaming.chunk_duration_ms);
         let target_sample_rate = transcriber.get_sample_rate();
-        let audio_capture = AudioCapture::new(target_sample_rate)?;
+        if self.audio_capture.is_none() {
+            self.audio_capture = Some(Box::new(AudioCapture::new(target_sample_rate, self.config.audio.input_gain)?));
+        }
+        if self.config.audio.vad.trim_silence && self.vad.is_none() {
+            self.vad = Some(create_vad(&self.config.audio.vad, target_sample_rate));
+        }
         self.transcriber = Some(transcriber);
-        self.audio_capture = Some(audio_capture);
         info!("Audio processor initialized");
         Ok(())
     }
 
+    /// Mark a transcriber as loading elsewhere: recordings then start with the microphone
+    /// alone and are transcribed once `install_transcriber` has run
+    pub fn set_model_loading(&mut self, loading: bool) {
+        self.model_loading = loading;
+    }
+
+    /// Put `transcriber` (loaded elsewhere from `model`) in place of the current one, which is
+    /// unloaded once no `RecordingJob` still transcribes with it. Refused while a recording is
+    /// being captured at a different sample rate than the new model's.
+    pub fn swap_transcriber(&mut self, mut transcriber: Transcriber, model: ModelConfig) -> Result<(), Transcriber> {
+        let rate = transcriber.get_sample_rate();
+        if let Some(ref capture) = self.audio_capture {
+            if capture.is_recording() && capture.get_sample_rate() != rate {
+                return Err(transcriber);
+            }
+            if capture.get_sample_rate() != rate {
+                // Reopened at the new rate by the next recording
+                self.audio_capture = None;
+            }
+        }
+        transcriber.set_chunk_duration_ms(self.config.streaming.chunk_duration_ms);
+        if rate != self.sample_rate() {
+            self.vad = self.config.audio.vad.trim_silence.then(|| create_vad(&self.config.audio.vad, rate));
+        }
+        let old = self.transcriber.replace(transcriber);
+        self.config.model = model;
+        self.model_loading = false;
+        drop(old);
+        info!("Switched to {}", self.model_description().unwrap_or_default());
+        Ok(())
+    }
+
+    /// Unload the current model ahead of loading `model`, for backends that hold one model per
+    /// process. Recordings meanwhile capture the microphone alone, as at launch, until
+    /// `swap_transcriber`. Returns false (changing nothing) while a recording is captured.
+    pub fn unload_transcriber(&mut self, model: ModelConfig) -> bool {
+        if self.audio_capture.as_ref().is_some_and(|capture| capture.is_recording()) {
+            return false;
+        }
+        if let Some(old) = self.transcriber.take() {
+            info!("Unloading {}", old.description());
+        }
+        self.config.model = model;
+        self.model_loading = true;
+        true
+    }
+
+    /// After a load following `unload_transcriber` failed: the next recording loads `model`
+    pub fn restore_model(&mut self, model: ModelConfig) {
+        self.config.model = model;
+        self.model_loading = false;
+    }
+
+    pub fn model_config(&self) -> &ModelConfig {
+        &self.config.model
+    }
+
+    /// The loaded model's `Transcriber::description`, if one is loaded
+    pub fn model_description(&self) -> Option<String> {
+        self.transcriber.as_ref().map(Transcriber::description)
+    }
+
     pub fn start_recording(&mut self) -> VoicyResult<()> {
-        if self.audio_capture.is_none() || self.transcriber.is_none() {
+        if self.transcriber.is_none() && !self.model_loading {
             self.initialize()?;
         }
+        if self.audio_capture.is_none() {
+            // Capture runs at the model's rate, which `sample_rate` knows before it loads
+            let capture = AudioCapture::new(self.sample_rate(), self.config.audio.input_gain)?;
+            self.audio_capture = Some(Box::new(capture));
+        }
         self.audio_buffer.clear();
         if let Some(ref mut capture) = self.audio_capture {
+            if std::mem::take(&mut self.calibrating) {
+                // The dictation wins; that room noise is thrown away
+                debug!("Noise calibration abandoned for a recording");
+                capture.stop_recording()?;
+                while !capture.read_audio(8000).is_empty() {}
+            }
             capture.start_recording()?;
         }
-        // Streaming removed: batch mode only
+        self.system_pos = 0;
+        self.system_pending.clear();
+        self.echo = None;
+        self.tracks = None;
+        if std::mem::take(&mut self.want_system_audio) {
+            let rate = self.sample_rate();
+            self.system_audio = crate::platform::macos::ffi::system_audio_start(rate);
+            if !self.system_audio {
+                warn!("System audio unavailable (Screen Recording permission?); recording the microphone only");
+            } else {
+                self.echo = self.want_echo_cancellation.then(|| EchoCanceller::new(rate));
+                self.tracks = Some(SourceTracks::new(rate));
+            }
+        }
+        // Streaming partials, if enabled, are pulled by the controller via partial_transcript
         Ok(())
     }
 
-    pub fn stop_recording(&mut self) -> VoicyResult<String> {
-        if let Some(ref mut capture) = self.audio_capture {
-            capture.stop_recording()?;
-            self.audio_buffer.clear();
+    /// Also capture what the Mac plays (the other side of a call) in the next recording, with
+    /// `cancel_echo` removing it from the microphone first so speakers aren't transcribed twice
+    pub fn set_system_audio(&mut self, enabled: bool, cancel_echo: bool) {
+        self.want_system_audio = enabled;
+        self.want_echo_cancellation = cancel_echo;
+    }
+
+    /// Apply edited audio settings (gain, VAD) without restarting
+    pub fn set_audio_config(&mut self, audio: AudioConfig) {
+        if let Some(ref capture) = self.audio_capture {
+            capture.set_gain(audio.input_gain);
+        }
+        let rate = self.transcriber.as_ref().map_or(16000, |t| t.get_sample_rate());
+        self.vad = audio.vad.trim_silence.then(|| create_vad(&audio.vad, rate));
+        self.config.audio = audio;
+    }
+
+    /// Apply edited `[streaming]` settings; the chunk length takes effect from the next transcription
+    pub fn set_streaming_config(&mut self, streaming: StreamingConfig) {
+        if let Some(ref mut transcriber) = self.transcriber {
+            transcriber.set_chunk_duration_ms(streaming.chunk_duration_ms);
+        }
+        self.config.streaming = streaming;
+    }
+
+    /// Record `duration` of microphone audio outside the dictation flow (microphone test).
+    /// Fails if a dictation is being recorded.
+    pub fn record_sample(&mut self, duration: Duration) -> VoicyResult<Vec<f32>> {
+        if self.audio_capture.is_none() || self.transcriber.is_none() {
+            self.initialize()?;
+        }
+        let Some(ref mut capture) = self.audio_capture else { return Ok(Vec::new()) };
+        if capture.is_recording() {
+            return Err(VoicyError::AudioInitFailed("A dictation is being recorded".to_string()));
+        }
+        capture.start_recording()?;
+        std::thread::sleep(duration);
+        capture.stop_recording()?;
+        let mut samples = Vec::new();
+        loop {
+            let chunk = capture.read_audio(8000);
+            if chunk.is_empty() {
+                break;
+            }
+            samples.extend_from_slice(&chunk);
+        }
+        Ok(samples)
+    }
+
+    /// Start capturing room noise so an adaptive VAD can tell speech from its background; the
+    /// caller lets it run without holding this processor, then calls `finish_noise_calibration`.
+    /// A recording started meanwhile takes the microphone over. Returns false when nothing adapts
+    /// (no silence trimming, or a detector with a fixed threshold).
+    pub fn start_noise_calibration(&mut self) -> VoicyResult<bool> {
+        if self.vad.is_none() || !self.config.audio.vad.adaptive {
+            return Ok(false);
+        }
+        if self.audio_capture.is_none() || self.transcriber.is_none() {
+            self.initialize()?;
+        }
+        let Some(ref mut capture) = self.audio_capture else { return Ok(false) };
+        if capture.is_recording() {
+            return Err(VoicyError::AudioInitFailed("A dictation is being recorded".to_string()));
+        }
+        capture.start_recording()?;
+        self.calibrating = true;
+        Ok(true)
+    }
+
+    /// Stop the capture begun by `start_noise_calibration` and calibrate from it. Returns the
+    /// threshold now in use; `None` when a recording took the microphone over in between.
+    pub fn finish_noise_calibration(&mut self) -> VoicyResult<Option<f32>> {
+        if !std::mem::take(&mut self.calibrating) {
+            return Ok(None);
+        }
+        let Some(ref mut capture) = self.audio_capture else { return Ok(None) };
+        capture.stop_recording()?;
+        let mut ambient = Vec::new();
+        loop {
+            let chunk = capture.read_audio(8000);
+            if chunk.is_empty() {
+                break;
+            }
+            ambient.extend_from_slice(&chunk);
+        }
+        Ok(self.vad.as_mut().and_then(|vad| vad.calibrate(&ambient)))
+    }
+
+    /// Transcribe `samples` with the loaded model, e.g. a microphone test recording
+    pub fn transcribe_samples(&self, samples: &[f32]) -> VoicyResult<Transcript> {
+        match self.transcriber {
+            // Leaves the session buffer of any dictation alone
+            Some(ref transcriber) => transcriber.transcribe_partial(samples),
+            None => Ok(Transcript::default()),
+        }
+    }
+
+    /// Transcribe a WAV file with the loaded model, resampled to the model's rate
+    pub fn transcribe_file(&mut self, path: &std::path::Path) -> VoicyResult<Transcript> {
+        if self.transcriber.is_none() {
+            self.initialize()?;
+        }
+        let wav = crate::services::wav::read_wav_file(path)?;
+        let samples = crate::services::wav::resample_linear(&wav.samples, wav.sample_rate, self.sample_rate());
+        match self.transcriber {
+            Some(ref transcriber) => transcriber.transcribe_audio(&samples),
+            None => Ok(Transcript::default()),
+        }
+    }
+
+    pub fn audio_config(&self) -> &AudioConfig {
+        &self.config.audio
+    }
+
+    pub fn sample_rate(&self) -> u32 {
+        self.transcriber.as_ref().map_or(16000, |t| t.get_sample_rate())
+    }
+
+    /// Report transcription progress of each recording to `callback`
+    pub fn set_progress_callback(&mut self, callback: ProgressCallback) {
+        self.progress = Some(callback);
+    }
+
+    /// Input device opened by the current/last recording
+    pub fn input_device(&self) -> Option<InputDeviceInfo> {
+        self.audio_capture.as_ref().and_then(|c| c.input_device())
+    }
+
+    /// Set the prompt for the next transcription, if the backend supports one
+    pub fn set_prompt(&self, prompt: TranscriptionPrompt) {
+        if let Some(ref transcriber) = self.transcriber {
+            if transcriber.capabilities().prompt {
+                transcriber.set_prompt(prompt);
+            } else if !prompt.is_empty() {
+                debug!("Backend does not accept a prompt; context/bias skipped");
+            }
+        }
+    }
+
+    /// Move captured audio into the recording buffer; returns the buffered duration in ms.
+    /// The capture ring buffer holds 30 s, so long recordings must call this periodically.
+    pub fn drain_capture(&mut self) -> u64 {
+        if let Some(ref capture) = self.audio_capture {
             loop {
                 let chunk = capture.read_audio(8000);
                 if chunk.is_empty() {
@@ -455,22 +1180,232 @@ impl AudioProcessor {
                 }
                 self.audio_buffer.extend_from_slice(&chunk);
             }
-            if !self.audio_buffer.is_empty() {
-                info!(
-                    "Processing {} samples ({}s @ 16kHz)",
-                    self.audio_buffer.len(),
-                    self.audio_buffer.len() / 16000
-                );
-                if let Some(ref transcriber) = self.transcriber {
-                    transcriber.start_session()?;
-                    transcriber.process_audio(&self.audio_buffer)?;
-                    let final_text = transcriber.end_session()?;
-                    return Ok(final_text.trim().to_string());
+        }
+        if self.system_audio {
+            self.mix_system_audio();
+        }
+        let rate = self.transcriber.as_ref().map_or(16000, |t| t.get_sample_rate()) as u64;
+        self.audio_buffer.len() as u64 * 1000 / rate
+    }
+
+    /// Add captured system audio onto the microphone samples recorded at the same time.
+    /// Both streams start together at the same rate, so sample positions line up; system audio
+    /// that arrives first waits for the microphone to catch up.
+    fn mix_system_audio(&mut self) {
+        loop {
+            let chunk = crate::platform::macos::ffi::system_audio_read(8000);
+            if chunk.is_empty() {
+                break;
+            }
+            self.system_pending.extend_from_slice(&chunk);
+        }
+        let ready = self.audio_buffer.len().saturating_sub(self.system_pos).min(self.system_pending.len());
+        for sample in self.system_pending.drain(..ready) {
+            let mic = &mut self.audio_buffer[self.system_pos];
+            let cleaned = match self.echo {
+                Some(ref mut echo) => echo.process(*mic, sample),
+                None => *mic,
+            };
+            *mic = (cleaned + sample).clamp(-1.0, 1.0);
+            if let Some(ref mut tracks) = self.tracks {
+                tracks.add(self.system_pos, cleaned, sample);
+            }
+            self.system_pos += 1;
+        }
+    }
+
+    /// Transcript of everything recorded so far, once at least `min_audio_ms` is buffered
+    pub fn partial_transcript(&mut self, min_audio_ms: u64) -> VoicyResult<Option<Transcript>> {
+        if self.drain_capture() < min_audio_ms {
+            return Ok(None);
+        }
+        match self.transcriber {
+            Some(ref transcriber) => transcriber.transcribe_partial(&self.audio_buffer).map(Some),
+            None => Ok(None),
+        }
+    }
+
+    /// Audio of the last recording as it was transcribed (after silence trimming),
+    /// until the next recording starts
+    pub fn last_recording(&self) -> &[f32] {
+        &self.audio_buffer
+    }
+
+    /// How loud the microphone and system audio each were in the last recording, when system
+    /// audio was mixed into it
+    pub fn source_tracks(&self) -> Option<&SourceTracks> {
+        self.tracks.as_ref()
+    }
+
+    pub fn stop_recording(&mut self) -> VoicyResult<String> {
+        self.stop_recording_detailed().map(|t| t.text)
+    }
+
+    /// Stop capture and transcribe, keeping word timings
+    pub fn stop_recording_detailed(&mut self) -> VoicyResult<Transcript> {
+        self.stop_capture()?;
+        self.transcribe_recording()
+    }
+
+    /// Stop capture and collect the recording, without transcribing it yet
+    pub fn stop_capture(&mut self) -> VoicyResult<()> {
+        let Some(ref mut capture) = self.audio_capture else { return Ok(()) };
+        capture.stop_recording()?;
+        // Keeps audio already drained for streaming partials
+        self.drain_capture();
+        if std::mem::take(&mut self.system_audio) {
+            crate::platform::macos::ffi::system_audio_stop();
+            // System audio from after the microphone stopped has nothing to mix onto
+            for &sample in &self.system_pending {
+                if let Some(ref mut tracks) = self.tracks {
+                    tracks.add(self.system_pos, 0.0, sample);
                 }
+                self.system_pos += 1;
+            }
+            self.audio_buffer.extend_from_slice(&self.system_pending);
+            self.system_pending.clear();
+            self.echo = None;
+        }
+        if let Some(ref mut vad) = self.vad {
+            let before = self.audio_buffer.len();
+            let speech = speech_range(vad.as_mut(), &self.audio_buffer);
+            if let Some(ref mut tracks) = self.tracks {
+                tracks.trim_start(speech.start);
             }
+            self.audio_buffer.retain_range(speech);
+            debug!("VAD trimmed {} -> {} samples", before, self.audio_buffer.len());
         }
-        Ok(String::new())
+        Ok(())
+    }
+
+    /// Transcribe the recording collected by `stop_capture`
+    pub fn transcribe_recording(&mut self) -> VoicyResult<Transcript> {
+        transcribe_stopped(self.transcriber.as_ref(), &mut self.audio_buffer, self.progress.as_ref())
+    }
+
+    /// Hand the recording collected by `stop_capture` out with the loaded model, to be
+    /// transcribed without holding this processor. `last_recording` is empty afterwards.
+    pub fn take_recording(&mut self) -> RecordingJob {
+        let empty = SampleBuffer::with_capacity(self.sample_rate() as usize * 30, SPILL_AFTER_SAMPLES);
+        RecordingJob {
+            audio: std::mem::replace(&mut self.audio_buffer, empty),
+            transcriber: self.transcriber.clone(),
+            progress: self.progress.clone(),
+            sample_rate: self.sample_rate(),
+            tracks: self.tracks.take(),
+        }
+    }
+}
+
+/// A stopped recording taken out of the `AudioProcessor` (`take_recording`), so a model that
+/// hangs on it holds up only this job, not the next recording
+pub struct RecordingJob {
+    audio: SampleBuffer,
+    transcriber: Option<Transcriber>,
+    progress: Option<ProgressCallback>,
+    sample_rate: u32,
+    tracks: Option<SourceTracks>,
+}
+
+impl RecordingJob {
+    /// Transcribe the recording with the prompt set before it was taken
+    pub fn transcribe(&mut self) -> VoicyResult<Transcript> {
+        transcribe_stopped(self.transcriber.as_ref(), &mut self.audio, self.progress.as_ref())
+    }
+
+    /// The recording as transcribed (after silence trimming)
+    pub fn recording(&self) -> &[f32] {
+        &self.audio
+    }
+
+    pub fn sample_rate(&self) -> u32 {
+        self.sample_rate
+    }
+
+    /// See `AudioProcessor::source_tracks`
+    pub fn source_tracks(&self) -> Option<&SourceTracks> {
+        self.tracks.as_ref()
     }
 }
 
-pub type ImprovedAudioProcessor = AudioProcessor;
+fn transcribe_stopped(
+    transcriber: Option<&Transcriber>,
+    audio: &mut SampleBuffer,
+    progress: Option<&ProgressCallback>,
+) -> VoicyResult<Transcript> {
+    if audio.is_empty() {
+        return Ok(Transcript::default());
+    }
+    info!("Processing {} samples ({}s @ 16kHz)", audio.len(), audio.len() / 16000);
+    let Some(transcriber) = transcriber else {
+        warn!("No model loaded; recording not transcribed");
+        return Ok(Transcript::default());
+    };
+    limit_peaks(audio);
+    match progress {
+        Some(progress) => transcriber.transcribe_recording(audio, progress.as_ref()),
+        None => transcriber.transcribe_recording(audio, &|_, _| {}),
+    }
+}
+
+#[cfg(all(test, feature = "mock"))]
+mod tests {
+    use super::*;
+    use crate::config::TranscriberBackend;
+
+    fn fixture_processor(responses: &[&str]) -> AudioProcessor {
+        let path = std::path::Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures/tone-16k-mono.wav");
+        let source = MockAudioSource::from_wav_file(&path, 16000).unwrap();
+        let mut config = Config::default();
+        config.model.backend = TranscriberBackend::Mock;
+        config.model.preset = None;
+        config.model.mock.responses = responses.iter().map(|r| r.to_string()).collect();
+        AudioProcessor::with_source(config, Box::new(source))
+    }
+
+    #[test]
+    fn record_then_transcribe_fixture() {
+        let mut processor = fixture_processor(&["hello world"]);
+        processor.start_recording().unwrap();
+        assert_eq!(processor.drain_capture(), 500);
+
+        let transcript = processor.stop_recording_detailed().unwrap();
+        assert_eq!(transcript.text, "hello world");
+        assert_eq!(transcript.duration_ms, 500);
+        let words: Vec<_> = transcript.tokens.iter().map(|t| t.text.as_str()).collect();
+        assert_eq!(words, ["hello", "world"]);
+        assert_eq!(processor.last_recording().len(), 8000);
+    }
+
+    #[test]
+    fn each_recording_replays_the_fixture() {
+        let mut processor = fixture_processor(&[]);
+        for _ in 0..2 {
+            processor.start_recording().unwrap();
+            assert_eq!(processor.stop_recording().unwrap(), "[mock] 0.5s of audio");
+        }
+    }
+
+    #[test]
+    fn recording_takes_the_microphone_from_noise_calibration() {
+        let mut processor = fixture_processor(&["hello"]);
+        processor.config.audio.vad.trim_silence = true;
+        processor.config.audio.vad.adaptive = true;
+        processor.initialize().unwrap();
+        assert!(processor.start_noise_calibration().unwrap());
+
+        processor.start_recording().unwrap();
+        assert_eq!(processor.finish_noise_calibration().unwrap(), None);
+        assert_eq!(processor.stop_recording().unwrap(), "hello");
+    }
+
+    #[test]
+    fn nothing_recorded_transcribes_to_nothing() {
+        let mut config = Config::default();
+        config.model.backend = TranscriberBackend::Mock;
+        let mut processor = AudioProcessor::with_source(config, Box::new(MockAudioSource::new(Vec::new(), 16000)));
+        processor.start_recording().unwrap();
+        let transcript = processor.stop_recording_detailed().unwrap();
+        assert!(transcript.text.is_empty() && transcript.tokens.is_empty());
+    }
+}
@@ -0,0 +1,592 @@
+//! Speech-to-text engines behind one trait. Each is compiled in with its Cargo feature
+//! (`swift`, `whisper`, `mlx`, `remote`, `mock`) so minimal builds can leave engines out; `create_backend`
+//! builds the one named in `[model] backend`.
+
+use crate::config::{ModelConfig, TranscriberBackend, SUPPORTED_LANGUAGES};
+use crate::error::VoicyResult;
+use crate::services::audio::{Transcript, TranscriptionPrompt};
+use crate::services::models::resolve_preset;
+use std::time::Duration;
+use tracing::info;
+
+/// A transcription engine. Audio is mono f32 at `sample_rate()`.
+pub trait TranscriptionBackend: Send + Sync {
+    /// Short name for logs
+    fn name(&self) -> &'static str;
+
+    /// Name plus model and device, for logs and Preferences
+    fn description(&self) -> String {
+        self.name().to_string()
+    }
+
+    fn sample_rate(&self) -> u32 {
+        16000
+    }
+
+    fn capabilities(&self) -> TranscriberCapabilities;
+
+    /// Transcribe one piece of audio, no longer than `capabilities().max_duration`.
+    /// Backends without prompt support ignore `prompt`.
+    fn transcribe(&self, audio: &[f32], prompt: &TranscriptionPrompt) -> VoicyResult<Transcript>;
+}
+
+/// Build and load the backend configured in `config`, its model preset resolved first
+pub fn create_backend(config: &ModelConfig) -> VoicyResult<Box<dyn TranscriptionBackend>> {
+    let config = &resolve_preset(config)?;
+    let backend = match config.backend {
+        // MLX always runs out of process; the mock has no model to crash
+        TranscriberBackend::Swift | TranscriberBackend::Whisper if config.isolate => worker::create(config)?,
+        TranscriberBackend::Swift => swift::create(config)?,
+        TranscriberBackend::Mock => mock::create(config)?,
+        TranscriberBackend::Whisper => whisper::create(config)?,
+        TranscriberBackend::Mlx => mlx::create(config)?,
+        TranscriberBackend::Remote => remote::create(config)?,
+    };
+    info!("{} transcriber initialized ({}Hz)", backend.description(), backend.sample_rate());
+    Ok(backend)
+}
+
+/// What a transcription backend can do, so the controller and Preferences can skip or
+/// grey out options it doesn't support instead of failing mid-dictation
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TranscriberCapabilities {
+    /// Fast enough to re-transcribe the recording so far for streaming partials
+    pub streaming: bool,
+    /// Transcripts carry per-word timings
+    pub timestamps: bool,
+    /// Language codes recognised; empty means any
+    pub languages: Vec<&'static str>,
+    /// Uses `TranscriptionPrompt` (context, bias phrases, language hint)
+    pub prompt: bool,
+    /// Labels who is speaking
+    pub diarization: bool,
+    /// Longest audio taken in one call; longer recordings are chunked below it
+    pub max_duration: Option<Duration>,
+}
+
+impl TranscriberCapabilities {
+    /// Capabilities of `backend`, known without loading its model (or building it in)
+    pub fn of(backend: TranscriberBackend) -> Self {
+        match backend {
+            TranscriberBackend::Swift => Self {
+                streaming: true,
+                timestamps: true,
+                languages: SUPPORTED_LANGUAGES.iter().map(|(code, _)| *code).collect(),
+                // FluidAudio's Parakeet API has no prompt input
+                prompt: false,
+                diarization: false,
+                max_duration: None,
+            },
+            // Re-transcribing the whole recording for each partial is too slow for Whisper
+            TranscriberBackend::Whisper => Self {
+                streaming: false,
+                timestamps: true,
+                languages: Vec::new(),
+                prompt: true,
+                diarization: false,
+                max_duration: None,
+            },
+            // The same Parakeet TDT v3 model as the Swift backend
+            TranscriberBackend::Mlx => Self {
+                streaming: true,
+                timestamps: true,
+                languages: SUPPORTED_LANGUAGES.iter().map(|(code, _)| *code).collect(),
+                prompt: false,
+                diarization: false,
+                max_duration: None,
+            },
+            // Whatever the other machine runs; prompts are passed on. Sending the whole recording
+            // over the network for every partial is too slow for streaming
+            TranscriberBackend::Remote => Self {
+                streaming: false,
+                timestamps: true,
+                languages: Vec::new(),
+                prompt: true,
+                diarization: false,
+                max_duration: None,
+            },
+            TranscriberBackend::Mock => Self {
+                streaming: true,
+                timestamps: true,
+                languages: Vec::new(),
+                prompt: true,
+                diarization: false,
+                max_duration: None,
+            },
+        }
+    }
+
+    pub fn supports_language(&self, code: &str) -> bool {
+        self.languages.is_empty() || self.languages.iter().any(|l| l.eq_ignore_ascii_case(code))
+    }
+}
+
+// ===== Core ML (FluidAudio) via the Swift bridge =====
+
+#[cfg(feature = "swift")]
+mod swift {
+    use super::{TranscriberCapabilities, TranscriptionBackend};
+    use crate::config::{ModelConfig, TranscriberBackend};
+    use crate::error::{VoicyError, VoicyResult};
+    use crate::platform::macos::ffi::SharedSwiftTranscriber;
+    use crate::services::audio::{Transcript, TranscriptionPrompt};
+
+    struct SwiftBackend {
+        transcriber: SharedSwiftTranscriber,
+    }
+
+    pub fn create(config: &ModelConfig) -> VoicyResult<Box<dyn TranscriptionBackend>> {
+        let transcriber = SharedSwiftTranscriber::new();
+        // An absolute model_name is a model directory; anything else uses the default
+        let model_path = config.model_name.starts_with('/').then_some(config.model_name.as_str());
+        transcriber
+            .initialize(model_path)
+            .map_err(|e| VoicyError::ModelLoadFailed(format!("Swift transcriber init failed: {}", e)))?;
+        Ok(Box::new(SwiftBackend { transcriber }))
+    }
+
+    impl TranscriptionBackend for SwiftBackend {
+        fn name(&self) -> &'static str {
+            "Swift"
+        }
+
+        fn capabilities(&self) -> TranscriberCapabilities {
+            TranscriberCapabilities::of(TranscriberBackend::Swift)
+        }
+
+        fn transcribe(&self, audio: &[f32], _prompt: &TranscriptionPrompt) -> VoicyResult<Transcript> {
+            self.transcriber
+                .transcribe_with_timings(audio)
+                .map_err(|e| VoicyError::TranscriptionFailed(format!("Swift transcription failed: {}", e)))
+        }
+    }
+}
+
+#[cfg(not(feature = "swift"))]
+mod swift {
+    use super::TranscriptionBackend;
+    use crate::config::ModelConfig;
+    use crate::error::{VoicyError, VoicyResult};
+
+    pub fn create(_config: &ModelConfig) -> VoicyResult<Box<dyn TranscriptionBackend>> {
+        Err(VoicyError::ModelLoadFailed("built without the `swift` feature".to_string()))
+    }
+}
+
+// ===== whisper.cpp (GGML models), optionally on the GPU through Metal =====
+
+#[cfg(feature = "whisper")]
+mod whisper {
+    use super::{TranscriberCapabilities, TranscriptionBackend};
+    use crate::config::{ModelConfig, TranscriberBackend};
+    use crate::error::{VoicyError, VoicyResult};
+    use crate::services::audio::{Token, Transcript, TranscriptionPrompt};
+    use crate::services::models::{prepare_coreml_encoder, CoreMlEncoder};
+    use parking_lot::Mutex;
+    use tracing::{info, warn};
+    use whisper_rs::{FullParams, SamplingStrategy, WhisperContext, WhisperContextParameters, WhisperState};
+
+    struct WhisperBackend {
+        state: Mutex<WhisperState>,
+        model: String,
+        metal: bool,
+        coreml: bool,
+        threads: usize,
+    }
+
+    pub fn create(config: &ModelConfig) -> VoicyResult<Box<dyn TranscriptionBackend>> {
+        let settings = &config.whisper;
+        let path = settings.model_path.as_ref().ok_or_else(|| {
+            VoicyError::ModelLoadFailed("set [model.whisper] model_path to a GGML model file".to_string())
+        })?;
+        if !path.is_file() {
+            return Err(VoicyError::ModelLoadFailed(format!("Whisper model {} not found", path.display())));
+        }
+        // Metal is compiled in with `whisper-metal`; without it whisper.cpp only has the CPU
+        let metal = settings.use_metal && cfg!(feature = "whisper-metal");
+        if settings.use_metal && !metal {
+            warn!("Whisper: use_metal is set but this build has no Metal support; using the CPU");
+        }
+        // With `whisper-coreml`, whisper.cpp runs the encoder from a .mlmodelc next to the model
+        // if there is one, and the GGML encoder otherwise
+        let coreml = cfg!(feature = "whisper-coreml")
+            && match prepare_coreml_encoder(path) {
+                CoreMlEncoder::Ready(encoder) => {
+                    info!("Whisper: Core ML encoder {}", encoder.display());
+                    true
+                }
+                CoreMlEncoder::Missing => false,
+                CoreMlEncoder::Failed(reason) => {
+                    warn!("Whisper: Core ML encoder unusable ({}); using the GGML encoder", reason);
+                    false
+                }
+            };
+        let mut params = WhisperContextParameters::default();
+        params.use_gpu(metal);
+        let context = WhisperContext::new_with_params(&path.to_string_lossy(), params)
+            .map_err(|e| VoicyError::ModelLoadFailed(format!("Whisper model {}: {}", path.display(), e)))?;
+        let state = context
+            .create_state()
+            .map_err(|e| VoicyError::ModelLoadFailed(format!("Whisper state: {}", e)))?;
+        let threads = match settings.threads {
+            0 => std::thread::available_parallelism().map_or(4, |n| n.get().min(8)),
+            n => n,
+        };
+        let model = path.file_name().map_or_else(String::new, |name| name.to_string_lossy().into_owned());
+        Ok(Box::new(WhisperBackend { state: Mutex::new(state), model, metal, coreml, threads }))
+    }
+
+    impl WhisperBackend {
+        /// Words with timings, joining sub-word tokens and skipping special ones
+        fn words(state: &WhisperState) -> Result<Vec<Token>, whisper_rs::WhisperError> {
+            let mut words: Vec<Token> = Vec::new();
+            for segment in 0..state.full_n_segments()? {
+                for i in 0..state.full_n_tokens(segment)? {
+                    let text = state.full_get_token_text(segment, i)?;
+                    if text.starts_with("[_") || text.starts_with("<|") {
+                        continue;
+                    }
+                    let data = state.full_get_token_data(segment, i)?;
+                    // Timings are in centiseconds
+                    let (start_ms, end_ms) = (data.t0.max(0) as u64 * 10, data.t1.max(0) as u64 * 10);
+                    match words.last_mut() {
+                        Some(word) if !text.starts_with(' ') => {
+                            word.text.push_str(&text);
+                            word.end_ms = end_ms;
+                            word.confidence = word.confidence.map(|c| c.min(data.p));
+                        }
+                        _ => words.push(Token {
+                            text: text.trim().to_string(),
+                            start_ms,
+                            end_ms,
+                            confidence: Some(data.p),
+                        }),
+                    }
+                }
+            }
+            words.retain(|word| !word.text.is_empty());
+            Ok(words)
+        }
+    }
+
+    impl TranscriptionBackend for WhisperBackend {
+        fn name(&self) -> &'static str {
+            "Whisper"
+        }
+
+        fn description(&self) -> String {
+            let mut device = if self.metal { "Metal".to_string() } else { format!("CPU, {} threads", self.threads) };
+            if self.coreml {
+                device.push_str(", Core ML encoder");
+            }
+            format!("Whisper {} ({})", self.model, device)
+        }
+
+        fn capabilities(&self) -> TranscriberCapabilities {
+            TranscriberCapabilities::of(TranscriberBackend::Whisper)
+        }
+
+        fn transcribe(&self, audio: &[f32], prompt: &TranscriptionPrompt) -> VoicyResult<Transcript> {
+            let err = |e: whisper_rs::WhisperError| VoicyError::TranscriptionFailed(format!("Whisper: {}", e));
+            let mut params = FullParams::new(SamplingStrategy::Greedy { best_of: 1 });
+            params.set_n_threads(self.threads as i32);
+            params.set_language(Some(prompt.language.as_deref().unwrap_or("auto")));
+            params.set_token_timestamps(true);
+            params.set_print_progress(false);
+            params.set_print_realtime(false);
+            params.set_print_special(false);
+            params.set_print_timestamps(false);
+            // Whisper continues from its prompt, so vocabulary goes ahead of the typed context
+            let initial_prompt = [prompt.bias_phrases.join(", "), prompt.context.clone().unwrap_or_default()]
+                .into_iter()
+                .filter(|part| !part.is_empty())
+                .collect::<Vec<_>>()
+                .join(". ");
+            if !initial_prompt.is_empty() {
+                params.set_initial_prompt(&initial_prompt);
+            }
+
+            let mut state = self.state.lock();
+            state.full(params, audio).map_err(err)?;
+            let mut text = String::new();
+            for segment in 0..state.full_n_segments().map_err(err)? {
+                text.push_str(&state.full_get_segment_text(segment).map_err(err)?);
+            }
+            let tokens = Self::words(&state).map_err(err)?;
+            let duration_ms = audio.len() as u64 * 1000 / self.sample_rate() as u64;
+            Ok(Transcript { text: text.trim().to_string(), tokens, duration_ms })
+        }
+    }
+}
+
+#[cfg(not(feature = "whisper"))]
+mod whisper {
+    use super::TranscriptionBackend;
+    use crate::config::ModelConfig;
+    use crate::error::{VoicyError, VoicyResult};
+
+    pub fn create(_config: &ModelConfig) -> VoicyResult<Box<dyn TranscriptionBackend>> {
+        Err(VoicyError::ModelLoadFailed("built without the `whisper` feature".to_string()))
+    }
+}
+
+// ===== Parakeet on MLX, served by a separate Python process =====
+
+#[cfg(feature = "mlx")]
+mod mlx {
+    use super::{TranscriberCapabilities, TranscriptionBackend};
+    use crate::config::{ModelConfig, TranscriberBackend};
+    use crate::error::{VoicyError, VoicyResult};
+    use crate::services::audio::{Transcript, TranscriptionPrompt};
+    use crate::services::supervisor::{Supervisor, WorkerCommand};
+    use std::sync::Arc;
+    use std::time::Duration;
+
+    /// Written to a temp file and run with the configured Python, so nothing needs installing
+    /// beyond parakeet-mlx itself
+    const SERVER_SCRIPT: &str = include_str!("../../tools/parakeet_mlx_server.py");
+
+    struct MlxBackend {
+        server: Arc<Supervisor>,
+        model: String,
+    }
+
+    pub fn create(config: &ModelConfig) -> VoicyResult<Box<dyn TranscriptionBackend>> {
+        let script = std::env::temp_dir().join("typeswift-parakeet-mlx-server.py");
+        std::fs::write(&script, SERVER_SCRIPT)
+            .map_err(|e| VoicyError::ModelLoadFailed(format!("MLX server: {}: {}", script.display(), e)))?;
+        let server = Supervisor::start(WorkerCommand {
+            name: "MLX server".to_string(),
+            program: config.mlx.python.clone().into(),
+            args: vec![script.into(), "--model".into(), config.model_name.clone().into()],
+            startup_timeout: Duration::from_secs(config.mlx.startup_timeout_secs),
+        })?;
+        Ok(Box::new(MlxBackend { server, model: config.model_name.clone() }))
+    }
+
+    impl TranscriptionBackend for MlxBackend {
+        fn name(&self) -> &'static str {
+            "MLX"
+        }
+
+        fn description(&self) -> String {
+            match self.server.pid() {
+                Some(pid) => format!("Parakeet MLX {} (server pid {})", self.model, pid),
+                None => format!("Parakeet MLX {} (server restarting)", self.model),
+            }
+        }
+
+        fn capabilities(&self) -> TranscriberCapabilities {
+            TranscriberCapabilities::of(TranscriberBackend::Mlx)
+        }
+
+        fn transcribe(&self, audio: &[f32], prompt: &TranscriptionPrompt) -> VoicyResult<Transcript> {
+            self.server.transcribe(audio, self.sample_rate(), prompt)
+        }
+    }
+}
+
+#[cfg(not(feature = "mlx"))]
+mod mlx {
+    use super::TranscriptionBackend;
+    use crate::config::ModelConfig;
+    use crate::error::{VoicyError, VoicyResult};
+
+    pub fn create(_config: &ModelConfig) -> VoicyResult<Box<dyn TranscriptionBackend>> {
+        Err(VoicyError::ModelLoadFailed("built without the `mlx` feature".to_string()))
+    }
+}
+
+// ===== Any backend in a supervised `typeswift backend-worker` process (`[model] isolate`) =====
+
+mod worker {
+    use super::{TranscriberCapabilities, TranscriptionBackend};
+    use crate::config::{ModelConfig, TranscriberBackend};
+    use crate::error::{VoicyError, VoicyResult};
+    use crate::services::audio::{Transcript, TranscriptionPrompt};
+    use crate::services::supervisor::{Supervisor, WorkerCommand};
+    use std::sync::Arc;
+    use std::time::Duration;
+
+    /// The Swift backend downloads its model on first use
+    const STARTUP_TIMEOUT: Duration = Duration::from_secs(300);
+
+    struct WorkerBackend {
+        worker: Arc<Supervisor>,
+        kind: TranscriberBackend,
+    }
+
+    pub fn create(config: &ModelConfig) -> VoicyResult<Box<dyn TranscriptionBackend>> {
+        let exe = std::env::current_exe()
+            .map_err(|e| VoicyError::ModelLoadFailed(format!("backend worker: {}", e)))?;
+        // The worker reads the same config file and loads the backend in-process
+        let worker = Supervisor::start(WorkerCommand {
+            name: format!("{:?} worker", config.backend),
+            program: exe.into(),
+            args: vec!["backend-worker".into()],
+            startup_timeout: STARTUP_TIMEOUT,
+        })?;
+        Ok(Box::new(WorkerBackend { worker, kind: config.backend }))
+    }
+
+    impl TranscriptionBackend for WorkerBackend {
+        fn name(&self) -> &'static str {
+            match self.kind {
+                TranscriberBackend::Swift => "Swift",
+                TranscriberBackend::Whisper => "Whisper",
+                TranscriberBackend::Mlx => "MLX",
+                TranscriberBackend::Remote => "Remote",
+                TranscriberBackend::Mock => "Mock",
+            }
+        }
+
+        fn description(&self) -> String {
+            match self.worker.pid() {
+                Some(pid) => format!("{} (worker pid {})", self.name(), pid),
+                None => format!("{} (worker restarting)", self.name()),
+            }
+        }
+
+        fn capabilities(&self) -> TranscriberCapabilities {
+            TranscriberCapabilities::of(self.kind)
+        }
+
+        fn transcribe(&self, audio: &[f32], prompt: &TranscriptionPrompt) -> VoicyResult<Transcript> {
+            self.worker.transcribe(audio, self.sample_rate(), prompt)
+        }
+    }
+}
+
+// ===== Remote (a `typeswift backend-worker --listen` on another machine) =====
+
+#[cfg(feature = "remote")]
+mod remote {
+    use super::{TranscriberCapabilities, TranscriptionBackend};
+    use crate::config::{ModelConfig, TranscriberBackend};
+    use crate::error::VoicyResult;
+    use crate::services::audio::{Transcript, TranscriptionPrompt};
+    use crate::services::supervisor::RemoteWorker;
+    use std::time::Duration;
+
+    struct RemoteBackend {
+        worker: RemoteWorker,
+    }
+
+    pub fn create(config: &ModelConfig) -> VoicyResult<Box<dyn TranscriptionBackend>> {
+        let timeout = Duration::from_secs(config.remote.timeout_secs.max(1));
+        let remote = &config.remote;
+        let worker = RemoteWorker::connect(&remote.address, remote.token.clone(), remote.codec, timeout)?;
+        Ok(Box::new(RemoteBackend { worker }))
+    }
+
+    impl TranscriptionBackend for RemoteBackend {
+        fn name(&self) -> &'static str {
+            "Remote"
+        }
+
+        fn description(&self) -> String {
+            format!("Remote ({})", self.worker.address())
+        }
+
+        fn capabilities(&self) -> TranscriberCapabilities {
+            TranscriberCapabilities::of(TranscriberBackend::Remote)
+        }
+
+        fn transcribe(&self, audio: &[f32], prompt: &TranscriptionPrompt) -> VoicyResult<Transcript> {
+            self.worker.transcribe(audio, self.sample_rate(), prompt)
+        }
+    }
+}
+
+#[cfg(not(feature = "remote"))]
+mod remote {
+    use super::TranscriptionBackend;
+    use crate::config::ModelConfig;
+    use crate::error::{VoicyError, VoicyResult};
+
+    pub fn create(_config: &ModelConfig) -> VoicyResult<Box<dyn TranscriptionBackend>> {
+        Err(VoicyError::ModelLoadFailed("built without the `remote` feature".to_string()))
+    }
+}
+
+// ===== Mock (scripted responses; no model required) =====
+
+#[cfg(feature = "mock")]
+mod mock {
+    use super::{TranscriberCapabilities, TranscriptionBackend};
+    use crate::config::{MockBackendConfig, ModelConfig, TranscriberBackend};
+    use crate::error::VoicyResult;
+    use crate::services::audio::{Transcript, TranscriptionPrompt};
+    use std::sync::Arc;
+
+    struct MockTranscriber {
+        responses: Arc<Vec<String>>,
+        next: Arc<parking_lot::Mutex<usize>>,
+        delay: std::time::Duration,
+    }
+
+    pub fn create(config: &ModelConfig) -> VoicyResult<Box<dyn TranscriptionBackend>> {
+        Ok(Box::new(MockTranscriber::new(&config.mock)))
+    }
+
+    impl MockTranscriber {
+        fn new(config: &MockBackendConfig) -> Self {
+            Self {
+                responses: Arc::new(config.responses.clone()),
+                next: Arc::new(parking_lot::Mutex::new(0)),
+                delay: std::time::Duration::from_millis(config.delay_ms),
+            }
+        }
+
+        fn respond(&self, samples: &[f32], sample_rate: u32, prompt: &TranscriptionPrompt) -> String {
+            if !self.delay.is_zero() {
+                std::thread::sleep(self.delay);
+            }
+            if self.responses.is_empty() {
+                let secs = samples.len() as f32 / sample_rate as f32;
+                if prompt.is_empty() {
+                    return format!("[mock] {:.1}s of audio", secs);
+                }
+                let context_words = prompt.context.as_deref().map_or(0, |c| c.split_whitespace().count());
+                return format!(
+                    "[mock] {:.1}s of audio (context: {} words, bias: {} phrases, language: {})",
+                    secs,
+                    context_words,
+                    prompt.bias_phrases.len(),
+                    prompt.language.as_deref().unwrap_or("auto")
+                );
+            }
+            let mut next = self.next.lock();
+            let text = self.responses[*next % self.responses.len()].clone();
+            *next = next.wrapping_add(1);
+            text
+        }
+    }
+
+    impl TranscriptionBackend for MockTranscriber {
+        fn name(&self) -> &'static str {
+            "Mock"
+        }
+
+        fn capabilities(&self) -> TranscriberCapabilities {
+            TranscriberCapabilities::of(TranscriberBackend::Mock)
+        }
+
+        fn transcribe(&self, audio: &[f32], prompt: &TranscriptionPrompt) -> VoicyResult<Transcript> {
+            let rate = self.sample_rate();
+            let duration_ms = audio.len() as u64 * 1000 / rate as u64;
+            Ok(Transcript::with_even_timings(self.respond(audio, rate, prompt), duration_ms))
+        }
+    }
+}
+
+#[cfg(not(feature = "mock"))]
+mod mock {
+    use super::TranscriptionBackend;
+    use crate::config::ModelConfig;
+    use crate::error::{VoicyError, VoicyResult};
+
+    pub fn create(_config: &ModelConfig) -> VoicyResult<Box<dyn TranscriptionBackend>> {
+        Err(VoicyError::ModelLoadFailed("built without the `mock` feature".to_string()))
+    }
+}
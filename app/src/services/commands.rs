@@ -0,0 +1,372 @@
+use crate::config::CommandsConfig;
+use crate::output::TypingQueue;
+use crate::platform::macos::ffi;
+use std::fmt;
+use tracing::{info, warn};
+
+/// A transcript after matching it against the spoken command grammar
+#[derive(Debug, Clone, PartialEq)]
+pub enum Command {
+    /// Ordinary dictation, emitted after formatting
+    Text(String),
+    /// Letter-by-letter output from spelling mode, emitted verbatim
+    Spelled(String),
+    /// Controls the Mac instead of producing text (`commands.system`); the whole utterance
+    /// has to be the command
+    System(SystemCommand),
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum SystemCommand {
+    /// "switch to Safari", "open Notes": bring the app to the front, launching it if needed
+    ActivateApp(String),
+    /// "press enter", "press command shift t"
+    PressKeys(KeyChord),
+    /// "minimize window", "full screen": act on the frontmost window
+    Window(WindowAction),
+    /// "quit Safari", or "quit app" for the frontmost one
+    QuitApp(Option<String>),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum WindowAction {
+    Close,
+    Minimize,
+    /// Fill the screen without entering full screen
+    Maximize,
+    ToggleFullScreen,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Modifier {
+    Command,
+    Shift,
+    Option,
+    Control,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Key {
+    Return,
+    Tab,
+    Escape,
+    Space,
+    /// The Mac's Delete key
+    Backspace,
+    Up,
+    Down,
+    Left,
+    Right,
+    Home,
+    End,
+    PageUp,
+    PageDown,
+    /// F1 to F12
+    Function(u8),
+    Char(char),
+}
+
+/// One key pressed with modifiers held
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct KeyChord {
+    pub modifiers: Vec<Modifier>,
+    pub key: Key,
+}
+
+impl fmt::Display for KeyChord {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for modifier in &self.modifiers {
+            f.write_str(match modifier {
+                Modifier::Control => "⌃",
+                Modifier::Option => "⌥",
+                Modifier::Shift => "⇧",
+                Modifier::Command => "⌘",
+            })?;
+        }
+        match self.key {
+            Key::Return => f.write_str("Return"),
+            Key::Tab => f.write_str("Tab"),
+            Key::Escape => f.write_str("Esc"),
+            Key::Space => f.write_str("Space"),
+            Key::Backspace => f.write_str("Delete"),
+            Key::Up => f.write_str("↑"),
+            Key::Down => f.write_str("↓"),
+            Key::Left => f.write_str("←"),
+            Key::Right => f.write_str("→"),
+            Key::Home => f.write_str("Home"),
+            Key::End => f.write_str("End"),
+            Key::PageUp => f.write_str("Page Up"),
+            Key::PageDown => f.write_str("Page Down"),
+            Key::Function(n) => write!(f, "F{}", n),
+            Key::Char(c) => write!(f, "{}", c.to_ascii_uppercase()),
+        }
+    }
+}
+
+impl SystemCommand {
+    /// Whether it can lose work, so `commands.confirm_destructive` asks first
+    pub fn is_destructive(&self) -> bool {
+        match self {
+            SystemCommand::Window(WindowAction::Close) | SystemCommand::QuitApp(_) => true,
+            SystemCommand::PressKeys(chord) => {
+                chord.modifiers.contains(&Modifier::Command) && matches!(chord.key, Key::Char('q' | 'w'))
+            }
+            _ => false,
+        }
+    }
+
+    /// What it does, for logs and the confirmation
+    pub fn describe(&self) -> String {
+        match self {
+            SystemCommand::ActivateApp(name) => format!("Switch to {}", name),
+            SystemCommand::PressKeys(chord) => format!("Press {}", chord),
+            SystemCommand::Window(WindowAction::Close) => "Close the front window".to_string(),
+            SystemCommand::Window(WindowAction::Minimize) => "Minimize the front window".to_string(),
+            SystemCommand::Window(WindowAction::Maximize) => "Maximize the front window".to_string(),
+            SystemCommand::Window(WindowAction::ToggleFullScreen) => "Toggle full screen".to_string(),
+            SystemCommand::QuitApp(Some(name)) => format!("Quit {}", name),
+            SystemCommand::QuitApp(None) => "Quit the front app".to_string(),
+        }
+    }
+}
+
+/// Match a final transcript against the command grammar
+pub fn parse_command(text: &str, cfg: &CommandsConfig) -> Command {
+    if cfg.enabled {
+        let words = tokenize(text);
+        // Longest trigger first so "spell that" wins over "spell"
+        let mut prefixes: Vec<Vec<String>> = cfg.spell_prefixes.iter().map(|p| tokenize(p)).collect();
+        prefixes.sort_by_key(|p| std::cmp::Reverse(p.len()));
+        for prefix_words in prefixes {
+            if !prefix_words.is_empty() && words.len() > prefix_words.len() && words.starts_with(&prefix_words) {
+                return Command::Spelled(spell_words(&words[prefix_words.len()..], cfg.spell_uppercase));
+            }
+        }
+        if cfg.system {
+            if let Some(command) = parse_system(&words) {
+                return Command::System(command);
+            }
+        }
+    }
+    Command::Text(text.to_string())
+}
+
+/// Longest app name taken after "switch to", "open" or "quit"; longer is likely dictation
+const MAX_APP_NAME_WORDS: usize = 4;
+
+/// Words dropped from window and key commands ("close the window", "press the enter key")
+const FILLER: &[&str] = &["the", "this", "current", "front", "key"];
+
+fn parse_system(words: &[String]) -> Option<SystemCommand> {
+    let words: Vec<&str> = words.iter().map(String::as_str).collect();
+    let app_name = |name: &[&str]| (1..=MAX_APP_NAME_WORDS).contains(&name.len()).then(|| name.join(" "));
+    match words.as_slice() {
+        ["switch", "to", name @ ..] | ["open", name @ ..] | ["activate", name @ ..] => {
+            return app_name(name).map(SystemCommand::ActivateApp);
+        }
+        ["quit"] | ["quit", "app" | "application"] | ["quit", "this", "app" | "application"] => {
+            return Some(SystemCommand::QuitApp(None));
+        }
+        ["quit", name @ ..] => return app_name(name).map(|name| SystemCommand::QuitApp(Some(name))),
+        ["press" | "hit", keys @ ..] => return parse_chord(keys).map(SystemCommand::PressKeys),
+        _ => {}
+    }
+    let rest: Vec<&str> = words.into_iter().filter(|w| !FILLER.contains(w)).collect();
+    let action = match rest.as_slice() {
+        ["close", "window"] => WindowAction::Close,
+        ["minimize" | "minimise", "window"] => WindowAction::Minimize,
+        ["maximize" | "maximise" | "zoom", "window"] => WindowAction::Maximize,
+        ["full", "screen"] | ["fullscreen"] | ["enter" | "exit" | "toggle", "full", "screen"] => {
+            WindowAction::ToggleFullScreen
+        }
+        _ => return None,
+    };
+    Some(SystemCommand::Window(action))
+}
+
+/// Modifiers then exactly one key: "command shift t", "page down", "escape"
+fn parse_chord(words: &[&str]) -> Option<KeyChord> {
+    let words: Vec<&str> = words.iter().copied().filter(|w| !FILLER.contains(w)).collect();
+    let mut held = Vec::new();
+    let mut rest = words.as_slice();
+    while let Some((word, tail)) = rest.split_first() {
+        held.push(match *word {
+            "command" | "cmd" => Modifier::Command,
+            "shift" => Modifier::Shift,
+            "option" | "alt" => Modifier::Option,
+            "control" | "ctrl" => Modifier::Control,
+            _ => break,
+        });
+        rest = tail;
+    }
+    let key = match rest {
+        ["enter" | "return"] => Key::Return,
+        ["tab"] => Key::Tab,
+        ["escape" | "esc"] => Key::Escape,
+        ["space" | "spacebar"] => Key::Space,
+        ["delete" | "backspace"] => Key::Backspace,
+        ["up"] | ["up", "arrow"] => Key::Up,
+        ["down"] | ["down", "arrow"] => Key::Down,
+        ["left"] | ["left", "arrow"] => Key::Left,
+        ["right"] | ["right", "arrow"] => Key::Right,
+        ["home"] => Key::Home,
+        ["end"] => Key::End,
+        ["page", "up"] => Key::PageUp,
+        ["page", "down"] => Key::PageDown,
+        [word] => match word.strip_prefix('f').and_then(|n| n.parse::<u8>().ok()) {
+            Some(n @ 1..=12) => Key::Function(n),
+            _ => Key::Char(spelled_char(word)?),
+        },
+        _ => return None,
+    };
+    // In the order macOS shows them
+    let order = [Modifier::Control, Modifier::Option, Modifier::Shift, Modifier::Command];
+    Some(KeyChord { modifiers: order.into_iter().filter(|m| held.contains(m)).collect(), key })
+}
+
+/// Carry out `command`, asking first if it's destructive and `cfg.confirm_destructive` is
+/// set. Key presses queue behind text still being typed. Returns false when it named an app
+/// that isn't there ("open the door"), so the words are typed as dictation instead.
+pub fn run_system_command(command: &SystemCommand, cfg: &CommandsConfig, typing: &TypingQueue) -> bool {
+    let description = match command {
+        // Resolved first, so dictation that only sounds like a command isn't confirmed
+        SystemCommand::QuitApp(name) => match ffi::running_app_name(name.as_deref()) {
+            Some(app) => format!("Quit {}", app),
+            None => return false,
+        },
+        _ => command.describe(),
+    };
+    if command.is_destructive()
+        && cfg.confirm_destructive
+        && !ffi::MenuBarController::confirm(
+            &format!("{}?", description),
+            "You asked for this by voice. Unsaved work may be lost.",
+            &description,
+        )
+    {
+        info!("Voice command declined: {}", description);
+        return true;
+    }
+    info!("Voice command: {}", description);
+    let done = match command {
+        SystemCommand::ActivateApp(name) => {
+            if !ffi::activate_app(name) {
+                return false;
+            }
+            true
+        }
+        SystemCommand::PressKeys(chord) => match typing.queue_keys(chord.clone()) {
+            Ok(()) => true,
+            Err(e) => {
+                warn!("{}", e);
+                false
+            }
+        },
+        SystemCommand::Window(action) => ffi::window_action(*action),
+        SystemCommand::QuitApp(name) => ffi::quit_app(name.as_deref()),
+    };
+    if !done {
+        warn!("Voice command failed: {}", description);
+        ffi::MenuBarController::show_notification(
+            "Command didn't work",
+            &format!("{} failed. Check that Typeswift has Accessibility permission.", description),
+        );
+    }
+    true
+}
+
+/// Lowercased words with punctuation and hyphens as separators ("A-L-P-H-A." -> a l p h a)
+fn tokenize(text: &str) -> Vec<String> {
+    let lowered = text
+        .to_lowercase()
+        .replace("x-ray", "xray")
+        .replace("double-u", "doubleu")
+        .replace("double you", "doubleu");
+    lowered
+        .split(|c: char| !c.is_alphanumeric())
+        .filter(|w| !w.is_empty())
+        .map(str::to_string)
+        .collect()
+}
+
+const NATO: &[(&str, char)] = &[
+    ("alpha", 'a'), ("alfa", 'a'), ("bravo", 'b'), ("charlie", 'c'), ("delta", 'd'),
+    ("echo", 'e'), ("foxtrot", 'f'), ("golf", 'g'), ("hotel", 'h'), ("india", 'i'),
+    ("juliet", 'j'), ("juliett", 'j'), ("kilo", 'k'), ("lima", 'l'), ("mike", 'm'),
+    ("november", 'n'), ("oscar", 'o'), ("papa", 'p'), ("quebec", 'q'), ("romeo", 'r'),
+    ("sierra", 's'), ("tango", 't'), ("uniform", 'u'), ("victor", 'v'), ("whiskey", 'w'),
+    ("whisky", 'w'), ("xray", 'x'), ("yankee", 'y'), ("zulu", 'z'),
+];
+
+/// How ASR tends to transcribe isolated letter names
+const LETTER_NAMES: &[(&str, char)] = &[
+    ("bee", 'b'), ("be", 'b'), ("see", 'c'), ("sea", 'c'), ("dee", 'd'), ("gee", 'g'),
+    ("aitch", 'h'), ("jay", 'j'), ("kay", 'k'), ("el", 'l'), ("ell", 'l'), ("em", 'm'),
+    ("en", 'n'), ("oh", 'o'), ("pee", 'p'), ("cue", 'q'), ("queue", 'q'), ("are", 'r'),
+    ("ess", 's'), ("tee", 't'), ("tea", 't'), ("you", 'u'), ("vee", 'v'), ("doubleu", 'w'),
+    ("ex", 'x'), ("why", 'y'), ("zed", 'z'), ("zee", 'z'),
+];
+
+const DIGITS: &[(&str, char)] = &[
+    ("zero", '0'), ("one", '1'), ("two", '2'), ("three", '3'), ("four", '4'),
+    ("five", '5'), ("six", '6'), ("seven", '7'), ("eight", '8'), ("nine", '9'),
+];
+
+const SYMBOLS: &[(&str, &str)] = &[
+    ("dot", "."), ("period", "."), ("point", "."), ("at", "@"), ("dash", "-"),
+    ("hyphen", "-"), ("minus", "-"), ("underscore", "_"), ("slash", "/"), ("plus", "+"),
+    ("space", " "), ("comma", ","), ("colon", ":"), ("hash", "#"),
+];
+
+fn lookup<T: Copy>(table: &[(&str, T)], word: &str) -> Option<T> {
+    table.iter().find(|(name, _)| *name == word).map(|(_, v)| *v)
+}
+
+/// Single character for a spoken token, if it names one
+fn spelled_char(word: &str) -> Option<char> {
+    let mut chars = word.chars();
+    if let (Some(c), None) = (chars.next(), chars.next()) {
+        return Some(c);
+    }
+    lookup(NATO, word)
+        .or_else(|| lookup(LETTER_NAMES, word))
+        .or_else(|| lookup(DIGITS, word))
+}
+
+/// Join spelled tokens: letters/digits/symbols collapse, "capital" upcases the next letter,
+/// "double" repeats it, and unrecognised words pass through (e.g. "gmail" in an address).
+fn spell_words(words: &[String], uppercase: bool) -> String {
+    let mut out = String::new();
+    let mut capital_next = false;
+    let mut repeat_next = false;
+    for word in words {
+        match word.as_str() {
+            "capital" | "cap" | "uppercase" => {
+                capital_next = true;
+                continue;
+            }
+            "double" => {
+                repeat_next = true;
+                continue;
+            }
+            _ => {}
+        }
+        let piece = if let Some(sym) = lookup(SYMBOLS, word) {
+            sym.to_string()
+        } else if let Some(c) = spelled_char(word) {
+            let c = if uppercase || capital_next { c.to_ascii_uppercase() } else { c };
+            if repeat_next { format!("{c}{c}") } else { c.to_string() }
+        } else if word.chars().all(|c| c.is_ascii_digit()) {
+            word.clone()
+        } else if capital_next {
+            let mut chars = word.chars();
+            chars.next().map(|f| f.to_uppercase().chain(chars).collect()).unwrap_or_default()
+        } else {
+            word.clone()
+        };
+        capital_next = false;
+        repeat_next = false;
+        out.push_str(&piece);
+    }
+    out
+}
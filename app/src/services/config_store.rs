@@ -0,0 +1,51 @@
+//! Writes config.toml for everything that changes settings while the app runs. Changes made in
+//! quick succession (clicking through Preferences) become one write once they stop arriving, and
+//! every write goes through `Config::save`, which replaces the file atomically and keeps keys it
+//! doesn't know. Writers used to each spawn a thread, and two racing could interleave the file.
+
+use crate::config::Config;
+use once_cell::sync::Lazy;
+use parking_lot::Mutex;
+use smol::Timer;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::Duration;
+use tracing::{debug, warn};
+
+/// Quiet time after the last change before it's written
+const DEBOUNCE: Duration = Duration::from_millis(500);
+
+static STORE: Lazy<ConfigStore> = Lazy::new(ConfigStore::default);
+
+#[derive(Default)]
+struct ConfigStore {
+    /// Newest config not yet written
+    pending: Mutex<Option<Config>>,
+    /// Bumped by each `save`; a delayed write only goes ahead if no newer one was queued
+    generation: AtomicU64,
+    /// Held while writing, so `flush` and a delayed write never overlap
+    writing: Mutex<()>,
+}
+
+/// Write `config` to config.toml shortly, replacing any write still waiting
+pub fn save(config: &Config) {
+    *STORE.pending.lock() = Some(config.clone());
+    let generation = STORE.generation.fetch_add(1, Ordering::SeqCst) + 1;
+    crate::runtime::spawn(async move {
+        Timer::after(DEBOUNCE).await;
+        if STORE.generation.load(Ordering::SeqCst) == generation {
+            smol::unblock(flush).await;
+        }
+    });
+}
+
+/// Write the waiting change now, if there is one. Call before relaunching or quitting: the
+/// delayed write is cancelled with the other background tasks.
+pub fn flush() {
+    let _writing = STORE.writing.lock();
+    let Some(config) = STORE.pending.lock().take() else { return };
+    let Some(path) = Config::config_path() else { return };
+    match config.save(path) {
+        Ok(()) => debug!("Saved config"),
+        Err(e) => warn!("Failed to save config: {}", e),
+    }
+}
@@ -0,0 +1,174 @@
+//! Echo cancellation for meeting recordings that mix in system audio. The microphone also
+//! hears the speakers, so the other side of a call would be transcribed twice: once from the
+//! system stream and again, delayed and muffled, from the room. The canceller finds that delay
+//! and subtracts an adaptive (NLMS) estimate of the echo from the microphone before mixing.
+
+use std::collections::VecDeque;
+use tracing::debug;
+
+/// Echo path length the adaptive filter models, after the bulk delay
+const FILTER_MS: usize = 32;
+/// Longest speaker-to-microphone delay looked for (output latency included; Bluetooth is slow)
+const MAX_DELAY_MS: usize = 500;
+/// Envelope resolution for the delay search
+const BLOCK_MS: usize = 10;
+/// Envelope history the delay is estimated from
+const ESTIMATE_WINDOW_BLOCKS: usize = 300;
+/// Re-estimate the delay this often, in blocks
+const ESTIMATE_EVERY_BLOCKS: usize = 100;
+/// Correlation the envelopes need before a delay is trusted
+const MIN_CORRELATION: f32 = 0.3;
+/// NLMS step size
+const STEP: f32 = 0.3;
+/// Below this reference energy (per filter window) there's nothing to cancel
+const SILENT_REFERENCE: f32 = 1e-6;
+/// Geigel double-talk detector: a microphone sample above this fraction of the recent reference
+/// peak is taken to be someone in the room talking, and the filter doesn't adapt on it
+const DOUBLE_TALK: f32 = 0.5;
+
+/// Subtracts the echo of one reference stream (system audio) from another (the microphone).
+/// Feed both a sample at a time, in step, with `process`.
+pub struct EchoCanceller {
+    /// Recent reference samples, newest last, at most `history` of them
+    reference: VecDeque<f32>,
+    history: usize,
+    weights: Vec<f32>,
+    /// Samples between the reference and the start of the echo the filter models
+    delay: usize,
+    block_len: usize,
+    max_lag_blocks: usize,
+    mic_block: f32,
+    reference_block: f32,
+    block_pos: usize,
+    mic_envelope: VecDeque<f32>,
+    reference_envelope: VecDeque<f32>,
+    blocks_since_estimate: usize,
+}
+
+impl EchoCanceller {
+    pub fn new(sample_rate: u32) -> Self {
+        let per_ms = (sample_rate as usize / 1000).max(1);
+        let taps = FILTER_MS * per_ms;
+        let max_delay = MAX_DELAY_MS * per_ms;
+        Self {
+            reference: VecDeque::with_capacity(max_delay + taps),
+            history: max_delay + taps,
+            weights: vec![0.0; taps],
+            delay: 0,
+            block_len: BLOCK_MS * per_ms,
+            max_lag_blocks: MAX_DELAY_MS / BLOCK_MS,
+            mic_block: 0.0,
+            reference_block: 0.0,
+            block_pos: 0,
+            mic_envelope: VecDeque::with_capacity(ESTIMATE_WINDOW_BLOCKS),
+            reference_envelope: VecDeque::with_capacity(ESTIMATE_WINDOW_BLOCKS),
+            blocks_since_estimate: 0,
+        }
+    }
+
+    /// The microphone sample with the echo of `reference` (system audio at the same instant)
+    /// taken out
+    pub fn process(&mut self, mic: f32, reference: f32) -> f32 {
+        let taps = self.weights.len();
+        if self.reference.len() == self.history {
+            self.reference.pop_front();
+        }
+        self.reference.push_back(reference);
+        self.track_envelopes(mic, reference);
+
+        let newest = self.reference.len();
+        if newest < self.delay + taps {
+            return mic;
+        }
+        // Reference samples from `delay` back, newest first
+        let window = self.reference.range(newest - self.delay - taps..newest - self.delay).rev();
+        let (mut estimate, mut energy, mut peak) = (0.0f32, 0.0f32, 0.0f32);
+        for (w, x) in self.weights.iter().zip(window.clone()) {
+            estimate += w * x;
+            energy += x * x;
+            peak = peak.max(x.abs());
+        }
+        if energy < SILENT_REFERENCE {
+            return mic;
+        }
+        let error = mic - estimate;
+        if mic.abs() < DOUBLE_TALK * peak {
+            let gain = STEP * error / (energy + SILENT_REFERENCE);
+            for (w, x) in self.weights.iter_mut().zip(window) {
+                *w += gain * x;
+            }
+        }
+        error
+    }
+
+    fn track_envelopes(&mut self, mic: f32, reference: f32) {
+        self.mic_block += mic.abs();
+        self.reference_block += reference.abs();
+        self.block_pos += 1;
+        if self.block_pos < self.block_len {
+            return;
+        }
+        for (envelope, level) in [
+            (&mut self.mic_envelope, std::mem::take(&mut self.mic_block)),
+            (&mut self.reference_envelope, std::mem::take(&mut self.reference_block)),
+        ] {
+            if envelope.len() == ESTIMATE_WINDOW_BLOCKS {
+                envelope.pop_front();
+            }
+            envelope.push_back(level);
+        }
+        self.block_pos = 0;
+        self.blocks_since_estimate += 1;
+        let window_full = self.mic_envelope.len() == ESTIMATE_WINDOW_BLOCKS;
+        if window_full && self.blocks_since_estimate >= ESTIMATE_EVERY_BLOCKS {
+            self.blocks_since_estimate = 0;
+            self.estimate_delay();
+        }
+    }
+
+    /// Find the lag at which the microphone's loudness follows the reference's most closely,
+    /// and move the filter there when it moved by more than the filter covers
+    fn estimate_delay(&mut self) {
+        let Some(lag) = best_lag(&self.mic_envelope, &self.reference_envelope, self.max_lag_blocks) else {
+            return;
+        };
+        // Start the filter a block early, so the echo's onset is inside it
+        let delay = lag.saturating_sub(1) * self.block_len;
+        let taps = self.weights.len();
+        if delay.abs_diff(self.delay) >= taps / 2 {
+            debug!("Echo delay now {} ms", delay * BLOCK_MS / self.block_len);
+            self.delay = delay;
+            self.weights.iter_mut().for_each(|w| *w = 0.0);
+        }
+    }
+}
+
+/// The lag (in blocks) maximizing the normalized correlation of `mic` with `reference` lagged,
+/// if it correlates well enough to trust
+fn best_lag(mic: &VecDeque<f32>, reference: &VecDeque<f32>, max_lag: usize) -> Option<usize> {
+    let len = mic.len().min(reference.len());
+    if len <= max_lag * 2 {
+        return None;
+    }
+    let mut best: Option<(usize, f32)> = None;
+    for lag in 0..=max_lag {
+        let pairs = || (lag..len).map(|i| (mic[i], reference[i - lag]));
+        let count = (len - lag) as f32;
+        let (mic_mean, ref_mean) = pairs().fold((0.0, 0.0), |(m, r), (a, b)| (m + a / count, r + b / count));
+        let (mut cov, mut mic_var, mut ref_var) = (0.0f32, 0.0f32, 0.0f32);
+        for (a, b) in pairs() {
+            let (da, db) = (a - mic_mean, b - ref_mean);
+            cov += da * db;
+            mic_var += da * da;
+            ref_var += db * db;
+        }
+        if mic_var <= 0.0 || ref_var <= 0.0 {
+            continue;
+        }
+        let correlation = cov / (mic_var * ref_var).sqrt();
+        if best.is_none_or(|(_, c)| correlation > c) {
+            best = Some((lag, correlation));
+        }
+    }
+    best.filter(|&(_, c)| c >= MIN_CORRELATION).map(|(lag, _)| lag)
+}
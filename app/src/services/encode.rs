@@ -0,0 +1,119 @@
+//! Compressed audio for the recordings archive and for the `remote` backend's uploads, in
+//! place of WAV or raw samples. Encoding and decoding go through the system AudioToolbox codecs.
+
+use crate::config::AudioCodec;
+use crate::error::{VoicyError, VoicyResult};
+use crate::platform::macos::ffi as menubar_ffi;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicU64, Ordering};
+
+impl AudioCodec {
+    pub const ALL: [AudioCodec; 3] = [AudioCodec::Aac, AudioCodec::Opus, AudioCodec::Flac];
+
+    /// File extension, which also picks the container (Opus goes in CAF)
+    pub fn extension(self) -> &'static str {
+        match self {
+            AudioCodec::Aac => "m4a",
+            AudioCodec::Opus => "caf",
+            AudioCodec::Flac => "flac",
+        }
+    }
+
+    /// Content type for uploads
+    pub fn mime_type(self) -> &'static str {
+        match self {
+            AudioCodec::Aac => "audio/mp4",
+            AudioCodec::Opus => "audio/x-caf",
+            AudioCodec::Flac => "audio/flac",
+        }
+    }
+
+    /// Codec code understood by `typeswift_write_audio_file`
+    fn ffi_code(self) -> i32 {
+        match self {
+            AudioCodec::Aac => 0,
+            AudioCodec::Opus => 1,
+            AudioCodec::Flac => 2,
+        }
+    }
+}
+
+/// Encode mono `samples` at `sample_rate` into `path`, which should end in `codec.extension()`
+pub fn encode_to_file(samples: &[f32], sample_rate: u32, codec: AudioCodec, path: &Path) -> VoicyResult<()> {
+    if samples.is_empty() {
+        return Err(VoicyError::AudioInitFailed("No audio to encode".to_string()));
+    }
+    if menubar_ffi::write_audio_file(samples, sample_rate, codec.ffi_code(), path) {
+        Ok(())
+    } else {
+        Err(VoicyError::AudioInitFailed(format!("{:?} encoding failed for {}", codec, path.display())))
+    }
+}
+
+/// Encode mono `samples` at `sample_rate` in memory, e.g. for an upload body
+pub fn encode(samples: &[f32], sample_rate: u32, codec: AudioCodec) -> VoicyResult<Vec<u8>> {
+    // AudioToolbox writes files, so go through a private temporary one
+    let path = temp_path(codec);
+    let result = encode_to_file(samples, sample_rate, codec, &path).and_then(|_| {
+        std::fs::read(&path).map_err(|e| VoicyError::AudioInitFailed(format!("Encoded audio unreadable: {}", e)))
+    });
+    let _ = std::fs::remove_file(&path);
+    result
+}
+
+/// Decode `bytes` from `encode` back to at most `max_samples` mono samples at `sample_rate`
+pub fn decode(bytes: &[u8], sample_rate: u32, codec: AudioCodec, max_samples: usize) -> VoicyResult<Vec<f32>> {
+    let path = temp_path(codec);
+    let result = std::fs::write(&path, bytes)
+        .map_err(|e| VoicyError::AudioInitFailed(format!("Couldn't stage encoded audio: {}", e)))
+        .and_then(|_| {
+            menubar_ffi::read_audio_file(&path, sample_rate, max_samples)
+                .ok_or_else(|| VoicyError::AudioInitFailed(format!("{:?} decoding failed", codec)))
+        });
+    let _ = std::fs::remove_file(&path);
+    result
+}
+
+fn temp_path(codec: AudioCodec) -> PathBuf {
+    static NEXT: AtomicU64 = AtomicU64::new(0);
+    let n = NEXT.fetch_add(1, Ordering::Relaxed);
+    std::env::temp_dir().join(format!("typeswift-{}-{}.{}", std::process::id(), n, codec.extension()))
+}
+
+#[cfg(all(test, feature = "swift"))]
+mod tests {
+    use super::*;
+
+    fn tone(len: usize) -> Vec<f32> {
+        (0..len).map(|i| (i as f32 * 440.0 * std::f32::consts::TAU / 16000.0).sin() * 0.5).collect()
+    }
+
+    #[test]
+    fn flac_round_trips_to_16_bit_precision() {
+        let samples = tone(16000);
+        let bytes = encode(&samples, 16000, AudioCodec::Flac).unwrap();
+        assert!(bytes.len() < samples.len() * 4);
+        let decoded = decode(&bytes, 16000, AudioCodec::Flac, samples.len()).unwrap();
+        assert_eq!(decoded.len(), samples.len());
+        for (a, b) in samples.iter().zip(&decoded) {
+            assert!((a - b).abs() <= 1.0 / 32768.0, "{} vs {}", a, b);
+        }
+    }
+
+    #[test]
+    fn lossy_codecs_round_trip_smaller_than_raw() {
+        let samples = tone(16000);
+        for codec in [AudioCodec::Aac, AudioCodec::Opus] {
+            let bytes = encode(&samples, 16000, codec).unwrap();
+            assert!(bytes.len() * 8 < samples.len() * 4, "{:?}: {} bytes", codec, bytes.len());
+            let decoded = decode(&bytes, 16000, codec, samples.len()).unwrap();
+            assert!(!decoded.is_empty() && decoded.len() <= samples.len(), "{:?}: {} samples", codec, decoded.len());
+        }
+    }
+
+    #[test]
+    fn refuses_audio_at_another_rate() {
+        let bytes = encode(&tone(1600), 16000, AudioCodec::Flac).unwrap();
+        assert!(decode(&bytes, 44100, AudioCodec::Flac, 1600).is_err());
+    }
+}
@@ -0,0 +1,274 @@
+use crate::config::{DashStyle, EllipsisStyle, FormatOptions, QuoteStyle};
+
+/// Apply punctuation preferences to a final transcript before it is emitted
+pub fn format_text(text: &str, opts: &FormatOptions) -> String {
+    let mut out = match opts.ellipsis {
+        EllipsisStyle::Keep => text.to_string(),
+        EllipsisStyle::Dots => text.replace('\u{2026}', "..."),
+        EllipsisStyle::Character => text.replace("...", "\u{2026}"),
+    };
+    out = match opts.dashes {
+        DashStyle::Keep => out,
+        DashStyle::Ascii => out.replace('\u{2014}', "--").replace('\u{2013}', "-"),
+        DashStyle::Typographic => out.replace("--", "\u{2014}").replace(" - ", " \u{2014} "),
+    };
+    out = match opts.quotes {
+        QuoteStyle::Keep => out,
+        QuoteStyle::Straight => straighten_quotes(&out),
+        QuoteStyle::Smart => smarten_quotes(&straighten_quotes(&out)),
+    };
+    if opts.rtl_isolates && is_rtl(&out) {
+        out = isolate_rtl(&out);
+    }
+    out
+}
+
+/// Whether `text` reads right to left: its first letter is from a right-to-left script
+pub fn is_rtl(text: &str) -> bool {
+    text.chars().find(|c| c.is_alphabetic()).is_some_and(|c| {
+        matches!(c,
+            '\u{0590}'..='\u{08FF}'     // Hebrew, Arabic, Syriac, Thaana, NKo and supplements
+            | '\u{FB1D}'..='\u{FDFF}'   // Hebrew and Arabic presentation forms
+            | '\u{FE70}'..='\u{FEFF}')
+    })
+}
+
+/// Explicit direction marks and embeddings; models sometimes emit them and they fight the isolate
+fn is_direction_mark(c: char) -> bool {
+    matches!(c, '\u{200E}' | '\u{200F}' | '\u{202A}'..='\u{202E}' | '\u{2066}'..='\u{2069}')
+}
+
+/// `text` between a right-to-left isolate and its pop (RLI ... PDI), stray marks removed
+fn isolate_rtl(text: &str) -> String {
+    let mut out = String::with_capacity(text.len() + 6);
+    out.push('\u{2067}');
+    out.extend(text.chars().filter(|&c| !is_direction_mark(c)));
+    out.push('\u{2069}');
+    out
+}
+
+fn straighten_quotes(text: &str) -> String {
+    text.chars()
+        .map(|c| match c {
+            '\u{201C}' | '\u{201D}' | '\u{201E}' | '\u{00AB}' | '\u{00BB}' => '"',
+            '\u{2018}' | '\u{2019}' | '\u{201A}' => '\'',
+            other => other,
+        })
+        .collect()
+}
+
+/// Curl straight quotes: opening after whitespace/brackets or at the start, closing otherwise.
+/// An apostrophe inside a word (don't, it's) becomes a right single quote.
+fn smarten_quotes(text: &str) -> String {
+    let mut out = String::with_capacity(text.len());
+    let mut prev: Option<char> = None;
+    for c in text.chars() {
+        let opens = prev.is_none_or(|p| p.is_whitespace() || matches!(p, '(' | '[' | '{' | '\u{2014}' | '\u{2013}'));
+        let curled = match c {
+            '"' if opens => '\u{201C}',
+            '"' => '\u{201D}',
+            '\'' if opens => '\u{2018}',
+            '\'' => '\u{2019}',
+            other => other,
+        };
+        out.push(curled);
+        prev = Some(c);
+    }
+    out
+}
+
+/// Restore the configured spelling of bias phrases the model got nearly right:
+/// case-insensitive matches, with or without internal spaces ("type swift" -> "Typeswift").
+/// Backends with native biasing get the phrases as a prompt; this covers the rest.
+pub fn apply_vocabulary(text: &str, phrases: &[String]) -> String {
+    let mut out = text.to_string();
+    for phrase in phrases.iter().filter(|p| !p.trim().is_empty()) {
+        let target: Vec<char> = phrase.chars().filter(|c| !c.is_whitespace()).flat_map(char::to_lowercase).collect();
+        let chars: Vec<char> = out.chars().collect();
+        let mut result = String::with_capacity(out.len());
+        let mut i = 0;
+        while i < chars.len() {
+            match match_phrase(&chars, i, &target) {
+                Some(end) => {
+                    result.push_str(phrase.trim());
+                    i = end;
+                }
+                None => {
+                    result.push(chars[i]);
+                    i += 1;
+                }
+            }
+        }
+        out = result;
+    }
+    out
+}
+
+/// End index if `target` (lowercase, no spaces) matches at a word start, skipping spaces
+fn match_phrase(chars: &[char], start: usize, target: &[char]) -> Option<usize> {
+    if start > 0 && chars[start - 1].is_alphanumeric() {
+        return None;
+    }
+    let mut i = start;
+    for (n, want) in target.iter().enumerate() {
+        if n > 0 {
+            while i < chars.len() && chars[i] == ' ' {
+                i += 1;
+            }
+        }
+        let got = chars.get(i)?.to_lowercase().next()?;
+        if got != *want {
+            return None;
+        }
+        i += 1;
+    }
+    if chars.get(i).is_some_and(|c| c.is_alphanumeric()) {
+        return None;
+    }
+    Some(i)
+}
+
+/// Whole-word, case-insensitive find and replace, rule by rule. Spaces inside `from`
+/// match any run of spaces.
+pub fn replace_phrases(text: &str, rules: &[(&str, &str)]) -> String {
+    let mut out = text.to_string();
+    for (from, to) in rules.iter().filter(|(from, _)| !from.trim().is_empty()) {
+        let words: Vec<Vec<char>> = from.split_whitespace().map(|w| w.chars().flat_map(char::to_lowercase).collect()).collect();
+        let chars: Vec<char> = out.chars().collect();
+        let mut result = String::with_capacity(out.len());
+        let mut i = 0;
+        while i < chars.len() {
+            match match_words(&chars, i, &words) {
+                Some(end) => {
+                    result.push_str(to);
+                    i = end;
+                }
+                None => {
+                    result.push(chars[i]);
+                    i += 1;
+                }
+            }
+        }
+        out = result;
+    }
+    out
+}
+
+/// End index if `words` (lowercase) match at a word start, separated by spaces
+fn match_words(chars: &[char], start: usize, words: &[Vec<char>]) -> Option<usize> {
+    if start > 0 && chars[start - 1].is_alphanumeric() {
+        return None;
+    }
+    let mut i = start;
+    for (n, word) in words.iter().enumerate() {
+        if n > 0 {
+            let spaces = chars[i..].iter().take_while(|c| **c == ' ').count();
+            if spaces == 0 {
+                return None;
+            }
+            i += spaces;
+        }
+        for want in word {
+            if chars.get(i)?.to_lowercase().next()? != *want {
+                return None;
+            }
+            i += 1;
+        }
+    }
+    if chars.get(i).is_some_and(|c| c.is_alphanumeric()) {
+        return None;
+    }
+    Some(i)
+}
+
+/// Drop standalone filler words along with a comma right after them ("Um, so" -> "So"),
+/// keeping the sentence's capitalization
+pub fn remove_fillers(text: &str, fillers: &[String]) -> String {
+    let is_filler = |word: &str| {
+        let bare = word.trim_end_matches([',', '.', '?', '!', ';', ':', '\u{2026}']);
+        !bare.is_empty() && fillers.iter().any(|f| f.eq_ignore_ascii_case(bare))
+    };
+    let mut out: Vec<String> = Vec::new();
+    let mut capitalize_next = false;
+    for word in text.split_whitespace() {
+        if is_filler(word) {
+            // Keep sentence-ending punctuation the filler carried
+            let end = word.trim_start_matches(|c: char| c.is_alphanumeric()).trim_start_matches(',');
+            if let (false, Some(prev)) = (end.is_empty(), out.last_mut()) {
+                prev.push_str(end);
+            }
+            let first_of_sentence = out.last().is_none_or(|p| p.ends_with(['.', '?', '!']));
+            capitalize_next |= first_of_sentence && word.starts_with(char::is_uppercase);
+            continue;
+        }
+        let mut word = word.to_string();
+        if std::mem::take(&mut capitalize_next) {
+            let mut chars = word.chars();
+            if let Some(first) = chars.next() {
+                word = first.to_uppercase().chain(chars).collect();
+            }
+        }
+        out.push(word);
+    }
+    out.join(" ")
+}
+
+/// Whether text inserted after `before` (the text left of the cursor) needs a separating
+/// space: not at the start of a document, after whitespace, or after an opening bracket or quote.
+pub fn needs_leading_space(before: &str) -> bool {
+    let mut chars = before.chars().rev();
+    match chars.next() {
+        None => false,
+        Some(c) if c.is_whitespace() => false,
+        // A straight quote opens when it follows a space or bracket (or starts the text)
+        Some('"' | '\'') => chars.next().is_some_and(|p| !p.is_whitespace() && !matches!(p, '(' | '[' | '{')),
+        Some(c) => !matches!(c, '(' | '[' | '{' | '\u{201C}' | '\u{2018}' | '\u{00AB}' | '/' | '@' | '#'),
+    }
+}
+
+/// Whether text inserted after `before` starts a new sentence: at the start of a document,
+/// after a line break, or after sentence-ending punctuation (ignoring closing quotes/brackets).
+pub fn starts_sentence(before: &str) -> bool {
+    let trimmed = before.trim_end_matches([' ', '\t']);
+    let mut chars = trimmed.chars().rev().skip_while(|c| matches!(c, '"' | '\'' | ')' | ']' | '\u{201D}' | '\u{2019}'));
+    match chars.next() {
+        None => true,
+        Some(c) => matches!(c, '.' | '!' | '?' | '\u{2026}' | '\n' | '\r'),
+    }
+}
+
+/// What the text before the cursor implies for the next utterance
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct CursorContext {
+    pub leading_space: bool,
+    /// Some(true) at a sentence start, Some(false) mid-sentence, None when unknown
+    pub sentence_start: Option<bool>,
+}
+
+impl CursorContext {
+    /// Capitalize or lowercase the first word to fit the sentence position. Words that
+    /// are always capitalized ("I", acronyms, `keep` phrases) are left alone mid-sentence.
+    pub fn apply_case(&self, text: &str, keep: &[String]) -> String {
+        let Some(sentence_start) = self.sentence_start else {
+            return text.to_string();
+        };
+        let Some((start, first)) = text.char_indices().find(|(_, c)| c.is_alphabetic()) else {
+            return text.to_string();
+        };
+        let rest = &text[start + first.len_utf8()..];
+        let fixed: String = if sentence_start {
+            first.to_uppercase().collect()
+        } else {
+            let word: String = text[start..].chars().take_while(|c| c.is_alphanumeric() || *c == '\'').collect();
+            let proper = word == "I"
+                || word.starts_with("I'")
+                || (word.chars().count() > 1 && word.chars().all(|c| !c.is_lowercase()))
+                || keep.iter().any(|k| k.split_whitespace().next() == Some(word.as_str()));
+            if proper {
+                return text.to_string();
+            }
+            first.to_lowercase().collect()
+        };
+        format!("{}{}{}", &text[..start], fixed, rest)
+    }
+}
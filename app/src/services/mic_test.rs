@@ -0,0 +1,188 @@
+use crate::config::{AudioConfig, Config};
+use crate::services::audio::AudioProcessor;
+use parking_lot::{Mutex, RwLock};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+use tracing::{info, warn};
+
+/// Length of the test recording
+pub const TEST_DURATION: Duration = Duration::from_secs(3);
+/// Bars in the waveform preview
+pub const WAVEFORM_BARS: usize = 48;
+/// Speech level the recommended gain aims for (RMS, about -20 dBFS)
+const TARGET_SPEECH_RMS: f32 = 0.1;
+/// Keep peaks below this after the recommended gain (about -1 dBFS)
+const MAX_PEAK: f32 = 0.9;
+/// Speech must be this much louder than the noise floor to calibrate from
+const MIN_SPEECH_TO_NOISE: f32 = 2.0;
+
+/// Settings suggested by a microphone test
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Calibration {
+    pub input_gain: f32,
+    pub vad_threshold: f32,
+}
+
+impl Calibration {
+    pub fn apply(&self, audio: &mut AudioConfig) {
+        audio.input_gain = self.input_gain;
+        audio.vad.threshold = self.vad_threshold;
+    }
+}
+
+/// What a microphone test measured. Levels are RMS/peak in [0, 1] with the current gain applied.
+#[derive(Debug, Clone, Default)]
+pub struct MicTestReport {
+    /// Peak level per bar, oldest first
+    pub waveform: Vec<f32>,
+    pub rms: f32,
+    pub peak: f32,
+    /// Quiet frames (10th percentile of 30 ms frame RMS)
+    pub noise_floor: f32,
+    /// Loud frames (90th percentile of 30 ms frame RMS)
+    pub speech_level: f32,
+    pub transcript: String,
+    /// `None` when no speech stood out from the noise
+    pub recommendation: Option<Calibration>,
+}
+
+/// Level in dBFS for display
+pub fn dbfs(level: f32) -> f32 {
+    20.0 * level.max(1e-6).log10()
+}
+
+fn percentile(sorted: &[f32], p: f32) -> f32 {
+    if sorted.is_empty() {
+        return 0.0;
+    }
+    sorted[((sorted.len() - 1) as f32 * p).round() as usize]
+}
+
+/// Measure a recording made with `current_gain` and suggest gain and RMS VAD threshold values
+pub fn analyze(samples: &[f32], sample_rate: u32, current_gain: f32) -> MicTestReport {
+    if samples.is_empty() {
+        return MicTestReport::default();
+    }
+    let bar_len = samples.len().div_ceil(WAVEFORM_BARS);
+    let waveform = samples
+        .chunks(bar_len)
+        .map(|c| c.iter().fold(0.0f32, |m, s| m.max(s.abs())))
+        .collect();
+    let rms = (samples.iter().map(|s| s * s).sum::<f32>() / samples.len() as f32).sqrt();
+    let peak = samples.iter().fold(0.0f32, |m, s| m.max(s.abs()));
+
+    let frame_len = (sample_rate as usize * 3 / 100).max(1);
+    let mut frames: Vec<f32> = samples
+        .chunks_exact(frame_len)
+        .map(|f| (f.iter().map(|s| s * s).sum::<f32>() / f.len() as f32).sqrt())
+        .collect();
+    frames.sort_by(f32::total_cmp);
+    let noise_floor = percentile(&frames, 0.1);
+    let speech_level = percentile(&frames, 0.9);
+
+    let recommendation = (speech_level > 0.0 && speech_level >= noise_floor * MIN_SPEECH_TO_NOISE).then(|| {
+        let factor = (TARGET_SPEECH_RMS / speech_level).min(MAX_PEAK / peak.max(1e-6));
+        let input_gain = ((current_gain * factor).clamp(0.25, 8.0) * 20.0).round() / 20.0;
+        let applied = input_gain / current_gain.max(1e-6);
+        // Halfway between noise and speech on a log scale
+        let threshold = (noise_floor * applied * speech_level * applied).sqrt().clamp(0.002, 0.2);
+        Calibration { input_gain, vad_threshold: (threshold * 1000.0).round() / 1000.0 }
+    });
+
+    MicTestReport { waveform, rms, peak, noise_floor, speech_level, transcript: String::new(), recommendation }
+}
+
+#[derive(Debug, Clone, Default)]
+pub enum MicTestStatus {
+    #[default]
+    Idle,
+    Recording,
+    Transcribing,
+    Done(MicTestReport),
+    Failed(String),
+}
+
+/// Runs microphone tests on the dictation audio processor and tracks the latest result.
+/// Cheap to clone; `rev` changes whenever the status does.
+#[derive(Clone)]
+pub struct MicTest {
+    processor: Arc<std::sync::Mutex<AudioProcessor>>,
+    status: Arc<Mutex<MicTestStatus>>,
+    rev: Arc<AtomicU64>,
+}
+
+impl MicTest {
+    pub fn new(processor: Arc<std::sync::Mutex<AudioProcessor>>) -> Self {
+        Self { processor, status: Arc::new(Mutex::new(MicTestStatus::Idle)), rev: Arc::new(AtomicU64::new(0)) }
+    }
+
+    pub fn status(&self) -> MicTestStatus {
+        self.status.lock().clone()
+    }
+
+    pub fn rev(&self) -> u64 {
+        self.rev.load(Ordering::Relaxed)
+    }
+
+    pub fn is_running(&self) -> bool {
+        matches!(*self.status.lock(), MicTestStatus::Recording | MicTestStatus::Transcribing)
+    }
+
+    fn set_status(&self, status: MicTestStatus) {
+        *self.status.lock() = status;
+        self.rev.fetch_add(1, Ordering::Relaxed);
+    }
+
+    /// Record `TEST_DURATION`, transcribe it and analyze the levels in the background
+    pub fn start(&self) {
+        if self.is_running() {
+            return;
+        }
+        self.set_status(MicTestStatus::Recording);
+        let this = self.clone();
+        std::thread::spawn(move || {
+            let Ok(mut processor) = this.processor.lock() else { return };
+            let samples = match processor.record_sample(TEST_DURATION) {
+                Ok(samples) => samples,
+                Err(e) => {
+                    warn!("Microphone test failed: {}", e);
+                    this.set_status(MicTestStatus::Failed(e.to_string()));
+                    return;
+                }
+            };
+            this.set_status(MicTestStatus::Transcribing);
+            let gain = processor.audio_config().input_gain;
+            let mut report = analyze(&samples, processor.sample_rate(), gain);
+            match processor.transcribe_samples(&samples) {
+                Ok(transcript) => report.transcript = transcript.text,
+                Err(e) => warn!("Microphone test transcription failed: {}", e),
+            }
+            info!(
+                "Microphone test: rms {:.1} dBFS, peak {:.1} dBFS, noise {:.1} dBFS, suggestion {:?}",
+                dbfs(report.rms),
+                dbfs(report.peak),
+                dbfs(report.noise_floor),
+                report.recommendation
+            );
+            this.set_status(MicTestStatus::Done(report));
+        });
+    }
+
+    /// Save the suggested settings and use them from the next recording on
+    pub fn apply(&self, calibration: Calibration, config: &Arc<RwLock<Config>>) {
+        let to_save = {
+            let mut cfg = config.write();
+            calibration.apply(&mut cfg.audio);
+            cfg.clone()
+        };
+        let processor = Arc::clone(&self.processor);
+        std::thread::spawn(move || {
+            if let Ok(mut processor) = processor.lock() {
+                processor.set_audio_config(to_save.audio.clone());
+            }
+            crate::services::config_store::save(&to_save);
+        });
+        info!("Applied microphone calibration: {:?}", calibration);
+    }
+}
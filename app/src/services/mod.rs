@@ -1,2 +1,17 @@
 pub mod audio;
-
+pub mod backend;
+pub mod commands;
+pub mod config_store;
+pub mod echo;
+pub mod encode;
+pub mod format;
+pub mod mic_test;
+pub mod models;
+pub mod pipeline;
+pub mod scratch;
+pub mod script;
+pub mod streaming;
+pub mod supervisor;
+pub mod tracks;
+pub mod vad;
+pub mod wav;
@@ -0,0 +1,308 @@
+//! Model files on disk: which models can be switched to, which one a preset stands for,
+//! finding the companion files a backend loads next to its main model, and preparing them
+//! before the backend starts so a bad one can be set aside.
+
+use crate::config::{ModelConfig, ModelPreset, TranscriberBackend, DEFAULT_MODEL_NAME};
+use crate::error::{VoicyError, VoicyResult};
+use crate::platform::macos::ffi;
+use once_cell::sync::Lazy;
+use parking_lot::Mutex;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant};
+use tracing::{info, warn};
+
+/// A model that can be loaded in this build
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct InstalledModel {
+    /// Shown in the menu and Preferences
+    pub label: String,
+    pub backend: TranscriberBackend,
+    /// `model.model_name` (swift and mlx) or `model.whisper.model_path` (whisper)
+    pub name: String,
+}
+
+impl InstalledModel {
+    /// `model` switched to this one, its other settings kept
+    pub fn apply(&self, model: &ModelConfig) -> ModelConfig {
+        let mut model = model.clone();
+        model.preset = None;
+        model.backend = self.backend;
+        match self.backend {
+            TranscriberBackend::Whisper => model.whisper.model_path = Some(PathBuf::from(&self.name)),
+            _ => model.model_name = self.name.clone(),
+        }
+        model
+    }
+
+    /// Whether `model` is set to this one (rather than to a preset)
+    pub fn is_current(&self, model: &ModelConfig) -> bool {
+        model.preset.is_none()
+            && model.backend == self.backend
+            && match self.backend {
+                TranscriberBackend::Whisper => model.whisper.model_path.as_deref() == Some(Path::new(&self.name)),
+                _ => model.model_name == self.name,
+            }
+    }
+}
+
+/// How long a scan of the model folders is reused (the menu asks several times a second)
+const INSTALLED_CACHE_TTL: Duration = Duration::from_secs(10);
+
+static INSTALLED: Lazy<Mutex<Option<(Instant, Vec<InstalledModel>)>>> = Lazy::new(|| Mutex::new(None));
+
+/// Models to offer in the model picker: the default Parakeet, Core ML model folders and GGML
+/// files in ~/.typeswift/models (and next to the configured whisper model), Parakeet models
+/// in the Hugging Face cache for mlx, and whatever `model` is set to now. Backends this
+/// build leaves out are skipped.
+pub fn installed_models(model: &ModelConfig) -> Vec<InstalledModel> {
+    let mut cached = INSTALLED.lock();
+    let mut models = match *cached {
+        Some((at, ref models)) if at.elapsed() < INSTALLED_CACHE_TTL => models.clone(),
+        _ => {
+            let models = scan_models(model);
+            *cached = Some((Instant::now(), models.clone()));
+            models
+        }
+    };
+    drop(cached);
+    if model.preset.is_none() && !models.iter().any(|m| m.is_current(model)) {
+        if let Some(current) = current_model(model) {
+            models.push(current);
+        }
+    }
+    models
+}
+
+fn scan_models(model: &ModelConfig) -> Vec<InstalledModel> {
+    let mut models = Vec::new();
+    if cfg!(feature = "swift") {
+        models.push(InstalledModel {
+            label: "Parakeet TDT v3 (Core ML)".to_string(),
+            backend: TranscriberBackend::Swift,
+            name: DEFAULT_MODEL_NAME.to_string(),
+        });
+    }
+    for dir in model_dirs(model) {
+        let Ok(entries) = std::fs::read_dir(&dir) else { continue };
+        let mut paths: Vec<PathBuf> = entries.flatten().map(|e| e.path()).collect();
+        paths.sort();
+        for path in paths {
+            let file_name = path.file_name().map_or_else(String::new, |n| n.to_string_lossy().into_owned());
+            if cfg!(feature = "whisper") && file_name.starts_with("ggml-") && file_name.ends_with(".bin") {
+                let label = format!("{} (whisper.cpp)", file_name.trim_start_matches("ggml-").trim_end_matches(".bin"));
+                models.push(InstalledModel { label, backend: TranscriberBackend::Whisper, name: path_string(&path) });
+            } else if cfg!(feature = "swift") && path.is_dir() && contains_mlmodelc(&path) {
+                let label = format!("{} (Core ML)", file_name);
+                models.push(InstalledModel { label, backend: TranscriberBackend::Swift, name: path_string(&path) });
+            }
+        }
+    }
+    if cfg!(feature = "mlx") {
+        // The Hugging Face hub caches "org/name" as models--org--name
+        let hub = std::env::var("HOME").map(PathBuf::from).ok().map(|h| h.join(".cache").join("huggingface").join("hub"));
+        let mut names: Vec<String> = hub
+            .and_then(|hub| std::fs::read_dir(hub).ok())
+            .into_iter()
+            .flatten()
+            .flatten()
+            .filter_map(|e| {
+                let dir = e.file_name().to_string_lossy().into_owned();
+                let (org, name) = dir.strip_prefix("models--")?.split_once("--")?;
+                name.contains("parakeet").then(|| format!("{}/{}", org, name))
+            })
+            .collect();
+        names.sort();
+        for name in names {
+            models.push(InstalledModel {
+                label: format!("{} (MLX)", name.rsplit('/').next().unwrap_or(&name)),
+                backend: TranscriberBackend::Mlx,
+                name,
+            });
+        }
+    }
+    models
+}
+
+/// ~/.typeswift/models, and the folder of the configured whisper model if elsewhere
+fn model_dirs(model: &ModelConfig) -> Vec<PathBuf> {
+    let home = std::env::var("HOME").map(PathBuf::from).ok();
+    let mut dirs: Vec<PathBuf> = home.iter().map(|h| h.join(".typeswift").join("models")).collect();
+    if let Some(dir) = model.whisper.model_path.as_deref().and_then(Path::parent) {
+        if !dirs.iter().any(|d| d == dir) {
+            dirs.push(dir.to_path_buf());
+        }
+    }
+    dirs
+}
+
+/// whisper.cpp models a preset uses, best first: quantized where speed matters most, the
+/// English-only ".en" builds first when dictating in English
+fn whisper_variants(preset: ModelPreset, english: bool) -> Vec<String> {
+    let (size, quantized_first) = match preset {
+        ModelPreset::Fast => ("tiny", true),
+        ModelPreset::Balanced => ("base", true),
+        ModelPreset::Accurate => ("small", false),
+    };
+    let mut sizes = vec![size.to_string(), format!("{}.en", size)];
+    if english {
+        sizes.reverse();
+    }
+    let mut variants = Vec::new();
+    for size in sizes {
+        let quantized = [format!("{}-q5_1", size), format!("{}-q8_0", size)];
+        if quantized_first {
+            variants.extend(quantized);
+            variants.push(size);
+        } else {
+            variants.push(size);
+            variants.extend(quantized);
+        }
+    }
+    variants
+}
+
+/// The Parakeet model a preset uses on mlx. Only v3 is multilingual, so every preset uses it
+/// unless the language is set to English.
+fn parakeet_variant(preset: ModelPreset, english: bool) -> &'static str {
+    match preset {
+        ModelPreset::Fast if english => "mlx-community/parakeet-ctc-0.6b",
+        ModelPreset::Accurate if english => "mlx-community/parakeet-tdt-1.1b",
+        _ => DEFAULT_MODEL_NAME,
+    }
+}
+
+/// `model` with its preset (if any) turned into a concrete model for its backend:
+/// - whisper: tiny, base or small, from the GGML files in the model folders
+/// - mlx: Parakeet CTC 0.6B, TDT v3 or TDT 1.1B (downloaded on first use)
+/// - swift: Parakeet TDT v3, the one Core ML model it has
+///
+/// Fails if a whisper preset's model isn't downloaded.
+pub fn resolve_preset(model: &ModelConfig) -> VoicyResult<ModelConfig> {
+    let Some(preset) = model.preset else {
+        return Ok(model.clone());
+    };
+    let english = model.language.as_deref() == Some("en");
+    let mut resolved = model.clone();
+    match model.backend {
+        TranscriberBackend::Whisper => {
+            let variants = whisper_variants(preset, english);
+            let found = model_dirs(model).into_iter().find_map(|dir| {
+                variants.iter().map(|v| dir.join(format!("ggml-{}.bin", v))).find(|path| path.is_file())
+            });
+            let Some(path) = found else {
+                return Err(VoicyError::ModelLoadFailed(format!(
+                    "the {} preset needs ggml-{}.bin in ~/.typeswift/models \
+                     (from https://huggingface.co/ggerganov/whisper.cpp)",
+                    preset.label(),
+                    variants[0]
+                )));
+            };
+            resolved.whisper.model_path = Some(path);
+        }
+        TranscriberBackend::Mlx => resolved.model_name = parakeet_variant(preset, english).to_string(),
+        TranscriberBackend::Swift => resolved.model_name = DEFAULT_MODEL_NAME.to_string(),
+        // The remote worker serves whatever model its own config names
+        TranscriberBackend::Mock | TranscriberBackend::Remote => {}
+    }
+    info!("{} preset: using {}", preset.label(), describe(&resolved));
+    Ok(resolved)
+}
+
+fn describe(model: &ModelConfig) -> String {
+    match (model.backend, model.whisper.model_path.as_deref()) {
+        (TranscriberBackend::Whisper, Some(path)) => path_string(path),
+        _ => model.model_name.clone(),
+    }
+}
+
+/// The configured model, for when it isn't in one of the scanned places
+fn current_model(model: &ModelConfig) -> Option<InstalledModel> {
+    let name = match model.backend {
+        TranscriberBackend::Whisper => path_string(model.whisper.model_path.as_deref()?),
+        TranscriberBackend::Mock | TranscriberBackend::Remote => return None,
+        _ => model.model_name.clone(),
+    };
+    let short = name.rsplit('/').next().unwrap_or(&name).to_string();
+    Some(InstalledModel { label: format!("{} ({:?})", short, model.backend), backend: model.backend, name })
+}
+
+fn contains_mlmodelc(dir: &Path) -> bool {
+    std::fs::read_dir(dir)
+        .map(|entries| entries.flatten().any(|e| e.path().extension().is_some_and(|ext| ext == "mlmodelc")))
+        .unwrap_or(false)
+}
+
+fn path_string(path: &Path) -> String {
+    path.to_string_lossy().into_owned()
+}
+
+/// whisper.cpp's Core ML encoder for a GGML model
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum CoreMlEncoder {
+    /// Compiled and loads on this Mac; whisper.cpp will run the encoder with Core ML
+    Ready(PathBuf),
+    /// None next to the model; the GGML encoder runs
+    Missing,
+    /// Compiling or loading it failed; it was set aside and the GGML encoder runs
+    Failed(String),
+}
+
+/// Where whisper.cpp looks for the Core ML encoder of `ggml`: "ggml-base.en-q5_0.bin" has
+/// "ggml-base.en-encoder.mlmodelc" (the quantization suffix is dropped)
+pub fn coreml_encoder_path(ggml: &Path) -> PathBuf {
+    let stem = ggml.file_stem().map_or_else(String::new, |s| s.to_string_lossy().into_owned());
+    let stem = match stem.rfind('-') {
+        Some(pos) if is_quantization_suffix(&stem[pos..]) => &stem[..pos],
+        _ => stem.as_str(),
+    };
+    ggml.with_file_name(format!("{}-encoder.mlmodelc", stem))
+}
+
+/// "-q5_0", "-q8_0": whisper.cpp matches exactly this shape
+fn is_quantization_suffix(suffix: &str) -> bool {
+    let bytes = suffix.as_bytes();
+    bytes.len() == 5 && bytes[1] == b'q' && bytes[3] == b'_'
+}
+
+/// Written next to the encoder when it fails, so later launches skip it; delete to retry
+fn failure_marker(encoder: &Path) -> PathBuf {
+    encoder.with_extension("mlmodelc.failed")
+}
+
+/// Get the Core ML encoder for `ggml` ready: compile it from an .mlpackage or .mlmodel next to
+/// the model if only the source is there, then load it once. On failure it is moved aside
+/// (to "*.mlmodelc.disabled") so whisper.cpp falls back to its GGML encoder. Can take minutes
+/// the first time; call off the main thread.
+pub fn prepare_coreml_encoder(ggml: &Path) -> CoreMlEncoder {
+    let encoder = coreml_encoder_path(ggml);
+    let marker = failure_marker(&encoder);
+    if let Ok(reason) = std::fs::read_to_string(&marker) {
+        return CoreMlEncoder::Failed(reason);
+    }
+    let fail = |reason: String| {
+        warn!("Core ML encoder {}: {}; using the GGML encoder", encoder.display(), reason);
+        if encoder.exists() {
+            let _ = std::fs::rename(&encoder, encoder.with_extension("mlmodelc.disabled"));
+        }
+        let _ = std::fs::write(&marker, &reason);
+        CoreMlEncoder::Failed(reason)
+    };
+
+    if !encoder.exists() {
+        let source = ["mlpackage", "mlmodel"]
+            .iter()
+            .map(|ext| encoder.with_extension(ext))
+            .find(|path| path.exists());
+        let Some(source) = source else {
+            return CoreMlEncoder::Missing;
+        };
+        info!("Compiling Core ML encoder {} (first run only)", source.display());
+        if let Err(e) = ffi::compile_coreml_model(&source, &encoder) {
+            return fail(format!("compile failed: {}", e));
+        }
+    }
+    match ffi::check_coreml_model(&encoder) {
+        Ok(()) => CoreMlEncoder::Ready(encoder),
+        Err(e) => fail(format!("load failed: {}", e)),
+    }
+}
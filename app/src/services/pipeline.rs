@@ -0,0 +1,315 @@
+use crate::config::{Config, PipelineConfig, StageConfig};
+use crate::error::{VoicyError, VoicyResult};
+use crate::services::format::{apply_vocabulary, format_text, remove_fillers, replace_phrases};
+use crate::services::script;
+use std::ffi::{CStr, CString};
+use std::io::{Read, Write};
+use std::os::raw::{c_char, c_void};
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+use std::time::{Duration, Instant};
+use tracing::{debug, warn};
+
+/// What a stage knows about the utterance besides its text
+pub struct StageContext<'a> {
+    pub config: &'a Config,
+    /// The transcript as recognized, before any stage ran
+    pub raw_text: &'a str,
+    /// Bundle identifier of the app the text is going to
+    pub bundle_id: Option<&'a str>,
+    /// Profile named by the push-to-talk binding that started the dictation
+    pub profile: Option<&'a str>,
+}
+
+/// One step of post-processing. Implement this to add a stage in Rust; stages
+/// outside the crate plug in through the `command` and `dylib` stage types.
+pub trait PostProcessor: Send + Sync {
+    fn name(&self) -> &str;
+    fn process(&self, text: &str, ctx: &StageContext) -> VoicyResult<String>;
+}
+
+/// Ordered stages run on every final transcript. A failing stage is logged and
+/// skipped, so a broken plug-in never loses a dictation.
+#[derive(Default)]
+pub struct Pipeline {
+    stages: Vec<Box<dyn PostProcessor>>,
+}
+
+impl Pipeline {
+    pub fn from_config(config: &PipelineConfig) -> Self {
+        Self::build(config.stages.iter())
+    }
+
+    pub fn from_stages(stages: &[StageConfig]) -> Self {
+        Self::build(stages.iter())
+    }
+
+    /// Only the in-process stages. Streaming partials are re-processed many times
+    /// per utterance, so commands and plug-in libraries wait for the final text.
+    pub fn local_from_config(config: &PipelineConfig) -> Self {
+        Self::build(config.stages.iter().filter(|stage| {
+            !matches!(stage, StageConfig::Command { .. } | StageConfig::Llm { .. } | StageConfig::Dylib { .. })
+        }))
+    }
+
+    fn build<'a>(stages: impl Iterator<Item = &'a StageConfig>) -> Self {
+        let mut pipeline = Self::default();
+        for stage in stages {
+            match build_stage(stage) {
+                Ok(stage) => pipeline.push(stage),
+                Err(e) => warn!("Skipping post-processing stage: {}", e),
+            }
+        }
+        pipeline
+    }
+
+    pub fn push(&mut self, stage: Box<dyn PostProcessor>) {
+        self.stages.push(stage);
+    }
+
+    pub fn run(&self, text: &str, ctx: &StageContext) -> String {
+        let mut text = text.to_string();
+        for stage in &self.stages {
+            match stage.process(&text, ctx) {
+                Ok(out) => text = out,
+                Err(e) => warn!("Post-processing stage {} failed: {}", stage.name(), e),
+            }
+        }
+        text
+    }
+}
+
+fn build_stage(stage: &StageConfig) -> VoicyResult<Box<dyn PostProcessor>> {
+    Ok(match stage {
+        StageConfig::Vocabulary => Box::new(Vocabulary),
+        StageConfig::Format => Box::new(Format),
+        StageConfig::Replacements { rules } => Box::new(Replacements {
+            rules: rules.iter().map(|r| (r.from.clone(), r.to.clone())).collect(),
+        }),
+        StageConfig::FillerRemoval { words } => Box::new(FillerRemoval { words: words.clone() }),
+        StageConfig::Command { command, timeout_ms } => Box::new(ExternalCommand::new(
+            "command",
+            command,
+            None,
+            Duration::from_millis(*timeout_ms),
+        )?),
+        StageConfig::Llm { command, prompt, timeout_ms } => Box::new(ExternalCommand::new(
+            "llm",
+            command,
+            Some(prompt.clone()),
+            Duration::from_millis(*timeout_ms),
+        )?),
+        StageConfig::Dylib { path } => Box::new(Dylib::open(path)?),
+        StageConfig::Script { path } => script::load(path)?,
+    })
+}
+
+struct Vocabulary;
+
+impl PostProcessor for Vocabulary {
+    fn name(&self) -> &str {
+        "vocabulary"
+    }
+
+    fn process(&self, text: &str, ctx: &StageContext) -> VoicyResult<String> {
+        Ok(apply_vocabulary(text, &ctx.config.bias_phrases_for(ctx.profile, ctx.bundle_id)))
+    }
+}
+
+struct Format;
+
+impl PostProcessor for Format {
+    fn name(&self) -> &str {
+        "format"
+    }
+
+    fn process(&self, text: &str, ctx: &StageContext) -> VoicyResult<String> {
+        Ok(format_text(text, &ctx.config.format_options_for(ctx.profile, ctx.bundle_id)))
+    }
+}
+
+struct Replacements {
+    rules: Vec<(String, String)>,
+}
+
+impl PostProcessor for Replacements {
+    fn name(&self) -> &str {
+        "replacements"
+    }
+
+    fn process(&self, text: &str, _ctx: &StageContext) -> VoicyResult<String> {
+        let rules: Vec<(&str, &str)> = self.rules.iter().map(|(f, t)| (f.as_str(), t.as_str())).collect();
+        Ok(replace_phrases(text, &rules))
+    }
+}
+
+struct FillerRemoval {
+    words: Vec<String>,
+}
+
+impl PostProcessor for FillerRemoval {
+    fn name(&self) -> &str {
+        "filler_removal"
+    }
+
+    fn process(&self, text: &str, _ctx: &StageContext) -> VoicyResult<String> {
+        Ok(remove_fillers(text, &self.words))
+    }
+}
+
+/// Runs a program per utterance: text on stdin (after `prompt` and a blank line, if set),
+/// result on stdout. `TYPESWIFT_APP` holds the target app's bundle id.
+struct ExternalCommand {
+    kind: &'static str,
+    program: String,
+    args: Vec<String>,
+    prompt: Option<String>,
+    timeout: Duration,
+}
+
+impl ExternalCommand {
+    fn new(kind: &'static str, command: &[String], prompt: Option<String>, timeout: Duration) -> VoicyResult<Self> {
+        let (program, args) = command
+            .split_first()
+            .ok_or_else(|| VoicyError::PostProcessingFailed(format!("{} stage has an empty command", kind)))?;
+        Ok(Self { kind, program: program.clone(), args: args.to_vec(), prompt, timeout })
+    }
+}
+
+impl PostProcessor for ExternalCommand {
+    fn name(&self) -> &str {
+        self.kind
+    }
+
+    fn process(&self, text: &str, ctx: &StageContext) -> VoicyResult<String> {
+        let fail = |msg: String| VoicyError::PostProcessingFailed(format!("{}: {}", self.program, msg));
+        let mut child = Command::new(&self.program)
+            .args(&self.args)
+            .env("TYPESWIFT_APP", ctx.bundle_id.unwrap_or(""))
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::null())
+            .spawn()
+            .map_err(|e| fail(e.to_string()))?;
+
+        let input = match self.prompt {
+            Some(ref prompt) => format!("{}\n\n{}", prompt, text),
+            None => text.to_string(),
+        };
+        // Feed and drain on threads so a chatty program can't deadlock on full pipes
+        let mut stdin = child.stdin.take().ok_or_else(|| fail("no stdin".to_string()))?;
+        std::thread::spawn(move || {
+            let _ = stdin.write_all(input.as_bytes());
+        });
+        let mut stdout = child.stdout.take().ok_or_else(|| fail("no stdout".to_string()))?;
+        let reader = std::thread::spawn(move || {
+            let mut out = String::new();
+            stdout.read_to_string(&mut out).map(|_| out)
+        });
+
+        let started = Instant::now();
+        let status = loop {
+            if let Some(status) = child.try_wait().map_err(|e| fail(e.to_string()))? {
+                break status;
+            }
+            if started.elapsed() >= self.timeout {
+                let _ = child.kill();
+                let _ = child.wait();
+                return Err(fail(format!("timed out after {} ms", self.timeout.as_millis())));
+            }
+            std::thread::sleep(Duration::from_millis(10));
+        };
+        if !status.success() {
+            return Err(fail(format!("exited with {}", status)));
+        }
+        let out = reader
+            .join()
+            .map_err(|_| fail("output reader panicked".to_string()))?
+            .map_err(|e| fail(e.to_string()))?;
+        debug!("{} stage returned {} chars in {:?}", self.kind, out.len(), started.elapsed());
+        Ok(out.trim_end_matches(['\n', '\r']).to_string())
+    }
+}
+
+type ProcessFn = unsafe extern "C" fn(text: *const c_char, bundle_id: *const c_char) -> *mut c_char;
+type FreeFn = unsafe extern "C" fn(text: *mut c_char);
+
+/// A plug-in library exporting
+/// `char *typeswift_process(const char *text, const char *bundle_id)` (NULL keeps the text)
+/// and optionally `void typeswift_free(char *)` to release results (else `free`).
+/// Its functions must be safe to call from any thread.
+struct Dylib {
+    path: PathBuf,
+    handle: *mut c_void,
+    process: ProcessFn,
+    free: Option<FreeFn>,
+}
+
+// The library handle is only used to look up and call thread-safe functions
+unsafe impl Send for Dylib {}
+unsafe impl Sync for Dylib {}
+
+impl Dylib {
+    fn open(path: &Path) -> VoicyResult<Self> {
+        let fail = |msg: String| VoicyError::PostProcessingFailed(format!("{}: {}", path.display(), msg));
+        let c_path = CString::new(path.to_string_lossy().as_bytes()).map_err(|e| fail(e.to_string()))?;
+        unsafe {
+            let handle = libc::dlopen(c_path.as_ptr(), libc::RTLD_NOW | libc::RTLD_LOCAL);
+            if handle.is_null() {
+                let reason = libc::dlerror();
+                let reason = if reason.is_null() {
+                    "dlopen failed".to_string()
+                } else {
+                    CStr::from_ptr(reason).to_string_lossy().into_owned()
+                };
+                return Err(fail(reason));
+            }
+            let process_name = CString::new("typeswift_process").unwrap();
+            let free_name = CString::new("typeswift_free").unwrap();
+            let process = libc::dlsym(handle, process_name.as_ptr());
+            if process.is_null() {
+                libc::dlclose(handle);
+                return Err(fail("missing typeswift_process".to_string()));
+            }
+            let free = libc::dlsym(handle, free_name.as_ptr());
+            Ok(Self {
+                path: path.to_path_buf(),
+                handle,
+                process: std::mem::transmute::<*mut c_void, ProcessFn>(process),
+                free: (!free.is_null()).then(|| std::mem::transmute::<*mut c_void, FreeFn>(free)),
+            })
+        }
+    }
+}
+
+impl PostProcessor for Dylib {
+    fn name(&self) -> &str {
+        "dylib"
+    }
+
+    fn process(&self, text: &str, ctx: &StageContext) -> VoicyResult<String> {
+        let c_text = CString::new(text.replace('\0', "")).unwrap();
+        let c_app = CString::new(ctx.bundle_id.unwrap_or("").replace('\0', "")).unwrap();
+        unsafe {
+            let result = (self.process)(c_text.as_ptr(), c_app.as_ptr());
+            if result.is_null() {
+                return Ok(text.to_string());
+            }
+            let out = CStr::from_ptr(result).to_string_lossy().into_owned();
+            match self.free {
+                Some(free) => free(result),
+                None => libc::free(result as *mut c_void),
+            }
+            debug!("{} returned {} chars", self.path.display(), out.len());
+            Ok(out)
+        }
+    }
+}
+
+impl Drop for Dylib {
+    fn drop(&mut self) {
+        unsafe {
+            libc::dlclose(self.handle);
+        }
+    }
+}
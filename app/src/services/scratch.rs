@@ -0,0 +1,179 @@
+//! Recording buffer that moves to a memory-mapped temp file once a recording gets long, so
+//! multi-minute captures live in file-backed pages the kernel can write out and drop instead
+//! of one ever-growing heap allocation.
+
+use std::fs::File;
+use std::ops::{Deref, DerefMut, Range};
+use std::os::fd::AsRawFd;
+use std::sync::atomic::{AtomicU32, Ordering};
+use tracing::{info, warn};
+
+/// Mono f32 samples, in memory until there are more than `spill_after`, then in a mapped file.
+/// Derefs to `[f32]`, so transcription reads it a chunk at a time without copying.
+pub struct SampleBuffer {
+    memory: Vec<f32>,
+    mapped: Option<MappedSamples>,
+    spill_after: usize,
+}
+
+impl SampleBuffer {
+    pub fn with_capacity(capacity: usize, spill_after: usize) -> Self {
+        Self { memory: Vec::with_capacity(capacity.min(spill_after)), mapped: None, spill_after }
+    }
+
+    pub fn extend_from_slice(&mut self, samples: &[f32]) {
+        if self.mapped.is_none() && self.memory.len() + samples.len() > self.spill_after {
+            self.spill();
+        }
+        let Some(ref mut mapped) = self.mapped else {
+            self.memory.extend_from_slice(samples);
+            return;
+        };
+        if let Err(e) = mapped.extend_from_slice(samples) {
+            // Disk full or similar: carry on in memory rather than lose the recording
+            warn!("Recording scratch file failed ({}); keeping the recording in memory", e);
+            self.memory = mapped.as_slice().to_vec();
+            self.mapped = None;
+            self.memory.extend_from_slice(samples);
+        }
+    }
+
+    pub fn push(&mut self, sample: f32) {
+        self.extend_from_slice(&[sample]);
+    }
+
+    /// Empty the buffer, deleting the scratch file if there is one
+    pub fn clear(&mut self) {
+        self.mapped = None;
+        self.memory.clear();
+    }
+
+    /// Keep only the samples in `range`, moved to the start
+    pub fn retain_range(&mut self, range: Range<usize>) {
+        let len = range.len();
+        self.copy_within(range, 0);
+        match self.mapped {
+            Some(ref mut mapped) => mapped.len = len,
+            None => self.memory.truncate(len),
+        }
+    }
+
+    /// Whether the samples are in a scratch file rather than memory
+    pub fn is_spilled(&self) -> bool {
+        self.mapped.is_some()
+    }
+
+    fn spill(&mut self) {
+        match MappedSamples::create(self.spill_after * 2) {
+            Ok(mut mapped) => {
+                if mapped.extend_from_slice(&self.memory).is_ok() {
+                    info!("Recording passed {} samples; continuing in a scratch file", self.spill_after);
+                    self.mapped = Some(mapped);
+                    // Give back the allocation; `clear` starts the next recording small again
+                    self.memory = Vec::new();
+                }
+            }
+            Err(e) => warn!("Couldn't create a recording scratch file ({}); keeping the recording in memory", e),
+        }
+    }
+}
+
+impl Deref for SampleBuffer {
+    type Target = [f32];
+
+    fn deref(&self) -> &[f32] {
+        match self.mapped {
+            Some(ref mapped) => mapped.as_slice(),
+            None => &self.memory,
+        }
+    }
+}
+
+impl DerefMut for SampleBuffer {
+    fn deref_mut(&mut self) -> &mut [f32] {
+        match self.mapped {
+            Some(ref mut mapped) => mapped.as_mut_slice(),
+            None => &mut self.memory,
+        }
+    }
+}
+
+/// Samples in a shared mapping of an unlinked temp file, which grows by remapping
+struct MappedSamples {
+    file: File,
+    ptr: *mut f32,
+    capacity: usize,
+    len: usize,
+}
+
+// The mapping is owned by this value alone, like a Vec's allocation
+unsafe impl Send for MappedSamples {}
+unsafe impl Sync for MappedSamples {}
+
+impl MappedSamples {
+    fn create(capacity: usize) -> std::io::Result<Self> {
+        static NEXT: AtomicU32 = AtomicU32::new(0);
+        let path = std::env::temp_dir().join(format!(
+            "typeswift-recording-{}-{}.f32",
+            std::process::id(),
+            NEXT.fetch_add(1, Ordering::Relaxed)
+        ));
+        let file = std::fs::OpenOptions::new().read(true).write(true).create_new(true).open(&path)?;
+        // Unlinked straight away: the mapping keeps it alive, and a crash leaves no audio behind
+        let _ = std::fs::remove_file(&path);
+        file.set_len((capacity * std::mem::size_of::<f32>()) as u64)?;
+        let ptr = map(&file, capacity)?;
+        Ok(Self { file, ptr, capacity, len: 0 })
+    }
+
+    fn extend_from_slice(&mut self, samples: &[f32]) -> std::io::Result<()> {
+        let needed = self.len + samples.len();
+        if needed > self.capacity {
+            self.grow(needed.max(self.capacity * 2))?;
+        }
+        unsafe { std::ptr::copy_nonoverlapping(samples.as_ptr(), self.ptr.add(self.len), samples.len()) };
+        self.len = needed;
+        Ok(())
+    }
+
+    fn grow(&mut self, capacity: usize) -> std::io::Result<()> {
+        self.file.set_len((capacity * std::mem::size_of::<f32>()) as u64)?;
+        let ptr = map(&self.file, capacity)?;
+        unsafe { libc::munmap(self.ptr.cast(), self.capacity * std::mem::size_of::<f32>()) };
+        self.ptr = ptr;
+        self.capacity = capacity;
+        Ok(())
+    }
+
+    fn as_slice(&self) -> &[f32] {
+        unsafe { std::slice::from_raw_parts(self.ptr, self.len) }
+    }
+
+    fn as_mut_slice(&mut self) -> &mut [f32] {
+        unsafe { std::slice::from_raw_parts_mut(self.ptr, self.len) }
+    }
+}
+
+impl Drop for MappedSamples {
+    fn drop(&mut self) {
+        unsafe { libc::munmap(self.ptr.cast(), self.capacity * std::mem::size_of::<f32>()) };
+    }
+}
+
+fn map(file: &File, capacity: usize) -> std::io::Result<*mut f32> {
+    let ptr = unsafe {
+        libc::mmap(
+            std::ptr::null_mut(),
+            capacity * std::mem::size_of::<f32>(),
+            libc::PROT_READ | libc::PROT_WRITE,
+            libc::MAP_SHARED,
+            file.as_raw_fd(),
+            0,
+        )
+    };
+    if ptr == libc::MAP_FAILED {
+        Err(std::io::Error::last_os_error())
+    } else {
+        Ok(ptr.cast())
+    }
+}
@@ -0,0 +1,94 @@
+//! Rhai transform scripts for the post-processing pipeline.
+//!
+//! A script sees the text so far as `text`, plus `raw` (the transcript before any stage),
+//! `app` (target bundle id, "" when unknown) and `profile` ("" when none). Its last
+//! expression is the new text; `()` leaves the text unchanged. Scripts get no file,
+//! network or process access, and runaway loops stop after an operation budget.
+
+use crate::error::VoicyResult;
+use crate::services::pipeline::PostProcessor;
+use std::path::Path;
+
+#[cfg(feature = "scripting")]
+mod rhai_stage {
+    use crate::error::{VoicyError, VoicyResult};
+    use crate::services::pipeline::{PostProcessor, StageContext};
+    use rhai::{Dynamic, Engine, EvalAltResult, Scope, AST};
+    use std::path::{Path, PathBuf};
+
+    /// Operations a script may run per utterance before it is stopped
+    const MAX_OPERATIONS: u64 = 1_000_000;
+    const MAX_STRING_SIZE: usize = 64 * 1024;
+
+    struct ScriptStage {
+        path: PathBuf,
+        engine: Engine,
+        ast: AST,
+    }
+
+    fn regex(pattern: &str) -> Result<regex::Regex, Box<EvalAltResult>> {
+        regex::Regex::new(pattern).map_err(|e| e.to_string().into())
+    }
+
+    fn engine() -> Engine {
+        let mut engine = Engine::new();
+        engine.set_max_operations(MAX_OPERATIONS);
+        engine.set_max_string_size(MAX_STRING_SIZE);
+        engine.set_max_call_levels(32);
+        engine.set_max_expr_depths(64, 32);
+        engine.on_print(|s| tracing::info!("script: {}", s));
+        engine.on_debug(|s, _, pos| tracing::debug!("script {}: {}", pos, s));
+        engine.register_fn("regex_replace", |text: &str, pattern: &str, with: &str| {
+            regex(pattern).map(|re| re.replace_all(text, with).into_owned())
+        });
+        engine.register_fn("regex_match", |text: &str, pattern: &str| regex(pattern).map(|re| re.is_match(text)));
+        engine
+    }
+
+    pub fn load(path: &Path) -> VoicyResult<Box<dyn PostProcessor>> {
+        let fail = |msg: String| VoicyError::PostProcessingFailed(format!("{}: {}", path.display(), msg));
+        let source = std::fs::read_to_string(path).map_err(|e| fail(e.to_string()))?;
+        let engine = engine();
+        let ast = engine.compile(&source).map_err(|e| fail(e.to_string()))?;
+        Ok(Box::new(ScriptStage { path: path.to_path_buf(), engine, ast }))
+    }
+
+    impl PostProcessor for ScriptStage {
+        fn name(&self) -> &str {
+            "script"
+        }
+
+        fn process(&self, text: &str, ctx: &StageContext) -> VoicyResult<String> {
+            let fail = |msg: String| VoicyError::PostProcessingFailed(format!("{}: {}", self.path.display(), msg));
+            let profile = ctx.config.profile_for(ctx.profile, ctx.bundle_id).map(|p| p.name.clone());
+            let profile = profile.unwrap_or_default();
+            let mut scope = Scope::new();
+            scope.push("text", text.to_string());
+            scope.push_constant("raw", ctx.raw_text.to_string());
+            scope.push_constant("app", ctx.bundle_id.unwrap_or("").to_string());
+            scope.push_constant("profile", profile);
+            let result: Dynamic =
+                self.engine.eval_ast_with_scope(&mut scope, &self.ast).map_err(|e| fail(e.to_string()))?;
+            if result.is_unit() {
+                return Ok(text.to_string());
+            }
+            result.into_string().map_err(|kind| fail(format!("returned {} instead of a string", kind)))
+        }
+    }
+}
+
+#[cfg(not(feature = "scripting"))]
+mod rhai_stage {
+    use crate::error::{VoicyError, VoicyResult};
+    use crate::services::pipeline::PostProcessor;
+    use std::path::Path;
+
+    pub fn load(_path: &Path) -> VoicyResult<Box<dyn PostProcessor>> {
+        Err(VoicyError::PostProcessingFailed("built without the `scripting` feature".to_string()))
+    }
+}
+
+/// Compile the script at `path` into a pipeline stage
+pub fn load(path: &Path) -> VoicyResult<Box<dyn PostProcessor>> {
+    rhai_stage::load(path)
+}
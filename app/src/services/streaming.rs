@@ -0,0 +1,90 @@
+use crate::services::audio::Transcript;
+use crate::services::format::CursorContext;
+
+/// Keystrokes that turn the text typed so far into a revised transcript
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Edit {
+    pub backspaces: usize,
+    pub insert: String,
+}
+
+impl Edit {
+    pub fn is_empty(&self) -> bool {
+        self.backspaces == 0 && self.insert.is_empty()
+    }
+}
+
+/// Minimal backspace+retype edit from `typed` to `target`: erase back to the longest
+/// common prefix, then type the rest. `None` when more than `max_distance` characters
+/// would have to be erased.
+pub fn plan_correction(typed: &str, target: &str, max_distance: usize) -> Option<Edit> {
+    let mut common = 0;
+    for ((i, a), b) in typed.char_indices().zip(target.chars()) {
+        if a != b {
+            break;
+        }
+        common = i + a.len_utf8();
+    }
+    let backspaces = typed[common..].chars().count();
+    if backspaces > max_distance {
+        return None;
+    }
+    Some(Edit { backspaces, insert: target[common..].to_string() })
+}
+
+/// Tracks what streaming mode has typed for the current utterance and turns each
+/// revised partial transcript into a correction
+pub struct StreamingManager {
+    typed: String,
+    max_correction: usize,
+    context: CursorContext,
+}
+
+impl StreamingManager {
+    /// `context` (spacing and casing) is read once up front, since later checks
+    /// would see our own streamed text
+    pub fn new(max_correction: usize, context: CursorContext) -> Self {
+        Self { typed: String::new(), max_correction, context }
+    }
+
+    pub fn context(&self) -> CursorContext {
+        self.context
+    }
+
+    /// Text typed so far
+    pub fn typed(&self) -> &str {
+        &self.typed
+    }
+
+    /// Edit for a revised transcript, assuming the caller applies it.
+    /// Revisions reaching further back than the correction limit are left alone.
+    pub fn update(&mut self, transcript: &str) -> Option<Edit> {
+        let edit = plan_correction(&self.typed, transcript, self.max_correction)?;
+        if edit.is_empty() {
+            return None;
+        }
+        self.typed = transcript.to_string();
+        Some(edit)
+    }
+}
+
+/// The part of a partial transcript old enough to trust: words ending at least
+/// `hold_back_ms` before the end of the audio. Without word timings the last
+/// word is held back instead.
+pub fn stable_text(transcript: &Transcript, hold_back_ms: u64) -> String {
+    if hold_back_ms == 0 {
+        return transcript.text.clone();
+    }
+    if transcript.tokens.is_empty() {
+        let words: Vec<&str> = transcript.text.split_whitespace().collect();
+        return words[..words.len().saturating_sub(1)].join(" ");
+    }
+    let cutoff = transcript.duration_ms.saturating_sub(hold_back_ms);
+    transcript
+        .tokens
+        .iter()
+        .take_while(|t| t.end_ms <= cutoff)
+        .map(|t| t.text.as_str())
+        .collect::<Vec<_>>()
+        .join(" ")
+}
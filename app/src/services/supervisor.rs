@@ -0,0 +1,707 @@
+//! Transcription backends in supervised child processes, so a model crash or hang never takes
+//! down hotkeys and typing. The supervisor restarts a worker that dies or misses a heartbeat,
+//! and stops restarting for a while (a circuit breaker) when it keeps failing.
+//!
+//! Workers listen on a Unix socket and speak one protocol: a JSON line, followed for
+//! transcription by the raw little-endian f32 samples, answered by one JSON line.
+//!
+//!   {"ping": true}                                 -> {"pong": true, "codecs": [..]}
+//!   {"samples": N, "sample_rate": R, "prompt": {}}  -> {"text", "tokens"} or {"error"}
+//!
+//! `tools/parakeet_mlx_server.py` and `typeswift backend-worker` (`serve`) implement it.
+//! `typeswift backend-worker --listen ADDR` (`serve_tcp`) offers the same over TCP to the
+//! `remote` backend (`RemoteWorker`) on another machine; with a shared token set, every line
+//! the client sends also carries `"token"`. When the pong lists codecs the worker decodes, the
+//! client may send the audio encoded with one of them (`services::encode`) instead of raw
+//! samples, adding `"codec": C, "bytes": B` to the header.
+
+use crate::config::AudioCodec;
+use crate::error::{VoicyError, VoicyResult};
+use crate::services::audio::{Token, Transcript, TranscriptionPrompt};
+use crate::services::backend::TranscriptionBackend;
+use crate::services::encode;
+use parking_lot::Mutex;
+use serde_json::{json, Value};
+use std::collections::VecDeque;
+use std::ffi::OsString;
+use std::io::{BufRead, BufReader, Read, Write};
+use std::net::{TcpListener, TcpStream, ToSocketAddrs};
+use std::os::unix::net::{UnixListener, UnixStream};
+use std::path::{Path, PathBuf};
+use std::process::{Child, Command, Stdio};
+use std::sync::atomic::{AtomicU32, Ordering};
+use std::sync::{Arc, Weak};
+use std::time::{Duration, Instant};
+use tracing::{error, info, warn};
+
+const HEARTBEAT_INTERVAL: Duration = Duration::from_secs(15);
+const HEARTBEAT_TIMEOUT: Duration = Duration::from_secs(5);
+/// How long reaching a remote worker may take
+const CONNECT_TIMEOUT: Duration = Duration::from_secs(5);
+/// Longest one transcription may take before the worker is considered hung
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(120);
+/// This many failures within `FAILURE_WINDOW` open the circuit breaker for `BREAKER_COOLDOWN`
+const MAX_FAILURES: usize = 3;
+const FAILURE_WINDOW: Duration = Duration::from_secs(300);
+const BREAKER_COOLDOWN: Duration = Duration::from_secs(60);
+/// Longest JSON line either side reads; a prompt or a reply's word timings fit well within it
+const MAX_LINE_BYTES: u64 = 1 << 20;
+/// Longest audio one request may carry. The app sends chunks far shorter than this, so
+/// anything longer is a bad or hostile client, not a long dictation.
+const MAX_REQUEST_MINUTES: u64 = 10;
+
+/// How to start a worker; `--socket PATH` is appended to `args`
+#[derive(Debug, Clone)]
+pub struct WorkerCommand {
+    /// For logs and errors, e.g. "MLX server"
+    pub name: String,
+    pub program: OsString,
+    pub args: Vec<OsString>,
+    /// How long loading the model may take
+    pub startup_timeout: Duration,
+}
+
+pub struct Supervisor {
+    command: WorkerCommand,
+    worker: Mutex<Option<Worker>>,
+    breaker: Mutex<Breaker>,
+}
+
+impl Supervisor {
+    /// Start the worker, and a heartbeat thread that replaces it if it stops answering
+    pub fn start(command: WorkerCommand) -> VoicyResult<Arc<Self>> {
+        let worker = Worker::spawn(&command)
+            .map_err(|e| VoicyError::ModelLoadFailed(format!("{}: {}", command.name, e)))?;
+        let supervisor = Arc::new(Self {
+            command,
+            worker: Mutex::new(Some(worker)),
+            breaker: Mutex::new(Breaker::default()),
+        });
+        spawn_heartbeat(Arc::downgrade(&supervisor));
+        Ok(supervisor)
+    }
+
+    /// Process id of the running worker
+    pub fn pid(&self) -> Option<u32> {
+        self.worker.lock().as_ref().map(|worker| worker.child.id())
+    }
+
+    /// Transcribe in the worker, restarting it once if it has died or hangs
+    pub fn transcribe(&self, audio: &[f32], sample_rate: u32, prompt: &TranscriptionPrompt) -> VoicyResult<Transcript> {
+        let err = |msg: String| VoicyError::TranscriptionFailed(format!("{}: {}", self.command.name, msg));
+        let mut worker = self.worker.lock();
+        let mut last_error = String::new();
+        for _ in 0..2 {
+            self.breaker.lock().check().map_err(err)?;
+            let result = self
+                .running(&mut *worker)
+                .and_then(|w| w.transcribe(audio, sample_rate, prompt).map_err(|e| e.to_string()));
+            match result {
+                Ok(Reply { error: Some(message), .. }) => return Err(err(message)),
+                Ok(reply) => {
+                    let duration_ms = audio.len() as u64 * 1000 / sample_rate.max(1) as u64;
+                    return Ok(Transcript { text: reply.text, tokens: reply.tokens, duration_ms });
+                }
+                Err(e) => {
+                    warn!("{} failed ({}); restarting it", self.command.name, e);
+                    *worker = None;
+                    self.breaker.lock().record_failure(&self.command.name);
+                    last_error = e;
+                }
+            }
+        }
+        Err(err(last_error))
+    }
+
+    /// The worker in `slot`, started if there is none
+    fn running<'a>(&self, slot: &'a mut Option<Worker>) -> Result<&'a mut Worker, String> {
+        if slot.is_none() {
+            *slot = Some(Worker::spawn(&self.command)?);
+        }
+        Ok(slot.as_mut().expect("worker was just started"))
+    }
+
+    fn heartbeat(&self) {
+        // A transcription in progress shows it's alive
+        let Some(mut worker) = self.worker.try_lock() else { return };
+        let healthy = match worker.as_mut().map(Worker::ping) {
+            Some(Ok(())) => true,
+            Some(Err(e)) => {
+                warn!("{} missed a heartbeat ({}); restarting it", self.command.name, e);
+                false
+            }
+            None => false,
+        };
+        if healthy {
+            return;
+        }
+        if worker.take().is_some() {
+            self.breaker.lock().record_failure(&self.command.name);
+        }
+        // Restart now so the next dictation doesn't wait for the model to load
+        if self.breaker.lock().check().is_ok() {
+            if let Err(e) = self.running(&mut *worker) {
+                warn!("{} didn't restart: {}", self.command.name, e);
+                self.breaker.lock().record_failure(&self.command.name);
+            }
+        }
+    }
+}
+
+fn spawn_heartbeat(supervisor: Weak<Supervisor>) {
+    std::thread::spawn(move || loop {
+        std::thread::sleep(HEARTBEAT_INTERVAL);
+        // Stops once the backend is dropped
+        let Some(supervisor) = supervisor.upgrade() else { return };
+        supervisor.heartbeat();
+    });
+}
+
+/// Opens after `MAX_FAILURES` crashes in `FAILURE_WINDOW`; requests then fail at once
+/// instead of restarting a worker that won't stay up
+#[derive(Default)]
+struct Breaker {
+    failures: VecDeque<Instant>,
+    open_until: Option<Instant>,
+}
+
+impl Breaker {
+    fn check(&mut self) -> Result<(), String> {
+        match self.open_until {
+            Some(until) if Instant::now() < until => Err(format!(
+                "stopped after {} failures; retrying in {}s",
+                MAX_FAILURES,
+                (until - Instant::now()).as_secs() + 1
+            )),
+            Some(_) => {
+                self.open_until = None;
+                self.failures.clear();
+                Ok(())
+            }
+            None => Ok(()),
+        }
+    }
+
+    fn record_failure(&mut self, name: &str) {
+        let now = Instant::now();
+        self.failures.push_back(now);
+        while self.failures.front().is_some_and(|&at| now.duration_since(at) > FAILURE_WINDOW) {
+            self.failures.pop_front();
+        }
+        if self.failures.len() >= MAX_FAILURES && self.open_until.is_none() {
+            error!("{} failed {} times; not restarting it for {:?}", name, self.failures.len(), BREAKER_COOLDOWN);
+            self.open_until = Some(now + BREAKER_COOLDOWN);
+        }
+    }
+}
+
+#[derive(serde::Deserialize)]
+struct Reply {
+    #[serde(default)]
+    text: String,
+    #[serde(default)]
+    tokens: Vec<Token>,
+    error: Option<String>,
+}
+
+/// A running worker process and its connection; killed when dropped
+struct Worker {
+    child: Child,
+    socket: PathBuf,
+    stream: UnixStream,
+}
+
+impl Worker {
+    /// Start the worker and wait until it accepts connections
+    fn spawn(command: &WorkerCommand) -> Result<Self, String> {
+        static NEXT: AtomicU32 = AtomicU32::new(0);
+        let socket = std::env::temp_dir().join(format!(
+            "typeswift-worker-{}-{}.sock",
+            std::process::id(),
+            NEXT.fetch_add(1, Ordering::Relaxed)
+        ));
+        let _ = std::fs::remove_file(&socket);
+
+        let mut child = Command::new(&command.program)
+            .args(&command.args)
+            .arg("--socket")
+            .arg(&socket)
+            .stdin(Stdio::null())
+            .stdout(Stdio::null())
+            .stderr(Stdio::piped())
+            .spawn()
+            .map_err(|e| format!("couldn't run {}: {}", command.program.to_string_lossy(), e))?;
+        info!("{} started (pid {})", command.name, child.id());
+        // Forward the worker's output to our log
+        if let Some(stderr) = child.stderr.take() {
+            let name = command.name.clone();
+            std::thread::spawn(move || {
+                for line in BufReader::new(stderr).lines().map_while(Result::ok) {
+                    info!("{}: {}", name, line);
+                }
+            });
+        }
+
+        let deadline = Instant::now() + command.startup_timeout;
+        loop {
+            if let Ok(Some(status)) = child.try_wait() {
+                return Err(format!("exited with {} while starting", status));
+            }
+            if let Ok(stream) = UnixStream::connect(&socket) {
+                return Ok(Self { child, socket, stream });
+            }
+            if Instant::now() >= deadline {
+                let _ = child.kill();
+                let _ = child.wait();
+                return Err(format!("not ready after {:?}", command.startup_timeout));
+            }
+            std::thread::sleep(Duration::from_millis(200));
+        }
+    }
+
+    fn transcribe(&mut self, audio: &[f32], sample_rate: u32, prompt: &TranscriptionPrompt) -> std::io::Result<Reply> {
+        self.stream.set_read_timeout(Some(REQUEST_TIMEOUT))?;
+        // Same machine, so nothing is gained by encoding
+        request(&self.stream, None, None, audio, sample_rate, prompt)
+    }
+
+    fn ping(&mut self) -> std::io::Result<()> {
+        self.stream.set_read_timeout(Some(HEARTBEAT_TIMEOUT))?;
+        ping(&self.stream, None).map(drop)
+    }
+}
+
+/// Send one transcription request on `stream`, encoded with `codec` if given, and read the reply
+fn request<S>(
+    stream: &S,
+    token: Option<&str>,
+    codec: Option<AudioCodec>,
+    audio: &[f32],
+    sample_rate: u32,
+    prompt: &TranscriptionPrompt,
+) -> std::io::Result<Reply>
+where
+    for<'a> &'a S: Read + Write,
+{
+    let mut header = json!({
+        "samples": audio.len(),
+        "sample_rate": sample_rate,
+        "prompt": {
+            "context": prompt.context,
+            "bias_phrases": prompt.bias_phrases,
+            "language": prompt.language,
+        },
+    });
+    if let Some(token) = token {
+        header["token"] = json!(token);
+    }
+    let encoded = codec.filter(|_| !audio.is_empty()).and_then(|codec| match encode::encode(audio, sample_rate, codec) {
+        Ok(bytes) => Some((codec, bytes)),
+        Err(e) => {
+            warn!("{}; sending raw samples", e);
+            None
+        }
+    });
+    if let Some((codec, ref bytes)) = encoded {
+        header["codec"] = json!(codec);
+        header["bytes"] = json!(bytes.len());
+    }
+    let mut message = format!("{}\n", header).into_bytes();
+    match encoded {
+        Some((_, bytes)) => message.extend_from_slice(&bytes),
+        None => {
+            message.reserve(audio.len() * 4);
+            for sample in audio {
+                message.extend_from_slice(&sample.to_le_bytes());
+            }
+        }
+    }
+    let mut writer = stream;
+    writer.write_all(&message)?;
+    let line = read_line(stream)?;
+    serde_json::from_str(&line).map_err(std::io::Error::other)
+}
+
+/// Check the worker answers; returns the codecs it decodes
+fn ping<S>(stream: &S, token: Option<&str>) -> std::io::Result<Vec<AudioCodec>>
+where
+    for<'a> &'a S: Read + Write,
+{
+    let mut line = json!({ "ping": true });
+    if let Some(token) = token {
+        line["token"] = json!(token);
+    }
+    let mut writer = stream;
+    writer.write_all(format!("{}\n", line).as_bytes())?;
+    let reply: Value = serde_json::from_str(&read_line(stream)?).map_err(std::io::Error::other)?;
+    if reply["pong"].as_bool() == Some(true) {
+        Ok(serde_json::from_value(reply["codecs"].clone()).unwrap_or_default())
+    } else {
+        Err(std::io::Error::other("unexpected reply to ping"))
+    }
+}
+
+fn read_line<S>(stream: &S) -> std::io::Result<String>
+where
+    for<'a> &'a S: Read,
+{
+    // Replies are one line each, so nothing is buffered past it
+    read_bounded_line(&mut BufReader::new(stream))?.ok_or_else(|| std::io::ErrorKind::UnexpectedEof.into())
+}
+
+/// Read one line of at most `MAX_LINE_BYTES`; `None` when the stream ended cleanly
+fn read_bounded_line(reader: &mut impl BufRead) -> std::io::Result<Option<String>> {
+    let mut line = String::new();
+    let read = reader.by_ref().take(MAX_LINE_BYTES).read_line(&mut line)?;
+    if read == 0 {
+        return Ok(None);
+    }
+    if !line.ends_with('\n') && read as u64 == MAX_LINE_BYTES {
+        return Err(std::io::Error::new(
+            std::io::ErrorKind::InvalidData,
+            format!("line longer than {} bytes", MAX_LINE_BYTES),
+        ));
+    }
+    Ok(Some(line))
+}
+
+impl Drop for Worker {
+    fn drop(&mut self) {
+        let _ = self.child.kill();
+        let _ = self.child.wait();
+        let _ = std::fs::remove_file(&self.socket);
+    }
+}
+
+/// Serve `backend` on `socket` until the parent process exits (`typeswift backend-worker`)
+pub fn serve(socket: &Path, backend: &dyn TranscriptionBackend) -> std::io::Result<()> {
+    let _ = std::fs::remove_file(socket);
+    let listener = UnixListener::bind(socket)?;
+    exit_with_parent();
+    info!("Serving {} on {}", backend.description(), socket.display());
+    for stream in listener.incoming() {
+        if let Err(e) = serve_connection(&stream?, backend, None) {
+            warn!("Connection closed: {}", e);
+        }
+    }
+    Ok(())
+}
+
+/// Answer requests on `stream` until it closes. With a `token`, lines that don't carry it end
+/// the connection.
+fn serve_connection<S>(stream: &S, backend: &dyn TranscriptionBackend, token: Option<&str>) -> std::io::Result<()>
+where
+    for<'a> &'a S: Read + Write,
+{
+    let mut reader = BufReader::new(stream);
+    let mut writer = stream;
+    let max_samples = backend.sample_rate() as u64 * 60 * MAX_REQUEST_MINUTES;
+    loop {
+        let Some(line) = read_bounded_line(&mut reader)? else {
+            return Ok(());
+        };
+        let request: Value = serde_json::from_str(&line).map_err(std::io::Error::other)?;
+        if let Some(token) = token {
+            if !tokens_match(request["token"].as_str().unwrap_or(""), token) {
+                writer.write_all(format!("{}\n", json!({ "error": "wrong or missing token" })).as_bytes())?;
+                return Err(std::io::Error::new(std::io::ErrorKind::PermissionDenied, "wrong or missing token"));
+            }
+        }
+        let reply = if request["ping"].as_bool() == Some(true) {
+            json!({ "pong": true, "codecs": AudioCodec::ALL })
+        } else {
+            let count = request["samples"].as_u64().unwrap_or(0);
+            let codec = serde_json::from_value::<AudioCodec>(request["codec"].clone()).ok();
+            let body = match codec {
+                Some(_) => request["bytes"].as_u64(),
+                None => count.checked_mul(4),
+            };
+            let len = body.filter(|&body| count <= max_samples && body <= max_samples * 4);
+            let Some(len) = len.and_then(|len| usize::try_from(len).ok()) else {
+                // The body that follows can't be skipped safely, so the connection ends here
+                let message = format!("{} samples is over the {} minute limit", count, MAX_REQUEST_MINUTES);
+                writer.write_all(format!("{}\n", json!({ "error": message })).as_bytes())?;
+                return Err(std::io::Error::new(std::io::ErrorKind::InvalidData, message));
+            };
+            let mut bytes = vec![0u8; len];
+            reader.read_exact(&mut bytes)?;
+            let sample_rate = request["sample_rate"].as_u64().unwrap_or(0);
+            if sample_rate != backend.sample_rate() as u64 {
+                let expected = backend.sample_rate();
+                let message = format!("audio at {} Hz; {} takes {} Hz", sample_rate, backend.name(), expected);
+                writer.write_all(format!("{}\n", json!({ "error": message })).as_bytes())?;
+                continue;
+            }
+            let audio: Vec<f32> = match codec {
+                Some(codec) => match encode::decode(&bytes, backend.sample_rate(), codec, count as usize) {
+                    Ok(audio) => audio,
+                    Err(e) => {
+                        writer.write_all(format!("{}\n", json!({ "error": e.to_string() })).as_bytes())?;
+                        continue;
+                    }
+                },
+                None => bytes.chunks_exact(4).map(|b| f32::from_le_bytes([b[0], b[1], b[2], b[3]])).collect(),
+            };
+            let prompt = &request["prompt"];
+            let prompt = TranscriptionPrompt {
+                context: prompt["context"].as_str().map(str::to_string),
+                bias_phrases: prompt["bias_phrases"]
+                    .as_array()
+                    .map(|phrases| phrases.iter().filter_map(|p| p.as_str().map(str::to_string)).collect())
+                    .unwrap_or_default(),
+                language: prompt["language"].as_str().map(str::to_string),
+            };
+            match backend.transcribe(&audio, &prompt) {
+                Ok(transcript) => json!({ "text": transcript.text, "tokens": transcript.tokens }),
+                Err(e) => json!({ "error": e.to_string() }),
+            }
+        };
+        writer.write_all(format!("{}\n", reply).as_bytes())?;
+    }
+}
+
+/// Serve `backend` to `remote` backends connecting to `address` (`typeswift backend-worker
+/// --listen`), each connection on its own thread, until killed. Clients must send `token` when
+/// there is one; without one only loopback addresses may be listened on.
+pub fn serve_tcp(address: &str, backend: &dyn TranscriptionBackend, token: Option<&str>) -> std::io::Result<()> {
+    let listener = TcpListener::bind(address)?;
+    let local = listener.local_addr()?;
+    if token.is_none() && !local.ip().is_loopback() {
+        return Err(std::io::Error::new(
+            std::io::ErrorKind::PermissionDenied,
+            "set a token under [model.remote] to listen beyond 127.0.0.1",
+        ));
+    }
+    info!("Serving {} on {}", backend.description(), local);
+    std::thread::scope(|scope| -> std::io::Result<()> {
+        loop {
+            let stream = match listener.accept() {
+                Ok((stream, _)) => stream,
+                Err(e) => {
+                    // E.g. out of file descriptors or a client that gave up; the next one may do
+                    warn!("Couldn't accept a connection: {}", e);
+                    std::thread::sleep(Duration::from_millis(100));
+                    continue;
+                }
+            };
+            let peer = stream.peer_addr().map_or_else(|_| "?".to_string(), |a| a.to_string());
+            info!("{} connected", peer);
+            scope.spawn(move || {
+                let served = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| {
+                    serve_connection(&stream, backend, token)
+                }));
+                match served {
+                    Ok(Ok(())) => info!("{} disconnected", peer),
+                    Ok(Err(e)) => warn!("Connection from {} closed: {}", peer, e),
+                    Err(_) => error!("Connection from {} panicked; still serving others", peer),
+                }
+            });
+        }
+    })
+}
+
+/// Compare in time that doesn't depend on where the strings first differ
+fn tokens_match(given: &str, expected: &str) -> bool {
+    given.len() == expected.len() && given.bytes().zip(expected.bytes()).fold(0, |diff, (a, b)| diff | (a ^ b)) == 0
+}
+
+/// A worker on another machine, reached over TCP. Unlike `Supervisor` it can't restart the
+/// worker, only reconnect to it.
+pub struct RemoteWorker {
+    address: String,
+    token: Option<String>,
+    /// Preferred codec for uploads, used when the worker decodes it
+    codec: AudioCodec,
+    timeout: Duration,
+    /// The connection, and the codec agreed on it (raw samples if none)
+    stream: Mutex<Option<(TcpStream, Option<AudioCodec>)>>,
+}
+
+impl RemoteWorker {
+    /// Connect to the worker at `address` ("host:port") and check it answers
+    pub fn connect(address: &str, token: Option<String>, codec: AudioCodec, timeout: Duration) -> VoicyResult<Self> {
+        let worker = Self { address: address.to_string(), token, codec, timeout, stream: Mutex::new(None) };
+        let stream = worker.open().map_err(|e| VoicyError::ModelLoadFailed(format!("{}: {}", address, e)))?;
+        *worker.stream.lock() = Some(stream);
+        Ok(worker)
+    }
+
+    pub fn address(&self) -> &str {
+        &self.address
+    }
+
+    /// Transcribe on the worker, reconnecting once if the connection was lost
+    pub fn transcribe(&self, audio: &[f32], sample_rate: u32, prompt: &TranscriptionPrompt) -> VoicyResult<Transcript> {
+        let err = |msg: String| VoicyError::TranscriptionFailed(format!("{}: {}", self.address, msg));
+        let mut slot = self.stream.lock();
+        let mut last_error = String::new();
+        for _ in 0..2 {
+            let result = match slot.take() {
+                Some(connection) => Ok(connection),
+                None => self.open(),
+            }
+            .and_then(|(stream, codec)| {
+                stream.set_read_timeout(Some(self.timeout))?;
+                let reply = request(&stream, self.token.as_deref(), codec, audio, sample_rate, prompt)?;
+                *slot = Some((stream, codec));
+                Ok(reply)
+            });
+            match result {
+                Ok(Reply { error: Some(message), .. }) => return Err(err(message)),
+                Ok(reply) => {
+                    let duration_ms = audio.len() as u64 * 1000 / sample_rate.max(1) as u64;
+                    return Ok(Transcript { text: reply.text, tokens: reply.tokens, duration_ms });
+                }
+                Err(e) => {
+                    warn!("Remote worker {} failed ({}); reconnecting", self.address, e);
+                    last_error = e.to_string();
+                }
+            }
+        }
+        Err(err(last_error))
+    }
+
+    fn open(&self) -> std::io::Result<(TcpStream, Option<AudioCodec>)> {
+        let mut last_error = None;
+        for address in self.address.to_socket_addrs()? {
+            match TcpStream::connect_timeout(&address, CONNECT_TIMEOUT) {
+                Ok(stream) => {
+                    stream.set_nodelay(true)?;
+                    stream.set_read_timeout(Some(HEARTBEAT_TIMEOUT))?;
+                    let codecs = ping(&stream, self.token.as_deref())?;
+                    let codec = codecs.contains(&self.codec).then_some(self.codec);
+                    if codec.is_none() {
+                        info!("{} doesn't decode {:?}; sending raw samples", self.address, self.codec);
+                    }
+                    return Ok((stream, codec));
+                }
+                Err(e) => last_error = Some(e),
+            }
+        }
+        Err(last_error.unwrap_or_else(|| std::io::Error::other("no address")))
+    }
+}
+
+/// Exit when the app that started us goes away, even if it crashed without stopping us
+fn exit_with_parent() {
+    let parent = unsafe { libc::getppid() };
+    std::thread::spawn(move || loop {
+        std::thread::sleep(Duration::from_secs(2));
+        if unsafe { libc::getppid() } != parent {
+            std::process::exit(0);
+        }
+    });
+}
+
+#[cfg(all(test, feature = "mock"))]
+mod tests {
+    use super::*;
+    use crate::config::{Config, TranscriberBackend};
+    use crate::services::backend::create_backend;
+
+    fn mock_backend() -> Box<dyn TranscriptionBackend> {
+        let mut model = Config::default().model;
+        model.backend = TranscriberBackend::Mock;
+        model.preset = None;
+        model.mock.responses = vec!["hello there".to_string()];
+        create_backend(&model).unwrap()
+    }
+
+    fn send_line(stream: &UnixStream, line: &Value) {
+        let mut writer = stream;
+        writer.write_all(format!("{}\n", line).as_bytes()).unwrap();
+    }
+
+    #[test]
+    fn transcribes_a_request() {
+        let backend = mock_backend();
+        let (client, server) = UnixStream::pair().unwrap();
+        std::thread::scope(|scope| {
+            scope.spawn(|| serve_connection(&server, backend.as_ref(), None));
+            ping(&client, None).unwrap();
+            let reply = request(&client, None, None, &[0.0; 1600], 16000, &TranscriptionPrompt::default()).unwrap();
+            assert_eq!(reply.text, "hello there");
+            assert_eq!(reply.tokens.len(), 2);
+            client.shutdown(std::net::Shutdown::Both).unwrap();
+        });
+    }
+
+    #[cfg(feature = "swift")]
+    #[test]
+    fn transcribes_an_encoded_request() {
+        let backend = mock_backend();
+        let (client, server) = UnixStream::pair().unwrap();
+        std::thread::scope(|scope| {
+            scope.spawn(|| serve_connection(&server, backend.as_ref(), None));
+            assert_eq!(ping(&client, None).unwrap(), AudioCodec::ALL);
+            let audio: Vec<f32> = (0..16000).map(|i| (i as f32 / 20.0).sin() * 0.3).collect();
+            let prompt = TranscriptionPrompt::default();
+            let reply = request(&client, None, Some(AudioCodec::Flac), &audio, 16000, &prompt).unwrap();
+            assert_eq!((reply.text.as_str(), reply.error), ("hello there", None));
+            client.shutdown(std::net::Shutdown::Both).unwrap();
+        });
+    }
+
+    #[test]
+    fn refuses_more_samples_than_the_limit() {
+        let backend = mock_backend();
+        let (client, server) = UnixStream::pair().unwrap();
+        for samples in [u64::MAX, 16000 * 60 * MAX_REQUEST_MINUTES + 1] {
+            send_line(&client, &json!({ "samples": samples, "sample_rate": 16000 }));
+            let served = serve_connection(&server, backend.as_ref(), None);
+            assert_eq!(served.unwrap_err().kind(), std::io::ErrorKind::InvalidData);
+            let reply: Value = serde_json::from_str(&read_line(&client).unwrap()).unwrap();
+            assert!(reply["error"].as_str().unwrap().contains("limit"), "{}", reply);
+        }
+    }
+
+    #[test]
+    fn refuses_an_overlong_line() {
+        let backend = mock_backend();
+        let (client, server) = UnixStream::pair().unwrap();
+        std::thread::scope(|scope| {
+            scope.spawn(|| {
+                let mut writer = &client;
+                // Fails once the server hangs up
+                let _ = writer.write_all(&vec![b' '; MAX_LINE_BYTES as usize + 1]);
+            });
+            let served = serve_connection(&server, backend.as_ref(), None);
+            assert_eq!(served.unwrap_err().kind(), std::io::ErrorKind::InvalidData);
+            server.shutdown(std::net::Shutdown::Both).unwrap();
+        });
+    }
+
+    #[test]
+    fn refuses_audio_at_another_sample_rate() {
+        let backend = mock_backend();
+        let (client, server) = UnixStream::pair().unwrap();
+        std::thread::scope(|scope| {
+            scope.spawn(|| serve_connection(&server, backend.as_ref(), None));
+            let reply = request(&client, None, None, &[0.0; 4410], 44100, &TranscriptionPrompt::default()).unwrap();
+            assert!(reply.error.unwrap().contains("44100 Hz"));
+            // The connection stays usable
+            ping(&client, None).unwrap();
+            client.shutdown(std::net::Shutdown::Both).unwrap();
+        });
+    }
+
+    #[test]
+    fn requires_the_token_when_there_is_one() {
+        let backend = mock_backend();
+        let (client, server) = UnixStream::pair().unwrap();
+        std::thread::scope(|scope| {
+            scope.spawn(|| serve_connection(&server, backend.as_ref(), Some("secret")));
+            ping(&client, Some("secret")).unwrap();
+            let reply = request(&client, Some("secret"), None, &[0.0; 1600], 16000, &TranscriptionPrompt::default());
+            assert_eq!(reply.unwrap().text, "hello there");
+            assert!(ping(&client, Some("guess")).is_err());
+        });
+    }
+
+    #[test]
+    fn refuses_to_listen_beyond_loopback_without_a_token() {
+        let backend = mock_backend();
+        let refused = serve_tcp("0.0.0.0:0", backend.as_ref(), None).unwrap_err();
+        assert_eq!(refused.kind(), std::io::ErrorKind::PermissionDenied);
+    }
+}
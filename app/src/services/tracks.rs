@@ -0,0 +1,96 @@
+//! Which source was loud when: block levels of the microphone and of the system audio mixed
+//! into a meeting recording, kept apart so the transcript's words can be attributed to "Me" or
+//! "Others" without diarization.
+
+use crate::services::audio::Token;
+
+/// Level resolution
+const BLOCK_MS: usize = 50;
+/// A source must carry this many times the other's energy to be credited with a word
+const DOMINANCE: f32 = 2.0;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Speaker {
+    /// The microphone: whoever is at this Mac
+    Me,
+    /// System audio: the other side of the call
+    Others,
+}
+
+impl Speaker {
+    pub fn label(self) -> &'static str {
+        match self {
+            Speaker::Me => "Me",
+            Speaker::Others => "Others",
+        }
+    }
+}
+
+/// Per-block energy of each source, from the start of the recording
+#[derive(Debug, Clone)]
+pub struct SourceTracks {
+    block_len: usize,
+    /// (microphone, system audio) sums of squares
+    blocks: Vec<(f32, f32)>,
+}
+
+impl SourceTracks {
+    pub fn new(sample_rate: u32) -> Self {
+        Self { block_len: (sample_rate as usize * BLOCK_MS / 1000).max(1), blocks: Vec::new() }
+    }
+
+    /// Count the microphone (echo already removed) and system samples mixed at `pos`
+    pub fn add(&mut self, pos: usize, mic: f32, system: f32) {
+        let block = pos / self.block_len;
+        if block >= self.blocks.len() {
+            self.blocks.resize(block + 1, (0.0, 0.0));
+        }
+        self.blocks[block].0 += mic * mic;
+        self.blocks[block].1 += system * system;
+    }
+
+    /// Drop the levels before sample `start`, once silence trimming has cut it from the recording
+    pub fn trim_start(&mut self, start: usize) {
+        let blocks = (start / self.block_len).min(self.blocks.len());
+        self.blocks.drain(..blocks);
+    }
+
+    /// Whose audio dominates `start_ms..end_ms`; `None` when neither clearly does
+    fn speaker_between(&self, start_ms: u64, end_ms: u64) -> Option<Speaker> {
+        let first = start_ms as usize / BLOCK_MS;
+        let last = (end_ms as usize).div_ceil(BLOCK_MS).max(first + 1);
+        let Some(blocks) = self.blocks.get(first..last.min(self.blocks.len())) else {
+            // Past the system audio: only the microphone was recording
+            return Some(Speaker::Me);
+        };
+        let (mic, system) = blocks.iter().fold((0.0, 0.0), |(m, s), &(bm, bs)| (m + bm, s + bs));
+        if mic > system * DOMINANCE {
+            Some(Speaker::Me)
+        } else if system > mic * DOMINANCE {
+            Some(Speaker::Others)
+        } else {
+            None
+        }
+    }
+
+    /// The transcript's words grouped into consecutive runs by speaker. Words that are unclear
+    /// (both sides talking, or silence) stay with the speaker before them.
+    pub fn attribute(&self, tokens: &[Token]) -> Vec<(Speaker, String)> {
+        let mut segments: Vec<(Speaker, String)> = Vec::new();
+        for token in tokens {
+            let previous = segments.last().map(|(speaker, _)| *speaker);
+            let speaker = self
+                .speaker_between(token.start_ms, token.end_ms)
+                .or(previous)
+                .unwrap_or(Speaker::Me);
+            match segments.last_mut() {
+                Some((last, text)) if *last == speaker => {
+                    text.push(' ');
+                    text.push_str(&token.text);
+                }
+                _ => segments.push((speaker, token.text.clone())),
+            }
+        }
+        segments
+    }
+}
@@ -0,0 +1,243 @@
+use crate::config::{VadConfig, VadEngine};
+use tracing::{info, warn};
+
+/// Frame-level voice activity detector
+pub trait Vad: Send {
+    /// Samples per frame this detector expects
+    fn frame_len(&self) -> usize;
+    /// Speech probability in [0, 1] for one frame of `frame_len()` samples
+    fn speech_probability(&mut self, frame: &[f32]) -> f32;
+    /// Probability at or above which a frame counts as speech
+    fn threshold(&self) -> f32 {
+        0.5
+    }
+    /// Clear any recurrent state between recordings
+    fn reset(&mut self) {}
+    /// Learn the room's background level from `ambient`, audio without speech in it. Returns
+    /// the threshold now in use, for detectors that adapt to noise.
+    fn calibrate(&mut self, _ambient: &[f32]) -> Option<f32> {
+        None
+    }
+
+    fn is_speech(&mut self, frame: &[f32]) -> bool {
+        let threshold = self.threshold();
+        self.speech_probability(frame) >= threshold
+    }
+}
+
+/// How quickly the tracked noise floor follows quieter frames, per frame
+const NOISE_FALL: f32 = 0.1;
+/// How quickly it follows louder non-speech frames; slow, so speech doesn't raise it
+const NOISE_RISE: f32 = 0.01;
+
+/// Legacy energy detector: a frame is speech when its RMS reaches `threshold`. Adaptive, the
+/// threshold also stays `noise_margin` times above the background level, which a calibration
+/// measures and non-speech frames keep up to date.
+pub struct RmsVad {
+    threshold: f32,
+    frame_len: usize,
+    noise_margin: Option<f32>,
+    noise_floor: f32,
+}
+
+impl RmsVad {
+    pub fn new(threshold: f32, sample_rate: u32) -> Self {
+        // 30 ms frames
+        Self {
+            threshold: threshold.max(1e-6),
+            frame_len: (sample_rate as usize * 3 / 100).max(1),
+            noise_margin: None,
+            noise_floor: 0.0,
+        }
+    }
+
+    /// Raise the threshold to `margin` times the background level when that's higher
+    pub fn adaptive(mut self, margin: f32) -> Self {
+        self.noise_margin = Some(margin.max(1.0));
+        self
+    }
+
+    /// The RMS level currently counted as speech
+    fn effective_threshold(&self) -> f32 {
+        match self.noise_margin {
+            Some(margin) => self.threshold.max(self.noise_floor * margin),
+            None => self.threshold,
+        }
+    }
+}
+
+fn frame_rms(frame: &[f32]) -> f32 {
+    (frame.iter().map(|s| s * s).sum::<f32>() / frame.len() as f32).sqrt()
+}
+
+impl Vad for RmsVad {
+    fn frame_len(&self) -> usize {
+        self.frame_len
+    }
+
+    fn speech_probability(&mut self, frame: &[f32]) -> f32 {
+        if frame.is_empty() {
+            return 0.0;
+        }
+        let rms = frame_rms(frame);
+        let threshold = self.effective_threshold();
+        if self.noise_margin.is_some() {
+            if rms < self.noise_floor {
+                self.noise_floor += (rms - self.noise_floor) * NOISE_FALL;
+            } else if rms < threshold {
+                self.noise_floor += (rms - self.noise_floor) * NOISE_RISE;
+            }
+        }
+        // Maps the threshold to 0.5 so the shared is_speech cutoff applies
+        (rms / threshold * 0.5).min(1.0)
+    }
+
+    fn calibrate(&mut self, ambient: &[f32]) -> Option<f32> {
+        if self.noise_margin.is_none() {
+            return None;
+        }
+        let mut frames: Vec<f32> = ambient.chunks_exact(self.frame_len).map(frame_rms).collect();
+        if frames.is_empty() {
+            return None;
+        }
+        // The median, so a cough or a door during the calibration doesn't count as background
+        frames.sort_by(f32::total_cmp);
+        self.noise_floor = frames[frames.len() / 2];
+        Some(self.effective_threshold())
+    }
+}
+
+/// Build the configured detector, falling back to RMS when Silero is unavailable
+pub fn create_vad(config: &VadConfig, sample_rate: u32) -> Box<dyn Vad> {
+    let rms = || -> Box<dyn Vad> {
+        let vad = RmsVad::new(config.threshold, sample_rate);
+        Box::new(if config.adaptive { vad.adaptive(config.noise_margin) } else { vad })
+    };
+    match config.engine {
+        VadEngine::Rms => rms(),
+        VadEngine::Silero => match silero::create(config, sample_rate) {
+            Ok(vad) => {
+                info!("Silero VAD loaded");
+                vad
+            }
+            Err(e) => {
+                warn!("Silero VAD unavailable ({}); using RMS detector", e);
+                rms()
+            }
+        },
+    }
+}
+
+/// Sample range from the first to the last speech frame, padded by `pad_frames` on each side.
+/// Returns `None` when no frame is speech.
+pub fn speech_bounds(vad: &mut dyn Vad, samples: &[f32], pad_frames: usize) -> Option<std::ops::Range<usize>> {
+    vad.reset();
+    let frame_len = vad.frame_len();
+    let mut first = None;
+    let mut last = 0;
+    for (i, frame) in samples.chunks(frame_len).enumerate() {
+        if frame.len() == frame_len && vad.is_speech(frame) {
+            first.get_or_insert(i);
+            last = i;
+        }
+    }
+    let first = first?;
+    let start = first.saturating_sub(pad_frames) * frame_len;
+    let end = ((last + 1 + pad_frames) * frame_len).min(samples.len());
+    Some(start..end)
+}
+
+#[cfg(feature = "silero-vad")]
+mod silero {
+    use super::Vad;
+    use crate::config::VadConfig;
+    use crate::error::{VoicyError, VoicyResult};
+    use ort::session::Session;
+    use ort::value::Tensor;
+
+    /// Silero VAD v5 ONNX model (512-sample frames at 16 kHz)
+    struct SileroVad {
+        session: Session,
+        state: Vec<f32>,
+        sample_rate: i64,
+        threshold: f32,
+    }
+
+    const STATE_LEN: usize = 2 * 128;
+
+    pub fn create(config: &VadConfig, sample_rate: u32) -> VoicyResult<Box<dyn Vad>> {
+        if sample_rate != 16000 && sample_rate != 8000 {
+            return Err(VoicyError::ModelLoadFailed(format!("Silero needs 8 or 16 kHz, got {}", sample_rate)));
+        }
+        let path = config
+            .silero_model
+            .as_ref()
+            .ok_or_else(|| VoicyError::ModelLoadFailed("audio.vad.silero_model is not set".to_string()))?;
+        let session = Session::builder()
+            .and_then(|b| b.commit_from_file(path))
+            .map_err(|e| VoicyError::ModelLoadFailed(format!("Silero model {}: {}", path.display(), e)))?;
+        Ok(Box::new(SileroVad {
+            session,
+            state: vec![0.0; STATE_LEN],
+            sample_rate: sample_rate as i64,
+            threshold: config.silero_threshold,
+        }))
+    }
+
+    impl SileroVad {
+        fn run(&mut self, frame: &[f32]) -> ort::Result<f32> {
+            let input = Tensor::from_array(([1usize, frame.len()], frame.to_vec()))?;
+            let state = Tensor::from_array(([2usize, 1, 128], self.state.clone()))?;
+            let sr = Tensor::from_array(([1usize], vec![self.sample_rate]))?;
+            let outputs = self.session.run(ort::inputs!["input" => input, "state" => state, "sr" => sr]?)?;
+            let (_, prob) = outputs["output"].try_extract_raw_tensor::<f32>()?;
+            let (_, next_state) = outputs["stateN"].try_extract_raw_tensor::<f32>()?;
+            self.state.copy_from_slice(&next_state[..STATE_LEN]);
+            Ok(prob.first().copied().unwrap_or(0.0))
+        }
+    }
+
+    impl Vad for SileroVad {
+        fn frame_len(&self) -> usize {
+            if self.sample_rate == 8000 { 256 } else { 512 }
+        }
+
+        fn speech_probability(&mut self, frame: &[f32]) -> f32 {
+            self.run(frame).unwrap_or_else(|e| {
+                tracing::warn!("Silero inference failed: {}", e);
+                0.0
+            })
+        }
+
+        fn threshold(&self) -> f32 {
+            self.threshold
+        }
+
+        fn reset(&mut self) {
+            self.state.iter_mut().for_each(|s| *s = 0.0);
+        }
+    }
+}
+
+#[cfg(not(feature = "silero-vad"))]
+mod silero {
+    use super::Vad;
+    use crate::config::VadConfig;
+    use crate::error::{VoicyError, VoicyResult};
+
+    pub fn create(_config: &VadConfig, _sample_rate: u32) -> VoicyResult<Box<dyn Vad>> {
+        Err(VoicyError::ModelLoadFailed("built without the `silero-vad` feature".to_string()))
+    }
+}
+
+/// Trim leading/trailing non-speech; returns the input unchanged when nothing is detected
+pub fn trim_silence(vad: &mut dyn Vad, samples: &[f32]) -> Vec<f32> {
+    samples[speech_range(vad, samples)].to_vec()
+}
+
+/// The part of `samples` `trim_silence` keeps, for trimming a buffer in place
+pub fn speech_range(vad: &mut dyn Vad, samples: &[f32]) -> std::ops::Range<usize> {
+    // ~150 ms of padding at 16 kHz keeps word onsets intact
+    let pad_frames = (2400 / vad.frame_len()).max(1);
+    speech_bounds(vad, samples, pad_frames).unwrap_or(0..samples.len())
+}
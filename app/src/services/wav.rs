@@ -0,0 +1,294 @@
+use crate::error::{VoicyError, VoicyResult};
+use std::path::Path;
+
+/// Decoded WAV audio, downmixed to mono f32 in [-1.0, 1.0]
+#[derive(Debug, Clone)]
+pub struct WavAudio {
+    pub sample_rate: u32,
+    pub samples: Vec<f32>,
+}
+
+const WAVE_FORMAT_PCM: u16 = 1;
+const WAVE_FORMAT_IEEE_FLOAT: u16 = 3;
+const WAVE_FORMAT_EXTENSIBLE: u16 = 0xFFFE;
+
+pub fn read_wav_file(path: &Path) -> VoicyResult<WavAudio> {
+    let bytes = std::fs::read(path).map_err(|e| {
+        VoicyError::AudioInitFailed(format!("Failed to read {}: {}", path.display(), e))
+    })?;
+    decode_wav(&bytes)
+}
+
+/// Parse a RIFF/WAVE byte buffer (PCM 8/16/24/32-bit or float32, any channel count)
+pub fn decode_wav(bytes: &[u8]) -> VoicyResult<WavAudio> {
+    let err = |msg: &str| VoicyError::AudioInitFailed(format!("Invalid WAV: {}", msg));
+
+    if bytes.len() < 12 || &bytes[0..4] != b"RIFF" || &bytes[8..12] != b"WAVE" {
+        return Err(err("missing RIFF/WAVE header"));
+    }
+
+    let mut format: Option<PcmFormat> = None;
+    let mut data: Option<&[u8]> = None;
+    let mut pos = 12;
+    while pos + 8 <= bytes.len() {
+        let id = &bytes[pos..pos + 4];
+        let size = u32::from_le_bytes([bytes[pos + 4], bytes[pos + 5], bytes[pos + 6], bytes[pos + 7]]) as usize;
+        let body_start = pos + 8;
+        let body_end = body_start.saturating_add(size).min(bytes.len());
+        let body = &bytes[body_start..body_end];
+        match id {
+            b"fmt " => format = Some(parse_fmt(body)?),
+            b"data" => data = Some(body),
+            _ => {}
+        }
+        // Chunks are word-aligned
+        pos = body_start.saturating_add(size).saturating_add(size & 1);
+    }
+
+    let format = format.ok_or_else(|| err("missing fmt chunk"))?;
+    let data = data.ok_or_else(|| err("missing data chunk"))?;
+    let samples = format.decode(data)?;
+    Ok(WavAudio { sample_rate: format.sample_rate, samples })
+}
+
+/// Sample layout of PCM audio, as described by a WAV `fmt ` chunk
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct PcmFormat {
+    pub tag: u16,
+    pub channels: u16,
+    pub sample_rate: u32,
+    pub bits: u16,
+}
+
+impl PcmFormat {
+    /// Mono 16-bit signed little-endian
+    pub fn s16le(sample_rate: u32) -> Self {
+        Self { tag: WAVE_FORMAT_PCM, channels: 1, sample_rate, bits: 16 }
+    }
+
+    /// Mono 32-bit float little-endian
+    pub fn f32le(sample_rate: u32) -> Self {
+        Self { tag: WAVE_FORMAT_IEEE_FLOAT, channels: 1, sample_rate, bits: 32 }
+    }
+
+    /// Bytes per interleaved frame (one sample per channel)
+    pub fn frame_bytes(&self) -> usize {
+        (self.bits as usize / 8 * self.channels as usize).max(1)
+    }
+
+    /// Convert interleaved sample bytes to mono f32; a trailing partial frame is ignored
+    pub fn decode(&self, data: &[u8]) -> VoicyResult<Vec<f32>> {
+        let err = |msg: &str| VoicyError::AudioInitFailed(format!("Invalid WAV: {}", msg));
+        if self.channels == 0 || self.sample_rate == 0 {
+            return Err(err("zero channels or sample rate"));
+        }
+        let data = &data[..data.len() - data.len() % self.frame_bytes()];
+
+        let interleaved: Vec<f32> = match (self.tag, self.bits) {
+            (WAVE_FORMAT_PCM, 8) => data.iter().map(|&b| (b as f32 - 128.0) / 128.0).collect(),
+            (WAVE_FORMAT_PCM, 16) => data
+                .chunks_exact(2)
+                .map(|c| i16::from_le_bytes([c[0], c[1]]) as f32 / 32768.0)
+                .collect(),
+            (WAVE_FORMAT_PCM, 24) => data
+                .chunks_exact(3)
+                .map(|c| (i32::from_le_bytes([0, c[0], c[1], c[2]]) >> 8) as f32 / 8_388_608.0)
+                .collect(),
+            (WAVE_FORMAT_PCM, 32) => data
+                .chunks_exact(4)
+                .map(|c| i32::from_le_bytes([c[0], c[1], c[2], c[3]]) as f32 / 2_147_483_648.0)
+                .collect(),
+            (WAVE_FORMAT_IEEE_FLOAT, 32) => data
+                .chunks_exact(4)
+                .map(|c| f32::from_le_bytes([c[0], c[1], c[2], c[3]]))
+                .collect(),
+            (tag, bits) => return Err(err(&format!("unsupported format tag {} with {} bits", tag, bits))),
+        };
+
+        let channels = self.channels as usize;
+        Ok(if channels > 1 {
+            interleaved
+                .chunks_exact(channels)
+                .map(|frame| frame.iter().copied().sum::<f32>() / channels as f32)
+                .collect()
+        } else {
+            interleaved
+        })
+    }
+}
+
+fn parse_fmt(body: &[u8]) -> VoicyResult<PcmFormat> {
+    if body.len() < 16 {
+        return Err(VoicyError::AudioInitFailed("Invalid WAV: short fmt chunk".to_string()));
+    }
+    let mut tag = u16::from_le_bytes([body[0], body[1]]);
+    if tag == WAVE_FORMAT_EXTENSIBLE && body.len() >= 26 {
+        // First two bytes of the SubFormat GUID carry the real format tag
+        tag = u16::from_le_bytes([body[24], body[25]]);
+    }
+    Ok(PcmFormat {
+        tag,
+        channels: u16::from_le_bytes([body[2], body[3]]),
+        sample_rate: u32::from_le_bytes([body[4], body[5], body[6], body[7]]),
+        bits: u16::from_le_bytes([body[14], body[15]]),
+    })
+}
+
+/// For WAV arriving as a stream: the format and the offset where sample data starts,
+/// or `None` until enough of the header has arrived. The data chunk's size is ignored,
+/// since streaming writers often can't fill it in.
+pub fn parse_stream_header(bytes: &[u8]) -> VoicyResult<Option<(PcmFormat, usize)>> {
+    if bytes.len() < 12 {
+        return Ok(None);
+    }
+    if &bytes[0..4] != b"RIFF" || &bytes[8..12] != b"WAVE" {
+        return Err(VoicyError::AudioInitFailed("Invalid WAV: missing RIFF/WAVE header".to_string()));
+    }
+    let mut format = None;
+    let mut pos = 12;
+    while pos + 8 <= bytes.len() {
+        let id = &bytes[pos..pos + 4];
+        let size = u32::from_le_bytes([bytes[pos + 4], bytes[pos + 5], bytes[pos + 6], bytes[pos + 7]]) as usize;
+        let body_start = pos + 8;
+        if id == b"data" {
+            return match format {
+                Some(format) => Ok(Some((format, body_start))),
+                None => Err(VoicyError::AudioInitFailed("Invalid WAV: data before fmt chunk".to_string())),
+            };
+        }
+        let body_end = body_start.saturating_add(size);
+        if body_end > bytes.len() {
+            return Ok(None);
+        }
+        if id == b"fmt " {
+            format = Some(parse_fmt(&bytes[body_start..body_end])?);
+        }
+        pos = body_end + (size & 1);
+    }
+    Ok(None)
+}
+
+/// Deterministic linear resampler for offline use (fixtures, files).
+/// The capture path uses rubato; this avoids its latency for whole buffers.
+pub fn resample_linear(samples: &[f32], from_rate: u32, to_rate: u32) -> Vec<f32> {
+    if from_rate == to_rate || samples.is_empty() {
+        return samples.to_vec();
+    }
+    let ratio = from_rate as f64 / to_rate as f64;
+    let out_len = ((samples.len() as f64) / ratio).floor() as usize;
+    let last = samples.len() - 1;
+    (0..out_len)
+        .map(|i| {
+            let src = i as f64 * ratio;
+            let idx = src.floor() as usize;
+            let frac = (src - idx as f64) as f32;
+            let a = samples[idx.min(last)];
+            let b = samples[(idx + 1).min(last)];
+            a + (b - a) * frac
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A RIFF/WAVE file with a `fmt ` chunk for `tag`/`channels`/`bits` and the given extra chunks
+    fn wav(tag: u16, channels: u16, bits: u16, chunks: &[(&[u8; 4], &[u8])]) -> Vec<u8> {
+        let rate = 16000u32;
+        let block_align = channels * bits / 8;
+        let mut fmt = Vec::new();
+        fmt.extend_from_slice(&tag.to_le_bytes());
+        fmt.extend_from_slice(&channels.to_le_bytes());
+        fmt.extend_from_slice(&rate.to_le_bytes());
+        fmt.extend_from_slice(&(rate * block_align as u32).to_le_bytes());
+        fmt.extend_from_slice(&block_align.to_le_bytes());
+        fmt.extend_from_slice(&bits.to_le_bytes());
+
+        let mut body = b"WAVE".to_vec();
+        for (id, data) in std::iter::once((b"fmt ", fmt.as_slice())).chain(chunks.iter().copied()) {
+            body.extend_from_slice(*id);
+            body.extend_from_slice(&(data.len() as u32).to_le_bytes());
+            body.extend_from_slice(data);
+            if data.len() % 2 == 1 {
+                body.push(0);
+            }
+        }
+        let mut bytes = b"RIFF".to_vec();
+        bytes.extend_from_slice(&(body.len() as u32).to_le_bytes());
+        bytes.extend_from_slice(&body);
+        bytes
+    }
+
+    fn s16(samples: &[i16]) -> Vec<u8> {
+        samples.iter().flat_map(|s| s.to_le_bytes()).collect()
+    }
+
+    #[test]
+    fn mono_16_bit() {
+        let audio = decode_wav(&wav(WAVE_FORMAT_PCM, 1, 16, &[(b"data", &s16(&[0, 16384, -32768]))])).unwrap();
+        assert_eq!(audio.sample_rate, 16000);
+        assert_eq!(audio.samples, vec![0.0, 0.5, -1.0]);
+    }
+
+    #[test]
+    fn stereo_is_downmixed() {
+        let data = s16(&[16384, 0, -16384, -16384]);
+        let audio = decode_wav(&wav(WAVE_FORMAT_PCM, 2, 16, &[(b"data", &data)])).unwrap();
+        assert_eq!(audio.samples, vec![0.25, -0.5]);
+    }
+
+    #[test]
+    fn eight_bit_is_unsigned() {
+        let audio = decode_wav(&wav(WAVE_FORMAT_PCM, 1, 8, &[(b"data", &[128, 192, 0])])).unwrap();
+        assert_eq!(audio.samples, vec![0.0, 0.5, -1.0]);
+    }
+
+    #[test]
+    fn twenty_four_bit_keeps_sign() {
+        let data = [0x00, 0x00, 0x40, 0x00, 0x00, 0xC0];
+        let audio = decode_wav(&wav(WAVE_FORMAT_PCM, 1, 24, &[(b"data", &data)])).unwrap();
+        assert_eq!(audio.samples, vec![0.5, -0.5]);
+    }
+
+    #[test]
+    fn float_32() {
+        let data: Vec<u8> = [0.25f32, -0.75].iter().flat_map(|s| s.to_le_bytes()).collect();
+        let audio = decode_wav(&wav(WAVE_FORMAT_IEEE_FLOAT, 1, 32, &[(b"data", &data)])).unwrap();
+        assert_eq!(audio.samples, vec![0.25, -0.75]);
+    }
+
+    #[test]
+    fn truncated_data_chunk_keeps_whole_frames() {
+        let mut bytes = wav(WAVE_FORMAT_PCM, 2, 16, &[(b"data", &s16(&[16384, 16384, 0, 0]))]);
+        // Cut the file mid-frame: the data chunk's size now claims more than is there
+        bytes.truncate(bytes.len() - 3);
+        let audio = decode_wav(&bytes).unwrap();
+        assert_eq!(audio.samples, vec![0.5]);
+    }
+
+    #[test]
+    fn unknown_chunks_are_skipped() {
+        let list = b"INFOISFT\x05\x00\x00\x00test\x00";
+        let bytes = wav(WAVE_FORMAT_PCM, 1, 16, &[(b"LIST", list), (b"odd ", &[1, 2, 3]), (b"data", &s16(&[16384]))]);
+        assert_eq!(decode_wav(&bytes).unwrap().samples, vec![0.5]);
+    }
+
+    #[test]
+    fn unsupported_or_incomplete_files_are_errors() {
+        assert!(decode_wav(b"RIFF\x04\x00\x00\x00WAVX").is_err());
+        assert!(decode_wav(&wav(WAVE_FORMAT_PCM, 1, 16, &[])).is_err());
+        assert!(decode_wav(&wav(2, 1, 4, &[(b"data", &[0, 0])])).is_err());
+        assert!(decode_wav(&wav(WAVE_FORMAT_PCM, 0, 16, &[(b"data", &[0, 0])])).is_err());
+    }
+
+    #[test]
+    fn fixture_decodes() {
+        let path = Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures/tone-16k-mono.wav");
+        let audio = read_wav_file(&path).unwrap();
+        assert_eq!(audio.sample_rate, 16000);
+        assert_eq!(audio.samples.len(), 8000);
+        let peak = audio.samples.iter().fold(0.0f32, |m, s| m.max(s.abs()));
+        assert!((peak - 0.25).abs() < 0.01, "peak {}", peak);
+    }
+}
@@ -0,0 +1,159 @@
+//! Destinations for finished dictations other than the cursor, chosen per profile or from the
+//! menu bar
+
+use crate::config::{FileSink, ObsidianMethod, ObsidianSink, SinkConfig};
+use crate::error::{VoicyError, VoicyResult};
+use crate::history::{format_timestamp, now_secs};
+use std::io::Write;
+use tracing::info;
+
+/// Deliver `text`, dictated into `app`, to the sink
+pub fn deliver(sink: &SinkConfig, text: &str, app: Option<&str>) -> VoicyResult<()> {
+    match sink {
+        SinkConfig::Obsidian(obsidian) => append_obsidian(obsidian, text, app),
+        SinkConfig::File(file) => append_file(file, text, app),
+    }
+}
+
+/// Short label for notifications and logs
+pub fn describe(sink: &SinkConfig) -> String {
+    match sink {
+        SinkConfig::Obsidian(obsidian) => format!("Obsidian ({})", obsidian.note.replace("{date}", &today())),
+        SinkConfig::File(file) => file.path.display().to_string(),
+    }
+}
+
+fn today() -> String {
+    let stamp = format_timestamp(now_secs());
+    stamp.split(' ').next().unwrap_or_default().to_string()
+}
+
+/// A profile's output template filled in for `text`, dictated into `app` (see `Profile::template`).
+/// A template without `{text}` goes in front of it.
+pub fn apply_template(template: &str, text: &str, app: Option<&str>) -> String {
+    let stamp = format_timestamp(now_secs());
+    let (date, time) = stamp.split_once(' ').unwrap_or((stamp.as_str(), ""));
+    if template.contains("{text}") {
+        fill(template, text, date, time, app)
+    } else {
+        format!("{}{}", fill(template, "", date, time, app), text)
+    }
+}
+
+fn fill(template: &str, text: &str, date: &str, time: &str, app: Option<&str>) -> String {
+    template
+        .replace("{timestamp}", &format!("{} {}", date, time))
+        .replace("{date}", date)
+        .replace("{time}", time)
+        .replace("{app}", app.unwrap_or(""))
+        .replace("{text}", text)
+}
+
+fn append_obsidian(sink: &ObsidianSink, text: &str, app: Option<&str>) -> VoicyResult<()> {
+    let err = |msg: String| VoicyError::OutputFailed(format!("Obsidian: {}", msg));
+    // Don't create a stray vault from a mistyped path
+    if !sink.vault.is_dir() {
+        return Err(err(format!("vault folder {} not found", sink.vault.display())));
+    }
+    let stamp = format_timestamp(now_secs());
+    let (date, time) = stamp.split_once(' ').unwrap_or((stamp.as_str(), ""));
+    let note = fill(&sink.note, "", date, time, app);
+    let path = sink.vault.join(&note);
+
+    // The header goes in once per day; the note is read to see whether it's there yet
+    let existing = std::fs::read_to_string(&path).unwrap_or_default();
+    let header = fill(&sink.header, "", date, time, app);
+    let mut content = String::new();
+    if !existing.is_empty() && !existing.ends_with('\n') {
+        content.push('\n');
+    }
+    if !header.is_empty() && !existing.lines().any(|line| line == header) {
+        if !existing.trim().is_empty() {
+            content.push('\n');
+        }
+        content.push_str(&header);
+        content.push_str("\n\n");
+    }
+    content.push_str(&fill(&sink.entry, text, date, time, app));
+    content.push('\n');
+
+    match sink.method {
+        ObsidianMethod::File => {
+            if let Some(parent) = path.parent() {
+                std::fs::create_dir_all(parent).map_err(|e| err(format!("{}: {}", parent.display(), e)))?;
+            }
+            std::fs::OpenOptions::new()
+                .create(true)
+                .append(true)
+                .open(&path)
+                .and_then(|mut file| file.write_all(content.as_bytes()))
+                .map_err(|e| err(format!("{}: {}", path.display(), e)))?;
+            info!("Appended {} chars to {}", text.len(), path.display());
+        }
+        ObsidianMethod::Url => {
+            let vault = sink
+                .vault
+                .file_name()
+                .and_then(|name| name.to_str())
+                .ok_or_else(|| err(format!("no vault name in {}", sink.vault.display())))?;
+            let url = format!(
+                "obsidian://new?vault={}&file={}&content={}&append=true",
+                percent_encode(vault),
+                percent_encode(note.strip_suffix(".md").unwrap_or(&note)),
+                percent_encode(&content)
+            );
+            open_in_background(&url).map_err(err)?;
+            info!("Sent {} chars to Obsidian note {}", text.len(), note);
+        }
+    }
+    Ok(())
+}
+
+fn append_file(sink: &FileSink, text: &str, app: Option<&str>) -> VoicyResult<()> {
+    let err = |msg: String| VoicyError::OutputFailed(format!("{}: {}", sink.path.display(), msg));
+    let stamp = format_timestamp(now_secs());
+    let (date, time) = stamp.split_once(' ').unwrap_or((stamp.as_str(), ""));
+    // Start on a line of its own if the file doesn't end with one
+    let ends_mid_line = std::fs::read(&sink.path).is_ok_and(|bytes| bytes.last().is_some_and(|&b| b != b'\n'));
+    let mut content = if ends_mid_line { "\n".to_string() } else { String::new() };
+    content.push_str(&fill(&sink.entry, text, date, time, app));
+    content.push('\n');
+    if let Some(parent) = sink.path.parent() {
+        std::fs::create_dir_all(parent).map_err(|e| err(e.to_string()))?;
+    }
+    std::fs::OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(&sink.path)
+        .and_then(|mut file| file.write_all(content.as_bytes()))
+        .map_err(|e| err(e.to_string()))?;
+    info!("Appended {} chars to {}", text.len(), sink.path.display());
+    Ok(())
+}
+
+fn percent_encode(text: &str) -> String {
+    let mut out = String::with_capacity(text.len());
+    for byte in text.bytes() {
+        if byte.is_ascii_alphanumeric() || matches!(byte, b'-' | b'_' | b'.' | b'~') {
+            out.push(byte as char);
+        } else {
+            out.push_str(&format!("%{:02X}", byte));
+        }
+    }
+    out
+}
+
+/// Open a URL without bringing its app to the front, so focus stays where the user is
+fn open_in_background(url: &str) -> Result<(), String> {
+    let status = std::process::Command::new("/usr/bin/open")
+        .arg("-g")
+        .arg(url)
+        .status()
+        .map_err(|e| e.to_string())?;
+    if status.success() {
+        Ok(())
+    } else {
+        Err(format!("open exited with {}", status))
+    }
+}
+
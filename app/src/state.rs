@@ -1,6 +1,7 @@
-use parking_lot::RwLock;
+use parking_lot::{Condvar, Mutex, RwLock};
+use std::fmt;
 use std::sync::Arc;
-use tracing::info;
+use tracing::{info, warn};
 
 /// Single source of truth for application state
 #[derive(Debug, Clone, Copy, PartialEq)]
@@ -10,12 +11,149 @@ pub enum RecordingState {
     Processing,
 }
 
+/// Events that drive the recording state machine
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RecordingEvent {
+    /// Push-to-talk pressed: Idle -> Recording
+    Start,
+    /// Push-to-talk released: Recording -> Processing
+    Stop,
+    /// Transcription/typing finished: Processing -> Idle
+    Finish,
+    /// Abort the current recording or job: Recording | Processing -> Idle
+    Cancel,
+}
+
+impl RecordingState {
+    /// Transition table. Returns None when `event` is not valid in this state.
+    pub fn next(self, event: RecordingEvent) -> Option<RecordingState> {
+        use RecordingEvent::*;
+        use RecordingState::*;
+        match (self, event) {
+            (Idle, Start) => Some(Recording),
+            (Recording, Stop) => Some(Processing),
+            (Processing, Finish) => Some(Idle),
+            (Recording, Cancel) | (Processing, Cancel) => Some(Idle),
+            _ => None,
+        }
+    }
+}
+
+/// Transcript progress for API subscribers (editor plugins)
+#[derive(Debug, Clone, PartialEq)]
+pub enum TranscriptUpdate {
+    State(RecordingState),
+    /// Post-processed text of the recording so far; replaces the previous partial
+    Partial(String),
+    /// Emitted text, and the model's transcript it was formatted from
+    Final { text: String, raw_text: String },
+}
+
+/// Whether the speech model is loaded; it loads in the background after launch
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub enum ModelStatus {
+    #[default]
+    Initializing,
+    Ready,
+    /// Loading failed; the next recording retries
+    Failed(String),
+}
+
+/// The streaming preview: words old enough to trust, then the recent ones that may still change
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct LiveTranscript {
+    pub stable: String,
+    pub draft: String,
+}
+
+impl LiveTranscript {
+    /// Split `text` after the words of its stable prefix `stable`
+    pub fn split(text: &str, stable: &str) -> Self {
+        let draft = text.split_whitespace().skip(stable.split_whitespace().count()).collect::<Vec<_>>().join(" ");
+        Self { stable: stable.to_string(), draft }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.stable.is_empty() && self.draft.is_empty()
+    }
+
+    /// Both parts as one line of text, and the byte offset where the draft starts
+    pub fn text(&self) -> (String, usize) {
+        let mut text = self.stable.clone();
+        if !text.is_empty() && !self.draft.is_empty() {
+            text.push(' ');
+        }
+        let draft_at = text.len();
+        text.push_str(&self.draft);
+        (text, draft_at)
+    }
+}
+
+/// Where the current recording's text goes instead of the configured output
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub enum OutputOverride {
+    #[default]
+    None,
+    /// An API client (editor plugin) receives the text; nothing is typed
+    Client,
+    /// Copied to the clipboard instead of typed
+    Clipboard,
+    /// Saved as a meeting transcript named after this calendar event
+    Meeting(String),
+}
+
+/// Rejected state transition (event not valid in the current state)
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct InvalidTransition {
+    pub from: RecordingState,
+    pub event: RecordingEvent,
+}
+
+impl fmt::Display for InvalidTransition {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "invalid transition: {:?} on {:?}", self.event, self.from)
+    }
+}
+
 /// Observable state container
 pub struct AppStateManager {
     recording_state: Arc<RwLock<RecordingState>>,
     transcription: Arc<RwLock<String>>,
+    live_transcript: Arc<RwLock<LiveTranscript>>,
     is_window_visible: Arc<RwLock<bool>>,
     is_preferences_visible: Arc<RwLock<bool>>,
+    // When the current/last recording started
+    recording_started: Arc<RwLock<Option<std::time::Instant>>>,
+    // When the recording state last changed
+    state_since: Arc<RwLock<std::time::Instant>>,
+    // Counts recordings handed to processing, so a job can tell it was given up on
+    processing_job: Arc<RwLock<u64>>,
+    // Recently emitted text and the app it went to, for context continuation
+    recent_output: Arc<RwLock<(Option<String>, String)>>,
+    // Most recent final text, for read-back
+    last_output: Arc<RwLock<Option<String>>>,
+    /// The model's transcript behind `last_output`
+    last_raw_output: Arc<RwLock<Option<String>>>,
+    output_rev: Arc<RwLock<u64>>,
+    // Dictations collected when there is no typing target
+    scratchpad: Arc<RwLock<Vec<String>>>,
+    scratchpad_rev: Arc<RwLock<u64>>,
+    // Chunks transcribed / total while processing a long recording
+    progress: Arc<RwLock<Option<(usize, usize)>>>,
+    // Push-to-talk ignored (e.g. during a Focus mode the user chose to pause in)
+    paused: Arc<RwLock<bool>>,
+    // Where the current recording's text goes when not typed as usual
+    output_override: Arc<RwLock<OutputOverride>>,
+    // Profile picked for the current recording by its own push-to-talk binding
+    dictation_profile: Arc<RwLock<Option<String>>>,
+    call_suggestion: Arc<RwLock<Option<String>>>,
+    /// Signalled on every change, for `wait_for_model`
+    model_status: Arc<(Mutex<ModelStatus>, Condvar)>,
+    // Recording keeps going after push-to-talk is let go, until the next press
+    recording_locked: Arc<RwLock<bool>>,
+    /// Flagged moments of the current recording, in ms from its start
+    markers: Arc<RwLock<Vec<u64>>>,
+    subscribers: Arc<RwLock<Vec<crossbeam_channel::Sender<TranscriptUpdate>>>>,
     listeners: Arc<RwLock<Vec<Box<dyn Fn() + Send + Sync>>>>,
 }
 
@@ -24,8 +162,27 @@ impl AppStateManager {
         Self {
             recording_state: Arc::new(RwLock::new(RecordingState::Idle)),
             transcription: Arc::new(RwLock::new(String::new())),
+            live_transcript: Arc::new(RwLock::new(LiveTranscript::default())),
             is_window_visible: Arc::new(RwLock::new(false)),
             is_preferences_visible: Arc::new(RwLock::new(false)),
+            recording_started: Arc::new(RwLock::new(None)),
+            state_since: Arc::new(RwLock::new(std::time::Instant::now())),
+            processing_job: Arc::new(RwLock::new(0)),
+            recent_output: Arc::new(RwLock::new((None, String::new()))),
+            last_output: Arc::new(RwLock::new(None)),
+            last_raw_output: Arc::new(RwLock::new(None)),
+            output_rev: Arc::new(RwLock::new(0)),
+            scratchpad: Arc::new(RwLock::new(Vec::new())),
+            scratchpad_rev: Arc::new(RwLock::new(0)),
+            progress: Arc::new(RwLock::new(None)),
+            paused: Arc::new(RwLock::new(false)),
+            output_override: Arc::new(RwLock::new(OutputOverride::None)),
+            dictation_profile: Arc::new(RwLock::new(None)),
+            call_suggestion: Arc::new(RwLock::new(None)),
+            model_status: Arc::new((Mutex::new(ModelStatus::Initializing), Condvar::new())),
+            recording_locked: Arc::new(RwLock::new(false)),
+            markers: Arc::new(RwLock::new(Vec::new())),
+            subscribers: Arc::new(RwLock::new(Vec::new())),
             listeners: Arc::new(RwLock::new(Vec::new())),
         }
     }
@@ -34,12 +191,81 @@ impl AppStateManager {
         *self.recording_state.read()
     }
     
+    /// Apply `event` to the recording state machine.
+    /// The check and the update happen under one write lock, so concurrent
+    /// press/release handlers can't both observe the same source state.
+    pub fn transition(&self, event: RecordingEvent) -> Result<RecordingState, InvalidTransition> {
+        let (old_state, new_state) = {
+            let mut current = self.recording_state.write();
+            let old_state = *current;
+            match old_state.next(event) {
+                Some(new_state) => {
+                    *current = new_state;
+                    *self.state_since.write() = std::time::Instant::now();
+                    if new_state == RecordingState::Processing {
+                        *self.processing_job.write() += 1;
+                    }
+                    if new_state == RecordingState::Recording {
+                        *self.recording_started.write() = Some(std::time::Instant::now());
+                        self.markers.write().clear();
+                    } else {
+                        *self.recording_locked.write() = false;
+                    }
+                    (old_state, new_state)
+                }
+                None => {
+                    warn!("Rejected state transition: {:?} on {:?}", event, old_state);
+                    return Err(InvalidTransition { from: old_state, event });
+                }
+            }
+        };
+        info!("State transition: {:?} -> {:?} ({:?})", old_state, new_state, event);
+        self.notify_listeners();
+        self.publish(TranscriptUpdate::State(new_state));
+        Ok(new_state)
+    }
+
+    /// Time spent in the current recording state
+    pub fn state_elapsed(&self) -> std::time::Duration {
+        self.state_since.read().elapsed()
+    }
+
+    /// Number of the recording being (or last) processed; changes with every recording
+    pub fn processing_job(&self) -> u64 {
+        *self.processing_job.read()
+    }
+
+    /// Time since the current/last recording started
+    pub fn recording_elapsed(&self) -> Option<std::time::Duration> {
+        self.recording_started.read().map(|t| t.elapsed())
+    }
+
+    /// Flag the current moment of the recording; returns its offset in ms
+    pub fn add_marker(&self) -> Option<u64> {
+        if self.get_recording_state() != RecordingState::Recording {
+            return None;
+        }
+        let at = self.recording_elapsed()?.as_millis() as u64;
+        self.markers.write().push(at);
+        Some(at)
+    }
+
+    /// Markers of the recording just stopped
+    pub fn take_markers(&self) -> Vec<u64> {
+        std::mem::take(&mut *self.markers.write())
+    }
+
+    /// Force the recording state, bypassing transition validation.
+    /// Prefer `transition`; this is only for recovery paths.
     pub fn set_recording_state(&self, state: RecordingState) {
-        let old_state = *self.recording_state.read();
+        let old_state = {
+            let mut current = self.recording_state.write();
+            std::mem::replace(&mut *current, state)
+        };
         if old_state != state {
-            info!("State transition: {:?} -> {:?}", old_state, state);
-            *self.recording_state.write() = state;
+            info!("State forced: {:?} -> {:?}", old_state, state);
             self.notify_listeners();
+            self.publish(TranscriptUpdate::State(state));
         }
     }
     
@@ -59,8 +285,18 @@ impl AppStateManager {
     
     pub fn clear_transcription(&self) {
         self.transcription.write().clear();
+        *self.live_transcript.write() = LiveTranscript::default();
         self.notify_listeners();
     }
+
+    /// What the popup previews while streaming
+    pub fn live_transcript(&self) -> LiveTranscript {
+        self.live_transcript.read().clone()
+    }
+
+    pub fn set_live_transcript(&self, live: LiveTranscript) {
+        *self.live_transcript.write() = live;
+    }
     
     pub fn is_window_visible(&self) -> bool {
         *self.is_window_visible.read()
@@ -80,6 +316,180 @@ impl AppStateManager {
         self.notify_listeners();
     }
     
+    /// Remember emitted text and the transcript it was made from; switching apps starts a
+    /// fresh context
+    pub fn record_output(&self, bundle_id: Option<String>, text: &str, raw_text: &str) {
+        const MAX_CHARS: usize = 2000;
+        *self.last_output.write() = Some(text.to_string());
+        *self.last_raw_output.write() = Some(raw_text.to_string());
+        *self.output_rev.write() += 1;
+        let mut recent = self.recent_output.write();
+        if recent.0 != bundle_id {
+            *recent = (bundle_id, String::new());
+        }
+        if !recent.1.is_empty() {
+            recent.1.push(' ');
+        }
+        recent.1.push_str(text);
+        if recent.1.len() > MAX_CHARS {
+            let mut cut = recent.1.len() - MAX_CHARS;
+            while !recent.1.is_char_boundary(cut) {
+                cut += 1;
+            }
+            recent.1.drain(..cut);
+        }
+    }
+
+    /// The most recent final text
+    pub fn last_output(&self) -> Option<String> {
+        self.last_output.read().clone()
+    }
+
+    /// What the model heard for `last_output`, before commands and formatting
+    pub fn last_raw_output(&self) -> Option<String> {
+        self.last_raw_output.read().clone()
+    }
+
+    /// Bumped each time a final text is recorded, so callers can wait for the next one
+    pub fn output_rev(&self) -> u64 {
+        *self.output_rev.read()
+    }
+
+    /// Last `max_words` words emitted into `bundle_id`, if any
+    pub fn recent_context(&self, bundle_id: Option<&str>, max_words: usize) -> Option<String> {
+        let recent = self.recent_output.read();
+        if recent.0.as_deref() != bundle_id || recent.1.is_empty() || max_words == 0 {
+            return None;
+        }
+        let words: Vec<&str> = recent.1.split_whitespace().collect();
+        let start = words.len().saturating_sub(max_words);
+        Some(words[start..].join(" "))
+    }
+
+    pub fn scratchpad_entries(&self) -> Vec<String> {
+        self.scratchpad.read().clone()
+    }
+
+    pub fn push_scratchpad(&self, text: String) {
+        self.scratchpad.write().push(text);
+        *self.scratchpad_rev.write() += 1;
+        self.notify_listeners();
+    }
+
+    /// Remove and return all scratchpad entries
+    pub fn take_scratchpad(&self) -> Vec<String> {
+        let entries = std::mem::take(&mut *self.scratchpad.write());
+        *self.scratchpad_rev.write() += 1;
+        self.notify_listeners();
+        entries
+    }
+
+    /// How far transcription of the current recording has got, as (chunks done, total chunks);
+    /// None when nothing is being transcribed or the backend hasn't reported yet
+    pub fn progress(&self) -> Option<(usize, usize)> {
+        *self.progress.read()
+    }
+
+    pub fn set_progress(&self, done: usize, total: usize) {
+        *self.progress.write() = Some((done.min(total), total));
+        self.notify_listeners();
+    }
+
+    pub fn clear_progress(&self) {
+        if self.progress.write().take().is_some() {
+            self.notify_listeners();
+        }
+    }
+
+    pub fn is_paused(&self) -> bool {
+        *self.paused.read()
+    }
+
+    pub fn set_paused(&self, paused: bool) {
+        *self.paused.write() = paused;
+    }
+
+    /// Whether the recording in progress was locked on by a tap; cleared when it stops
+    pub fn is_recording_locked(&self) -> bool {
+        *self.recording_locked.read()
+    }
+
+    pub fn set_recording_locked(&self, locked: bool) {
+        *self.recording_locked.write() = locked;
+        self.notify_listeners();
+    }
+
+    /// Output for the recording in progress, set by whatever started it (API client, URL)
+    pub fn output_override(&self) -> OutputOverride {
+        self.output_override.read().clone()
+    }
+
+    pub fn set_output_override(&self, output: OutputOverride) {
+        *self.output_override.write() = output;
+    }
+
+    /// Name of the profile the current recording's push-to-talk binding belongs to, if any
+    pub fn dictation_profile(&self) -> Option<String> {
+        self.dictation_profile.read().clone()
+    }
+
+    pub fn set_dictation_profile(&self, profile: Option<String>) {
+        *self.dictation_profile.write() = profile;
+    }
+
+    /// Name of the video call in progress ("Zoom call"), if one was detected
+    pub fn call_suggestion(&self) -> Option<String> {
+        self.call_suggestion.read().clone()
+    }
+
+    pub fn set_call_suggestion(&self, call: Option<String>) {
+        *self.call_suggestion.write() = call;
+    }
+
+    pub fn model_status(&self) -> ModelStatus {
+        self.model_status.0.lock().clone()
+    }
+
+    pub fn set_model_status(&self, status: ModelStatus) {
+        *self.model_status.0.lock() = status;
+        self.model_status.1.notify_all();
+        self.notify_listeners();
+    }
+
+    /// Block until the model is no longer `Initializing`, or for at most `timeout`;
+    /// returns the status then
+    pub fn wait_for_model(&self, timeout: std::time::Duration) -> ModelStatus {
+        let deadline = std::time::Instant::now() + timeout;
+        let (status, changed) = &*self.model_status;
+        let mut status = status.lock();
+        while *status == ModelStatus::Initializing {
+            if changed.wait_until(&mut status, deadline).timed_out() {
+                break;
+            }
+        }
+        status.clone()
+    }
+
+    /// Receive transcript updates until the receiver is dropped
+    pub fn subscribe(&self) -> crossbeam_channel::Receiver<TranscriptUpdate> {
+        let (tx, rx) = crossbeam_channel::unbounded();
+        self.subscribers.write().push(tx);
+        rx
+    }
+
+    pub fn has_subscribers(&self) -> bool {
+        !self.subscribers.read().is_empty()
+    }
+
+    pub fn publish(&self, update: TranscriptUpdate) {
+        self.subscribers.write().retain(|tx| tx.send(update.clone()).is_ok());
+    }
+
+    /// Bumped on every scratchpad change so the UI can poll cheaply
+    pub fn scratchpad_rev(&self) -> u64 {
+        *self.scratchpad_rev.read()
+    }
+
     pub fn add_listener<F>(&self, listener: F) 
     where 
         F: Fn() + Send + Sync + 'static
@@ -95,12 +505,12 @@ impl AppStateManager {
     
     /// Check if we can start recording
     pub fn can_start_recording(&self) -> bool {
-        self.get_recording_state() == RecordingState::Idle
+        self.get_recording_state().next(RecordingEvent::Start).is_some()
     }
     
     /// Check if we can stop recording
     pub fn can_stop_recording(&self) -> bool {
-        self.get_recording_state() == RecordingState::Recording
+        self.get_recording_state().next(RecordingEvent::Stop).is_some()
     }
 }
 
@@ -109,9 +519,154 @@ impl Clone for AppStateManager {
         Self {
             recording_state: Arc::clone(&self.recording_state),
             transcription: Arc::clone(&self.transcription),
+            live_transcript: Arc::clone(&self.live_transcript),
             is_window_visible: Arc::clone(&self.is_window_visible),
             is_preferences_visible: Arc::clone(&self.is_preferences_visible),
+            recording_started: Arc::clone(&self.recording_started),
+            state_since: Arc::clone(&self.state_since),
+            processing_job: Arc::clone(&self.processing_job),
+            recent_output: Arc::clone(&self.recent_output),
+            last_output: Arc::clone(&self.last_output),
+            last_raw_output: Arc::clone(&self.last_raw_output),
+            output_rev: Arc::clone(&self.output_rev),
+            scratchpad: Arc::clone(&self.scratchpad),
+            scratchpad_rev: Arc::clone(&self.scratchpad_rev),
+            progress: Arc::clone(&self.progress),
+            paused: Arc::clone(&self.paused),
+            output_override: Arc::clone(&self.output_override),
+            dictation_profile: Arc::clone(&self.dictation_profile),
+            call_suggestion: Arc::clone(&self.call_suggestion),
+            model_status: Arc::clone(&self.model_status),
+            recording_locked: Arc::clone(&self.recording_locked),
+            markers: Arc::clone(&self.markers),
+            subscribers: Arc::clone(&self.subscribers),
             listeners: Arc::clone(&self.listeners),
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use RecordingEvent::*;
+    use RecordingState::*;
+
+    const STATES: [RecordingState; 3] = [Idle, Recording, Processing];
+    const EVENTS: [RecordingEvent; 4] = [Start, Stop, Finish, Cancel];
+
+    #[test]
+    fn transition_table() {
+        let allowed = [
+            (Idle, Start, Recording),
+            (Recording, Stop, Processing),
+            (Processing, Finish, Idle),
+            (Recording, Cancel, Idle),
+            (Processing, Cancel, Idle),
+        ];
+        for state in STATES {
+            for event in EVENTS {
+                let expected = allowed.iter().find(|(s, e, _)| *s == state && *e == event).map(|(_, _, to)| *to);
+                assert_eq!(state.next(event), expected, "{:?} on {:?}", event, state);
+            }
+        }
+    }
+
+    #[test]
+    fn invalid_transition_leaves_state_alone() {
+        let state = AppStateManager::new();
+        assert_eq!(state.transition(Stop), Err(InvalidTransition { from: Idle, event: Stop }));
+        assert_eq!(state.transition(Finish), Err(InvalidTransition { from: Idle, event: Finish }));
+        assert_eq!(state.get_recording_state(), Idle);
+
+        state.transition(Start).unwrap();
+        assert_eq!(state.transition(Start), Err(InvalidTransition { from: Recording, event: Start }));
+        assert_eq!(state.get_recording_state(), Recording);
+    }
+
+    #[test]
+    fn press_release_press_while_processing() {
+        // A second press within milliseconds of the release lands while still Processing
+        let state = AppStateManager::new();
+        state.transition(Start).unwrap();
+        state.transition(Stop).unwrap();
+        let job = state.processing_job();
+        assert!(state.transition(Start).is_err());
+        assert_eq!(state.get_recording_state(), Processing);
+        assert_eq!(state.processing_job(), job);
+
+        state.transition(Finish).unwrap();
+        assert_eq!(state.transition(Start), Ok(Recording));
+    }
+
+    #[test]
+    fn cancel_during_processing() {
+        let state = AppStateManager::new();
+        state.transition(Start).unwrap();
+        state.transition(Stop).unwrap();
+        let job = state.processing_job();
+        assert_eq!(state.transition(Cancel), Ok(Idle));
+        // The cancelled job's late Finish is rejected rather than ending the next recording
+        assert!(state.transition(Finish).is_err());
+        state.transition(Start).unwrap();
+        state.transition(Stop).unwrap();
+        assert_eq!(state.processing_job(), job + 1);
+    }
+
+    #[test]
+    fn concurrent_starts_only_one_wins() {
+        for _ in 0..100 {
+            let state = AppStateManager::new();
+            let barrier = Arc::new(std::sync::Barrier::new(8));
+            let handles: Vec<_> = (0..8)
+                .map(|_| {
+                    let state = state.clone();
+                    let barrier = Arc::clone(&barrier);
+                    std::thread::spawn(move || {
+                        barrier.wait();
+                        state.transition(Start).is_ok()
+                    })
+                })
+                .collect();
+            let winners = handles.into_iter().map(|h| h.join().unwrap()).filter(|won| *won).count();
+            assert_eq!(winners, 1);
+            assert_eq!(state.get_recording_state(), Recording);
+        }
+    }
+
+    #[test]
+    fn concurrent_stop_and_cancel_end_in_a_valid_state() {
+        for _ in 0..100 {
+            let state = AppStateManager::new();
+            state.transition(Start).unwrap();
+            let stopper = state.clone();
+            let canceller = state.clone();
+            // Whichever lands first, Cancel is valid in both Recording and Processing
+            let stop = std::thread::spawn(move || stopper.transition(Stop));
+            let cancel = std::thread::spawn(move || canceller.transition(Cancel));
+            let _ = stop.join().unwrap();
+            assert_eq!(cancel.join().unwrap(), Ok(Idle));
+            assert_eq!(state.get_recording_state(), Idle);
+        }
+    }
+
+    #[test]
+    fn wait_for_model_returns_once_loaded() {
+        let state = AppStateManager::new();
+        let loader = state.clone();
+        let load = std::thread::spawn(move || {
+            std::thread::sleep(std::time::Duration::from_millis(50));
+            loader.set_model_status(ModelStatus::Ready);
+        });
+        assert_eq!(state.wait_for_model(std::time::Duration::from_secs(10)), ModelStatus::Ready);
+        load.join().unwrap();
+    }
+
+    #[test]
+    fn wait_for_model_gives_up_at_the_timeout() {
+        let state = AppStateManager::new();
+        let timeout = std::time::Duration::from_millis(50);
+        let started = std::time::Instant::now();
+        assert_eq!(state.wait_for_model(timeout), ModelStatus::Initializing);
+        assert!(started.elapsed() >= timeout);
+    }
+}
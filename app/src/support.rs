@@ -0,0 +1,209 @@
+//! Support bundles: a zip of recent logs, the config with personal bits removed,
+//! model/backend details and permission states, for attaching to a GitHub issue.
+//! Nothing is sent anywhere; the user decides what to do with the file.
+
+use crate::config::Config;
+use crate::platform::macos::ffi as menubar_ffi;
+use once_cell::sync::Lazy;
+use parking_lot::Mutex;
+use std::collections::VecDeque;
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+use std::process::Command;
+use std::time::{SystemTime, UNIX_EPOCH};
+use tracing::info;
+
+/// Log lines kept in memory for the bundle
+const RECENT_LOG_LINES: usize = 2000;
+
+static RECENT_LOGS: Lazy<Mutex<VecDeque<String>>> =
+    Lazy::new(|| Mutex::new(VecDeque::with_capacity(RECENT_LOG_LINES)));
+
+/// Log writer for `tracing_subscriber::fmt`: output still goes to stdout, and the
+/// most recent lines are remembered for support bundles
+#[derive(Debug, Clone, Copy, Default)]
+pub struct LogCapture;
+
+impl<'a> tracing_subscriber::fmt::MakeWriter<'a> for LogCapture {
+    type Writer = LogCaptureWriter;
+
+    fn make_writer(&'a self) -> Self::Writer {
+        LogCaptureWriter
+    }
+}
+
+pub struct LogCaptureWriter;
+
+impl Write for LogCaptureWriter {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        let text = strip_ansi(&String::from_utf8_lossy(buf));
+        let mut logs = RECENT_LOGS.lock();
+        for line in text.lines().filter(|l| !l.trim().is_empty()) {
+            if logs.len() == RECENT_LOG_LINES {
+                logs.pop_front();
+            }
+            logs.push_back(line.to_string());
+        }
+        drop(logs);
+        io::stdout().write(buf)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        io::stdout().flush()
+    }
+}
+
+/// Drop terminal color codes (ESC [ ... letter)
+fn strip_ansi(text: &str) -> String {
+    let mut out = String::with_capacity(text.len());
+    let mut chars = text.chars();
+    while let Some(c) = chars.next() {
+        if c == '\u{1b}' {
+            for c in chars.by_ref() {
+                if c.is_ascii_alphabetic() {
+                    break;
+                }
+            }
+        } else {
+            out.push(c);
+        }
+    }
+    out
+}
+
+fn recent_logs() -> Vec<String> {
+    RECENT_LOGS.lock().iter().cloned().collect()
+}
+
+/// Replace the home directory with `~` so usernames don't end up in the bundle
+fn redact_home(text: &str) -> String {
+    match std::env::var("HOME") {
+        Ok(home) if !home.is_empty() => text.replace(&home, "~"),
+        _ => text.to_string(),
+    }
+}
+
+/// The config as TOML, minus things that could be personal: bias phrases are
+/// replaced by their count and home paths are shortened
+fn sanitized_config(config: &Config) -> String {
+    let mut config = config.clone();
+    let phrases = config.model.bias_phrases.len();
+    config.model.bias_phrases = if phrases > 0 { vec![format!("<{} redacted>", phrases)] } else { Vec::new() };
+    for profile in &mut config.profiles {
+        if let Some(ref mut bias) = profile.bias_phrases {
+            let count = bias.len();
+            *bias = vec![format!("<{} redacted>", count)];
+        }
+    }
+    match toml::to_string_pretty(&config) {
+        Ok(text) => redact_home(&text),
+        Err(e) => format!("# could not serialize config: {}\n", e),
+    }
+}
+
+fn command_output(program: &str, args: &[&str]) -> String {
+    Command::new(program)
+        .args(args)
+        .output()
+        .ok()
+        .map(|o| String::from_utf8_lossy(&o.stdout).trim().to_string())
+        .filter(|s| !s.is_empty())
+        .unwrap_or_else(|| "unknown".to_string())
+}
+
+fn system_report(config: &Config) -> String {
+    let model = &config.model;
+    let mut report = String::new();
+    report.push_str(&format!("Typeswift {}\n", env!("CARGO_PKG_VERSION")));
+    report.push_str(&format!(
+        "macOS {} ({})\n",
+        command_output("/usr/bin/sw_vers", &["-productVersion"]),
+        command_output("/usr/bin/sw_vers", &["-buildVersion"]),
+    ));
+    report.push_str(&format!("Architecture: {}\n", std::env::consts::ARCH));
+    report.push_str(&format!(
+        "Memory (RSS): {}\n",
+        crate::mem::current_rss_mb().map_or("unknown".to_string(), |mb| format!("{:.0} MB", mb))
+    ));
+    report.push_str(&format!("\nBackend: {:?}\n", model.backend));
+    report.push_str(&format!("Model: {}\n", redact_home(&model.model_name)));
+    report.push_str(&format!("Language: {}\n", model.language.as_deref().unwrap_or("auto")));
+    report.push_str(&format!("Streaming: {}\n", config.streaming.enabled));
+    report.push_str(&format!("VAD engine: {:?}\n", config.audio.vad.engine));
+    report.push_str(&format!(
+        "Bluetooth input: {}\n",
+        menubar_ffi::default_input_is_bluetooth().map_or("unknown".to_string(), |b| b.to_string())
+    ));
+    report
+}
+
+fn permissions_report() -> String {
+    let focus = match menubar_ffi::focus_active() {
+        Some(_) => "authorized",
+        None => "not authorized",
+    };
+    format!(
+        "Accessibility: {}\nMicrophone: {}\nFocus status: {}\nLaunch at login: {}\n",
+        if menubar_ffi::accessibility_trusted() { "granted" } else { "not granted" },
+        menubar_ffi::microphone_authorization(),
+        focus,
+        menubar_ffi::MenuBarController::is_launch_at_login_enabled(),
+    )
+}
+
+fn timestamp() -> u64 {
+    SystemTime::now().duration_since(UNIX_EPOCH).map(|d| d.as_secs()).unwrap_or(0)
+}
+
+/// Where bundles are saved: the Desktop when there is one, else the temp directory
+fn bundle_dir() -> PathBuf {
+    std::env::var("HOME")
+        .ok()
+        .map(|home| PathBuf::from(home).join("Desktop"))
+        .filter(|p| p.is_dir())
+        .unwrap_or_else(std::env::temp_dir)
+}
+
+fn write_file(dir: &Path, name: &str, contents: &str) -> io::Result<()> {
+    std::fs::write(dir.join(name), contents)
+}
+
+/// Collect everything into `typeswift-support-<time>.zip` and return its path.
+/// Dictated text is never included: history isn't read and logs only record lengths.
+pub fn generate_support_bundle(config: &Config) -> io::Result<PathBuf> {
+    let name = format!("typeswift-support-{}", timestamp());
+    let staging = std::env::temp_dir().join(&name);
+    std::fs::create_dir_all(&staging)?;
+
+    let logs = recent_logs();
+    let errors: Vec<&String> = logs.iter().filter(|l| l.contains(" WARN ") || l.contains(" ERROR ")).collect();
+    let result = (|| {
+        write_file(&staging, "system.txt", &system_report(config))?;
+        write_file(&staging, "permissions.txt", &permissions_report())?;
+        write_file(&staging, "config.toml", &sanitized_config(config))?;
+        write_file(&staging, "log.txt", &redact_home(&logs.join("\n")))?;
+        write_file(
+            &staging,
+            "recent-errors.txt",
+            &redact_home(&errors.iter().map(|l| l.as_str()).collect::<Vec<_>>().join("\n")),
+        )?;
+
+        // ditto ships with macOS and writes the same zips as Finder's Compress
+        let zip = bundle_dir().join(format!("{}.zip", name));
+        let status = Command::new("/usr/bin/ditto")
+            .args(["-c", "-k", "--keepParent"])
+            .arg(&staging)
+            .arg(&zip)
+            .status()?;
+        if !status.success() {
+            return Err(io::Error::other(format!("ditto exited with {}", status)));
+        }
+        Ok(zip)
+    })();
+    let _ = std::fs::remove_dir_all(&staging);
+
+    if let Ok(ref zip) = result {
+        info!("Support bundle written to {}", zip.display());
+    }
+    result
+}
@@ -0,0 +1,99 @@
+//! How the user left the app's windows, in ~/.typeswift/ui-state.json: where the popup was
+//! dragged, the History window's size and date filter, the last Preferences tab and hands-free.
+//! Kept apart from config.toml, which holds settings the user chose rather than arranged.
+
+use crate::config::{Config, PopupPosition};
+use crate::history::DateRange;
+use once_cell::sync::Lazy;
+use parking_lot::RwLock;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::path::PathBuf;
+use tracing::{info, warn};
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct UiState {
+    /// Where the popup was dragged to, per display (keyed by display id)
+    pub popup_positions: BTreeMap<String, PopupPosition>,
+    /// History window size in points when it was last closed
+    pub history_size: Option<(f32, f32)>,
+    pub history_range: DateRange,
+    pub preferences_tab: PreferencesTab,
+    /// Last hands-free toggle from the menu bar, palette or a link; restored at launch with
+    /// `[ui] remember_hands_free`
+    pub hands_free: Option<bool>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum PreferencesTab {
+    #[default]
+    General,
+    Advanced,
+}
+
+static UI_STATE: Lazy<RwLock<UiState>> = Lazy::new(|| RwLock::new(load()));
+
+fn path() -> Option<PathBuf> {
+    std::env::var("HOME")
+        .ok()
+        .map(|home| PathBuf::from(home).join(".typeswift").join("ui-state.json"))
+}
+
+fn load() -> UiState {
+    let Some(contents) = path().and_then(|p| std::fs::read_to_string(p).ok()) else {
+        return UiState::default();
+    };
+    serde_json::from_str(&contents).unwrap_or_else(|e| {
+        warn!("Ignoring unreadable ui-state.json: {}", e);
+        UiState::default()
+    })
+}
+
+fn save(state: &UiState) {
+    let Some(path) = path() else { return };
+    let result = serde_json::to_string_pretty(state).map_err(std::io::Error::other).and_then(|json| {
+        if let Some(dir) = path.parent() {
+            std::fs::create_dir_all(dir)?;
+        }
+        std::fs::write(&path, json)
+    });
+    if let Err(e) = result {
+        warn!("Failed to save ui-state.json: {}", e);
+    }
+}
+
+/// The saved state
+pub fn get() -> UiState {
+    UI_STATE.read().clone()
+}
+
+/// Change the state and write it out, if `change` changed anything
+pub fn update(change: impl FnOnce(&mut UiState)) {
+    let mut state = UI_STATE.write();
+    let before = state.clone();
+    change(&mut state);
+    if *state != before {
+        save(&state);
+    }
+}
+
+/// Apply the saved state to a freshly loaded config at launch. Popup positions saved in
+/// config.toml by earlier versions move here.
+pub fn restore(config: &mut Config) {
+    if !config.ui.popup_positions.is_empty() {
+        let positions = std::mem::take(&mut config.ui.popup_positions);
+        info!("Moving {} popup position(s) from config.toml to ui-state.json", positions.len());
+        update(|state| {
+            for (display, position) in positions {
+                state.popup_positions.entry(display).or_insert(position);
+            }
+        });
+    }
+    if config.ui.remember_hands_free {
+        if let Some(hands_free) = get().hands_free {
+            config.hotkeys.hands_free = hands_free;
+        }
+    }
+}
@@ -1,29 +1,122 @@
 #![allow(unexpected_cfgs)]
+use crate::config::{Config, PopupPosition};
 use crate::error::{VoicyError, VoicyResult};
 use parking_lot::RwLock;
 use std::sync::Arc;
 
 use cocoa::base::{id, nil};
-use cocoa::foundation::NSSize;
+use cocoa::foundation::{NSPoint, NSRect, NSSize};
 use cocoa::appkit::NSApp;
 use dispatch::Queue;
-use objc::{msg_send, sel, sel_impl};
+use objc::{class, msg_send, sel, sel_impl};
 use tracing::{info, warn, error};
 
+/// Fixed content size of the Preferences window (points)
+pub const PREFERENCES_WIDTH: f64 = 320.0;
+pub const PREFERENCES_HEIGHT: f64 = 440.0;
+
+/// Titles used to find our titled windows among NSApp.windows
+pub const PREFERENCES_TITLE: &str = "Typeswift Preferences";
+pub const HISTORY_TITLE: &str = "Typeswift History";
+pub const COMMAND_PALETTE_TITLE: &str = "Typeswift Commands";
+
+/// Initial size of the History window (points)
+pub const HISTORY_WIDTH: f64 = 520.0;
+pub const HISTORY_HEIGHT: f64 = 420.0;
+
+/// Size of the command palette window (points)
+pub const COMMAND_PALETTE_WIDTH: f64 = 380.0;
+pub const COMMAND_PALETTE_HEIGHT: f64 = 300.0;
+
+/// Size of the floating scratchpad window (points)
+pub const SCRATCHPAD_WIDTH: f64 = 340.0;
+pub const SCRATCHPAD_HEIGHT: f64 = 220.0;
+
+/// What VoiceOver says for each of our windows (the popup has no title)
+const POPUP_DESCRIPTION: &str = "Typeswift recording status";
+const WINDOW_DESCRIPTIONS: &[(&str, &str)] = &[
+    (PREFERENCES_TITLE, "Typeswift preferences: typing, spacing, dictation target, push-to-talk shortcut and launch at login"),
+    (HISTORY_TITLE, "Typeswift history: search, past dictations, and actions for the selected dictation"),
+    (COMMAND_PALETTE_TITLE, "Typeswift commands: type to search every action, Enter runs the selected one"),
+];
+
+/// A dragged popup snaps to a screen edge when dropped within this distance (points)
+pub const POPUP_SNAP_DISTANCE: f64 = 16.0;
+
+/// Horizontal padding around the popup's streaming text (points)
+pub const POPUP_TEXT_PADDING: f32 = 8.0;
+/// Height the popup grows by per extra line of wrapped text (points)
+pub const POPUP_LINE_HEIGHT: f32 = 16.0;
+/// Average advance of a popup text character, for estimating where lines wrap (points)
+const POPUP_CHAR_WIDTH: f32 = 6.0;
+
+/// Fit `text` into `max_lines` wrapped lines of a popup `width` points wide, keeping the end
+/// (the newest words). Returns the byte offset to show it from (0 when it all fits) and the
+/// number of lines that takes. An estimate: gpui does the actual wrapping.
+pub fn fit_popup_text(text: &str, width: f32, max_lines: usize) -> (usize, usize) {
+    let per_line = (((width - 2.0 * POPUP_TEXT_PADDING) / POPUP_CHAR_WIDTH) as usize).max(1);
+    let words: Vec<(usize, usize)> = text
+        .split_whitespace()
+        .map(|word| (word.as_ptr() as usize - text.as_ptr() as usize, word.chars().count()))
+        .collect();
+    let (mut lines, mut used) = (1, 0);
+    for (index, &(_, len)) in words.iter().enumerate().rev() {
+        if used == 0 || used + 1 + len <= per_line {
+            used += len + usize::from(used > 0);
+        } else if lines < max_lines.max(1) {
+            lines += 1;
+            used = len;
+        } else {
+            // Start after the words that didn't fit
+            return (words[index + 1].0, lines);
+        }
+    }
+    (0, lines)
+}
+
 #[derive(Debug, Clone, Copy, PartialEq)]
 pub enum WindowState {
     Hidden,
     Visible,
 }
 
+/// How the popup should look: the `[ui]` options combined with the matching
+/// macOS accessibility display settings, and whether dictations are rehearsed
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct PopupStyle {
+    pub high_contrast: bool,
+    pub reduce_motion: bool,
+    /// Lines of streaming text before the oldest words are cut
+    pub max_lines: usize,
+    /// Keep showing the last dictation after it's done, since rehearsals aren't typed
+    pub rehearsal: bool,
+}
+
+impl PopupStyle {
+    pub fn resolve(config: &Config) -> Self {
+        use crate::platform::macos::ffi;
+        Self {
+            high_contrast: config.ui.high_contrast || ffi::system_increase_contrast(),
+            reduce_motion: config.ui.reduce_motion || ffi::system_reduce_motion(),
+            max_lines: config.ui.popup_max_lines.max(1),
+            rehearsal: config.output.rehearsal,
+        }
+    }
+}
+
 pub struct WindowManager {
     state: Arc<RwLock<WindowState>>,
+    config: Arc<RwLock<Config>>,
+    // Popup origin as last placed by us; anything else means the user dragged it
+    placed_at: Arc<RwLock<Option<NSPoint>>>,
 }
 
 impl WindowManager {
-    pub fn new() -> Self {
+    pub fn new(config: Arc<RwLock<Config>>) -> Self {
         Self {
             state: Arc::new(RwLock::new(WindowState::Hidden)),
+            config,
+            placed_at: Arc::new(RwLock::new(None)),
         }
     }
 }
@@ -32,6 +125,8 @@ impl Clone for WindowManager {
     fn clone(&self) -> Self {
         Self {
             state: Arc::clone(&self.state),
+            config: Arc::clone(&self.config),
+            placed_at: Arc::clone(&self.placed_at),
         }
     }
 }
@@ -42,9 +137,14 @@ impl WindowManager {
     }
     
     pub fn show_without_focus(&self) -> VoicyResult<()> {
+        if crate::platform::macos::ffi::is_quiet() {
+            return Ok(());
+        }
         info!("Showing window without focus");
         let state = self.state.clone();
+        let manager = self.clone();
         Queue::main().exec_async(move || {
+            manager.place_popup();
             if let Err(e) = show_window_macos() {
                 error!("Failed to show window: {}", e);
                 return;
@@ -118,6 +218,180 @@ impl WindowManager {
         *self.state.read()
     }
 
+    /// Move the popup to its saved spot on its display, or bottom-center by default.
+    /// Main thread only.
+    pub fn place_popup(&self) {
+        unsafe {
+            let Some(window) = popup_window() else { return };
+            let screen = window_screen(window);
+            if screen == nil {
+                return;
+            }
+            let visible: NSRect = msg_send![screen, visibleFrame];
+            let frame: NSRect = msg_send![window, frame];
+            let saved = crate::ui_state::get().popup_positions.get(&screen_key(screen)).copied();
+            let gap = self.config.read().ui.gap_from_bottom as f64;
+            let origin = match saved {
+                Some(pos) => clamp_origin(
+                    NSPoint::new(visible.origin.x + pos.x, visible.origin.y + pos.y),
+                    frame.size,
+                    visible,
+                ),
+                None => {
+                    // Default placement measures from the full screen, as before
+                    let full: NSRect = msg_send![screen, frame];
+                    NSPoint::new(
+                        full.origin.x + (full.size.width - frame.size.width) / 2.0,
+                        full.origin.y + gap,
+                    )
+                }
+            };
+            let _: () = msg_send![window, setFrameOrigin: origin];
+            let placed: NSRect = msg_send![window, frame];
+            *self.placed_at.write() = Some(placed.origin);
+        }
+    }
+
+    /// Size the popup for `lines` lines of text: `[ui] window_height` for one, taller per extra
+    /// line up to `popup_max_height` when `popup_auto_grow` is on. The bottom edge stays put.
+    /// Main thread only.
+    pub fn fit_popup_height(&self, lines: usize) {
+        let height = {
+            let cfg = self.config.read();
+            let base = cfg.ui.window_height;
+            if cfg.ui.popup_auto_grow {
+                let grown = base + lines.saturating_sub(1) as f32 * POPUP_LINE_HEIGHT;
+                grown.clamp(base, cfg.ui.popup_max_height.max(base))
+            } else {
+                base
+            }
+        } as f64;
+        unsafe {
+            let Some(window) = popup_window() else { return };
+            let frame: NSRect = msg_send![window, frame];
+            if frame.size.height == height {
+                return;
+            }
+            // AppKit frames grow upwards from the origin, which is the bottom-left corner
+            let frame = NSRect::new(frame.origin, NSSize::new(frame.size.width, height));
+            let _: () = msg_send![window, setFrame: frame display: true];
+        }
+    }
+
+    /// Turn the popup's show/hide fade on or off. Main thread only.
+    pub fn set_popup_animations(&self, enabled: bool) {
+        // NSWindowAnimationBehaviorDefault / NSWindowAnimationBehaviorNone
+        let behavior: i64 = if enabled { 0 } else { 2 };
+        unsafe {
+            let Some(window) = popup_window() else { return };
+            let _: () = msg_send![window, setAnimationBehavior: behavior];
+        }
+    }
+
+    /// Notice a finished drag of the popup: snap it to nearby screen edges and remember
+    /// the spot for its display in ui-state.json. Main thread only; call periodically.
+    pub fn track_popup_drag(&self) {
+        unsafe {
+            let Some(window) = popup_window() else { return };
+            let visible_now: bool = msg_send![window, isVisible];
+            let frame: NSRect = msg_send![window, frame];
+            let Some(placed) = *self.placed_at.read() else { return };
+            if !visible_now || (frame.origin.x == placed.x && frame.origin.y == placed.y) {
+                return;
+            }
+            // Wait until the mouse is released
+            let buttons: u64 = msg_send![class!(NSEvent), pressedMouseButtons];
+            if buttons != 0 {
+                return;
+            }
+            let screen = window_screen(window);
+            if screen == nil {
+                return;
+            }
+            let visible: NSRect = msg_send![screen, visibleFrame];
+            let origin = snap_origin(frame.origin, frame.size, visible, POPUP_SNAP_DISTANCE);
+            let _: () = msg_send![window, setFrameOrigin: origin];
+            let placed: NSRect = msg_send![window, frame];
+            *self.placed_at.write() = Some(placed.origin);
+
+            let position = PopupPosition { x: origin.x - visible.origin.x, y: origin.y - visible.origin.y };
+            crate::ui_state::update(|state| {
+                state.popup_positions.insert(screen_key(screen), position);
+            });
+            info!("Popup moved to ({:.0}, {:.0}) on display {}", origin.x, origin.y, screen_key(screen));
+        }
+    }
+
+    /// Forget saved popup positions on all displays and return the popup to bottom-center
+    pub fn reset_popup_position(&self) {
+        crate::ui_state::update(|state| state.popup_positions.clear());
+        let manager = self.clone();
+        Queue::main().exec_async(move || manager.place_popup());
+    }
+
+    /// Give focus back to the previously active app
+    pub fn deactivate_app() -> VoicyResult<()> {
+        Queue::main().exec_async(move || {
+            if let Err(e) = deactivate_app_macos() {
+                warn!("Failed to deactivate app: {}", e);
+            }
+        });
+        Ok(())
+    }
+
+    /// Bring a titled window (e.g. `HISTORY_TITLE`) to front and activate the app
+    pub fn focus_window_titled(title: &'static str) -> VoicyResult<()> {
+        Queue::main().exec_async(move || unsafe {
+            let app: id = NSApp();
+            if app.is_null() { return; }
+            let windows: id = msg_send![app, windows];
+            let count: usize = msg_send![windows, count];
+            for i in 0..count {
+                let window: id = msg_send![windows, objectAtIndex:i];
+                if window_title(window) == title {
+                    let _: () = msg_send![window, makeKeyAndOrderFront:nil];
+                    let _: () = msg_send![app, activateIgnoringOtherApps:true];
+                    break;
+                }
+            }
+        });
+        Ok(())
+    }
+
+    /// Label our windows for VoiceOver. GPUI doesn't publish its elements to the
+    /// accessibility tree, so each window's content is described as one group.
+    pub fn describe_windows_for_accessibility() -> VoicyResult<()> {
+        Queue::main().exec_async(|| unsafe {
+            use cocoa::foundation::NSString;
+            let app: id = NSApp();
+            if app.is_null() { return; }
+            let windows: id = msg_send![app, windows];
+            let count: usize = msg_send![windows, count];
+            for i in 0..count {
+                let window: id = msg_send![windows, objectAtIndex:i];
+                let title = window_title(window);
+                let description = if i == 0 {
+                    Some(POPUP_DESCRIPTION)
+                } else {
+                    WINDOW_DESCRIPTIONS.iter().find(|(t, _)| *t == title).map(|(_, d)| *d)
+                };
+                let Some(description) = description else { continue };
+                let label = NSString::alloc(nil).init_str(description);
+                let role = NSString::alloc(nil).init_str("AXGroup");
+                let _: () = msg_send![window, setAccessibilityLabel: label];
+                let content: id = msg_send![window, contentView];
+                if content != nil {
+                    let _: () = msg_send![content, setAccessibilityElement: true];
+                    let _: () = msg_send![content, setAccessibilityRole: role];
+                    let _: () = msg_send![content, setAccessibilityLabel: label];
+                }
+                let _: () = msg_send![label, release];
+                let _: () = msg_send![role, release];
+            }
+        });
+        Ok(())
+    }
+
     pub fn focus_preferences() -> VoicyResult<()> {
         Queue::main().exec_async(move || {
             if let Err(e) = focus_preferences_window_macos() {
@@ -224,6 +498,83 @@ fn deactivate_app_macos() -> VoicyResult<()> {
     Ok(())
 }
 
+/// The status popup is the first window we create
+unsafe fn popup_window() -> Option<id> {
+    let app: id = NSApp();
+    if app.is_null() {
+        return None;
+    }
+    let windows: id = msg_send![app, windows];
+    if windows.is_null() {
+        return None;
+    }
+    let count: usize = msg_send![windows, count];
+    if count == 0 {
+        return None;
+    }
+    Some(msg_send![windows, objectAtIndex:0])
+}
+
+/// Screen the window is on, or the main screen before it has been shown
+unsafe fn window_screen(window: id) -> id {
+    let screen: id = msg_send![window, screen];
+    if screen != nil {
+        return screen;
+    }
+    msg_send![class!(NSScreen), mainScreen]
+}
+
+/// Stable per-display key (CGDirectDisplayID) for saved popup positions
+unsafe fn screen_key(screen: id) -> String {
+    use cocoa::foundation::NSString;
+    let description: id = msg_send![screen, deviceDescription];
+    let key = NSString::alloc(nil).init_str("NSScreenNumber");
+    let number: id = msg_send![description, objectForKey: key];
+    let _: () = msg_send![key, release];
+    if number == nil {
+        return "main".to_string();
+    }
+    let display_id: u32 = msg_send![number, unsignedIntValue];
+    display_id.to_string()
+}
+
+/// Keep the whole window inside `bounds`
+fn clamp_origin(origin: NSPoint, size: NSSize, bounds: NSRect) -> NSPoint {
+    let max_x = bounds.origin.x + (bounds.size.width - size.width).max(0.0);
+    let max_y = bounds.origin.y + (bounds.size.height - size.height).max(0.0);
+    NSPoint::new(origin.x.clamp(bounds.origin.x, max_x), origin.y.clamp(bounds.origin.y, max_y))
+}
+
+/// Pull the window flush against any edge of `bounds` it is within `distance` of
+fn snap_origin(origin: NSPoint, size: NSSize, bounds: NSRect, distance: f64) -> NSPoint {
+    let mut origin = clamp_origin(origin, size, bounds);
+    let right = bounds.origin.x + bounds.size.width - size.width;
+    let top = bounds.origin.y + bounds.size.height - size.height;
+    if origin.x - bounds.origin.x < distance {
+        origin.x = bounds.origin.x;
+    } else if right - origin.x < distance {
+        origin.x = right;
+    }
+    if origin.y - bounds.origin.y < distance {
+        origin.y = bounds.origin.y;
+    } else if top - origin.y < distance {
+        origin.y = top;
+    }
+    origin
+}
+
+unsafe fn window_title(window: id) -> String {
+    let title: id = msg_send![window, title];
+    if title == nil {
+        return String::new();
+    }
+    let ptr: *const std::os::raw::c_char = msg_send![title, UTF8String];
+    if ptr.is_null() {
+        return String::new();
+    }
+    std::ffi::CStr::from_ptr(ptr).to_string_lossy().into_owned()
+}
+
 fn focus_preferences_window_macos() -> VoicyResult<()> {
     unsafe {
         let app: id = NSApp();
@@ -246,11 +597,11 @@ fn focus_preferences_window_macos() -> VoicyResult<()> {
             let level: i64 = msg_send![window, level];
             const NS_FLOATING_WINDOW_LEVEL: i64 = 3;
             let is_floating = level == NS_FLOATING_WINDOW_LEVEL;
-            if has_title && !is_floating {
+            if has_title && !is_floating && window_title(window) == PREFERENCES_TITLE {
                 // Constrain preferences window size and disable green zoom
                 let new_style = style_mask & !8; // Remove NSWindowStyleMaskResizable
                 let _: () = msg_send![window, setStyleMask:new_style];
-                let min = NSSize { width: 320.0, height: 203.0 };
+                let min = NSSize { width: PREFERENCES_WIDTH, height: PREFERENCES_HEIGHT };
                 let _: () = msg_send![window, setContentMinSize: min];
                 let _: () = msg_send![window, setContentMaxSize: min];
                 let _: () = msg_send![window, setContentSize: min];
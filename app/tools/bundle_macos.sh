@@ -89,8 +89,25 @@ cat > "$INFO_PLIST" <<EOF
   <true/>
   <key>NSMicrophoneUsageDescription</key>
   <string>Typeswift needs microphone access to transcribe speech.</string>
+  <key>NSFocusStatusUsageDescription</key>
+  <string>Typeswift can stay quiet or pause while a Focus mode is on.</string>
+  <key>NSCalendarsUsageDescription</key>
+  <string>Typeswift can offer to transcribe meetings when they start.</string>
+  <key>NSCalendarsFullAccessUsageDescription</key>
+  <string>Typeswift can offer to transcribe meetings when they start.</string>
   <key>NSHighResolutionCapable</key>
   <true/>
+  <key>CFBundleURLTypes</key>
+  <array>
+    <dict>
+      <key>CFBundleURLName</key>
+      <string>${APP_ID}</string>
+      <key>CFBundleURLSchemes</key>
+      <array>
+        <string>typeswift</string>
+      </array>
+    </dict>
+  </array>
 </dict>
 </plist>
 EOF
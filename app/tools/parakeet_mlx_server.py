@@ -0,0 +1,95 @@
+#!/usr/bin/env python3
+"""Parakeet (MLX) transcription server for Typeswift's `mlx` backend.
+
+Typeswift runs this as a supervised worker process, so Python and MLX never load into the
+app. It listens on a Unix socket once the model is loaded and speaks the worker protocol in
+src/services/supervisor.rs: a JSON line, then for transcription the raw little-endian float32
+mono samples, answered with one JSON line. Needs `pip install parakeet-mlx`.
+"""
+
+import argparse
+import json
+import os
+import socket
+import sys
+import threading
+import time
+
+import mlx.core as mx
+import numpy as np
+from parakeet_mlx import from_pretrained
+from parakeet_mlx.audio import get_logmel
+
+
+def transcribe(model, samples):
+    mel = get_logmel(mx.array(samples), model.preprocessor_config)
+    result = model.generate(mel)[0]
+    # Tokens are word pieces; a leading space starts a new word
+    words = []
+    for sentence in result.sentences:
+        for token in sentence.tokens:
+            start_ms, end_ms = int(token.start * 1000), int(token.end * 1000)
+            if words and not token.text.startswith(" "):
+                words[-1]["text"] += token.text
+                words[-1]["end_ms"] = end_ms
+            else:
+                words.append({"text": token.text.strip(), "start_ms": start_ms, "end_ms": end_ms})
+    return {"text": result.text.strip(), "tokens": [w for w in words if w["text"]]}
+
+
+def serve(conn, model):
+    stream = conn.makefile("rb")
+    while True:
+        header = stream.readline()
+        if not header:
+            return
+        try:
+            request = json.loads(header)
+            if request.get("ping"):
+                conn.sendall(b'{"pong": true}\n')
+                continue
+            data = stream.read(request["samples"] * 4)
+            if request["sample_rate"] != model.preprocessor_config.sample_rate:
+                reply = {"error": f"expected {model.preprocessor_config.sample_rate} Hz audio"}
+            else:
+                reply = transcribe(model, np.frombuffer(data, dtype="<f4"))
+        except Exception as e:  # noqa: BLE001 - report anything back instead of dying
+            reply = {"error": str(e)}
+        conn.sendall((json.dumps(reply) + "\n").encode())
+
+
+def exit_with_parent():
+    # Typeswift may crash or be killed without stopping us; don't outlive it
+    parent = os.getppid()
+    while os.getppid() == parent:
+        time.sleep(2)
+    os._exit(0)
+
+
+def main():
+    parser = argparse.ArgumentParser(description=__doc__.splitlines()[0])
+    parser.add_argument("--socket", required=True)
+    parser.add_argument("--model", default="mlx-community/parakeet-tdt-0.6b-v3")
+    args = parser.parse_args()
+    threading.Thread(target=exit_with_parent, daemon=True).start()
+
+    print(f"Loading {args.model}", file=sys.stderr, flush=True)
+    model = from_pretrained(args.model)
+
+    if os.path.exists(args.socket):
+        os.unlink(args.socket)
+    server = socket.socket(socket.AF_UNIX, socket.SOCK_STREAM)
+    server.bind(args.socket)
+    server.listen(1)
+    print(f"Listening on {args.socket}", file=sys.stderr, flush=True)
+    try:
+        while True:
+            conn, _ = server.accept()
+            with conn:
+                serve(conn, model)
+    finally:
+        os.unlink(args.socket)
+
+
+if __name__ == "__main__":
+    main()